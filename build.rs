@@ -17,6 +17,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         "proto/bookmark/service/v1/permission.proto",
         "proto/bookmark/service/v1/backup.proto",
         "proto/bookmark/service/v1/user.proto",
+        "proto/bookmark/service/v1/admin.proto",
+        "proto/bookmark/service/v1/api_key.proto",
+        "proto/bookmark/service/v1/permission_template.proto",
+        "proto/bookmark/service/v1/auto_tag_rule.proto",
+        "proto/bookmark/service/v1/access_request.proto",
+        "proto/bookmark/service/v1/tag.proto",
+        "proto/bookmark/service/v1/saved_search.proto",
     ];
 
     let registration_proto = "proto/common/service/v1/module_registration.proto";
@@ -37,7 +44,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .build_client(true)
         .compile_protos(&[registration_proto], &include_dirs)?;
 
-    // Compile admin stub proto (client only — we call admin-service UserService/RoleService)
+    // Compile admin stub proto (client only — we call admin-service UserService/RoleService
+    // and InternalMessageService)
     tonic_build::configure()
         .build_server(false)
         .build_client(true)
@@ -0,0 +1,9 @@
+CREATE TABLE bookmark_url_scrub_policies (
+    tenant_id INTEGER PRIMARY KEY,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    strip_params TEXT[] NOT NULL DEFAULT '{}',
+    param_patterns TEXT[] NOT NULL DEFAULT '{}',
+    update_time TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE bookmark_bookmarks ADD COLUMN original_url TEXT;
@@ -0,0 +1,10 @@
+CREATE TABLE bookmark_group_members (
+    tenant_id INTEGER NOT NULL,
+    group_id VARCHAR(36) NOT NULL,
+    user_id VARCHAR(36) NOT NULL,
+    added_by INTEGER,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, group_id, user_id)
+);
+
+CREATE INDEX idx_group_members_user ON bookmark_group_members(tenant_id, user_id);
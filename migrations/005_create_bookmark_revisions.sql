@@ -0,0 +1,14 @@
+CREATE TABLE bookmark_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    bookmark_id UUID NOT NULL REFERENCES bookmark_bookmarks(id) ON DELETE CASCADE,
+    tenant_id INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    title VARCHAR(500) NOT NULL DEFAULT '',
+    description TEXT NOT NULL DEFAULT '',
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    original_url TEXT,
+    edited_by INTEGER,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_revisions_bookmark ON bookmark_revisions(bookmark_id, create_time DESC);
@@ -0,0 +1,2 @@
+-- Populated by the service at startup for existing rows (see url_normalizer).
+ALTER TABLE bookmark_bookmarks ADD COLUMN normalized_url TEXT;
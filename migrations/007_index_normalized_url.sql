@@ -0,0 +1,3 @@
+-- no-transaction
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_bookmarks_normalized_url
+    ON bookmark_bookmarks(tenant_id, normalized_url);
@@ -0,0 +1,12 @@
+CREATE TABLE bookmark_archives (
+    bookmark_id UUID PRIMARY KEY REFERENCES bookmark_bookmarks(id) ON DELETE CASCADE,
+    tenant_id INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    format VARCHAR(10) NOT NULL,
+    content TEXT NOT NULL,
+    content_bytes INTEGER NOT NULL,
+    truncated BOOLEAN NOT NULL DEFAULT FALSE,
+    archive_time TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_archives_tenant ON bookmark_archives(tenant_id);
@@ -0,0 +1,2 @@
+-- Maintained by the service from the snapshot's readable text.
+ALTER TABLE bookmark_archives ADD COLUMN content_tsv TSVECTOR;
@@ -0,0 +1,3 @@
+-- no-transaction
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_archives_content_tsv
+    ON bookmark_archives USING GIN(content_tsv);
@@ -0,0 +1,2 @@
+ALTER TABLE bookmark_bookmarks ADD COLUMN visit_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE bookmark_bookmarks ADD COLUMN last_visited_at TIMESTAMPTZ;
@@ -0,0 +1,11 @@
+CREATE TABLE bookmark_user_flags (
+    tenant_id INTEGER NOT NULL,
+    user_id VARCHAR(36) NOT NULL,
+    bookmark_id UUID NOT NULL REFERENCES bookmark_bookmarks(id) ON DELETE CASCADE,
+    is_pinned BOOLEAN NOT NULL DEFAULT FALSE,
+    pinned_at TIMESTAMPTZ,
+    update_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, bookmark_id)
+);
+
+CREATE INDEX idx_user_flags_pinned ON bookmark_user_flags(tenant_id, user_id) WHERE is_pinned;
@@ -0,0 +1,3 @@
+ALTER TABLE bookmark_user_flags
+    ADD COLUMN reading_status VARCHAR(32) NOT NULL DEFAULT 'READING_STATUS_UNREAD';
+ALTER TABLE bookmark_user_flags ADD COLUMN reading_status_time TIMESTAMPTZ;
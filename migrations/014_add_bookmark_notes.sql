@@ -0,0 +1 @@
+ALTER TABLE bookmark_bookmarks ADD COLUMN notes TEXT NOT NULL DEFAULT '';
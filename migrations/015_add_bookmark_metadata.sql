@@ -0,0 +1 @@
+ALTER TABLE bookmark_bookmarks ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,3 @@
+-- no-transaction
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_bookmarks_metadata
+    ON bookmark_bookmarks USING GIN(metadata jsonb_path_ops);
@@ -0,0 +1,10 @@
+CREATE TABLE bookmark_audit_events (
+    id BIGSERIAL PRIMARY KEY,
+    tenant_id INTEGER NOT NULL DEFAULT 0,
+    user_id VARCHAR(36) NOT NULL DEFAULT '',
+    operation VARCHAR(255) NOT NULL,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_events_tenant_time ON bookmark_audit_events(tenant_id, create_time);
+CREATE INDEX idx_audit_events_time ON bookmark_audit_events(create_time);
@@ -0,0 +1,15 @@
+CREATE TABLE bookmark_api_keys (
+    id UUID PRIMARY KEY,
+    tenant_id INTEGER NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    key_prefix VARCHAR(16) NOT NULL,
+    key_hash CHAR(64) NOT NULL UNIQUE,
+    roles TEXT[] NOT NULL DEFAULT '{}',
+    created_by VARCHAR(36) NOT NULL,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_api_keys_tenant ON bookmark_api_keys(tenant_id, create_time DESC);
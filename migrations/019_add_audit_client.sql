@@ -0,0 +1 @@
+ALTER TABLE bookmark_audit_events ADD COLUMN client VARCHAR(255) NOT NULL DEFAULT '';
@@ -0,0 +1,11 @@
+CREATE TABLE bookmark_short_links (
+    tenant_id INTEGER NOT NULL,
+    code VARCHAR(64) NOT NULL,
+    bookmark_id UUID NOT NULL REFERENCES bookmark_bookmarks(id) ON DELETE CASCADE,
+    created_by VARCHAR(36) NOT NULL,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    revoked_at TIMESTAMPTZ,
+    PRIMARY KEY (tenant_id, code)
+);
+
+CREATE INDEX idx_short_links_bookmark ON bookmark_short_links(bookmark_id);
@@ -0,0 +1,33 @@
+-- Append-only log of bookmark writes, read by the SyncChanges RPC. `txid` is
+-- the writing transaction, so readers can skip rows from transactions that
+-- have not committed yet and never move a cursor past them.
+CREATE TABLE bookmark_changes (
+    seq BIGSERIAL PRIMARY KEY,
+    txid BIGINT NOT NULL DEFAULT (pg_current_xact_id()::text::bigint),
+    tenant_id INTEGER NOT NULL,
+    bookmark_id UUID NOT NULL,
+    deleted BOOLEAN NOT NULL DEFAULT FALSE,
+    change_time TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_bookmark_changes_tenant ON bookmark_changes(tenant_id, txid, seq);
+
+CREATE FUNCTION bookmark_record_change() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        INSERT INTO bookmark_changes (tenant_id, bookmark_id, deleted)
+        VALUES (OLD.tenant_id, OLD.id, TRUE);
+        RETURN OLD;
+    END IF;
+    INSERT INTO bookmark_changes (tenant_id, bookmark_id) VALUES (NEW.tenant_id, NEW.id);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER bookmark_changes_log
+    AFTER INSERT OR UPDATE OR DELETE ON bookmark_bookmarks
+    FOR EACH ROW EXECUTE FUNCTION bookmark_record_change();
+
+-- Existing bookmarks start out as creates so a first sync sees everything.
+INSERT INTO bookmark_changes (tenant_id, bookmark_id, change_time)
+SELECT tenant_id, id, update_time FROM bookmark_bookmarks ORDER BY update_time, id;
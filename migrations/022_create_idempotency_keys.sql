@@ -0,0 +1,15 @@
+-- Responses to mutating RPCs retried with the same `x-idempotency-key`. A row
+-- without a response is a request still in flight.
+CREATE TABLE bookmark_idempotency_keys (
+    tenant_id INTEGER NOT NULL,
+    user_id VARCHAR(36) NOT NULL,
+    method VARCHAR(64) NOT NULL,
+    idempotency_key VARCHAR(255) NOT NULL,
+    request_hash BYTEA NOT NULL,
+    response BYTEA,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, user_id, method, idempotency_key)
+);
+
+CREATE INDEX idx_idempotency_keys_expires ON bookmark_idempotency_keys(expires_at);
@@ -0,0 +1,24 @@
+-- Notifications owed to recipients of ShareBookmark. Rows stay `pending` until
+-- the configured notifier accepts them; failed deliveries are retried.
+CREATE TABLE bookmark_share_notifications (
+    id SERIAL PRIMARY KEY,
+    tenant_id INTEGER NOT NULL,
+    permission_id INTEGER NOT NULL,
+    bookmark_id VARCHAR(36) NOT NULL,
+    recipient_id VARCHAR(36) NOT NULL,
+    shared_by VARCHAR(36) NOT NULL,
+    relation VARCHAR(32) NOT NULL,
+    message TEXT NOT NULL DEFAULT '',
+    expires_at TIMESTAMPTZ NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    -- Lease: a delivery attempt owns the row until then.
+    next_attempt_at TIMESTAMPTZ NOT NULL,
+    last_error TEXT,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_time TIMESTAMPTZ
+);
+
+CREATE INDEX idx_share_notifications_due
+    ON bookmark_share_notifications (next_attempt_at)
+    WHERE status = 'pending';
@@ -0,0 +1,13 @@
+-- Named bundles of grants that tenant managers apply to resources in one step.
+-- `grants` is a JSON array of {relation, subject_type, subject_id}.
+CREATE TABLE bookmark_permission_templates (
+    id SERIAL PRIMARY KEY,
+    tenant_id INTEGER NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    grants JSONB NOT NULL DEFAULT '[]',
+    created_by VARCHAR(36) NOT NULL,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    update_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, name)
+);
@@ -0,0 +1,44 @@
+-- Relations move from strings like 'RELATION_OWNER' to SMALLINT codes (the
+-- proto enum values, see `Relation::code`). While instances that only know the
+-- string column are still running, the trigger keeps both columns in sync, so
+-- either side may write just its own column. Existing tuples get their code
+-- from a batched backfill at startup, not from this migration, so the table
+-- isn't rewritten under lock. A later migration drops `relation` and its
+-- unique constraint once no such instance remains.
+ALTER TABLE bookmark_permissions ADD COLUMN relation_code SMALLINT;
+
+CREATE FUNCTION bookmark_relation_code(relation TEXT) RETURNS SMALLINT AS $$
+    SELECT CASE relation
+        WHEN 'RELATION_OWNER' THEN 1
+        WHEN 'RELATION_EDITOR' THEN 2
+        WHEN 'RELATION_VIEWER' THEN 3
+        WHEN 'RELATION_SHARER' THEN 4
+    END::SMALLINT;
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE FUNCTION bookmark_relation_name(code SMALLINT) RETURNS TEXT AS $$
+    SELECT CASE code
+        WHEN 1 THEN 'RELATION_OWNER'
+        WHEN 2 THEN 'RELATION_EDITOR'
+        WHEN 3 THEN 'RELATION_VIEWER'
+        WHEN 4 THEN 'RELATION_SHARER'
+    END;
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE FUNCTION bookmark_permission_sync_relation() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND NEW.relation_code IS DISTINCT FROM OLD.relation_code THEN
+        NEW.relation := bookmark_relation_name(NEW.relation_code);
+    ELSIF NEW.relation_code IS NULL
+        OR (TG_OP = 'UPDATE' AND NEW.relation IS DISTINCT FROM OLD.relation) THEN
+        NEW.relation_code := bookmark_relation_code(NEW.relation);
+    ELSIF NEW.relation IS NULL THEN
+        NEW.relation := bookmark_relation_name(NEW.relation_code);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER bookmark_permissions_sync_relation
+    BEFORE INSERT OR UPDATE ON bookmark_permissions
+    FOR EACH ROW EXECUTE FUNCTION bookmark_permission_sync_relation();
@@ -0,0 +1,4 @@
+-- no-transaction
+-- Takes over from the string-keyed unique constraint as the ON CONFLICT target.
+CREATE UNIQUE INDEX CONCURRENTLY IF NOT EXISTS idx_perms_tuple_code
+    ON bookmark_permissions (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id);
@@ -0,0 +1 @@
+ALTER TABLE bookmark_bookmarks ADD COLUMN kind VARCHAR(32);
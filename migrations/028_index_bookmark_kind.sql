@@ -0,0 +1,3 @@
+-- no-transaction
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_bookmarks_tenant_kind
+    ON bookmark_bookmarks(tenant_id, kind);
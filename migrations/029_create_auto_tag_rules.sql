@@ -0,0 +1,16 @@
+-- Per-tenant rules that add `tags` to bookmarks whose URL host, path and title
+-- match every non-empty pattern.
+CREATE TABLE bookmark_auto_tag_rules (
+    id SERIAL PRIMARY KEY,
+    tenant_id INTEGER NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    domain VARCHAR(255) NOT NULL DEFAULT '',
+    path_pattern TEXT NOT NULL DEFAULT '',
+    title_pattern TEXT NOT NULL DEFAULT '',
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by VARCHAR(36) NOT NULL,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    update_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, name)
+);
@@ -0,0 +1,3 @@
+-- The user's manual position for a pinned bookmark: a fractional-ranking key
+-- compared bytewise, NULL until the user first reorders their pins.
+ALTER TABLE bookmark_user_flags ADD COLUMN sort_order TEXT COLLATE "C";
@@ -0,0 +1,14 @@
+-- Per-tenant usage rolled up by day (UTC) from bookmarks, revisions and audit
+-- events, so usage reports don't scan the raw tables.
+CREATE TABLE bookmark_daily_stats (
+    tenant_id INTEGER NOT NULL,
+    day DATE NOT NULL,
+    creates BIGINT NOT NULL DEFAULT 0,
+    edits BIGINT NOT NULL DEFAULT 0,
+    searches BIGINT NOT NULL DEFAULT 0,
+    visits BIGINT NOT NULL DEFAULT 0,
+    -- [{"domain": ..., "count": ...}], most bookmarked first.
+    top_domains JSONB NOT NULL DEFAULT '[]',
+    update_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, day)
+);
@@ -0,0 +1,4 @@
+-- no-transaction
+-- The daily stats rollup counts edits by day across all tenants.
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_revisions_time
+    ON bookmark_revisions(create_time);
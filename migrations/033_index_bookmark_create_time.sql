@@ -0,0 +1,3 @@
+-- no-transaction
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_bookmarks_create_time
+    ON bookmark_bookmarks(create_time);
@@ -0,0 +1,24 @@
+-- Requests from users denied access to a bookmark. Rows stay `pending` until
+-- someone who can share the bookmark approves (granting the relation) or denies.
+CREATE TABLE bookmark_access_requests (
+    id SERIAL PRIMARY KEY,
+    tenant_id INTEGER NOT NULL,
+    bookmark_id VARCHAR(36) NOT NULL,
+    requester_id VARCHAR(36) NOT NULL,
+    relation VARCHAR(32) NOT NULL,
+    message TEXT NOT NULL DEFAULT '',
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    resolved_by VARCHAR(36),
+    resolution_note TEXT NOT NULL DEFAULT '',
+    -- Grant made on approval.
+    permission_id INTEGER,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolve_time TIMESTAMPTZ
+);
+
+-- One open request per user and bookmark.
+CREATE UNIQUE INDEX idx_access_requests_pending
+    ON bookmark_access_requests (tenant_id, bookmark_id, requester_id)
+    WHERE status = 'pending';
+CREATE INDEX idx_access_requests_tenant_time
+    ON bookmark_access_requests (tenant_id, create_time DESC, id DESC);
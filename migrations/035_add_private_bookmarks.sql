@@ -0,0 +1,19 @@
+-- Private bookmarks keep `url`, `original_url` and `notes` encrypted with a
+-- tenant data key. Their `normalized_url` stays NULL, so they never take part
+-- in duplicate detection or domain filters.
+ALTER TABLE bookmark_bookmarks ADD COLUMN is_private BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE bookmark_revisions ADD COLUMN is_private BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Data keys, wrapped by the configured key-encryption key. Encrypted values
+-- name the key that sealed them; new values use the tenant's newest key.
+CREATE TABLE bookmark_field_keys (
+    id UUID PRIMARY KEY,
+    tenant_id INTEGER NOT NULL,
+    wrapped_key TEXT NOT NULL,
+    -- Identifies the key-encryption key, so a changed master key is detected
+    -- rather than producing garbage.
+    kek_id VARCHAR(255) NOT NULL,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_field_keys_tenant ON bookmark_field_keys (tenant_id, create_time DESC);
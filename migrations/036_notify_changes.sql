@@ -0,0 +1,46 @@
+-- Announce bookmark and permission writes on the `bookmark_changes` channel so
+-- every instance can drop cached permission lookups and feed its watchers as
+-- soon as a write commits. `origin` is the writer's application_name, which
+-- lets an instance recognise its own writes. Payloads stay well under the
+-- 8000 byte NOTIFY limit.
+CREATE FUNCTION bookmark_notify_change() RETURNS TRIGGER AS $$
+DECLARE
+    r RECORD;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        r := OLD;
+    ELSE
+        r := NEW;
+    END IF;
+    IF TG_TABLE_NAME = 'bookmark_bookmarks' THEN
+        PERFORM pg_notify('bookmark_changes', json_build_object(
+            'entity', 'bookmark',
+            'op', TG_OP,
+            'origin', current_setting('application_name'),
+            'tenantId', r.tenant_id,
+            'id', r.id
+        )::text);
+    ELSE
+        PERFORM pg_notify('bookmark_changes', json_build_object(
+            'entity', 'permission',
+            'op', TG_OP,
+            'origin', current_setting('application_name'),
+            'tenantId', r.tenant_id,
+            'resourceType', r.resource_type,
+            'resourceId', r.resource_id,
+            'relation', r.relation,
+            'subjectType', r.subject_type,
+            'subjectId', r.subject_id
+        )::text);
+    END IF;
+    RETURN r;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER bookmark_bookmarks_notify
+    AFTER INSERT OR UPDATE OR DELETE ON bookmark_bookmarks
+    FOR EACH ROW EXECUTE FUNCTION bookmark_notify_change();
+
+CREATE TRIGGER bookmark_permissions_notify
+    AFTER INSERT OR UPDATE OR DELETE ON bookmark_permissions
+    FOR EACH ROW EXECUTE FUNCTION bookmark_notify_change();
@@ -0,0 +1,4 @@
+-- gRPC status code and handler latency of each audited call. Events recorded
+-- before these columns existed have neither.
+ALTER TABLE bookmark_audit_events ADD COLUMN status_code INTEGER;
+ALTER TABLE bookmark_audit_events ADD COLUMN duration_ms INTEGER;
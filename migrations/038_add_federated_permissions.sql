@@ -0,0 +1,4 @@
+-- Cross-tenant (federated) grants: the tuple lives in the tenant owning the
+-- resource while its subject is a user of `subject_tenant_id`. NULL on every
+-- ordinary tuple, whose subject belongs to the resource's tenant.
+ALTER TABLE bookmark_permissions ADD COLUMN subject_tenant_id INTEGER;
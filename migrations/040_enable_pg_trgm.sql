@@ -0,0 +1,2 @@
+-- Trigram similarity for fuzzy bookmark search.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
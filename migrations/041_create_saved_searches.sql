@@ -0,0 +1,26 @@
+-- Searches a user saved to run again. A subscribed search is evaluated every
+-- `alert_interval_secs`; each alert covers bookmarks created after
+-- `last_alert_time`.
+CREATE TABLE bookmark_saved_searches (
+    id SERIAL PRIMARY KEY,
+    tenant_id INTEGER NOT NULL,
+    user_id VARCHAR(36) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    query TEXT NOT NULL,
+    filter TEXT NOT NULL DEFAULT '',
+    -- Roles the owner had when they last saved or subscribed; alerts see
+    -- what those roles could see.
+    roles TEXT[] NOT NULL DEFAULT '{}',
+    -- NULL when not subscribed.
+    alert_interval_secs BIGINT,
+    -- Lease: an evaluation owns the row until then.
+    next_alert_at TIMESTAMPTZ,
+    last_alert_time TIMESTAMPTZ,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    update_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, user_id, name)
+);
+
+CREATE INDEX idx_saved_searches_due
+    ON bookmark_saved_searches (next_alert_at)
+    WHERE alert_interval_secs IS NOT NULL;
@@ -0,0 +1,4 @@
+-- no-transaction
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_perms_federated
+    ON bookmark_permissions (subject_tenant_id, subject_type, subject_id)
+    WHERE subject_tenant_id IS NOT NULL;
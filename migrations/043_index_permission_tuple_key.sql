@@ -0,0 +1,9 @@
+-- no-transaction
+-- Takes over from idx_perms_tuple_code as the ON CONFLICT target. A federated
+-- grant is a different tuple from a local one with the same subject ID, since
+-- the subject belongs to another tenant.
+CREATE UNIQUE INDEX CONCURRENTLY IF NOT EXISTS idx_perms_tuple_key
+    ON bookmark_permissions (
+        tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id,
+        COALESCE(subject_tenant_id, 0)
+    );
@@ -0,0 +1,5 @@
+-- The string-keyed unique constraint would still let a federated grant
+-- collide with the local tuple of a same-numbered user; idx_perms_tuple_key
+-- enforces uniqueness now.
+ALTER TABLE bookmark_permissions
+    DROP CONSTRAINT IF EXISTS bookmark_permissions_tenant_id_resource_type_resource_id_re_key;
@@ -0,0 +1,4 @@
+-- Alerts no longer run with the roles a user had when saving or subscribing,
+-- so forget the snapshots. The column stays until no running instance
+-- writes it.
+UPDATE bookmark_saved_searches SET roles = '{}' WHERE roles <> '{}';
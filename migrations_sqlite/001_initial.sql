@@ -0,0 +1,133 @@
+-- SQLite schema equivalent to migrations/001-019 for local development.
+-- UUIDs are hyphenated TEXT, arrays and JSONB are JSON TEXT, and timestamps
+-- are RFC 3339 TEXT in UTC so they sort chronologically.
+
+CREATE TABLE bookmark_bookmarks (
+    id TEXT PRIMARY KEY,
+    tenant_id INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT NOT NULL DEFAULT '',
+    description TEXT NOT NULL DEFAULT '',
+    tags TEXT NOT NULL DEFAULT '[]',
+    created_by INTEGER,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    update_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    original_url TEXT,
+    normalized_url TEXT,
+    visit_count INTEGER NOT NULL DEFAULT 0,
+    last_visited_at TEXT,
+    notes TEXT NOT NULL DEFAULT '',
+    metadata TEXT NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX idx_bookmarks_tenant ON bookmark_bookmarks(tenant_id, create_time);
+CREATE INDEX idx_bookmarks_normalized_url ON bookmark_bookmarks(tenant_id, normalized_url);
+
+CREATE TABLE bookmark_permissions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id INTEGER NOT NULL,
+    resource_type TEXT NOT NULL,
+    resource_id TEXT NOT NULL,
+    relation TEXT NOT NULL,
+    subject_type TEXT NOT NULL,
+    subject_id TEXT NOT NULL,
+    granted_by INTEGER,
+    expires_at TEXT,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    UNIQUE(tenant_id, resource_type, resource_id, relation, subject_type, subject_id)
+);
+
+CREATE INDEX idx_perms_resource ON bookmark_permissions(tenant_id, resource_type, resource_id);
+CREATE INDEX idx_perms_subject ON bookmark_permissions(subject_type, subject_id);
+CREATE INDEX idx_perms_expires ON bookmark_permissions(expires_at);
+
+CREATE TABLE bookmark_url_scrub_policies (
+    tenant_id INTEGER PRIMARY KEY,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    strip_params TEXT NOT NULL DEFAULT '[]',
+    param_patterns TEXT NOT NULL DEFAULT '[]',
+    update_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
+
+CREATE TABLE bookmark_group_members (
+    tenant_id INTEGER NOT NULL,
+    group_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    added_by INTEGER,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    PRIMARY KEY (tenant_id, group_id, user_id)
+);
+
+CREATE INDEX idx_group_members_user ON bookmark_group_members(tenant_id, user_id);
+
+CREATE TABLE bookmark_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    bookmark_id TEXT NOT NULL REFERENCES bookmark_bookmarks(id) ON DELETE CASCADE,
+    tenant_id INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT NOT NULL DEFAULT '',
+    description TEXT NOT NULL DEFAULT '',
+    tags TEXT NOT NULL DEFAULT '[]',
+    original_url TEXT,
+    edited_by INTEGER,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
+
+CREATE INDEX idx_revisions_bookmark ON bookmark_revisions(bookmark_id, create_time DESC);
+
+-- search_text stands in for the Postgres tsvector and is matched with LIKE.
+CREATE TABLE bookmark_archives (
+    bookmark_id TEXT PRIMARY KEY REFERENCES bookmark_bookmarks(id) ON DELETE CASCADE,
+    tenant_id INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    format TEXT NOT NULL,
+    content TEXT NOT NULL,
+    content_bytes INTEGER NOT NULL,
+    truncated INTEGER NOT NULL DEFAULT 0,
+    archive_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    search_text TEXT NOT NULL DEFAULT ''
+);
+
+CREATE INDEX idx_archives_tenant ON bookmark_archives(tenant_id);
+
+CREATE TABLE bookmark_user_flags (
+    tenant_id INTEGER NOT NULL,
+    user_id TEXT NOT NULL,
+    bookmark_id TEXT NOT NULL REFERENCES bookmark_bookmarks(id) ON DELETE CASCADE,
+    is_pinned INTEGER NOT NULL DEFAULT 0,
+    pinned_at TEXT,
+    update_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    reading_status TEXT NOT NULL DEFAULT 'READING_STATUS_UNREAD',
+    reading_status_time TEXT,
+    PRIMARY KEY (user_id, bookmark_id)
+);
+
+CREATE INDEX idx_user_flags_pinned ON bookmark_user_flags(tenant_id, user_id) WHERE is_pinned;
+
+CREATE TABLE bookmark_audit_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id INTEGER NOT NULL DEFAULT 0,
+    user_id TEXT NOT NULL DEFAULT '',
+    operation TEXT NOT NULL,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    client TEXT NOT NULL DEFAULT ''
+);
+
+CREATE INDEX idx_audit_events_tenant_time ON bookmark_audit_events(tenant_id, create_time);
+CREATE INDEX idx_audit_events_time ON bookmark_audit_events(create_time);
+
+CREATE TABLE bookmark_api_keys (
+    id TEXT PRIMARY KEY,
+    tenant_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    key_prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    roles TEXT NOT NULL DEFAULT '[]',
+    created_by TEXT NOT NULL,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    expires_at TEXT,
+    revoked_at TEXT,
+    last_used_at TEXT
+);
+
+CREATE INDEX idx_api_keys_tenant ON bookmark_api_keys(tenant_id, create_time DESC);
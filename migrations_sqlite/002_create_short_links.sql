@@ -0,0 +1,11 @@
+CREATE TABLE bookmark_short_links (
+    tenant_id INTEGER NOT NULL,
+    code TEXT NOT NULL,
+    bookmark_id TEXT NOT NULL REFERENCES bookmark_bookmarks(id) ON DELETE CASCADE,
+    created_by TEXT NOT NULL,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    revoked_at TEXT,
+    PRIMARY KEY (tenant_id, code)
+);
+
+CREATE INDEX idx_short_links_bookmark ON bookmark_short_links(bookmark_id);
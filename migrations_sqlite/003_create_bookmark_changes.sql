@@ -0,0 +1,29 @@
+-- Append-only log of bookmark writes, read by the SyncChanges RPC. SQLite has
+-- a single writer, so `seq` order is commit order and no `txid` is needed.
+CREATE TABLE bookmark_changes (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id INTEGER NOT NULL,
+    bookmark_id TEXT NOT NULL,
+    deleted INTEGER NOT NULL DEFAULT 0,
+    change_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
+
+CREATE INDEX idx_bookmark_changes_tenant ON bookmark_changes(tenant_id, seq);
+
+CREATE TRIGGER bookmark_changes_insert AFTER INSERT ON bookmark_bookmarks
+BEGIN
+    INSERT INTO bookmark_changes (tenant_id, bookmark_id) VALUES (NEW.tenant_id, NEW.id);
+END;
+
+CREATE TRIGGER bookmark_changes_update AFTER UPDATE ON bookmark_bookmarks
+BEGIN
+    INSERT INTO bookmark_changes (tenant_id, bookmark_id) VALUES (NEW.tenant_id, NEW.id);
+END;
+
+CREATE TRIGGER bookmark_changes_delete AFTER DELETE ON bookmark_bookmarks
+BEGIN
+    INSERT INTO bookmark_changes (tenant_id, bookmark_id, deleted) VALUES (OLD.tenant_id, OLD.id, 1);
+END;
+
+INSERT INTO bookmark_changes (tenant_id, bookmark_id, change_time)
+SELECT tenant_id, id, update_time FROM bookmark_bookmarks ORDER BY update_time, id;
@@ -0,0 +1,13 @@
+CREATE TABLE bookmark_idempotency_keys (
+    tenant_id INTEGER NOT NULL,
+    user_id TEXT NOT NULL,
+    method TEXT NOT NULL,
+    idempotency_key TEXT NOT NULL,
+    request_hash BLOB NOT NULL,
+    response BLOB,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    expires_at TEXT NOT NULL,
+    PRIMARY KEY (tenant_id, user_id, method, idempotency_key)
+);
+
+CREATE INDEX idx_idempotency_keys_expires ON bookmark_idempotency_keys(expires_at);
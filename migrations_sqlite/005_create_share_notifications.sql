@@ -0,0 +1,21 @@
+CREATE TABLE bookmark_share_notifications (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id INTEGER NOT NULL,
+    permission_id INTEGER NOT NULL,
+    bookmark_id TEXT NOT NULL,
+    recipient_id TEXT NOT NULL,
+    shared_by TEXT NOT NULL,
+    relation TEXT NOT NULL,
+    message TEXT NOT NULL DEFAULT '',
+    expires_at TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TEXT NOT NULL,
+    last_error TEXT,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    sent_time TEXT
+);
+
+CREATE INDEX idx_share_notifications_due
+    ON bookmark_share_notifications(next_attempt_at)
+    WHERE status = 'pending';
@@ -0,0 +1,3 @@
+ALTER TABLE bookmark_bookmarks ADD COLUMN kind TEXT;
+
+CREATE INDEX idx_bookmarks_tenant_kind ON bookmark_bookmarks(tenant_id, kind);
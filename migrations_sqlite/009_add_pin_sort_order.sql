@@ -0,0 +1 @@
+ALTER TABLE bookmark_user_flags ADD COLUMN sort_order TEXT;
@@ -0,0 +1,14 @@
+CREATE TABLE bookmark_daily_stats (
+    tenant_id INTEGER NOT NULL,
+    day TEXT NOT NULL,
+    creates INTEGER NOT NULL DEFAULT 0,
+    edits INTEGER NOT NULL DEFAULT 0,
+    searches INTEGER NOT NULL DEFAULT 0,
+    visits INTEGER NOT NULL DEFAULT 0,
+    top_domains TEXT NOT NULL DEFAULT '[]',
+    update_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    PRIMARY KEY (tenant_id, day)
+);
+
+CREATE INDEX idx_revisions_time ON bookmark_revisions(create_time);
+CREATE INDEX idx_bookmarks_create_time ON bookmark_bookmarks(create_time);
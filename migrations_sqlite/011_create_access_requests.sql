@@ -0,0 +1,20 @@
+CREATE TABLE bookmark_access_requests (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id INTEGER NOT NULL,
+    bookmark_id TEXT NOT NULL,
+    requester_id TEXT NOT NULL,
+    relation TEXT NOT NULL,
+    message TEXT NOT NULL DEFAULT '',
+    status TEXT NOT NULL DEFAULT 'pending',
+    resolved_by TEXT,
+    resolution_note TEXT NOT NULL DEFAULT '',
+    permission_id INTEGER,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    resolve_time TEXT
+);
+
+CREATE UNIQUE INDEX idx_access_requests_pending
+    ON bookmark_access_requests(tenant_id, bookmark_id, requester_id)
+    WHERE status = 'pending';
+CREATE INDEX idx_access_requests_tenant_time
+    ON bookmark_access_requests(tenant_id, create_time, id);
@@ -0,0 +1,12 @@
+ALTER TABLE bookmark_bookmarks ADD COLUMN is_private INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE bookmark_revisions ADD COLUMN is_private INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE bookmark_field_keys (
+    id TEXT PRIMARY KEY,
+    tenant_id INTEGER NOT NULL,
+    wrapped_key TEXT NOT NULL,
+    kek_id TEXT NOT NULL,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now'))
+);
+
+CREATE INDEX idx_field_keys_tenant ON bookmark_field_keys(tenant_id, create_time);
@@ -0,0 +1,2 @@
+ALTER TABLE bookmark_audit_events ADD COLUMN status_code INTEGER;
+ALTER TABLE bookmark_audit_events ADD COLUMN duration_ms INTEGER;
@@ -0,0 +1,4 @@
+ALTER TABLE bookmark_permissions ADD COLUMN subject_tenant_id INTEGER;
+
+CREATE INDEX idx_perms_federated ON bookmark_permissions (subject_tenant_id, subject_type, subject_id)
+    WHERE subject_tenant_id IS NOT NULL;
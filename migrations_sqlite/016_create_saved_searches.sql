@@ -0,0 +1,19 @@
+CREATE TABLE bookmark_saved_searches (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id INTEGER NOT NULL,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    query TEXT NOT NULL,
+    filter TEXT NOT NULL DEFAULT '',
+    roles TEXT NOT NULL DEFAULT '[]',
+    alert_interval_secs INTEGER,
+    next_alert_at TEXT,
+    last_alert_time TEXT,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    update_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    UNIQUE (tenant_id, user_id, name)
+);
+
+CREATE INDEX idx_saved_searches_due
+    ON bookmark_saved_searches(next_alert_at)
+    WHERE alert_interval_secs IS NOT NULL;
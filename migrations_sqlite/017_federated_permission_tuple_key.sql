@@ -0,0 +1,35 @@
+-- A federated grant is a different tuple from a local one with the same
+-- subject ID, so the subject's tenant joins the unique key. SQLite can't drop
+-- a table constraint, hence the rebuild.
+CREATE TABLE bookmark_permissions_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant_id INTEGER NOT NULL,
+    resource_type TEXT NOT NULL,
+    resource_id TEXT NOT NULL,
+    relation TEXT NOT NULL,
+    subject_type TEXT NOT NULL,
+    subject_id TEXT NOT NULL,
+    granted_by INTEGER,
+    expires_at TEXT,
+    create_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')),
+    subject_tenant_id INTEGER
+);
+
+INSERT INTO bookmark_permissions_new
+    (id, tenant_id, resource_type, resource_id, relation, subject_type, subject_id,
+     granted_by, expires_at, create_time, subject_tenant_id)
+SELECT id, tenant_id, resource_type, resource_id, relation, subject_type, subject_id,
+       granted_by, expires_at, create_time, subject_tenant_id
+FROM bookmark_permissions;
+
+DROP TABLE bookmark_permissions;
+ALTER TABLE bookmark_permissions_new RENAME TO bookmark_permissions;
+
+CREATE UNIQUE INDEX idx_perms_tuple_key ON bookmark_permissions
+    (tenant_id, resource_type, resource_id, relation, subject_type, subject_id,
+     COALESCE(subject_tenant_id, 0));
+CREATE INDEX idx_perms_resource ON bookmark_permissions(tenant_id, resource_type, resource_id);
+CREATE INDEX idx_perms_subject ON bookmark_permissions(subject_type, subject_id);
+CREATE INDEX idx_perms_expires ON bookmark_permissions(expires_at);
+CREATE INDEX idx_perms_federated ON bookmark_permissions (subject_tenant_id, subject_type, subject_id)
+    WHERE subject_tenant_id IS NOT NULL;
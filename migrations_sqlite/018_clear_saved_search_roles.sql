@@ -0,0 +1,2 @@
+-- Alerts no longer run with the roles a user had when saving or subscribing.
+UPDATE bookmark_saved_searches SET roles = '[]' WHERE roles <> '[]';
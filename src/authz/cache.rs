@@ -0,0 +1,494 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use moka::policy::EvictionPolicy;
+use moka::sync::Cache;
+use opentelemetry::metrics::Counter;
+use opentelemetry::KeyValue;
+
+use crate::authz::relations::{Relation, ResourceType, SubjectType};
+use crate::config::{parse_duration, AuthzCacheConfig};
+use crate::data::permission_repo::{
+    PermissionRow, PermissionStore, SubjectResourceRow, TupleGrant, TupleRevoke,
+};
+
+/// A `has_permission` lookup: one subject on one resource.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct TupleKey {
+    tenant_id: i32,
+    resource_type: ResourceType,
+    resource_id: String,
+    subject_type: SubjectType,
+    subject_id: String,
+}
+
+impl TupleKey {
+    fn is_resource(&self, tenant_id: i32, resource_type: ResourceType, resource_id: &str) -> bool {
+        self.tenant_id == tenant_id
+            && self.resource_type == resource_type
+            && self.resource_id == resource_id
+    }
+}
+
+/// TTL + LRU cache of permission tuple lookups, including misses, since most
+/// role, group and tenant checks find nothing. Cheap to clone.
+#[derive(Clone)]
+pub struct DecisionCache {
+    cache: Option<Cache<TupleKey, Option<PermissionRow>>>,
+    lookups: Counter<u64>,
+}
+
+impl DecisionCache {
+    pub fn new(cfg: &AuthzCacheConfig) -> Self {
+        let cache = cfg.enabled.then(|| {
+            let ttl = parse_duration(&cfg.ttl).unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "invalid authz_cache ttl, using 30s");
+                std::time::Duration::from_secs(30)
+            });
+            Cache::builder()
+                .max_capacity(cfg.max_entries)
+                .time_to_live(ttl)
+                .eviction_policy(EvictionPolicy::lru())
+                .support_invalidation_closures()
+                .build()
+        });
+        let lookups = opentelemetry::global::meter("bookmark")
+            .u64_counter("bookmark.authz.cache.lookups")
+            .with_description("Permission tuple lookups by cache result (hit or miss)")
+            .build();
+        Self { cache, lookups }
+    }
+
+    /// A cache that stores nothing, for one-off commands.
+    pub fn disabled() -> Self {
+        Self::new(&AuthzCacheConfig {
+            enabled: false,
+            ..Default::default()
+        })
+    }
+
+    /// Forget every lookup on a resource, after its tuples changed.
+    pub fn invalidate_resource(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+    ) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        let resource_id = resource_id.to_string();
+        if let Err(e) = cache.invalidate_entries_if(move |key, _| {
+            key.is_resource(tenant_id, resource_type, &resource_id)
+        }) {
+            tracing::warn!(error = %e, "failed to invalidate authz cache, clearing it");
+            cache.invalidate_all();
+        }
+    }
+
+    pub fn invalidate_all(&self) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate_all();
+        }
+    }
+
+    fn get(&self, key: &TupleKey) -> Option<Option<PermissionRow>> {
+        let cache = self.cache.as_ref()?;
+        let cached = cache.get(key);
+        let result = if cached.is_some() { "hit" } else { "miss" };
+        self.lookups.add(1, &[KeyValue::new("result", result)]);
+        cached
+    }
+
+    fn insert(&self, key: TupleKey, row: Option<PermissionRow>) {
+        if let Some(cache) = &self.cache {
+            cache.insert(key, row);
+        }
+    }
+
+    fn invalidate(&self, key: &TupleKey) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(key);
+        }
+    }
+}
+
+/// [`PermissionStore`] that answers `has_permission` from a [`DecisionCache`]
+/// and invalidates it on every write made through the store.
+pub struct CachedPermissionStore {
+    inner: Arc<dyn PermissionStore>,
+    cache: DecisionCache,
+}
+
+impl CachedPermissionStore {
+    pub fn new(inner: Arc<dyn PermissionStore>, cache: DecisionCache) -> Self {
+        Self { inner, cache }
+    }
+}
+
+#[tonic::async_trait]
+impl PermissionStore for CachedPermissionStore {
+    async fn has_permission(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        subject_type: SubjectType,
+        subject_id: &str,
+    ) -> anyhow::Result<Option<PermissionRow>> {
+        let key = TupleKey {
+            tenant_id,
+            resource_type,
+            resource_id: resource_id.to_string(),
+            subject_type,
+            subject_id: subject_id.to_string(),
+        };
+        if let Some(row) = self.cache.get(&key) {
+            return Ok(row);
+        }
+        let row = self
+            .inner
+            .has_permission(tenant_id, resource_type, resource_id, subject_type, subject_id)
+            .await?;
+        self.cache.insert(key, row.clone());
+        Ok(row)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn create_permission(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        relation: Relation,
+        subject_type: SubjectType,
+        subject_id: &str,
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<PermissionRow> {
+        let result = self
+            .inner
+            .create_permission(
+                tenant_id,
+                resource_type,
+                resource_id,
+                relation,
+                subject_type,
+                subject_id,
+                granted_by,
+                expires_at,
+            )
+            .await;
+        self.cache.invalidate(&TupleKey {
+            tenant_id,
+            resource_type,
+            resource_id: resource_id.to_string(),
+            subject_type,
+            subject_id: subject_id.to_string(),
+        });
+        result
+    }
+
+    async fn create_permissions(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        grants: &[(Relation, SubjectType, String)],
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<PermissionRow>> {
+        let result = self
+            .inner
+            .create_permissions(
+                tenant_id,
+                resource_type,
+                resource_id,
+                grants,
+                granted_by,
+                expires_at,
+            )
+            .await;
+        self.cache
+            .invalidate_resource(tenant_id, resource_type, resource_id);
+        result
+    }
+
+    async fn batch_grant(
+        &self,
+        tenant_id: i32,
+        tuples: &[TupleGrant],
+        granted_by: Option<i32>,
+    ) -> anyhow::Result<Vec<PermissionRow>> {
+        let result = self.inner.batch_grant(tenant_id, tuples, granted_by).await;
+        for t in tuples {
+            self.cache.invalidate(&TupleKey {
+                tenant_id,
+                resource_type: t.resource_type,
+                resource_id: t.resource_id.clone(),
+                subject_type: t.subject_type,
+                subject_id: t.subject_id.clone(),
+            });
+        }
+        result
+    }
+
+    async fn batch_revoke(
+        &self,
+        tenant_id: i32,
+        tuples: &[TupleRevoke],
+    ) -> anyhow::Result<Vec<u64>> {
+        let result = self.inner.batch_revoke(tenant_id, tuples).await;
+        for t in tuples {
+            self.cache.invalidate(&TupleKey {
+                tenant_id,
+                resource_type: t.resource_type,
+                resource_id: t.resource_id.clone(),
+                subject_type: t.subject_type,
+                subject_id: t.subject_id.clone(),
+            });
+        }
+        result
+    }
+
+    async fn delete_permission(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        relation: Option<Relation>,
+        subject_type: SubjectType,
+        subject_id: &str,
+    ) -> anyhow::Result<u64> {
+        let result = self
+            .inner
+            .delete_permission(
+                tenant_id,
+                resource_type,
+                resource_id,
+                relation,
+                subject_type,
+                subject_id,
+            )
+            .await;
+        self.cache.invalidate(&TupleKey {
+            tenant_id,
+            resource_type,
+            resource_id: resource_id.to_string(),
+            subject_type,
+            subject_id: subject_id.to_string(),
+        });
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn transfer_ownership(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        from_user: &str,
+        to_user: &str,
+        keep_as_editor: bool,
+        granted_by: Option<i32>,
+    ) -> anyhow::Result<Option<PermissionRow>> {
+        let result = self
+            .inner
+            .transfer_ownership(
+                tenant_id,
+                resource_type,
+                resource_id,
+                from_user,
+                to_user,
+                keep_as_editor,
+                granted_by,
+            )
+            .await;
+        self.cache
+            .invalidate_resource(tenant_id, resource_type, resource_id);
+        result
+    }
+
+    async fn purge_expired(&self) -> anyhow::Result<u64> {
+        let purged = self.inner.purge_expired().await?;
+        if purged > 0 {
+            self.cache.invalidate_all();
+        }
+        Ok(purged)
+    }
+
+    async fn backfill_relation_codes(&self) -> anyhow::Result<u64> {
+        let updated = self.inner.backfill_relation_codes().await?;
+        if updated > 0 {
+            self.cache.invalidate_all();
+        }
+        Ok(updated)
+    }
+
+    async fn delete_all_for_resource(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+    ) -> anyhow::Result<u64> {
+        let result = self
+            .inner
+            .delete_all_for_resource(tenant_id, resource_type, resource_id)
+            .await;
+        self.cache
+            .invalidate_resource(tenant_id, resource_type, resource_id);
+        result
+    }
+
+    async fn get_direct_permissions(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+    ) -> anyhow::Result<Vec<PermissionRow>> {
+        self.inner
+            .get_direct_permissions(tenant_id, resource_type, resource_id)
+            .await
+    }
+
+    async fn list_resources_by_subject(
+        &self,
+        tenant_id: i32,
+        subject_type: SubjectType,
+        subject_id: &str,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>> {
+        self.inner
+            .list_resources_by_subject(tenant_id, subject_type, subject_id, resource_type)
+            .await
+    }
+
+    async fn list_tenant_resources(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>> {
+        self.inner
+            .list_tenant_resources(tenant_id, resource_type)
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn grant_federated(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        subject_tenant_id: i32,
+        user_id: &str,
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<PermissionRow> {
+        let result = self
+            .inner
+            .grant_federated(
+                tenant_id,
+                resource_type,
+                resource_id,
+                subject_tenant_id,
+                user_id,
+                granted_by,
+                expires_at,
+            )
+            .await;
+        // The grant may have taken over a local tuple of the same user.
+        self.cache.invalidate(&TupleKey {
+            tenant_id,
+            resource_type,
+            resource_id: resource_id.to_string(),
+            subject_type: SubjectType::User,
+            subject_id: user_id.to_string(),
+        });
+        result
+    }
+
+    /// Not cached: revoking the tuple through its owning tenant could not
+    /// invalidate an entry keyed by the subject's tenant.
+    async fn has_federated_permission(
+        &self,
+        subject_tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        user_id: &str,
+    ) -> anyhow::Result<Option<PermissionRow>> {
+        self.inner
+            .has_federated_permission(subject_tenant_id, resource_type, resource_id, user_id)
+            .await
+    }
+
+    async fn list_federated_resources(
+        &self,
+        subject_tenant_id: i32,
+        user_id: &str,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>> {
+        self.inner
+            .list_federated_resources(subject_tenant_id, user_id, resource_type)
+            .await
+    }
+
+    async fn filter_owned(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        user_id: &str,
+        resource_ids: &[String],
+    ) -> anyhow::Result<Vec<String>> {
+        self.inner
+            .filter_owned(tenant_id, resource_type, user_id, resource_ids)
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn list_resources_for_subject(
+        &self,
+        tenant_id: i32,
+        subject_type: SubjectType,
+        subject_id: &str,
+        owned_by: Option<&str>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<SubjectResourceRow>, i64)> {
+        self.inner
+            .list_resources_for_subject(
+                tenant_id,
+                subject_type,
+                subject_id,
+                owned_by,
+                page,
+                page_size,
+            )
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn list_permissions_filtered(
+        &self,
+        tenant_id: i32,
+        resource_type: Option<ResourceType>,
+        resource_id: Option<&str>,
+        subject_type: Option<SubjectType>,
+        subject_id: Option<&str>,
+        after: Option<(DateTime<Utc>, i32)>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<PermissionRow>, i64)> {
+        self.inner
+            .list_permissions_filtered(
+                tenant_id,
+                resource_type,
+                resource_id,
+                subject_type,
+                subject_id,
+                after,
+                page,
+                page_size,
+            )
+            .await
+    }
+}
@@ -1,7 +1,7 @@
 use tonic::Status;
 
 use crate::authz::engine::{CheckContext, Engine};
-use crate::authz::relations::{Permission, Relation, ResourceType};
+use crate::authz::relations::{Permission, Relation, ResourceType, SubjectType};
 
 /// High-level convenience API for permission checks.
 #[derive(Clone)]
@@ -104,6 +104,16 @@ impl Checker {
             .await
     }
 
+    /// Subjects whose tuples grant the user access, for resolving access in SQL.
+    pub async fn subjects(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        role_ids: &[String],
+    ) -> anyhow::Result<Vec<(SubjectType, String)>> {
+        self.engine.subjects(tenant_id, user_id, role_ids).await
+    }
+
     pub async fn get_effective_permissions(
         &self,
         tenant_id: i32,
@@ -1,7 +1,13 @@
-use chrono::Utc;
+use std::sync::Arc;
 
-use crate::authz::relations::{Permission, Relation, ResourceType, SubjectType};
-use crate::data::permission_repo::PermissionRepo;
+use chrono::{DateTime, Utc};
+
+use crate::authz::cache::{CachedPermissionStore, DecisionCache};
+use crate::authz::relations::{
+    Permission, Relation, ResourceType, SubjectType, WILDCARD_RESOURCE,
+};
+use crate::data::group_repo::GroupStore;
+use crate::data::permission_repo::{PermissionRow, PermissionStore};
 
 /// Result of a permission check.
 #[derive(Debug)]
@@ -11,6 +17,30 @@ pub struct CheckResult {
     pub reason: String,
 }
 
+/// What a single tuple lookup contributed to a check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    NoTuple,
+    Granted,
+    /// A tuple exists but its relation does not grant the permission.
+    Insufficient,
+    /// An expired tuple, which denies the check outright.
+    Expired,
+    LookupFailed,
+}
+
+/// One tuple lookup made while evaluating a check, for explaining decisions.
+#[derive(Debug, Clone)]
+pub struct TraceStep {
+    pub subject_type: SubjectType,
+    pub subject_id: String,
+    /// The checked resource, or [`WILDCARD_RESOURCE`].
+    pub resource_id: String,
+    pub relation: Option<Relation>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub outcome: StepOutcome,
+}
+
 /// Context for a permission check.
 pub struct CheckContext {
     pub tenant_id: i32,
@@ -23,21 +53,82 @@ pub struct CheckContext {
 /// Zanzibar-like permission engine (simplified for flat bookmarks — no hierarchy).
 #[derive(Clone)]
 pub struct Engine {
-    store: PermissionRepo,
+    store: Arc<dyn PermissionStore>,
+    groups: Arc<dyn GroupStore>,
+    cache: DecisionCache,
+    /// Honor tuples granting users of this tenant access to other tenants' resources.
+    federation: bool,
 }
 
 impl Engine {
-    pub fn new(store: PermissionRepo) -> Self {
-        Self { store }
+    /// Permission lookups go through `cache`, which writes made via
+    /// [`Engine::store`] keep up to date.
+    pub fn new(
+        store: Arc<dyn PermissionStore>,
+        groups: Arc<dyn GroupStore>,
+        cache: DecisionCache,
+    ) -> Self {
+        Self {
+            store: Arc::new(CachedPermissionStore::new(store, cache.clone())),
+            groups,
+            cache,
+            federation: false,
+        }
+    }
+
+    /// Also consult federated tuples, granted by other tenants, in checks and
+    /// listings.
+    pub fn with_federation(mut self, enabled: bool) -> Self {
+        self.federation = enabled;
+        self
+    }
+
+    pub fn federation(&self) -> bool {
+        self.federation
+    }
+
+    /// Groups the user belongs to; lookup failures are treated as no membership.
+    async fn user_groups(&self, tenant_id: i32, user_id: &str) -> Vec<String> {
+        self.groups
+            .list_groups_for_user(tenant_id, user_id)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::debug!(error = %e, "error resolving group memberships");
+                Vec::new()
+            })
     }
 
     /// Check performs a permission check following the Zanzibar algorithm:
     /// 1. Check direct user permission on resource
     /// 2. Check user's role permissions on resource
-    /// 3. Check tenant-level permissions
+    /// 3. Check user's group permissions on resource
+    /// 4. Check tenant-level permissions
+    /// 5. With federation on, check a grant from the tenant owning the resource
     ///
+    /// At each step a tuple on the resource itself is consulted before a
+    /// [`WILDCARD_RESOURCE`] tuple covering the whole tenant.
     /// No hierarchy traversal needed (flat bookmarks).
     pub async fn check(&self, ctx: &CheckContext, role_ids: &[String]) -> CheckResult {
+        self.evaluate(ctx, role_ids, None).await
+    }
+
+    /// [`Engine::check`], also returning every tuple lookup made, in order.
+    pub async fn explain(
+        &self,
+        ctx: &CheckContext,
+        role_ids: &[String],
+    ) -> (CheckResult, Vec<TraceStep>) {
+        let mut trace = Vec::new();
+        let result = self.evaluate(ctx, role_ids, Some(&mut trace)).await;
+        (result, trace)
+    }
+
+    async fn evaluate(
+        &self,
+        ctx: &CheckContext,
+        role_ids: &[String],
+        mut trace: Option<&mut Vec<TraceStep>>,
+    ) -> CheckResult {
         tracing::debug!(
             user = %ctx.user_id,
             resource_type = ?ctx.resource_type,
@@ -48,7 +139,7 @@ impl Engine {
 
         // Step 1: Check direct user permission
         if let Some(result) = self
-            .check_direct(ctx, SubjectType::User, &ctx.user_id)
+            .check_direct(ctx, SubjectType::User, &ctx.user_id, trace.as_deref_mut())
             .await
         {
             return result;
@@ -56,16 +147,39 @@ impl Engine {
 
         // Step 2: Check user's role permissions
         for role_id in role_ids {
-            if let Some(result) = self.check_direct(ctx, SubjectType::Role, role_id).await {
+            if let Some(result) = self
+                .check_direct(ctx, SubjectType::Role, role_id, trace.as_deref_mut())
+                .await
+            {
                 return result;
             }
         }
 
-        // Step 3: Check tenant-level permissions
-        if let Some(result) = self.check_direct(ctx, SubjectType::Tenant, "all").await {
+        // Step 3: Check user's group permissions
+        for group_id in self.user_groups(ctx.tenant_id, &ctx.user_id).await {
+            if let Some(result) = self
+                .check_direct(ctx, SubjectType::Group, &group_id, trace.as_deref_mut())
+                .await
+            {
+                return result;
+            }
+        }
+
+        // Step 4: Check tenant-level permissions
+        if let Some(result) = self
+            .check_direct(ctx, SubjectType::Tenant, "all", trace.as_deref_mut())
+            .await
+        {
             return result;
         }
 
+        // Step 5: Check grants made to the user by other tenants
+        if self.federation && ctx.resource_id != WILDCARD_RESOURCE {
+            if let Some(result) = self.check_federated(ctx, trace).await {
+                return result;
+            }
+        }
+
         CheckResult {
             allowed: false,
             relation: None,
@@ -78,50 +192,131 @@ impl Engine {
         ctx: &CheckContext,
         subject_type: SubjectType,
         subject_id: &str,
+        mut trace: Option<&mut Vec<TraceStep>>,
     ) -> Option<CheckResult> {
-        let row = match self
-            .store
-            .has_permission(
-                ctx.tenant_id,
-                ctx.resource_type,
+        if let Some(result) = self
+            .check_tuple(
+                ctx,
                 &ctx.resource_id,
                 subject_type,
                 subject_id,
+                "direct permission",
+                trace.as_deref_mut(),
             )
             .await
         {
-            Ok(Some(row)) => row,
-            Ok(None) => return None,
-            Err(e) => {
-                tracing::debug!(error = %e, "error checking permission");
-                return None;
-            }
-        };
+            return Some(result);
+        }
+        if ctx.resource_id == WILDCARD_RESOURCE {
+            return None;
+        }
+        self.check_tuple(
+            ctx,
+            WILDCARD_RESOURCE,
+            subject_type,
+            subject_id,
+            "wildcard permission",
+            trace,
+        )
+        .await
+    }
 
-        // Check expiration
-        if let Some(expires) = &row.expires_at {
-            if *expires < Utc::now() {
-                return Some(CheckResult {
-                    allowed: false,
-                    relation: None,
-                    reason: "permission expired".to_string(),
-                });
-            }
+    async fn check_tuple(
+        &self,
+        ctx: &CheckContext,
+        resource_id: &str,
+        subject_type: SubjectType,
+        subject_id: &str,
+        reason: &str,
+        trace: Option<&mut Vec<TraceStep>>,
+    ) -> Option<CheckResult> {
+        let mut step = TraceStep {
+            subject_type,
+            subject_id: subject_id.to_string(),
+            resource_id: resource_id.to_string(),
+            relation: None,
+            expires_at: None,
+            outcome: StepOutcome::NoTuple,
+        };
+        let result = self.decide_tuple(ctx, reason, &mut step).await;
+        if let Some(trace) = trace {
+            trace.push(step);
         }
+        result
+    }
 
-        // Check if relation grants the required permission
-        let relation = Relation::from_str(&row.relation)?;
-        if relation.grants(ctx.permission) {
-            Some(CheckResult {
-                allowed: true,
-                relation: Some(relation),
-                reason: "direct permission".to_string(),
-            })
-        } else {
-            None
+    async fn check_federated(
+        &self,
+        ctx: &CheckContext,
+        trace: Option<&mut Vec<TraceStep>>,
+    ) -> Option<CheckResult> {
+        let mut step = TraceStep {
+            subject_type: SubjectType::User,
+            subject_id: ctx.user_id.clone(),
+            resource_id: ctx.resource_id.clone(),
+            relation: None,
+            expires_at: None,
+            outcome: StepOutcome::NoTuple,
+        };
+        let lookup = self
+            .store
+            .has_federated_permission(
+                ctx.tenant_id,
+                ctx.resource_type,
+                &ctx.resource_id,
+                &ctx.user_id,
+            )
+            .await;
+        let result = decide(ctx, "federated permission", &mut step, lookup);
+        if let Some(trace) = trace {
+            trace.push(step);
         }
+        result
+    }
+
+    /// Look up one tuple and decide the check from it, recording what was found.
+    async fn decide_tuple(
+        &self,
+        ctx: &CheckContext,
+        reason: &str,
+        step: &mut TraceStep,
+    ) -> Option<CheckResult> {
+        let lookup = self
+            .store
+            .has_permission(
+                ctx.tenant_id,
+                ctx.resource_type,
+                &step.resource_id,
+                step.subject_type,
+                &step.subject_id,
+            )
+            .await;
+        decide(ctx, reason, step, lookup)
+    }
+
+    /// Every subject the user acts as: themselves, their roles and groups, and
+    /// the tenant as a whole.
+    pub async fn subjects(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        role_ids: &[String],
+    ) -> anyhow::Result<Vec<(SubjectType, String)>> {
+        let mut subjects = vec![(SubjectType::User, user_id.to_string())];
+        subjects.extend(role_ids.iter().map(|r| (SubjectType::Role, r.clone())));
+        subjects.extend(
+            self.groups
+                .list_groups_for_user(tenant_id, user_id)
+                .await?
+                .into_iter()
+                .map(|g| (SubjectType::Group, g)),
+        );
+        subjects.push((SubjectType::Tenant, "all".to_string()));
+        Ok(subjects)
     }
 
+    /// Resources the user can reach through any tuple. A [`WILDCARD_RESOURCE`]
+    /// tuple expands to every resource of the type in the tenant.
     pub async fn list_accessible_resources(
         &self,
         tenant_id: i32,
@@ -130,31 +325,48 @@ impl Engine {
         role_ids: &[String],
     ) -> anyhow::Result<Vec<String>> {
         let mut accessible = std::collections::HashSet::new();
-
-        // User's direct permissions
-        let user_resources = self
-            .store
-            .list_resources_by_subject(tenant_id, SubjectType::User, user_id, resource_type)
-            .await?;
-        accessible.extend(user_resources);
-
-        // Role permissions
-        for role_id in role_ids {
-            let role_resources = self
+        for (subject_type, subject_id) in self.subjects(tenant_id, user_id, role_ids).await? {
+            let resources = self
                 .store
-                .list_resources_by_subject(tenant_id, SubjectType::Role, role_id, resource_type)
+                .list_resources_by_subject(tenant_id, subject_type, &subject_id, resource_type)
                 .await?;
-            accessible.extend(role_resources);
+            accessible.extend(resources);
         }
 
-        // Tenant-level permissions
-        let tenant_resources = self
-            .store
-            .list_resources_by_subject(tenant_id, SubjectType::Tenant, "all", resource_type)
-            .await?;
-        accessible.extend(tenant_resources);
+        let mut resources = if accessible.contains(WILDCARD_RESOURCE) {
+            self.store.list_tenant_resources(tenant_id, resource_type).await?
+        } else {
+            accessible.into_iter().collect()
+        };
+        if self.federation {
+            resources.extend(
+                self.store
+                    .list_federated_resources(tenant_id, user_id, resource_type)
+                    .await?,
+            );
+        }
+        Ok(resources)
+    }
 
-        Ok(accessible.into_iter().collect())
+    /// The tenant owning a resource that another tenant shared with the user
+    /// through an unexpired federated tuple. `None` while federation is off.
+    pub async fn federated_tenant(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        resource_type: ResourceType,
+        resource_id: &str,
+    ) -> anyhow::Result<Option<i32>> {
+        if !self.federation {
+            return Ok(None);
+        }
+        let now = Utc::now();
+        Ok(self
+            .store
+            .has_federated_permission(tenant_id, resource_type, resource_id, user_id)
+            .await?
+            .filter(|row| row.expires_at.is_none_or(|e| e > now))
+            .map(|row| row.tenant_id))
     }
 
     pub async fn get_effective_permissions(
@@ -188,7 +400,311 @@ impl Engine {
         (permissions, highest_relation)
     }
 
-    pub fn store(&self) -> &PermissionRepo {
+    pub fn store(&self) -> &Arc<dyn PermissionStore> {
         &self.store
     }
+
+    pub fn groups(&self) -> &Arc<dyn GroupStore> {
+        &self.groups
+    }
+
+    /// For writers that change permission tuples without going through the store.
+    pub fn cache(&self) -> &DecisionCache {
+        &self.cache
+    }
+}
+
+/// Decide a check from one tuple lookup, recording what was found.
+fn decide(
+    ctx: &CheckContext,
+    reason: &str,
+    step: &mut TraceStep,
+    lookup: anyhow::Result<Option<PermissionRow>>,
+) -> Option<CheckResult> {
+    let row = match lookup {
+        Ok(Some(row)) => row,
+        Ok(None) => return None,
+        Err(e) => {
+            tracing::debug!(error = %e, "error checking permission");
+            step.outcome = StepOutcome::LookupFailed;
+            return None;
+        }
+    };
+    step.relation = Relation::from_str(&row.relation);
+    step.expires_at = row.expires_at;
+
+    // Check expiration
+    if let Some(expires) = &row.expires_at {
+        if *expires < Utc::now() {
+            step.outcome = StepOutcome::Expired;
+            return Some(CheckResult {
+                allowed: false,
+                relation: None,
+                reason: "permission expired".to_string(),
+            });
+        }
+    }
+
+    // Check if relation grants the required permission
+    match step.relation {
+        Some(relation) if relation.grants(ctx.permission) => {
+            step.outcome = StepOutcome::Granted;
+            Some(CheckResult {
+                allowed: true,
+                relation: Some(relation),
+                reason: reason.to_string(),
+            })
+        }
+        _ => {
+            step.outcome = StepOutcome::Insufficient;
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::config::AuthzCacheConfig;
+    use crate::data::bookmark_repo::{BookmarkKind, BookmarkStore};
+    use crate::data::memory::MemoryDb;
+
+    const TENANT: i32 = 1;
+
+    fn engine(db: &MemoryDb) -> Engine {
+        Engine::new(
+            Arc::new(db.permissions()),
+            Arc::new(db.groups()),
+            DecisionCache::new(&AuthzCacheConfig::default()),
+        )
+    }
+
+    fn ctx(user_id: &str, resource_id: &str, permission: Permission) -> CheckContext {
+        CheckContext {
+            tenant_id: TENANT,
+            user_id: user_id.to_string(),
+            resource_type: ResourceType::Bookmark,
+            resource_id: resource_id.to_string(),
+            permission,
+        }
+    }
+
+    async fn grant_until(
+        engine: &Engine,
+        resource_id: &str,
+        relation: Relation,
+        subject_type: SubjectType,
+        subject_id: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) {
+        engine
+            .store()
+            .create_permission(
+                TENANT,
+                ResourceType::Bookmark,
+                resource_id,
+                relation,
+                subject_type,
+                subject_id,
+                None,
+                expires_at,
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn grant(e: &Engine, resource_id: &str, relation: Relation, st: SubjectType, sid: &str) {
+        grant_until(e, resource_id, relation, st, sid, None).await;
+    }
+
+    async fn allowed(engine: &Engine, user_id: &str, permission: Permission) -> bool {
+        engine
+            .check(&ctx(user_id, "b1", permission), &[])
+            .await
+            .allowed
+    }
+
+    #[tokio::test]
+    async fn relation_bounds_permissions() {
+        let db = MemoryDb::new();
+        let engine = engine(&db);
+        grant(&engine, "b1", Relation::Owner, SubjectType::User, "alice").await;
+        grant(&engine, "b1", Relation::Viewer, SubjectType::User, "bob").await;
+
+        assert!(allowed(&engine, "alice", Permission::Delete).await);
+        assert!(allowed(&engine, "bob", Permission::Read).await);
+        assert!(!allowed(&engine, "bob", Permission::Write).await);
+        assert!(!allowed(&engine, "carol", Permission::Read).await);
+    }
+
+    #[tokio::test]
+    async fn group_tuple_applies_to_members() {
+        let db = MemoryDb::new();
+        let engine = engine(&db);
+        engine
+            .groups()
+            .add_member(TENANT, "eng", "carol", None)
+            .await
+            .unwrap();
+        grant(&engine, "b1", Relation::Editor, SubjectType::Group, "eng").await;
+
+        let (result, trace) = engine
+            .explain(&ctx("carol", "b1", Permission::Write), &[])
+            .await;
+        assert!(result.allowed);
+        assert_eq!(result.relation, Some(Relation::Editor));
+        let last = trace.last().unwrap();
+        assert_eq!(last.subject_type, SubjectType::Group);
+        assert_eq!(last.outcome, StepOutcome::Granted);
+
+        assert!(!allowed(&engine, "dave", Permission::Read).await);
+    }
+
+    #[tokio::test]
+    async fn expired_tuple_denies_before_broader_grants() {
+        let db = MemoryDb::new();
+        let engine = engine(&db);
+        let expired = Some(Utc::now() - chrono::Duration::hours(1));
+        grant_until(
+            &engine,
+            "b1",
+            Relation::Viewer,
+            SubjectType::User,
+            "bob",
+            expired,
+        )
+        .await;
+        grant(&engine, "b1", Relation::Viewer, SubjectType::Tenant, "all").await;
+
+        let result = engine.check(&ctx("bob", "b1", Permission::Read), &[]).await;
+        assert!(!result.allowed);
+        assert_eq!(result.reason, "permission expired");
+        assert!(allowed(&engine, "carol", Permission::Read).await);
+    }
+
+    #[tokio::test]
+    async fn wildcard_tuple_covers_the_tenant() {
+        let db = MemoryDb::new();
+        let engine = engine(&db);
+        let mut ids = Vec::new();
+        for tenant_id in [TENANT, TENANT, TENANT + 1] {
+            let row = db
+                .bookmarks()
+                .create(
+                    tenant_id,
+                    Uuid::new_v4(),
+                    "https://example.com",
+                    "",
+                    "",
+                    &[],
+                    None,
+                    None,
+                    BookmarkKind::Other,
+                    false,
+                )
+                .await
+                .unwrap();
+            ids.push(row.id.to_string());
+        }
+        let roles = ["auditor".to_string()];
+        grant(
+            &engine,
+            WILDCARD_RESOURCE,
+            Relation::Viewer,
+            SubjectType::Role,
+            "auditor",
+        )
+        .await;
+
+        let result = engine
+            .check(&ctx("erin", &ids[0], Permission::Read), &roles)
+            .await;
+        assert!(result.allowed);
+        assert_eq!(result.reason, "wildcard permission");
+
+        let mut accessible = engine
+            .list_accessible_resources(TENANT, "erin", ResourceType::Bookmark, &roles)
+            .await
+            .unwrap();
+        accessible.sort();
+        let mut expected = ids[..2].to_vec();
+        expected.sort();
+        assert_eq!(accessible, expected);
+    }
+
+    #[tokio::test]
+    async fn federated_tuple_grants_read_only_when_enabled() {
+        let db = MemoryDb::new();
+        let owner_tenant = TENANT + 1;
+        engine(&db)
+            .store()
+            .grant_federated(
+                owner_tenant,
+                ResourceType::Bookmark,
+                "b1",
+                TENANT,
+                "fred",
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let disabled = engine(&db);
+        assert!(!allowed(&disabled, "fred", Permission::Read).await);
+        assert_eq!(
+            disabled
+                .federated_tenant(TENANT, "fred", ResourceType::Bookmark, "b1")
+                .await
+                .unwrap(),
+            None
+        );
+
+        let enabled = engine(&db).with_federation(true);
+        let result = enabled
+            .check(&ctx("fred", "b1", Permission::Read), &[])
+            .await;
+        assert!(result.allowed);
+        assert_eq!(result.reason, "federated permission");
+        assert!(!allowed(&enabled, "fred", Permission::Write).await);
+        assert!(!allowed(&enabled, "gina", Permission::Read).await);
+        assert_eq!(
+            enabled
+                .federated_tenant(TENANT, "fred", ResourceType::Bookmark, "b1")
+                .await
+                .unwrap(),
+            Some(owner_tenant)
+        );
+        assert_eq!(
+            enabled
+                .list_accessible_resources(TENANT, "fred", ResourceType::Bookmark, &[])
+                .await
+                .unwrap(),
+            vec!["b1".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn revoking_through_the_store_clears_cached_decisions() {
+        let db = MemoryDb::new();
+        let engine = engine(&db);
+        grant(&engine, "b1", Relation::Viewer, SubjectType::User, "bob").await;
+        assert!(allowed(&engine, "bob", Permission::Read).await);
+
+        engine
+            .store()
+            .delete_permission(
+                TENANT,
+                ResourceType::Bookmark,
+                "b1",
+                None,
+                SubjectType::User,
+                "bob",
+            )
+            .await
+            .unwrap();
+        assert!(!allowed(&engine, "bob", Permission::Read).await);
+    }
 }
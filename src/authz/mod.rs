@@ -1,3 +1,5 @@
 pub mod relations;
 pub mod engine;
+pub mod cache;
 pub mod checker;
+pub mod purge;
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{parse_duration, PermissionPurgeConfig};
+use crate::data::permission_repo::PermissionStore;
+use crate::shutdown::ShutdownSignal;
+
+/// Periodically delete expired permission tuples. `Engine::check_direct` already
+/// ignores them; this keeps the table from growing without bound.
+pub fn spawn_expired_permission_purge(
+    repo: Arc<dyn PermissionStore>,
+    cfg: &PermissionPurgeConfig,
+    mut shutdown: ShutdownSignal,
+) -> Option<tokio::task::JoinHandle<()>> {
+    if !cfg.enabled {
+        return None;
+    }
+    let interval = parse_duration(&cfg.interval).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "invalid permission_purge interval, using 1h");
+        Duration::from_secs(3600)
+    });
+
+    Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        let mut total_purged: u64 = 0;
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.recv() => break,
+            }
+            match repo.purge_expired().await {
+                Ok(purged) => {
+                    total_purged += purged;
+                    tracing::info!(
+                        service = "bookmark-service",
+                        purged,
+                        total_purged,
+                        timestamp = %chrono::Utc::now().to_rfc3339(),
+                        "audit: expired permissions purged"
+                    );
+                }
+                Err(e) => tracing::error!(error = %e, "failed to purge expired permissions"),
+            }
+        }
+    }))
+}
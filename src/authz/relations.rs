@@ -46,6 +46,15 @@ impl Relation {
         }
     }
 
+    /// Compact code stored in `bookmark_permissions.relation_code`; the proto value.
+    pub fn code(self) -> i16 {
+        self.to_proto() as i16
+    }
+
+    pub fn from_code(code: i16) -> Option<Self> {
+        Self::from_proto(code.into())
+    }
+
     /// Hierarchy level (higher = more permissions).
     pub fn hierarchy_level(self) -> u8 {
         match self {
@@ -75,6 +84,18 @@ impl Relation {
     pub fn grants(self, perm: Permission) -> bool {
         self.granted_permissions().contains(&perm)
     }
+
+    /// Relations that grant a specific permission.
+    pub fn granting(perm: Permission) -> impl Iterator<Item = Relation> {
+        Self::ALL.iter().copied().filter(move |r| r.grants(perm))
+    }
+
+    pub const ALL: &[Relation] = &[
+        Relation::Owner,
+        Relation::Editor,
+        Relation::Viewer,
+        Relation::Sharer,
+    ];
 }
 
 /// Permission represents an action that can be performed on a resource.
@@ -114,6 +135,9 @@ impl Permission {
     ];
 }
 
+/// Resource id of a tuple covering every resource of its type in the tenant.
+pub const WILDCARD_RESOURCE: &str = "*";
+
 /// Resource types that can be protected.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ResourceType {
@@ -127,6 +151,13 @@ impl ResourceType {
         }
     }
 
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "RESOURCE_TYPE_BOOKMARK" => Some(Self::Bookmark),
+            _ => None,
+        }
+    }
+
     pub fn from_proto(v: i32) -> Option<Self> {
         match v {
             1 => Some(Self::Bookmark),
@@ -147,6 +178,7 @@ pub enum SubjectType {
     User,
     Role,
     Tenant,
+    Group,
 }
 
 impl SubjectType {
@@ -155,6 +187,7 @@ impl SubjectType {
             Self::User => "SUBJECT_TYPE_USER",
             Self::Role => "SUBJECT_TYPE_ROLE",
             Self::Tenant => "SUBJECT_TYPE_TENANT",
+            Self::Group => "SUBJECT_TYPE_GROUP",
         }
     }
 
@@ -163,6 +196,7 @@ impl SubjectType {
             "SUBJECT_TYPE_USER" => Some(Self::User),
             "SUBJECT_TYPE_ROLE" => Some(Self::Role),
             "SUBJECT_TYPE_TENANT" => Some(Self::Tenant),
+            "SUBJECT_TYPE_GROUP" => Some(Self::Group),
             _ => None,
         }
     }
@@ -172,6 +206,7 @@ impl SubjectType {
             1 => Some(Self::User),
             2 => Some(Self::Role),
             3 => Some(Self::Tenant),
+            4 => Some(Self::Group),
             _ => None,
         }
     }
@@ -181,6 +216,7 @@ impl SubjectType {
             Self::User => 1,
             Self::Role => 2,
             Self::Tenant => 3,
+            Self::Group => 4,
         }
     }
 }
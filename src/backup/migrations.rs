@@ -0,0 +1,197 @@
+use serde_json::Value;
+
+use crate::service::bookmark_kind::classify_url;
+
+/// Backup format version written by this build.
+pub const CURRENT_VERSION: &str = "1.5";
+
+/// Upgrades a raw backup document from one version to the next, in place.
+type Transform = fn(&mut Value) -> Result<(), String>;
+
+/// Ordered chain of `(from, to, transform)` steps. Each step's `to` must be the
+/// next step's `from`, ending at [`CURRENT_VERSION`].
+const MIGRATIONS: &[(&str, &str, Transform)] = &[
+    ("1.0", "1.1", v1_0_to_v1_1),
+    ("1.1", "1.2", v1_1_to_v1_2),
+    ("1.2", "1.3", v1_2_to_v1_3),
+    ("1.3", "1.4", v1_3_to_v1_4),
+    ("1.4", "1.5", v1_4_to_v1_5),
+];
+
+/// Upgrade a raw backup document to [`CURRENT_VERSION`] by applying every
+/// transform from its declared version onward. Returns the steps applied.
+pub fn upgrade(doc: &mut Value) -> Result<Vec<String>, String> {
+    let mut version = doc
+        .get("version")
+        .and_then(Value::as_str)
+        .ok_or("backup has no version")?
+        .to_string();
+
+    let mut applied = Vec::new();
+    while version != CURRENT_VERSION {
+        let (_, to, transform) = MIGRATIONS
+            .iter()
+            .find(|(from, _, _)| *from == version)
+            .ok_or_else(|| format!("unsupported backup version {version}"))?;
+
+        transform(doc).map_err(|e| format!("upgrade {version} -> {to}: {e}"))?;
+        doc["version"] = Value::from(*to);
+        applied.push(format!("{version} -> {to}"));
+        version = to.to_string();
+    }
+
+    Ok(applied)
+}
+
+/// 1.1 added `originalUrl` to bookmarks (URL before tracking parameters were scrubbed).
+fn v1_0_to_v1_1(doc: &mut Value) -> Result<(), String> {
+    let Some(bookmarks) = doc
+        .pointer_mut("/data/bookmarks")
+        .and_then(Value::as_array_mut)
+    else {
+        return Ok(());
+    };
+
+    for bookmark in bookmarks {
+        let obj = bookmark
+            .as_object_mut()
+            .ok_or("bookmark entry is not an object")?;
+        obj.entry("originalUrl").or_insert(Value::Null);
+    }
+    Ok(())
+}
+
+/// 1.2 added markdown `notes` to bookmarks.
+fn v1_1_to_v1_2(doc: &mut Value) -> Result<(), String> {
+    let Some(bookmarks) = doc
+        .pointer_mut("/data/bookmarks")
+        .and_then(Value::as_array_mut)
+    else {
+        return Ok(());
+    };
+
+    for bookmark in bookmarks {
+        let obj = bookmark
+            .as_object_mut()
+            .ok_or("bookmark entry is not an object")?;
+        obj.entry("notes").or_insert(Value::from(""));
+    }
+    Ok(())
+}
+
+/// 1.3 added the `metadata` key/value map to bookmarks.
+fn v1_2_to_v1_3(doc: &mut Value) -> Result<(), String> {
+    let Some(bookmarks) = doc
+        .pointer_mut("/data/bookmarks")
+        .and_then(Value::as_array_mut)
+    else {
+        return Ok(());
+    };
+
+    for bookmark in bookmarks {
+        let obj = bookmark
+            .as_object_mut()
+            .ok_or("bookmark entry is not an object")?;
+        obj.entry("metadata")
+            .or_insert_with(|| Value::Object(Default::default()));
+    }
+    Ok(())
+}
+
+/// 1.4 added the content `kind` to bookmarks; older entries are classified
+/// from their URL.
+fn v1_3_to_v1_4(doc: &mut Value) -> Result<(), String> {
+    let Some(bookmarks) = doc
+        .pointer_mut("/data/bookmarks")
+        .and_then(Value::as_array_mut)
+    else {
+        return Ok(());
+    };
+
+    for bookmark in bookmarks {
+        let obj = bookmark
+            .as_object_mut()
+            .ok_or("bookmark entry is not an object")?;
+        let kind = classify_url(obj.get("url").and_then(Value::as_str).unwrap_or_default());
+        obj.entry("kind").or_insert(Value::from(kind.as_str()));
+    }
+    Ok(())
+}
+
+/// 1.5 added `isPrivate` to bookmarks and the `fieldKeys` their values are
+/// sealed with; older bookmarks are all public.
+fn v1_4_to_v1_5(doc: &mut Value) -> Result<(), String> {
+    let Some(bookmarks) = doc
+        .pointer_mut("/data/bookmarks")
+        .and_then(Value::as_array_mut)
+    else {
+        return Ok(());
+    };
+
+    for bookmark in bookmarks {
+        let obj = bookmark
+            .as_object_mut()
+            .ok_or("bookmark entry is not an object")?;
+        obj.entry("isPrivate").or_insert(Value::from(false));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::{BackupData, BookmarkBackup, FieldKeyBackup, PermissionBackup};
+
+    const FIXTURES: &[(&str, &str)] = &[
+        ("1.0", include_str!("fixtures/backup_v1_0.json")),
+        ("1.1", include_str!("fixtures/backup_v1_1.json")),
+        ("1.2", include_str!("fixtures/backup_v1_2.json")),
+        ("1.3", include_str!("fixtures/backup_v1_3.json")),
+        ("1.4", include_str!("fixtures/backup_v1_4.json")),
+        ("1.5", include_str!("fixtures/backup_v1_5.json")),
+    ];
+
+    #[test]
+    fn every_historical_version_imports() {
+        for (version, raw) in FIXTURES {
+            let mut doc: Value = serde_json::from_str(raw).unwrap();
+            assert_eq!(doc["version"], *version);
+
+            upgrade(&mut doc).unwrap_or_else(|e| panic!("upgrade {version}: {e}"));
+
+            let backup: BackupData = serde_json::from_value(doc).unwrap();
+            assert_eq!(backup.version, CURRENT_VERSION);
+            assert_eq!(backup.module, "bookmark");
+
+            for item in &backup.data.bookmarks {
+                let bk: BookmarkBackup = serde_json::from_value(item.clone()).unwrap();
+                assert!(!bk.url.is_empty());
+                assert!(item.get("originalUrl").is_some());
+                assert!(item.get("notes").is_some());
+                assert!(item.get("metadata").is_some());
+                assert!(item.get("kind").is_some());
+                assert!(item.get("isPrivate").is_some());
+            }
+            for item in &backup.data.permissions {
+                serde_json::from_value::<PermissionBackup>(item.clone()).unwrap();
+            }
+            for item in &backup.data.field_keys {
+                serde_json::from_value::<FieldKeyBackup>(item.clone()).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn chain_ends_at_current_version() {
+        for pair in MIGRATIONS.windows(2) {
+            assert_eq!(pair[0].1, pair[1].0);
+        }
+        assert_eq!(MIGRATIONS.last().map(|m| m.1), Some(CURRENT_VERSION));
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let mut doc = serde_json::json!({ "version": "9.9", "data": {} });
+        assert!(upgrade(&mut doc).is_err());
+    }
+}
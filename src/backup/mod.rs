@@ -0,0 +1,123 @@
+pub mod migrations;
+pub mod storage;
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::authz::relations::ResourceType;
+
+// --- Serde models for JSON backup data ---
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupData {
+    pub module: String,
+    pub version: String,
+    pub exported_at: String,
+    pub tenant_id: u32,
+    pub full_backup: bool,
+    pub data: BackupEntities,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupEntities {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bookmarks: Vec<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permissions: Vec<serde_json::Value>,
+    /// Wrapped data keys of private bookmarks; exported and restored with bookmarks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub field_keys: Vec<serde_json::Value>,
+}
+
+impl BackupEntities {
+    /// Assign a fresh UUID to every bookmark and point permissions on the old IDs
+    /// at the new ones. Repeated IDs map to the same new ID. Returns each
+    /// replaced ID's new one.
+    pub fn regenerate_bookmark_ids(&mut self) -> HashMap<String, String> {
+        let mut ids: HashMap<String, String> = HashMap::new();
+        for item in &mut self.bookmarks {
+            let Some(old) = item.get("id").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let new = ids
+                .entry(old.to_string())
+                .or_insert_with(|| Uuid::new_v4().to_string())
+                .clone();
+            item["id"] = serde_json::Value::String(new);
+        }
+
+        for item in &mut self.permissions {
+            let is_bookmark = item.get("resourceType").and_then(|v| v.as_str())
+                == Some(ResourceType::Bookmark.as_str());
+            if !is_bookmark {
+                continue;
+            }
+            let new = item
+                .get("resourceId")
+                .and_then(|v| v.as_str())
+                .and_then(|old| ids.get(old));
+            if let Some(new) = new {
+                item["resourceId"] = serde_json::Value::String(new.clone());
+            }
+        }
+        ids
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkBackup {
+    pub id: String,
+    pub tenant_id: i32,
+    pub url: String,
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub created_by: Option<i32>,
+    pub create_time: String,
+    pub update_time: String,
+    #[serde(default)]
+    pub original_url: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// A `BookmarkKind` name; reclassified from the URL when missing or unknown.
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// `url`, `original_url` and `notes` are sealed values, restored as is.
+    #[serde(default)]
+    pub is_private: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldKeyBackup {
+    pub id: String,
+    pub tenant_id: i32,
+    pub wrapped_key: String,
+    pub kek_id: String,
+    pub create_time: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionBackup {
+    pub tenant_id: i32,
+    pub resource_type: String,
+    pub resource_id: String,
+    pub relation: String,
+    pub subject_type: String,
+    pub subject_id: String,
+    pub granted_by: Option<i32>,
+    pub expires_at: Option<String>,
+    pub create_time: String,
+    /// Tenant of a federated subject; absent for local tuples.
+    #[serde(default)]
+    pub subject_tenant_id: Option<i32>,
+}
+
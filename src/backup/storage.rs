@@ -0,0 +1,492 @@
+//! Object storage that backups are exported to.
+//!
+//! Uploads are written part by part as the export produces them (S3 multipart
+//! uploads, Azure block blobs), so at most one part is held in memory.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{Method, StatusCode};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::config::{parse_duration, BackupStorageConfig};
+
+/// S3 rejects parts below this size, except for the last one.
+const S3_MIN_PART_MIB: usize = 5;
+/// REST API version sent to Azure Blob Storage.
+const AZURE_API_VERSION: &str = "2021-08-06";
+
+/// Starts uploads into one bucket.
+#[tonic::async_trait]
+pub trait ObjectStore: Send + Sync {
+    fn bucket(&self) -> &str;
+    async fn begin(&self, key: &str, content_type: &str) -> anyhow::Result<Box<dyn ObjectUpload>>;
+}
+
+/// An object being uploaded in parts, in order. It only appears under its key
+/// once completed.
+#[tonic::async_trait]
+pub trait ObjectUpload: Send {
+    async fn put_part(&mut self, data: Vec<u8>) -> anyhow::Result<()>;
+    async fn complete(self: Box<Self>) -> anyhow::Result<()>;
+    /// Discard the parts uploaded so far. Best effort.
+    async fn abort(self: Box<Self>);
+}
+
+/// The configured object store plus how backups are laid out in it.
+#[derive(Clone)]
+pub struct BackupStorage {
+    store: Arc<dyn ObjectStore>,
+    prefix: String,
+    part_size: usize,
+}
+
+impl BackupStorage {
+    /// Build the store selected by `cfg.driver`; `None` for the `none` driver.
+    pub fn from_config(cfg: &BackupStorageConfig) -> anyhow::Result<Option<Self>> {
+        let timeout = parse_duration(&cfg.timeout)?;
+        let store: Arc<dyn ObjectStore> = match cfg.driver.as_str() {
+            "none" => return Ok(None),
+            "s3" => Arc::new(S3Store::new(cfg, timeout)?),
+            "azure" => Arc::new(AzureStore::new(cfg, timeout)?),
+            other => anyhow::bail!("unknown backup storage driver {other:?}"),
+        };
+        if cfg.bucket.is_empty() {
+            anyhow::bail!("data.backup_storage.bucket is required");
+        }
+        if cfg.driver == "s3" && cfg.part_size_mib < S3_MIN_PART_MIB {
+            anyhow::bail!("data.backup_storage.part_size_mib must be at least {S3_MIN_PART_MIB}");
+        }
+        Ok(Some(Self {
+            store,
+            prefix: cfg.prefix.clone(),
+            part_size: cfg.part_size_mib.max(1) * 1024 * 1024,
+        }))
+    }
+
+    pub fn bucket(&self) -> &str {
+        self.store.bucket()
+    }
+
+    /// Key for an object named `name` under the configured prefix.
+    pub fn key(&self, name: &str) -> String {
+        format!("{}{name}", self.prefix)
+    }
+
+    /// Bytes to buffer before handing a part to the upload.
+    pub fn part_size(&self) -> usize {
+        self.part_size
+    }
+
+    pub async fn begin(
+        &self,
+        key: &str,
+        content_type: &str,
+    ) -> anyhow::Result<Box<dyn ObjectUpload>> {
+        self.store.begin(key, content_type).await
+    }
+}
+
+// --- S3 (and S3-compatible) multipart uploads, signed with SigV4 ---
+
+#[derive(Clone)]
+struct S3Store {
+    client: reqwest::Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    path_style: bool,
+    access_key: String,
+    secret_key: String,
+}
+
+impl S3Store {
+    fn new(cfg: &BackupStorageConfig, timeout: Duration) -> anyhow::Result<Self> {
+        let endpoint = match &cfg.endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None => format!("https://s3.{}.amazonaws.com", cfg.region),
+        };
+        Ok(Self {
+            client: reqwest::Client::builder().timeout(timeout).build()?,
+            endpoint: Url::parse(&endpoint)?,
+            bucket: cfg.bucket.clone(),
+            region: cfg.region.clone(),
+            path_style: cfg.path_style,
+            access_key: cfg.access_key.clone(),
+            secret_key: cfg.secret_key.clone(),
+        })
+    }
+
+    fn object_url(&self, key: &str) -> anyhow::Result<Url> {
+        let mut url = self.endpoint.clone();
+        let base = url.path().trim_end_matches('/').to_string();
+        if self.path_style {
+            url.set_path(&format!("{base}/{}/{}", self.bucket, uri_encode(key, false)));
+        } else {
+            let host = url
+                .host_str()
+                .ok_or_else(|| anyhow::anyhow!("backup storage endpoint has no host"))?;
+            url.set_host(Some(&format!("{}.{host}", self.bucket)))?;
+            url.set_path(&format!("{base}/{}", uri_encode(key, false)));
+        }
+        Ok(url)
+    }
+
+    /// Send a SigV4-signed request. `query` must already be in canonical form:
+    /// encoded and sorted by name.
+    async fn send(
+        &self,
+        method: Method,
+        url: &Url,
+        query: &str,
+        content_type: Option<&str>,
+        body: Vec<u8>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut url = url.clone();
+        url.set_query(Some(query));
+        let payload_hash = format!("{:x}", Sha256::digest(&body));
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let date = &amz_date[..8];
+
+        let canonical_request = format!(
+            "{method}\n{}\n{query}\nhost:{}\nx-amz-content-sha256:{payload_hash}\n\
+             x-amz-date:{amz_date}\n\nhost;x-amz-content-sha256;x-amz-date\n{payload_hash}",
+            url.path(),
+            host_header(&url),
+        );
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{:x}",
+            Sha256::digest(canonical_request.as_bytes())
+        );
+        let mut key = hmac_sha256(format!("AWS4{}", self.secret_key).as_bytes(), date.as_bytes());
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        let mut req = self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", &payload_hash)
+            .header("x-amz-date", &amz_date)
+            .header(
+                reqwest::header::AUTHORIZATION,
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{scope}, \
+                     SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={signature}",
+                    self.access_key
+                ),
+            );
+        if let Some(content_type) = content_type {
+            req = req.header(reqwest::header::CONTENT_TYPE, content_type);
+        }
+        check_status(req.body(body).send().await?).await
+    }
+}
+
+#[tonic::async_trait]
+impl ObjectStore for S3Store {
+    fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    async fn begin(&self, key: &str, content_type: &str) -> anyhow::Result<Box<dyn ObjectUpload>> {
+        let url = self.object_url(key)?;
+        let body = self
+            .send(Method::POST, &url, "uploads=", Some(content_type), Vec::new())
+            .await?
+            .text()
+            .await?;
+        let upload_id = xml_element(&body, "UploadId")
+            .ok_or_else(|| anyhow::anyhow!("multipart upload response has no UploadId"))?
+            .to_string();
+        Ok(Box::new(S3Upload {
+            store: self.clone(),
+            url,
+            upload_id,
+            etags: Vec::new(),
+        }))
+    }
+}
+
+struct S3Upload {
+    store: S3Store,
+    url: Url,
+    upload_id: String,
+    etags: Vec<String>,
+}
+
+impl S3Upload {
+    fn upload_query(&self) -> String {
+        format!("uploadId={}", uri_encode(&self.upload_id, true))
+    }
+}
+
+#[tonic::async_trait]
+impl ObjectUpload for S3Upload {
+    async fn put_part(&mut self, data: Vec<u8>) -> anyhow::Result<()> {
+        let query = format!("partNumber={}&{}", self.etags.len() + 1, self.upload_query());
+        let resp = self.store.send(Method::PUT, &self.url, &query, None, data).await?;
+        let etag = resp
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| anyhow::anyhow!("upload part response has no ETag"))?;
+        self.etags.push(etag.to_string());
+        Ok(())
+    }
+
+    async fn complete(self: Box<Self>) -> anyhow::Result<()> {
+        let mut body = String::from("<CompleteMultipartUpload>");
+        for (i, etag) in self.etags.iter().enumerate() {
+            body.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                i + 1,
+                xml_escape(etag)
+            ));
+        }
+        body.push_str("</CompleteMultipartUpload>");
+        let resp = self
+            .store
+            .send(
+                Method::POST,
+                &self.url,
+                &self.upload_query(),
+                Some("application/xml"),
+                body.into_bytes(),
+            )
+            .await?;
+        // S3 can report a failed completion in the body of a 200 response.
+        let text = resp.text().await?;
+        if text.contains("<Error>") {
+            anyhow::bail!("complete multipart upload: {}", text.trim());
+        }
+        Ok(())
+    }
+
+    async fn abort(self: Box<Self>) {
+        let query = self.upload_query();
+        if let Err(e) = self
+            .store
+            .send(Method::DELETE, &self.url, &query, None, Vec::new())
+            .await
+        {
+            tracing::warn!(
+                error = %e,
+                upload_id = %self.upload_id,
+                "abort multipart upload failed"
+            );
+        }
+    }
+}
+
+// --- Azure block blobs, signed with Shared Key ---
+
+#[derive(Clone)]
+struct AzureStore {
+    client: reqwest::Client,
+    endpoint: Url,
+    account: String,
+    key: Vec<u8>,
+    container: String,
+}
+
+impl AzureStore {
+    fn new(cfg: &BackupStorageConfig, timeout: Duration) -> anyhow::Result<Self> {
+        let endpoint = match &cfg.endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None => format!("https://{}.blob.core.windows.net", cfg.access_key),
+        };
+        let key = base64::engine::general_purpose::STANDARD
+            .decode(cfg.secret_key.trim())
+            .map_err(|e| anyhow::anyhow!("data.backup_storage.secret_key: {e}"))?;
+        Ok(Self {
+            client: reqwest::Client::builder().timeout(timeout).build()?,
+            endpoint: Url::parse(&endpoint)?,
+            account: cfg.access_key.clone(),
+            key,
+            container: cfg.bucket.clone(),
+        })
+    }
+
+    fn blob_url(&self, key: &str) -> Url {
+        let mut url = self.endpoint.clone();
+        let base = url.path().trim_end_matches('/').to_string();
+        url.set_path(&format!("{base}/{}/{}", self.container, uri_encode(key, false)));
+        url
+    }
+
+    /// Send a Shared Key signed request. `query` holds decoded name/value pairs.
+    async fn send(
+        &self,
+        url: &Url,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut url = url.clone();
+        url.query_pairs_mut().clear().extend_pairs(query);
+        let date = Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+
+        let mut ms_headers: Vec<(&str, &str)> =
+            vec![("x-ms-date", &date), ("x-ms-version", AZURE_API_VERSION)];
+        ms_headers.extend(headers.iter().copied());
+        ms_headers.sort();
+        let canonical_headers: String =
+            ms_headers.iter().map(|(name, value)| format!("{name}:{value}\n")).collect();
+        let mut sorted_query = query.to_vec();
+        sorted_query.sort();
+        let mut canonical_resource = format!("/{}{}", self.account, url.path());
+        for (name, value) in &sorted_query {
+            canonical_resource.push_str(&format!("\n{name}:{value}"));
+        }
+        let content_length = if body.is_empty() {
+            String::new()
+        } else {
+            body.len().to_string()
+        };
+        // Every standard header but Content-Length is empty in these requests.
+        let string_to_sign = format!(
+            "PUT\n\n\n{content_length}\n\n\n\n\n\n\n\n\n{canonical_headers}{canonical_resource}"
+        );
+        let signature = base64::engine::general_purpose::STANDARD
+            .encode(hmac_sha256(&self.key, string_to_sign.as_bytes()));
+
+        let mut req = self.client.put(url).header(
+            reqwest::header::AUTHORIZATION,
+            format!("SharedKey {}:{signature}", self.account),
+        );
+        for (name, value) in &ms_headers {
+            req = req.header(*name, *value);
+        }
+        check_status(req.body(body).send().await?).await
+    }
+}
+
+#[tonic::async_trait]
+impl ObjectStore for AzureStore {
+    fn bucket(&self) -> &str {
+        &self.container
+    }
+
+    async fn begin(&self, key: &str, content_type: &str) -> anyhow::Result<Box<dyn ObjectUpload>> {
+        Ok(Box::new(AzureUpload {
+            store: self.clone(),
+            url: self.blob_url(key),
+            content_type: content_type.to_string(),
+            block_ids: Vec::new(),
+        }))
+    }
+}
+
+struct AzureUpload {
+    store: AzureStore,
+    url: Url,
+    content_type: String,
+    block_ids: Vec<String>,
+}
+
+#[tonic::async_trait]
+impl ObjectUpload for AzureUpload {
+    async fn put_part(&mut self, data: Vec<u8>) -> anyhow::Result<()> {
+        // Block IDs must all have the same length within a blob.
+        let block_id = base64::engine::general_purpose::STANDARD
+            .encode(format!("{:08}", self.block_ids.len()));
+        self.store
+            .send(&self.url, &[("comp", "block"), ("blockid", &block_id)], &[], data)
+            .await?;
+        self.block_ids.push(block_id);
+        Ok(())
+    }
+
+    async fn complete(self: Box<Self>) -> anyhow::Result<()> {
+        let mut body = String::from(r#"<?xml version="1.0" encoding="utf-8"?><BlockList>"#);
+        for id in &self.block_ids {
+            body.push_str(&format!("<Latest>{id}</Latest>"));
+        }
+        body.push_str("</BlockList>");
+        self.store
+            .send(
+                &self.url,
+                &[("comp", "blocklist")],
+                &[("x-ms-blob-content-type", &self.content_type)],
+                body.into_bytes(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn abort(self: Box<Self>) {
+        // Uncommitted blocks are garbage collected by the service after a week.
+        tracing::debug!(url = %self.url, "abandoning uncommitted blob blocks");
+    }
+}
+
+// --- Helpers ---
+
+async fn check_status(resp: reqwest::Response) -> anyhow::Result<reqwest::Response> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let body = resp.text().await.unwrap_or_default();
+    let detail = body.trim();
+    if status == StatusCode::NOT_FOUND {
+        anyhow::bail!("backup storage returned 404 (does the bucket exist?): {detail}");
+    }
+    anyhow::bail!("backup storage returned {status}: {detail}")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// `Host` header value as reqwest sends it: the port only when it is not the default.
+fn host_header(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    }
+}
+
+/// Percent-encode everything but unreserved characters (and `/` unless `encode_slash`).
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut out = String::with_capacity(value.len());
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            b'/' if !encode_slash => out.push('/'),
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+fn xml_element<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{name}>");
+    let start = body.find(&open)? + open.len();
+    let end = start + body[start..].find(&format!("</{name}>"))?;
+    Some(&body[start..end])
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,156 @@
+//! Listener for the `bookmark_changes` NOTIFY channel, which triggers on the
+//! bookmark and permission tables feed. Every write, from any instance, drops
+//! the matching cached permission lookups; writes from other instances are
+//! also handed to in-process event subscribers such as `WatchBookmarks`.
+//! `SyncChanges` reads the change log itself and needs no notification.
+
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use serde::Deserialize;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+
+use crate::authz::cache::DecisionCache;
+use crate::authz::relations::ResourceType;
+use crate::events::{Event, EventPublisher, EventType};
+use crate::shutdown::ShutdownSignal;
+
+const CHANNEL: &str = "bookmark_changes";
+/// Pause before retrying after the listener failed to reconnect.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Postgres `application_name` of this process's connections. Notifications
+/// carry the writer's, so the listener can tell its own writes apart.
+pub fn instance_name() -> &'static str {
+    static NAME: LazyLock<String> = LazyLock::new(|| {
+        format!(
+            "bookmark-service-{}",
+            &uuid::Uuid::new_v4().simple().to_string()[..12]
+        )
+    });
+    &NAME
+}
+
+/// Payload `bookmark_notify_change()` sends. Bookmark notifications carry `id`,
+/// permission notifications the tuple's columns.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Notification {
+    entity: String,
+    op: String,
+    origin: String,
+    tenant_id: i32,
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    resource_type: String,
+    #[serde(default)]
+    resource_id: String,
+    #[serde(default)]
+    relation: String,
+    #[serde(default)]
+    subject_type: String,
+    #[serde(default)]
+    subject_id: String,
+}
+
+/// Start listening. Fails if the first connection cannot be made; later
+/// connection losses are retried, clearing the cache since notifications
+/// sent in between are lost.
+pub async fn spawn_change_feed(
+    pool: &PgPool,
+    cache: DecisionCache,
+    events: EventPublisher,
+    mut shutdown: ShutdownSignal,
+) -> anyhow::Result<tokio::task::JoinHandle<()>> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(CHANNEL).await?;
+
+    Ok(tokio::spawn(async move {
+        loop {
+            let received = tokio::select! {
+                received = listener.try_recv() => received,
+                _ = shutdown.recv() => break,
+            };
+            match received {
+                Ok(Some(notification)) => {
+                    match serde_json::from_str::<Notification>(notification.payload()) {
+                        Ok(n) => apply(&n, &cache, &events),
+                        Err(e) => tracing::warn!(
+                            error = %e,
+                            payload = notification.payload(),
+                            "ignoring malformed change notification"
+                        ),
+                    }
+                }
+                // The next call reconnects.
+                Ok(None) => {
+                    tracing::warn!("change feed connection lost, clearing authz cache");
+                    cache.invalidate_all();
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "change feed failed to reconnect, retrying");
+                    tokio::select! {
+                        _ = tokio::time::sleep(RECONNECT_DELAY) => {}
+                        _ = shutdown.recv() => break,
+                    }
+                }
+            }
+        }
+    }))
+}
+
+fn apply(n: &Notification, cache: &DecisionCache, events: &EventPublisher) {
+    let remote = n.origin != instance_name();
+    match n.entity.as_str() {
+        "bookmark" => {
+            if !remote {
+                return;
+            }
+            let event_type = match n.op.as_str() {
+                "INSERT" => EventType::BookmarkCreated,
+                "DELETE" => EventType::BookmarkDeleted,
+                _ => EventType::BookmarkUpdated,
+            };
+            let data = serde_json::json!({ "id": n.id });
+            events.publish_local(Event::from_change_feed(
+                event_type,
+                n.tenant_id,
+                n.id.clone(),
+                data,
+            ));
+        }
+        "permission" => {
+            // Writes outside the cached store (restores, erasures) leave
+            // entries behind in this process too, so own writes count.
+            match ResourceType::from_str(&n.resource_type) {
+                Some(resource_type) => {
+                    cache.invalidate_resource(n.tenant_id, resource_type, &n.resource_id)
+                }
+                None => cache.invalidate_all(),
+            }
+            if !remote {
+                return;
+            }
+            let event_type = match n.op.as_str() {
+                "DELETE" => EventType::PermissionRevoked,
+                _ => EventType::PermissionGranted,
+            };
+            let data = serde_json::json!({
+                "resourceType": n.resource_type,
+                "resourceId": n.resource_id,
+                "relation": n.relation,
+                "subjectType": n.subject_type,
+                "subjectId": n.subject_id,
+            });
+            events.publish_local(Event::from_change_feed(
+                event_type,
+                n.tenant_id,
+                n.resource_id.clone(),
+                data,
+            ));
+        }
+        other => tracing::warn!(entity = other, "ignoring change notification"),
+    }
+}
@@ -0,0 +1,455 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use tonic::metadata::MetadataValue;
+use tonic::Request;
+
+use crate::authz::cache::DecisionCache;
+use crate::backup::storage::BackupStorage;
+use crate::config::{
+    self, DataConfig, LoggerConfig, PolicyConfig, RegistrationConfig, ServerConfig,
+};
+use crate::data::db::Database;
+use crate::service::archiver::ArchiveFormat;
+use crate::service::backup_service::BackupServiceImpl;
+use crate::service::bookmark_service::proto::backup_service_server::BackupService;
+use crate::service::bookmark_service::proto::{
+    ExportBackupRequest, ImportBackupRequest, RestoreMode,
+};
+use crate::service::field_cipher::{key_wrapper_from_config, FieldCipher};
+use crate::service::idempotency::Idempotency;
+use crate::service::url_policy::UrlPolicy;
+
+/// Bookmark service: gRPC server and one-off maintenance tasks.
+#[derive(Debug, Parser)]
+#[command(name = "bookmark-server", version)]
+pub struct Cli {
+    /// Directory holding logger.yaml, server.yaml, data.yaml and policy.yaml.
+    #[arg(long, env = "CONFIG_DIR", default_value = "configs", global = true)]
+    pub config_dir: PathBuf,
+
+    /// Apply migrations the guard flags as unsafe (table rewrites, blocking locks).
+    #[arg(long, global = true)]
+    pub allow_unsafe_migrations: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Run the gRPC server (default).
+    Serve,
+    /// Apply pending database migrations and exit.
+    Migrate,
+    /// Write a JSON backup to a file.
+    ExportBackup(ExportBackupArgs),
+    /// Restore a JSON backup file.
+    ImportBackup(ImportBackupArgs),
+    /// Validate the configuration files and exit.
+    CheckConfig,
+    /// Load demo tenants, bookmarks and permissions from a YAML fixture.
+    Seed(SeedArgs),
+    /// Turn flat tags such as `lang:rust` into tag paths such as `lang/rust`.
+    SplitTags(SplitTagsArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ExportBackupArgs {
+    /// Tenant to export; every tenant when omitted.
+    #[arg(long)]
+    pub tenant_id: Option<u32>,
+    /// File to write the backup to.
+    #[arg(long, short)]
+    pub output: PathBuf,
+    /// Entity types to export (bookmarks, permissions); all when omitted.
+    #[arg(long, value_delimiter = ',')]
+    pub entity_types: Vec<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ImportBackupArgs {
+    /// Backup file to restore.
+    #[arg(long, short)]
+    pub input: PathBuf,
+    /// How to handle records that already exist.
+    #[arg(long, value_enum, default_value_t = ImportMode::Skip)]
+    pub mode: ImportMode,
+    /// Write nothing unless every record imports successfully.
+    #[arg(long)]
+    pub atomic: bool,
+    /// Entity types to restore (bookmarks, permissions); all when omitted.
+    #[arg(long, value_delimiter = ',')]
+    pub entity_types: Vec<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct SeedArgs {
+    /// Fixture to load; see assets/seed.yaml for the format.
+    #[arg(long, short, env = "SEED_DATA_PATH")]
+    pub file: PathBuf,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct SplitTagsArgs {
+    /// Separator used in the existing tags, e.g. `:` or `.`.
+    #[arg(long, short)]
+    pub delimiter: String,
+    /// Tenant to rewrite; every tenant when omitted.
+    #[arg(long)]
+    pub tenant_id: Option<u32>,
+    /// Count the bookmarks that would change without writing anything.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ImportMode {
+    Skip,
+    Overwrite,
+    Merge,
+    RegenerateIds,
+}
+
+impl From<ImportMode> for RestoreMode {
+    fn from(mode: ImportMode) -> Self {
+        match mode {
+            ImportMode::Skip => RestoreMode::Skip,
+            ImportMode::Overwrite => RestoreMode::Overwrite,
+            ImportMode::Merge => RestoreMode::Merge,
+            ImportMode::RegenerateIds => RestoreMode::RegenerateIds,
+        }
+    }
+}
+
+/// Backup RPCs invoked from the CLI run as a platform admin in the platform tenant.
+fn cli_request<T>(message: T) -> Request<T> {
+    let mut req = Request::new(message);
+    let md = req.metadata_mut();
+    md.insert("x-md-global-tenant-id", MetadataValue::from_static("0"));
+    md.insert("x-md-global-user-id", MetadataValue::from_static("cli"));
+    md.insert("x-md-global-username", MetadataValue::from_static("cli"));
+    md.insert(
+        "x-md-global-roles",
+        MetadataValue::from_static("platform:admin"),
+    );
+    req
+}
+
+fn backup_service(
+    db: &Database,
+    server_cfg: &ServerConfig,
+    data_cfg: &DataConfig,
+) -> anyhow::Result<BackupServiceImpl> {
+    let pool = db
+        .postgres()
+        .ok_or_else(|| anyhow::anyhow!("backups require the postgresql database driver"))?;
+    // CLI requests never carry an idempotency key, so the default TTL is unused.
+    let stores = db.stores();
+    let idempotency = Idempotency::new(stores.idempotency, &Default::default());
+    let cipher = FieldCipher::new(
+        key_wrapper_from_config(&data_cfg.data.field_encryption)?,
+        stores.field_keys,
+    );
+    Ok(BackupServiceImpl::new(
+        pool.clone(),
+        idempotency,
+        DecisionCache::disabled(),
+        None,
+        cipher,
+        UrlPolicy::new(&server_cfg.server.url_policy),
+    ))
+}
+
+pub async fn export_backup(
+    db: &Database,
+    server_cfg: &ServerConfig,
+    data_cfg: &DataConfig,
+    args: ExportBackupArgs,
+) -> anyhow::Result<()> {
+    let svc = backup_service(db, server_cfg, data_cfg)?;
+    let resp = svc
+        .export_backup(cli_request(ExportBackupRequest {
+            tenant_id: args.tenant_id,
+            entity_types: args.entity_types,
+        }))
+        .await
+        .map_err(|s| anyhow::anyhow!("export failed: {}", s.message()))?
+        .into_inner();
+
+    std::fs::write(&args.output, &resp.data)
+        .with_context(|| format!("writing {}", args.output.display()))?;
+
+    let mut counts: Vec<_> = resp.entity_counts.into_iter().collect();
+    counts.sort();
+    tracing::info!(
+        path = %args.output.display(),
+        version = %resp.version,
+        counts = ?counts,
+        "backup exported"
+    );
+    Ok(())
+}
+
+pub async fn import_backup(
+    db: &Database,
+    server_cfg: &ServerConfig,
+    data_cfg: &DataConfig,
+    args: ImportBackupArgs,
+) -> anyhow::Result<()> {
+    let data =
+        std::fs::read(&args.input).with_context(|| format!("reading {}", args.input.display()))?;
+
+    let svc = backup_service(db, server_cfg, data_cfg)?;
+    let resp = svc
+        .import_backup(cli_request(ImportBackupRequest {
+            data,
+            mode: RestoreMode::from(args.mode) as i32,
+            atomic: args.atomic,
+            entity_types: args.entity_types,
+        }))
+        .await
+        .map_err(|s| anyhow::anyhow!("import failed: {}", s.message()))?
+        .into_inner();
+
+    for warning in &resp.warnings {
+        tracing::warn!(warning = %warning, "import warning");
+    }
+    for r in &resp.results {
+        tracing::info!(
+            entity_type = %r.entity_type,
+            total = r.total,
+            created = r.created,
+            updated = r.updated,
+            skipped = r.skipped,
+            failed = r.failed,
+            "backup imported"
+        );
+    }
+    if !resp.success {
+        anyhow::bail!("some records failed to import");
+    }
+    Ok(())
+}
+
+pub async fn split_tags(db: &Database, args: SplitTagsArgs) -> anyhow::Result<()> {
+    if args.delimiter.is_empty() || args.delimiter == "/" {
+        anyhow::bail!("--delimiter must be set and differ from '/'");
+    }
+    let changed = db
+        .stores()
+        .bookmarks
+        .split_tags(
+            args.tenant_id.map(|t| t as i32),
+            &args.delimiter,
+            args.dry_run,
+        )
+        .await?;
+    tracing::info!(
+        delimiter = %args.delimiter,
+        tenant_id = ?args.tenant_id,
+        dry_run = args.dry_run,
+        bookmarks = changed,
+        "tags split into paths"
+    );
+    Ok(())
+}
+
+/// Check everything the server would otherwise only reject at startup (or
+/// silently replace with a default), without touching the database.
+pub fn check_config(
+    logger: &LoggerConfig,
+    server: &ServerConfig,
+    data: &DataConfig,
+    policy: &PolicyConfig,
+    registration: &RegistrationConfig,
+) -> anyhow::Result<()> {
+    let l = &logger.logger;
+    let s = &server.server;
+    let r = &registration.registration;
+    let mut errors = Vec::new();
+    let mut check = |what: &str, result: anyhow::Result<()>| {
+        if let Err(e) = result {
+            errors.push(format!("{what}: {e}"));
+        }
+    };
+
+    check(
+        "logger.output",
+        crate::log_file::LogOutput::from_str(&l.output).map(drop),
+    );
+    check("logger.file", crate::log_file::validate(&l.file));
+    if l.redact_pii && l.redact_key.is_empty() {
+        check(
+            "logger.redact_key",
+            Err(anyhow::anyhow!("required when redact_pii is enabled")),
+        );
+    }
+    check(
+        "server.grpc.addr",
+        s.grpc
+            .addr
+            .parse::<SocketAddr>()
+            .map(drop)
+            .map_err(Into::into),
+    );
+    if let Some(http) = &s.http {
+        check(
+            "server.http.addr",
+            http.addr
+                .parse::<SocketAddr>()
+                .map(drop)
+                .map_err(Into::into),
+        );
+    }
+    for (what, value) in [
+        ("server.grpc.timeout", &s.grpc.timeout),
+        ("server.frontend.check_interval", &s.frontend.check_interval),
+        ("server.metadata_fetch.timeout", &s.metadata_fetch.timeout),
+        ("server.jwt.jwks_refresh", &s.jwt.jwks_refresh),
+        ("server.api_keys.cache_ttl", &s.api_keys.cache_ttl),
+        ("server.sharing.default_expiry", &s.sharing.default_expiry),
+        ("server.sharing.notifier.timeout", &s.sharing.notifier.timeout),
+        (
+            "server.sharing.notifier.retry_interval",
+            &s.sharing.notifier.retry_interval,
+        ),
+        ("server.shutdown.drain_timeout", &s.shutdown.drain_timeout),
+        ("data.audit.prune_interval", &data.data.audit.prune_interval),
+        (
+            "data.permission_purge.interval",
+            &data.data.permission_purge.interval,
+        ),
+        ("data.idempotency.ttl", &data.data.idempotency.ttl),
+        ("data.authz_cache.ttl", &data.data.authz_cache.ttl),
+        (
+            "data.database.slow_query_threshold",
+            &data.data.database.slow_query_threshold,
+        ),
+        (
+            "data.database.pool_metrics_interval",
+            &data.data.database.pool_metrics_interval,
+        ),
+        (
+            "data.database.circuit_breaker.probe_interval",
+            &data.data.database.circuit_breaker.probe_interval,
+        ),
+        (
+            "data.idempotency.purge_interval",
+            &data.data.idempotency.purge_interval,
+        ),
+        ("registration.startup_delay", &r.startup_delay),
+        ("registration.heartbeat_interval", &r.heartbeat_interval),
+        (
+            "registration.backoff.initial_interval",
+            &r.backoff.initial_interval,
+        ),
+        ("registration.backoff.max_interval", &r.backoff.max_interval),
+        ("registration.backoff.max_elapsed", &r.backoff.max_elapsed),
+    ] {
+        check(what, config::parse_duration(value).map(drop));
+    }
+    for (what, names) in [
+        ("server.grpc.compression.accept", &s.grpc.compression.accept),
+        ("server.grpc.compression.send", &s.grpc.compression.send),
+    ] {
+        for name in names {
+            check(what, config::parse_compression(name).map(drop));
+        }
+    }
+    if !data.data.database.statement_timeout.is_empty() {
+        check(
+            "data.database.statement_timeout",
+            config::parse_duration(&data.data.database.statement_timeout).map(drop),
+        );
+    }
+    check("server.search", s.search.validate());
+    check(
+        "server.concurrency_limit",
+        crate::middleware::concurrency::validate(&s.concurrency_limit),
+    );
+    for network in &s.metadata_fetch.allowed_networks {
+        check(
+            "server.metadata_fetch.allowed_networks",
+            network.parse::<ipnet::IpNet>().map(drop).map_err(anyhow::Error::from),
+        );
+    }
+    check(
+        "server.archive.format",
+        ArchiveFormat::from_str(&s.archive.format)
+            .map(drop)
+            .ok_or_else(|| anyhow::anyhow!("unknown format {:?}", s.archive.format)),
+    );
+    if !matches!(
+        data.data.database.driver.as_str(),
+        "postgresql" | "postgres" | "sqlite"
+    ) {
+        check(
+            "data.database.driver",
+            Err(anyhow::anyhow!(
+                "unknown driver {:?}",
+                data.data.database.driver
+            )),
+        );
+    }
+    if data.data.database.driver == "sqlite" && data.data.database.replica_source.is_some() {
+        check(
+            "data.database.replica_source",
+            Err(anyhow::anyhow!("only supported with the postgresql driver")),
+        );
+    }
+    match s.sharing.notifier.driver.as_str() {
+        "none" | "platform" => {}
+        "webhook" if s.sharing.notifier.webhook_url.is_empty() => check(
+            "server.sharing.notifier.webhook_url",
+            Err(anyhow::anyhow!("required for the webhook driver")),
+        ),
+        "webhook" => {}
+        other => check(
+            "server.sharing.notifier.driver",
+            Err(anyhow::anyhow!("unknown driver {other:?}")),
+        ),
+    }
+    if s.jwt.enabled && s.jwt.jwks_url.is_empty() {
+        check(
+            "server.jwt.jwks_url",
+            Err(anyhow::anyhow!("required when jwt is enabled")),
+        );
+    }
+    if r.reregister_after_failures == 0 {
+        check(
+            "registration.reregister_after_failures",
+            Err(anyhow::anyhow!("must be at least 1")),
+        );
+    }
+    if r.backoff.multiplier.is_nan() || r.backoff.multiplier < 1.0 {
+        check(
+            "registration.backoff.multiplier",
+            Err(anyhow::anyhow!("must be at least 1")),
+        );
+    }
+    check(
+        "data.backup_storage",
+        BackupStorage::from_config(&data.data.backup_storage).map(drop),
+    );
+    check(
+        "data.field_encryption",
+        crate::service::field_cipher::key_wrapper_from_config(&data.data.field_encryption)
+            .map(drop),
+    );
+    check(
+        "policy",
+        crate::middleware::policy::MethodPolicy::from_config(&policy.policy).map(drop),
+    );
+
+    if !errors.is_empty() {
+        for e in &errors {
+            eprintln!("{e}");
+        }
+        anyhow::bail!("{} configuration error(s)", errors.len());
+    }
+    println!("configuration OK");
+    Ok(())
+}
@@ -6,22 +6,25 @@ pub mod proto {
     tonic::include_proto!("admin.service.v1");
 }
 
+use proto::internal_message_service_client::InternalMessageServiceClient;
 use proto::user_service_client::UserServiceClient;
 use proto::role_service_client::RoleServiceClient;
-use proto::{ListUserResponse, ListRoleResponse, PagingRequest};
+use proto::{AdminUser, ListUserResponse, ListRoleResponse, PagingRequest, SendMessageRequest};
 
-/// Calls admin-service gRPC API for user and role listing.
+/// Calls admin-service gRPC API for user and role listing and user messages.
 #[derive(Clone)]
 pub struct AdminClient {
     user_client: UserServiceClient<Channel>,
     role_client: RoleServiceClient<Channel>,
+    message_client: InternalMessageServiceClient<Channel>,
 }
 
 impl AdminClient {
     pub fn new(channel: Channel) -> Self {
         Self {
             user_client: UserServiceClient::new(channel.clone()),
-            role_client: RoleServiceClient::new(channel),
+            role_client: RoleServiceClient::new(channel.clone()),
+            message_client: InternalMessageServiceClient::new(channel),
         }
     }
 
@@ -45,6 +48,12 @@ impl AdminClient {
         Ok(resp.into_inner())
     }
 
+    /// Look up a single user by ID.
+    pub async fn find_user(&self, user_id: u32) -> Result<Option<AdminUser>, tonic::Status> {
+        let resp = self.list_users().await?;
+        Ok(resp.items.into_iter().find(|u| u.id == user_id))
+    }
+
     pub async fn list_roles(&self) -> Result<ListRoleResponse, tonic::Status> {
         let req = PagingRequest {
             no_paging: Some(true),
@@ -52,4 +61,22 @@ impl AdminClient {
         let resp = self.role_client.clone().list(req).await?;
         Ok(resp.into_inner())
     }
+
+    /// Send an in-app message to one user.
+    pub async fn send_message(
+        &self,
+        tenant_id: u32,
+        recipient_user_id: u32,
+        title: &str,
+        content: &str,
+    ) -> Result<(), tonic::Status> {
+        let req = SendMessageRequest {
+            recipient_user_id,
+            title: title.to_string(),
+            content: content.to_string(),
+            tenant_id: Some(tenant_id),
+        };
+        self.message_client.clone().send_message(req).await?;
+        Ok(())
+    }
 }
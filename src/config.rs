@@ -1,5 +1,6 @@
 use serde::Deserialize;
 use std::path::Path;
+use std::time::Duration;
 
 #[derive(Debug, Deserialize)]
 pub struct ServerConfig {
@@ -11,6 +12,533 @@ pub struct ServerSection {
     pub grpc: GrpcConfig,
     #[serde(default)]
     pub http: Option<HttpConfig>,
+    #[serde(default)]
+    pub slo: SloConfig,
+    #[serde(default)]
+    pub frontend: FrontendConfig,
+    #[serde(default)]
+    pub metadata_fetch: MetadataFetchConfig,
+    #[serde(default)]
+    pub url_policy: UrlPolicyConfig,
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+    #[serde(default)]
+    pub search: SearchConfig,
+    #[serde(default)]
+    pub jwt: JwtConfig,
+    #[serde(default)]
+    pub api_keys: ApiKeyConfig,
+    #[serde(default)]
+    pub mtls: MtlsConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub concurrency_limit: ConcurrencyLimitConfig,
+    #[serde(default)]
+    pub sharing: SharingConfig,
+    /// Report not ready so load balancers drain this instance. Reloaded at runtime.
+    #[serde(default)]
+    pub maintenance: bool,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+}
+
+/// How long a stopping server waits for in-flight work.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShutdownConfig {
+    /// Budget for in-flight RPCs and background jobs after SIGTERM; whatever is
+    /// still running then is cancelled. Keep it below the orchestrator's grace period.
+    #[serde(default = "default_drain_timeout")]
+    pub drain_timeout: String,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            drain_timeout: default_drain_timeout(),
+        }
+    }
+}
+
+fn default_drain_timeout() -> String {
+    "20s".to_string()
+}
+
+/// `ShareBookmark` expiry defaults and how recipients are notified.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SharingConfig {
+    /// Used when a share request sets no expiry.
+    #[serde(default = "default_share_expiry")]
+    pub default_expiry: String,
+    #[serde(default)]
+    pub notifier: NotifierConfig,
+    /// Let platform admins grant users of one tenant read access to another
+    /// tenant's bookmarks with `GrantFederatedAccess`. Turning it off again
+    /// stops existing cross-tenant grants from being honored.
+    #[serde(default)]
+    pub federation: bool,
+}
+
+impl Default for SharingConfig {
+    fn default() -> Self {
+        Self {
+            default_expiry: default_share_expiry(),
+            notifier: NotifierConfig::default(),
+            federation: false,
+        }
+    }
+}
+
+fn default_share_expiry() -> String {
+    "168h".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotifierConfig {
+    /// `none`, `webhook`, or `platform` (the admin gateway's message service).
+    #[serde(default = "default_notifier_driver")]
+    pub driver: String,
+    #[serde(default)]
+    pub webhook_url: String,
+    #[serde(default = "default_notifier_timeout")]
+    pub timeout: String,
+    /// Failed deliveries are retried until this many attempts were made.
+    #[serde(default = "default_notifier_max_attempts")]
+    pub max_attempts: i32,
+    #[serde(default = "default_notifier_retry_interval")]
+    pub retry_interval: String,
+}
+
+impl Default for NotifierConfig {
+    fn default() -> Self {
+        Self {
+            driver: default_notifier_driver(),
+            webhook_url: String::new(),
+            timeout: default_notifier_timeout(),
+            max_attempts: default_notifier_max_attempts(),
+            retry_interval: default_notifier_retry_interval(),
+        }
+    }
+}
+
+fn default_notifier_driver() -> String {
+    "none".to_string()
+}
+
+fn default_notifier_timeout() -> String {
+    "5s".to_string()
+}
+
+fn default_notifier_max_attempts() -> i32 {
+    5
+}
+
+fn default_notifier_retry_interval() -> String {
+    "1m".to_string()
+}
+
+/// Bearer JWT validation for running without the gateway in front. Validated
+/// claims replace any `x-md-global-*` metadata sent by the client.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwtConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Reject requests without a bearer token. When false, token-less requests
+    /// keep their gateway-provided metadata.
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub jwks_url: String,
+    #[serde(default = "default_jwks_refresh")]
+    pub jwks_refresh: String,
+    #[serde(default)]
+    pub issuer: Option<String>,
+    #[serde(default)]
+    pub audience: Option<String>,
+    #[serde(default = "default_tenant_claim")]
+    pub tenant_claim: String,
+    #[serde(default = "default_user_claim")]
+    pub user_claim: String,
+    #[serde(default = "default_username_claim")]
+    pub username_claim: String,
+    /// Array of strings or a comma-separated string.
+    #[serde(default = "default_roles_claim")]
+    pub roles_claim: String,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            required: false,
+            jwks_url: String::new(),
+            jwks_refresh: default_jwks_refresh(),
+            issuer: None,
+            audience: None,
+            tenant_claim: default_tenant_claim(),
+            user_claim: default_user_claim(),
+            username_claim: default_username_claim(),
+            roles_claim: default_roles_claim(),
+        }
+    }
+}
+
+/// Client certificate handling when the server runs with mTLS.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MtlsConfig {
+    /// Certificate CNs or SANs allowed to call BackupService. Unrestricted when
+    /// empty; when set, clients without a matching certificate are rejected.
+    #[serde(default)]
+    pub backup_trusted_clients: Vec<String>,
+}
+
+/// `x-api-key` authentication for automation clients.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiKeyConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// How long a validated key is trusted before it is looked up again. Bounds
+    /// how long a key revoked on another replica keeps working here.
+    #[serde(default = "default_api_key_cache_ttl")]
+    pub cache_ttl: String,
+}
+
+impl Default for ApiKeyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cache_ttl: default_api_key_cache_ttl(),
+        }
+    }
+}
+
+fn default_api_key_cache_ttl() -> String {
+    "60s".to_string()
+}
+
+fn default_jwks_refresh() -> String {
+    "10m".to_string()
+}
+
+fn default_tenant_claim() -> String {
+    "tenant_id".to_string()
+}
+
+fn default_user_claim() -> String {
+    "sub".to_string()
+}
+
+fn default_username_claim() -> String {
+    "preferred_username".to_string()
+}
+
+fn default_roles_claim() -> String {
+    "roles".to_string()
+}
+
+/// Per-tenant token-bucket request limit. Reloaded without a restart.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RateLimitConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Sustained requests per second allowed for each tenant.
+    #[serde(default = "default_requests_per_second")]
+    pub requests_per_second: f64,
+    /// Requests a tenant may make in a burst above the sustained rate.
+    #[serde(default = "default_burst")]
+    pub burst: f64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            requests_per_second: default_requests_per_second(),
+            burst: default_burst(),
+        }
+    }
+}
+
+fn default_requests_per_second() -> f64 {
+    50.0
+}
+
+fn default_burst() -> f64 {
+    100.0
+}
+
+/// Caps on RPCs handled at once; calls over a cap fail fast with
+/// RESOURCE_EXHAUSTED instead of queueing.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConcurrencyLimitConfig {
+    #[serde(default = "default_concurrency_limit_enabled")]
+    pub enabled: bool,
+    /// Calls in flight across all methods.
+    #[serde(default = "default_max_in_flight")]
+    pub max_in_flight: usize,
+    /// Lower caps for expensive methods, counted on top of the global one.
+    /// Replaces the defaults (the backup RPCs) when set.
+    #[serde(default = "default_method_concurrency_limits")]
+    pub methods: Vec<MethodConcurrencyLimit>,
+}
+
+impl Default for ConcurrencyLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_concurrency_limit_enabled(),
+            max_in_flight: default_max_in_flight(),
+            methods: default_method_concurrency_limits(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MethodConcurrencyLimit {
+    /// Full gRPC method path, e.g. `/bookmark.service.v1.BackupService/ExportBackup`.
+    pub method: String,
+    pub max_in_flight: usize,
+}
+
+fn default_concurrency_limit_enabled() -> bool {
+    true
+}
+
+fn default_max_in_flight() -> usize {
+    1024
+}
+
+fn default_method_concurrency_limits() -> Vec<MethodConcurrencyLimit> {
+    ["ExportBackup", "ExportBackupToStorage", "ImportBackup"]
+        .into_iter()
+        .map(|name| MethodConcurrencyLimit {
+            method: format!("/bookmark.service.v1.BackupService/{name}"),
+            max_in_flight: 2,
+        })
+        .collect()
+}
+
+/// Page snapshots taken when a bookmark is saved. `enabled` is reloaded without a restart.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArchiveConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// `text` (readable text only) or `html` (full page source).
+    #[serde(default = "default_archive_format")]
+    pub format: String,
+    #[serde(default = "default_archive_max_bytes")]
+    pub max_bytes: usize,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: default_archive_format(),
+            max_bytes: default_archive_max_bytes(),
+        }
+    }
+}
+
+fn default_archive_format() -> String {
+    "text".to_string()
+}
+
+fn default_archive_max_bytes() -> usize {
+    5 * 1024 * 1024
+}
+
+/// `SearchBookmarks` tuning.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchConfig {
+    /// Trigram word similarity (0 to 1) a title or host needs to match a
+    /// `fuzzy` search. Lower values tolerate more typos and return more noise.
+    #[serde(default = "default_fuzzy_threshold")]
+    pub fuzzy_threshold: f32,
+    #[serde(default)]
+    pub saved: SavedSearchConfig,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            fuzzy_threshold: default_fuzzy_threshold(),
+            saved: SavedSearchConfig::default(),
+        }
+    }
+}
+
+impl SearchConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(0.0..=1.0).contains(&self.fuzzy_threshold) {
+            anyhow::bail!(
+                "fuzzy_threshold must be between 0 and 1, got {}",
+                self.fuzzy_threshold
+            );
+        }
+        Ok(())
+    }
+}
+
+fn default_fuzzy_threshold() -> f32 {
+    0.5
+}
+
+/// Saved searches and the alerts users subscribe to on them. Alerts go
+/// through `sharing.notifier`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SavedSearchConfig {
+    #[serde(default = "default_max_saved_searches")]
+    pub max_per_user: usize,
+    /// Evaluate subscriptions. When off they are kept, but nothing is sent.
+    #[serde(default = "default_true")]
+    pub alerts_enabled: bool,
+    /// How often the alert job looks for subscriptions that are due.
+    #[serde(default = "default_alert_check_interval")]
+    pub check_interval: String,
+    /// Shortest interval a user may subscribe with, bounding how often each
+    /// of their searches can notify them.
+    #[serde(default = "default_min_alert_interval")]
+    pub min_alert_interval: String,
+    /// Used when a subscribe request sets no interval.
+    #[serde(default = "default_alert_interval")]
+    pub default_alert_interval: String,
+    /// Bookmarks listed in one alert; the alert still reports the total.
+    #[serde(default = "default_alert_max_matches")]
+    pub max_matches: u32,
+}
+
+impl Default for SavedSearchConfig {
+    fn default() -> Self {
+        Self {
+            max_per_user: default_max_saved_searches(),
+            alerts_enabled: true,
+            check_interval: default_alert_check_interval(),
+            min_alert_interval: default_min_alert_interval(),
+            default_alert_interval: default_alert_interval(),
+            max_matches: default_alert_max_matches(),
+        }
+    }
+}
+
+fn default_max_saved_searches() -> usize {
+    50
+}
+
+fn default_alert_check_interval() -> String {
+    "5m".to_string()
+}
+
+fn default_min_alert_interval() -> String {
+    "1h".to_string()
+}
+
+fn default_alert_interval() -> String {
+    "24h".to_string()
+}
+
+fn default_alert_max_matches() -> u32 {
+    20
+}
+
+/// Outbound fetching of page titles/descriptions for new bookmarks.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetadataFetchConfig {
+    #[serde(default = "default_metadata_timeout")]
+    pub timeout: String,
+    /// Maximum bytes of the page body read while looking for metadata.
+    #[serde(default = "default_metadata_max_bytes")]
+    pub max_bytes: usize,
+    #[serde(default = "default_metadata_user_agent")]
+    pub user_agent: String,
+    /// Refuse loopback, private, link-local (cloud metadata) and other
+    /// non-public addresses, checked after DNS resolution and on redirects.
+    #[serde(default = "default_true")]
+    pub block_private_networks: bool,
+    /// CIDRs exempt from `block_private_networks`, e.g. an intranet wiki.
+    #[serde(default)]
+    pub allowed_networks: Vec<String>,
+    /// Host names never fetched; subdomains are blocked too.
+    #[serde(default = "default_blocked_hosts")]
+    pub blocked_hosts: Vec<String>,
+}
+
+impl Default for MetadataFetchConfig {
+    fn default() -> Self {
+        Self {
+            timeout: default_metadata_timeout(),
+            max_bytes: default_metadata_max_bytes(),
+            user_agent: default_metadata_user_agent(),
+            block_private_networks: true,
+            allowed_networks: Vec::new(),
+            blocked_hosts: default_blocked_hosts(),
+        }
+    }
+}
+
+fn default_blocked_hosts() -> Vec<String> {
+    vec!["metadata.google.internal".to_string()]
+}
+
+/// Which bookmark URLs are accepted on create, update and import.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UrlPolicyConfig {
+    /// Lowercase schemes allowed in bookmark URLs.
+    #[serde(default = "default_allowed_schemes")]
+    pub allowed_schemes: Vec<String>,
+}
+
+impl Default for UrlPolicyConfig {
+    fn default() -> Self {
+        Self {
+            allowed_schemes: default_allowed_schemes(),
+        }
+    }
+}
+
+fn default_allowed_schemes() -> Vec<String> {
+    vec!["http".to_string(), "https".to_string()]
+}
+
+fn default_metadata_timeout() -> String {
+    "5s".to_string()
+}
+
+fn default_metadata_max_bytes() -> usize {
+    512 * 1024
+}
+
+fn default_metadata_user_agent() -> String {
+    "tangra-bookmark/1.0 (+metadata fetch)".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FrontendConfig {
+    /// Module Federation manifest whose hash is verified, relative to the dist dir.
+    #[serde(default = "default_manifest_file")]
+    pub manifest_file: String,
+    /// Expected lowercase hex SHA-256 of the manifest; only presence is checked when empty.
+    #[serde(default)]
+    pub expected_manifest_sha256: String,
+    #[serde(default = "default_check_interval")]
+    pub check_interval: String,
+}
+
+impl Default for FrontendConfig {
+    fn default() -> Self {
+        Self {
+            manifest_file: default_manifest_file(),
+            expected_manifest_sha256: String::new(),
+            check_interval: default_check_interval(),
+        }
+    }
+}
+
+fn default_manifest_file() -> String {
+    "mf-manifest.json".to_string()
+}
+
+fn default_check_interval() -> String {
+    "60s".to_string()
 }
 
 #[derive(Debug, Deserialize)]
@@ -23,12 +551,136 @@ pub struct GrpcConfig {
     pub addr: String,
     #[serde(default = "default_timeout")]
     pub timeout: String,
+    #[serde(default)]
+    pub web: GrpcWebConfig,
+    /// Largest request message accepted, in bytes.
+    #[serde(default = "default_max_message_size")]
+    pub max_decoding_message_size: usize,
+    /// Largest response message sent, in bytes.
+    #[serde(default = "default_max_message_size")]
+    pub max_encoding_message_size: usize,
+    #[serde(default)]
+    pub compression: GrpcCompressionConfig,
+}
+
+fn default_max_message_size() -> usize {
+    64 * 1024 * 1024
+}
+
+/// Message compression encodings (`gzip`, `zstd`). Responses are only
+/// compressed when the client also accepts the encoding.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GrpcCompressionConfig {
+    /// Encodings accepted on requests.
+    #[serde(default = "default_accept_compression")]
+    pub accept: Vec<String>,
+    /// Encodings used for responses, in order of preference.
+    #[serde(default)]
+    pub send: Vec<String>,
+}
+
+impl Default for GrpcCompressionConfig {
+    fn default() -> Self {
+        Self {
+            accept: default_accept_compression(),
+            send: Vec::new(),
+        }
+    }
+}
+
+fn default_accept_compression() -> Vec<String> {
+    vec!["gzip".to_string(), "zstd".to_string()]
+}
+
+/// Parse a compression encoding name as used in `server.grpc.compression`.
+pub fn parse_compression(name: &str) -> anyhow::Result<tonic::codec::CompressionEncoding> {
+    match name {
+        "gzip" => Ok(tonic::codec::CompressionEncoding::Gzip),
+        "zstd" => Ok(tonic::codec::CompressionEncoding::Zstd),
+        other => anyhow::bail!("unknown compression encoding {other:?}"),
+    }
+}
+
+/// gRPC-Web settings for calling the services directly from the browser.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GrpcWebConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Origins allowed to make credentialed cross-origin calls. When empty,
+    /// any origin may call but browsers send no cookies.
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for GrpcWebConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allowed_origins: Vec::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
 }
 
 fn default_timeout() -> String {
     "30s".to_string()
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SloConfig {
+    /// Burn rate over both the 5m and 1h windows that triggers a page-level alert.
+    #[serde(default = "default_fast_burn_threshold")]
+    pub fast_burn_threshold: f64,
+    /// Burn rate over the 1h window that triggers a ticket-level alert.
+    #[serde(default = "default_slow_burn_threshold")]
+    pub slow_burn_threshold: f64,
+    #[serde(default)]
+    pub objectives: Vec<SloObjective>,
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        Self {
+            fast_burn_threshold: default_fast_burn_threshold(),
+            slow_burn_threshold: default_slow_burn_threshold(),
+            objectives: Vec::new(),
+        }
+    }
+}
+
+fn default_fast_burn_threshold() -> f64 {
+    14.4
+}
+
+fn default_slow_burn_threshold() -> f64 {
+    6.0
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SloObjective {
+    /// Full gRPC method path (e.g. `/bookmark.service.v1.BookmarkService/GetBookmark`),
+    /// or `*` for the default applied to every other method.
+    pub method: String,
+    pub latency_ms: u64,
+    /// Fraction of requests that must complete within `latency_ms`.
+    #[serde(default = "default_latency_target")]
+    pub latency_target: f64,
+    /// Fraction of requests that must not fail with a server-side error.
+    #[serde(default = "default_availability_target")]
+    pub availability_target: f64,
+}
+
+fn default_latency_target() -> f64 {
+    0.99
+}
+
+fn default_availability_target() -> f64 {
+    0.999
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DataConfig {
     pub data: DataSection,
@@ -39,6 +691,340 @@ pub struct DataSection {
     pub database: DatabaseConfig,
     #[serde(default)]
     pub redis: Option<RedisConfig>,
+    #[serde(default)]
+    pub permission_purge: PermissionPurgeConfig,
+    #[serde(default)]
+    pub events: EventsConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub usage_stats: UsageStatsConfig,
+    #[serde(default)]
+    pub idempotency: IdempotencyConfig,
+    #[serde(default)]
+    pub authz_cache: AuthzCacheConfig,
+    #[serde(default)]
+    pub backup_storage: BackupStorageConfig,
+    #[serde(default)]
+    pub field_encryption: FieldEncryptionConfig,
+    #[serde(default)]
+    pub change_feed: ChangeFeedConfig,
+}
+
+/// Postgres LISTEN/NOTIFY feed of bookmark and permission writes made by any
+/// instance. Ignored with SQLite, which has a single writer.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ChangeFeedConfig {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Object storage `ExportBackupToStorage` uploads backups to.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackupStorageConfig {
+    /// `none`, `s3` (AWS S3, MinIO, GCS interoperability) or `azure`.
+    #[serde(default = "default_backup_storage_driver")]
+    pub driver: String,
+    /// Bucket, or container for `azure`.
+    #[serde(default)]
+    pub bucket: String,
+    /// Prepended to generated object keys, e.g. `backups/bookmark/`.
+    #[serde(default)]
+    pub prefix: String,
+    /// Service URL. Defaults to AWS for `s3` and the account's blob endpoint for `azure`.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default = "default_backup_storage_region")]
+    pub region: String,
+    /// Address buckets as `endpoint/bucket` rather than `bucket.endpoint`.
+    #[serde(default)]
+    pub path_style: bool,
+    /// Access key ID, or the storage account name for `azure`.
+    #[serde(default)]
+    pub access_key: String,
+    /// Secret access key, or the base64 account key for `azure`.
+    #[serde(default)]
+    pub secret_key: String,
+    /// Bytes buffered per uploaded part, in MiB. S3 requires at least 5.
+    #[serde(default = "default_backup_storage_part_size_mib")]
+    pub part_size_mib: usize,
+    /// Per request to the storage service.
+    #[serde(default = "default_backup_storage_timeout")]
+    pub timeout: String,
+}
+
+impl Default for BackupStorageConfig {
+    fn default() -> Self {
+        Self {
+            driver: default_backup_storage_driver(),
+            bucket: String::new(),
+            prefix: String::new(),
+            endpoint: None,
+            region: default_backup_storage_region(),
+            path_style: false,
+            access_key: String::new(),
+            secret_key: String::new(),
+            part_size_mib: default_backup_storage_part_size_mib(),
+            timeout: default_backup_storage_timeout(),
+        }
+    }
+}
+
+fn default_backup_storage_driver() -> String {
+    "none".to_string()
+}
+
+fn default_backup_storage_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_backup_storage_part_size_mib() -> usize {
+    16
+}
+
+fn default_backup_storage_timeout() -> String {
+    "60s".to_string()
+}
+
+/// Key-encryption key that wraps the per-tenant data keys private bookmarks
+/// are sealed with.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldEncryptionConfig {
+    /// `none` (private bookmarks are refused), `local` or `vault` (HashiCorp
+    /// Vault's transit engine).
+    #[serde(default = "default_field_encryption_driver")]
+    pub driver: String,
+    /// Base64 of the 32-byte master key, for `local`.
+    #[serde(default)]
+    pub master_key: String,
+    /// Vault address, e.g. `https://vault:8200`.
+    #[serde(default)]
+    pub endpoint: String,
+    /// Mount path of the transit engine.
+    #[serde(default = "default_field_encryption_mount")]
+    pub mount: String,
+    /// Transit key that wraps data keys.
+    #[serde(default)]
+    pub key_name: String,
+    #[serde(default)]
+    pub token: String,
+    /// Per request to Vault.
+    #[serde(default = "default_field_encryption_timeout")]
+    pub timeout: String,
+}
+
+impl Default for FieldEncryptionConfig {
+    fn default() -> Self {
+        Self {
+            driver: default_field_encryption_driver(),
+            master_key: String::new(),
+            endpoint: String::new(),
+            mount: default_field_encryption_mount(),
+            key_name: String::new(),
+            token: String::new(),
+            timeout: default_field_encryption_timeout(),
+        }
+    }
+}
+
+fn default_field_encryption_driver() -> String {
+    "none".to_string()
+}
+
+fn default_field_encryption_mount() -> String {
+    "transit".to_string()
+}
+
+fn default_field_encryption_timeout() -> String {
+    "10s".to_string()
+}
+
+/// In-process cache of permission tuple lookups made by the authz engine.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthzCacheConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Upper bound on how stale a decision can be after a write that bypasses
+    /// the engine, e.g. a change made by another replica.
+    #[serde(default = "default_authz_cache_ttl")]
+    pub ttl: String,
+    /// Least recently used entries are evicted beyond this many.
+    #[serde(default = "default_authz_cache_max_entries")]
+    pub max_entries: u64,
+}
+
+impl Default for AuthzCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl: default_authz_cache_ttl(),
+            max_entries: default_authz_cache_max_entries(),
+        }
+    }
+}
+
+fn default_authz_cache_ttl() -> String {
+    "30s".to_string()
+}
+
+fn default_authz_cache_max_entries() -> u64 {
+    100_000
+}
+
+/// How long responses to requests with an `x-idempotency-key` are kept for replay.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdempotencyConfig {
+    #[serde(default = "default_idempotency_ttl")]
+    pub ttl: String,
+    #[serde(default = "default_purge_interval")]
+    pub purge_interval: String,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            ttl: default_idempotency_ttl(),
+            purge_interval: default_purge_interval(),
+        }
+    }
+}
+
+fn default_idempotency_ttl() -> String {
+    "24h".to_string()
+}
+
+/// Persisted audit trail of RPC calls and how long it is kept.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuditConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Events older than this are pruned; 0 keeps them forever.
+    #[serde(default = "default_audit_retention_days")]
+    pub retention_days: u32,
+    #[serde(default = "default_audit_prune_interval")]
+    pub prune_interval: String,
+    /// When set, pruned events are first appended to NDJSON files in this directory.
+    #[serde(default)]
+    pub archive_dir: Option<String>,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention_days: default_audit_retention_days(),
+            prune_interval: default_audit_prune_interval(),
+            archive_dir: None,
+        }
+    }
+}
+
+fn default_audit_retention_days() -> u32 {
+    90
+}
+
+fn default_audit_prune_interval() -> String {
+    "24h".to_string()
+}
+
+/// Periodic rollup of usage into per-tenant daily stats.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UsageStatsConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_usage_stats_interval")]
+    pub interval: String,
+    /// Days recomputed on each run, ending today, so late audit writes and
+    /// short outages are covered.
+    #[serde(default = "default_usage_stats_lookback_days")]
+    pub lookback_days: u32,
+    /// Domains kept per tenant and day.
+    #[serde(default = "default_usage_stats_top_domains")]
+    pub top_domains: u32,
+}
+
+impl Default for UsageStatsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: default_usage_stats_interval(),
+            lookback_days: default_usage_stats_lookback_days(),
+            top_domains: default_usage_stats_top_domains(),
+        }
+    }
+}
+
+fn default_usage_stats_interval() -> String {
+    "24h".to_string()
+}
+
+fn default_usage_stats_lookback_days() -> u32 {
+    3
+}
+
+fn default_usage_stats_top_domains() -> u32 {
+    10
+}
+
+/// Publishing of bookmark and permission events to a message broker.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// `nats`, or `kafka` when built with the `kafka` feature.
+    #[serde(default = "default_events_driver")]
+    pub driver: String,
+    /// NATS server URL, or comma-separated Kafka bootstrap brokers.
+    #[serde(default = "default_events_url")]
+    pub url: String,
+    /// NATS subject prefix (events go to `<subject>.bookmark.created` etc.) or Kafka topic.
+    #[serde(default = "default_events_subject")]
+    pub subject: String,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            driver: default_events_driver(),
+            url: default_events_url(),
+            subject: default_events_subject(),
+        }
+    }
+}
+
+fn default_events_driver() -> String {
+    "nats".to_string()
+}
+
+fn default_events_url() -> String {
+    "nats://localhost:4222".to_string()
+}
+
+fn default_events_subject() -> String {
+    "bookmark.events".to_string()
+}
+
+/// Periodic deletion of permission tuples whose `expires_at` has passed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PermissionPurgeConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_purge_interval")]
+    pub interval: String,
+}
+
+impl Default for PermissionPurgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: default_purge_interval(),
+        }
+    }
+}
+
+fn default_purge_interval() -> String {
+    "1h".to_string()
 }
 
 #[derive(Debug, Deserialize)]
@@ -46,8 +1032,62 @@ pub struct DatabaseConfig {
     #[serde(default = "default_driver")]
     pub driver: String,
     pub source: String,
+    /// Optional Postgres read replica for read-only queries.
+    #[serde(default)]
+    pub replica_source: Option<String>,
     #[serde(default = "default_max_connections")]
     pub max_connections: u32,
+    /// Statements and connection acquires taking longer are logged at WARN.
+    /// Statements are logged with their placeholders, never bound values.
+    #[serde(default = "default_slow_query_threshold")]
+    pub slow_query_threshold: String,
+    /// How often pool acquire latency is sampled for metrics.
+    #[serde(default = "default_pool_metrics_interval")]
+    pub pool_metrics_interval: String,
+    /// Postgres `statement_timeout` while serving. Empty follows
+    /// `server.grpc.timeout`, since a request cannot wait any longer; `0`
+    /// disables it.
+    #[serde(default)]
+    pub statement_timeout: String,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+}
+
+/// Fail RPCs fast while the database is unreachable instead of letting each
+/// one wait out the connect timeout.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CircuitBreakerConfig {
+    #[serde(default = "default_circuit_breaker_enabled")]
+    pub enabled: bool,
+    /// Consecutive connection failures that open the breaker.
+    #[serde(default = "default_circuit_breaker_failure_threshold")]
+    pub failure_threshold: u32,
+    /// How often the pool is probed while open; also the retry delay sent to
+    /// callers and the probe's timeout.
+    #[serde(default = "default_circuit_breaker_probe_interval")]
+    pub probe_interval: String,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_circuit_breaker_enabled(),
+            failure_threshold: default_circuit_breaker_failure_threshold(),
+            probe_interval: default_circuit_breaker_probe_interval(),
+        }
+    }
+}
+
+fn default_circuit_breaker_enabled() -> bool {
+    true
+}
+
+fn default_circuit_breaker_failure_threshold() -> u32 {
+    5
+}
+
+fn default_circuit_breaker_probe_interval() -> String {
+    "2s".to_string()
 }
 
 fn default_driver() -> String {
@@ -58,6 +1098,14 @@ fn default_max_connections() -> u32 {
     20
 }
 
+fn default_slow_query_threshold() -> String {
+    "500ms".to_string()
+}
+
+fn default_pool_metrics_interval() -> String {
+    "15s".to_string()
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RedisConfig {
     pub addr: String,
@@ -67,6 +1115,147 @@ pub struct RedisConfig {
     pub db: u8,
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct PolicyConfig {
+    #[serde(default)]
+    pub policy: PolicySection,
+}
+
+/// Per-RPC role requirements checked before requests reach the handlers.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PolicySection {
+    /// `allow` or `deny` for methods that match no rule.
+    #[serde(default = "default_policy_action")]
+    pub default_action: String,
+    /// Evaluated in order; the first rule whose `method` matches applies.
+    #[serde(default = "default_policy_rules")]
+    pub rules: Vec<PolicyRule>,
+}
+
+impl Default for PolicySection {
+    fn default() -> Self {
+        Self {
+            default_action: default_policy_action(),
+            rules: default_policy_rules(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PolicyRule {
+    /// `Service/Method`, where `Service` is the short or fully-qualified service
+    /// name and either part may be `*`.
+    pub method: String,
+    /// The caller must hold at least one of these roles; empty allows any
+    /// authenticated caller.
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Skip the authentication check entirely.
+    #[serde(default)]
+    pub public: bool,
+}
+
+fn default_policy_action() -> String {
+    "allow".to_string()
+}
+
+/// Used when no policy file is present, so backups are never open to every user.
+fn default_policy_rules() -> Vec<PolicyRule> {
+    vec![PolicyRule {
+        method: "BackupService/*".to_string(),
+        roles: vec!["platform:admin".to_string(), "super:admin".to_string()],
+        public: false,
+    }]
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RegistrationConfig {
+    #[serde(default)]
+    pub registration: RegistrationSection,
+}
+
+/// Timing of registration with the admin gateway.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegistrationSection {
+    /// Wait before the first attempt so the gRPC server is listening.
+    #[serde(default = "default_startup_delay")]
+    pub startup_delay: String,
+    #[serde(default = "default_heartbeat_interval")]
+    pub heartbeat_interval: String,
+    /// Re-register after this many consecutive failed heartbeats. An
+    /// unacknowledged heartbeat re-registers immediately.
+    #[serde(default = "default_reregister_after_failures")]
+    pub reregister_after_failures: u32,
+    /// Retry schedule for connecting and registering.
+    #[serde(default)]
+    pub backoff: BackoffConfig,
+}
+
+impl Default for RegistrationSection {
+    fn default() -> Self {
+        Self {
+            startup_delay: default_startup_delay(),
+            heartbeat_interval: default_heartbeat_interval(),
+            reregister_after_failures: default_reregister_after_failures(),
+            backoff: BackoffConfig::default(),
+        }
+    }
+}
+
+fn default_startup_delay() -> String {
+    "3s".to_string()
+}
+
+fn default_heartbeat_interval() -> String {
+    "30s".to_string()
+}
+
+fn default_reregister_after_failures() -> u32 {
+    3
+}
+
+/// Exponential backoff: each delay is a random point between half and all of
+/// the current interval, which grows by `multiplier` up to `max_interval`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackoffConfig {
+    #[serde(default = "default_backoff_initial_interval")]
+    pub initial_interval: String,
+    #[serde(default = "default_backoff_max_interval")]
+    pub max_interval: String,
+    #[serde(default = "default_backoff_multiplier")]
+    pub multiplier: f64,
+    /// Give up once this much time has passed; `0` retries forever.
+    #[serde(default = "default_backoff_max_elapsed")]
+    pub max_elapsed: String,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial_interval: default_backoff_initial_interval(),
+            max_interval: default_backoff_max_interval(),
+            multiplier: default_backoff_multiplier(),
+            max_elapsed: default_backoff_max_elapsed(),
+        }
+    }
+}
+
+fn default_backoff_initial_interval() -> String {
+    "1s".to_string()
+}
+
+fn default_backoff_max_interval() -> String {
+    "60s".to_string()
+}
+
+fn default_backoff_multiplier() -> f64 {
+    2.0
+}
+
+fn default_backoff_max_elapsed() -> String {
+    "30m".to_string()
+}
+
 #[derive(Debug, Deserialize)]
 pub struct LoggerConfig {
     pub logger: LoggerSection,
@@ -76,10 +1265,112 @@ pub struct LoggerConfig {
 pub struct LoggerSection {
     #[serde(default = "default_level")]
     pub level: String,
+    /// Where log lines go: `stdout`, `file` or `both`.
     #[serde(default = "default_output")]
     pub output: String,
     #[serde(default = "default_format")]
     pub format: String,
+    /// Log file settings, used when `output` is `file` or `both`.
+    #[serde(default)]
+    pub file: LogFileConfig,
+    /// Hash user ids, usernames and URLs in log fields and audit exports.
+    #[serde(default)]
+    pub redact_pii: bool,
+    /// Secret keying the redaction hash; required with `redact_pii`. Keep it
+    /// the same across instances so their tokens match.
+    #[serde(default)]
+    pub redact_key: String,
+    #[serde(default)]
+    pub tracing: TracingConfig,
+}
+
+/// Rotating log file settings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogFileConfig {
+    #[serde(default = "default_log_dir")]
+    pub dir: String,
+    /// Files are named `<prefix>.<date>.log` with time rotation, and
+    /// `<prefix>.log`, `<prefix>.1.log`, ... with size rotation.
+    #[serde(default = "default_log_prefix")]
+    pub prefix: String,
+    /// `minutely`, `hourly`, `daily` or `never`, or `size` to roll once the
+    /// file reaches `max_bytes`.
+    #[serde(default = "default_log_rotation")]
+    pub rotation: String,
+    #[serde(default = "default_log_max_bytes")]
+    pub max_bytes: u64,
+    /// Rotated files kept besides the current one; older ones are deleted.
+    #[serde(default = "default_log_max_files")]
+    pub max_files: usize,
+}
+
+impl Default for LogFileConfig {
+    fn default() -> Self {
+        Self {
+            dir: default_log_dir(),
+            prefix: default_log_prefix(),
+            rotation: default_log_rotation(),
+            max_bytes: default_log_max_bytes(),
+            max_files: default_log_max_files(),
+        }
+    }
+}
+
+fn default_log_dir() -> String {
+    "logs".to_string()
+}
+
+fn default_log_prefix() -> String {
+    "bookmark-service".to_string()
+}
+
+fn default_log_rotation() -> String {
+    "daily".to_string()
+}
+
+fn default_log_max_bytes() -> u64 {
+    100 * 1024 * 1024
+}
+
+fn default_log_max_files() -> usize {
+    7
+}
+
+/// OpenTelemetry trace export settings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TracingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_otlp_endpoint")]
+    pub otlp_endpoint: String,
+    /// Fraction of root traces sampled; child spans follow the caller's decision.
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: f64,
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            otlp_endpoint: default_otlp_endpoint(),
+            sample_rate: default_sample_rate(),
+            service_name: default_service_name(),
+        }
+    }
+}
+
+fn default_otlp_endpoint() -> String {
+    "http://localhost:4317".to_string()
+}
+
+fn default_sample_rate() -> f64 {
+    1.0
+}
+
+fn default_service_name() -> String {
+    "bookmark-service".to_string()
 }
 
 fn default_level() -> String {
@@ -94,6 +1385,24 @@ fn default_format() -> String {
     "json".to_string()
 }
 
+/// Parse a duration such as `500ms`, `30s`, `5m` or `1h`. A bare number is seconds.
+pub fn parse_duration(s: &str) -> anyhow::Result<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let value: u64 = num
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid duration {s:?}"))?;
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(value)),
+        "" | "s" => Ok(Duration::from_secs(value)),
+        "m" => Ok(Duration::from_secs(value * 60)),
+        "h" => Ok(Duration::from_secs(value * 3600)),
+        _ => anyhow::bail!("invalid duration unit in {s:?}"),
+    }
+}
+
 pub fn load_config<T: serde::de::DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
     let content = std::fs::read_to_string(path)?;
     let config: T = serde_yaml::from_str(&content)?;
@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::watch;
+use tracing_subscriber::EnvFilter;
+
+use crate::config::{self, LoggerConfig, RateLimitConfig, ServerConfig};
+use crate::log_level::LogLevel;
+
+/// How often config files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Reloads tunables from `logger.yaml` and `server.yaml` on SIGHUP or when
+/// either file changes, publishing them on watch channels. Everything else in
+/// those files still needs a restart.
+pub struct ConfigWatcher {
+    config_dir: PathBuf,
+    log_filter: LogLevel,
+    log_level: String,
+    rate_limit: watch::Sender<RateLimitConfig>,
+    archive_enabled: watch::Sender<bool>,
+    maintenance: watch::Sender<bool>,
+}
+
+impl ConfigWatcher {
+    pub fn new(
+        config_dir: &Path,
+        logger: &LoggerConfig,
+        server: &ServerConfig,
+        log_filter: LogLevel,
+    ) -> Self {
+        Self {
+            config_dir: config_dir.to_path_buf(),
+            log_filter,
+            log_level: logger.logger.level.clone(),
+            rate_limit: watch::Sender::new(server.server.rate_limit.clone()),
+            archive_enabled: watch::Sender::new(server.server.archive.enabled),
+            maintenance: watch::Sender::new(server.server.maintenance),
+        }
+    }
+
+    pub fn rate_limit(&self) -> watch::Receiver<RateLimitConfig> {
+        self.rate_limit.subscribe()
+    }
+
+    pub fn archive_enabled(&self) -> watch::Receiver<bool> {
+        self.archive_enabled.subscribe()
+    }
+
+    pub fn maintenance(&self) -> watch::Receiver<bool> {
+        self.maintenance.subscribe()
+    }
+
+    pub fn spawn(mut self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            #[cfg(unix)]
+            let mut hangup =
+                match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+                    Ok(s) => Some(s),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "failed to install SIGHUP handler");
+                        None
+                    }
+                };
+
+            let mut ticker = tokio::time::interval(POLL_INTERVAL);
+            let mut last_modified = self.modified();
+            loop {
+                #[cfg(unix)]
+                let sighup = async {
+                    match hangup.as_mut() {
+                        Some(s) => s.recv().await,
+                        None => std::future::pending().await,
+                    }
+                };
+                #[cfg(not(unix))]
+                let sighup = std::future::pending::<Option<()>>();
+
+                tokio::select! {
+                    _ = ticker.tick() => {
+                        let modified = self.modified();
+                        if modified == last_modified {
+                            continue;
+                        }
+                        last_modified = modified;
+                        tracing::info!("config files changed, reloading");
+                    }
+                    _ = sighup => tracing::info!("SIGHUP received, reloading config"),
+                }
+
+                if let Err(e) = self.reload() {
+                    tracing::warn!(error = %e, "config reload failed, keeping previous settings");
+                }
+            }
+        })
+    }
+
+    fn modified(&self) -> [Option<SystemTime>; 2] {
+        ["logger.yaml", "server.yaml"].map(|name| {
+            std::fs::metadata(self.config_dir.join(name))
+                .and_then(|m| m.modified())
+                .ok()
+        })
+    }
+
+    /// Parse both files before applying anything so a bad edit changes nothing.
+    fn reload(&mut self) -> anyhow::Result<()> {
+        let logger: LoggerConfig = config::load_config(&self.config_dir.join("logger.yaml"))?;
+        let server: ServerConfig = config::load_config(&self.config_dir.join("server.yaml"))?;
+        let level = logger.logger.level;
+        EnvFilter::try_new(&level)?;
+
+        if level != self.log_level {
+            self.log_filter.set_configured(&level)?;
+            self.log_level = level;
+        }
+
+        let rate_limit = server.server.rate_limit;
+        if self.rate_limit.send_if_modified(|current| {
+            let changed = *current != rate_limit;
+            *current = rate_limit.clone();
+            changed
+        }) {
+            tracing::info!(rate_limit = ?rate_limit, "rate limit reloaded");
+        }
+
+        let archive = server.server.archive.enabled;
+        if self
+            .archive_enabled
+            .send_if_modified(|current| std::mem::replace(current, archive) != archive)
+        {
+            tracing::info!(enabled = archive, "page archiving toggled");
+        }
+
+        let maintenance = server.server.maintenance;
+        if self
+            .maintenance
+            .send_if_modified(|current| std::mem::replace(current, maintenance) != maintenance)
+        {
+            tracing::info!(enabled = maintenance, "maintenance mode toggled");
+        }
+
+        Ok(())
+    }
+}
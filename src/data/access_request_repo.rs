@@ -0,0 +1,204 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::authz::relations::{Relation, ResourceType, SubjectType};
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_APPROVED: &str = "approved";
+pub const STATUS_DENIED: &str = "denied";
+
+/// A request for access to a bookmark.
+#[derive(Debug, Clone)]
+pub struct NewAccessRequest {
+    pub tenant_id: i32,
+    pub bookmark_id: String,
+    pub requester_id: String,
+    pub relation: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessRequestRow {
+    pub id: i32,
+    pub tenant_id: i32,
+    pub bookmark_id: String,
+    pub requester_id: String,
+    pub relation: String,
+    pub message: String,
+    pub status: String,
+    pub resolved_by: Option<String>,
+    pub resolution_note: String,
+    #[serde(skip)]
+    pub permission_id: Option<i32>,
+    pub create_time: DateTime<Utc>,
+    pub resolve_time: Option<DateTime<Utc>>,
+}
+
+/// Filters for [`AccessRequestStore::list`]; unset fields match everything.
+#[derive(Debug, Default)]
+pub struct AccessRequestFilter<'a> {
+    pub bookmark_id: Option<&'a str>,
+    pub requester_id: Option<&'a str>,
+    pub status: Option<&'a str>,
+    /// Only requests on bookmarks this user directly owns.
+    pub owned_by: Option<&'a str>,
+}
+
+#[tonic::async_trait]
+pub trait AccessRequestStore: Send + Sync {
+    /// Returns `None` if the requester already has a pending request for the bookmark.
+    async fn create(&self, request: &NewAccessRequest)
+        -> anyhow::Result<Option<AccessRequestRow>>;
+
+    async fn get(&self, tenant_id: i32, id: i32) -> anyhow::Result<Option<AccessRequestRow>>;
+
+    /// Move a pending request to `status`. Returns `None` if it is not pending.
+    async fn resolve(
+        &self,
+        tenant_id: i32,
+        id: i32,
+        status: &str,
+        resolved_by: &str,
+        note: &str,
+        permission_id: Option<i32>,
+    ) -> anyhow::Result<Option<AccessRequestRow>>;
+
+    /// Requests matching the filter, newest first, keyset-paged from `after`'s
+    /// `(create_time, id)`. Returns up to `page_size + 1` rows so callers can
+    /// tell whether another page exists.
+    async fn list(
+        &self,
+        tenant_id: i32,
+        filter: &AccessRequestFilter<'_>,
+        after: Option<(DateTime<Utc>, i32)>,
+        page_size: u32,
+    ) -> anyhow::Result<Vec<AccessRequestRow>>;
+}
+
+#[derive(Clone)]
+pub struct AccessRequestRepo {
+    pool: PgPool,
+}
+
+impl AccessRequestRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl AccessRequestStore for AccessRequestRepo {
+    async fn create(
+        &self,
+        r: &NewAccessRequest,
+    ) -> anyhow::Result<Option<AccessRequestRow>> {
+        let row = sqlx::query_as::<_, AccessRequestRow>(
+            r#"
+            INSERT INTO bookmark_access_requests
+                (tenant_id, bookmark_id, requester_id, relation, message)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (tenant_id, bookmark_id, requester_id) WHERE status = 'pending'
+            DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(r.tenant_id)
+        .bind(&r.bookmark_id)
+        .bind(&r.requester_id)
+        .bind(&r.relation)
+        .bind(&r.message)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn get(&self, tenant_id: i32, id: i32) -> anyhow::Result<Option<AccessRequestRow>> {
+        let row = sqlx::query_as::<_, AccessRequestRow>(
+            "SELECT * FROM bookmark_access_requests WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn resolve(
+        &self,
+        tenant_id: i32,
+        id: i32,
+        status: &str,
+        resolved_by: &str,
+        note: &str,
+        permission_id: Option<i32>,
+    ) -> anyhow::Result<Option<AccessRequestRow>> {
+        let row = sqlx::query_as::<_, AccessRequestRow>(
+            r#"
+            UPDATE bookmark_access_requests
+            SET status = $3, resolved_by = $4, resolution_note = $5, permission_id = $6,
+                resolve_time = NOW()
+            WHERE tenant_id = $1 AND id = $2 AND status = 'pending'
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .bind(status)
+        .bind(resolved_by)
+        .bind(note)
+        .bind(permission_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list(
+        &self,
+        tenant_id: i32,
+        f: &AccessRequestFilter<'_>,
+        after: Option<(DateTime<Utc>, i32)>,
+        page_size: u32,
+    ) -> anyhow::Result<Vec<AccessRequestRow>> {
+        let rows = sqlx::query_as::<_, AccessRequestRow>(
+            r#"
+            SELECT * FROM bookmark_access_requests r
+            WHERE r.tenant_id = $1
+              AND ($2::TEXT IS NULL OR r.bookmark_id = $2)
+              AND ($3::TEXT IS NULL OR r.requester_id = $3)
+              AND ($4::TEXT IS NULL OR r.status = $4)
+              AND ($5::TEXT IS NULL OR EXISTS (
+                  SELECT 1 FROM bookmark_permissions o
+                  WHERE o.tenant_id = r.tenant_id
+                    AND o.resource_type = $6
+                    AND o.resource_id = r.bookmark_id
+                    AND o.relation_code = $7
+                    AND o.subject_type = $8
+                    AND o.subject_id = $5
+              ))
+              AND ($9::TIMESTAMPTZ IS NULL OR (r.create_time, r.id) < ($9, $10))
+            ORDER BY r.create_time DESC, r.id DESC
+            LIMIT $11
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(f.bookmark_id)
+        .bind(f.requester_id)
+        .bind(f.status)
+        .bind(f.owned_by)
+        .bind(ResourceType::Bookmark.as_str())
+        .bind(Relation::Owner.code())
+        .bind(SubjectType::User.as_str())
+        .bind(after.map(|(t, _)| t))
+        .bind(after.map_or(0, |(_, id)| id))
+        .bind(page_size as i64 + 1)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
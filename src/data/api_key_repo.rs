@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ApiKeyRow {
+    pub id: Uuid,
+    pub tenant_id: i32,
+    pub name: String,
+    pub key_prefix: String,
+    pub key_hash: String,
+    pub roles: Vec<String>,
+    pub created_by: String,
+    pub create_time: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// API key storage; secrets are only ever stored hashed.
+#[tonic::async_trait]
+pub trait ApiKeyStore: Send + Sync {
+    #[allow(clippy::too_many_arguments)]
+    async fn create(
+        &self,
+        id: Uuid,
+        tenant_id: i32,
+        name: &str,
+        key_prefix: &str,
+        key_hash: &str,
+        roles: &[String],
+        created_by: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<ApiKeyRow>;
+
+    async fn list(&self, tenant_id: i32, include_revoked: bool) -> anyhow::Result<Vec<ApiKeyRow>>;
+
+    /// Mark a key revoked. Returns false if no active key with that ID exists in the tenant.
+    async fn revoke(&self, tenant_id: i32, id: Uuid) -> anyhow::Result<bool>;
+
+    /// Look up an unrevoked, unexpired key by the SHA-256 of its secret.
+    async fn find_active(&self, key_hash: &str) -> anyhow::Result<Option<ApiKeyRow>>;
+
+    async fn touch(&self, id: Uuid) -> anyhow::Result<()>;
+}
+
+#[derive(Clone)]
+pub struct ApiKeyRepo {
+    pool: PgPool,
+}
+
+impl ApiKeyRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl ApiKeyStore for ApiKeyRepo {
+    #[allow(clippy::too_many_arguments)]
+    async fn create(
+        &self,
+        id: Uuid,
+        tenant_id: i32,
+        name: &str,
+        key_prefix: &str,
+        key_hash: &str,
+        roles: &[String],
+        created_by: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<ApiKeyRow> {
+        let row = sqlx::query_as::<_, ApiKeyRow>(
+            r#"
+            INSERT INTO bookmark_api_keys
+                (id, tenant_id, name, key_prefix, key_hash, roles, created_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(tenant_id)
+        .bind(name)
+        .bind(key_prefix)
+        .bind(key_hash)
+        .bind(roles)
+        .bind(created_by)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list(&self, tenant_id: i32, include_revoked: bool) -> anyhow::Result<Vec<ApiKeyRow>> {
+        let rows = sqlx::query_as::<_, ApiKeyRow>(
+            r#"
+            SELECT * FROM bookmark_api_keys
+            WHERE tenant_id = $1 AND ($2 OR revoked_at IS NULL)
+            ORDER BY create_time DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(include_revoked)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn revoke(&self, tenant_id: i32, id: Uuid) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE bookmark_api_keys SET revoked_at = NOW()
+            WHERE tenant_id = $1 AND id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find_active(&self, key_hash: &str) -> anyhow::Result<Option<ApiKeyRow>> {
+        let row = sqlx::query_as::<_, ApiKeyRow>(
+            r#"
+            SELECT * FROM bookmark_api_keys
+            WHERE key_hash = $1
+              AND revoked_at IS NULL
+              AND (expires_at IS NULL OR expires_at > NOW())
+            "#,
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn touch(&self, id: Uuid) -> anyhow::Result<()> {
+        sqlx::query("UPDATE bookmark_api_keys SET last_used_at = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
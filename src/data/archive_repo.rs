@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ArchiveRow {
+    pub bookmark_id: Uuid,
+    pub tenant_id: i32,
+    pub url: String,
+    /// `text` or `html`.
+    pub format: String,
+    pub content: String,
+    pub content_bytes: i32,
+    pub truncated: bool,
+    pub archive_time: DateTime<Utc>,
+}
+
+/// Column list for [`ArchiveRow`]; the search vector is never read back.
+const ARCHIVE_COLUMNS: &str =
+    "bookmark_id, tenant_id, url, format, content, content_bytes, truncated, archive_time";
+
+/// Page snapshot storage.
+#[tonic::async_trait]
+pub trait ArchiveStore: Send + Sync {
+    async fn get(&self, tenant_id: i32, bookmark_id: Uuid) -> anyhow::Result<Option<ArchiveRow>>;
+
+    /// Store the latest snapshot of a bookmark, replacing any previous one.
+    /// `search_text` is the readable text indexed for full-text search.
+    #[allow(clippy::too_many_arguments)]
+    async fn upsert(
+        &self,
+        bookmark_id: Uuid,
+        tenant_id: i32,
+        url: &str,
+        format: &str,
+        content: &str,
+        search_text: &str,
+        truncated: bool,
+    ) -> anyhow::Result<()>;
+
+    /// Drop a bookmark's snapshot. Returns false if it had none.
+    async fn delete(&self, tenant_id: i32, bookmark_id: Uuid) -> anyhow::Result<bool>;
+}
+
+#[derive(Clone)]
+pub struct ArchiveRepo {
+    pool: PgPool,
+}
+
+impl ArchiveRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl ArchiveStore for ArchiveRepo {
+    async fn get(&self, tenant_id: i32, bookmark_id: Uuid) -> anyhow::Result<Option<ArchiveRow>> {
+        let row = sqlx::query_as::<_, ArchiveRow>(&format!(
+            "SELECT {ARCHIVE_COLUMNS} FROM bookmark_archives WHERE bookmark_id = $1 AND tenant_id = $2"
+        ))
+        .bind(bookmark_id)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn upsert(
+        &self,
+        bookmark_id: Uuid,
+        tenant_id: i32,
+        url: &str,
+        format: &str,
+        content: &str,
+        search_text: &str,
+        truncated: bool,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO bookmark_archives
+                (bookmark_id, tenant_id, url, format, content, content_bytes, truncated, content_tsv)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, to_tsvector('simple', $8))
+            ON CONFLICT (bookmark_id) DO UPDATE SET
+                url = EXCLUDED.url,
+                format = EXCLUDED.format,
+                content = EXCLUDED.content,
+                content_bytes = EXCLUDED.content_bytes,
+                truncated = EXCLUDED.truncated,
+                content_tsv = EXCLUDED.content_tsv,
+                archive_time = NOW()
+            "#,
+        )
+        .bind(bookmark_id)
+        .bind(tenant_id)
+        .bind(url)
+        .bind(format)
+        .bind(content)
+        .bind(content.len() as i32)
+        .bind(truncated)
+        .bind(search_text)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, tenant_id: i32, bookmark_id: Uuid) -> anyhow::Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM bookmark_archives WHERE bookmark_id = $1 AND tenant_id = $2")
+                .bind(bookmark_id)
+                .bind(tenant_id)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct AuditEventRow {
+    pub id: i64,
+    pub tenant_id: i32,
+    pub user_id: String,
+    pub operation: String,
+    pub create_time: DateTime<Utc>,
+    /// mTLS client certificate name; empty when the request had none.
+    pub client: String,
+    /// gRPC status code; `None` for events recorded before it was captured.
+    pub status_code: Option<i32>,
+    pub duration_ms: Option<i32>,
+}
+
+/// An audit event waiting to be written.
+#[derive(Debug)]
+pub struct NewAuditEvent {
+    pub tenant_id: i32,
+    pub user_id: String,
+    pub operation: String,
+    pub create_time: DateTime<Utc>,
+    pub client: String,
+    pub status_code: i32,
+    pub duration_ms: i32,
+}
+
+/// Audit event storage.
+#[tonic::async_trait]
+pub trait AuditStore: Send + Sync {
+    async fn insert_batch(&self, events: &[NewAuditEvent]) -> anyhow::Result<u64>;
+
+    /// Events in id order after `after_id`. `tenant_id` of `None` covers every tenant.
+    async fn list_page(
+        &self,
+        tenant_id: Option<i32>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        after_id: i64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<AuditEventRow>>;
+
+    /// Delete events older than `cutoff`, limited to ids up to `max_id` when given.
+    async fn delete_older_than(
+        &self,
+        cutoff: DateTime<Utc>,
+        max_id: Option<i64>,
+    ) -> anyhow::Result<u64>;
+}
+
+#[derive(Clone)]
+pub struct AuditRepo {
+    pool: PgPool,
+}
+
+impl AuditRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl AuditStore for AuditRepo {
+    async fn insert_batch(&self, events: &[NewAuditEvent]) -> anyhow::Result<u64> {
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        let tenant_ids: Vec<i32> = events.iter().map(|e| e.tenant_id).collect();
+        let user_ids: Vec<&str> = events.iter().map(|e| e.user_id.as_str()).collect();
+        let operations: Vec<&str> = events.iter().map(|e| e.operation.as_str()).collect();
+        let times: Vec<DateTime<Utc>> = events.iter().map(|e| e.create_time).collect();
+        let clients: Vec<&str> = events.iter().map(|e| e.client.as_str()).collect();
+        let codes: Vec<i32> = events.iter().map(|e| e.status_code).collect();
+        let durations: Vec<i32> = events.iter().map(|e| e.duration_ms).collect();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO bookmark_audit_events
+                (tenant_id, user_id, operation, create_time, client, status_code, duration_ms)
+            SELECT * FROM UNNEST(
+                $1::INTEGER[], $2::VARCHAR[], $3::VARCHAR[], $4::TIMESTAMPTZ[], $5::VARCHAR[],
+                $6::INTEGER[], $7::INTEGER[]
+            )
+            "#,
+        )
+        .bind(&tenant_ids)
+        .bind(&user_ids)
+        .bind(&operations)
+        .bind(&times)
+        .bind(&clients)
+        .bind(&codes)
+        .bind(&durations)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn list_page(
+        &self,
+        tenant_id: Option<i32>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        after_id: i64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<AuditEventRow>> {
+        let rows = sqlx::query_as::<_, AuditEventRow>(
+            r#"
+            SELECT * FROM bookmark_audit_events
+            WHERE id > $1
+              AND ($2::INTEGER IS NULL OR tenant_id = $2)
+              AND ($3::TIMESTAMPTZ IS NULL OR create_time >= $3)
+              AND ($4::TIMESTAMPTZ IS NULL OR create_time < $4)
+            ORDER BY id
+            LIMIT $5
+            "#,
+        )
+        .bind(after_id)
+        .bind(tenant_id)
+        .bind(since)
+        .bind(until)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn delete_older_than(
+        &self,
+        cutoff: DateTime<Utc>,
+        max_id: Option<i64>,
+    ) -> anyhow::Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM bookmark_audit_events
+            WHERE create_time < $1 AND ($2::BIGINT IS NULL OR id <= $2)
+            "#,
+        )
+        .bind(cutoff)
+        .bind(max_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,145 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AutoTagRuleRow {
+    pub id: i32,
+    pub tenant_id: i32,
+    pub name: String,
+    pub domain: String,
+    pub path_pattern: String,
+    pub title_pattern: String,
+    pub tags: Vec<String>,
+    pub enabled: bool,
+    pub created_by: String,
+    pub create_time: DateTime<Utc>,
+    pub update_time: DateTime<Utc>,
+}
+
+/// Everything about a rule except its name, which is fixed at creation.
+#[derive(Debug)]
+pub struct AutoTagRuleFields {
+    pub domain: String,
+    pub path_pattern: String,
+    pub title_pattern: String,
+    pub tags: Vec<String>,
+    pub enabled: bool,
+}
+
+#[tonic::async_trait]
+pub trait AutoTagRuleStore: Send + Sync {
+    /// Returns `None` if the tenant already has a rule with that name.
+    async fn create(
+        &self,
+        tenant_id: i32,
+        name: &str,
+        fields: &AutoTagRuleFields,
+        created_by: &str,
+    ) -> anyhow::Result<Option<AutoTagRuleRow>>;
+
+    async fn list(&self, tenant_id: i32) -> anyhow::Result<Vec<AutoTagRuleRow>>;
+
+    async fn update(
+        &self,
+        tenant_id: i32,
+        id: i32,
+        fields: &AutoTagRuleFields,
+    ) -> anyhow::Result<Option<AutoTagRuleRow>>;
+
+    /// Returns false if the tenant has no rule with that ID.
+    async fn delete(&self, tenant_id: i32, id: i32) -> anyhow::Result<bool>;
+}
+
+#[derive(Clone)]
+pub struct AutoTagRuleRepo {
+    pool: PgPool,
+}
+
+impl AutoTagRuleRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl AutoTagRuleStore for AutoTagRuleRepo {
+    async fn create(
+        &self,
+        tenant_id: i32,
+        name: &str,
+        fields: &AutoTagRuleFields,
+        created_by: &str,
+    ) -> anyhow::Result<Option<AutoTagRuleRow>> {
+        let row = sqlx::query_as::<_, AutoTagRuleRow>(
+            r#"
+            INSERT INTO bookmark_auto_tag_rules
+                (tenant_id, name, domain, path_pattern, title_pattern, tags, enabled, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (tenant_id, name) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .bind(&fields.domain)
+        .bind(&fields.path_pattern)
+        .bind(&fields.title_pattern)
+        .bind(&fields.tags)
+        .bind(fields.enabled)
+        .bind(created_by)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list(&self, tenant_id: i32) -> anyhow::Result<Vec<AutoTagRuleRow>> {
+        let rows = sqlx::query_as::<_, AutoTagRuleRow>(
+            "SELECT * FROM bookmark_auto_tag_rules WHERE tenant_id = $1 ORDER BY name",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn update(
+        &self,
+        tenant_id: i32,
+        id: i32,
+        fields: &AutoTagRuleFields,
+    ) -> anyhow::Result<Option<AutoTagRuleRow>> {
+        let row = sqlx::query_as::<_, AutoTagRuleRow>(
+            r#"
+            UPDATE bookmark_auto_tag_rules
+            SET domain = $3, path_pattern = $4, title_pattern = $5, tags = $6, enabled = $7,
+                update_time = NOW()
+            WHERE tenant_id = $1 AND id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .bind(&fields.domain)
+        .bind(&fields.path_pattern)
+        .bind(&fields.title_pattern)
+        .bind(&fields.tags)
+        .bind(fields.enabled)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn delete(&self, tenant_id: i32, id: i32) -> anyhow::Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM bookmark_auto_tag_rules WHERE tenant_id = $1 AND id = $2")
+                .bind(tenant_id)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
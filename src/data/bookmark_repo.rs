@@ -1,8 +1,20 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use sqlx::types::Json;
+use sqlx::{Connection, PgConnection, PgPool};
 use uuid::Uuid;
 
-#[derive(Debug, sqlx::FromRow)]
+use crate::authz::relations::{
+    Permission, Relation, ResourceType, SubjectType, WILDCARD_RESOURCE,
+};
+use crate::data::filter_expr::{and_clause, FilterExpr, FilterParam, Predicate};
+use crate::data::replica::ReadPool;
+use crate::data::user_flag_repo::ReadingStatus;
+use crate::service::bookmark_kind::classify_url;
+use crate::service::url_normalizer::normalize_url;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
 pub struct BookmarkRow {
     pub id: Uuid,
     pub tenant_id: i32,
@@ -13,58 +25,640 @@ pub struct BookmarkRow {
     pub created_by: Option<i32>,
     pub create_time: DateTime<Utc>,
     pub update_time: DateTime<Utc>,
+    pub original_url: Option<String>,
+    /// Canonical URL for duplicate detection; `None` until backfilled.
+    pub normalized_url: Option<String>,
+    pub visit_count: i32,
+    pub last_visited_at: Option<DateTime<Utc>>,
+    /// Free-form markdown, edited separately from the other fields.
+    pub notes: String,
+    /// Integrator-defined key/value pairs.
+    pub metadata: Json<HashMap<String, String>>,
+    /// A [`BookmarkKind`] name; `None` until backfilled.
+    pub kind: Option<String>,
+    /// `url`, `original_url` and `notes` hold sealed values; see
+    /// [`FieldCipher`](crate::service::field_cipher::FieldCipher).
+    pub is_private: bool,
+}
+
+/// What a bookmarked URL points at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BookmarkKind {
+    Article,
+    Video,
+    Pdf,
+    Repo,
+    Image,
+    #[default]
+    Other,
+}
+
+impl BookmarkKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Article => "BOOKMARK_KIND_ARTICLE",
+            Self::Video => "BOOKMARK_KIND_VIDEO",
+            Self::Pdf => "BOOKMARK_KIND_PDF",
+            Self::Repo => "BOOKMARK_KIND_REPO",
+            Self::Image => "BOOKMARK_KIND_IMAGE",
+            Self::Other => "BOOKMARK_KIND_OTHER",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "BOOKMARK_KIND_ARTICLE" => Some(Self::Article),
+            "BOOKMARK_KIND_VIDEO" => Some(Self::Video),
+            "BOOKMARK_KIND_PDF" => Some(Self::Pdf),
+            "BOOKMARK_KIND_REPO" => Some(Self::Repo),
+            "BOOKMARK_KIND_IMAGE" => Some(Self::Image),
+            "BOOKMARK_KIND_OTHER" => Some(Self::Other),
+            _ => None,
+        }
+    }
+
+    pub fn from_proto(v: i32) -> Option<Self> {
+        match v {
+            1 => Some(Self::Article),
+            2 => Some(Self::Video),
+            3 => Some(Self::Pdf),
+            4 => Some(Self::Repo),
+            5 => Some(Self::Image),
+            6 => Some(Self::Other),
+            _ => None,
+        }
+    }
+
+    pub fn to_proto(self) -> i32 {
+        match self {
+            Self::Article => 1,
+            Self::Video => 2,
+            Self::Pdf => 3,
+            Self::Repo => 4,
+            Self::Image => 5,
+            Self::Other => 6,
+        }
+    }
+}
+
+/// Snapshot of a bookmark as it was before an update.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RevisionRow {
+    pub id: i64,
+    pub bookmark_id: Uuid,
+    pub tenant_id: i32,
+    pub url: String,
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub original_url: Option<String>,
+    pub edited_by: Option<i32>,
+    pub create_time: DateTime<Utc>,
+    /// Whether the URLs were sealed when the revision was recorded.
+    pub is_private: bool,
+}
+
+/// Optional restrictions applied when listing bookmarks.
+#[derive(Debug, Default)]
+pub struct BookmarkFilter {
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+    /// The caller whose per-user flags `pinned_only` and `reading_status` refer to.
+    pub user_id: String,
+    /// Only bookmarks the caller has pinned.
+    pub pinned_only: bool,
+    /// Only bookmarks the caller has in this reading status.
+    pub reading_status: Option<ReadingStatus>,
+    /// Only bookmarks whose metadata contains all of these pairs.
+    pub metadata: Option<HashMap<String, String>>,
+    pub kind: Option<BookmarkKind>,
+    /// Order by the caller's manual pin order instead of newest first.
+    /// Cursors are ignored; callers use this with `pinned_only`.
+    pub manual_order: bool,
+    /// `list_accessible` also returns bookmarks other tenants shared with
+    /// `user_id` through federated tuples.
+    pub federated: bool,
+    /// Only bookmarks with a tag matching this filter; see [`tag_matches`].
+    pub tag: Option<String>,
+    /// Only bookmarks matching this expression. Applied by `list_accessible`;
+    /// `list_by_ids` callers already name the bookmarks they want.
+    pub expr: Option<FilterExpr>,
+}
+
+/// A bookmark matched by full-text search.
+#[derive(Debug, sqlx::FromRow)]
+pub struct SearchRow {
+    #[sqlx(flatten)]
+    pub bookmark: BookmarkRow,
+    /// The match came from the archived page rather than the bookmark's own fields.
+    pub matched_content: bool,
+    /// Only trigram similarity matched, not the words of the query.
+    pub fuzzy_match: bool,
+    /// Highlighted excerpt of the archived text, for content matches.
+    pub snippet: Option<String>,
+    pub total_count: i64,
+}
+
+/// Usage of one tag across a set of bookmarks.
+#[derive(Debug, sqlx::FromRow)]
+pub struct TagStatRow {
+    pub tag: String,
+    pub bookmark_count: i64,
+    /// Oldest `create_time` among the tagged bookmarks.
+    pub first_used: DateTime<Utc>,
+    /// Newest `update_time` among the tagged bookmarks.
+    pub last_used: DateTime<Utc>,
+    /// Distinct tags before the limit was applied.
+    pub total_count: i64,
+}
+
+/// A tag path and its usage, for [`BookmarkStore::tag_tree`].
+#[derive(Debug, sqlx::FromRow)]
+pub struct TagPathRow {
+    pub path: String,
+    /// Bookmarks tagged with exactly `path`.
+    pub bookmark_count: i64,
+    /// Bookmarks tagged with `path` or any tag below it.
+    pub subtree_count: i64,
+}
+
+/// Separates the segments of a hierarchical tag such as `lang/rust`.
+pub const TAG_SEPARATOR: char = '/';
+
+/// Whether `tag` matches a tag filter. A filter ending in the separator, such
+/// as `lang/`, matches the namespace `lang` and every tag below it; any other
+/// filter matches only the identical tag.
+pub fn tag_matches(tag: &str, filter: &str) -> bool {
+    match filter.strip_suffix(TAG_SEPARATOR) {
+        Some(namespace) => tag
+            .strip_prefix(namespace)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(TAG_SEPARATOR)),
+        None => tag == filter,
+    }
+}
+
+/// `tag` with each `delimiter` turned into the separator and blank segments
+/// dropped, so `lang: rust` split on `:` becomes `lang/rust`. A tag that would
+/// be left empty is returned unchanged.
+pub fn split_tag(tag: &str, delimiter: &str) -> String {
+    let path = tag
+        .split(delimiter)
+        .flat_map(|part| part.split(TAG_SEPARATOR))
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    if path.is_empty() {
+        tag.to_string()
+    } else {
+        path
+    }
+}
+
+/// `tags` split on `delimiter`, keeping the first of any duplicates this
+/// creates. `None` when nothing changes.
+pub(crate) fn split_tags(tags: &[String], delimiter: &str) -> Option<Vec<String>> {
+    let mut split: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = if tag.contains(delimiter) {
+            split_tag(tag, delimiter)
+        } else {
+            tag.clone()
+        };
+        if !split.contains(&tag) {
+            split.push(tag);
+        }
+    }
+    (split != tags).then_some(split)
+}
+
+/// Postgres SQL for one filter-expression predicate over the bookmark row
+/// `table`, with its value bound to `param`.
+fn filter_predicate(table: &str, predicate: &Predicate, param: &str) -> String {
+    match predicate {
+        Predicate::Tag(_) => format!(
+            "EXISTS (SELECT 1 FROM UNNEST({table}.tags) AS t(tag) \
+             WHERE t.tag = {param} OR (RIGHT({param}, 1) = '/' AND STARTS_WITH(t.tag || '/', {param})))"
+        ),
+        Predicate::Domain(_) => format!(
+            "COALESCE((SELECT h = {param} OR RIGHT(h, LENGTH({param}) + 1) = '.' || {param} \
+             FROM SUBSTRING({table}.normalized_url FROM '^[^/?:]*') AS h), FALSE)"
+        ),
+        Predicate::Kind(_) => format!("COALESCE({table}.kind = {param}, FALSE)"),
+        Predicate::Title(_) => format!("STRPOS(LOWER({table}.title), LOWER({param})) > 0"),
+        Predicate::Time(field, cmp, _) => {
+            format!("{table}.{} {} {param}", field.column(), cmp.as_sql())
+        }
+    }
+}
+
+/// Sort order for [`BookmarkStore::tag_stats`]; ties break by tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagOrder {
+    MostUsed,
+    LeastUsed,
+    MostRecent,
+    LeastRecent,
+}
+
+impl TagOrder {
+    pub(crate) fn order_by(self) -> &'static str {
+        match self {
+            Self::MostUsed => "bookmark_count DESC, tag",
+            Self::LeastUsed => "bookmark_count ASC, tag",
+            Self::MostRecent => "last_used DESC, tag",
+            Self::LeastRecent => "last_used ASC, tag",
+        }
+    }
+}
+
+/// Selects the bookmarks changed by [`BookmarkStore::update_tags_bulk`];
+/// unset fields match everything.
+#[derive(Debug, Default)]
+pub struct BulkTagFilter {
+    /// Bookmarks with a tag matching this filter; see [`tag_matches`].
+    pub tag: Option<String>,
+    /// Lower-case host without `www.`; matches the host and its subdomains.
+    pub domain: Option<String>,
+    /// Search query over title, description and tags, as in `search`.
+    pub query: Option<String>,
+}
+
+/// Input for one item of a batch create.
+#[derive(Debug)]
+pub struct NewBookmark {
+    /// Chosen up front so private values can be sealed for the bookmark.
+    pub id: Uuid,
+    pub url: String,
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub original_url: Option<String>,
+    pub kind: BookmarkKind,
+    /// `url` and `original_url` are sealed values.
+    pub is_private: bool,
+}
+
+/// Input for a bookmark update; `None` fields are left unchanged.
+#[derive(Debug)]
+pub struct BookmarkChanges {
+    pub id: Uuid,
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub original_url: Option<String>,
+    pub metadata: Option<HashMap<String, String>>,
+    /// Reclassified kind; set along with `url`.
+    pub kind: Option<BookmarkKind>,
+    pub notes: Option<String>,
+    /// Set along with `url`: a private URL is stored without a normalized form.
+    pub is_private: Option<bool>,
+}
+
+impl BookmarkChanges {
+    /// `column = $n` assignments for the present fields, numbered from
+    /// `first_param`. Bind values in the same order: url (with original and
+    /// normalized url), title, description, tags, metadata, kind, notes,
+    /// is_private.
+    pub(crate) fn set_clause(&self, first_param: u32) -> String {
+        let url = self.url.is_some();
+        let columns = [
+            ("url", url),
+            ("original_url", url),
+            ("normalized_url", url),
+            ("title", self.title.is_some()),
+            ("description", self.description.is_some()),
+            ("tags", self.tags.is_some()),
+            ("metadata", self.metadata.is_some()),
+            ("kind", self.kind.is_some()),
+            ("notes", self.notes.is_some()),
+            ("is_private", self.is_private.is_some()),
+        ];
+        let mut param_idx = first_param;
+        let mut sets = Vec::new();
+        for (column, present) in columns {
+            if present {
+                sets.push(format!("{column} = ${param_idx}"));
+                param_idx += 1;
+            }
+        }
+        // Keeps the clause valid when nothing changes; the update still bumps update_time.
+        if sets.is_empty() {
+            sets.push("id = id".to_string());
+        }
+        sets.join(", ")
+    }
+
+    /// Value stored in `normalized_url` along with `url`.
+    pub(crate) fn normalized_url(&self) -> Option<String> {
+        let url = self.url.as_deref()?;
+        (!self.is_private.unwrap_or(false)).then(|| normalize_url(url))
+    }
+}
+
+/// Bookmark and revision storage, implemented for each supported database.
+#[tonic::async_trait]
+pub trait BookmarkStore: Send + Sync {
+    /// With `is_private` set, `url` and `original_url` are sealed values and no
+    /// normalized URL is stored.
+    #[allow(clippy::too_many_arguments)]
+    async fn create(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+        url: &str,
+        title: &str,
+        description: &str,
+        tags: &[String],
+        created_by: Option<i32>,
+        original_url: Option<&str>,
+        kind: BookmarkKind,
+        is_private: bool,
+    ) -> anyhow::Result<BookmarkRow>;
+
+    async fn get_by_id(&self, tenant_id: i32, id: Uuid) -> anyhow::Result<Option<BookmarkRow>>;
+
+    async fn list_by_tenant(
+        &self,
+        tenant_id: i32,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<BookmarkRow>, i64)>;
+
+    /// List the given bookmarks, newest first. With `after` set, pages by keyset
+    /// from that `(create_time, id)` and ignores `page`. Returns up to
+    /// `page_size + 1` rows so callers can tell whether another page exists;
+    /// `total` counts every match regardless of position.
+    #[allow(clippy::too_many_arguments)]
+    async fn list_by_ids(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+        filter: &BookmarkFilter,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<BookmarkRow>, i64)>;
+
+    /// Like [`BookmarkStore::list_by_ids`], but over the bookmarks on which any of
+    /// `subjects` holds a tuple, directly or through a wildcard tuple. Access is
+    /// resolved in SQL, so callers need not load every accessible ID first.
+    #[allow(clippy::too_many_arguments)]
+    async fn list_accessible(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        filter: &BookmarkFilter,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<BookmarkRow>, i64)>;
+
+    /// Update a bookmark. `original_url` is only applied when `url` changes, so a
+    /// new URL that needed no scrubbing clears the previously preserved original.
+    /// The previous state is recorded in `bookmark_revisions` in the same transaction.
+    async fn update(
+        &self,
+        tenant_id: i32,
+        changes: &BookmarkChanges,
+        edited_by: Option<i32>,
+    ) -> anyhow::Result<Option<BookmarkRow>>;
+
+    /// Create bookmarks in one transaction, each under its own savepoint so a
+    /// failing item does not abort the rest. OWNER tuples for every created
+    /// bookmark are inserted with a single statement.
+    async fn batch_create(
+        &self,
+        tenant_id: i32,
+        owner_id: &str,
+        created_by: Option<i32>,
+        items: &[NewBookmark],
+    ) -> anyhow::Result<Vec<anyhow::Result<BookmarkRow>>>;
+
+    /// Update bookmarks in one transaction with per-item savepoints. `Ok(None)`
+    /// marks an item whose bookmark does not exist.
+    async fn batch_update(
+        &self,
+        tenant_id: i32,
+        items: &[BookmarkChanges],
+        edited_by: Option<i32>,
+    ) -> anyhow::Result<Vec<anyhow::Result<Option<BookmarkRow>>>>;
+
+    /// Delete bookmarks and their permission tuples in one transaction with
+    /// per-item savepoints. `Ok(false)` marks an item that did not exist.
+    async fn batch_delete(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+    ) -> anyhow::Result<Vec<anyhow::Result<bool>>>;
+
+    /// Add `add` to and drop `remove` from the tags of every bookmark matching
+    /// `filter` on which any of `subjects` holds an unexpired tuple granting
+    /// write access, in one statement. Only rows whose tags change are touched,
+    /// each with a revision. Returns the updated rows.
+    #[allow(clippy::too_many_arguments)]
+    async fn update_tags_bulk(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        filter: &BulkTagFilter,
+        add: &[String],
+        remove: &[String],
+        edited_by: Option<i32>,
+    ) -> anyhow::Result<Vec<BookmarkRow>>;
+
+    /// Full-text search over title, description and tags of the given bookmarks,
+    /// and over their archived page text when `search_content` is set. With
+    /// `fuzzy_threshold`, titles and hosts whose trigram word similarity to the
+    /// query reaches it also match; they sort after every full-text match,
+    /// ordered by similarity.
+    /// Hits are limited to bookmarks matching `expr` when set.
+    #[allow(clippy::too_many_arguments)]
+    async fn search(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+        query: &str,
+        search_content: bool,
+        fuzzy_threshold: Option<f32>,
+        expr: Option<&FilterExpr>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<SearchRow>, i64)>;
+
+    /// Replace a bookmark's notes.
+    async fn update_notes(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+        notes: &str,
+    ) -> anyhow::Result<Option<BookmarkRow>>;
+
+    /// Count a visit with an atomic increment. Does not touch `update_time`.
+    async fn record_visit(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+    ) -> anyhow::Result<Option<(i32, DateTime<Utc>)>>;
+
+    /// The most visited of the given bookmarks.
+    async fn list_most_visited(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+        limit: u32,
+    ) -> anyhow::Result<Vec<BookmarkRow>>;
+
+    /// Per-tag counts over the bookmarks on which any of `subjects` holds a
+    /// tuple, resolved in SQL as in [`BookmarkStore::list_accessible`]. Only
+    /// tags starting with `prefix` (case-insensitive) are counted.
+    async fn tag_stats(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        prefix: &str,
+        order: TagOrder,
+        limit: u32,
+    ) -> anyhow::Result<Vec<TagStatRow>>;
+
+    /// Every tag path over the bookmarks on which any of `subjects` holds a
+    /// tuple, including the namespaces above each tag, ordered by path. With a
+    /// non-empty `root`, only `root` and the paths below it.
+    async fn tag_tree(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        root: &str,
+    ) -> anyhow::Result<Vec<TagPathRow>>;
+
+    /// Rewrite tags containing `delimiter` into tag paths with [`split_tag`],
+    /// across one tenant or all of them. Each changed bookmark gets a
+    /// revision; nothing is written when `dry_run` is set. Returns the number
+    /// of bookmarks changed, or that would change.
+    async fn split_tags(
+        &self,
+        tenant_id: Option<i32>,
+        delimiter: &str,
+        dry_run: bool,
+    ) -> anyhow::Result<u64>;
+
+    /// Bookmarks in the tenant whose normalized URL matches.
+    async fn find_by_normalized_url(
+        &self,
+        tenant_id: i32,
+        normalized_url: &str,
+    ) -> anyhow::Result<Vec<BookmarkRow>>;
+
+    /// Bookmarks among `ids` that share a normalized URL with at least one other
+    /// bookmark in the same set, ordered so duplicates are adjacent.
+    async fn find_duplicate_groups(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+    ) -> anyhow::Result<Vec<BookmarkRow>>;
+
+    /// Fill `normalized_url` for rows created before the column existed.
+    /// Returns the number of rows updated.
+    async fn backfill_normalized_urls(&self) -> anyhow::Result<u64>;
+
+    /// Classify rows created before `kind` existed from their URL alone.
+    /// Returns the number of rows updated.
+    async fn backfill_kinds(&self) -> anyhow::Result<u64>;
+
+    async fn list_revisions(
+        &self,
+        tenant_id: i32,
+        bookmark_id: Uuid,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<RevisionRow>, i64)>;
+
+    async fn get_revision(
+        &self,
+        tenant_id: i32,
+        bookmark_id: Uuid,
+        revision_id: i64,
+    ) -> anyhow::Result<Option<RevisionRow>>;
+
+    async fn delete(&self, tenant_id: i32, id: Uuid) -> anyhow::Result<bool>;
 }
 
 #[derive(Clone)]
 pub struct BookmarkRepo {
     pool: PgPool,
+    reads: ReadPool,
 }
 
 impl BookmarkRepo {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, reads: ReadPool) -> Self {
+        Self { pool, reads }
     }
+}
 
-    pub async fn create(
+#[tonic::async_trait]
+impl BookmarkStore for BookmarkRepo {
+    #[allow(clippy::too_many_arguments)]
+    async fn create(
         &self,
         tenant_id: i32,
+        id: Uuid,
         url: &str,
         title: &str,
         description: &str,
         tags: &[String],
         created_by: Option<i32>,
+        original_url: Option<&str>,
+        kind: BookmarkKind,
+        is_private: bool,
     ) -> anyhow::Result<BookmarkRow> {
-        let row = sqlx::query_as::<_, BookmarkRow>(
+        let row = sqlx::query_as!(
+            BookmarkRow,
             r#"
-            INSERT INTO bookmark_bookmarks (tenant_id, url, title, description, tags, created_by)
-            VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING *
+            INSERT INTO bookmark_bookmarks (id, tenant_id, url, title, description, tags, created_by, original_url, normalized_url, kind, is_private)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING id, tenant_id, url, title, description, tags, created_by,
+                      create_time, update_time, original_url, normalized_url, visit_count,
+                      last_visited_at, notes, metadata AS "metadata: _", kind, is_private
             "#,
+            id,
+            tenant_id,
+            url,
+            title,
+            description,
+            tags,
+            created_by,
+            original_url,
+            (!is_private).then(|| normalize_url(url)),
+            kind.as_str(),
+            is_private,
         )
-        .bind(tenant_id)
-        .bind(url)
-        .bind(title)
-        .bind(description)
-        .bind(tags)
-        .bind(created_by)
         .fetch_one(&self.pool)
         .await?;
 
         Ok(row)
     }
 
-    pub async fn get_by_id(&self, id: Uuid) -> anyhow::Result<Option<BookmarkRow>> {
-        let row = sqlx::query_as::<_, BookmarkRow>(
-            "SELECT * FROM bookmark_bookmarks WHERE id = $1",
+    async fn get_by_id(&self, tenant_id: i32, id: Uuid) -> anyhow::Result<Option<BookmarkRow>> {
+        let row = sqlx::query_as!(
+            BookmarkRow,
+            r#"
+            SELECT id, tenant_id, url, title, description, tags, created_by,
+                   create_time, update_time, original_url, normalized_url, visit_count,
+                   last_visited_at, notes, metadata AS "metadata: _", kind, is_private
+            FROM bookmark_bookmarks WHERE id = $1 AND tenant_id = $2
+            "#,
+            id,
+            tenant_id,
         )
-        .bind(id)
         .fetch_optional(&self.pool)
         .await?;
 
         Ok(row)
     }
 
-    pub async fn list_by_tenant(
+    async fn list_by_tenant(
         &self,
         tenant_id: i32,
         page: u32,
@@ -72,34 +666,41 @@ impl BookmarkRepo {
     ) -> anyhow::Result<(Vec<BookmarkRow>, i64)> {
         let offset = (page.saturating_sub(1)) * page_size;
 
-        let total: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM bookmark_bookmarks WHERE tenant_id = $1",
+        let total = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM bookmark_bookmarks WHERE tenant_id = $1"#,
+            tenant_id,
         )
-        .bind(tenant_id)
         .fetch_one(&self.pool)
         .await?;
 
-        let rows = sqlx::query_as::<_, BookmarkRow>(
+        let rows = sqlx::query_as!(
+            BookmarkRow,
             r#"
-            SELECT * FROM bookmark_bookmarks
+            SELECT id, tenant_id, url, title, description, tags, created_by,
+                   create_time, update_time, original_url, normalized_url, visit_count,
+                   last_visited_at, notes, metadata AS "metadata: _", kind, is_private
+            FROM bookmark_bookmarks
             WHERE tenant_id = $1
             ORDER BY create_time DESC
             LIMIT $2 OFFSET $3
             "#,
+            tenant_id,
+            page_size as i64,
+            offset as i64,
         )
-        .bind(tenant_id)
-        .bind(page_size as i64)
-        .bind(offset as i64)
         .fetch_all(&self.pool)
         .await?;
 
-        Ok((rows, total.0))
+        Ok((rows, total))
     }
 
-    pub async fn list_by_ids(
+    #[allow(clippy::too_many_arguments)]
+    async fn list_by_ids(
         &self,
         tenant_id: i32,
         ids: &[Uuid],
+        filter: &BookmarkFilter,
+        after: Option<(DateTime<Utc>, Uuid)>,
         page: u32,
         page_size: u32,
     ) -> anyhow::Result<(Vec<BookmarkRow>, i64)> {
@@ -107,71 +708,1112 @@ impl BookmarkRepo {
             return Ok((vec![], 0));
         }
 
-        let offset = (page.saturating_sub(1)) * page_size;
+        let offset = if after.is_some() {
+            0
+        } else {
+            (page.saturating_sub(1)) * page_size
+        };
+        let (after_time, after_id) = after.unzip();
 
-        let total: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM bookmark_bookmarks WHERE tenant_id = $1 AND id = ANY($2)",
+        let mut conn = self.reads.acquire_cancellable().await?;
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!" FROM bookmark_bookmarks
+            WHERE tenant_id = $1 AND id = ANY($2)
+              AND ($3::TIMESTAMPTZ IS NULL OR create_time >= $3)
+              AND ($4::TIMESTAMPTZ IS NULL OR create_time < $4)
+              AND ($5::INTEGER IS NULL OR created_by = $5)
+              AND (NOT $7 OR EXISTS (
+                  SELECT 1 FROM bookmark_user_flags f
+                  WHERE f.bookmark_id = bookmark_bookmarks.id AND f.user_id = $6 AND f.is_pinned))
+              AND ($8::TEXT IS NULL OR COALESCE((
+                  SELECT f.reading_status FROM bookmark_user_flags f
+                  WHERE f.bookmark_id = bookmark_bookmarks.id AND f.user_id = $6
+              ), 'READING_STATUS_UNREAD') = $8)
+              AND ($9::JSONB IS NULL OR metadata @> $9)
+              AND ($10::TEXT IS NULL OR kind = $10)
+              AND ($11::TEXT IS NULL OR EXISTS (
+                  SELECT 1 FROM UNNEST(tags) AS t(tag)
+                  WHERE t.tag = $11 OR (RIGHT($11, 1) = '/' AND STARTS_WITH(t.tag || '/', $11))))
+            "#,
+            tenant_id,
+            ids,
+            filter.created_after,
+            filter.created_before,
+            filter.created_by,
+            &filter.user_id,
+            filter.pinned_only,
+            filter.reading_status.map(|s| s.as_str()),
+            filter.metadata.as_ref().map(Json) as _,
+            filter.kind.map(|k| k.as_str()),
+            filter.tag,
         )
-        .bind(tenant_id)
-        .bind(ids)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *conn)
         .await?;
 
-        let rows = sqlx::query_as::<_, BookmarkRow>(
+        let rows = sqlx::query_as!(
+            BookmarkRow,
             r#"
-            SELECT * FROM bookmark_bookmarks
+            SELECT id, tenant_id, url, title, description, tags, created_by,
+                   create_time, update_time, original_url, normalized_url, visit_count,
+                   last_visited_at, notes, metadata AS "metadata: _", kind, is_private
+            FROM bookmark_bookmarks
             WHERE tenant_id = $1 AND id = ANY($2)
-            ORDER BY create_time DESC
-            LIMIT $3 OFFSET $4
+              AND ($3::TIMESTAMPTZ IS NULL OR create_time >= $3)
+              AND ($4::TIMESTAMPTZ IS NULL OR create_time < $4)
+              AND ($5::INTEGER IS NULL OR created_by = $5)
+              AND (NOT $7 OR EXISTS (
+                  SELECT 1 FROM bookmark_user_flags f
+                  WHERE f.bookmark_id = bookmark_bookmarks.id AND f.user_id = $6 AND f.is_pinned))
+              AND ($8::TEXT IS NULL OR COALESCE((
+                  SELECT f.reading_status FROM bookmark_user_flags f
+                  WHERE f.bookmark_id = bookmark_bookmarks.id AND f.user_id = $6
+              ), 'READING_STATUS_UNREAD') = $8)
+              AND ($9::JSONB IS NULL OR metadata @> $9)
+              AND ($10::TEXT IS NULL OR kind = $10)
+              AND ($11::TEXT IS NULL OR EXISTS (
+                  SELECT 1 FROM UNNEST(tags) AS t(tag)
+                  WHERE t.tag = $11 OR (RIGHT($11, 1) = '/' AND STARTS_WITH(t.tag || '/', $11))))
+              AND ($12::TIMESTAMPTZ IS NULL OR (create_time, id) < ($12, $13))
+            ORDER BY create_time DESC, id DESC
+            LIMIT $14 OFFSET $15
             "#,
+            tenant_id,
+            ids,
+            filter.created_after,
+            filter.created_before,
+            filter.created_by,
+            &filter.user_id,
+            filter.pinned_only,
+            filter.reading_status.map(|s| s.as_str()),
+            filter.metadata.as_ref().map(Json) as _,
+            filter.kind.map(|k| k.as_str()),
+            filter.tag,
+            after_time,
+            after_id,
+            page_size as i64 + 1,
+            offset as i64,
         )
+        .fetch_all(&mut *conn)
+        .await?;
+        conn.finish();
+
+        Ok((rows, total))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn list_accessible(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        filter: &BookmarkFilter,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<BookmarkRow>, i64)> {
+        if subjects.is_empty() {
+            return Ok((vec![], 0));
+        }
+
+        const ACCESS: &str = r#"
+            tenant_id = $1
+              AND EXISTS (
+                  SELECT 1 FROM bookmark_permissions p
+                  JOIN UNNEST($3::VARCHAR[], $4::VARCHAR[]) AS s(subject_type, subject_id)
+                    ON p.subject_type = s.subject_type AND p.subject_id = s.subject_id
+                  WHERE p.tenant_id = bookmark_bookmarks.tenant_id
+                    AND p.resource_type = $2
+                    AND p.resource_id IN (bookmark_bookmarks.id::TEXT, $5)
+                    AND p.subject_tenant_id IS NULL)
+        "#;
+        // Shared with the user `$9` of tenant `$1` by the bookmark's tenant.
+        const FEDERATED_ACCESS: &str = r#"
+            EXISTS (
+                SELECT 1 FROM bookmark_permissions p
+                WHERE p.tenant_id = bookmark_bookmarks.tenant_id
+                  AND p.subject_tenant_id = $1
+                  AND p.subject_type = 'SUBJECT_TYPE_USER'
+                  AND p.subject_id = $9
+                  AND p.resource_type = $2
+                  AND p.resource_id = bookmark_bookmarks.id::TEXT
+                  AND (p.expires_at IS NULL OR p.expires_at > NOW()))
+        "#;
+        const FILTER: &str = r#"
+              AND ($6::TIMESTAMPTZ IS NULL OR create_time >= $6)
+              AND ($7::TIMESTAMPTZ IS NULL OR create_time < $7)
+              AND ($8::INTEGER IS NULL OR created_by = $8)
+              AND (NOT $10 OR EXISTS (
+                  SELECT 1 FROM bookmark_user_flags f
+                  WHERE f.bookmark_id = bookmark_bookmarks.id AND f.user_id = $9 AND f.is_pinned))
+              AND ($11::TEXT IS NULL OR COALESCE((
+                  SELECT f.reading_status FROM bookmark_user_flags f
+                  WHERE f.bookmark_id = bookmark_bookmarks.id AND f.user_id = $9
+              ), 'READING_STATUS_UNREAD') = $11)
+              AND ($12::JSONB IS NULL OR metadata @> $12)
+              AND ($13::TEXT IS NULL OR kind = $13)
+              AND ($14::TEXT IS NULL OR EXISTS (
+                  SELECT 1 FROM UNNEST(tags) AS t(tag)
+                  WHERE t.tag = $14 OR (RIGHT($14, 1) = '/' AND STARTS_WITH(t.tag || '/', $14))))
+        "#;
+
+        let after = after.filter(|_| !filter.manual_order);
+        let offset = if after.is_some() {
+            0
+        } else {
+            (page.saturating_sub(1)) * page_size
+        };
+        let (after_time, after_id) = after.unzip();
+        let (subject_types, subject_ids): (Vec<&str>, Vec<&str>) = subjects
+            .iter()
+            .map(|(t, id)| (t.as_str(), id.as_str()))
+            .unzip();
+
+        let access = if filter.federated {
+            format!("(({ACCESS}) OR {FEDERATED_ACCESS})")
+        } else {
+            ACCESS.to_string()
+        };
+        // The expression's values follow the fixed parameters of each query.
+        let expr = |first_param| {
+            and_clause(filter.expr.as_ref(), first_param, &|p, n| {
+                filter_predicate("bookmark_bookmarks", p, n)
+            })
+        };
+        let mut conn = self.reads.acquire_cancellable().await?;
+        let (count_expr, expr_params) = expr(15);
+        let count_sql = format!(
+            "SELECT COUNT(*) FROM bookmark_bookmarks WHERE {access} {FILTER} {count_expr}"
+        );
+        let mut count = sqlx::query_as(&count_sql)
+        .bind(tenant_id)
+        .bind(ResourceType::Bookmark.as_str())
+        .bind(&subject_types)
+        .bind(&subject_ids)
+        .bind(WILDCARD_RESOURCE)
+        .bind(filter.created_after)
+        .bind(filter.created_before)
+        .bind(filter.created_by)
+        .bind(&filter.user_id)
+        .bind(filter.pinned_only)
+        .bind(filter.reading_status.map(|s| s.as_str()))
+        .bind(filter.metadata.as_ref().map(Json))
+        .bind(filter.kind.map(|k| k.as_str()))
+        .bind(&filter.tag);
+        for param in &expr_params {
+            count = match param {
+                FilterParam::Text(v) => count.bind(v),
+                FilterParam::Time(v) => count.bind(v),
+            };
+        }
+        let (total,): (i64,) = count.fetch_one(&mut *conn).await?;
+
+        let order = if filter.manual_order {
+            r#"(SELECT f.sort_order FROM bookmark_user_flags f
+                WHERE f.bookmark_id = bookmark_bookmarks.id AND f.user_id = $9) NULLS LAST,
+               create_time DESC, id DESC"#
+        } else {
+            "create_time DESC, id DESC"
+        };
+        let (rows_expr, _) = expr(19);
+        let sql = format!(
+            r#"
+            SELECT * FROM bookmark_bookmarks
+            WHERE {access} {FILTER} {rows_expr}
+              AND ($15::TIMESTAMPTZ IS NULL OR (create_time, id) < ($15, $16))
+            ORDER BY {order}
+            LIMIT $17 OFFSET $18
+            "#
+        );
+        let mut q = sqlx::query_as::<_, BookmarkRow>(&sql)
+        .bind(tenant_id)
+        .bind(ResourceType::Bookmark.as_str())
+        .bind(&subject_types)
+        .bind(&subject_ids)
+        .bind(WILDCARD_RESOURCE)
+        .bind(filter.created_after)
+        .bind(filter.created_before)
+        .bind(filter.created_by)
+        .bind(&filter.user_id)
+        .bind(filter.pinned_only)
+        .bind(filter.reading_status.map(|s| s.as_str()))
+        .bind(filter.metadata.as_ref().map(Json))
+        .bind(filter.kind.map(|k| k.as_str()))
+        .bind(&filter.tag)
+        .bind(after_time)
+        .bind(after_id)
+        .bind(page_size as i64 + 1)
+        .bind(offset as i64);
+        for param in &expr_params {
+            q = match param {
+                FilterParam::Text(v) => q.bind(v),
+                FilterParam::Time(v) => q.bind(v),
+            };
+        }
+        let rows = q.fetch_all(&mut *conn).await?;
+        conn.finish();
+
+        Ok((rows, total))
+    }
+
+    async fn update(
+        &self,
+        tenant_id: i32,
+        changes: &BookmarkChanges,
+        edited_by: Option<i32>,
+    ) -> anyhow::Result<Option<BookmarkRow>> {
+        let mut tx = self.pool.begin().await?;
+        let row = update_in(&mut tx, tenant_id, changes, edited_by).await?;
+        tx.commit().await?;
+        Ok(row)
+    }
+
+    async fn batch_create(
+        &self,
+        tenant_id: i32,
+        owner_id: &str,
+        created_by: Option<i32>,
+        items: &[NewBookmark],
+    ) -> anyhow::Result<Vec<anyhow::Result<BookmarkRow>>> {
+        let mut tx = self.pool.begin().await?;
+        let mut results = Vec::with_capacity(items.len());
+
+        for item in items {
+            let mut sp = Connection::begin(&mut *tx).await?;
+            let inserted = sqlx::query_as!(
+                BookmarkRow,
+                r#"
+                INSERT INTO bookmark_bookmarks (id, tenant_id, url, title, description, tags, created_by, original_url, normalized_url, kind, is_private)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                RETURNING id, tenant_id, url, title, description, tags, created_by,
+                          create_time, update_time, original_url, normalized_url, visit_count,
+                          last_visited_at, notes, metadata AS "metadata: _", kind, is_private
+                "#,
+                item.id,
+                tenant_id,
+                item.url,
+                item.title,
+                item.description,
+                &item.tags,
+                created_by,
+                item.original_url,
+                (!item.is_private).then(|| normalize_url(&item.url)),
+                item.kind.as_str(),
+                item.is_private,
+            )
+            .fetch_one(&mut *sp)
+            .await;
+
+            match inserted {
+                Ok(row) => {
+                    sp.commit().await?;
+                    results.push(Ok(row));
+                }
+                Err(e) => {
+                    sp.rollback().await?;
+                    results.push(Err(e.into()));
+                }
+            }
+        }
+
+        let created: Vec<String> = results
+            .iter()
+            .filter_map(|r| r.as_ref().ok().map(|row| row.id.to_string()))
+            .collect();
+        if !created.is_empty() {
+            sqlx::query!(
+                r#"
+                INSERT INTO bookmark_permissions
+                    (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id, granted_by)
+                SELECT $1, $2, resource_id, $3, $4, $5, $6
+                FROM UNNEST($7::VARCHAR[]) AS resource_id
+                ON CONFLICT (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id,
+                    COALESCE(subject_tenant_id, 0)) DO NOTHING
+                "#,
+                tenant_id,
+                ResourceType::Bookmark.as_str(),
+                Relation::Owner.code(),
+                SubjectType::User.as_str(),
+                owner_id,
+                created_by,
+                &created,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    async fn batch_update(
+        &self,
+        tenant_id: i32,
+        items: &[BookmarkChanges],
+        edited_by: Option<i32>,
+    ) -> anyhow::Result<Vec<anyhow::Result<Option<BookmarkRow>>>> {
+        let mut tx = self.pool.begin().await?;
+        let mut results = Vec::with_capacity(items.len());
+
+        for item in items {
+            let mut sp = Connection::begin(&mut *tx).await?;
+            let updated = update_in(&mut sp, tenant_id, item, edited_by).await;
+
+            match updated {
+                Ok(row) => {
+                    sp.commit().await?;
+                    results.push(Ok(row));
+                }
+                Err(e) => {
+                    sp.rollback().await?;
+                    results.push(Err(e));
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    async fn batch_delete(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+    ) -> anyhow::Result<Vec<anyhow::Result<bool>>> {
+        let mut tx = self.pool.begin().await?;
+        let mut results = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            let mut sp = Connection::begin(&mut *tx).await?;
+            let deleted = delete_in(&mut sp, tenant_id, *id).await;
+
+            match deleted {
+                Ok(found) => {
+                    sp.commit().await?;
+                    results.push(Ok(found));
+                }
+                Err(e) => {
+                    sp.rollback().await?;
+                    results.push(Err(e));
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    async fn update_tags_bulk(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        filter: &BulkTagFilter,
+        add: &[String],
+        remove: &[String],
+        edited_by: Option<i32>,
+    ) -> anyhow::Result<Vec<BookmarkRow>> {
+        if subjects.is_empty() {
+            return Ok(vec![]);
+        }
+        let (subject_types, subject_ids): (Vec<&str>, Vec<&str>) = subjects
+            .iter()
+            .map(|(t, id)| (t.as_str(), id.as_str()))
+            .unzip();
+        let relations: Vec<i16> =
+            Relation::granting(Permission::Write).map(Relation::code).collect();
+
+        // Kept tags stay in order, then missing added tags follow in request order.
+        let rows = sqlx::query_as!(
+            BookmarkRow,
+            r#"
+            WITH targets AS (
+                SELECT b.id FROM bookmark_bookmarks b
+                WHERE b.tenant_id = $1
+                  AND EXISTS (
+                      SELECT 1 FROM bookmark_permissions p
+                      JOIN UNNEST($3::VARCHAR[], $4::VARCHAR[]) AS s(subject_type, subject_id)
+                        ON p.subject_type = s.subject_type AND p.subject_id = s.subject_id
+                      WHERE p.tenant_id = b.tenant_id
+                        AND p.resource_type = $2
+                        AND p.resource_id IN (b.id::TEXT, $5)
+                        AND p.relation_code = ANY($6)
+                        AND (p.expires_at IS NULL OR p.expires_at > NOW())
+                        AND p.subject_tenant_id IS NULL)
+                  AND ($7::TEXT IS NULL OR EXISTS (
+                      SELECT 1 FROM UNNEST(b.tags) AS t(tag)
+                      WHERE t.tag = $7 OR (RIGHT($7, 1) = '/' AND STARTS_WITH(t.tag || '/', $7))))
+                  AND ($8::TEXT IS NULL OR (
+                      SELECT h = $8 OR RIGHT(h, LENGTH($8) + 1) = '.' || $8
+                      FROM SUBSTRING(b.normalized_url FROM '^[^/?:]*') AS h))
+                  AND ($9::TEXT IS NULL
+                      OR to_tsvector('simple', b.title || ' ' || b.description || ' ' || array_to_string(b.tags, ' '))
+                         @@ websearch_to_tsquery('simple', $9))
+                  AND (b.tags && $10::TEXT[] OR NOT b.tags @> $11::TEXT[])
+                FOR UPDATE
+            ),
+            revisions AS (
+                INSERT INTO bookmark_revisions
+                    (bookmark_id, tenant_id, url, title, description, tags, original_url, edited_by, is_private)
+                SELECT b.id, b.tenant_id, b.url, b.title, b.description, b.tags, b.original_url, $12, b.is_private
+                FROM bookmark_bookmarks b
+                JOIN targets t ON t.id = b.id
+            )
+            UPDATE bookmark_bookmarks b
+            SET tags = ARRAY(
+                    SELECT tag FROM UNNEST(b.tags) WITH ORDINALITY AS k(tag, n)
+                    WHERE tag <> ALL($10) ORDER BY n)
+                || ARRAY(
+                    SELECT tag FROM UNNEST($11::TEXT[]) WITH ORDINALITY AS a(tag, n)
+                    WHERE tag <> ALL(b.tags) ORDER BY n),
+                update_time = NOW()
+            FROM targets t
+            WHERE b.id = t.id
+            RETURNING b.id, b.tenant_id, b.url, b.title, b.description, b.tags, b.created_by,
+                      b.create_time, b.update_time, b.original_url, b.normalized_url, b.visit_count,
+                      b.last_visited_at, b.notes, b.metadata AS "metadata: _", b.kind, b.is_private
+            "#,
+            tenant_id,
+            ResourceType::Bookmark.as_str(),
+            &subject_types as &[&str],
+            &subject_ids as &[&str],
+            WILDCARD_RESOURCE,
+            &relations,
+            filter.tag,
+            filter.domain,
+            filter.query,
+            remove,
+            add,
+            edited_by,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn search(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+        query: &str,
+        search_content: bool,
+        fuzzy_threshold: Option<f32>,
+        expr: Option<&FilterExpr>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<SearchRow>, i64)> {
+        if ids.is_empty() {
+            return Ok((vec![], 0));
+        }
+
+        let offset = (page.saturating_sub(1)) * page_size;
+        let (expr_sql, expr_params) = and_clause(expr, 8, &|p, n| filter_predicate("b", p, n));
+
+        let mut conn = self.reads.acquire_cancellable().await?;
+        // Snippets are computed after paging so only returned rows pay for ts_headline.
+        // Full-text matches come first; fuzzy-only matches follow, ordered by
+        // similarity, which also breaks ties between full-text matches.
+        let sql = format!(
+            r#"
+            WITH q AS (SELECT websearch_to_tsquery('simple', $3) AS query),
+            scored AS (
+                SELECT b.*,
+                       to_tsvector('simple', b.title || ' ' || b.description || ' ' || array_to_string(b.tags, ' ')) @@ q.query
+                           AS matched_fields,
+                       COALESCE(a.content_tsv @@ q.query, FALSE) AS matched_content,
+                       ts_rank(to_tsvector('simple', b.title || ' ' || b.description), q.query) * 2
+                           + COALESCE(ts_rank(a.content_tsv, q.query), 0) AS text_rank,
+                       CASE WHEN $7::REAL IS NULL THEN 0
+                            ELSE GREATEST(
+                                word_similarity($3, b.title),
+                                word_similarity($3, SUBSTRING(b.normalized_url FROM '^[^/?:]*')))
+                       END AS similarity
+                FROM bookmark_bookmarks b
+                CROSS JOIN q
+                LEFT JOIN bookmark_archives a ON $4 AND a.bookmark_id = b.id
+                WHERE b.tenant_id = $1
+                  AND b.id = ANY($2)
+                  {expr_sql}
+            ),
+            matches AS (
+                SELECT *,
+                       text_rank + similarity AS rank,
+                       NOT (matched_fields OR matched_content) AS fuzzy_match
+                FROM scored
+                WHERE matched_fields OR matched_content OR similarity >= $7
+            ),
+            paged AS (
+                SELECT *, COUNT(*) OVER () AS total_count
+                FROM matches
+                ORDER BY fuzzy_match, rank DESC, create_time DESC
+                LIMIT $5 OFFSET $6
+            )
+            SELECT p.*,
+                   CASE WHEN p.matched_content AND a.format = 'text'
+                        THEN ts_headline('simple', a.content, q.query, 'MaxFragments=1, MaxWords=30, MinWords=10')
+                   END AS snippet
+            FROM paged p
+            CROSS JOIN q
+            LEFT JOIN bookmark_archives a ON a.bookmark_id = p.id
+            ORDER BY p.fuzzy_match, p.rank DESC, p.create_time DESC
+            "#
+        );
+        let mut q = sqlx::query_as::<_, SearchRow>(&sql)
         .bind(tenant_id)
         .bind(ids)
+        .bind(query)
+        .bind(search_content)
         .bind(page_size as i64)
         .bind(offset as i64)
-        .fetch_all(&self.pool)
-        .await?;
+        .bind(fuzzy_threshold);
+        for param in expr_params {
+            q = match param {
+                FilterParam::Text(v) => q.bind(v),
+                FilterParam::Time(v) => q.bind(v),
+            };
+        }
+        let rows = q.fetch_all(&mut *conn).await?;
+        conn.finish();
 
-        Ok((rows, total.0))
+        let total = rows.first().map(|r| r.total_count).unwrap_or(0);
+        Ok((rows, total))
     }
 
-    pub async fn update(
+    async fn update_notes(
         &self,
+        tenant_id: i32,
         id: Uuid,
-        url: Option<&str>,
-        title: Option<&str>,
-        description: Option<&str>,
-        tags: Option<&[String]>,
+        notes: &str,
     ) -> anyhow::Result<Option<BookmarkRow>> {
-        let row = sqlx::query_as::<_, BookmarkRow>(
+        let row = sqlx::query_as!(
+            BookmarkRow,
             r#"
-            UPDATE bookmark_bookmarks SET
-                url = COALESCE($2, url),
-                title = COALESCE($3, title),
-                description = COALESCE($4, description),
-                tags = COALESCE($5, tags),
-                update_time = NOW()
-            WHERE id = $1
-            RETURNING *
+            UPDATE bookmark_bookmarks
+            SET notes = $3, update_time = NOW()
+            WHERE id = $1 AND tenant_id = $2
+            RETURNING id, tenant_id, url, title, description, tags, created_by,
+                      create_time, update_time, original_url, normalized_url, visit_count,
+                      last_visited_at, notes, metadata AS "metadata: _", kind, is_private
             "#,
+            id,
+            tenant_id,
+            notes,
         )
-        .bind(id)
-        .bind(url)
-        .bind(title)
-        .bind(description)
-        .bind(tags)
         .fetch_optional(&self.pool)
         .await?;
 
         Ok(row)
     }
 
-    pub async fn delete(&self, id: Uuid) -> anyhow::Result<bool> {
-        let result = sqlx::query("DELETE FROM bookmark_bookmarks WHERE id = $1")
-            .bind(id)
+    async fn record_visit(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+    ) -> anyhow::Result<Option<(i32, DateTime<Utc>)>> {
+        let row = sqlx::query!(
+            r#"
+            UPDATE bookmark_bookmarks
+            SET visit_count = visit_count + 1, last_visited_at = NOW()
+            WHERE id = $1 AND tenant_id = $2
+            RETURNING visit_count, last_visited_at AS "last_visited_at!"
+            "#,
+            id,
+            tenant_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| (r.visit_count, r.last_visited_at)))
+    }
+
+    async fn list_most_visited(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+        limit: u32,
+    ) -> anyhow::Result<Vec<BookmarkRow>> {
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let rows = sqlx::query_as!(
+            BookmarkRow,
+            r#"
+            SELECT id, tenant_id, url, title, description, tags, created_by,
+                   create_time, update_time, original_url, normalized_url, visit_count,
+                   last_visited_at, notes, metadata AS "metadata: _", kind, is_private
+            FROM bookmark_bookmarks
+            WHERE tenant_id = $1 AND id = ANY($2) AND visit_count > 0
+            ORDER BY visit_count DESC, last_visited_at DESC
+            LIMIT $3
+            "#,
+            tenant_id,
+            ids,
+            limit as i64,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn tag_stats(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        prefix: &str,
+        order: TagOrder,
+        limit: u32,
+    ) -> anyhow::Result<Vec<TagStatRow>> {
+        if subjects.is_empty() {
+            return Ok(vec![]);
+        }
+        let (subject_types, subject_ids): (Vec<&str>, Vec<&str>) = subjects
+            .iter()
+            .map(|(t, id)| (t.as_str(), id.as_str()))
+            .unzip();
+
+        let mut conn = self.reads.acquire_cancellable().await?;
+        let rows = sqlx::query_as::<_, TagStatRow>(&format!(
+            r#"
+            SELECT t.tag,
+                   COUNT(DISTINCT b.id) AS bookmark_count,
+                   MIN(b.create_time) AS first_used,
+                   MAX(b.update_time) AS last_used,
+                   COUNT(*) OVER () AS total_count
+            FROM bookmark_bookmarks b
+            CROSS JOIN LATERAL UNNEST(b.tags) AS t(tag)
+            WHERE b.tenant_id = $1
+              AND EXISTS (
+                  SELECT 1 FROM bookmark_permissions p
+                  JOIN UNNEST($3::VARCHAR[], $4::VARCHAR[]) AS s(subject_type, subject_id)
+                    ON p.subject_type = s.subject_type AND p.subject_id = s.subject_id
+                  WHERE p.tenant_id = b.tenant_id
+                    AND p.resource_type = $2
+                    AND p.resource_id IN (b.id::TEXT, $5)
+                    AND p.subject_tenant_id IS NULL)
+              AND LEFT(LOWER(t.tag), LENGTH($6)) = LOWER($6)
+            GROUP BY t.tag
+            ORDER BY {}
+            LIMIT $7
+            "#,
+            order.order_by()
+        ))
+        .bind(tenant_id)
+        .bind(ResourceType::Bookmark.as_str())
+        .bind(&subject_types)
+        .bind(&subject_ids)
+        .bind(WILDCARD_RESOURCE)
+        .bind(prefix)
+        .bind(limit as i64)
+        .fetch_all(&mut *conn)
+        .await?;
+        conn.finish();
+
+        Ok(rows)
+    }
+
+    async fn tag_tree(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        root: &str,
+    ) -> anyhow::Result<Vec<TagPathRow>> {
+        if subjects.is_empty() {
+            return Ok(vec![]);
+        }
+        let (subject_types, subject_ids): (Vec<&str>, Vec<&str>) = subjects
+            .iter()
+            .map(|(t, id)| (t.as_str(), id.as_str()))
+            .unzip();
+
+        // Each tag expands into its namespaces, one segment longer per step.
+        let mut conn = self.reads.acquire_cancellable().await?;
+        let rows = sqlx::query_as::<_, TagPathRow>(
+            r#"
+            WITH RECURSIVE tagged AS (
+                SELECT DISTINCT b.id, t.tag
+                FROM bookmark_bookmarks b
+                CROSS JOIN LATERAL UNNEST(b.tags) AS t(tag)
+                WHERE b.tenant_id = $1
+                  AND EXISTS (
+                      SELECT 1 FROM bookmark_permissions p
+                      JOIN UNNEST($3::VARCHAR[], $4::VARCHAR[]) AS s(subject_type, subject_id)
+                        ON p.subject_type = s.subject_type AND p.subject_id = s.subject_id
+                      WHERE p.tenant_id = b.tenant_id
+                        AND p.resource_type = $2
+                        AND p.resource_id IN (b.id::TEXT, $5)
+                        AND p.subject_tenant_id IS NULL)
+                  AND ($6 = '' OR t.tag = $6 OR STARTS_WITH(t.tag, $6 || '/'))
+            ),
+            paths AS (
+                SELECT id, tag, SPLIT_PART(tag, '/', 1) AS path, 1 AS depth
+                FROM tagged
+                UNION ALL
+                SELECT id, tag, path || '/' || SPLIT_PART(tag, '/', depth + 1), depth + 1
+                FROM paths
+                WHERE path <> tag
+            )
+            SELECT path,
+                   COUNT(DISTINCT id) FILTER (WHERE path = tag) AS bookmark_count,
+                   COUNT(DISTINCT id) AS subtree_count
+            FROM paths
+            WHERE $6 = '' OR path = $6 OR STARTS_WITH(path, $6 || '/')
+            GROUP BY path
+            ORDER BY path
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(ResourceType::Bookmark.as_str())
+        .bind(&subject_types)
+        .bind(&subject_ids)
+        .bind(WILDCARD_RESOURCE)
+        .bind(root)
+        .fetch_all(&mut *conn)
+        .await?;
+        conn.finish();
+
+        Ok(rows)
+    }
+
+    async fn split_tags(
+        &self,
+        tenant_id: Option<i32>,
+        delimiter: &str,
+        dry_run: bool,
+    ) -> anyhow::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, tags FROM bookmark_bookmarks
+            WHERE ($1::INTEGER IS NULL OR tenant_id = $1)
+              AND EXISTS (SELECT 1 FROM UNNEST(tags) AS t(tag) WHERE STRPOS(t.tag, $2) > 0)
+            FOR UPDATE
+            "#,
+            tenant_id,
+            delimiter,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let changes: Vec<(Uuid, Vec<String>)> = rows
+            .into_iter()
+            .filter_map(|r| split_tags(&r.tags, delimiter).map(|tags| (r.id, tags)))
+            .collect();
+        if dry_run || changes.is_empty() {
+            return Ok(changes.len() as u64);
+        }
+
+        let ids: Vec<Uuid> = changes.iter().map(|(id, _)| *id).collect();
+        sqlx::query!(
+            r#"
+            INSERT INTO bookmark_revisions
+                (bookmark_id, tenant_id, url, title, description, tags, original_url, is_private)
+            SELECT id, tenant_id, url, title, description, tags, original_url, is_private
+            FROM bookmark_bookmarks
+            WHERE id = ANY($1)
+            "#,
+            &ids,
+        )
+        .execute(&mut *tx)
+        .await?;
+        for (id, tags) in &changes {
+            sqlx::query!(
+                "UPDATE bookmark_bookmarks SET tags = $2, update_time = NOW() WHERE id = $1",
+                id,
+                tags,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(changes.len() as u64)
+    }
+
+    async fn find_by_normalized_url(
+        &self,
+        tenant_id: i32,
+        normalized_url: &str,
+    ) -> anyhow::Result<Vec<BookmarkRow>> {
+        let rows = sqlx::query_as!(
+            BookmarkRow,
+            r#"
+            SELECT id, tenant_id, url, title, description, tags, created_by,
+                   create_time, update_time, original_url, normalized_url, visit_count,
+                   last_visited_at, notes, metadata AS "metadata: _", kind, is_private
+            FROM bookmark_bookmarks
+            WHERE tenant_id = $1 AND normalized_url = $2
+            ORDER BY create_time
+            "#,
+            tenant_id,
+            normalized_url,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn find_duplicate_groups(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+    ) -> anyhow::Result<Vec<BookmarkRow>> {
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let rows = sqlx::query_as!(
+            BookmarkRow,
+            r#"
+            SELECT id, tenant_id, url, title, description, tags, created_by,
+                   create_time, update_time, original_url, normalized_url, visit_count,
+                   last_visited_at, notes, metadata AS "metadata: _", kind, is_private
+            FROM bookmark_bookmarks
+            WHERE tenant_id = $1
+              AND id = ANY($2)
+              AND normalized_url IN (
+                  SELECT normalized_url FROM bookmark_bookmarks
+                  WHERE tenant_id = $1 AND id = ANY($2) AND normalized_url IS NOT NULL
+                  GROUP BY normalized_url
+                  HAVING COUNT(*) > 1
+              )
+            ORDER BY normalized_url, create_time
+            "#,
+            tenant_id,
+            ids,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn backfill_normalized_urls(&self) -> anyhow::Result<u64> {
+        const BATCH: i64 = 500;
+        let mut updated = 0;
+
+        loop {
+            let rows = sqlx::query!(
+                "SELECT id, url FROM bookmark_bookmarks WHERE normalized_url IS NULL AND NOT is_private LIMIT $1",
+                BATCH,
+            )
+            .fetch_all(&self.pool)
+            .await?;
+            if rows.is_empty() {
+                return Ok(updated);
+            }
+
+            let (ids, normalized): (Vec<Uuid>, Vec<String>) = rows
+                .into_iter()
+                .map(|r| (r.id, normalize_url(&r.url)))
+                .unzip();
+            let result = sqlx::query!(
+                r#"
+                UPDATE bookmark_bookmarks b SET normalized_url = v.normalized_url
+                FROM UNNEST($1::UUID[], $2::TEXT[]) AS v(id, normalized_url)
+                WHERE b.id = v.id
+                "#,
+                &ids,
+                &normalized,
+            )
             .execute(&self.pool)
             .await?;
+            updated += result.rows_affected();
+        }
+    }
+
+    async fn backfill_kinds(&self) -> anyhow::Result<u64> {
+        const BATCH: i64 = 500;
+        let mut updated = 0;
+
+        loop {
+            let rows = sqlx::query!(
+                "SELECT id, url FROM bookmark_bookmarks WHERE kind IS NULL LIMIT $1",
+                BATCH,
+            )
+            .fetch_all(&self.pool)
+            .await?;
+            if rows.is_empty() {
+                return Ok(updated);
+            }
+
+            let (ids, kinds): (Vec<Uuid>, Vec<&str>) = rows
+                .into_iter()
+                .map(|r| (r.id, classify_url(&r.url).as_str()))
+                .unzip();
+            let result = sqlx::query!(
+                r#"
+                UPDATE bookmark_bookmarks b SET kind = v.kind
+                FROM UNNEST($1::UUID[], $2::TEXT[]) AS v(id, kind)
+                WHERE b.id = v.id
+                "#,
+                &ids,
+                &kinds as &[&str],
+            )
+            .execute(&self.pool)
+            .await?;
+            updated += result.rows_affected();
+        }
+    }
+
+    async fn list_revisions(
+        &self,
+        tenant_id: i32,
+        bookmark_id: Uuid,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<RevisionRow>, i64)> {
+        let offset = (page.saturating_sub(1)) * page_size;
+
+        let total = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM bookmark_revisions WHERE bookmark_id = $1 AND tenant_id = $2"#,
+            bookmark_id,
+            tenant_id,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let rows = sqlx::query_as!(
+            RevisionRow,
+            r#"
+            SELECT * FROM bookmark_revisions
+            WHERE bookmark_id = $1 AND tenant_id = $2
+            ORDER BY create_time DESC, id DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            bookmark_id,
+            tenant_id,
+            page_size as i64,
+            offset as i64,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((rows, total))
+    }
+
+    async fn get_revision(
+        &self,
+        tenant_id: i32,
+        bookmark_id: Uuid,
+        revision_id: i64,
+    ) -> anyhow::Result<Option<RevisionRow>> {
+        let row = sqlx::query_as!(
+            RevisionRow,
+            "SELECT * FROM bookmark_revisions WHERE bookmark_id = $1 AND tenant_id = $2 AND id = $3",
+            bookmark_id,
+            tenant_id,
+            revision_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn delete(&self, tenant_id: i32, id: Uuid) -> anyhow::Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM bookmark_bookmarks WHERE id = $1 AND tenant_id = $2",
+            id,
+            tenant_id,
+        )
+        .execute(&self.pool)
+        .await?;
 
         Ok(result.rows_affected() > 0)
     }
 }
+
+/// Apply one update on an open connection, recording the previous state as a
+/// revision first. Only the fields present in `changes` appear in the SET
+/// clause. Returns `None` if the bookmark does not exist in the tenant.
+async fn update_in(
+    conn: &mut PgConnection,
+    tenant_id: i32,
+    changes: &BookmarkChanges,
+    edited_by: Option<i32>,
+) -> anyhow::Result<Option<BookmarkRow>> {
+    let recorded = sqlx::query!(
+        r#"
+        INSERT INTO bookmark_revisions
+            (bookmark_id, tenant_id, url, title, description, tags, original_url, edited_by, is_private)
+        SELECT id, tenant_id, url, title, description, tags, original_url, $3, is_private
+        FROM bookmark_bookmarks
+        WHERE id = $1 AND tenant_id = $2
+        FOR UPDATE
+        "#,
+        changes.id,
+        tenant_id,
+        edited_by,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if recorded.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    let sql = format!(
+        "UPDATE bookmark_bookmarks SET {}, update_time = NOW() WHERE id = $1 AND tenant_id = $2 RETURNING *",
+        changes.set_clause(3)
+    );
+    let mut query = sqlx::query_as::<_, BookmarkRow>(&sql)
+        .bind(changes.id)
+        .bind(tenant_id);
+    if let Some(url) = &changes.url {
+        query = query
+            .bind(url)
+            .bind(&changes.original_url)
+            .bind(changes.normalized_url());
+    }
+    if let Some(title) = &changes.title {
+        query = query.bind(title);
+    }
+    if let Some(description) = &changes.description {
+        query = query.bind(description);
+    }
+    if let Some(tags) = &changes.tags {
+        query = query.bind(tags);
+    }
+    if let Some(metadata) = &changes.metadata {
+        query = query.bind(Json(metadata));
+    }
+    if let Some(kind) = changes.kind {
+        query = query.bind(kind.as_str());
+    }
+    if let Some(notes) = &changes.notes {
+        query = query.bind(notes);
+    }
+    if let Some(is_private) = changes.is_private {
+        query = query.bind(is_private);
+    }
+    let row = query.fetch_optional(&mut *conn).await?;
+
+    Ok(row)
+}
+
+async fn delete_in(conn: &mut PgConnection, tenant_id: i32, id: Uuid) -> anyhow::Result<bool> {
+    let result = sqlx::query!(
+        "DELETE FROM bookmark_bookmarks WHERE id = $1 AND tenant_id = $2",
+        id,
+        tenant_id,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query!(
+        "DELETE FROM bookmark_permissions WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3",
+        tenant_id,
+        ResourceType::Bookmark.as_str(),
+        id.to_string(),
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(true)
+}
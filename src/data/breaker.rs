@@ -0,0 +1,173 @@
+//! Database circuit breaker. Connection failures surfacing from repository
+//! calls are counted as they are turned into RPC errors; once enough happen in
+//! a row the breaker opens and RPCs fail fast until a background probe gets a
+//! connection from the pool again.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+use opentelemetry::KeyValue;
+
+use crate::data::pool_metrics::MonitoredPool;
+use crate::shutdown::ShutdownSignal;
+
+struct State {
+    threshold: u32,
+    retry_after: Duration,
+    failures: AtomicU32,
+    opened_at: Mutex<Option<Instant>>,
+}
+
+/// Cheap to clone; clones share state.
+#[derive(Clone)]
+pub struct DbBreaker {
+    state: Arc<State>,
+}
+
+impl DbBreaker {
+    pub fn new(threshold: u32, probe_interval: Duration) -> Self {
+        Self {
+            state: Arc::new(State {
+                threshold: threshold.max(1),
+                retry_after: probe_interval,
+                failures: AtomicU32::new(0),
+                opened_at: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// How long callers should wait before retrying, while the breaker is open.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.opened_at().is_some().then_some(self.state.retry_after)
+    }
+
+    pub fn record_failure(&self) {
+        let failures = self.state.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures < self.state.threshold {
+            return;
+        }
+        let mut opened_at = self.lock();
+        if opened_at.is_none() {
+            *opened_at = Some(Instant::now());
+            tracing::error!(failures, "database unreachable, circuit breaker opened");
+        }
+    }
+
+    /// A call went through; a run of failures is over.
+    pub fn record_success(&self) {
+        if self.state.failures.load(Ordering::Relaxed) != 0 && self.opened_at().is_none() {
+            self.state.failures.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn close(&self) {
+        self.state.failures.store(0, Ordering::Relaxed);
+        if let Some(opened_at) = self.lock().take() {
+            tracing::warn!(
+                open_for = ?opened_at.elapsed(),
+                "database reachable again, circuit breaker closed"
+            );
+        }
+    }
+
+    fn opened_at(&self) -> Option<Instant> {
+        *self.lock()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Instant>> {
+        self.state
+            .opened_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// While open, try to check a connection out of `pool` every
+    /// `retry_after`, and close the breaker once that works.
+    pub fn spawn_probe(
+        &self,
+        pool: MonitoredPool,
+        mut shutdown: ShutdownSignal,
+    ) -> tokio::task::JoinHandle<()> {
+        let breaker = self.clone();
+        let observed = self.clone();
+        opentelemetry::global::meter("bookmark")
+            .u64_observable_gauge("bookmark.db.circuit_breaker.open")
+            .with_description("1 while RPCs are failing fast because the database is unreachable")
+            .with_callback(move |gauge| {
+                gauge.observe(u64::from(observed.opened_at().is_some()), &[]);
+            })
+            .build();
+        let probes = opentelemetry::global::meter("bookmark")
+            .u64_counter("bookmark.db.circuit_breaker.probes")
+            .with_description("Connection probes made while the breaker was open, by result")
+            .build();
+
+        tokio::spawn(async move {
+            let interval = breaker.state.retry_after;
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = shutdown.recv() => break,
+                }
+                if breaker.opened_at().is_none() {
+                    continue;
+                }
+                let result = match tokio::time::timeout(interval, pool.acquire()).await {
+                    Ok(Ok(())) => "ok",
+                    Ok(Err(e)) => {
+                        tracing::debug!(error = %e, "database probe failed");
+                        "error"
+                    }
+                    Err(_) => "timeout",
+                };
+                probes.add(1, &[KeyValue::new("result", result)]);
+                if result == "ok" {
+                    breaker.close();
+                }
+            }
+        })
+    }
+}
+
+static ACTIVE: OnceLock<DbBreaker> = OnceLock::new();
+
+/// Make `breaker` the one repository errors are reported to.
+pub fn install(breaker: DbBreaker) {
+    let _ = ACTIVE.set(breaker);
+}
+
+/// Count a failure to reach the database, if a breaker is installed.
+pub fn record_connection_failure() {
+    if let Some(breaker) = ACTIVE.get() {
+        breaker.record_failure();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_consecutive_failures_only() {
+        let breaker = DbBreaker::new(3, Duration::from_secs(2));
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.retry_after(), None);
+
+        breaker.record_failure();
+        assert_eq!(breaker.retry_after(), Some(Duration::from_secs(2)));
+        // Calls that slip through while open do not close it; only the probe does.
+        breaker.record_success();
+        assert!(breaker.retry_after().is_some());
+
+        breaker.close();
+        assert_eq!(breaker.retry_after(), None);
+        breaker.record_failure();
+        assert_eq!(breaker.retry_after(), None);
+    }
+}
@@ -0,0 +1,66 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// One entry of the bookmark change log.
+#[derive(Debug, sqlx::FromRow)]
+pub struct ChangeRow {
+    pub txid: i64,
+    pub seq: i64,
+    pub bookmark_id: Uuid,
+    pub deleted: bool,
+}
+
+/// Read side of `bookmark_changes`, which triggers on `bookmark_bookmarks` fill.
+#[tonic::async_trait]
+pub trait ChangeStore: Send + Sync {
+    /// Up to `limit` changes in the tenant after the `(txid, seq)` position,
+    /// oldest first. Only changes from committed transactions older than every
+    /// running one are returned, so nothing can later appear before the last
+    /// row handed out.
+    async fn list_changes(
+        &self,
+        tenant_id: i32,
+        after: (i64, i64),
+        limit: i64,
+    ) -> anyhow::Result<Vec<ChangeRow>>;
+}
+
+#[derive(Clone)]
+pub struct ChangeRepo {
+    pool: PgPool,
+}
+
+impl ChangeRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl ChangeStore for ChangeRepo {
+    async fn list_changes(
+        &self,
+        tenant_id: i32,
+        after: (i64, i64),
+        limit: i64,
+    ) -> anyhow::Result<Vec<ChangeRow>> {
+        let rows = sqlx::query_as::<_, ChangeRow>(
+            r#"
+            SELECT txid, seq, bookmark_id, deleted FROM bookmark_changes
+            WHERE tenant_id = $1
+              AND (txid, seq) > ($2, $3)
+              AND txid < pg_snapshot_xmin(pg_current_snapshot())::text::bigint
+            ORDER BY txid, seq
+            LIMIT $4
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(after.0)
+        .bind(after.1)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
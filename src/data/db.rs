@@ -1,20 +1,284 @@
-use sqlx::postgres::PgPoolOptions;
-use sqlx::PgPool;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::config::DataConfig;
+use sqlx::migrate::Migrator;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use sqlx::{ConnectOptions, PgPool, SqlitePool};
 
-pub async fn create_pool(config: &DataConfig) -> anyhow::Result<PgPool> {
-    let pool = PgPoolOptions::new()
-        .max_connections(config.data.database.max_connections)
-        .connect(&config.data.database.source)
-        .await?;
+use crate::config::{parse_duration, DataConfig};
+use crate::data::access_request_repo::{AccessRequestRepo, AccessRequestStore};
+use crate::data::api_key_repo::{ApiKeyRepo, ApiKeyStore};
+use crate::data::archive_repo::{ArchiveRepo, ArchiveStore};
+use crate::data::audit_repo::{AuditRepo, AuditStore};
+use crate::data::auto_tag_rule_repo::{AutoTagRuleRepo, AutoTagRuleStore};
+use crate::data::bookmark_repo::{BookmarkRepo, BookmarkStore};
+use crate::data::change_repo::{ChangeRepo, ChangeStore};
+use crate::data::field_key_repo::{FieldKeyRepo, FieldKeyStore};
+use crate::data::group_repo::{GroupRepo, GroupStore};
+use crate::data::idempotency_repo::{IdempotencyRepo, IdempotencyStore};
+use crate::data::migration_guard;
+use crate::data::permission_repo::{PermissionRepo, PermissionStore};
+use crate::data::permission_template_repo::{PermissionTemplateRepo, PermissionTemplateStore};
+use crate::data::pool_metrics::MonitoredPool;
+use crate::data::replica::ReadPool;
+use crate::data::saved_search_repo::{SavedSearchRepo, SavedSearchStore};
+use crate::data::scrub_policy_repo::{ScrubPolicyRepo, ScrubPolicyStore};
+use crate::data::share_notification_repo::{ShareNotificationRepo, ShareNotificationStore};
+use crate::data::short_link_repo::{ShortLinkRepo, ShortLinkStore};
+use crate::data::sqlite;
+use crate::data::stats_repo::{StatsRepo, StatsStore};
+use crate::data::tag_repo::{TagRepo, TagStore};
+use crate::data::user_data_repo::{UserDataRepo, UserDataStore};
+use crate::data::user_flag_repo::{UserFlagRepo, UserFlagStore};
 
-    tracing::info!("database connection pool created");
-    Ok(pool)
+/// Connection pool for the configured `database.driver`.
+#[derive(Clone)]
+pub enum Database {
+    /// Writes go to `pool`; selected read paths use `reads`.
+    Postgres { pool: PgPool, reads: ReadPool },
+    /// For development and small single-node deployments; features that need
+    /// Postgres (backups) are unavailable.
+    Sqlite(SqlitePool),
 }
 
-pub async fn run_migrations(pool: &PgPool) -> anyhow::Result<()> {
-    sqlx::migrate!("./migrations").run(pool).await?;
-    tracing::info!("database migrations applied");
-    Ok(())
+/// One handle per data store, backed by whichever database is configured.
+#[derive(Clone)]
+pub struct Stores {
+    pub bookmarks: Arc<dyn BookmarkStore>,
+    pub permissions: Arc<dyn PermissionStore>,
+    pub groups: Arc<dyn GroupStore>,
+    pub user_flags: Arc<dyn UserFlagStore>,
+    pub archives: Arc<dyn ArchiveStore>,
+    pub scrub_policies: Arc<dyn ScrubPolicyStore>,
+    pub audit: Arc<dyn AuditStore>,
+    pub api_keys: Arc<dyn ApiKeyStore>,
+    pub stats: Arc<dyn StatsStore>,
+    pub short_links: Arc<dyn ShortLinkStore>,
+    pub changes: Arc<dyn ChangeStore>,
+    pub idempotency: Arc<dyn IdempotencyStore>,
+    pub share_notifications: Arc<dyn ShareNotificationStore>,
+    pub permission_templates: Arc<dyn PermissionTemplateStore>,
+    pub auto_tag_rules: Arc<dyn AutoTagRuleStore>,
+    pub access_requests: Arc<dyn AccessRequestStore>,
+    pub user_data: Arc<dyn UserDataStore>,
+    pub field_keys: Arc<dyn FieldKeyStore>,
+    pub tags: Arc<dyn TagStore>,
+    pub saved_searches: Arc<dyn SavedSearchStore>,
+}
+
+/// Connect to the configured database. With `statement_timeout` set, Postgres
+/// cancels any statement that runs longer, so a query abandoned by a timed-out
+/// request does not keep running.
+pub async fn create_pool(
+    config: &DataConfig,
+    statement_timeout: Option<Duration>,
+) -> anyhow::Result<Database> {
+    let db = &config.data.database;
+    let slow_threshold = parse_duration(&db.slow_query_threshold)?;
+    let database = match db.driver.as_str() {
+        "postgresql" | "postgres" => {
+            let mut options = pg_options(&db.source, statement_timeout, slow_threshold)?;
+            // Change notifications name the writer, so name this process.
+            if config.data.change_feed.enabled {
+                options = options.application_name(crate::change_feed::instance_name());
+            }
+            let pool = PgPoolOptions::new()
+                .max_connections(db.max_connections)
+                .acquire_slow_threshold(slow_threshold)
+                .connect_with(options)
+                .await?;
+            let replica = db
+                .replica_source
+                .as_deref()
+                .map(|source| {
+                    pg_options(source, statement_timeout, slow_threshold)
+                        .map(|options| ReadPool::connect_replica(options, db.max_connections))
+                })
+                .transpose()?;
+            if replica.is_some() {
+                tracing::info!("read replica configured");
+            }
+            let reads = ReadPool::new(pool.clone(), replica);
+            Database::Postgres { pool, reads }
+        }
+        // WAL lets readers proceed while the single writer holds the lock.
+        "sqlite" if db.replica_source.is_some() => {
+            anyhow::bail!("database.replica_source is only supported with the postgresql driver")
+        }
+        "sqlite" => Database::Sqlite(
+            SqlitePoolOptions::new()
+                .max_connections(db.max_connections)
+                .acquire_slow_threshold(slow_threshold)
+                .connect_with(
+                    SqliteConnectOptions::from_str(&db.source)?
+                        .create_if_missing(true)
+                        .journal_mode(SqliteJournalMode::Wal)
+                        .log_slow_statements(log::LevelFilter::Warn, slow_threshold),
+                )
+                .await?,
+        ),
+        other => anyhow::bail!("unsupported database driver {other:?}"),
+    };
+
+    tracing::info!(driver = %db.driver, "database connection pool created");
+    Ok(database)
+}
+
+fn pg_options(
+    source: &str,
+    statement_timeout: Option<Duration>,
+    slow_threshold: Duration,
+) -> anyhow::Result<PgConnectOptions> {
+    let options = PgConnectOptions::from_str(source)?
+        .log_slow_statements(log::LevelFilter::Warn, slow_threshold);
+    Ok(match statement_timeout {
+        Some(timeout) => {
+            options.options([("statement_timeout", format!("{}ms", timeout.as_millis()))])
+        }
+        None => options,
+    })
+}
+
+impl Database {
+    /// Apply pending migrations. Postgres migrations classified as unsafe for
+    /// online application are refused unless `allow_unsafe` is set.
+    pub async fn run_migrations(&self, allow_unsafe: bool) -> anyhow::Result<()> {
+        match self {
+            Self::Postgres { pool, .. } => {
+                let migrator = sqlx::migrate!("./migrations");
+                migration_guard::check_pending(pool, &migrator, allow_unsafe).await?;
+                // Migrations may legitimately outlast the request statement timeout.
+                let mut conn = pool.acquire().await?;
+                sqlx::query("SET statement_timeout = 0")
+                    .execute(&mut *conn)
+                    .await?;
+                migrator.run(&mut *conn).await?;
+                // Don't hand the unlimited session back to the pool.
+                conn.detach();
+            }
+            Self::Sqlite(pool) => sqlx::migrate!("./migrations_sqlite").run(pool).await?,
+        }
+        tracing::info!("database migrations applied");
+        Ok(())
+    }
+
+    /// Ok when the database answers and every migration this build ships with
+    /// has been applied.
+    pub async fn check_ready(&self) -> anyhow::Result<()> {
+        const APPLIED: &str = "SELECT version FROM _sqlx_migrations WHERE success";
+        let (migrator, applied): (Migrator, Vec<i64>) = match self {
+            Self::Postgres { pool, .. } => (
+                sqlx::migrate!("./migrations"),
+                sqlx::query_scalar(APPLIED).fetch_all(pool).await?,
+            ),
+            Self::Sqlite(pool) => (
+                sqlx::migrate!("./migrations_sqlite"),
+                sqlx::query_scalar(APPLIED).fetch_all(pool).await?,
+            ),
+        };
+        let pending: Vec<i64> = migrator
+            .iter()
+            .map(|m| m.version)
+            .filter(|v| !applied.contains(v))
+            .collect();
+        if !pending.is_empty() {
+            anyhow::bail!("migrations not applied: {pending:?}");
+        }
+        Ok(())
+    }
+
+    /// Every pool this database uses, named for metrics.
+    pub fn pools(&self) -> Vec<(&'static str, MonitoredPool)> {
+        match self {
+            Self::Postgres { pool, reads } => {
+                let mut pools = vec![("primary", MonitoredPool::Postgres(pool.clone()))];
+                if let Some(replica) = reads.replica() {
+                    pools.push(("replica", MonitoredPool::Postgres(replica.clone())));
+                }
+                pools
+            }
+            Self::Sqlite(pool) => vec![("primary", MonitoredPool::Sqlite(pool.clone()))],
+        }
+    }
+
+    /// The Postgres pool, for features not ported to other databases.
+    pub fn postgres(&self) -> Option<&PgPool> {
+        match self {
+            Self::Postgres { pool, .. } => Some(pool),
+            Self::Sqlite(_) => None,
+        }
+    }
+
+    pub fn stores(&self) -> Stores {
+        match self {
+            Self::Postgres { pool, reads } => Stores {
+                bookmarks: Arc::new(BookmarkRepo::new(pool.clone(), reads.clone())),
+                permissions: Arc::new(PermissionRepo::new(pool.clone(), reads.clone())),
+                groups: Arc::new(GroupRepo::new(pool.clone())),
+                user_flags: Arc::new(UserFlagRepo::new(pool.clone())),
+                archives: Arc::new(ArchiveRepo::new(pool.clone())),
+                scrub_policies: Arc::new(ScrubPolicyRepo::new(pool.clone())),
+                audit: Arc::new(AuditRepo::new(pool.clone())),
+                api_keys: Arc::new(ApiKeyRepo::new(pool.clone())),
+                stats: Arc::new(StatsRepo::new(pool.clone())),
+                short_links: Arc::new(ShortLinkRepo::new(pool.clone())),
+                changes: Arc::new(ChangeRepo::new(pool.clone())),
+                idempotency: Arc::new(IdempotencyRepo::new(pool.clone())),
+                share_notifications: Arc::new(ShareNotificationRepo::new(pool.clone())),
+                permission_templates: Arc::new(PermissionTemplateRepo::new(pool.clone())),
+                auto_tag_rules: Arc::new(AutoTagRuleRepo::new(pool.clone())),
+                access_requests: Arc::new(AccessRequestRepo::new(pool.clone())),
+                user_data: Arc::new(UserDataRepo::new(pool.clone())),
+                field_keys: Arc::new(FieldKeyRepo::new(pool.clone())),
+                tags: Arc::new(TagRepo::new(pool.clone())),
+                saved_searches: Arc::new(SavedSearchRepo::new(pool.clone())),
+            },
+            Self::Sqlite(pool) => Stores {
+                bookmarks: Arc::new(sqlite::bookmark_repo::SqliteBookmarkRepo::new(pool.clone())),
+                permissions: Arc::new(sqlite::permission_repo::SqlitePermissionRepo::new(
+                    pool.clone(),
+                )),
+                groups: Arc::new(sqlite::group_repo::SqliteGroupRepo::new(pool.clone())),
+                user_flags: Arc::new(sqlite::user_flag_repo::SqliteUserFlagRepo::new(
+                    pool.clone(),
+                )),
+                archives: Arc::new(sqlite::archive_repo::SqliteArchiveRepo::new(pool.clone())),
+                scrub_policies: Arc::new(sqlite::scrub_policy_repo::SqliteScrubPolicyRepo::new(
+                    pool.clone(),
+                )),
+                audit: Arc::new(sqlite::audit_repo::SqliteAuditRepo::new(pool.clone())),
+                api_keys: Arc::new(sqlite::api_key_repo::SqliteApiKeyRepo::new(pool.clone())),
+                stats: Arc::new(sqlite::stats_repo::SqliteStatsRepo::new(pool.clone())),
+                short_links: Arc::new(sqlite::short_link_repo::SqliteShortLinkRepo::new(
+                    pool.clone(),
+                )),
+                changes: Arc::new(sqlite::change_repo::SqliteChangeRepo::new(pool.clone())),
+                idempotency: Arc::new(sqlite::idempotency_repo::SqliteIdempotencyRepo::new(
+                    pool.clone(),
+                )),
+                share_notifications: Arc::new(
+                    sqlite::share_notification_repo::SqliteShareNotificationRepo::new(pool.clone()),
+                ),
+                permission_templates: Arc::new(
+                    sqlite::permission_template_repo::SqlitePermissionTemplateRepo::new(
+                        pool.clone(),
+                    ),
+                ),
+                auto_tag_rules: Arc::new(sqlite::auto_tag_rule_repo::SqliteAutoTagRuleRepo::new(
+                    pool.clone(),
+                )),
+                access_requests: Arc::new(
+                    sqlite::access_request_repo::SqliteAccessRequestRepo::new(pool.clone()),
+                ),
+                user_data: Arc::new(sqlite::user_data_repo::SqliteUserDataRepo::new(pool.clone())),
+                field_keys: Arc::new(sqlite::field_key_repo::SqliteFieldKeyRepo::new(pool.clone())),
+                tags: Arc::new(sqlite::tag_repo::SqliteTagRepo::new(pool.clone())),
+                saved_searches: Arc::new(sqlite::saved_search_repo::SqliteSavedSearchRepo::new(
+                    pool.clone(),
+                )),
+            },
+        }
+    }
 }
@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// A tenant data key as stored: wrapped by the key-encryption key `kek_id`.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FieldKeyRow {
+    pub id: Uuid,
+    pub tenant_id: i32,
+    pub wrapped_key: String,
+    pub kek_id: String,
+    pub create_time: DateTime<Utc>,
+}
+
+/// Storage for the data keys private bookmarks are sealed with.
+#[tonic::async_trait]
+pub trait FieldKeyStore: Send + Sync {
+    /// The tenant's newest key, used to seal new values.
+    async fn current(&self, tenant_id: i32) -> anyhow::Result<Option<FieldKeyRow>>;
+
+    /// Keys are looked up by ID alone: a sealed value names its key.
+    async fn get(&self, id: Uuid) -> anyhow::Result<Option<FieldKeyRow>>;
+
+    /// Store a new key. A key that already exists is left unchanged.
+    async fn insert(&self, key: &FieldKeyRow) -> anyhow::Result<()>;
+}
+
+#[derive(Clone)]
+pub struct FieldKeyRepo {
+    pool: PgPool,
+}
+
+impl FieldKeyRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl FieldKeyStore for FieldKeyRepo {
+    async fn current(&self, tenant_id: i32) -> anyhow::Result<Option<FieldKeyRow>> {
+        let row = sqlx::query_as::<_, FieldKeyRow>(
+            r#"
+            SELECT * FROM bookmark_field_keys
+            WHERE tenant_id = $1
+            ORDER BY create_time DESC, id
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn get(&self, id: Uuid) -> anyhow::Result<Option<FieldKeyRow>> {
+        let row =
+            sqlx::query_as::<_, FieldKeyRow>("SELECT * FROM bookmark_field_keys WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(row)
+    }
+
+    async fn insert(&self, key: &FieldKeyRow) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO bookmark_field_keys (id, tenant_id, wrapped_key, kek_id, create_time)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(key.id)
+        .bind(key.tenant_id)
+        .bind(&key.wrapped_key)
+        .bind(&key.kek_id)
+        .bind(key.create_time)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,500 @@
+//! Filter expressions for listing and searching bookmarks, such as
+//! `tag:rust AND domain:github.com AND created>2024-01-01`.
+//!
+//! Terms are `field` `op` `value`. Values may be double-quoted to include
+//! spaces or parentheses. Terms combine with `AND` (also implied between
+//! adjacent terms), `OR`, `NOT` or a leading `-`, and parentheses; `AND`
+//! binds tighter than `OR`.
+//!
+//! | field     | ops                  | value                                      |
+//! |-----------|----------------------|--------------------------------------------|
+//! | `tag`     | `:`                  | tag, or namespace ending in `/`            |
+//! | `domain`  | `:`                  | host; also matches its subdomains          |
+//! | `kind`    | `:`                  | `article`, `video`, `pdf`, `repo`, ...     |
+//! | `title`   | `:`                  | case-insensitive substring                 |
+//! | `created` | `:` `>` `>=` `<` `<=` | `YYYY-MM-DD` (a whole UTC day) or RFC 3339 |
+//! | `updated` | as `created`         | as `created`                               |
+//!
+//! Expressions are parsed here and rendered into parameterized SQL by each
+//! store, which supplies the SQL for single predicates.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::data::bookmark_repo::{BookmarkKind, TAG_SEPARATOR};
+
+/// Longest accepted expression, in bytes.
+pub const MAX_FILTER_LEN: usize = 1024;
+/// Most predicates in one expression.
+const MAX_TERMS: usize = 32;
+/// Deepest nesting of parentheses and negations.
+const MAX_DEPTH: usize = 16;
+
+/// A parsed filter expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    And(Vec<FilterExpr>),
+    Or(Vec<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Match(Predicate),
+}
+
+/// A single condition on a bookmark.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    /// A tag matching this filter; see [`crate::data::bookmark_repo::tag_matches`].
+    Tag(String),
+    /// Lower-case host without `www.`; matches the host and its subdomains.
+    Domain(String),
+    Kind(BookmarkKind),
+    /// Case-insensitive substring of the title.
+    Title(String),
+    Time(TimeField, Cmp, DateTime<Utc>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeField {
+    Created,
+    Updated,
+}
+
+impl TimeField {
+    pub fn column(&self) -> &'static str {
+        match self {
+            Self::Created => "create_time",
+            Self::Updated => "update_time",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        }
+    }
+}
+
+/// A value bound for a predicate.
+#[derive(Debug, Clone)]
+pub enum FilterParam {
+    Text(String),
+    Time(DateTime<Utc>),
+}
+
+impl Predicate {
+    fn param(&self) -> FilterParam {
+        match self {
+            Self::Tag(v) | Self::Domain(v) | Self::Title(v) => FilterParam::Text(v.clone()),
+            Self::Kind(k) => FilterParam::Text(k.as_str().to_string()),
+            Self::Time(_, _, at) => FilterParam::Time(*at),
+        }
+    }
+}
+
+impl FilterExpr {
+    /// Parse an expression; errors describe the first problem found.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        if input.len() > MAX_FILTER_LEN {
+            return Err(format!("filter is longer than {MAX_FILTER_LEN} bytes"));
+        }
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+            terms: 0,
+        };
+        if parser.tokens.is_empty() {
+            return Err("filter is empty".to_string());
+        }
+        let expr = parser.parse_or(0)?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(expr),
+            Some(Token::RParen) => Err("unbalanced ')'".to_string()),
+            Some(token) => Err(format!("unexpected {token}")),
+        }
+    }
+
+    /// Render as a SQL condition. `predicate` renders one predicate given the
+    /// placeholder its value is bound to; values are numbered from
+    /// `first_param` and returned in binding order.
+    pub fn to_sql(
+        &self,
+        first_param: usize,
+        predicate: &dyn Fn(&Predicate, &str) -> String,
+    ) -> (String, Vec<FilterParam>) {
+        let mut params = Vec::new();
+        let sql = self.write_sql(first_param, predicate, &mut params);
+        (sql, params)
+    }
+
+    fn write_sql(
+        &self,
+        first_param: usize,
+        predicate: &dyn Fn(&Predicate, &str) -> String,
+        params: &mut Vec<FilterParam>,
+    ) -> String {
+        let join = |items: &[FilterExpr], op: &str, params: &mut Vec<FilterParam>| {
+            let parts: Vec<String> = items
+                .iter()
+                .map(|e| e.write_sql(first_param, predicate, params))
+                .collect();
+            format!("({})", parts.join(op))
+        };
+        match self {
+            Self::And(items) => join(items, " AND ", params),
+            Self::Or(items) => join(items, " OR ", params),
+            Self::Not(inner) => format!("NOT {}", inner.write_sql(first_param, predicate, params)),
+            Self::Match(p) => {
+                let placeholder = format!("${}", first_param + params.len());
+                params.push(p.param());
+                format!("({})", predicate(p, &placeholder))
+            }
+        }
+    }
+}
+
+/// `expr` as a clause to append to a `WHERE`, starting with `AND`, with its
+/// values bound from `$first_param`; empty without an expression.
+pub fn and_clause(
+    expr: Option<&FilterExpr>,
+    first_param: usize,
+    predicate: &dyn Fn(&Predicate, &str) -> String,
+) -> (String, Vec<FilterParam>) {
+    match expr {
+        Some(expr) => {
+            let (sql, params) = expr.to_sql(first_param, predicate);
+            (format!("AND {sql}"), params)
+        }
+        None => (String::new(), Vec::new()),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Term(String),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LParen => f.write_str("'('"),
+            Self::RParen => f.write_str("')'"),
+            Self::And => f.write_str("AND"),
+            Self::Or => f.write_str("OR"),
+            Self::Not => f.write_str("NOT"),
+            Self::Term(t) => write!(f, "{t:?}"),
+        }
+    }
+}
+
+/// Split into parentheses, keywords and terms. Quotes group characters into
+/// the current term and are dropped; a quoted word is never a keyword.
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            _ => {
+                let mut term = String::new();
+                let mut quoted = false;
+                while let Some(&c) = chars.peek() {
+                    if c == '"' {
+                        chars.next();
+                        quoted = true;
+                        loop {
+                            match chars.next() {
+                                Some('"') => break,
+                                Some(c) => term.push(c),
+                                None => return Err("unterminated quote".to_string()),
+                            }
+                        }
+                    } else if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    } else {
+                        chars.next();
+                        term.push(c);
+                    }
+                }
+                match term.as_str() {
+                    "AND" if !quoted => tokens.push(Token::And),
+                    "OR" if !quoted => tokens.push(Token::Or),
+                    "NOT" if !quoted => tokens.push(Token::Not),
+                    _ => match term.strip_prefix('-') {
+                        Some(rest) if !rest.is_empty() => {
+                            tokens.push(Token::Not);
+                            tokens.push(Token::Term(rest.to_string()));
+                        }
+                        _ => tokens.push(Token::Term(term)),
+                    },
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    terms: usize,
+}
+
+impl Parser {
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self, depth: usize) -> Result<FilterExpr, String> {
+        let mut items = vec![self.parse_and(depth)?];
+        while self.eat(&Token::Or) {
+            items.push(self.parse_and(depth)?);
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            FilterExpr::Or(items)
+        })
+    }
+
+    fn parse_and(&mut self, depth: usize) -> Result<FilterExpr, String> {
+        let mut items = vec![self.parse_unary(depth)?];
+        loop {
+            if self.eat(&Token::And) {
+                items.push(self.parse_unary(depth)?);
+                continue;
+            }
+            match self.tokens.get(self.pos) {
+                None | Some(Token::RParen) | Some(Token::Or) => break,
+                Some(_) => items.push(self.parse_unary(depth)?),
+            }
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            FilterExpr::And(items)
+        })
+    }
+
+    fn parse_unary(&mut self, depth: usize) -> Result<FilterExpr, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("filter nests deeper than {MAX_DEPTH} levels"));
+        }
+        match self.tokens.get(self.pos) {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(FilterExpr::Not(Box::new(self.parse_unary(depth + 1)?)))
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let expr = self.parse_or(depth + 1)?;
+                if !self.eat(&Token::RParen) {
+                    return Err("missing ')'".to_string());
+                }
+                Ok(expr)
+            }
+            Some(Token::Term(term)) => {
+                self.terms += 1;
+                if self.terms > MAX_TERMS {
+                    return Err(format!("filter has more than {MAX_TERMS} terms"));
+                }
+                let expr = parse_term(term)?;
+                self.pos += 1;
+                Ok(expr)
+            }
+            Some(token) => Err(format!("unexpected {token}")),
+            None => Err("filter ends early".to_string()),
+        }
+    }
+}
+
+fn parse_term(term: &str) -> Result<FilterExpr, String> {
+    let split = term
+        .find([':', '=', '<', '>'])
+        .ok_or_else(|| format!("expected field:value, got {term:?}"))?;
+    let (field, rest) = term.split_at(split);
+    // `created:>2024-01-01` reads the same as `created>2024-01-01`.
+    let rest = rest
+        .strip_prefix(':')
+        .filter(|r| r.starts_with(['<', '>']))
+        .unwrap_or(rest);
+    let (op, value) = [">=", "<=", ">", "<", ":", "="]
+        .into_iter()
+        .find_map(|op| rest.strip_prefix(op).map(|v| (op, v.trim())))
+        .unwrap_or(("", rest));
+    if value.is_empty() {
+        return Err(format!("{field} needs a value"));
+    }
+
+    let field = field.to_ascii_lowercase();
+    let time_field = match field.as_str() {
+        "created" => Some(TimeField::Created),
+        "updated" => Some(TimeField::Updated),
+        _ => None,
+    };
+    if let Some(time_field) = time_field {
+        return parse_time(time_field, op, value);
+    }
+    if op != ":" && op != "=" {
+        return Err(format!("{field} only supports ':'"));
+    }
+
+    let predicate = match field.as_str() {
+        "tag" => {
+            if value.strip_suffix(TAG_SEPARATOR) == Some("") {
+                return Err("tag needs a namespace before '/'".to_string());
+            }
+            Predicate::Tag(value.to_string())
+        }
+        "domain" => {
+            let domain = value.trim_matches('.').to_ascii_lowercase();
+            let domain = domain.strip_prefix("www.").unwrap_or(&domain);
+            if domain.is_empty() || domain.contains(['/', ':']) {
+                return Err(format!("invalid domain {value:?}"));
+            }
+            Predicate::Domain(domain.to_string())
+        }
+        "kind" => {
+            let kind = format!("BOOKMARK_KIND_{}", value.to_ascii_uppercase());
+            Predicate::Kind(
+                BookmarkKind::from_str(&kind).ok_or_else(|| format!("unknown kind {value:?}"))?,
+            )
+        }
+        "title" => Predicate::Title(value.to_string()),
+        _ => return Err(format!("unknown field {field:?}")),
+    };
+    Ok(FilterExpr::Match(predicate))
+}
+
+/// A date covers its whole UTC day, so `>2024-01-01` starts on the 2nd and
+/// `:2024-01-01` spans the 1st.
+fn parse_time(field: TimeField, op: &str, value: &str) -> Result<FilterExpr, String> {
+    let at = |cmp, at| FilterExpr::Match(Predicate::Time(field, cmp, at));
+    if let Ok(day) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let start = day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        let end = start + Duration::days(1);
+        return Ok(match op {
+            ">" => at(Cmp::Ge, end),
+            ">=" => at(Cmp::Ge, start),
+            "<" => at(Cmp::Lt, start),
+            "<=" => at(Cmp::Lt, end),
+            _ => FilterExpr::And(vec![at(Cmp::Ge, start), at(Cmp::Lt, end)]),
+        });
+    }
+    let time = DateTime::parse_from_rfc3339(value)
+        .map_err(|_| format!("expected YYYY-MM-DD or an RFC 3339 time, got {value:?}"))?
+        .with_timezone(&Utc);
+    Ok(match op {
+        ">" => at(Cmp::Gt, time),
+        ">=" => at(Cmp::Ge, time),
+        "<" => at(Cmp::Lt, time),
+        "<=" => at(Cmp::Le, time),
+        _ => FilterExpr::And(vec![at(Cmp::Ge, time), at(Cmp::Le, time)]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_precedence_negation_and_dates() {
+        let expr = FilterExpr::parse(
+            r#"tag:rust domain:www.GitHub.com OR (-kind:video AND title:"rust book") created>2024-01-01"#,
+        )
+        .unwrap();
+        let m = FilterExpr::Match;
+        assert_eq!(
+            expr,
+            FilterExpr::Or(vec![
+                FilterExpr::And(vec![
+                    m(Predicate::Tag("rust".into())),
+                    m(Predicate::Domain("github.com".into())),
+                ]),
+                FilterExpr::And(vec![
+                    FilterExpr::And(vec![
+                        FilterExpr::Not(Box::new(m(Predicate::Kind(BookmarkKind::Video)))),
+                        m(Predicate::Title("rust book".into())),
+                    ]),
+                    m(Predicate::Time(
+                        TimeField::Created,
+                        Cmp::Ge,
+                        day("2024-01-02T00:00:00Z")
+                    )),
+                ]),
+            ])
+        );
+
+        let (sql, params) = expr.to_sql(3, &|p, n| match p {
+            Predicate::Time(_, cmp, _) => format!("t {} {n}", cmp.as_sql()),
+            _ => format!("x = {n}"),
+        });
+        assert_eq!(
+            sql,
+            "(((x = $3) AND (x = $4)) OR ((NOT (x = $5) AND (x = $6)) AND (t >= $7)))"
+        );
+        assert_eq!(params.len(), 5);
+    }
+
+    #[test]
+    fn rejects_malformed_filters() {
+        for bad in [
+            "",
+            "rust",
+            "tag:",
+            "tag>rust",
+            "color:red",
+            "kind:movie",
+            "created>yesterday",
+            "(tag:a",
+            "tag:a)",
+            "tag:a OR",
+            r#"title:"open"#,
+            "tag:/",
+        ] {
+            assert!(FilterExpr::parse(bad).is_err(), "{bad:?}");
+        }
+        let many = vec!["tag:a"; MAX_TERMS + 1].join(" ");
+        assert!(FilterExpr::parse(&many).is_err());
+        let deep = format!(
+            "{}tag:a{}",
+            "(".repeat(MAX_DEPTH + 1),
+            ")".repeat(MAX_DEPTH + 1)
+        );
+        assert!(FilterExpr::parse(&deep).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct GroupMemberRow {
+    pub tenant_id: i32,
+    pub group_id: String,
+    pub user_id: String,
+    pub added_by: Option<i32>,
+    pub create_time: DateTime<Utc>,
+}
+
+/// Group membership storage.
+#[tonic::async_trait]
+pub trait GroupStore: Send + Sync {
+    /// IDs of every group the user belongs to within a tenant.
+    async fn list_groups_for_user(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+    ) -> anyhow::Result<Vec<String>>;
+
+    async fn list_members(
+        &self,
+        tenant_id: i32,
+        group_id: &str,
+    ) -> anyhow::Result<Vec<GroupMemberRow>>;
+
+    async fn add_member(
+        &self,
+        tenant_id: i32,
+        group_id: &str,
+        user_id: &str,
+        added_by: Option<i32>,
+    ) -> anyhow::Result<GroupMemberRow>;
+
+    async fn remove_member(
+        &self,
+        tenant_id: i32,
+        group_id: &str,
+        user_id: &str,
+    ) -> anyhow::Result<bool>;
+}
+
+#[derive(Clone)]
+pub struct GroupRepo {
+    pool: PgPool,
+}
+
+impl GroupRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl GroupStore for GroupRepo {
+    async fn list_groups_for_user(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        let ids: Vec<(String,)> = sqlx::query_as(
+            "SELECT group_id FROM bookmark_group_members WHERE tenant_id = $1 AND user_id = $2",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids.into_iter().map(|(id,)| id).collect())
+    }
+
+    async fn list_members(
+        &self,
+        tenant_id: i32,
+        group_id: &str,
+    ) -> anyhow::Result<Vec<GroupMemberRow>> {
+        let rows = sqlx::query_as::<_, GroupMemberRow>(
+            r#"
+            SELECT * FROM bookmark_group_members
+            WHERE tenant_id = $1 AND group_id = $2
+            ORDER BY create_time
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn add_member(
+        &self,
+        tenant_id: i32,
+        group_id: &str,
+        user_id: &str,
+        added_by: Option<i32>,
+    ) -> anyhow::Result<GroupMemberRow> {
+        let row = sqlx::query_as::<_, GroupMemberRow>(
+            r#"
+            INSERT INTO bookmark_group_members (tenant_id, group_id, user_id, added_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (tenant_id, group_id, user_id) DO UPDATE
+                SET added_by = bookmark_group_members.added_by
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(group_id)
+        .bind(user_id)
+        .bind(added_by)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    async fn remove_member(
+        &self,
+        tenant_id: i32,
+        group_id: &str,
+        user_id: &str,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            "DELETE FROM bookmark_group_members WHERE tenant_id = $1 AND group_id = $2 AND user_id = $3",
+        )
+        .bind(tenant_id)
+        .bind(group_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
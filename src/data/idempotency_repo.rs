@@ -0,0 +1,167 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// Scope of an idempotency key: keys are only unique per caller and method.
+#[derive(Debug, Clone)]
+pub struct IdempotencyKey {
+    pub tenant_id: i32,
+    pub user_id: String,
+    pub method: &'static str,
+    pub key: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct IdempotencyRow {
+    pub request_hash: Vec<u8>,
+    /// Encoded response; `None` while the first request is still running.
+    pub response: Option<Vec<u8>>,
+}
+
+#[tonic::async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    /// Record that a request with this key has started, holding the key until
+    /// `expires_at`. Returns `None` if the caller now owns the key, or the
+    /// existing unexpired entry otherwise.
+    async fn claim(
+        &self,
+        key: &IdempotencyKey,
+        request_hash: &[u8],
+        expires_at: DateTime<Utc>,
+    ) -> anyhow::Result<Option<IdempotencyRow>>;
+
+    /// Store the response of a claimed request, kept for replay until `expires_at`.
+    async fn complete(
+        &self,
+        key: &IdempotencyKey,
+        response: &[u8],
+        expires_at: DateTime<Utc>,
+    ) -> anyhow::Result<()>;
+
+    /// Forget a claimed request that failed so it can be retried.
+    async fn release(&self, key: &IdempotencyKey) -> anyhow::Result<()>;
+
+    async fn purge_expired(&self) -> anyhow::Result<u64>;
+}
+
+#[derive(Clone)]
+pub struct IdempotencyRepo {
+    pool: PgPool,
+}
+
+impl IdempotencyRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl IdempotencyStore for IdempotencyRepo {
+    async fn claim(
+        &self,
+        key: &IdempotencyKey,
+        request_hash: &[u8],
+        expires_at: DateTime<Utc>,
+    ) -> anyhow::Result<Option<IdempotencyRow>> {
+        // An expired entry no longer reserves the key.
+        sqlx::query(
+            r#"
+            DELETE FROM bookmark_idempotency_keys
+            WHERE tenant_id = $1 AND user_id = $2 AND method = $3 AND idempotency_key = $4
+              AND expires_at <= NOW()
+            "#,
+        )
+        .bind(key.tenant_id)
+        .bind(&key.user_id)
+        .bind(key.method)
+        .bind(&key.key)
+        .execute(&self.pool)
+        .await?;
+
+        let claimed = sqlx::query(
+            r#"
+            INSERT INTO bookmark_idempotency_keys
+                (tenant_id, user_id, method, idempotency_key, request_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(key.tenant_id)
+        .bind(&key.user_id)
+        .bind(key.method)
+        .bind(&key.key)
+        .bind(request_hash)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?
+        .rows_affected()
+            > 0;
+        if claimed {
+            return Ok(None);
+        }
+
+        let row = sqlx::query_as::<_, IdempotencyRow>(
+            r#"
+            SELECT request_hash, response FROM bookmark_idempotency_keys
+            WHERE tenant_id = $1 AND user_id = $2 AND method = $3 AND idempotency_key = $4
+            "#,
+        )
+        .bind(key.tenant_id)
+        .bind(&key.user_id)
+        .bind(key.method)
+        .bind(&key.key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn complete(
+        &self,
+        key: &IdempotencyKey,
+        response: &[u8],
+        expires_at: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE bookmark_idempotency_keys SET response = $5, expires_at = $6
+            WHERE tenant_id = $1 AND user_id = $2 AND method = $3 AND idempotency_key = $4
+            "#,
+        )
+        .bind(key.tenant_id)
+        .bind(&key.user_id)
+        .bind(key.method)
+        .bind(&key.key)
+        .bind(response)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn release(&self, key: &IdempotencyKey) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM bookmark_idempotency_keys
+            WHERE tenant_id = $1 AND user_id = $2 AND method = $3 AND idempotency_key = $4
+              AND response IS NULL
+            "#,
+        )
+        .bind(key.tenant_id)
+        .bind(&key.user_id)
+        .bind(key.method)
+        .bind(&key.key)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn purge_expired(&self) -> anyhow::Result<u64> {
+        let result = sqlx::query("DELETE FROM bookmark_idempotency_keys WHERE expires_at <= NOW()")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
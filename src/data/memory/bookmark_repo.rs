@@ -0,0 +1,790 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use super::{offset, page_with_lookahead, MemoryDb, Tables};
+use crate::authz::relations::{Permission, Relation, ResourceType, SubjectType};
+use crate::data::bookmark_repo::{
+    split_tags, tag_matches, BookmarkChanges, BookmarkFilter, BookmarkKind, BookmarkRow,
+    BookmarkStore, BulkTagFilter, NewBookmark, RevisionRow, SearchRow, TagOrder, TagPathRow,
+    TagStatRow, TAG_SEPARATOR,
+};
+use crate::data::filter_expr::{Cmp, FilterExpr, Predicate, TimeField};
+use crate::data::user_flag_repo::ReadingStatus;
+use crate::service::bookmark_kind::classify_url;
+use crate::service::url_normalizer::normalize_url;
+
+#[derive(Clone)]
+pub struct MemoryBookmarkRepo {
+    db: MemoryDb,
+}
+
+impl MemoryBookmarkRepo {
+    pub fn new(db: MemoryDb) -> Self {
+        Self { db }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn insert(
+    tables: &mut Tables,
+    tenant_id: i32,
+    id: Uuid,
+    url: &str,
+    title: &str,
+    description: &str,
+    tags: &[String],
+    created_by: Option<i32>,
+    original_url: Option<&str>,
+    kind: BookmarkKind,
+    is_private: bool,
+) -> BookmarkRow {
+    let now = Utc::now();
+    let row = BookmarkRow {
+        id,
+        tenant_id,
+        url: url.to_string(),
+        title: title.to_string(),
+        description: description.to_string(),
+        tags: tags.to_vec(),
+        created_by,
+        create_time: now,
+        update_time: now,
+        original_url: original_url.map(str::to_string),
+        normalized_url: (!is_private).then(|| normalize_url(url)),
+        visit_count: 0,
+        last_visited_at: None,
+        notes: String::new(),
+        metadata: Json(HashMap::new()),
+        kind: Some(kind.as_str().to_string()),
+        is_private,
+    };
+    tables.bookmarks.push(row.clone());
+    row
+}
+
+fn apply_changes(
+    tables: &mut Tables,
+    tenant_id: i32,
+    changes: &BookmarkChanges,
+    edited_by: Option<i32>,
+) -> Option<BookmarkRow> {
+    let in_tenant = tables
+        .bookmarks
+        .iter()
+        .any(|b| b.id == changes.id && b.tenant_id == tenant_id);
+    if !in_tenant || !tables.record_revision(changes.id, edited_by) {
+        return None;
+    }
+    let b = tables.bookmarks.iter_mut().find(|b| b.id == changes.id)?;
+    if let Some(url) = &changes.url {
+        b.url = url.clone();
+        b.original_url = changes.original_url.clone();
+        b.normalized_url = changes.normalized_url();
+    }
+    if let Some(title) = &changes.title {
+        b.title = title.clone();
+    }
+    if let Some(description) = &changes.description {
+        b.description = description.clone();
+    }
+    if let Some(tags) = &changes.tags {
+        b.tags = tags.clone();
+    }
+    if let Some(metadata) = &changes.metadata {
+        b.metadata = Json(metadata.clone());
+    }
+    if let Some(kind) = changes.kind {
+        b.kind = Some(kind.as_str().to_string());
+    }
+    if let Some(notes) = &changes.notes {
+        b.notes = notes.clone();
+    }
+    if let Some(is_private) = changes.is_private {
+        b.is_private = is_private;
+    }
+    b.update_time = Utc::now();
+    Some(b.clone())
+}
+
+fn find_mut(tables: &mut Tables, tenant_id: i32, id: Uuid) -> Option<&mut BookmarkRow> {
+    tables
+        .bookmarks
+        .iter_mut()
+        .find(|b| b.id == id && b.tenant_id == tenant_id)
+}
+
+fn delete_with_tuples(tables: &mut Tables, tenant_id: i32, id: Uuid) -> bool {
+    let before = tables.bookmarks.len();
+    tables
+        .bookmarks
+        .retain(|b| !(b.id == id && b.tenant_id == tenant_id));
+    if tables.bookmarks.len() == before {
+        return false;
+    }
+    tables.revisions.retain(|r| r.bookmark_id != id);
+    let resource_id = id.to_string();
+    tables.permissions.retain(|p| {
+        !(p.tenant_id == tenant_id
+            && p.resource_type == ResourceType::Bookmark.as_str()
+            && p.resource_id == resource_id)
+    });
+    true
+}
+
+/// Per-user flags are not modelled, so every bookmark is unpinned and unread.
+fn matches_filter(b: &BookmarkRow, filter: &BookmarkFilter) -> bool {
+    filter.created_after.is_none_or(|t| b.create_time >= t)
+        && filter.created_before.is_none_or(|t| b.create_time < t)
+        && filter.created_by.is_none_or(|c| b.created_by == Some(c))
+        && !filter.pinned_only
+        && filter
+            .reading_status
+            .is_none_or(|s| s == ReadingStatus::Unread)
+        && filter
+            .metadata
+            .as_ref()
+            .is_none_or(|m| m.iter().all(|(k, v)| b.metadata.get(k) == Some(v)))
+        && filter
+            .kind
+            .is_none_or(|k| b.kind.as_deref() == Some(k.as_str()))
+        && filter
+            .tag
+            .as_deref()
+            .is_none_or(|f| b.tags.iter().any(|t| tag_matches(t, f)))
+}
+
+/// `b` satisfies a filter expression, as the SQL the stores render for it.
+fn matches_expr(b: &BookmarkRow, expr: &FilterExpr) -> bool {
+    match expr {
+        FilterExpr::And(items) => items.iter().all(|e| matches_expr(b, e)),
+        FilterExpr::Or(items) => items.iter().any(|e| matches_expr(b, e)),
+        FilterExpr::Not(inner) => !matches_expr(b, inner),
+        FilterExpr::Match(Predicate::Tag(f)) => b.tags.iter().any(|t| tag_matches(t, f)),
+        FilterExpr::Match(Predicate::Domain(d)) => matches_domain(b, d),
+        FilterExpr::Match(Predicate::Kind(k)) => b.kind.as_deref() == Some(k.as_str()),
+        FilterExpr::Match(Predicate::Title(t)) => {
+            b.title.to_lowercase().contains(&t.to_lowercase())
+        }
+        FilterExpr::Match(Predicate::Time(field, cmp, at)) => {
+            let time = match field {
+                TimeField::Created => b.create_time,
+                TimeField::Updated => b.update_time,
+            };
+            match cmp {
+                Cmp::Lt => time < *at,
+                Cmp::Le => time <= *at,
+                Cmp::Gt => time > *at,
+                Cmp::Ge => time >= *at,
+            }
+        }
+    }
+}
+
+/// Every whitespace-separated term occurs in the title, description or tags,
+/// ignoring case; a rough stand-in for `websearch_to_tsquery`.
+fn matches_query(b: &BookmarkRow, query: &str) -> bool {
+    let text = format!("{} {} {}", b.title, b.description, b.tags.join(" ")).to_lowercase();
+    query
+        .split_whitespace()
+        .all(|term| text.contains(&term.to_lowercase()))
+}
+
+/// Host part of `normalized_url` is `domain` or one of its subdomains.
+fn matches_domain(b: &BookmarkRow, domain: &str) -> bool {
+    let Some(normalized) = &b.normalized_url else {
+        return false;
+    };
+    let host = normalized.split(['/', '?', ':']).next().unwrap_or_default();
+    host == domain || host.ends_with(&format!(".{domain}"))
+}
+
+/// Newest first, paged by keyset from `after` when set.
+fn page_newest_first(
+    mut rows: Vec<BookmarkRow>,
+    after: Option<(DateTime<Utc>, Uuid)>,
+    page: u32,
+    page_size: u32,
+) -> (Vec<BookmarkRow>, i64) {
+    let total = rows.len() as i64;
+    rows.sort_by_key(|r| std::cmp::Reverse((r.create_time, r.id)));
+    if let Some(cursor) = after {
+        rows.retain(|b| (b.create_time, b.id) < cursor);
+    }
+    let skip = if after.is_some() {
+        0
+    } else {
+        offset(page, page_size)
+    };
+    (page_with_lookahead(rows, skip, page_size), total)
+}
+
+#[tonic::async_trait]
+impl BookmarkStore for MemoryBookmarkRepo {
+    #[allow(clippy::too_many_arguments)]
+    async fn create(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+        url: &str,
+        title: &str,
+        description: &str,
+        tags: &[String],
+        created_by: Option<i32>,
+        original_url: Option<&str>,
+        kind: BookmarkKind,
+        is_private: bool,
+    ) -> anyhow::Result<BookmarkRow> {
+        let mut tables = self.db.lock();
+        Ok(insert(
+            &mut tables,
+            tenant_id,
+            id,
+            url,
+            title,
+            description,
+            tags,
+            created_by,
+            original_url,
+            kind,
+            is_private,
+        ))
+    }
+
+    async fn get_by_id(&self, tenant_id: i32, id: Uuid) -> anyhow::Result<Option<BookmarkRow>> {
+        let tables = self.db.lock();
+        Ok(tables
+            .bookmarks
+            .iter()
+            .find(|b| b.id == id && b.tenant_id == tenant_id)
+            .cloned())
+    }
+
+    async fn list_by_tenant(
+        &self,
+        tenant_id: i32,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<BookmarkRow>, i64)> {
+        let tables = self.db.lock();
+        let mut rows: Vec<BookmarkRow> = tables
+            .bookmarks
+            .iter()
+            .filter(|b| b.tenant_id == tenant_id)
+            .cloned()
+            .collect();
+        let total = rows.len() as i64;
+        rows.sort_by_key(|r| std::cmp::Reverse(r.create_time));
+        let rows = rows
+            .into_iter()
+            .skip(offset(page, page_size))
+            .take(page_size as usize)
+            .collect();
+        Ok((rows, total))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn list_by_ids(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+        filter: &BookmarkFilter,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<BookmarkRow>, i64)> {
+        let tables = self.db.lock();
+        let rows = tables
+            .bookmarks
+            .iter()
+            .filter(|b| {
+                b.tenant_id == tenant_id && ids.contains(&b.id) && matches_filter(b, filter)
+            })
+            .cloned()
+            .collect();
+        Ok(page_newest_first(rows, after, page, page_size))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn list_accessible(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        filter: &BookmarkFilter,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<BookmarkRow>, i64)> {
+        let tables = self.db.lock();
+        let rows = tables
+            .bookmarks
+            .iter()
+            .filter(|b| {
+                (b.tenant_id == tenant_id && tables.can_reach(b, subjects, None)
+                    || filter.federated && tables.federated_to(b, tenant_id, &filter.user_id))
+                    && matches_filter(b, filter)
+                    && filter.expr.as_ref().is_none_or(|e| matches_expr(b, e))
+            })
+            .cloned()
+            .collect();
+        let after = after.filter(|_| !filter.manual_order);
+        Ok(page_newest_first(rows, after, page, page_size))
+    }
+
+    async fn update(
+        &self,
+        tenant_id: i32,
+        changes: &BookmarkChanges,
+        edited_by: Option<i32>,
+    ) -> anyhow::Result<Option<BookmarkRow>> {
+        let mut tables = self.db.lock();
+        Ok(apply_changes(&mut tables, tenant_id, changes, edited_by))
+    }
+
+    async fn batch_create(
+        &self,
+        tenant_id: i32,
+        owner_id: &str,
+        created_by: Option<i32>,
+        items: &[NewBookmark],
+    ) -> anyhow::Result<Vec<anyhow::Result<BookmarkRow>>> {
+        let mut tables = self.db.lock();
+        let mut results = Vec::with_capacity(items.len());
+        for item in items {
+            let row = insert(
+                &mut tables,
+                tenant_id,
+                item.id,
+                &item.url,
+                &item.title,
+                &item.description,
+                &item.tags,
+                created_by,
+                item.original_url.as_deref(),
+                item.kind,
+                item.is_private,
+            );
+            tables.upsert_permission(
+                tenant_id,
+                ResourceType::Bookmark,
+                &row.id.to_string(),
+                Relation::Owner,
+                SubjectType::User,
+                owner_id,
+                created_by,
+                None,
+            );
+            results.push(Ok(row));
+        }
+        Ok(results)
+    }
+
+    async fn batch_update(
+        &self,
+        tenant_id: i32,
+        items: &[BookmarkChanges],
+        edited_by: Option<i32>,
+    ) -> anyhow::Result<Vec<anyhow::Result<Option<BookmarkRow>>>> {
+        let mut tables = self.db.lock();
+        Ok(items
+            .iter()
+            .map(|item| Ok(apply_changes(&mut tables, tenant_id, item, edited_by)))
+            .collect())
+    }
+
+    async fn batch_delete(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+    ) -> anyhow::Result<Vec<anyhow::Result<bool>>> {
+        let mut tables = self.db.lock();
+        Ok(ids
+            .iter()
+            .map(|id| Ok(delete_with_tuples(&mut tables, tenant_id, *id)))
+            .collect())
+    }
+
+    async fn update_tags_bulk(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        filter: &BulkTagFilter,
+        add: &[String],
+        remove: &[String],
+        edited_by: Option<i32>,
+    ) -> anyhow::Result<Vec<BookmarkRow>> {
+        let relations: Vec<Relation> = Relation::granting(Permission::Write).collect();
+        let mut tables = self.db.lock();
+        let targets: Vec<Uuid> = tables
+            .bookmarks
+            .iter()
+            .filter(|b| {
+                b.tenant_id == tenant_id
+                    && tables.can_reach(b, subjects, Some(&relations))
+                    && filter
+                        .tag
+                        .as_deref()
+                        .is_none_or(|f| b.tags.iter().any(|t| tag_matches(t, f)))
+                    && filter
+                        .domain
+                        .as_deref()
+                        .is_none_or(|d| matches_domain(b, d))
+                    && filter.query.as_deref().is_none_or(|q| matches_query(b, q))
+                    && (b.tags.iter().any(|t| remove.contains(t))
+                        || !add.iter().all(|t| b.tags.contains(t)))
+            })
+            .map(|b| b.id)
+            .collect();
+
+        let mut updated = Vec::with_capacity(targets.len());
+        for id in targets {
+            tables.record_revision(id, edited_by);
+            if let Some(b) = tables.bookmarks.iter_mut().find(|b| b.id == id) {
+                let mut tags: Vec<String> = b
+                    .tags
+                    .iter()
+                    .filter(|t| !remove.contains(t))
+                    .cloned()
+                    .collect();
+                tags.extend(add.iter().filter(|t| !b.tags.contains(t)).cloned());
+                b.tags = tags;
+                b.update_time = Utc::now();
+                updated.push(b.clone());
+            }
+        }
+        Ok(updated)
+    }
+
+    async fn search(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+        query: &str,
+        _search_content: bool,
+        _fuzzy_threshold: Option<f32>,
+        expr: Option<&FilterExpr>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<SearchRow>, i64)> {
+        let tables = self.db.lock();
+        let mut matches: Vec<&BookmarkRow> = tables
+            .bookmarks
+            .iter()
+            .filter(|b| {
+                b.tenant_id == tenant_id
+                    && ids.contains(&b.id)
+                    && matches_query(b, query)
+                    && expr.is_none_or(|e| matches_expr(b, e))
+            })
+            .collect();
+        matches.sort_by_key(|r| std::cmp::Reverse(r.create_time));
+        let total = matches.len() as i64;
+        let rows = matches
+            .into_iter()
+            .skip(offset(page, page_size))
+            .take(page_size as usize)
+            .map(|b| SearchRow {
+                bookmark: b.clone(),
+                matched_content: false,
+                fuzzy_match: false,
+                snippet: None,
+                total_count: total,
+            })
+            .collect();
+        Ok((rows, total))
+    }
+
+    async fn update_notes(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+        notes: &str,
+    ) -> anyhow::Result<Option<BookmarkRow>> {
+        let mut tables = self.db.lock();
+        Ok(find_mut(&mut tables, tenant_id, id).map(|b| {
+            b.notes = notes.to_string();
+            b.update_time = Utc::now();
+            b.clone()
+        }))
+    }
+
+    async fn record_visit(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+    ) -> anyhow::Result<Option<(i32, DateTime<Utc>)>> {
+        let mut tables = self.db.lock();
+        Ok(find_mut(&mut tables, tenant_id, id).map(|b| {
+            let now = Utc::now();
+            b.visit_count += 1;
+            b.last_visited_at = Some(now);
+            (b.visit_count, now)
+        }))
+    }
+
+    async fn list_most_visited(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+        limit: u32,
+    ) -> anyhow::Result<Vec<BookmarkRow>> {
+        let tables = self.db.lock();
+        let mut rows: Vec<BookmarkRow> = tables
+            .bookmarks
+            .iter()
+            .filter(|b| b.tenant_id == tenant_id && ids.contains(&b.id) && b.visit_count > 0)
+            .cloned()
+            .collect();
+        rows.sort_by(|a, b| {
+            (b.visit_count, b.last_visited_at).cmp(&(a.visit_count, a.last_visited_at))
+        });
+        rows.truncate(limit as usize);
+        Ok(rows)
+    }
+
+    async fn tag_stats(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        prefix: &str,
+        order: TagOrder,
+        limit: u32,
+    ) -> anyhow::Result<Vec<TagStatRow>> {
+        let tables = self.db.lock();
+        let prefix = prefix.to_lowercase();
+        let mut stats: BTreeMap<&str, TagStatRow> = BTreeMap::new();
+        for b in tables
+            .bookmarks
+            .iter()
+            .filter(|b| b.tenant_id == tenant_id && tables.can_reach(b, subjects, None))
+        {
+            for tag in b
+                .tags
+                .iter()
+                .filter(|t| t.to_lowercase().starts_with(&prefix))
+            {
+                let stat = stats.entry(tag).or_insert_with(|| TagStatRow {
+                    tag: tag.clone(),
+                    bookmark_count: 0,
+                    first_used: b.create_time,
+                    last_used: b.update_time,
+                    total_count: 0,
+                });
+                stat.bookmark_count += 1;
+                stat.first_used = stat.first_used.min(b.create_time);
+                stat.last_used = stat.last_used.max(b.update_time);
+            }
+        }
+
+        let total = stats.len() as i64;
+        let mut rows: Vec<TagStatRow> = stats.into_values().collect();
+        for row in &mut rows {
+            row.total_count = total;
+        }
+        rows.sort_by(|a, b| {
+            let primary = match order {
+                TagOrder::MostUsed => b.bookmark_count.cmp(&a.bookmark_count),
+                TagOrder::LeastUsed => a.bookmark_count.cmp(&b.bookmark_count),
+                TagOrder::MostRecent => b.last_used.cmp(&a.last_used),
+                TagOrder::LeastRecent => a.last_used.cmp(&b.last_used),
+            };
+            primary.then_with(|| a.tag.cmp(&b.tag))
+        });
+        rows.truncate(limit as usize);
+        Ok(rows)
+    }
+
+    async fn tag_tree(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        root: &str,
+    ) -> anyhow::Result<Vec<TagPathRow>> {
+        let under_root = |path: &str| {
+            root.is_empty()
+                || path
+                    .strip_prefix(root)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(TAG_SEPARATOR))
+        };
+        let tables = self.db.lock();
+        let mut counts: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+        for b in tables
+            .bookmarks
+            .iter()
+            .filter(|b| b.tenant_id == tenant_id && tables.can_reach(b, subjects, None))
+        {
+            let mut exact = BTreeSet::new();
+            let mut subtree = BTreeSet::new();
+            for tag in b.tags.iter().filter(|t| under_root(t)) {
+                exact.insert(tag.as_str());
+                let namespaces = tag.match_indices(TAG_SEPARATOR).map(|(i, _)| &tag[..i]);
+                subtree.extend(namespaces.chain([tag.as_str()]).filter(|p| under_root(p)));
+            }
+            for path in subtree {
+                let entry = counts.entry(path.to_string()).or_default();
+                entry.0 += i64::from(exact.contains(path));
+                entry.1 += 1;
+            }
+        }
+        Ok(counts
+            .into_iter()
+            .map(|(path, (bookmark_count, subtree_count))| TagPathRow {
+                path,
+                bookmark_count,
+                subtree_count,
+            })
+            .collect())
+    }
+
+    async fn split_tags(
+        &self,
+        tenant_id: Option<i32>,
+        delimiter: &str,
+        dry_run: bool,
+    ) -> anyhow::Result<u64> {
+        let mut tables = self.db.lock();
+        let changes: Vec<(Uuid, Vec<String>)> = tables
+            .bookmarks
+            .iter()
+            .filter(|b| {
+                tenant_id.is_none_or(|t| b.tenant_id == t)
+                    && b.tags.iter().any(|t| t.contains(delimiter))
+            })
+            .filter_map(|b| split_tags(&b.tags, delimiter).map(|tags| (b.id, tags)))
+            .collect();
+        if dry_run {
+            return Ok(changes.len() as u64);
+        }
+        for (id, tags) in &changes {
+            tables.record_revision(*id, None);
+            if let Some(b) = tables.bookmarks.iter_mut().find(|b| b.id == *id) {
+                b.tags = tags.clone();
+                b.update_time = Utc::now();
+            }
+        }
+        Ok(changes.len() as u64)
+    }
+
+    async fn find_by_normalized_url(
+        &self,
+        tenant_id: i32,
+        normalized_url: &str,
+    ) -> anyhow::Result<Vec<BookmarkRow>> {
+        let tables = self.db.lock();
+        let mut rows: Vec<BookmarkRow> = tables
+            .bookmarks
+            .iter()
+            .filter(|b| {
+                b.tenant_id == tenant_id && b.normalized_url.as_deref() == Some(normalized_url)
+            })
+            .cloned()
+            .collect();
+        rows.sort_by_key(|b| b.create_time);
+        Ok(rows)
+    }
+
+    async fn find_duplicate_groups(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+    ) -> anyhow::Result<Vec<BookmarkRow>> {
+        let tables = self.db.lock();
+        let mut groups: BTreeMap<&str, Vec<BookmarkRow>> = BTreeMap::new();
+        for b in tables
+            .bookmarks
+            .iter()
+            .filter(|b| b.tenant_id == tenant_id && ids.contains(&b.id))
+        {
+            if let Some(normalized) = &b.normalized_url {
+                groups.entry(normalized).or_default().push(b.clone());
+            }
+        }
+        Ok(groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .flat_map(|mut group| {
+                group.sort_by_key(|b| b.create_time);
+                group
+            })
+            .collect())
+    }
+
+    async fn backfill_normalized_urls(&self) -> anyhow::Result<u64> {
+        let mut tables = self.db.lock();
+        let mut updated = 0;
+        for b in tables
+            .bookmarks
+            .iter_mut()
+            .filter(|b| b.normalized_url.is_none() && !b.is_private)
+        {
+            b.normalized_url = Some(normalize_url(&b.url));
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    async fn backfill_kinds(&self) -> anyhow::Result<u64> {
+        let mut tables = self.db.lock();
+        let mut updated = 0;
+        for b in tables.bookmarks.iter_mut().filter(|b| b.kind.is_none()) {
+            b.kind = Some(classify_url(&b.url).as_str().to_string());
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    async fn list_revisions(
+        &self,
+        tenant_id: i32,
+        bookmark_id: Uuid,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<RevisionRow>, i64)> {
+        let tables = self.db.lock();
+        let mut rows: Vec<RevisionRow> = tables
+            .revisions
+            .iter()
+            .filter(|r| r.bookmark_id == bookmark_id && r.tenant_id == tenant_id)
+            .cloned()
+            .collect();
+        let total = rows.len() as i64;
+        rows.sort_by_key(|r| std::cmp::Reverse((r.create_time, r.id)));
+        let rows = rows
+            .into_iter()
+            .skip(offset(page, page_size))
+            .take(page_size as usize)
+            .collect();
+        Ok((rows, total))
+    }
+
+    async fn get_revision(
+        &self,
+        tenant_id: i32,
+        bookmark_id: Uuid,
+        revision_id: i64,
+    ) -> anyhow::Result<Option<RevisionRow>> {
+        let tables = self.db.lock();
+        Ok(tables
+            .revisions
+            .iter()
+            .find(|r| {
+                r.bookmark_id == bookmark_id && r.tenant_id == tenant_id && r.id == revision_id
+            })
+            .cloned())
+    }
+
+    async fn delete(&self, tenant_id: i32, id: Uuid) -> anyhow::Result<bool> {
+        let mut tables = self.db.lock();
+        let before = tables.bookmarks.len();
+        tables
+            .bookmarks
+            .retain(|b| !(b.id == id && b.tenant_id == tenant_id));
+        if tables.bookmarks.len() == before {
+            return Ok(false);
+        }
+        tables.revisions.retain(|r| r.bookmark_id != id);
+        Ok(true)
+    }
+}
@@ -0,0 +1,86 @@
+use chrono::Utc;
+
+use super::MemoryDb;
+use crate::data::group_repo::{GroupMemberRow, GroupStore};
+
+#[derive(Clone)]
+pub struct MemoryGroupRepo {
+    db: MemoryDb,
+}
+
+impl MemoryGroupRepo {
+    pub fn new(db: MemoryDb) -> Self {
+        Self { db }
+    }
+}
+
+#[tonic::async_trait]
+impl GroupStore for MemoryGroupRepo {
+    async fn list_groups_for_user(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        let tables = self.db.lock();
+        Ok(tables
+            .group_members
+            .iter()
+            .filter(|m| m.tenant_id == tenant_id && m.user_id == user_id)
+            .map(|m| m.group_id.clone())
+            .collect())
+    }
+
+    async fn list_members(
+        &self,
+        tenant_id: i32,
+        group_id: &str,
+    ) -> anyhow::Result<Vec<GroupMemberRow>> {
+        let tables = self.db.lock();
+        Ok(tables
+            .group_members
+            .iter()
+            .filter(|m| m.tenant_id == tenant_id && m.group_id == group_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn add_member(
+        &self,
+        tenant_id: i32,
+        group_id: &str,
+        user_id: &str,
+        added_by: Option<i32>,
+    ) -> anyhow::Result<GroupMemberRow> {
+        let mut tables = self.db.lock();
+        let existing = tables
+            .group_members
+            .iter()
+            .find(|m| m.tenant_id == tenant_id && m.group_id == group_id && m.user_id == user_id);
+        if let Some(member) = existing {
+            return Ok(member.clone());
+        }
+        let member = GroupMemberRow {
+            tenant_id,
+            group_id: group_id.to_string(),
+            user_id: user_id.to_string(),
+            added_by,
+            create_time: Utc::now(),
+        };
+        tables.group_members.push(member.clone());
+        Ok(member)
+    }
+
+    async fn remove_member(
+        &self,
+        tenant_id: i32,
+        group_id: &str,
+        user_id: &str,
+    ) -> anyhow::Result<bool> {
+        let mut tables = self.db.lock();
+        let before = tables.group_members.len();
+        tables.group_members.retain(|m| {
+            !(m.tenant_id == tenant_id && m.group_id == group_id && m.user_id == user_id)
+        });
+        Ok(tables.group_members.len() < before)
+    }
+}
@@ -0,0 +1,186 @@
+//! In-memory implementations of the bookmark, permission and group stores, for
+//! unit tests of authz and service logic without a database.
+//!
+//! The stores share one [`MemoryDb`], so permission queries that join bookmarks
+//! see bookmarks created through the bookmark store. Per-user flags and archives
+//! are not modelled: `pinned_only` matches nothing, every bookmark reads as
+//! unread, and search covers title, description and tags only.
+
+pub mod bookmark_repo;
+pub mod group_repo;
+pub mod permission_repo;
+
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::authz::relations::{Relation, ResourceType, SubjectType, WILDCARD_RESOURCE};
+use crate::data::bookmark_repo::{BookmarkRow, RevisionRow};
+use crate::data::group_repo::GroupMemberRow;
+use crate::data::permission_repo::PermissionRow;
+
+pub use bookmark_repo::MemoryBookmarkRepo;
+pub use group_repo::MemoryGroupRepo;
+pub use permission_repo::MemoryPermissionRepo;
+
+#[derive(Default)]
+struct Tables {
+    bookmarks: Vec<BookmarkRow>,
+    revisions: Vec<RevisionRow>,
+    next_revision_id: i64,
+    permissions: Vec<PermissionRow>,
+    next_permission_id: i32,
+    group_members: Vec<GroupMemberRow>,
+}
+
+impl Tables {
+    /// Whether any of `subjects` holds a tuple on the bookmark, directly or
+    /// through a wildcard tuple. With `relations` set, only unexpired tuples
+    /// with one of those relations count.
+    fn can_reach(
+        &self,
+        bookmark: &BookmarkRow,
+        subjects: &[(SubjectType, String)],
+        relations: Option<&[Relation]>,
+    ) -> bool {
+        let id = bookmark.id.to_string();
+        let now = Utc::now();
+        self.permissions.iter().any(|p| {
+            p.tenant_id == bookmark.tenant_id
+                && p.resource_type == ResourceType::Bookmark.as_str()
+                && (p.resource_id == id || p.resource_id == WILDCARD_RESOURCE)
+                && subjects
+                    .iter()
+                    .any(|(t, s)| p.subject_type == t.as_str() && &p.subject_id == s)
+                && p.subject_tenant_id.is_none()
+                && relations.is_none_or(|rels| {
+                    rels.iter().any(|r| r.as_str() == p.relation)
+                        && p.expires_at.is_none_or(|e| e > now)
+                })
+        })
+    }
+
+    /// Whether the bookmark's tenant shared it with `user_id` of `tenant_id`.
+    fn federated_to(&self, bookmark: &BookmarkRow, tenant_id: i32, user_id: &str) -> bool {
+        let id = bookmark.id.to_string();
+        let now = Utc::now();
+        self.permissions.iter().any(|p| {
+            p.tenant_id == bookmark.tenant_id
+                && p.subject_tenant_id == Some(tenant_id)
+                && p.subject_type == SubjectType::User.as_str()
+                && p.subject_id == user_id
+                && p.resource_type == ResourceType::Bookmark.as_str()
+                && p.resource_id == id
+                && p.expires_at.is_none_or(|e| e > now)
+        })
+    }
+
+    /// Insert or refresh a tuple, as `ON CONFLICT ... DO UPDATE` does.
+    #[allow(clippy::too_many_arguments)]
+    fn upsert_permission(
+        &mut self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        relation: Relation,
+        subject_type: SubjectType,
+        subject_id: &str,
+        granted_by: Option<i32>,
+        expires_at: Option<chrono::DateTime<Utc>>,
+    ) -> PermissionRow {
+        let existing = self.permissions.iter_mut().find(|p| {
+            p.tenant_id == tenant_id
+                && p.resource_type == resource_type.as_str()
+                && p.resource_id == resource_id
+                && p.relation == relation.as_str()
+                && p.subject_type == subject_type.as_str()
+                && p.subject_id == subject_id
+                && p.subject_tenant_id.is_none()
+        });
+        if let Some(row) = existing {
+            row.granted_by = granted_by;
+            row.expires_at = expires_at;
+            return row.clone();
+        }
+        self.next_permission_id += 1;
+        let row = PermissionRow {
+            id: self.next_permission_id,
+            tenant_id,
+            resource_type: resource_type.as_str().to_string(),
+            resource_id: resource_id.to_string(),
+            relation: relation.as_str().to_string(),
+            subject_type: subject_type.as_str().to_string(),
+            subject_id: subject_id.to_string(),
+            subject_tenant_id: None,
+            granted_by,
+            expires_at,
+            create_time: Utc::now(),
+        };
+        self.permissions.push(row.clone());
+        row
+    }
+
+    /// Snapshot a bookmark into `revisions` before it changes.
+    fn record_revision(&mut self, bookmark_id: Uuid, edited_by: Option<i32>) -> bool {
+        let Some(b) = self.bookmarks.iter().find(|b| b.id == bookmark_id) else {
+            return false;
+        };
+        self.next_revision_id += 1;
+        let revision = RevisionRow {
+            id: self.next_revision_id,
+            bookmark_id,
+            tenant_id: b.tenant_id,
+            url: b.url.clone(),
+            title: b.title.clone(),
+            description: b.description.clone(),
+            tags: b.tags.clone(),
+            original_url: b.original_url.clone(),
+            edited_by,
+            create_time: Utc::now(),
+            is_private: b.is_private,
+        };
+        self.revisions.push(revision);
+        true
+    }
+}
+
+/// Tables shared by the in-memory stores. Cheap to clone; clones share state.
+#[derive(Clone, Default)]
+pub struct MemoryDb {
+    tables: Arc<Mutex<Tables>>,
+}
+
+impl MemoryDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bookmarks(&self) -> MemoryBookmarkRepo {
+        MemoryBookmarkRepo::new(self.clone())
+    }
+
+    pub fn permissions(&self) -> MemoryPermissionRepo {
+        MemoryPermissionRepo::new(self.clone())
+    }
+
+    pub fn groups(&self) -> MemoryGroupRepo {
+        MemoryGroupRepo::new(self.clone())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Tables> {
+        self.tables.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Rows `page` (1-based) of `page_size`, plus one more when it exists.
+fn page_with_lookahead<T>(rows: Vec<T>, offset: usize, page_size: u32) -> Vec<T> {
+    rows.into_iter()
+        .skip(offset)
+        .take(page_size as usize + 1)
+        .collect()
+}
+
+fn offset(page: u32, page_size: u32) -> usize {
+    (page.saturating_sub(1) * page_size) as usize
+}
@@ -0,0 +1,558 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Utc};
+
+use super::{offset, page_with_lookahead, MemoryDb};
+use crate::authz::relations::{Relation, ResourceType, SubjectType};
+use crate::data::permission_repo::{
+    PermissionRow, PermissionStore, SubjectResourceRow, TupleGrant, TupleRevoke,
+};
+
+#[derive(Clone)]
+pub struct MemoryPermissionRepo {
+    db: MemoryDb,
+}
+
+impl MemoryPermissionRepo {
+    pub fn new(db: MemoryDb) -> Self {
+        Self { db }
+    }
+}
+
+fn is_tuple(
+    p: &PermissionRow,
+    tenant_id: i32,
+    resource_type: ResourceType,
+    resource_id: &str,
+    subject_type: SubjectType,
+    subject_id: &str,
+) -> bool {
+    p.tenant_id == tenant_id
+        && p.resource_type == resource_type.as_str()
+        && p.resource_id == resource_id
+        && p.subject_type == subject_type.as_str()
+        && p.subject_id == subject_id
+        && p.subject_tenant_id.is_none()
+}
+
+#[tonic::async_trait]
+impl PermissionStore for MemoryPermissionRepo {
+    async fn has_permission(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        subject_type: SubjectType,
+        subject_id: &str,
+    ) -> anyhow::Result<Option<PermissionRow>> {
+        let tables = self.db.lock();
+        Ok(tables
+            .permissions
+            .iter()
+            .find(|p| {
+                is_tuple(
+                    p,
+                    tenant_id,
+                    resource_type,
+                    resource_id,
+                    subject_type,
+                    subject_id,
+                ) && p.subject_tenant_id.is_none()
+            })
+            .cloned())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn create_permission(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        relation: Relation,
+        subject_type: SubjectType,
+        subject_id: &str,
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<PermissionRow> {
+        Ok(self.db.lock().upsert_permission(
+            tenant_id,
+            resource_type,
+            resource_id,
+            relation,
+            subject_type,
+            subject_id,
+            granted_by,
+            expires_at,
+        ))
+    }
+
+    async fn create_permissions(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        grants: &[(Relation, SubjectType, String)],
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<PermissionRow>> {
+        let mut tables = self.db.lock();
+        Ok(grants
+            .iter()
+            .map(|(relation, subject_type, subject_id)| {
+                tables.upsert_permission(
+                    tenant_id,
+                    resource_type,
+                    resource_id,
+                    *relation,
+                    *subject_type,
+                    subject_id,
+                    granted_by,
+                    expires_at,
+                )
+            })
+            .collect())
+    }
+
+    async fn batch_grant(
+        &self,
+        tenant_id: i32,
+        tuples: &[TupleGrant],
+        granted_by: Option<i32>,
+    ) -> anyhow::Result<Vec<PermissionRow>> {
+        let mut tables = self.db.lock();
+        Ok(tuples
+            .iter()
+            .map(|t| {
+                tables.upsert_permission(
+                    tenant_id,
+                    t.resource_type,
+                    &t.resource_id,
+                    t.relation,
+                    t.subject_type,
+                    &t.subject_id,
+                    granted_by,
+                    t.expires_at,
+                )
+            })
+            .collect())
+    }
+
+    async fn batch_revoke(
+        &self,
+        tenant_id: i32,
+        tuples: &[TupleRevoke],
+    ) -> anyhow::Result<Vec<u64>> {
+        let mut tables = self.db.lock();
+        Ok(tuples
+            .iter()
+            .map(|t| {
+                let before = tables.permissions.len();
+                tables.permissions.retain(|p| {
+                    !(is_tuple(
+                        p,
+                        tenant_id,
+                        t.resource_type,
+                        &t.resource_id,
+                        t.subject_type,
+                        &t.subject_id,
+                    ) && t.relation.is_none_or(|r| r.as_str() == p.relation))
+                });
+                (before - tables.permissions.len()) as u64
+            })
+            .collect())
+    }
+
+    async fn delete_permission(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        relation: Option<Relation>,
+        subject_type: SubjectType,
+        subject_id: &str,
+    ) -> anyhow::Result<u64> {
+        let mut tables = self.db.lock();
+        let before = tables.permissions.len();
+        tables.permissions.retain(|p| {
+            !(is_tuple(
+                p,
+                tenant_id,
+                resource_type,
+                resource_id,
+                subject_type,
+                subject_id,
+            ) && relation.is_none_or(|r| r.as_str() == p.relation))
+        });
+        Ok((before - tables.permissions.len()) as u64)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn transfer_ownership(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        from_user: &str,
+        to_user: &str,
+        keep_as_editor: bool,
+        granted_by: Option<i32>,
+    ) -> anyhow::Result<Option<PermissionRow>> {
+        let mut tables = self.db.lock();
+        let before = tables.permissions.len();
+        tables.permissions.retain(|p| {
+            !(is_tuple(
+                p,
+                tenant_id,
+                resource_type,
+                resource_id,
+                SubjectType::User,
+                from_user,
+            ) && p.relation == Relation::Owner.as_str())
+        });
+        if tables.permissions.len() == before {
+            return Ok(None);
+        }
+
+        let owner = tables.upsert_permission(
+            tenant_id,
+            resource_type,
+            resource_id,
+            Relation::Owner,
+            SubjectType::User,
+            to_user,
+            granted_by,
+            None,
+        );
+        let has_editor = tables.permissions.iter().any(|p| {
+            is_tuple(
+                p,
+                tenant_id,
+                resource_type,
+                resource_id,
+                SubjectType::User,
+                from_user,
+            ) && p.relation == Relation::Editor.as_str()
+        });
+        if keep_as_editor && !has_editor {
+            tables.upsert_permission(
+                tenant_id,
+                resource_type,
+                resource_id,
+                Relation::Editor,
+                SubjectType::User,
+                from_user,
+                granted_by,
+                None,
+            );
+        }
+        Ok(Some(owner))
+    }
+
+    async fn purge_expired(&self) -> anyhow::Result<u64> {
+        let now = Utc::now();
+        let mut tables = self.db.lock();
+        let before = tables.permissions.len();
+        tables
+            .permissions
+            .retain(|p| p.expires_at.is_none_or(|e| e >= now));
+        Ok((before - tables.permissions.len()) as u64)
+    }
+
+    async fn backfill_relation_codes(&self) -> anyhow::Result<u64> {
+        Ok(0)
+    }
+
+    async fn delete_all_for_resource(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+    ) -> anyhow::Result<u64> {
+        let mut tables = self.db.lock();
+        let before = tables.permissions.len();
+        tables.permissions.retain(|p| {
+            !(p.tenant_id == tenant_id
+                && p.resource_type == resource_type.as_str()
+                && p.resource_id == resource_id)
+        });
+        Ok((before - tables.permissions.len()) as u64)
+    }
+
+    async fn get_direct_permissions(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+    ) -> anyhow::Result<Vec<PermissionRow>> {
+        let tables = self.db.lock();
+        let mut rows: Vec<PermissionRow> = tables
+            .permissions
+            .iter()
+            .filter(|p| {
+                p.tenant_id == tenant_id
+                    && p.resource_type == resource_type.as_str()
+                    && p.resource_id == resource_id
+            })
+            .cloned()
+            .collect();
+        rows.sort_by_key(|r| std::cmp::Reverse(r.create_time));
+        Ok(rows)
+    }
+
+    async fn list_resources_by_subject(
+        &self,
+        tenant_id: i32,
+        subject_type: SubjectType,
+        subject_id: &str,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>> {
+        let tables = self.db.lock();
+        let ids: BTreeSet<&str> = tables
+            .permissions
+            .iter()
+            .filter(|p| {
+                p.tenant_id == tenant_id
+                    && p.resource_type == resource_type.as_str()
+                    && p.subject_type == subject_type.as_str()
+                    && p.subject_id == subject_id
+                    && p.subject_tenant_id.is_none()
+            })
+            .map(|p| p.resource_id.as_str())
+            .collect();
+        Ok(ids.into_iter().map(str::to_string).collect())
+    }
+
+    async fn list_tenant_resources(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>> {
+        let tables = self.db.lock();
+        Ok(match resource_type {
+            ResourceType::Bookmark => tables
+                .bookmarks
+                .iter()
+                .filter(|b| b.tenant_id == tenant_id)
+                .map(|b| b.id.to_string())
+                .collect(),
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn grant_federated(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        subject_tenant_id: i32,
+        user_id: &str,
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<PermissionRow> {
+        let mut tables = self.db.lock();
+        let existing = tables.permissions.iter_mut().find(|p| {
+            p.tenant_id == tenant_id
+                && p.resource_type == resource_type.as_str()
+                && p.resource_id == resource_id
+                && p.relation == Relation::Viewer.as_str()
+                && p.subject_type == SubjectType::User.as_str()
+                && p.subject_id == user_id
+                && p.subject_tenant_id == Some(subject_tenant_id)
+        });
+        if let Some(row) = existing {
+            row.granted_by = granted_by;
+            row.expires_at = expires_at;
+            return Ok(row.clone());
+        }
+        tables.next_permission_id += 1;
+        let row = PermissionRow {
+            id: tables.next_permission_id,
+            tenant_id,
+            resource_type: resource_type.as_str().to_string(),
+            resource_id: resource_id.to_string(),
+            relation: Relation::Viewer.as_str().to_string(),
+            subject_type: SubjectType::User.as_str().to_string(),
+            subject_id: user_id.to_string(),
+            subject_tenant_id: Some(subject_tenant_id),
+            granted_by,
+            expires_at,
+            create_time: Utc::now(),
+        };
+        tables.permissions.push(row.clone());
+        Ok(row)
+    }
+
+    async fn has_federated_permission(
+        &self,
+        subject_tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        user_id: &str,
+    ) -> anyhow::Result<Option<PermissionRow>> {
+        let now = Utc::now();
+        let tables = self.db.lock();
+        Ok(tables
+            .permissions
+            .iter()
+            .find(|p| {
+                p.subject_tenant_id == Some(subject_tenant_id)
+                    && p.subject_type == SubjectType::User.as_str()
+                    && p.subject_id == user_id
+                    && p.resource_type == resource_type.as_str()
+                    && p.resource_id == resource_id
+                    && p.expires_at.is_none_or(|e| e > now)
+            })
+            .cloned())
+    }
+
+    async fn list_federated_resources(
+        &self,
+        subject_tenant_id: i32,
+        user_id: &str,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>> {
+        let now = Utc::now();
+        let tables = self.db.lock();
+        let ids: BTreeSet<&str> = tables
+            .permissions
+            .iter()
+            .filter(|p| {
+                p.subject_tenant_id == Some(subject_tenant_id)
+                    && p.subject_type == SubjectType::User.as_str()
+                    && p.subject_id == user_id
+                    && p.resource_type == resource_type.as_str()
+                    && p.expires_at.is_none_or(|e| e > now)
+            })
+            .map(|p| p.resource_id.as_str())
+            .collect();
+        Ok(ids.into_iter().map(str::to_string).collect())
+    }
+
+    async fn filter_owned(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        user_id: &str,
+        resource_ids: &[String],
+    ) -> anyhow::Result<Vec<String>> {
+        let now = Utc::now();
+        let tables = self.db.lock();
+        Ok(tables
+            .permissions
+            .iter()
+            .filter(|p| {
+                p.tenant_id == tenant_id
+                    && p.resource_type == resource_type.as_str()
+                    && p.relation == Relation::Owner.as_str()
+                    && p.subject_type == SubjectType::User.as_str()
+                    && p.subject_id == user_id
+                    && resource_ids.contains(&p.resource_id)
+                    && p.expires_at.is_none_or(|e| e > now)
+            })
+            .map(|p| p.resource_id.clone())
+            .collect())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn list_resources_for_subject(
+        &self,
+        tenant_id: i32,
+        subject_type: SubjectType,
+        subject_id: &str,
+        owned_by: Option<&str>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<SubjectResourceRow>, i64)> {
+        let tables = self.db.lock();
+        let owns = |owner: &str, p: &PermissionRow| {
+            tables.permissions.iter().any(|o| {
+                is_tuple(
+                    o,
+                    p.tenant_id,
+                    ResourceType::Bookmark,
+                    &p.resource_id,
+                    SubjectType::User,
+                    owner,
+                ) && o.relation == Relation::Owner.as_str()
+            })
+        };
+        let mut rows: Vec<SubjectResourceRow> = tables
+            .permissions
+            .iter()
+            .filter(|p| {
+                (p.tenant_id == tenant_id && p.subject_tenant_id.is_none()
+                    || p.subject_tenant_id == Some(tenant_id))
+                    && p.resource_type == ResourceType::Bookmark.as_str()
+                    && p.subject_type == subject_type.as_str()
+                    && p.subject_id == subject_id
+                    && owned_by.is_none_or(|owner| owns(owner, p))
+            })
+            .filter_map(|p| {
+                let b = tables
+                    .bookmarks
+                    .iter()
+                    .find(|b| b.id.to_string() == p.resource_id && b.tenant_id == p.tenant_id)?;
+                Some(SubjectResourceRow {
+                    resource_type: p.resource_type.clone(),
+                    resource_id: p.resource_id.clone(),
+                    relation: p.relation.clone(),
+                    expires_at: p.expires_at,
+                    title: b.title.clone(),
+                    url: if b.is_private {
+                        String::new()
+                    } else {
+                        b.url.clone()
+                    },
+                })
+            })
+            .collect();
+        rows.sort_by(|a, b| (&a.title, &a.resource_id).cmp(&(&b.title, &b.resource_id)));
+
+        let total = rows.len() as i64;
+        let rows = rows
+            .into_iter()
+            .skip(offset(page, page_size))
+            .take(page_size as usize)
+            .collect();
+        Ok((rows, total))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn list_permissions_filtered(
+        &self,
+        tenant_id: i32,
+        resource_type: Option<ResourceType>,
+        resource_id: Option<&str>,
+        subject_type: Option<SubjectType>,
+        subject_id: Option<&str>,
+        after: Option<(DateTime<Utc>, i32)>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<PermissionRow>, i64)> {
+        let tables = self.db.lock();
+        let mut rows: Vec<PermissionRow> = tables
+            .permissions
+            .iter()
+            .filter(|p| {
+                p.tenant_id == tenant_id
+                    && resource_type.is_none_or(|t| p.resource_type == t.as_str())
+                    && resource_id.is_none_or(|id| p.resource_id == id)
+                    && subject_type.is_none_or(|t| p.subject_type == t.as_str())
+                    && subject_id.is_none_or(|id| p.subject_id == id)
+            })
+            .cloned()
+            .collect();
+        let total = rows.len() as i64;
+
+        rows.sort_by_key(|r| std::cmp::Reverse((r.create_time, r.id)));
+        if let Some(cursor) = after {
+            rows.retain(|p| (p.create_time, p.id) < cursor);
+        }
+        let skip = if after.is_some() {
+            0
+        } else {
+            offset(page, page_size)
+        };
+        Ok((page_with_lookahead(rows, skip, page_size), total))
+    }
+}
@@ -0,0 +1,442 @@
+use std::collections::HashSet;
+
+use sqlx::migrate::Migrator;
+use sqlx::PgPool;
+
+/// Lock taken by a migration statement, ordered from least to most disruptive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LockLevel {
+    None,
+    RowExclusive,
+    ShareUpdateExclusive,
+    Share,
+    AccessExclusive,
+}
+
+impl LockLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::RowExclusive => "ROW EXCLUSIVE (locks the rows it changes)",
+            Self::ShareUpdateExclusive => "SHARE UPDATE EXCLUSIVE",
+            Self::Share => "SHARE (blocks writes)",
+            Self::AccessExclusive => "ACCESS EXCLUSIVE (blocks reads and writes)",
+        }
+    }
+}
+
+/// Classification of a single statement inside a migration.
+#[derive(Debug)]
+pub struct StatementRisk {
+    pub table: Option<String>,
+    pub lock: LockLevel,
+    pub safe: bool,
+    pub reason: &'static str,
+}
+
+/// Classification of a pending migration.
+#[derive(Debug)]
+pub struct MigrationReport {
+    pub version: i64,
+    pub description: String,
+    pub risks: Vec<StatementRisk>,
+}
+
+impl MigrationReport {
+    pub fn is_safe(&self) -> bool {
+        self.risks.iter().all(|r| r.safe)
+    }
+
+    pub fn max_lock(&self) -> LockLevel {
+        self.risks
+            .iter()
+            .map(|r| r.lock)
+            .max()
+            .unwrap_or(LockLevel::None)
+    }
+}
+
+/// Classify every pending migration, log the estimated lock impact, and refuse to
+/// continue if any of them is unsafe to apply online and `allow_unsafe` is not set.
+///
+/// A database with no applied migrations is being bootstrapped and has no live
+/// traffic, so every migration is allowed.
+pub async fn check_pending(
+    pool: &PgPool,
+    migrator: &Migrator,
+    allow_unsafe: bool,
+) -> anyhow::Result<()> {
+    let applied = applied_versions(pool).await?;
+    if applied.is_empty() {
+        tracing::info!("fresh database, skipping online migration checks");
+        return Ok(());
+    }
+
+    let reports: Vec<MigrationReport> = migrator
+        .iter()
+        .filter(|m| !applied.contains(&m.version))
+        .map(|m| MigrationReport {
+            version: m.version,
+            description: m.description.to_string(),
+            risks: classify_sql(&m.sql),
+        })
+        .collect();
+
+    if reports.is_empty() {
+        return Ok(());
+    }
+
+    let mut unsafe_versions = Vec::new();
+    for report in &reports {
+        for risk in &report.risks {
+            let estimated_rows = match &risk.table {
+                Some(table) if !risk.safe || risk.lock > LockLevel::ShareUpdateExclusive => {
+                    estimate_rows(pool, table).await
+                }
+                _ => None,
+            };
+            if risk.safe {
+                tracing::info!(
+                    version = report.version,
+                    table = risk.table.as_deref().unwrap_or(""),
+                    lock = risk.lock.as_str(),
+                    estimated_rows,
+                    reason = risk.reason,
+                    "pending migration statement is safe online"
+                );
+            } else {
+                tracing::warn!(
+                    version = report.version,
+                    table = risk.table.as_deref().unwrap_or(""),
+                    lock = risk.lock.as_str(),
+                    estimated_rows,
+                    reason = risk.reason,
+                    "pending migration statement is unsafe online"
+                );
+            }
+        }
+        if !report.is_safe() {
+            unsafe_versions.push(format!("{} ({})", report.version, report.description));
+        }
+    }
+
+    if unsafe_versions.is_empty() {
+        return Ok(());
+    }
+
+    if allow_unsafe {
+        tracing::warn!(
+            migrations = ?unsafe_versions,
+            "applying unsafe migrations (--allow-unsafe-migrations set)"
+        );
+        return Ok(());
+    }
+
+    anyhow::bail!(
+        "refusing to apply unsafe migrations {}; rerun with --allow-unsafe-migrations during a maintenance window",
+        unsafe_versions.join(", ")
+    )
+}
+
+async fn applied_versions(pool: &PgPool) -> anyhow::Result<HashSet<i64>> {
+    let (exists,): (bool,) =
+        sqlx::query_as("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+    if !exists {
+        return Ok(HashSet::new());
+    }
+
+    let rows: Vec<(i64,)> = sqlx::query_as("SELECT version FROM _sqlx_migrations WHERE success")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(|r| r.0).collect())
+}
+
+async fn estimate_rows(pool: &PgPool, table: &str) -> Option<i64> {
+    sqlx::query_as::<_, (i64,)>(
+        "SELECT GREATEST(reltuples, 0)::BIGINT FROM pg_class WHERE relname = $1 AND relkind = 'r'",
+    )
+    .bind(table)
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten()
+    .map(|r| r.0)
+}
+
+/// Heuristically classify the statements of a migration script.
+///
+/// Tables created earlier in the same script are empty, so statements touching
+/// them are always safe.
+pub fn classify_sql(sql: &str) -> Vec<StatementRisk> {
+    let mut created_tables: HashSet<String> = HashSet::new();
+    let mut risks = Vec::new();
+
+    for stmt in split_statements(sql) {
+        let upper = stmt.to_uppercase();
+        let words: Vec<&str> = upper.split_whitespace().collect();
+
+        let mut risk = classify_statement(&upper, &words);
+        if let Some(table) = &risk.table {
+            if upper.starts_with("CREATE TABLE") {
+                created_tables.insert(table.clone());
+            } else if created_tables.contains(table) {
+                risk.safe = true;
+                risk.reason = "table created in the same migration";
+            }
+        }
+        risks.push(risk);
+    }
+
+    risks
+}
+
+fn classify_statement(upper: &str, words: &[&str]) -> StatementRisk {
+    let table_after = |kw: &str| -> Option<String> {
+        let pos = words.iter().position(|w| *w == kw)?;
+        words[pos + 1..]
+            .iter()
+            .find(|w| !matches!(**w, "IF" | "NOT" | "EXISTS" | "ONLY" | "CONCURRENTLY"))
+            .and_then(|w| w.split('(').next())
+            .map(|w| w.trim_matches('"').to_lowercase())
+            .filter(|w| !w.is_empty())
+    };
+
+    if upper.starts_with("CREATE TABLE") {
+        return StatementRisk {
+            table: table_after("TABLE"),
+            lock: LockLevel::None,
+            safe: true,
+            reason: "new table",
+        };
+    }
+
+    if upper.starts_with("CREATE INDEX") || upper.starts_with("CREATE UNIQUE INDEX") {
+        let concurrent = words.contains(&"CONCURRENTLY");
+        return StatementRisk {
+            table: table_after("ON"),
+            lock: if concurrent {
+                LockLevel::ShareUpdateExclusive
+            } else {
+                LockLevel::Share
+            },
+            safe: concurrent,
+            reason: if concurrent {
+                "concurrent index build"
+            } else {
+                "index build without CONCURRENTLY blocks writes"
+            },
+        };
+    }
+
+    if upper.starts_with("DROP INDEX") {
+        let concurrent = words.contains(&"CONCURRENTLY");
+        return StatementRisk {
+            table: None,
+            lock: if concurrent {
+                LockLevel::ShareUpdateExclusive
+            } else {
+                LockLevel::AccessExclusive
+            },
+            safe: concurrent,
+            reason: "dropping an index",
+        };
+    }
+
+    if upper.starts_with("DROP TABLE") {
+        return StatementRisk {
+            table: table_after("TABLE"),
+            lock: LockLevel::AccessExclusive,
+            safe: false,
+            reason: "dropping a table breaks running instances",
+        };
+    }
+
+    if upper.starts_with("UPDATE") || upper.starts_with("DELETE") {
+        let keyword = if upper.starts_with("UPDATE") {
+            "UPDATE"
+        } else {
+            "FROM"
+        };
+        let bounded = words.contains(&"WHERE") || words.contains(&"LIMIT");
+        return StatementRisk {
+            table: table_after(keyword),
+            lock: LockLevel::RowExclusive,
+            safe: bounded,
+            reason: if bounded {
+                "bounded data change"
+            } else {
+                "rewriting every row holds the whole table in one transaction"
+            },
+        };
+    }
+
+    if upper.starts_with("VACUUM FULL") || upper.starts_with("CLUSTER") {
+        return StatementRisk {
+            table: None,
+            lock: LockLevel::AccessExclusive,
+            safe: false,
+            reason: "full table rewrite",
+        };
+    }
+
+    if upper.starts_with("ALTER TABLE") {
+        let table = table_after("TABLE");
+        let unsafe_alter = |reason| StatementRisk {
+            table: table.clone(),
+            lock: LockLevel::AccessExclusive,
+            safe: false,
+            reason,
+        };
+
+        if upper.contains(" TYPE ") {
+            return unsafe_alter("changing a column type rewrites the table");
+        }
+        if upper.contains("SET NOT NULL") {
+            return unsafe_alter("SET NOT NULL scans the table under lock");
+        }
+        if upper.contains("DROP COLUMN") {
+            return unsafe_alter("dropping a column breaks running instances");
+        }
+        if upper.contains("RENAME") {
+            return unsafe_alter("renaming breaks running instances");
+        }
+        if upper.contains("ADD CONSTRAINT") && !upper.contains("NOT VALID") {
+            return unsafe_alter("constraint validation scans the table under lock");
+        }
+        if upper.contains("ADD COLUMN") {
+            if upper.contains("NOT NULL") && !upper.contains("DEFAULT") {
+                return unsafe_alter("NOT NULL column without DEFAULT fails on existing rows");
+            }
+            if upper.contains("DEFAULT") && has_volatile_default(upper) {
+                return unsafe_alter("volatile DEFAULT rewrites the table");
+            }
+            return StatementRisk {
+                table,
+                lock: LockLevel::AccessExclusive,
+                safe: true,
+                reason: "metadata-only column add",
+            };
+        }
+
+        return StatementRisk {
+            table,
+            lock: LockLevel::AccessExclusive,
+            safe: true,
+            reason: "brief catalog change",
+        };
+    }
+
+    StatementRisk {
+        table: None,
+        lock: LockLevel::None,
+        safe: true,
+        reason: "no schema lock",
+    }
+}
+
+fn has_volatile_default(upper: &str) -> bool {
+    ["RANDOM()", "GEN_RANDOM_UUID()", "CLOCK_TIMESTAMP()", "UUID_GENERATE"]
+        .iter()
+        .any(|f| upper.contains(f))
+}
+
+/// Split a script on `;`, dropping `--` comments and empty statements.
+fn split_statements(sql: &str) -> Vec<String> {
+    let stripped: String = sql
+        .lines()
+        .map(|line| line.split("--").next().unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    stripped
+        .split(';')
+        .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify_one(sql: &str) -> StatementRisk {
+        let mut risks = classify_sql(sql);
+        assert_eq!(risks.len(), 1, "{sql}");
+        risks.remove(0)
+    }
+
+    #[test]
+    fn shipped_migrations_are_safe_online() {
+        for m in sqlx::migrate!("./migrations").iter() {
+            for risk in classify_sql(&m.sql) {
+                assert!(
+                    risk.safe,
+                    "migration {} ({}): {:?}",
+                    m.version, m.description, risk
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unbounded_data_changes_are_unsafe() {
+        let risk = classify_one("UPDATE bookmark_permissions SET relation = relation;");
+        assert!(!risk.safe);
+        assert_eq!(risk.table.as_deref(), Some("bookmark_permissions"));
+        assert_eq!(risk.lock, LockLevel::RowExclusive);
+
+        let risk = classify_one("DELETE FROM bookmark_changes");
+        assert!(!risk.safe);
+        assert_eq!(risk.table.as_deref(), Some("bookmark_changes"));
+
+        assert!(
+            classify_one("UPDATE bookmark_saved_searches SET roles = '{}' WHERE roles <> '{}'")
+                .safe
+        );
+        assert!(classify_one("DELETE FROM bookmark_changes WHERE id < 10").safe);
+    }
+
+    #[test]
+    fn index_builds_need_concurrently() {
+        let risk = classify_one("CREATE INDEX idx_kind ON bookmark_bookmarks(kind)");
+        assert!(!risk.safe);
+        assert_eq!(risk.table.as_deref(), Some("bookmark_bookmarks"));
+        assert_eq!(risk.lock, LockLevel::Share);
+
+        let risk = classify_one("CREATE INDEX CONCURRENTLY idx_kind ON bookmark_bookmarks (kind)");
+        assert!(risk.safe);
+        assert!(!classify_one("DROP INDEX idx_kind").safe);
+        assert!(classify_one("DROP INDEX CONCURRENTLY IF EXISTS idx_kind").safe);
+    }
+
+    #[test]
+    fn tables_created_in_the_same_migration_are_safe() {
+        let risks = classify_sql(
+            "-- A new table.\n\
+             CREATE TABLE bookmark_things (id SERIAL PRIMARY KEY, name TEXT);\n\
+             CREATE INDEX idx_things_name ON bookmark_things(name);\n\
+             DELETE FROM bookmark_things;\n\
+             ALTER TABLE bookmark_things DROP COLUMN name;",
+        );
+        assert_eq!(risks.len(), 4);
+        assert!(risks.iter().all(|r| r.safe), "{risks:?}");
+    }
+
+    #[test]
+    fn alter_table_risks() {
+        assert!(!classify_one("ALTER TABLE bookmark_bookmarks DROP COLUMN kind").safe);
+        assert!(
+            !classify_one("ALTER TABLE bookmark_bookmarks ALTER COLUMN kind SET NOT NULL").safe
+        );
+        assert!(classify_one("ALTER TABLE bookmark_bookmarks ADD COLUMN kind TEXT").safe);
+        assert!(!classify_one("ALTER TABLE bookmark_bookmarks ADD COLUMN kind TEXT NOT NULL").safe);
+        assert!(
+            !classify_one(
+                "ALTER TABLE bookmark_bookmarks ADD COLUMN token UUID DEFAULT gen_random_uuid()"
+            )
+            .safe
+        );
+    }
+}
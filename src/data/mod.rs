@@ -1,3 +1,29 @@
 pub mod db;
+pub mod migration_guard;
+pub mod access_request_repo;
+pub mod api_key_repo;
+pub mod archive_repo;
+pub mod audit_repo;
+pub mod auto_tag_rule_repo;
 pub mod bookmark_repo;
+pub mod breaker;
+pub mod change_repo;
+pub mod field_key_repo;
+pub mod filter_expr;
+pub mod group_repo;
+pub mod idempotency_repo;
+#[cfg(test)]
+pub mod memory;
 pub mod permission_repo;
+pub mod permission_template_repo;
+pub mod pool_metrics;
+pub mod replica;
+pub mod saved_search_repo;
+pub mod scrub_policy_repo;
+pub mod share_notification_repo;
+pub mod short_link_repo;
+pub mod sqlite;
+pub mod stats_repo;
+pub mod tag_repo;
+pub mod user_data_repo;
+pub mod user_flag_repo;
@@ -1,9 +1,11 @@
 use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, PgPool, Row};
 
 use crate::authz::relations::{Relation, ResourceType, SubjectType};
+use crate::data::replica::ReadPool;
 
-#[derive(Debug, sqlx::FromRow)]
+#[derive(Debug, Clone)]
 pub struct PermissionRow {
     pub id: i32,
     pub tenant_id: i32,
@@ -12,22 +14,345 @@ pub struct PermissionRow {
     pub relation: String,
     pub subject_type: String,
     pub subject_id: String,
+    /// Tenant of a user granted access from another tenant; `None` when the
+    /// subject belongs to `tenant_id`.
+    pub subject_tenant_id: Option<i32>,
     pub granted_by: Option<i32>,
     pub expires_at: Option<DateTime<Utc>>,
     pub create_time: DateTime<Utc>,
 }
 
+/// A permission tuple joined with the bookmark it grants access to.
+#[derive(Debug)]
+pub struct SubjectResourceRow {
+    pub resource_type: String,
+    pub resource_id: String,
+    pub relation: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub title: String,
+    /// Empty for private bookmarks.
+    pub url: String,
+}
+
+/// A `bookmark_permissions` row as stored, selected by the checked queries and
+/// converted with `PermissionRow::try_from`.
+#[derive(Debug)]
+pub(crate) struct PermissionRecord {
+    pub id: i32,
+    pub tenant_id: i32,
+    pub resource_type: String,
+    pub resource_id: String,
+    pub relation: String,
+    pub subject_type: String,
+    pub subject_id: String,
+    pub granted_by: Option<i32>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub create_time: DateTime<Utc>,
+    pub relation_code: Option<i16>,
+    pub subject_tenant_id: Option<i32>,
+}
+
+impl TryFrom<PermissionRecord> for PermissionRow {
+    type Error = sqlx::Error;
+
+    fn try_from(r: PermissionRecord) -> sqlx::Result<Self> {
+        Ok(Self {
+            relation: relation_name(r.relation_code, r.relation)?,
+            id: r.id,
+            tenant_id: r.tenant_id,
+            resource_type: r.resource_type,
+            resource_id: r.resource_id,
+            subject_type: r.subject_type,
+            subject_id: r.subject_id,
+            subject_tenant_id: r.subject_tenant_id,
+            granted_by: r.granted_by,
+            expires_at: r.expires_at,
+            create_time: r.create_time,
+        })
+    }
+}
+
+/// Convert fetched records, failing on the first unknown relation code.
+pub(crate) fn permission_rows(records: Vec<PermissionRecord>) -> sqlx::Result<Vec<PermissionRow>> {
+    records.into_iter().map(PermissionRow::try_from).collect()
+}
+
+/// Relation name of a stored tuple. Uses `relation_code`, falling back to the
+/// legacy string column for rows written by instances that predate the codes.
+fn relation_name(code: Option<i16>, legacy: String) -> sqlx::Result<String> {
+    let Some(code) = code else {
+        return Ok(legacy);
+    };
+    Relation::from_code(code)
+        .map(|r| r.as_str().to_string())
+        .ok_or_else(|| sqlx::Error::ColumnDecode {
+            index: "relation_code".to_string(),
+            source: format!("unknown relation code {code}").into(),
+        })
+}
+
+fn relation_from_row(row: &PgRow) -> sqlx::Result<String> {
+    relation_name(row.try_get("relation_code")?, row.try_get("relation")?)
+}
+
+impl<'r> FromRow<'r, PgRow> for PermissionRow {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            tenant_id: row.try_get("tenant_id")?,
+            resource_type: row.try_get("resource_type")?,
+            resource_id: row.try_get("resource_id")?,
+            relation: relation_from_row(row)?,
+            subject_type: row.try_get("subject_type")?,
+            subject_id: row.try_get("subject_id")?,
+            subject_tenant_id: row.try_get("subject_tenant_id")?,
+            granted_by: row.try_get("granted_by")?,
+            expires_at: row.try_get("expires_at")?,
+            create_time: row.try_get("create_time")?,
+        })
+    }
+}
+
+impl<'r> FromRow<'r, PgRow> for SubjectResourceRow {
+    fn from_row(row: &'r PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            resource_type: row.try_get("resource_type")?,
+            resource_id: row.try_get("resource_id")?,
+            relation: relation_from_row(row)?,
+            expires_at: row.try_get("expires_at")?,
+            title: row.try_get("title")?,
+            url: row.try_get("url")?,
+        })
+    }
+}
+
+/// A tuple to write with [`PermissionStore::batch_grant`].
+#[derive(Debug, Clone)]
+pub struct TupleGrant {
+    pub resource_type: ResourceType,
+    pub resource_id: String,
+    pub relation: Relation,
+    pub subject_type: SubjectType,
+    pub subject_id: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Tuples to remove with [`PermissionStore::batch_revoke`]; every relation the
+/// subject holds when `relation` is unset.
+#[derive(Debug, Clone)]
+pub struct TupleRevoke {
+    pub resource_type: ResourceType,
+    pub resource_id: String,
+    pub relation: Option<Relation>,
+    pub subject_type: SubjectType,
+    pub subject_id: String,
+}
+
+/// Permission tuple storage backing the authz engine.
+#[tonic::async_trait]
+pub trait PermissionStore: Send + Sync {
+    async fn has_permission(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        subject_type: SubjectType,
+        subject_id: &str,
+    ) -> anyhow::Result<Option<PermissionRow>>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn create_permission(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        relation: Relation,
+        subject_type: SubjectType,
+        subject_id: &str,
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<PermissionRow>;
+
+    /// Upsert several `(relation, subject_type, subject_id)` tuples on one
+    /// resource in a single transaction.
+    async fn create_permissions(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        grants: &[(Relation, SubjectType, String)],
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<PermissionRow>>;
+
+    /// Upsert distinct tuples across resources. Rows are returned in no
+    /// particular order.
+    async fn batch_grant(
+        &self,
+        tenant_id: i32,
+        tuples: &[TupleGrant],
+        granted_by: Option<i32>,
+    ) -> anyhow::Result<Vec<PermissionRow>>;
+
+    /// Delete tuples across resources. Returns how many rows each item
+    /// removed, in input order.
+    async fn batch_revoke(
+        &self,
+        tenant_id: i32,
+        tuples: &[TupleRevoke],
+    ) -> anyhow::Result<Vec<u64>>;
+
+    async fn delete_permission(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        relation: Option<Relation>,
+        subject_type: SubjectType,
+        subject_id: &str,
+    ) -> anyhow::Result<u64>;
+
+    /// Atomically move the OWNER relation from `from_user` to `to_user`, optionally
+    /// leaving `from_user` as EDITOR. Returns `None` if `from_user` is not an owner.
+    #[allow(clippy::too_many_arguments)]
+    async fn transfer_ownership(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        from_user: &str,
+        to_user: &str,
+        keep_as_editor: bool,
+        granted_by: Option<i32>,
+    ) -> anyhow::Result<Option<PermissionRow>>;
+
+    /// Delete every tuple whose expiry has passed. Returns the number removed.
+    async fn purge_expired(&self) -> anyhow::Result<u64>;
+
+    /// Fill `relation_code` for tuples written before the column existed.
+    /// Returns the number of rows updated.
+    async fn backfill_relation_codes(&self) -> anyhow::Result<u64>;
+
+    async fn delete_all_for_resource(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+    ) -> anyhow::Result<u64>;
+
+    /// Every tuple on the resource, federated ones included: a row with
+    /// `subject_tenant_id` set names a user of that tenant, not of this one.
+    async fn get_direct_permissions(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+    ) -> anyhow::Result<Vec<PermissionRow>>;
+
+    async fn list_resources_by_subject(
+        &self,
+        tenant_id: i32,
+        subject_type: SubjectType,
+        subject_id: &str,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>>;
+
+    /// Ids of every resource of a type in the tenant, for subjects holding a
+    /// [`WILDCARD_RESOURCE`](crate::authz::relations::WILDCARD_RESOURCE) tuple.
+    async fn list_tenant_resources(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>>;
+
+    /// Grant VIEWER on a resource of `tenant_id` to `user_id`, a user of
+    /// `subject_tenant_id`. Granting again refreshes the expiry.
+    #[allow(clippy::too_many_arguments)]
+    async fn grant_federated(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        subject_tenant_id: i32,
+        user_id: &str,
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<PermissionRow>;
+
+    /// The federated tuple granting `user_id` of `subject_tenant_id` access to
+    /// a resource owned by another tenant.
+    async fn has_federated_permission(
+        &self,
+        subject_tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        user_id: &str,
+    ) -> anyhow::Result<Option<PermissionRow>>;
+
+    /// Ids of resources other tenants granted `user_id` of `subject_tenant_id`.
+    async fn list_federated_resources(
+        &self,
+        subject_tenant_id: i32,
+        user_id: &str,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>>;
+
+    /// Return the subset of `resource_ids` on which the user holds a direct OWNER relation.
+    async fn filter_owned(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        user_id: &str,
+        resource_ids: &[String],
+    ) -> anyhow::Result<Vec<String>>;
+
+    /// List bookmarks granted to a subject, joined with their titles, including
+    /// grants from other tenants to a user of `tenant_id`. When `owned_by` is
+    /// set, only bookmarks that user directly owns are included.
+    #[allow(clippy::too_many_arguments)]
+    async fn list_resources_for_subject(
+        &self,
+        tenant_id: i32,
+        subject_type: SubjectType,
+        subject_id: &str,
+        owned_by: Option<&str>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<SubjectResourceRow>, i64)>;
+
+    /// List tuples matching the filters, newest first. With `after` set, pages by
+    /// keyset from that `(create_time, id)` and ignores `page`. Returns up to
+    /// `page_size + 1` rows so callers can tell whether another page exists;
+    /// `total` counts every match regardless of position.
+    #[allow(clippy::too_many_arguments)]
+    async fn list_permissions_filtered(
+        &self,
+        tenant_id: i32,
+        resource_type: Option<ResourceType>,
+        resource_id: Option<&str>,
+        subject_type: Option<SubjectType>,
+        subject_id: Option<&str>,
+        after: Option<(DateTime<Utc>, i32)>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<PermissionRow>, i64)>;
+}
+
 #[derive(Clone)]
 pub struct PermissionRepo {
     pool: PgPool,
+    reads: ReadPool,
 }
 
 impl PermissionRepo {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, reads: ReadPool) -> Self {
+        Self { pool, reads }
     }
+}
 
-    pub async fn has_permission(
+#[tonic::async_trait]
+impl PermissionStore for PermissionRepo {
+    async fn has_permission(
         &self,
         tenant_id: i32,
         resource_type: ResourceType,
@@ -35,7 +360,9 @@ impl PermissionRepo {
         subject_type: SubjectType,
         subject_id: &str,
     ) -> anyhow::Result<Option<PermissionRow>> {
-        let row = sqlx::query_as::<_, PermissionRow>(
+        let mut conn = self.reads.acquire().await?;
+        let row = sqlx::query_as!(
+            PermissionRecord,
             r#"
             SELECT * FROM bookmark_permissions
             WHERE tenant_id = $1
@@ -43,22 +370,23 @@ impl PermissionRepo {
               AND resource_id = $3
               AND subject_type = $4
               AND subject_id = $5
+              AND subject_tenant_id IS NULL
             LIMIT 1
             "#,
+            tenant_id,
+            resource_type.as_str(),
+            resource_id,
+            subject_type.as_str(),
+            subject_id,
         )
-        .bind(tenant_id)
-        .bind(resource_type.as_str())
-        .bind(resource_id)
-        .bind(subject_type.as_str())
-        .bind(subject_id)
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *conn)
         .await?;
 
-        Ok(row)
+        Ok(row.map(PermissionRow::try_from).transpose()?)
     }
 
     #[allow(clippy::too_many_arguments)]
-    pub async fn create_permission(
+    async fn create_permission(
         &self,
         tenant_id: i32,
         resource_type: ResourceType,
@@ -69,31 +397,179 @@ impl PermissionRepo {
         granted_by: Option<i32>,
         expires_at: Option<DateTime<Utc>>,
     ) -> anyhow::Result<PermissionRow> {
-        let row = sqlx::query_as::<_, PermissionRow>(
+        let row = sqlx::query_as!(
+            PermissionRecord,
             r#"
             INSERT INTO bookmark_permissions
-                (tenant_id, resource_type, resource_id, relation, subject_type, subject_id, granted_by, expires_at)
+                (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id, granted_by, expires_at)
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            ON CONFLICT (tenant_id, resource_type, resource_id, relation, subject_type, subject_id) DO UPDATE
+            ON CONFLICT (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id,
+                COALESCE(subject_tenant_id, 0)) DO UPDATE
                 SET granted_by = EXCLUDED.granted_by, expires_at = EXCLUDED.expires_at
             RETURNING *
             "#,
+            tenant_id,
+            resource_type.as_str(),
+            resource_id,
+            relation.code(),
+            subject_type.as_str(),
+            subject_id,
+            granted_by,
+            expires_at,
         )
-        .bind(tenant_id)
-        .bind(resource_type.as_str())
-        .bind(resource_id)
-        .bind(relation.as_str())
-        .bind(subject_type.as_str())
-        .bind(subject_id)
-        .bind(granted_by)
-        .bind(expires_at)
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(row)
+        Ok(row.try_into()?)
     }
 
-    pub async fn delete_permission(
+    async fn create_permissions(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        grants: &[(Relation, SubjectType, String)],
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<PermissionRow>> {
+        let mut tx = self.pool.begin().await?;
+        let mut rows = Vec::with_capacity(grants.len());
+
+        for (relation, subject_type, subject_id) in grants {
+            let row = sqlx::query_as!(
+                PermissionRecord,
+                r#"
+                INSERT INTO bookmark_permissions
+                    (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id, granted_by, expires_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id,
+                    COALESCE(subject_tenant_id, 0)) DO UPDATE
+                    SET granted_by = EXCLUDED.granted_by, expires_at = EXCLUDED.expires_at
+                RETURNING *
+                "#,
+                tenant_id,
+                resource_type.as_str(),
+                resource_id,
+                relation.code(),
+                subject_type.as_str(),
+                subject_id,
+                granted_by,
+                expires_at,
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+            rows.push(row.try_into()?);
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn batch_grant(
+        &self,
+        tenant_id: i32,
+        tuples: &[TupleGrant],
+        granted_by: Option<i32>,
+    ) -> anyhow::Result<Vec<PermissionRow>> {
+        if tuples.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let resource_types: Vec<&str> = tuples.iter().map(|t| t.resource_type.as_str()).collect();
+        let resource_ids: Vec<&str> = tuples.iter().map(|t| t.resource_id.as_str()).collect();
+        let relations: Vec<i16> = tuples.iter().map(|t| t.relation.code()).collect();
+        let subject_types: Vec<&str> = tuples.iter().map(|t| t.subject_type.as_str()).collect();
+        let subject_ids: Vec<&str> = tuples.iter().map(|t| t.subject_id.as_str()).collect();
+        let expires: Vec<Option<DateTime<Utc>>> = tuples.iter().map(|t| t.expires_at).collect();
+
+        let rows = sqlx::query_as!(
+            PermissionRecord,
+            r#"
+            INSERT INTO bookmark_permissions
+                (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id, granted_by, expires_at)
+            SELECT $1, t.resource_type, t.resource_id, t.relation_code, t.subject_type, t.subject_id,
+                   $2, t.expires_at
+            FROM UNNEST(
+                $3::VARCHAR[], $4::VARCHAR[], $5::SMALLINT[], $6::VARCHAR[], $7::VARCHAR[],
+                $8::TIMESTAMPTZ[]
+            ) AS t(resource_type, resource_id, relation_code, subject_type, subject_id, expires_at)
+            ON CONFLICT (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id,
+                COALESCE(subject_tenant_id, 0)) DO UPDATE
+                SET granted_by = EXCLUDED.granted_by, expires_at = EXCLUDED.expires_at
+            RETURNING *
+            "#,
+            tenant_id,
+            granted_by,
+            &resource_types as &[&str],
+            &resource_ids as &[&str],
+            &relations,
+            &subject_types as &[&str],
+            &subject_ids as &[&str],
+            &expires as &[Option<DateTime<Utc>>],
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(permission_rows(rows)?)
+    }
+
+    async fn batch_revoke(
+        &self,
+        tenant_id: i32,
+        tuples: &[TupleRevoke],
+    ) -> anyhow::Result<Vec<u64>> {
+        if tuples.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let resource_types: Vec<&str> = tuples.iter().map(|t| t.resource_type.as_str()).collect();
+        let resource_ids: Vec<&str> = tuples.iter().map(|t| t.resource_id.as_str()).collect();
+        let relations: Vec<Option<i16>> =
+            tuples.iter().map(|t| t.relation.map(Relation::code)).collect();
+        let subject_types: Vec<&str> = tuples.iter().map(|t| t.subject_type.as_str()).collect();
+        let subject_ids: Vec<&str> = tuples.iter().map(|t| t.subject_id.as_str()).collect();
+
+        // A row matched by several items is removed once and counted for one of them.
+        let counts = sqlx::query!(
+            r#"
+            WITH items AS (
+                SELECT * FROM UNNEST(
+                    $2::VARCHAR[], $3::VARCHAR[], $4::SMALLINT[], $5::VARCHAR[], $6::VARCHAR[]
+                ) WITH ORDINALITY
+                    AS i(resource_type, resource_id, relation_code, subject_type, subject_id, idx)
+            ),
+            removed AS (
+                DELETE FROM bookmark_permissions p
+                USING items i
+                WHERE p.tenant_id = $1
+                  AND p.resource_type = i.resource_type
+                  AND p.resource_id = i.resource_id
+                  AND (i.relation_code IS NULL OR p.relation_code = i.relation_code)
+                  AND p.subject_type = i.subject_type
+                  AND p.subject_id = i.subject_id
+                  AND p.subject_tenant_id IS NULL
+                RETURNING i.idx
+            )
+            SELECT idx AS "idx!", COUNT(*) AS "count!" FROM removed GROUP BY idx
+            "#,
+            tenant_id,
+            &resource_types as &[&str],
+            &resource_ids as &[&str],
+            &relations as &[Option<i16>],
+            &subject_types as &[&str],
+            &subject_ids as &[&str],
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut removed = vec![0; tuples.len()];
+        for row in counts {
+            removed[row.idx as usize - 1] = row.count as u64;
+        }
+        Ok(removed)
+    }
+
+    async fn delete_permission(
         &self,
         tenant_id: i32,
         resource_type: ResourceType,
@@ -103,27 +579,28 @@ impl PermissionRepo {
         subject_id: &str,
     ) -> anyhow::Result<u64> {
         let result = if let Some(rel) = relation {
-            sqlx::query(
+            sqlx::query!(
                 r#"
                 DELETE FROM bookmark_permissions
                 WHERE tenant_id = $1
                   AND resource_type = $2
                   AND resource_id = $3
-                  AND relation = $4
+                  AND relation_code = $4
                   AND subject_type = $5
                   AND subject_id = $6
+                  AND subject_tenant_id IS NULL
                 "#,
+                tenant_id,
+                resource_type.as_str(),
+                resource_id,
+                rel.code(),
+                subject_type.as_str(),
+                subject_id,
             )
-            .bind(tenant_id)
-            .bind(resource_type.as_str())
-            .bind(resource_id)
-            .bind(rel.as_str())
-            .bind(subject_type.as_str())
-            .bind(subject_id)
             .execute(&self.pool)
             .await?
         } else {
-            sqlx::query(
+            sqlx::query!(
                 r#"
                 DELETE FROM bookmark_permissions
                 WHERE tenant_id = $1
@@ -131,13 +608,14 @@ impl PermissionRepo {
                   AND resource_id = $3
                   AND subject_type = $4
                   AND subject_id = $5
+                  AND subject_tenant_id IS NULL
                 "#,
+                tenant_id,
+                resource_type.as_str(),
+                resource_id,
+                subject_type.as_str(),
+                subject_id,
             )
-            .bind(tenant_id)
-            .bind(resource_type.as_str())
-            .bind(resource_id)
-            .bind(subject_type.as_str())
-            .bind(subject_id)
             .execute(&self.pool)
             .await?
         };
@@ -145,87 +623,432 @@ impl PermissionRepo {
         Ok(result.rows_affected())
     }
 
-    pub async fn delete_all_for_resource(
+    #[allow(clippy::too_many_arguments)]
+    async fn transfer_ownership(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        from_user: &str,
+        to_user: &str,
+        keep_as_editor: bool,
+        granted_by: Option<i32>,
+    ) -> anyhow::Result<Option<PermissionRow>> {
+        let mut tx = self.pool.begin().await?;
+
+        let removed = sqlx::query!(
+            r#"
+            DELETE FROM bookmark_permissions
+            WHERE tenant_id = $1
+              AND resource_type = $2
+              AND resource_id = $3
+              AND relation_code = $4
+              AND subject_type = $5
+              AND subject_id = $6
+              AND subject_tenant_id IS NULL
+            "#,
+            tenant_id,
+            resource_type.as_str(),
+            resource_id,
+            Relation::Owner.code(),
+            SubjectType::User.as_str(),
+            from_user,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if removed.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let owner = sqlx::query_as!(
+            PermissionRecord,
+            r#"
+            INSERT INTO bookmark_permissions
+                (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id, granted_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NULL)
+            ON CONFLICT (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id,
+                COALESCE(subject_tenant_id, 0)) DO UPDATE
+                SET granted_by = EXCLUDED.granted_by, expires_at = NULL
+            RETURNING *
+            "#,
+            tenant_id,
+            resource_type.as_str(),
+            resource_id,
+            Relation::Owner.code(),
+            SubjectType::User.as_str(),
+            to_user,
+            granted_by,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if keep_as_editor {
+            sqlx::query!(
+                r#"
+                INSERT INTO bookmark_permissions
+                    (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id, granted_by)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id,
+                    COALESCE(subject_tenant_id, 0)) DO NOTHING
+                "#,
+                tenant_id,
+                resource_type.as_str(),
+                resource_id,
+                Relation::Editor.code(),
+                SubjectType::User.as_str(),
+                from_user,
+                granted_by,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(Some(owner.try_into()?))
+    }
+
+    async fn purge_expired(&self) -> anyhow::Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM bookmark_permissions WHERE expires_at IS NOT NULL AND expires_at < NOW()",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn backfill_relation_codes(&self) -> anyhow::Result<u64> {
+        const BATCH: i64 = 500;
+        let mut updated = 0;
+
+        // In batches, so no single transaction locks the whole table.
+        loop {
+            let result = sqlx::query!(
+                r#"
+                UPDATE bookmark_permissions SET relation_code = bookmark_relation_code(relation)
+                WHERE id IN (
+                    SELECT id FROM bookmark_permissions
+                    WHERE relation_code IS NULL AND bookmark_relation_code(relation) IS NOT NULL
+                    LIMIT $1
+                )
+                "#,
+                BATCH,
+            )
+            .execute(&self.pool)
+            .await?;
+            if result.rows_affected() == 0 {
+                return Ok(updated);
+            }
+            updated += result.rows_affected();
+        }
+    }
+
+    async fn delete_all_for_resource(
         &self,
         tenant_id: i32,
         resource_type: ResourceType,
         resource_id: &str,
     ) -> anyhow::Result<u64> {
-        let result = sqlx::query(
+        let result = sqlx::query!(
             r#"
             DELETE FROM bookmark_permissions
             WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3
             "#,
+            tenant_id,
+            resource_type.as_str(),
+            resource_id,
         )
-        .bind(tenant_id)
-        .bind(resource_type.as_str())
-        .bind(resource_id)
         .execute(&self.pool)
         .await?;
 
         Ok(result.rows_affected())
     }
 
-    pub async fn get_direct_permissions(
+    async fn get_direct_permissions(
         &self,
         tenant_id: i32,
         resource_type: ResourceType,
         resource_id: &str,
     ) -> anyhow::Result<Vec<PermissionRow>> {
-        let rows = sqlx::query_as::<_, PermissionRow>(
+        let rows = sqlx::query_as!(
+            PermissionRecord,
             r#"
             SELECT * FROM bookmark_permissions
             WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3
             ORDER BY create_time DESC
             "#,
+            tenant_id,
+            resource_type.as_str(),
+            resource_id,
         )
-        .bind(tenant_id)
-        .bind(resource_type.as_str())
-        .bind(resource_id)
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(rows)
+        Ok(permission_rows(rows)?)
     }
 
-    pub async fn list_resources_by_subject(
+    async fn list_resources_by_subject(
         &self,
         tenant_id: i32,
         subject_type: SubjectType,
         subject_id: &str,
         resource_type: ResourceType,
     ) -> anyhow::Result<Vec<String>> {
-        let rows: Vec<(String,)> = sqlx::query_as(
+        let rows = sqlx::query_scalar!(
             r#"
             SELECT DISTINCT resource_id FROM bookmark_permissions
             WHERE tenant_id = $1
               AND subject_type = $2
               AND subject_id = $3
               AND resource_type = $4
+              AND subject_tenant_id IS NULL
+            "#,
+            tenant_id,
+            subject_type.as_str(),
+            subject_id,
+            resource_type.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn list_tenant_resources(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>> {
+        let rows = match resource_type {
+            ResourceType::Bookmark => {
+                sqlx::query_scalar!(
+                    r#"SELECT id::TEXT AS "id!" FROM bookmark_bookmarks WHERE tenant_id = $1"#,
+                    tenant_id,
+                )
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        Ok(rows)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn grant_federated(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        subject_tenant_id: i32,
+        user_id: &str,
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<PermissionRow> {
+        let row = sqlx::query_as!(
+            PermissionRecord,
+            r#"
+            INSERT INTO bookmark_permissions
+                (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id,
+                 subject_tenant_id, granted_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id,
+                COALESCE(subject_tenant_id, 0)) DO UPDATE
+                SET granted_by = EXCLUDED.granted_by, expires_at = EXCLUDED.expires_at
+            RETURNING *
+            "#,
+            tenant_id,
+            resource_type.as_str(),
+            resource_id,
+            Relation::Viewer.code(),
+            SubjectType::User.as_str(),
+            user_id,
+            subject_tenant_id,
+            granted_by,
+            expires_at,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.try_into()?)
+    }
+
+    async fn has_federated_permission(
+        &self,
+        subject_tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        user_id: &str,
+    ) -> anyhow::Result<Option<PermissionRow>> {
+        let mut conn = self.reads.acquire().await?;
+        let row = sqlx::query_as!(
+            PermissionRecord,
+            r#"
+            SELECT * FROM bookmark_permissions
+            WHERE subject_tenant_id = $1
+              AND subject_type = $2
+              AND subject_id = $3
+              AND resource_type = $4
+              AND resource_id = $5
+              AND (expires_at IS NULL OR expires_at > NOW())
+            LIMIT 1
+            "#,
+            subject_tenant_id,
+            SubjectType::User.as_str(),
+            user_id,
+            resource_type.as_str(),
+            resource_id,
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(row.map(PermissionRow::try_from).transpose()?)
+    }
+
+    async fn list_federated_resources(
+        &self,
+        subject_tenant_id: i32,
+        user_id: &str,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>> {
+        let rows = sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT resource_id FROM bookmark_permissions
+            WHERE subject_tenant_id = $1
+              AND subject_type = $2
+              AND subject_id = $3
+              AND resource_type = $4
+              AND (expires_at IS NULL OR expires_at > NOW())
+            "#,
+            subject_tenant_id,
+            SubjectType::User.as_str(),
+            user_id,
+            resource_type.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn filter_owned(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        user_id: &str,
+        resource_ids: &[String],
+    ) -> anyhow::Result<Vec<String>> {
+        if resource_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let rows = sqlx::query_scalar!(
+            r#"
+            SELECT resource_id FROM bookmark_permissions
+            WHERE tenant_id = $1
+              AND resource_type = $2
+              AND relation_code = $3
+              AND subject_type = $4
+              AND subject_id = $5
+              AND resource_id = ANY($6)
+              AND (expires_at IS NULL OR expires_at > NOW())
             "#,
+            tenant_id,
+            resource_type.as_str(),
+            Relation::Owner.code(),
+            SubjectType::User.as_str(),
+            user_id,
+            resource_ids,
         )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn list_resources_for_subject(
+        &self,
+        tenant_id: i32,
+        subject_type: SubjectType,
+        subject_id: &str,
+        owned_by: Option<&str>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<SubjectResourceRow>, i64)> {
+        let offset = (page.saturating_sub(1)) * page_size;
+
+        const FROM_WHERE: &str = r#"
+            FROM bookmark_permissions p
+            JOIN bookmark_bookmarks b ON b.id::TEXT = p.resource_id AND b.tenant_id = p.tenant_id
+            WHERE ((p.tenant_id = $1 AND p.subject_tenant_id IS NULL) OR p.subject_tenant_id = $1)
+              AND p.resource_type = $2
+              AND p.subject_type = $3
+              AND p.subject_id = $4
+              AND ($5::TEXT IS NULL OR EXISTS (
+                  SELECT 1 FROM bookmark_permissions o
+                  WHERE o.tenant_id = p.tenant_id
+                    AND o.resource_type = p.resource_type
+                    AND o.resource_id = p.resource_id
+                    AND o.relation_code = $6
+                    AND o.subject_type = $7
+                    AND o.subject_id = $5
+                    AND o.subject_tenant_id IS NULL
+              ))
+        "#;
+
+        let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) {FROM_WHERE}"))
+            .bind(tenant_id)
+            .bind(ResourceType::Bookmark.as_str())
+            .bind(subject_type.as_str())
+            .bind(subject_id)
+            .bind(owned_by)
+            .bind(Relation::Owner.code())
+            .bind(SubjectType::User.as_str())
+            .fetch_one(&self.pool)
+            .await?;
+
+        let rows = sqlx::query_as::<_, SubjectResourceRow>(&format!(
+            r#"
+            SELECT p.resource_type, p.resource_id, p.relation_code, p.relation, p.expires_at,
+                   b.title, CASE WHEN b.is_private THEN '' ELSE b.url END AS url
+            {FROM_WHERE}
+            ORDER BY b.title, p.resource_id
+            LIMIT $8 OFFSET $9
+            "#
+        ))
         .bind(tenant_id)
+        .bind(ResourceType::Bookmark.as_str())
         .bind(subject_type.as_str())
         .bind(subject_id)
-        .bind(resource_type.as_str())
+        .bind(owned_by)
+        .bind(Relation::Owner.code())
+        .bind(SubjectType::User.as_str())
+        .bind(page_size as i64)
+        .bind(offset as i64)
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(rows.into_iter().map(|r| r.0).collect())
+        Ok((rows, total))
     }
 
     #[allow(clippy::too_many_arguments)]
-    pub async fn list_permissions_filtered(
+    async fn list_permissions_filtered(
         &self,
         tenant_id: i32,
         resource_type: Option<ResourceType>,
         resource_id: Option<&str>,
         subject_type: Option<SubjectType>,
         subject_id: Option<&str>,
+        after: Option<(DateTime<Utc>, i32)>,
         page: u32,
         page_size: u32,
     ) -> anyhow::Result<(Vec<PermissionRow>, i64)> {
-        let offset = (page.saturating_sub(1)) * page_size;
+        let offset = if after.is_some() {
+            0
+        } else {
+            (page.saturating_sub(1)) * page_size
+        };
 
         // Build dynamic query with optional filters
         let mut conditions = vec!["tenant_id = $1".to_string()];
@@ -250,8 +1073,17 @@ impl PermissionRepo {
 
         let where_clause = conditions.join(" AND ");
         let count_sql = format!("SELECT COUNT(*) FROM bookmark_permissions WHERE {where_clause}");
+
+        if after.is_some() {
+            conditions.push(format!(
+                "(create_time, id) < (${param_idx}, ${})",
+                param_idx + 1
+            ));
+            param_idx += 2;
+        }
+        let data_where = conditions.join(" AND ");
         let query_sql = format!(
-            "SELECT * FROM bookmark_permissions WHERE {where_clause} ORDER BY create_time DESC LIMIT ${param_idx} OFFSET ${}",
+            "SELECT * FROM bookmark_permissions WHERE {data_where} ORDER BY create_time DESC, id DESC LIMIT ${param_idx} OFFSET ${}",
             param_idx + 1
         );
 
@@ -269,7 +1101,8 @@ impl PermissionRepo {
         if let Some(si) = subject_id {
             count_query = count_query.bind(si);
         }
-        let (total,) = count_query.fetch_one(&self.pool).await?;
+        let mut conn = self.reads.acquire().await?;
+        let (total,) = count_query.fetch_one(&mut *conn).await?;
 
         // Execute data query
         let mut data_query = sqlx::query_as::<_, PermissionRow>(&query_sql).bind(tenant_id);
@@ -285,8 +1118,13 @@ impl PermissionRepo {
         if let Some(si) = subject_id {
             data_query = data_query.bind(si);
         }
-        data_query = data_query.bind(page_size as i64).bind(offset as i64);
-        let rows = data_query.fetch_all(&self.pool).await?;
+        if let Some((create_time, id)) = after {
+            data_query = data_query.bind(create_time).bind(id);
+        }
+        data_query = data_query
+            .bind(page_size as i64 + 1)
+            .bind(offset as i64);
+        let rows = data_query.fetch_all(&mut *conn).await?;
 
         Ok((rows, total))
     }
@@ -0,0 +1,154 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::PgPool;
+
+/// One grant of a template, with relation and subject type as stored in
+/// `bookmark_permissions`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateGrant {
+    pub relation: String,
+    pub subject_type: String,
+    pub subject_id: String,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PermissionTemplateRow {
+    pub id: i32,
+    pub tenant_id: i32,
+    pub name: String,
+    pub description: String,
+    pub grants: Json<Vec<TemplateGrant>>,
+    pub created_by: String,
+    pub create_time: DateTime<Utc>,
+    pub update_time: DateTime<Utc>,
+}
+
+#[tonic::async_trait]
+pub trait PermissionTemplateStore: Send + Sync {
+    /// Returns `None` if the tenant already has a template with that name.
+    async fn create(
+        &self,
+        tenant_id: i32,
+        name: &str,
+        description: &str,
+        grants: &[TemplateGrant],
+        created_by: &str,
+    ) -> anyhow::Result<Option<PermissionTemplateRow>>;
+
+    async fn list(&self, tenant_id: i32) -> anyhow::Result<Vec<PermissionTemplateRow>>;
+
+    async fn get(&self, tenant_id: i32, id: i32) -> anyhow::Result<Option<PermissionTemplateRow>>;
+
+    async fn update(
+        &self,
+        tenant_id: i32,
+        id: i32,
+        description: &str,
+        grants: &[TemplateGrant],
+    ) -> anyhow::Result<Option<PermissionTemplateRow>>;
+
+    /// Returns false if the tenant has no template with that ID.
+    async fn delete(&self, tenant_id: i32, id: i32) -> anyhow::Result<bool>;
+}
+
+#[derive(Clone)]
+pub struct PermissionTemplateRepo {
+    pool: PgPool,
+}
+
+impl PermissionTemplateRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl PermissionTemplateStore for PermissionTemplateRepo {
+    async fn create(
+        &self,
+        tenant_id: i32,
+        name: &str,
+        description: &str,
+        grants: &[TemplateGrant],
+        created_by: &str,
+    ) -> anyhow::Result<Option<PermissionTemplateRow>> {
+        let row = sqlx::query_as::<_, PermissionTemplateRow>(
+            r#"
+            INSERT INTO bookmark_permission_templates
+                (tenant_id, name, description, grants, created_by)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (tenant_id, name) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .bind(description)
+        .bind(Json(grants))
+        .bind(created_by)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list(&self, tenant_id: i32) -> anyhow::Result<Vec<PermissionTemplateRow>> {
+        let rows = sqlx::query_as::<_, PermissionTemplateRow>(
+            "SELECT * FROM bookmark_permission_templates WHERE tenant_id = $1 ORDER BY name",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn get(&self, tenant_id: i32, id: i32) -> anyhow::Result<Option<PermissionTemplateRow>> {
+        let row = sqlx::query_as::<_, PermissionTemplateRow>(
+            "SELECT * FROM bookmark_permission_templates WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn update(
+        &self,
+        tenant_id: i32,
+        id: i32,
+        description: &str,
+        grants: &[TemplateGrant],
+    ) -> anyhow::Result<Option<PermissionTemplateRow>> {
+        let row = sqlx::query_as::<_, PermissionTemplateRow>(
+            r#"
+            UPDATE bookmark_permission_templates
+            SET description = $3, grants = $4, update_time = NOW()
+            WHERE tenant_id = $1 AND id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .bind(description)
+        .bind(Json(grants))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn delete(&self, tenant_id: i32, id: i32) -> anyhow::Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM bookmark_permission_templates WHERE tenant_id = $1 AND id = $2")
+                .bind(tenant_id)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
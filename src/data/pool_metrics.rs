@@ -0,0 +1,113 @@
+use std::time::{Duration, Instant};
+
+use opentelemetry::KeyValue;
+use sqlx::{PgPool, SqlitePool};
+
+use crate::shutdown::ShutdownSignal;
+
+/// A connection pool whose utilization is exported as metrics.
+#[derive(Clone)]
+pub enum MonitoredPool {
+    Postgres(PgPool),
+    Sqlite(SqlitePool),
+}
+
+impl MonitoredPool {
+    fn size(&self) -> u64 {
+        let size = match self {
+            Self::Postgres(pool) => pool.size(),
+            Self::Sqlite(pool) => pool.size(),
+        };
+        size.into()
+    }
+
+    fn idle(&self) -> u64 {
+        let idle = match self {
+            Self::Postgres(pool) => pool.num_idle(),
+            Self::Sqlite(pool) => pool.num_idle(),
+        };
+        idle as u64
+    }
+
+    fn max(&self) -> u64 {
+        let max = match self {
+            Self::Postgres(pool) => pool.options().get_max_connections(),
+            Self::Sqlite(pool) => pool.options().get_max_connections(),
+        };
+        max.into()
+    }
+
+    /// Check a connection out and straight back in.
+    pub async fn acquire(&self) -> sqlx::Result<()> {
+        match self {
+            Self::Postgres(pool) => pool.acquire().await.map(drop),
+            Self::Sqlite(pool) => pool.acquire().await.map(drop),
+        }
+    }
+}
+
+/// Export connection counts for every pool, and sample how long checking out
+/// a connection takes every `interval`: the wait a request would see then.
+pub fn spawn_pool_metrics(
+    pools: Vec<(&'static str, MonitoredPool)>,
+    interval: Duration,
+    mut shutdown: ShutdownSignal,
+) -> tokio::task::JoinHandle<()> {
+    let meter = opentelemetry::global::meter("bookmark");
+    let observed = pools.clone();
+    meter
+        .u64_observable_gauge("bookmark.db.pool.connections")
+        .with_description("Open pool connections by state (idle or in_use)")
+        .with_callback(move |gauge| {
+            for (name, pool) in &observed {
+                let (size, idle) = (pool.size(), pool.idle());
+                let pool_attr = KeyValue::new("pool", *name);
+                gauge.observe(idle, &[pool_attr.clone(), KeyValue::new("state", "idle")]);
+                gauge.observe(
+                    size.saturating_sub(idle),
+                    &[pool_attr, KeyValue::new("state", "in_use")],
+                );
+            }
+        })
+        .build();
+    let observed = pools.clone();
+    meter
+        .u64_observable_gauge("bookmark.db.pool.max_connections")
+        .with_description("Configured pool size limit")
+        .with_callback(move |gauge| {
+            for (name, pool) in &observed {
+                gauge.observe(pool.max(), &[KeyValue::new("pool", *name)]);
+            }
+        })
+        .build();
+    let acquire_duration = meter
+        .f64_histogram("bookmark.db.pool.acquire_duration")
+        .with_description("Time to check out a pool connection, sampled")
+        .with_unit("s")
+        .build();
+    let acquire_errors = meter
+        .u64_counter("bookmark.db.pool.acquire_errors")
+        .with_description("Sampled connection checkouts that failed")
+        .build();
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.recv() => break,
+            }
+            for (name, pool) in &pools {
+                let attrs = [KeyValue::new("pool", *name)];
+                let started = Instant::now();
+                match pool.acquire().await {
+                    Ok(()) => acquire_duration.record(started.elapsed().as_secs_f64(), &attrs),
+                    Err(e) => {
+                        acquire_errors.add(1, &attrs);
+                        tracing::warn!(pool = name, error = %e, "sampling pool acquire failed");
+                    }
+                }
+            }
+        }
+    })
+}
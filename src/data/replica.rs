@@ -0,0 +1,142 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{PgConnection, PgPool, Postgres};
+
+/// How long to stop trying the replica after it failed to hand out a connection.
+const REPLICA_COOLDOWN: Duration = Duration::from_secs(30);
+const REPLICA_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Pool for read-only queries: the replica when configured and reachable,
+/// otherwise the primary. Reads may lag writes by the replication delay.
+#[derive(Clone)]
+pub struct ReadPool {
+    primary: PgPool,
+    replica: Option<PgPool>,
+    down_until: Arc<Mutex<Option<Instant>>>,
+}
+
+impl ReadPool {
+    pub fn new(primary: PgPool, replica: Option<PgPool>) -> Self {
+        Self {
+            primary,
+            replica,
+            down_until: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn replica(&self) -> Option<&PgPool> {
+        self.replica.as_ref()
+    }
+
+    /// Connect lazily so an unreachable replica does not block startup.
+    pub fn connect_replica(options: PgConnectOptions, max_connections: u32) -> PgPool {
+        PgPoolOptions::new()
+            .max_connections(max_connections)
+            .acquire_timeout(REPLICA_ACQUIRE_TIMEOUT)
+            .connect_lazy_with(options)
+    }
+
+    /// A connection from the replica, falling back to the primary while the
+    /// replica is unreachable.
+    pub async fn acquire(&self) -> sqlx::Result<PoolConnection<Postgres>> {
+        self.acquire_from().await.map(|(conn, _)| conn)
+    }
+
+    /// Like [`acquire`](Self::acquire), for queries expensive enough that they
+    /// should not outlive the request: the connection's backend is cancelled
+    /// if the guard is dropped before [`CancellableConn::finish`].
+    pub async fn acquire_cancellable(&self) -> sqlx::Result<CancellableConn> {
+        let (mut conn, pool) = self.acquire_from().await?;
+        let pid = sqlx::query_scalar("SELECT pg_backend_pid()")
+            .fetch_one(&mut *conn)
+            .await?;
+        Ok(CancellableConn {
+            conn: Some(conn),
+            pool: pool.clone(),
+            pid,
+        })
+    }
+
+    async fn acquire_from(&self) -> sqlx::Result<(PoolConnection<Postgres>, &PgPool)> {
+        if let Some(replica) = self.replica.as_ref().filter(|_| self.replica_usable()) {
+            match replica.acquire().await {
+                Ok(conn) => return Ok((conn, replica)),
+                Err(e) => {
+                    tracing::warn!(error = %e, "read replica unavailable, using primary");
+                    *self.down_until.lock().unwrap() = Some(Instant::now() + REPLICA_COOLDOWN);
+                }
+            }
+        }
+        Ok((self.primary.acquire().await?, &self.primary))
+    }
+
+    fn replica_usable(&self) -> bool {
+        let mut down_until = self.down_until.lock().unwrap();
+        match *down_until {
+            Some(until) if Instant::now() < until => false,
+            Some(_) => {
+                *down_until = None;
+                true
+            }
+            None => true,
+        }
+    }
+}
+
+/// A pooled connection whose query is cancelled on the server when the request
+/// awaiting it goes away. Dropping a query future only stops waiting for it;
+/// Postgres would run it to completion (or `statement_timeout`) and the pool
+/// would wait for that before reusing the connection.
+pub struct CancellableConn {
+    conn: Option<PoolConnection<Postgres>>,
+    pool: PgPool,
+    pid: i32,
+}
+
+impl CancellableConn {
+    /// The queries completed; return the connection to the pool.
+    pub fn finish(mut self) {
+        self.conn.take();
+    }
+}
+
+impl Deref for CancellableConn {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &PgConnection {
+        self.conn.as_ref().expect("connection taken only on finish")
+    }
+}
+
+impl DerefMut for CancellableConn {
+    fn deref_mut(&mut self) -> &mut PgConnection {
+        self.conn.as_mut().expect("connection taken only on finish")
+    }
+}
+
+impl Drop for CancellableConn {
+    /// The connection is detached rather than returned, so a late cancel can
+    /// never hit a query that reused it, and is closed once the cancel is sent.
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+        let conn = conn.detach();
+        let (pool, pid) = (self.pool.clone(), self.pid);
+        tokio::spawn(async move {
+            match sqlx::query("SELECT pg_cancel_backend($1)")
+                .bind(pid)
+                .execute(&pool)
+                .await
+            {
+                Ok(_) => tracing::debug!(pid, "cancelled abandoned query"),
+                Err(e) => tracing::warn!(pid, error = %e, "failed to cancel abandoned query"),
+            }
+            drop(conn);
+        });
+    }
+}
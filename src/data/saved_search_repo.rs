@@ -0,0 +1,263 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SavedSearchRow {
+    pub id: i32,
+    pub tenant_id: i32,
+    pub user_id: String,
+    pub name: String,
+    pub query: String,
+    pub filter: String,
+    /// Seconds between alerts; `None` when not subscribed.
+    pub alert_interval_secs: Option<i64>,
+    pub next_alert_at: Option<DateTime<Utc>>,
+    /// Alerts cover bookmarks created after this.
+    pub last_alert_time: Option<DateTime<Utc>>,
+    pub create_time: DateTime<Utc>,
+    pub update_time: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct NewSavedSearch {
+    pub tenant_id: i32,
+    pub user_id: String,
+    pub name: String,
+    pub query: String,
+    pub filter: String,
+}
+
+/// Saved searches are private to the user who saved them, so every lookup
+/// but the alert job's is scoped to one user.
+#[tonic::async_trait]
+pub trait SavedSearchStore: Send + Sync {
+    /// Returns `None` if the user already has a saved search with that name.
+    async fn create(&self, search: &NewSavedSearch) -> anyhow::Result<Option<SavedSearchRow>>;
+
+    async fn get(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        id: i32,
+    ) -> anyhow::Result<Option<SavedSearchRow>>;
+
+    async fn list(&self, tenant_id: i32, user_id: &str) -> anyhow::Result<Vec<SavedSearchRow>>;
+
+    /// Returns false if the user has no saved search with that ID.
+    async fn delete(&self, tenant_id: i32, user_id: &str, id: i32) -> anyhow::Result<bool>;
+
+    /// Alert on the search every `interval_secs`, first one interval from now.
+    /// A new subscription only reports bookmarks created from now on; changing
+    /// the interval of an existing one keeps its place.
+    async fn subscribe(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        id: i32,
+        interval_secs: i64,
+    ) -> anyhow::Result<Option<SavedSearchRow>>;
+
+    async fn unsubscribe(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        id: i32,
+    ) -> anyhow::Result<Option<SavedSearchRow>>;
+
+    /// Lease up to `limit` subscribed searches that are due, across tenants.
+    async fn claim_due(
+        &self,
+        limit: i64,
+        lease_until: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<SavedSearchRow>>;
+
+    /// Record that bookmarks created up to `through` were reported, and when
+    /// the search is next due. No-op if it was unsubscribed meanwhile.
+    async fn mark_alerted(
+        &self,
+        id: i32,
+        through: DateTime<Utc>,
+        next_alert_at: DateTime<Utc>,
+    ) -> anyhow::Result<()>;
+}
+
+#[derive(Clone)]
+pub struct SavedSearchRepo {
+    pool: PgPool,
+}
+
+impl SavedSearchRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl SavedSearchStore for SavedSearchRepo {
+    async fn create(&self, s: &NewSavedSearch) -> anyhow::Result<Option<SavedSearchRow>> {
+        let row = sqlx::query_as::<_, SavedSearchRow>(
+            r#"
+            INSERT INTO bookmark_saved_searches (tenant_id, user_id, name, query, filter)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (tenant_id, user_id, name) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(s.tenant_id)
+        .bind(&s.user_id)
+        .bind(&s.name)
+        .bind(&s.query)
+        .bind(&s.filter)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn get(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        id: i32,
+    ) -> anyhow::Result<Option<SavedSearchRow>> {
+        let row = sqlx::query_as::<_, SavedSearchRow>(
+            "SELECT * FROM bookmark_saved_searches \
+             WHERE tenant_id = $1 AND user_id = $2 AND id = $3",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list(&self, tenant_id: i32, user_id: &str) -> anyhow::Result<Vec<SavedSearchRow>> {
+        let rows = sqlx::query_as::<_, SavedSearchRow>(
+            "SELECT * FROM bookmark_saved_searches \
+             WHERE tenant_id = $1 AND user_id = $2 ORDER BY name",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn delete(&self, tenant_id: i32, user_id: &str, id: i32) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            "DELETE FROM bookmark_saved_searches \
+             WHERE tenant_id = $1 AND user_id = $2 AND id = $3",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn subscribe(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        id: i32,
+        interval_secs: i64,
+    ) -> anyhow::Result<Option<SavedSearchRow>> {
+        let row = sqlx::query_as::<_, SavedSearchRow>(
+            r#"
+            UPDATE bookmark_saved_searches
+            SET alert_interval_secs = $4,
+                next_alert_at = NOW() + make_interval(secs => $4),
+                last_alert_time = CASE
+                    WHEN alert_interval_secs IS NULL THEN NOW() ELSE last_alert_time
+                END,
+                update_time = NOW()
+            WHERE tenant_id = $1 AND user_id = $2 AND id = $3
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(id)
+        .bind(interval_secs)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn unsubscribe(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        id: i32,
+    ) -> anyhow::Result<Option<SavedSearchRow>> {
+        let row = sqlx::query_as::<_, SavedSearchRow>(
+            r#"
+            UPDATE bookmark_saved_searches
+            SET alert_interval_secs = NULL, next_alert_at = NULL, update_time = NOW()
+            WHERE tenant_id = $1 AND user_id = $2 AND id = $3
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn claim_due(
+        &self,
+        limit: i64,
+        lease_until: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<SavedSearchRow>> {
+        let rows = sqlx::query_as::<_, SavedSearchRow>(
+            r#"
+            UPDATE bookmark_saved_searches SET next_alert_at = $2
+            WHERE id IN (
+                SELECT id FROM bookmark_saved_searches
+                WHERE alert_interval_secs IS NOT NULL AND next_alert_at <= NOW()
+                ORDER BY next_alert_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_until)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn mark_alerted(
+        &self,
+        id: i32,
+        through: DateTime<Utc>,
+        next_alert_at: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE bookmark_saved_searches
+            SET last_alert_time = $2, next_alert_at = $3
+            WHERE id = $1 AND alert_interval_secs IS NOT NULL
+            "#,
+        )
+        .bind(id)
+        .bind(through)
+        .bind(next_alert_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ScrubPolicyRow {
+    pub tenant_id: i32,
+    pub enabled: bool,
+    pub strip_params: Vec<String>,
+    pub param_patterns: Vec<String>,
+    pub update_time: DateTime<Utc>,
+}
+
+/// Per-tenant URL scrub policy storage.
+#[tonic::async_trait]
+pub trait ScrubPolicyStore: Send + Sync {
+    async fn get(&self, tenant_id: i32) -> anyhow::Result<Option<ScrubPolicyRow>>;
+
+    async fn upsert(
+        &self,
+        tenant_id: i32,
+        enabled: bool,
+        strip_params: &[String],
+        param_patterns: &[String],
+    ) -> anyhow::Result<ScrubPolicyRow>;
+}
+
+#[derive(Clone)]
+pub struct ScrubPolicyRepo {
+    pool: PgPool,
+}
+
+impl ScrubPolicyRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl ScrubPolicyStore for ScrubPolicyRepo {
+    async fn get(&self, tenant_id: i32) -> anyhow::Result<Option<ScrubPolicyRow>> {
+        let row = sqlx::query_as::<_, ScrubPolicyRow>(
+            "SELECT * FROM bookmark_url_scrub_policies WHERE tenant_id = $1",
+        )
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn upsert(
+        &self,
+        tenant_id: i32,
+        enabled: bool,
+        strip_params: &[String],
+        param_patterns: &[String],
+    ) -> anyhow::Result<ScrubPolicyRow> {
+        let row = sqlx::query_as::<_, ScrubPolicyRow>(
+            r#"
+            INSERT INTO bookmark_url_scrub_policies (tenant_id, enabled, strip_params, param_patterns)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (tenant_id) DO UPDATE
+                SET enabled = EXCLUDED.enabled,
+                    strip_params = EXCLUDED.strip_params,
+                    param_patterns = EXCLUDED.param_patterns,
+                    update_time = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(enabled)
+        .bind(strip_params)
+        .bind(param_patterns)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+}
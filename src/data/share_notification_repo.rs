@@ -0,0 +1,173 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+
+/// A notification owed to the recipient of a share.
+#[derive(Debug, Clone)]
+pub struct NewShareNotification {
+    pub tenant_id: i32,
+    pub permission_id: i32,
+    pub bookmark_id: String,
+    pub recipient_id: String,
+    pub shared_by: String,
+    pub relation: String,
+    pub message: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareNotificationRow {
+    pub id: i32,
+    pub tenant_id: i32,
+    #[serde(skip)]
+    pub permission_id: i32,
+    pub bookmark_id: String,
+    pub recipient_id: String,
+    pub shared_by: String,
+    pub relation: String,
+    pub message: String,
+    pub expires_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub attempts: i32,
+    pub create_time: DateTime<Utc>,
+}
+
+#[tonic::async_trait]
+pub trait ShareNotificationStore: Send + Sync {
+    /// Record a pending notification, leased to the caller until `lease_until`.
+    async fn create(
+        &self,
+        notification: &NewShareNotification,
+        lease_until: DateTime<Utc>,
+    ) -> anyhow::Result<ShareNotificationRow>;
+
+    /// Lease up to `limit` pending notifications whose last lease has run out.
+    async fn claim_due(
+        &self,
+        limit: i64,
+        lease_until: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<ShareNotificationRow>>;
+
+    async fn mark_sent(&self, id: i32) -> anyhow::Result<()>;
+
+    /// Count a failed attempt. With `retry_at` unset the notification is given up.
+    async fn record_failure(
+        &self,
+        id: i32,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<()>;
+}
+
+const COLUMNS: &str = "id, tenant_id, permission_id, bookmark_id, recipient_id, shared_by, \
+                       relation, message, expires_at, attempts, create_time";
+
+#[derive(Clone)]
+pub struct ShareNotificationRepo {
+    pool: PgPool,
+}
+
+impl ShareNotificationRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl ShareNotificationStore for ShareNotificationRepo {
+    async fn create(
+        &self,
+        n: &NewShareNotification,
+        lease_until: DateTime<Utc>,
+    ) -> anyhow::Result<ShareNotificationRow> {
+        let row = sqlx::query_as::<_, ShareNotificationRow>(&format!(
+            r#"
+            INSERT INTO bookmark_share_notifications
+                (tenant_id, permission_id, bookmark_id, recipient_id, shared_by, relation,
+                 message, expires_at, next_attempt_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING {COLUMNS}
+            "#
+        ))
+        .bind(n.tenant_id)
+        .bind(n.permission_id)
+        .bind(&n.bookmark_id)
+        .bind(&n.recipient_id)
+        .bind(&n.shared_by)
+        .bind(&n.relation)
+        .bind(&n.message)
+        .bind(n.expires_at)
+        .bind(lease_until)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn claim_due(
+        &self,
+        limit: i64,
+        lease_until: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<ShareNotificationRow>> {
+        let rows = sqlx::query_as::<_, ShareNotificationRow>(&format!(
+            r#"
+            UPDATE bookmark_share_notifications SET next_attempt_at = $2
+            WHERE id IN (
+                SELECT id FROM bookmark_share_notifications
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {COLUMNS}
+            "#
+        ))
+        .bind(limit)
+        .bind(lease_until)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn mark_sent(&self, id: i32) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE bookmark_share_notifications
+            SET status = 'sent', attempts = attempts + 1, last_error = NULL, sent_time = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_failure(
+        &self,
+        id: i32,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE bookmark_share_notifications
+            SET attempts = attempts + 1,
+                last_error = $2,
+                status = CASE WHEN $3::TIMESTAMPTZ IS NULL THEN 'failed' ELSE status END,
+                next_attempt_at = COALESCE($3, next_attempt_at)
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .bind(retry_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct ShortLinkRow {
+    pub tenant_id: i32,
+    pub code: String,
+    pub bookmark_id: Uuid,
+    pub created_by: String,
+    pub create_time: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Short codes for bookmarks; codes are unique within a tenant.
+#[tonic::async_trait]
+pub trait ShortLinkStore: Send + Sync {
+    /// Returns `None` if the code is already taken in the tenant.
+    async fn create(
+        &self,
+        tenant_id: i32,
+        code: &str,
+        bookmark_id: Uuid,
+        created_by: &str,
+    ) -> anyhow::Result<Option<ShortLinkRow>>;
+
+    async fn get(&self, tenant_id: i32, code: &str) -> anyhow::Result<Option<ShortLinkRow>>;
+
+    /// Mark a link revoked. Returns false if no active link has that code.
+    async fn revoke(&self, tenant_id: i32, code: &str) -> anyhow::Result<bool>;
+
+    /// Bookmark ID and URL behind an active link. Links to private bookmarks
+    /// do not resolve.
+    async fn resolve(&self, tenant_id: i32, code: &str) -> anyhow::Result<Option<(Uuid, String)>>;
+}
+
+#[derive(Clone)]
+pub struct ShortLinkRepo {
+    pool: PgPool,
+}
+
+impl ShortLinkRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl ShortLinkStore for ShortLinkRepo {
+    async fn create(
+        &self,
+        tenant_id: i32,
+        code: &str,
+        bookmark_id: Uuid,
+        created_by: &str,
+    ) -> anyhow::Result<Option<ShortLinkRow>> {
+        let row = sqlx::query_as::<_, ShortLinkRow>(
+            r#"
+            INSERT INTO bookmark_short_links (tenant_id, code, bookmark_id, created_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (tenant_id, code) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(code)
+        .bind(bookmark_id)
+        .bind(created_by)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn get(&self, tenant_id: i32, code: &str) -> anyhow::Result<Option<ShortLinkRow>> {
+        let row = sqlx::query_as::<_, ShortLinkRow>(
+            "SELECT * FROM bookmark_short_links WHERE tenant_id = $1 AND code = $2",
+        )
+        .bind(tenant_id)
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn revoke(&self, tenant_id: i32, code: &str) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE bookmark_short_links SET revoked_at = NOW()
+            WHERE tenant_id = $1 AND code = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(code)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn resolve(&self, tenant_id: i32, code: &str) -> anyhow::Result<Option<(Uuid, String)>> {
+        let row = sqlx::query_as::<_, (Uuid, String)>(
+            r#"
+            SELECT b.id, b.url
+            FROM bookmark_short_links l
+            JOIN bookmark_bookmarks b ON b.id = l.bookmark_id
+            WHERE l.tenant_id = $1 AND l.code = $2 AND l.revoked_at IS NULL AND NOT b.is_private
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+}
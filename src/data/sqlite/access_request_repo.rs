@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use crate::authz::relations::{Relation, ResourceType, SubjectType};
+use crate::data::access_request_repo::{
+    AccessRequestFilter, AccessRequestRow, AccessRequestStore, NewAccessRequest,
+};
+
+#[derive(Clone)]
+pub struct SqliteAccessRequestRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteAccessRequestRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl AccessRequestStore for SqliteAccessRequestRepo {
+    async fn create(
+        &self,
+        r: &NewAccessRequest,
+    ) -> anyhow::Result<Option<AccessRequestRow>> {
+        let row = sqlx::query_as::<_, AccessRequestRow>(
+            r#"
+            INSERT INTO bookmark_access_requests
+                (tenant_id, bookmark_id, requester_id, relation, message, create_time)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (tenant_id, bookmark_id, requester_id) WHERE status = 'pending'
+            DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(r.tenant_id)
+        .bind(&r.bookmark_id)
+        .bind(&r.requester_id)
+        .bind(&r.relation)
+        .bind(&r.message)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn get(&self, tenant_id: i32, id: i32) -> anyhow::Result<Option<AccessRequestRow>> {
+        let row = sqlx::query_as::<_, AccessRequestRow>(
+            "SELECT * FROM bookmark_access_requests WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn resolve(
+        &self,
+        tenant_id: i32,
+        id: i32,
+        status: &str,
+        resolved_by: &str,
+        note: &str,
+        permission_id: Option<i32>,
+    ) -> anyhow::Result<Option<AccessRequestRow>> {
+        let row = sqlx::query_as::<_, AccessRequestRow>(
+            r#"
+            UPDATE bookmark_access_requests
+            SET status = $3, resolved_by = $4, resolution_note = $5, permission_id = $6,
+                resolve_time = $7
+            WHERE tenant_id = $1 AND id = $2 AND status = 'pending'
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .bind(status)
+        .bind(resolved_by)
+        .bind(note)
+        .bind(permission_id)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list(
+        &self,
+        tenant_id: i32,
+        f: &AccessRequestFilter<'_>,
+        after: Option<(DateTime<Utc>, i32)>,
+        page_size: u32,
+    ) -> anyhow::Result<Vec<AccessRequestRow>> {
+        let rows = sqlx::query_as::<_, AccessRequestRow>(
+            r#"
+            SELECT * FROM bookmark_access_requests r
+            WHERE r.tenant_id = $1
+              AND ($2 IS NULL OR r.bookmark_id = $2)
+              AND ($3 IS NULL OR r.requester_id = $3)
+              AND ($4 IS NULL OR r.status = $4)
+              AND ($5 IS NULL OR EXISTS (
+                  SELECT 1 FROM bookmark_permissions o
+                  WHERE o.tenant_id = r.tenant_id
+                    AND o.resource_type = $6
+                    AND o.resource_id = r.bookmark_id
+                    AND o.relation = $7
+                    AND o.subject_type = $8
+                    AND o.subject_id = $5
+              ))
+              AND ($9 IS NULL OR (r.create_time, r.id) < ($9, $10))
+            ORDER BY r.create_time DESC, r.id DESC
+            LIMIT $11
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(f.bookmark_id)
+        .bind(f.requester_id)
+        .bind(f.status)
+        .bind(f.owned_by)
+        .bind(ResourceType::Bookmark.as_str())
+        .bind(Relation::Owner.as_str())
+        .bind(SubjectType::User.as_str())
+        .bind(after.map(|(t, _)| t))
+        .bind(after.map_or(0, |(_, id)| id))
+        .bind(page_size as i64 + 1)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::types::Json;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use super::uuid_col;
+use crate::data::api_key_repo::{ApiKeyRow, ApiKeyStore};
+
+fn api_key_row(row: &SqliteRow) -> sqlx::Result<ApiKeyRow> {
+    Ok(ApiKeyRow {
+        id: uuid_col(row, "id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        name: row.try_get("name")?,
+        key_prefix: row.try_get("key_prefix")?,
+        key_hash: row.try_get("key_hash")?,
+        roles: row.try_get::<Json<Vec<String>>, _>("roles")?.0,
+        created_by: row.try_get("created_by")?,
+        create_time: row.try_get("create_time")?,
+        expires_at: row.try_get("expires_at")?,
+        revoked_at: row.try_get("revoked_at")?,
+        last_used_at: row.try_get("last_used_at")?,
+    })
+}
+
+#[derive(Clone)]
+pub struct SqliteApiKeyRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteApiKeyRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl ApiKeyStore for SqliteApiKeyRepo {
+    #[allow(clippy::too_many_arguments)]
+    async fn create(
+        &self,
+        id: Uuid,
+        tenant_id: i32,
+        name: &str,
+        key_prefix: &str,
+        key_hash: &str,
+        roles: &[String],
+        created_by: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<ApiKeyRow> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO bookmark_api_keys
+                (id, tenant_id, name, key_prefix, key_hash, roles, created_by, expires_at, create_time)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+            "#,
+        )
+        .bind(id.hyphenated())
+        .bind(tenant_id)
+        .bind(name)
+        .bind(key_prefix)
+        .bind(key_hash)
+        .bind(Json(roles))
+        .bind(created_by)
+        .bind(expires_at)
+        .bind(Utc::now())
+        .try_map(|r| api_key_row(&r))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list(&self, tenant_id: i32, include_revoked: bool) -> anyhow::Result<Vec<ApiKeyRow>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM bookmark_api_keys
+            WHERE tenant_id = $1 AND ($2 OR revoked_at IS NULL)
+            ORDER BY create_time DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(include_revoked)
+        .try_map(|r| api_key_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn revoke(&self, tenant_id: i32, id: Uuid) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE bookmark_api_keys SET revoked_at = $3
+            WHERE tenant_id = $1 AND id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id.hyphenated())
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find_active(&self, key_hash: &str) -> anyhow::Result<Option<ApiKeyRow>> {
+        let row = sqlx::query(
+            r#"
+            SELECT * FROM bookmark_api_keys
+            WHERE key_hash = $1
+              AND revoked_at IS NULL
+              AND (expires_at IS NULL OR expires_at > $2)
+            "#,
+        )
+        .bind(key_hash)
+        .bind(Utc::now())
+        .try_map(|r| api_key_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn touch(&self, id: Uuid) -> anyhow::Result<()> {
+        sqlx::query("UPDATE bookmark_api_keys SET last_used_at = $2 WHERE id = $1")
+            .bind(id.hyphenated())
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::Utc;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use super::uuid_col;
+use crate::data::archive_repo::{ArchiveRow, ArchiveStore};
+
+fn archive_row(row: &SqliteRow) -> sqlx::Result<ArchiveRow> {
+    Ok(ArchiveRow {
+        bookmark_id: uuid_col(row, "bookmark_id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        url: row.try_get("url")?,
+        format: row.try_get("format")?,
+        content: row.try_get("content")?,
+        content_bytes: row.try_get("content_bytes")?,
+        truncated: row.try_get("truncated")?,
+        archive_time: row.try_get("archive_time")?,
+    })
+}
+
+#[derive(Clone)]
+pub struct SqliteArchiveRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteArchiveRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl ArchiveStore for SqliteArchiveRepo {
+    async fn get(&self, tenant_id: i32, bookmark_id: Uuid) -> anyhow::Result<Option<ArchiveRow>> {
+        let row = sqlx::query(
+            "SELECT * FROM bookmark_archives WHERE bookmark_id = $1 AND tenant_id = $2",
+        )
+        .bind(bookmark_id.hyphenated())
+        .bind(tenant_id)
+        .try_map(|r| archive_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn upsert(
+        &self,
+        bookmark_id: Uuid,
+        tenant_id: i32,
+        url: &str,
+        format: &str,
+        content: &str,
+        search_text: &str,
+        truncated: bool,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO bookmark_archives
+                (bookmark_id, tenant_id, url, format, content, content_bytes, truncated, search_text, archive_time)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (bookmark_id) DO UPDATE SET
+                url = excluded.url,
+                format = excluded.format,
+                content = excluded.content,
+                content_bytes = excluded.content_bytes,
+                truncated = excluded.truncated,
+                search_text = excluded.search_text,
+                archive_time = excluded.archive_time
+            "#,
+        )
+        .bind(bookmark_id.hyphenated())
+        .bind(tenant_id)
+        .bind(url)
+        .bind(format)
+        .bind(content)
+        .bind(content.len() as i32)
+        .bind(truncated)
+        .bind(search_text)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, tenant_id: i32, bookmark_id: Uuid) -> anyhow::Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM bookmark_archives WHERE bookmark_id = $1 AND tenant_id = $2")
+                .bind(bookmark_id.hyphenated())
+                .bind(tenant_id)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
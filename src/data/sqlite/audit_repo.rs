@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use crate::data::audit_repo::{AuditEventRow, AuditStore, NewAuditEvent};
+
+#[derive(Clone)]
+pub struct SqliteAuditRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteAuditRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl AuditStore for SqliteAuditRepo {
+    /// One INSERT per event inside a single transaction; SQLite has no UNNEST.
+    async fn insert_batch(&self, events: &[NewAuditEvent]) -> anyhow::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut inserted = 0;
+        for e in events {
+            inserted += sqlx::query(
+                r#"
+                INSERT INTO bookmark_audit_events
+                    (tenant_id, user_id, operation, create_time, client, status_code, duration_ms)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(e.tenant_id)
+            .bind(&e.user_id)
+            .bind(&e.operation)
+            .bind(e.create_time)
+            .bind(&e.client)
+            .bind(e.status_code)
+            .bind(e.duration_ms)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+        tx.commit().await?;
+
+        Ok(inserted)
+    }
+
+    async fn list_page(
+        &self,
+        tenant_id: Option<i32>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        after_id: i64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<AuditEventRow>> {
+        let rows = sqlx::query_as::<_, AuditEventRow>(
+            r#"
+            SELECT * FROM bookmark_audit_events
+            WHERE id > $1
+              AND ($2 IS NULL OR tenant_id = $2)
+              AND ($3 IS NULL OR create_time >= $3)
+              AND ($4 IS NULL OR create_time < $4)
+            ORDER BY id
+            LIMIT $5
+            "#,
+        )
+        .bind(after_id)
+        .bind(tenant_id)
+        .bind(since)
+        .bind(until)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn delete_older_than(
+        &self,
+        cutoff: DateTime<Utc>,
+        max_id: Option<i64>,
+    ) -> anyhow::Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM bookmark_audit_events
+            WHERE create_time < $1 AND ($2 IS NULL OR id <= $2)
+            "#,
+        )
+        .bind(cutoff)
+        .bind(max_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,121 @@
+use chrono::Utc;
+use sqlx::sqlite::SqliteRow;
+use sqlx::types::Json;
+use sqlx::{Row, SqlitePool};
+
+use crate::data::auto_tag_rule_repo::{AutoTagRuleFields, AutoTagRuleRow, AutoTagRuleStore};
+
+fn rule_row(row: &SqliteRow) -> sqlx::Result<AutoTagRuleRow> {
+    Ok(AutoTagRuleRow {
+        id: row.try_get("id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        name: row.try_get("name")?,
+        domain: row.try_get("domain")?,
+        path_pattern: row.try_get("path_pattern")?,
+        title_pattern: row.try_get("title_pattern")?,
+        tags: row.try_get::<Json<Vec<String>>, _>("tags")?.0,
+        enabled: row.try_get("enabled")?,
+        created_by: row.try_get("created_by")?,
+        create_time: row.try_get("create_time")?,
+        update_time: row.try_get("update_time")?,
+    })
+}
+
+#[derive(Clone)]
+pub struct SqliteAutoTagRuleRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteAutoTagRuleRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl AutoTagRuleStore for SqliteAutoTagRuleRepo {
+    async fn create(
+        &self,
+        tenant_id: i32,
+        name: &str,
+        fields: &AutoTagRuleFields,
+        created_by: &str,
+    ) -> anyhow::Result<Option<AutoTagRuleRow>> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO bookmark_auto_tag_rules
+                (tenant_id, name, domain, path_pattern, title_pattern, tags, enabled, created_by,
+                 create_time, update_time)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+            ON CONFLICT (tenant_id, name) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .bind(&fields.domain)
+        .bind(&fields.path_pattern)
+        .bind(&fields.title_pattern)
+        .bind(Json(&fields.tags))
+        .bind(fields.enabled)
+        .bind(created_by)
+        .bind(Utc::now())
+        .try_map(|r| rule_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list(&self, tenant_id: i32) -> anyhow::Result<Vec<AutoTagRuleRow>> {
+        let rows =
+            sqlx::query("SELECT * FROM bookmark_auto_tag_rules WHERE tenant_id = $1 ORDER BY name")
+                .bind(tenant_id)
+                .try_map(|r| rule_row(&r))
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows)
+    }
+
+    async fn update(
+        &self,
+        tenant_id: i32,
+        id: i32,
+        fields: &AutoTagRuleFields,
+    ) -> anyhow::Result<Option<AutoTagRuleRow>> {
+        let row = sqlx::query(
+            r#"
+            UPDATE bookmark_auto_tag_rules
+            SET domain = $3, path_pattern = $4, title_pattern = $5, tags = $6, enabled = $7,
+                update_time = $8
+            WHERE tenant_id = $1 AND id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .bind(&fields.domain)
+        .bind(&fields.path_pattern)
+        .bind(&fields.title_pattern)
+        .bind(Json(&fields.tags))
+        .bind(fields.enabled)
+        .bind(Utc::now())
+        .try_map(|r| rule_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn delete(&self, tenant_id: i32, id: i32) -> anyhow::Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM bookmark_auto_tag_rules WHERE tenant_id = $1 AND id = $2")
+                .bind(tenant_id)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
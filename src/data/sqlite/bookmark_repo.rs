@@ -0,0 +1,1609 @@
+
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::types::Json;
+use sqlx::{Connection, Row, SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use super::{json_list, uuid_col, NORMALIZED_HOST};
+use crate::authz::relations::{Permission, Relation, ResourceType, SubjectType};
+use crate::data::bookmark_repo::{
+    split_tags, BookmarkChanges, BookmarkFilter, BookmarkKind, BookmarkRow, BookmarkStore,
+    BulkTagFilter, NewBookmark, RevisionRow, SearchRow, TagOrder, TagPathRow, TagStatRow,
+};
+use crate::data::filter_expr::{and_clause, FilterExpr, FilterParam, Predicate};
+use crate::service::bookmark_kind::classify_url;
+use crate::service::url_normalizer::normalize_url;
+
+/// Words of archived text kept around the first match in a search snippet.
+const SNIPPET_WORDS: usize = 30;
+
+fn bookmark_row(row: &SqliteRow) -> sqlx::Result<BookmarkRow> {
+    Ok(BookmarkRow {
+        id: uuid_col(row, "id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        url: row.try_get("url")?,
+        title: row.try_get("title")?,
+        description: row.try_get("description")?,
+        tags: row.try_get::<Json<Vec<String>>, _>("tags")?.0,
+        created_by: row.try_get("created_by")?,
+        create_time: row.try_get("create_time")?,
+        update_time: row.try_get("update_time")?,
+        original_url: row.try_get("original_url")?,
+        normalized_url: row.try_get("normalized_url")?,
+        visit_count: row.try_get("visit_count")?,
+        last_visited_at: row.try_get("last_visited_at")?,
+        notes: row.try_get("notes")?,
+        metadata: row.try_get("metadata")?,
+        kind: row.try_get("kind")?,
+        is_private: row.try_get("is_private")?,
+    })
+}
+
+fn revision_row(row: &SqliteRow) -> sqlx::Result<RevisionRow> {
+    Ok(RevisionRow {
+        id: row.try_get("id")?,
+        bookmark_id: uuid_col(row, "bookmark_id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        url: row.try_get("url")?,
+        title: row.try_get("title")?,
+        description: row.try_get("description")?,
+        tags: row.try_get::<Json<Vec<String>>, _>("tags")?.0,
+        original_url: row.try_get("original_url")?,
+        edited_by: row.try_get("edited_by")?,
+        create_time: row.try_get("create_time")?,
+        is_private: row.try_get("is_private")?,
+    })
+}
+
+/// Escape `LIKE` wildcards; queries using this pass `ESCAPE '\'`.
+fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+/// Up to [`SNIPPET_WORDS`] words around the first word containing a term,
+/// with matching words wrapped in `<b>` like Postgres' `ts_headline`.
+fn snippet(text: &str, terms: &[String]) -> Option<String> {
+    let terms: Vec<String> = terms.iter().map(|t| t.to_lowercase()).collect();
+    let matches = |w: &str| {
+        let w = w.to_lowercase();
+        terms.iter().any(|t| w.contains(t.as_str()))
+    };
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let first = words.iter().position(|w| matches(w))?;
+    let start = first.saturating_sub(SNIPPET_WORDS / 3);
+    let end = (start + SNIPPET_WORDS).min(words.len());
+    Some(
+        words[start..end]
+            .iter()
+            .map(|w| {
+                if matches(w) {
+                    format!("<b>{w}</b>")
+                } else {
+                    w.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Restricts `list_by_ids` to the IDs in `$2`.
+const ID_FILTER: &str = "tenant_id = $1 AND id IN (SELECT value FROM json_each($2))";
+
+/// Restricts `list_accessible` to bookmarks on which a subject in `$2`, a JSON
+/// array of `[subject_type, subject_id]` pairs, holds a tuple.
+const ACCESS_FILTER: &str = r#"
+    tenant_id = $1 AND EXISTS (
+        SELECT 1 FROM bookmark_permissions p, json_each($2) s
+        WHERE p.tenant_id = bookmark_bookmarks.tenant_id
+          AND p.resource_type = 'RESOURCE_TYPE_BOOKMARK'
+          AND p.resource_id IN (bookmark_bookmarks.id, '*')
+          AND p.subject_type = json_extract(s.value, '$[0]')
+          AND p.subject_id = json_extract(s.value, '$[1]')
+          AND p.subject_tenant_id IS NULL)
+"#;
+
+/// Bookmarks the bookmark's own tenant shared with the user `$6` of tenant
+/// `$1` through an unexpired tuple; OR-ed with `ACCESS_FILTER` when a listing
+/// includes federated grants.
+const FEDERATED_ACCESS_FILTER: &str = r#"
+    EXISTS (
+        SELECT 1 FROM bookmark_permissions p
+        WHERE p.tenant_id = bookmark_bookmarks.tenant_id
+          AND p.subject_tenant_id = $1
+          AND p.subject_type = 'SUBJECT_TYPE_USER'
+          AND p.subject_id = $6
+          AND p.resource_type = 'RESOURCE_TYPE_BOOKMARK'
+          AND p.resource_id = bookmark_bookmarks.id
+          AND (p.expires_at IS NULL
+               OR p.expires_at > strftime('%Y-%m-%dT%H:%M:%f+00:00', 'now')))
+"#;
+
+/// Filters shared by `list_by_ids` and `list_accessible`, following one of the
+/// clauses above; see the Postgres implementation.
+const LIST_FILTER: &str = r#"
+      AND ($3 IS NULL OR create_time >= $3)
+      AND ($4 IS NULL OR create_time < $4)
+      AND ($5 IS NULL OR created_by = $5)
+      AND (NOT $7 OR EXISTS (
+          SELECT 1 FROM bookmark_user_flags f
+          WHERE f.bookmark_id = bookmark_bookmarks.id AND f.user_id = $6 AND f.is_pinned))
+      AND ($8 IS NULL OR COALESCE((
+          SELECT f.reading_status FROM bookmark_user_flags f
+          WHERE f.bookmark_id = bookmark_bookmarks.id AND f.user_id = $6
+      ), 'READING_STATUS_UNREAD') = $8)
+      AND ($9 IS NULL OR NOT EXISTS (
+          SELECT 1 FROM json_each($9) want
+          WHERE NOT EXISTS (
+              SELECT 1 FROM json_each(bookmark_bookmarks.metadata) have
+              WHERE have.key = want.key AND have.value = want.value)))
+      AND ($10 IS NULL OR kind = $10)
+      AND ($11 IS NULL OR EXISTS (
+          SELECT 1 FROM json_each(bookmark_bookmarks.tags) t
+          WHERE t.value = $11
+             OR (substr($11, -1) = '/' AND substr(t.value || '/', 1, length($11)) = $11)))
+"#;
+
+/// SQLite SQL for one filter-expression predicate over the bookmark row
+/// `table`, with its value bound to `param`.
+fn filter_predicate(table: &str, predicate: &Predicate, param: &str) -> String {
+    match predicate {
+        Predicate::Tag(_) => format!(
+            "EXISTS (SELECT 1 FROM json_each({table}.tags) t \
+             WHERE t.value = {param} \
+                OR (substr({param}, -1) = '/' AND substr(t.value || '/', 1, length({param})) = {param}))"
+        ),
+        Predicate::Domain(_) => {
+            let host = NORMALIZED_HOST.replace("normalized_url", &format!("{table}.normalized_url"));
+            format!(
+                "COALESCE({host} = {param} OR substr({host}, -length({param}) - 1) = '.' || {param}, FALSE)"
+            )
+        }
+        Predicate::Kind(_) => format!("COALESCE({table}.kind = {param}, FALSE)"),
+        Predicate::Title(_) => format!("instr(lower({table}.title), lower({param})) > 0"),
+        Predicate::Time(field, cmp, _) => {
+            format!("{table}.{} {} {param}", field.column(), cmp.as_sql())
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SqliteBookmarkRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteBookmarkRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl BookmarkStore for SqliteBookmarkRepo {
+    #[allow(clippy::too_many_arguments)]
+    async fn create(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+        url: &str,
+        title: &str,
+        description: &str,
+        tags: &[String],
+        created_by: Option<i32>,
+        original_url: Option<&str>,
+        kind: BookmarkKind,
+        is_private: bool,
+    ) -> anyhow::Result<BookmarkRow> {
+        let row = insert_in(
+            &mut *self.pool.acquire().await?,
+            tenant_id,
+            id,
+            url,
+            title,
+            description,
+            tags,
+            created_by,
+            original_url,
+            kind,
+            is_private,
+        )
+        .await?;
+        Ok(row)
+    }
+
+    async fn get_by_id(&self, tenant_id: i32, id: Uuid) -> anyhow::Result<Option<BookmarkRow>> {
+        let row = sqlx::query("SELECT * FROM bookmark_bookmarks WHERE id = $1 AND tenant_id = $2")
+            .bind(id.hyphenated())
+            .bind(tenant_id)
+            .try_map(|r| bookmark_row(&r))
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row)
+    }
+
+    async fn list_by_tenant(
+        &self,
+        tenant_id: i32,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<BookmarkRow>, i64)> {
+        let offset = (page.saturating_sub(1)) * page_size;
+
+        let (total,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM bookmark_bookmarks WHERE tenant_id = $1")
+                .bind(tenant_id)
+                .fetch_one(&self.pool)
+                .await?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM bookmark_bookmarks
+            WHERE tenant_id = $1
+            ORDER BY create_time DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(page_size as i64)
+        .bind(offset as i64)
+        .try_map(|r| bookmark_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((rows, total))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn list_by_ids(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+        filter: &BookmarkFilter,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<BookmarkRow>, i64)> {
+        if ids.is_empty() {
+            return Ok((vec![], 0));
+        }
+
+        let offset = if after.is_some() {
+            0
+        } else {
+            (page.saturating_sub(1)) * page_size
+        };
+        let (after_time, after_id) = after.unzip();
+        let ids = json_list(ids);
+        let metadata = filter.metadata.as_ref().map(Json);
+
+        let (total,): (i64,) = sqlx::query_as(&format!(
+            "SELECT COUNT(*) FROM bookmark_bookmarks WHERE {ID_FILTER} {LIST_FILTER}"
+        ))
+        .bind(tenant_id)
+        .bind(&ids)
+        .bind(filter.created_after)
+        .bind(filter.created_before)
+        .bind(filter.created_by)
+        .bind(&filter.user_id)
+        .bind(filter.pinned_only)
+        .bind(filter.reading_status.map(|s| s.as_str()))
+        .bind(metadata)
+        .bind(filter.kind.map(|k| k.as_str()))
+        .bind(&filter.tag)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT * FROM bookmark_bookmarks
+            WHERE {ID_FILTER} {LIST_FILTER}
+              AND ($12 IS NULL OR (create_time, id) < ($12, $13))
+            ORDER BY create_time DESC, id DESC
+            LIMIT $14 OFFSET $15
+            "#
+        ))
+        .bind(tenant_id)
+        .bind(&ids)
+        .bind(filter.created_after)
+        .bind(filter.created_before)
+        .bind(filter.created_by)
+        .bind(&filter.user_id)
+        .bind(filter.pinned_only)
+        .bind(filter.reading_status.map(|s| s.as_str()))
+        .bind(metadata)
+        .bind(filter.kind.map(|k| k.as_str()))
+        .bind(&filter.tag)
+        .bind(after_time)
+        .bind(after_id.map(|id| id.hyphenated()))
+        .bind(page_size as i64 + 1)
+        .bind(offset as i64)
+        .try_map(|r| bookmark_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((rows, total))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn list_accessible(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        filter: &BookmarkFilter,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<BookmarkRow>, i64)> {
+        if subjects.is_empty() {
+            return Ok((vec![], 0));
+        }
+
+        let after = after.filter(|_| !filter.manual_order);
+        let offset = if after.is_some() {
+            0
+        } else {
+            (page.saturating_sub(1)) * page_size
+        };
+        let (after_time, after_id) = after.unzip();
+        let subjects: Vec<(&str, &str)> =
+            subjects.iter().map(|(t, id)| (t.as_str(), id.as_str())).collect();
+        let subjects = json_list(&subjects);
+        let metadata = filter.metadata.as_ref().map(Json);
+        let access = if filter.federated {
+            format!("(({ACCESS_FILTER}) OR {FEDERATED_ACCESS_FILTER})")
+        } else {
+            ACCESS_FILTER.to_string()
+        };
+        // The expression's values follow the fixed parameters of each query.
+        let expr = |first_param| {
+            and_clause(filter.expr.as_ref(), first_param, &|p, n| {
+                filter_predicate("bookmark_bookmarks", p, n)
+            })
+        };
+
+        let (count_expr, expr_params) = expr(12);
+        let count_sql = format!(
+            "SELECT COUNT(*) FROM bookmark_bookmarks WHERE {access} {LIST_FILTER} {count_expr}"
+        );
+        let mut count = sqlx::query_as(&count_sql)
+        .bind(tenant_id)
+        .bind(&subjects)
+        .bind(filter.created_after)
+        .bind(filter.created_before)
+        .bind(filter.created_by)
+        .bind(&filter.user_id)
+        .bind(filter.pinned_only)
+        .bind(filter.reading_status.map(|s| s.as_str()))
+        .bind(metadata)
+        .bind(filter.kind.map(|k| k.as_str()))
+        .bind(&filter.tag);
+        for param in &expr_params {
+            count = match param {
+                FilterParam::Text(v) => count.bind(v),
+                FilterParam::Time(v) => count.bind(v),
+            };
+        }
+        let (total,): (i64,) = count.fetch_one(&self.pool).await?;
+
+        let order = if filter.manual_order {
+            r#"(SELECT f.sort_order FROM bookmark_user_flags f
+                WHERE f.bookmark_id = bookmark_bookmarks.id AND f.user_id = $6) NULLS LAST,
+               create_time DESC, id DESC"#
+        } else {
+            "create_time DESC, id DESC"
+        };
+        let (rows_expr, _) = expr(16);
+        let sql = format!(
+            r#"
+            SELECT * FROM bookmark_bookmarks
+            WHERE {access} {LIST_FILTER} {rows_expr}
+              AND ($12 IS NULL OR (create_time, id) < ($12, $13))
+            ORDER BY {order}
+            LIMIT $14 OFFSET $15
+            "#
+        );
+        let mut q = sqlx::query(&sql)
+        .bind(tenant_id)
+        .bind(&subjects)
+        .bind(filter.created_after)
+        .bind(filter.created_before)
+        .bind(filter.created_by)
+        .bind(&filter.user_id)
+        .bind(filter.pinned_only)
+        .bind(filter.reading_status.map(|s| s.as_str()))
+        .bind(metadata)
+        .bind(filter.kind.map(|k| k.as_str()))
+        .bind(&filter.tag)
+        .bind(after_time)
+        .bind(after_id.map(|id| id.hyphenated()))
+        .bind(page_size as i64 + 1)
+        .bind(offset as i64);
+        for param in &expr_params {
+            q = match param {
+                FilterParam::Text(v) => q.bind(v),
+                FilterParam::Time(v) => q.bind(v),
+            };
+        }
+        let rows = q
+            .try_map(|r| bookmark_row(&r))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok((rows, total))
+    }
+
+    async fn update(
+        &self,
+        tenant_id: i32,
+        changes: &BookmarkChanges,
+        edited_by: Option<i32>,
+    ) -> anyhow::Result<Option<BookmarkRow>> {
+        let mut tx = self.pool.begin().await?;
+        let row = update_in(&mut tx, tenant_id, changes, edited_by).await?;
+        tx.commit().await?;
+        Ok(row)
+    }
+
+    async fn batch_create(
+        &self,
+        tenant_id: i32,
+        owner_id: &str,
+        created_by: Option<i32>,
+        items: &[NewBookmark],
+    ) -> anyhow::Result<Vec<anyhow::Result<BookmarkRow>>> {
+        let mut tx = self.pool.begin().await?;
+        let mut results = Vec::with_capacity(items.len());
+
+        for item in items {
+            let mut sp = Connection::begin(&mut *tx).await?;
+            let inserted = insert_in(
+                &mut sp,
+                tenant_id,
+                item.id,
+                &item.url,
+                &item.title,
+                &item.description,
+                &item.tags,
+                created_by,
+                item.original_url.as_deref(),
+                item.kind,
+                item.is_private,
+            )
+            .await;
+
+            match inserted {
+                Ok(row) => {
+                    sp.commit().await?;
+                    results.push(Ok(row));
+                }
+                Err(e) => {
+                    sp.rollback().await?;
+                    results.push(Err(e.into()));
+                }
+            }
+        }
+
+        let created: Vec<String> = results
+            .iter()
+            .filter_map(|r| r.as_ref().ok().map(|row| row.id.to_string()))
+            .collect();
+        if !created.is_empty() {
+            // `WHERE TRUE` keeps SQLite from parsing ON CONFLICT as a join constraint.
+            sqlx::query(
+                r#"
+                INSERT INTO bookmark_permissions
+                    (tenant_id, resource_type, resource_id, relation, subject_type, subject_id, granted_by)
+                SELECT $1, $2, value, $3, $4, $5, $6
+                FROM json_each($7) WHERE TRUE
+                ON CONFLICT (tenant_id, resource_type, resource_id, relation, subject_type, subject_id,
+                    COALESCE(subject_tenant_id, 0)) DO NOTHING
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(ResourceType::Bookmark.as_str())
+            .bind(Relation::Owner.as_str())
+            .bind(SubjectType::User.as_str())
+            .bind(owner_id)
+            .bind(created_by)
+            .bind(json_list(&created))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    async fn batch_update(
+        &self,
+        tenant_id: i32,
+        items: &[BookmarkChanges],
+        edited_by: Option<i32>,
+    ) -> anyhow::Result<Vec<anyhow::Result<Option<BookmarkRow>>>> {
+        let mut tx = self.pool.begin().await?;
+        let mut results = Vec::with_capacity(items.len());
+
+        for item in items {
+            let mut sp = Connection::begin(&mut *tx).await?;
+            let updated = update_in(&mut sp, tenant_id, item, edited_by).await;
+
+            match updated {
+                Ok(row) => {
+                    sp.commit().await?;
+                    results.push(Ok(row));
+                }
+                Err(e) => {
+                    sp.rollback().await?;
+                    results.push(Err(e));
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    async fn batch_delete(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+    ) -> anyhow::Result<Vec<anyhow::Result<bool>>> {
+        let mut tx = self.pool.begin().await?;
+        let mut results = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            let mut sp = Connection::begin(&mut *tx).await?;
+            let deleted = delete_in(&mut sp, tenant_id, *id).await;
+
+            match deleted {
+                Ok(found) => {
+                    sp.commit().await?;
+                    results.push(Ok(found));
+                }
+                Err(e) => {
+                    sp.rollback().await?;
+                    results.push(Err(e));
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    /// The query is matched as in `search`: every term must appear in the
+    /// bookmark's fields.
+    async fn update_tags_bulk(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        filter: &BulkTagFilter,
+        add: &[String],
+        remove: &[String],
+        edited_by: Option<i32>,
+    ) -> anyhow::Result<Vec<BookmarkRow>> {
+        if subjects.is_empty() {
+            return Ok(vec![]);
+        }
+        let subjects: Vec<(&str, &str)> =
+            subjects.iter().map(|(t, id)| (t.as_str(), id.as_str())).collect();
+        let relations: Vec<&str> =
+            Relation::granting(Permission::Write).map(|r| r.as_str()).collect();
+        let terms: Vec<&str> = filter
+            .query
+            .as_deref()
+            .map(|q| q.split_whitespace().collect())
+            .unwrap_or_default();
+
+        const FIRST_TERM: usize = 9;
+        let term_match: String = (0..terms.len())
+            .map(|i| {
+                format!(
+                    " AND (title || ' ' || description || ' ' || tags) LIKE ${} ESCAPE '\\'",
+                    FIRST_TERM + i
+                )
+            })
+            .collect();
+        let sql = format!(
+            r#"
+            SELECT id FROM bookmark_bookmarks
+            WHERE tenant_id = $1 AND EXISTS (
+                SELECT 1 FROM bookmark_permissions p, json_each($2) s
+                WHERE p.tenant_id = bookmark_bookmarks.tenant_id
+                  AND p.resource_type = 'RESOURCE_TYPE_BOOKMARK'
+                  AND p.resource_id IN (bookmark_bookmarks.id, '*')
+                  AND p.subject_type = json_extract(s.value, '$[0]')
+                  AND p.subject_id = json_extract(s.value, '$[1]')
+                  AND p.relation IN (SELECT value FROM json_each($3))
+                  AND (p.expires_at IS NULL OR p.expires_at > $4)
+                  AND p.subject_tenant_id IS NULL)
+              AND ($5 IS NULL OR EXISTS (
+                  SELECT 1 FROM json_each(bookmark_bookmarks.tags)
+                  WHERE value = $5
+                     OR (substr($5, -1) = '/' AND substr(value || '/', 1, length($5)) = $5)))
+              AND ($6 IS NULL OR {NORMALIZED_HOST} = $6
+                  OR substr({NORMALIZED_HOST}, -length($6) - 1) = '.' || $6)
+              AND (EXISTS (
+                      SELECT 1 FROM json_each(bookmark_bookmarks.tags)
+                      WHERE value IN (SELECT value FROM json_each($7)))
+                  OR EXISTS (
+                      SELECT 1 FROM json_each($8) a
+                      WHERE a.value NOT IN (SELECT value FROM json_each(bookmark_bookmarks.tags))))
+              {term_match}
+            "#
+        );
+        let now = Utc::now();
+        let (remove, add) = (Json(remove), Json(add));
+
+        let mut tx = self.pool.begin().await?;
+        let mut q = sqlx::query(&sql)
+            .bind(tenant_id)
+            .bind(json_list(&subjects))
+            .bind(json_list(&relations))
+            .bind(now)
+            .bind(&filter.tag)
+            .bind(&filter.domain)
+            .bind(remove)
+            .bind(add);
+        for term in &terms {
+            q = q.bind(like_pattern(term));
+        }
+        let ids: Vec<String> = q
+            .try_map(|r: SqliteRow| r.try_get("id"))
+            .fetch_all(&mut *tx)
+            .await?;
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let ids = json_list(&ids);
+
+        sqlx::query(
+            r#"
+            INSERT INTO bookmark_revisions
+                (bookmark_id, tenant_id, url, title, description, tags, original_url, edited_by,
+                 create_time, is_private)
+            SELECT id, tenant_id, url, title, description, tags, original_url, $2, $3, is_private
+            FROM bookmark_bookmarks
+            WHERE id IN (SELECT value FROM json_each($1))
+            "#,
+        )
+        .bind(&ids)
+        .bind(edited_by)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        // Kept tags stay in order, then missing added tags follow in request order.
+        let rows = sqlx::query(
+            r#"
+            UPDATE bookmark_bookmarks
+            SET tags = (
+                    SELECT json_group_array(value) FROM (
+                        SELECT 0 AS part, key, value FROM json_each(bookmark_bookmarks.tags)
+                        WHERE value NOT IN (SELECT value FROM json_each($2))
+                        UNION ALL
+                        SELECT 1, key, value FROM json_each($3)
+                        WHERE value NOT IN (SELECT value FROM json_each(bookmark_bookmarks.tags))
+                        ORDER BY part, key)),
+                update_time = $4
+            WHERE id IN (SELECT value FROM json_each($1))
+            RETURNING *
+            "#,
+        )
+        .bind(&ids)
+        .bind(remove)
+        .bind(add)
+        .bind(now)
+        .try_map(|r| bookmark_row(&r))
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    /// Substring search: every whitespace-separated term must appear in the
+    /// bookmark's fields or, with `search_content`, in its archived text.
+    /// Field matches rank above content-only matches.
+    /// There is no trigram matching, so `fuzzy_threshold` is ignored.
+    async fn search(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+        query: &str,
+        search_content: bool,
+        _fuzzy_threshold: Option<f32>,
+        expr: Option<&FilterExpr>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<SearchRow>, i64)> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_string).collect();
+        if ids.is_empty() || terms.is_empty() {
+            return Ok((vec![], 0));
+        }
+
+        let offset = (page.saturating_sub(1)) * page_size;
+
+        const FIRST_TERM: usize = 6;
+        let all_terms = |column: &str| {
+            (0..terms.len())
+                .map(|i| format!("{column} LIKE ${} ESCAPE '\\'", FIRST_TERM + i))
+                .collect::<Vec<_>>()
+                .join(" AND ")
+        };
+        let field_match = all_terms("(b.title || ' ' || b.description || ' ' || b.tags)");
+        let content_match = format!("COALESCE({}, FALSE)", all_terms("a.search_text"));
+        let (expr_sql, expr_params) = and_clause(expr, FIRST_TERM + terms.len(), &|p, n| {
+            filter_predicate("b", p, n)
+        });
+
+        let sql = format!(
+            r#"
+            WITH matches AS (
+                SELECT b.*,
+                       {content_match} AS matched_content,
+                       CASE WHEN {field_match} THEN 2 ELSE 0 END + {content_match} AS rank,
+                       a.format AS archive_format,
+                       a.search_text AS archive_text
+                FROM bookmark_bookmarks b
+                LEFT JOIN bookmark_archives a ON $3 AND a.bookmark_id = b.id
+                WHERE b.tenant_id = $1
+                  AND b.id IN (SELECT value FROM json_each($2))
+                  AND (({field_match}) OR {content_match})
+                  {expr_sql}
+            )
+            SELECT *, COUNT(*) OVER () AS total_count
+            FROM matches
+            ORDER BY rank DESC, create_time DESC
+            LIMIT $4 OFFSET $5
+            "#
+        );
+
+        let mut q = sqlx::query(&sql)
+            .bind(tenant_id)
+            .bind(json_list(ids))
+            .bind(search_content)
+            .bind(page_size as i64)
+            .bind(offset as i64);
+        for term in &terms {
+            q = q.bind(like_pattern(term));
+        }
+        for param in expr_params {
+            q = match param {
+                FilterParam::Text(v) => q.bind(v),
+                FilterParam::Time(v) => q.bind(v),
+            };
+        }
+        let rows = q
+            .try_map(|r: SqliteRow| {
+                let matched_content: bool = r.try_get("matched_content")?;
+                let format: Option<String> = r.try_get("archive_format")?;
+                let text: Option<String> = r.try_get("archive_text")?;
+                let snippet = match (matched_content, format.as_deref(), text) {
+                    (true, Some("text"), Some(text)) => snippet(&text, &terms),
+                    _ => None,
+                };
+                Ok(SearchRow {
+                    bookmark: bookmark_row(&r)?,
+                    matched_content,
+                    fuzzy_match: false,
+                    snippet,
+                    total_count: r.try_get("total_count")?,
+                })
+            })
+            .fetch_all(&self.pool)
+            .await?;
+
+        let total = rows.first().map(|r| r.total_count).unwrap_or(0);
+        Ok((rows, total))
+    }
+
+    async fn update_notes(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+        notes: &str,
+    ) -> anyhow::Result<Option<BookmarkRow>> {
+        let row = sqlx::query(
+            r#"
+            UPDATE bookmark_bookmarks
+            SET notes = $3, update_time = $4
+            WHERE id = $1 AND tenant_id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(id.hyphenated())
+        .bind(tenant_id)
+        .bind(notes)
+        .bind(Utc::now())
+        .try_map(|r| bookmark_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn record_visit(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+    ) -> anyhow::Result<Option<(i32, DateTime<Utc>)>> {
+        let row: Option<(i32, DateTime<Utc>)> = sqlx::query_as(
+            r#"
+            UPDATE bookmark_bookmarks
+            SET visit_count = visit_count + 1, last_visited_at = $3
+            WHERE id = $1 AND tenant_id = $2
+            RETURNING visit_count, last_visited_at
+            "#,
+        )
+        .bind(id.hyphenated())
+        .bind(tenant_id)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list_most_visited(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+        limit: u32,
+    ) -> anyhow::Result<Vec<BookmarkRow>> {
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM bookmark_bookmarks
+            WHERE tenant_id = $1 AND id IN (SELECT value FROM json_each($2)) AND visit_count > 0
+            ORDER BY visit_count DESC, last_visited_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(json_list(ids))
+        .bind(limit as i64)
+        .try_map(|r| bookmark_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn tag_stats(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        prefix: &str,
+        order: TagOrder,
+        limit: u32,
+    ) -> anyhow::Result<Vec<TagStatRow>> {
+        if subjects.is_empty() {
+            return Ok(vec![]);
+        }
+        let subjects: Vec<(&str, &str)> =
+            subjects.iter().map(|(t, id)| (t.as_str(), id.as_str())).collect();
+
+        let rows = sqlx::query_as::<_, TagStatRow>(&format!(
+            r#"
+            SELECT t.value AS tag,
+                   COUNT(DISTINCT bookmark_bookmarks.id) AS bookmark_count,
+                   MIN(bookmark_bookmarks.create_time) AS first_used,
+                   MAX(bookmark_bookmarks.update_time) AS last_used,
+                   COUNT(*) OVER () AS total_count
+            FROM bookmark_bookmarks, json_each(bookmark_bookmarks.tags) t
+            WHERE {ACCESS_FILTER}
+              AND substr(lower(t.value), 1, length($3)) = lower($3)
+            GROUP BY t.value
+            ORDER BY {}
+            LIMIT $4
+            "#,
+            order.order_by()
+        ))
+        .bind(tenant_id)
+        .bind(json_list(&subjects))
+        .bind(prefix)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Tags are expanded into their namespaces by peeling one segment off
+    /// `rest` per step, as SQLite has no `split_part`.
+    async fn tag_tree(
+        &self,
+        tenant_id: i32,
+        subjects: &[(SubjectType, String)],
+        root: &str,
+    ) -> anyhow::Result<Vec<TagPathRow>> {
+        if subjects.is_empty() {
+            return Ok(vec![]);
+        }
+        let subjects: Vec<(&str, &str)> =
+            subjects.iter().map(|(t, id)| (t.as_str(), id.as_str())).collect();
+
+        let rows = sqlx::query_as::<_, TagPathRow>(&format!(
+            r#"
+            WITH RECURSIVE tagged AS (
+                SELECT DISTINCT bookmark_bookmarks.id AS id, t.value AS tag
+                FROM bookmark_bookmarks, json_each(bookmark_bookmarks.tags) t
+                WHERE {ACCESS_FILTER}
+                  AND ($3 = '' OR t.value = $3 OR substr(t.value, 1, length($3) + 1) = $3 || '/')
+            ),
+            paths(id, tag, path, rest) AS (
+                SELECT id, tag,
+                       CASE WHEN instr(tag, '/') > 0 THEN substr(tag, 1, instr(tag, '/') - 1) ELSE tag END,
+                       CASE WHEN instr(tag, '/') > 0 THEN substr(tag, instr(tag, '/') + 1) END
+                FROM tagged
+                UNION ALL
+                SELECT id, tag,
+                       path || '/' || CASE WHEN instr(rest, '/') > 0
+                                           THEN substr(rest, 1, instr(rest, '/') - 1) ELSE rest END,
+                       CASE WHEN instr(rest, '/') > 0 THEN substr(rest, instr(rest, '/') + 1) END
+                FROM paths
+                WHERE rest IS NOT NULL
+            )
+            SELECT path,
+                   COUNT(DISTINCT CASE WHEN path = tag THEN id END) AS bookmark_count,
+                   COUNT(DISTINCT id) AS subtree_count
+            FROM paths
+            WHERE $3 = '' OR path = $3 OR substr(path, 1, length($3) + 1) = $3 || '/'
+            GROUP BY path
+            ORDER BY path
+            "#
+        ))
+        .bind(tenant_id)
+        .bind(json_list(&subjects))
+        .bind(root)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn split_tags(
+        &self,
+        tenant_id: Option<i32>,
+        delimiter: &str,
+        dry_run: bool,
+    ) -> anyhow::Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let rows = sqlx::query(
+            r#"
+            SELECT id, tags FROM bookmark_bookmarks
+            WHERE ($1 IS NULL OR tenant_id = $1)
+              AND EXISTS (SELECT 1 FROM json_each(tags) WHERE instr(value, $2) > 0)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(delimiter)
+        .try_map(|r: SqliteRow| {
+            Ok((
+                r.try_get::<String, _>("id")?,
+                r.try_get::<Json<Vec<String>>, _>("tags")?.0,
+            ))
+        })
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let changes: Vec<(String, Vec<String>)> = rows
+            .into_iter()
+            .filter_map(|(id, tags)| split_tags(&tags, delimiter).map(|tags| (id, tags)))
+            .collect();
+        if dry_run {
+            return Ok(changes.len() as u64);
+        }
+
+        let now = Utc::now();
+        for (id, tags) in &changes {
+            sqlx::query(
+                r#"
+                INSERT INTO bookmark_revisions
+                    (bookmark_id, tenant_id, url, title, description, tags, original_url,
+                     create_time, is_private)
+                SELECT id, tenant_id, url, title, description, tags, original_url, $2, is_private
+                FROM bookmark_bookmarks
+                WHERE id = $1
+                "#,
+            )
+            .bind(id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("UPDATE bookmark_bookmarks SET tags = $2, update_time = $3 WHERE id = $1")
+                .bind(id)
+                .bind(Json(tags))
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(changes.len() as u64)
+    }
+
+    async fn find_by_normalized_url(
+        &self,
+        tenant_id: i32,
+        normalized_url: &str,
+    ) -> anyhow::Result<Vec<BookmarkRow>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM bookmark_bookmarks
+            WHERE tenant_id = $1 AND normalized_url = $2
+            ORDER BY create_time
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(normalized_url)
+        .try_map(|r| bookmark_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn find_duplicate_groups(
+        &self,
+        tenant_id: i32,
+        ids: &[Uuid],
+    ) -> anyhow::Result<Vec<BookmarkRow>> {
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let rows = sqlx::query(
+            r#"
+            WITH candidates AS (
+                SELECT * FROM bookmark_bookmarks
+                WHERE tenant_id = $1 AND id IN (SELECT value FROM json_each($2))
+            )
+            SELECT * FROM candidates
+            WHERE normalized_url IN (
+                SELECT normalized_url FROM candidates
+                WHERE normalized_url IS NOT NULL
+                GROUP BY normalized_url
+                HAVING COUNT(*) > 1
+            )
+            ORDER BY normalized_url, create_time
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(json_list(ids))
+        .try_map(|r| bookmark_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn backfill_normalized_urls(&self) -> anyhow::Result<u64> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as(
+                "SELECT id, url FROM bookmark_bookmarks WHERE normalized_url IS NULL AND NOT is_private",
+            )
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut updated = 0;
+        for (id, url) in rows {
+            updated +=
+                sqlx::query("UPDATE bookmark_bookmarks SET normalized_url = $2 WHERE id = $1")
+                    .bind(id)
+                    .bind(normalize_url(&url))
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+        }
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    async fn backfill_kinds(&self) -> anyhow::Result<u64> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT id, url FROM bookmark_bookmarks WHERE kind IS NULL")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut updated = 0;
+        for (id, url) in rows {
+            updated += sqlx::query("UPDATE bookmark_bookmarks SET kind = $2 WHERE id = $1")
+                .bind(id)
+                .bind(classify_url(&url).as_str())
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    async fn list_revisions(
+        &self,
+        tenant_id: i32,
+        bookmark_id: Uuid,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<RevisionRow>, i64)> {
+        let offset = (page.saturating_sub(1)) * page_size;
+
+        let (total,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM bookmark_revisions WHERE bookmark_id = $1 AND tenant_id = $2",
+        )
+        .bind(bookmark_id.hyphenated())
+        .bind(tenant_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM bookmark_revisions
+            WHERE bookmark_id = $1 AND tenant_id = $2
+            ORDER BY create_time DESC, id DESC
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(bookmark_id.hyphenated())
+        .bind(tenant_id)
+        .bind(page_size as i64)
+        .bind(offset as i64)
+        .try_map(|r| revision_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((rows, total))
+    }
+
+    async fn get_revision(
+        &self,
+        tenant_id: i32,
+        bookmark_id: Uuid,
+        revision_id: i64,
+    ) -> anyhow::Result<Option<RevisionRow>> {
+        let row = sqlx::query(
+            "SELECT * FROM bookmark_revisions WHERE bookmark_id = $1 AND tenant_id = $2 AND id = $3",
+        )
+        .bind(bookmark_id.hyphenated())
+        .bind(tenant_id)
+        .bind(revision_id)
+        .try_map(|r| revision_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn delete(&self, tenant_id: i32, id: Uuid) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM bookmark_bookmarks WHERE id = $1 AND tenant_id = $2")
+            .bind(id.hyphenated())
+            .bind(tenant_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// IDs and timestamps are generated here; SQLite has no `gen_random_uuid()`.
+#[allow(clippy::too_many_arguments)]
+async fn insert_in(
+    conn: &mut SqliteConnection,
+    tenant_id: i32,
+    id: Uuid,
+    url: &str,
+    title: &str,
+    description: &str,
+    tags: &[String],
+    created_by: Option<i32>,
+    original_url: Option<&str>,
+    kind: BookmarkKind,
+    is_private: bool,
+) -> sqlx::Result<BookmarkRow> {
+    sqlx::query(
+        r#"
+        INSERT INTO bookmark_bookmarks
+            (id, tenant_id, url, title, description, tags, created_by, original_url, normalized_url,
+             create_time, update_time, kind, is_private)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10, $11, $12)
+        RETURNING *
+        "#,
+    )
+    .bind(id.hyphenated())
+    .bind(tenant_id)
+    .bind(url)
+    .bind(title)
+    .bind(description)
+    .bind(Json(tags))
+    .bind(created_by)
+    .bind(original_url)
+    .bind((!is_private).then(|| normalize_url(url)))
+    .bind(Utc::now())
+    .bind(kind.as_str())
+    .bind(is_private)
+    .try_map(|r| bookmark_row(&r))
+    .fetch_one(&mut *conn)
+    .await
+}
+
+/// Apply one update on an open connection, recording the previous state as a
+/// revision first. Returns `None` if the bookmark does not exist in the tenant.
+async fn update_in(
+    conn: &mut SqliteConnection,
+    tenant_id: i32,
+    changes: &BookmarkChanges,
+    edited_by: Option<i32>,
+) -> anyhow::Result<Option<BookmarkRow>> {
+    let now = Utc::now();
+    let recorded = sqlx::query(
+        r#"
+        INSERT INTO bookmark_revisions
+            (bookmark_id, tenant_id, url, title, description, tags, original_url, edited_by,
+             create_time, is_private)
+        SELECT id, tenant_id, url, title, description, tags, original_url, $3, $4, is_private
+        FROM bookmark_bookmarks
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+    )
+    .bind(changes.id.hyphenated())
+    .bind(tenant_id)
+    .bind(edited_by)
+    .bind(now)
+    .execute(&mut *conn)
+    .await?;
+
+    if recorded.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    let sql = format!(
+        "UPDATE bookmark_bookmarks SET {}, update_time = $2 WHERE id = $1 AND tenant_id = $3 RETURNING *",
+        changes.set_clause(4)
+    );
+    let mut query = sqlx::query(&sql)
+        .bind(changes.id.hyphenated())
+        .bind(now)
+        .bind(tenant_id);
+    if let Some(url) = &changes.url {
+        query = query
+            .bind(url)
+            .bind(&changes.original_url)
+            .bind(changes.normalized_url());
+    }
+    if let Some(title) = &changes.title {
+        query = query.bind(title);
+    }
+    if let Some(description) = &changes.description {
+        query = query.bind(description);
+    }
+    if let Some(tags) = &changes.tags {
+        query = query.bind(Json(tags));
+    }
+    if let Some(metadata) = &changes.metadata {
+        query = query.bind(Json(metadata));
+    }
+    if let Some(kind) = changes.kind {
+        query = query.bind(kind.as_str());
+    }
+    if let Some(notes) = &changes.notes {
+        query = query.bind(notes);
+    }
+    if let Some(is_private) = changes.is_private {
+        query = query.bind(is_private);
+    }
+    let row = query
+        .try_map(|r| bookmark_row(&r))
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    Ok(row)
+}
+
+async fn delete_in(conn: &mut SqliteConnection, tenant_id: i32, id: Uuid) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM bookmark_bookmarks WHERE id = $1 AND tenant_id = $2")
+        .bind(id.hyphenated())
+        .bind(tenant_id)
+        .execute(&mut *conn)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        "DELETE FROM bookmark_permissions WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3",
+    )
+    .bind(tenant_id)
+    .bind(ResourceType::Bookmark.as_str())
+    .bind(id.to_string())
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::sqlite::test_pool;
+
+    fn retitle(id: Uuid, title: &str) -> BookmarkChanges {
+        BookmarkChanges {
+            id,
+            url: None,
+            title: Some(title.to_string()),
+            description: None,
+            tags: None,
+            original_url: None,
+            metadata: None,
+            kind: None,
+            notes: None,
+            is_private: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn other_tenants_cannot_reach_a_bookmark_by_id() {
+        let pool = test_pool().await;
+        let repo = SqliteBookmarkRepo::new(pool);
+
+        let b = repo
+            .create(
+                1,
+                Uuid::new_v4(),
+                "https://a.test",
+                "a",
+                "",
+                &[],
+                Some(7),
+                None,
+                BookmarkKind::Other,
+                false,
+            )
+            .await
+            .unwrap();
+        repo.update(1, &retitle(b.id, "b"), Some(7))
+            .await
+            .unwrap()
+            .unwrap();
+        let (revisions, _) = repo.list_revisions(1, b.id, 1, 10).await.unwrap();
+        let revision = revisions[0].id;
+
+        assert!(repo.get_by_id(2, b.id).await.unwrap().is_none());
+        assert!(repo
+            .update(2, &retitle(b.id, "x"), None)
+            .await
+            .unwrap()
+            .is_none());
+        let batch = repo
+            .batch_update(2, &[retitle(b.id, "x")], None)
+            .await
+            .unwrap();
+        assert!(batch[0].as_ref().unwrap().is_none());
+        assert!(repo.update_notes(2, b.id, "x").await.unwrap().is_none());
+        assert!(repo.record_visit(2, b.id).await.unwrap().is_none());
+        assert_eq!(repo.list_revisions(2, b.id, 1, 10).await.unwrap().1, 0);
+        assert!(repo
+            .get_revision(2, b.id, revision)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(!repo.delete(2, b.id).await.unwrap());
+        assert!(!repo.batch_delete(2, &[b.id]).await.unwrap()[0]
+            .as_ref()
+            .unwrap());
+
+        let row = repo.get_by_id(1, b.id).await.unwrap().unwrap();
+        assert_eq!(
+            (row.title.as_str(), row.notes.as_str(), row.visit_count),
+            ("b", "", 0)
+        );
+        assert_eq!(repo.list_revisions(1, b.id, 1, 10).await.unwrap().1, 1);
+        assert!(repo.delete(1, b.id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn tag_paths_match_namespaces_and_nest() {
+        use crate::authz::relations::WILDCARD_RESOURCE;
+        use crate::data::permission_repo::PermissionStore;
+        use crate::data::sqlite::permission_repo::SqlitePermissionRepo;
+
+        let pool = test_pool().await;
+        let permissions = SqlitePermissionRepo::new(pool.clone());
+        permissions
+            .create_permission(
+                1,
+                ResourceType::Bookmark,
+                WILDCARD_RESOURCE,
+                Relation::Viewer,
+                SubjectType::User,
+                "u",
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let repo = SqliteBookmarkRepo::new(pool);
+        for tags in [&["lang:rust", "lang"][..], &["lang:go"], &["language"]] {
+            let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+            repo.create(
+                1,
+                Uuid::new_v4(),
+                "https://a.test",
+                "",
+                "",
+                &tags,
+                None,
+                None,
+                BookmarkKind::Other,
+                false,
+            )
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(repo.split_tags(Some(1), ":", true).await.unwrap(), 2);
+        assert_eq!(repo.split_tags(Some(1), ":", false).await.unwrap(), 2);
+        assert_eq!(repo.split_tags(Some(1), ":", false).await.unwrap(), 0);
+
+        let subjects = [(SubjectType::User, "u".to_string())];
+        let tree: Vec<(String, i64, i64)> = repo
+            .tag_tree(1, &subjects, "")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.path, r.bookmark_count, r.subtree_count))
+            .collect();
+        let expected = [
+            ("lang", 1, 2),
+            ("lang/go", 1, 1),
+            ("lang/rust", 1, 1),
+            ("language", 1, 1),
+        ];
+        assert_eq!(
+            tree,
+            expected.map(|(p, n, t)| (p.to_string(), n, t)).to_vec()
+        );
+        assert_eq!(
+            repo.tag_tree(1, &subjects, "lang/go").await.unwrap().len(),
+            1
+        );
+
+        // Tenant 3 shares a bookmark with user "u" of tenant 2, who is not
+        // tenant 3's local user "u".
+        let shared = Uuid::new_v4();
+        let tags = vec!["secret/plan".to_string()];
+        repo.create(
+            3,
+            shared,
+            "https://b.test",
+            "",
+            "",
+            &tags,
+            None,
+            None,
+            BookmarkKind::Other,
+            false,
+        )
+        .await
+        .unwrap();
+        permissions
+            .grant_federated(
+                3,
+                ResourceType::Bookmark,
+                &shared.to_string(),
+                2,
+                "u",
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(repo.tag_tree(3, &subjects, "").await.unwrap().is_empty());
+        assert!(repo
+            .tag_stats(3, &subjects, "", TagOrder::MostUsed, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        for (tag, matches) in [("lang/", 2), ("lang", 1), ("lang/rust", 1), ("lan/", 0)] {
+            let filter = BookmarkFilter {
+                tag: Some(tag.to_string()),
+                ..Default::default()
+            };
+            let (_, total) = repo
+                .list_accessible(1, &subjects, &filter, None, 1, 10)
+                .await
+                .unwrap();
+            assert_eq!(total, matches, "{tag}");
+        }
+    }
+
+    #[tokio::test]
+    async fn filter_expressions_narrow_lists_and_search() {
+        use crate::authz::relations::WILDCARD_RESOURCE;
+        use crate::data::permission_repo::PermissionStore;
+        use crate::data::sqlite::permission_repo::SqlitePermissionRepo;
+
+        let pool = test_pool().await;
+        SqlitePermissionRepo::new(pool.clone())
+            .create_permission(
+                1,
+                ResourceType::Bookmark,
+                WILDCARD_RESOURCE,
+                Relation::Viewer,
+                SubjectType::User,
+                "u",
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let repo = SqliteBookmarkRepo::new(pool);
+        let bookmarks = [
+            ("https://github.com/rust-lang/rust", "Rust compiler", "lang/rust", BookmarkKind::Repo),
+            ("https://docs.rs/serde", "Serde docs", "lang/rust", BookmarkKind::Article),
+            ("https://www.youtube.com/watch?v=1", "Rust talk", "video", BookmarkKind::Video),
+        ];
+        let mut ids = Vec::new();
+        for (url, title, tag, kind) in bookmarks {
+            let row = repo
+                .create(
+                    1,
+                    Uuid::new_v4(),
+                    url,
+                    title,
+                    "",
+                    &[tag.to_string()],
+                    None,
+                    None,
+                    kind,
+                    false,
+                )
+                .await
+                .unwrap();
+            ids.push(row.id);
+        }
+
+        let subjects = [(SubjectType::User, "u".to_string())];
+        for (expr, matches) in [
+            ("tag:lang/ AND domain:github.com", 1),
+            ("tag:lang/rust -kind:repo", 1),
+            ("title:RUST OR domain:docs.rs", 3),
+            ("domain:youtube.com", 1),
+            ("created>2000-01-01 NOT (kind:video OR kind:repo)", 1),
+            ("created<2000-01-01", 0),
+            ("updated>=2000-01-01", 3),
+        ] {
+            let filter = BookmarkFilter {
+                expr: Some(FilterExpr::parse(expr).unwrap()),
+                ..Default::default()
+            };
+            let (rows, total) = repo
+                .list_accessible(1, &subjects, &filter, None, 1, 10)
+                .await
+                .unwrap();
+            assert_eq!((rows.len() as i64, total), (matches, matches), "{expr}");
+        }
+
+        let videos = FilterExpr::parse("kind:video").unwrap();
+        let (hits, total) = repo
+            .search(1, &ids, "rust", false, None, Some(&videos), 1, 10)
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(hits[0].bookmark.title, "Rust talk");
+    }
+}
@@ -0,0 +1,51 @@
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+
+use super::uuid_col;
+use crate::data::change_repo::{ChangeRow, ChangeStore};
+
+#[derive(Clone)]
+pub struct SqliteChangeRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteChangeRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl ChangeStore for SqliteChangeRepo {
+    // A single writer means `seq` is already commit order; `txid` is always 0.
+    async fn list_changes(
+        &self,
+        tenant_id: i32,
+        after: (i64, i64),
+        limit: i64,
+    ) -> anyhow::Result<Vec<ChangeRow>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT seq, bookmark_id, deleted FROM bookmark_changes
+            WHERE tenant_id = $1 AND seq > $2
+            ORDER BY seq
+            LIMIT $3
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(after.1)
+        .bind(limit)
+        .try_map(|r: SqliteRow| {
+            Ok(ChangeRow {
+                txid: 0,
+                seq: r.try_get("seq")?,
+                bookmark_id: uuid_col(&r, "bookmark_id")?,
+                deleted: r.try_get("deleted")?,
+            })
+        })
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
@@ -0,0 +1,76 @@
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use super::uuid_col;
+use crate::data::field_key_repo::{FieldKeyRow, FieldKeyStore};
+
+fn field_key_row(row: &SqliteRow) -> sqlx::Result<FieldKeyRow> {
+    Ok(FieldKeyRow {
+        id: uuid_col(row, "id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        wrapped_key: row.try_get("wrapped_key")?,
+        kek_id: row.try_get("kek_id")?,
+        create_time: row.try_get("create_time")?,
+    })
+}
+
+#[derive(Clone)]
+pub struct SqliteFieldKeyRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteFieldKeyRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl FieldKeyStore for SqliteFieldKeyRepo {
+    async fn current(&self, tenant_id: i32) -> anyhow::Result<Option<FieldKeyRow>> {
+        let row = sqlx::query(
+            r#"
+            SELECT * FROM bookmark_field_keys
+            WHERE tenant_id = $1
+            ORDER BY create_time DESC, id
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .try_map(|r| field_key_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn get(&self, id: Uuid) -> anyhow::Result<Option<FieldKeyRow>> {
+        let row = sqlx::query("SELECT * FROM bookmark_field_keys WHERE id = $1")
+            .bind(id.hyphenated())
+            .try_map(|r| field_key_row(&r))
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row)
+    }
+
+    async fn insert(&self, key: &FieldKeyRow) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO bookmark_field_keys (id, tenant_id, wrapped_key, kek_id, create_time)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(key.id.hyphenated())
+        .bind(key.tenant_id)
+        .bind(&key.wrapped_key)
+        .bind(&key.kek_id)
+        .bind(key.create_time)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use crate::data::idempotency_repo::{IdempotencyKey, IdempotencyRow, IdempotencyStore};
+
+#[derive(Clone)]
+pub struct SqliteIdempotencyRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteIdempotencyRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl IdempotencyStore for SqliteIdempotencyRepo {
+    async fn claim(
+        &self,
+        key: &IdempotencyKey,
+        request_hash: &[u8],
+        expires_at: DateTime<Utc>,
+    ) -> anyhow::Result<Option<IdempotencyRow>> {
+        sqlx::query(
+            r#"
+            DELETE FROM bookmark_idempotency_keys
+            WHERE tenant_id = $1 AND user_id = $2 AND method = $3 AND idempotency_key = $4
+              AND expires_at <= $5
+            "#,
+        )
+        .bind(key.tenant_id)
+        .bind(&key.user_id)
+        .bind(key.method)
+        .bind(&key.key)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        let claimed = sqlx::query(
+            r#"
+            INSERT INTO bookmark_idempotency_keys
+                (tenant_id, user_id, method, idempotency_key, request_hash, create_time, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(key.tenant_id)
+        .bind(&key.user_id)
+        .bind(key.method)
+        .bind(&key.key)
+        .bind(request_hash)
+        .bind(Utc::now())
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?
+        .rows_affected()
+            > 0;
+        if claimed {
+            return Ok(None);
+        }
+
+        let row = sqlx::query_as::<_, IdempotencyRow>(
+            r#"
+            SELECT request_hash, response FROM bookmark_idempotency_keys
+            WHERE tenant_id = $1 AND user_id = $2 AND method = $3 AND idempotency_key = $4
+            "#,
+        )
+        .bind(key.tenant_id)
+        .bind(&key.user_id)
+        .bind(key.method)
+        .bind(&key.key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn complete(
+        &self,
+        key: &IdempotencyKey,
+        response: &[u8],
+        expires_at: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE bookmark_idempotency_keys SET response = $5, expires_at = $6
+            WHERE tenant_id = $1 AND user_id = $2 AND method = $3 AND idempotency_key = $4
+            "#,
+        )
+        .bind(key.tenant_id)
+        .bind(&key.user_id)
+        .bind(key.method)
+        .bind(&key.key)
+        .bind(response)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn release(&self, key: &IdempotencyKey) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM bookmark_idempotency_keys
+            WHERE tenant_id = $1 AND user_id = $2 AND method = $3 AND idempotency_key = $4
+              AND response IS NULL
+            "#,
+        )
+        .bind(key.tenant_id)
+        .bind(&key.user_id)
+        .bind(key.method)
+        .bind(&key.key)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn purge_expired(&self) -> anyhow::Result<u64> {
+        let result = sqlx::query("DELETE FROM bookmark_idempotency_keys WHERE expires_at <= $1")
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,61 @@
+//! SQLite implementations of the data stores, for local development and small
+//! single-node deployments. Schema lives in `migrations_sqlite/`.
+//!
+//! UUIDs are stored as hyphenated text and arrays/metadata as JSON text, so
+//! rows with those columns are mapped by hand rather than with `FromRow`.
+//! Lists of IDs are bound as one JSON array and expanded with `json_each`.
+
+pub mod access_request_repo;
+pub mod api_key_repo;
+pub mod archive_repo;
+pub mod audit_repo;
+pub mod auto_tag_rule_repo;
+pub mod bookmark_repo;
+pub mod change_repo;
+pub mod field_key_repo;
+pub mod group_repo;
+pub mod idempotency_repo;
+pub mod permission_repo;
+pub mod permission_template_repo;
+pub mod saved_search_repo;
+pub mod scrub_policy_repo;
+pub mod share_notification_repo;
+pub mod short_link_repo;
+pub mod stats_repo;
+pub mod tag_repo;
+pub mod user_data_repo;
+pub mod user_flag_repo;
+
+use serde::Serialize;
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+use uuid::Uuid;
+
+/// Host part of `normalized_url`, which starts with the host for web URLs.
+const NORMALIZED_HOST: &str = "substr(normalized_url, 1, min(instr(normalized_url || '/', '/'), \
+     instr(normalized_url || '?', '?'), instr(normalized_url || ':', ':')) - 1)";
+
+fn uuid_col(row: &SqliteRow, col: &str) -> sqlx::Result<Uuid> {
+    Ok(row.try_get::<uuid::fmt::Hyphenated, _>(col)?.into_uuid())
+}
+
+/// JSON array for binding to `json_each($n)`.
+fn json_list<T: Serialize>(items: &[T]) -> String {
+    serde_json::to_string(items).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// A migrated in-memory database for tests. One connection, since every
+/// connection to `sqlite::memory:` opens a database of its own.
+#[cfg(test)]
+pub(crate) async fn test_pool() -> sqlx::SqlitePool {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::migrate!("./migrations_sqlite")
+        .run(&pool)
+        .await
+        .unwrap();
+    pool
+}
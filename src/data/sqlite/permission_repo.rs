@@ -0,0 +1,837 @@
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row, SqlitePool};
+
+use super::json_list;
+use crate::authz::relations::{Relation, ResourceType, SubjectType};
+use crate::data::permission_repo::{
+    PermissionRow, PermissionStore, SubjectResourceRow, TupleGrant, TupleRevoke,
+};
+
+// SQLite keeps relations as strings; codes only matter at Postgres scale.
+impl<'r> FromRow<'r, SqliteRow> for PermissionRow {
+    fn from_row(row: &'r SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            tenant_id: row.try_get("tenant_id")?,
+            resource_type: row.try_get("resource_type")?,
+            resource_id: row.try_get("resource_id")?,
+            relation: row.try_get("relation")?,
+            subject_type: row.try_get("subject_type")?,
+            subject_id: row.try_get("subject_id")?,
+            subject_tenant_id: row.try_get("subject_tenant_id")?,
+            granted_by: row.try_get("granted_by")?,
+            expires_at: row.try_get("expires_at")?,
+            create_time: row.try_get("create_time")?,
+        })
+    }
+}
+
+impl<'r> FromRow<'r, SqliteRow> for SubjectResourceRow {
+    fn from_row(row: &'r SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            resource_type: row.try_get("resource_type")?,
+            resource_id: row.try_get("resource_id")?,
+            relation: row.try_get("relation")?,
+            expires_at: row.try_get("expires_at")?,
+            title: row.try_get("title")?,
+            url: row.try_get("url")?,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct SqlitePermissionRepo {
+    pool: SqlitePool,
+}
+
+impl SqlitePermissionRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl PermissionStore for SqlitePermissionRepo {
+    async fn has_permission(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        subject_type: SubjectType,
+        subject_id: &str,
+    ) -> anyhow::Result<Option<PermissionRow>> {
+        let row = sqlx::query_as::<_, PermissionRow>(
+            r#"
+            SELECT * FROM bookmark_permissions
+            WHERE tenant_id = $1
+              AND resource_type = $2
+              AND resource_id = $3
+              AND subject_type = $4
+              AND subject_id = $5
+              AND subject_tenant_id IS NULL
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(resource_type.as_str())
+        .bind(resource_id)
+        .bind(subject_type.as_str())
+        .bind(subject_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn create_permission(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        relation: Relation,
+        subject_type: SubjectType,
+        subject_id: &str,
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<PermissionRow> {
+        let row = sqlx::query_as::<_, PermissionRow>(
+            r#"
+            INSERT INTO bookmark_permissions
+                (tenant_id, resource_type, resource_id, relation, subject_type, subject_id, granted_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (tenant_id, resource_type, resource_id, relation, subject_type, subject_id,
+                COALESCE(subject_tenant_id, 0)) DO UPDATE
+                SET granted_by = EXCLUDED.granted_by, expires_at = EXCLUDED.expires_at
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(resource_type.as_str())
+        .bind(resource_id)
+        .bind(relation.as_str())
+        .bind(subject_type.as_str())
+        .bind(subject_id)
+        .bind(granted_by)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn create_permissions(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        grants: &[(Relation, SubjectType, String)],
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<PermissionRow>> {
+        let mut tx = self.pool.begin().await?;
+        let mut rows = Vec::with_capacity(grants.len());
+
+        for (relation, subject_type, subject_id) in grants {
+            let row = sqlx::query_as::<_, PermissionRow>(
+                r#"
+                INSERT INTO bookmark_permissions
+                    (tenant_id, resource_type, resource_id, relation, subject_type, subject_id, granted_by, expires_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (tenant_id, resource_type, resource_id, relation, subject_type, subject_id,
+                    COALESCE(subject_tenant_id, 0)) DO UPDATE
+                    SET granted_by = EXCLUDED.granted_by, expires_at = EXCLUDED.expires_at
+                RETURNING *
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(resource_type.as_str())
+            .bind(resource_id)
+            .bind(relation.as_str())
+            .bind(subject_type.as_str())
+            .bind(subject_id)
+            .bind(granted_by)
+            .bind(expires_at)
+            .fetch_one(&mut *tx)
+            .await?;
+            rows.push(row);
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    /// One statement per tuple inside a single transaction; SQLite has no UNNEST.
+    async fn batch_grant(
+        &self,
+        tenant_id: i32,
+        tuples: &[TupleGrant],
+        granted_by: Option<i32>,
+    ) -> anyhow::Result<Vec<PermissionRow>> {
+        let mut tx = self.pool.begin().await?;
+        let mut rows = Vec::with_capacity(tuples.len());
+
+        for t in tuples {
+            let row = sqlx::query_as::<_, PermissionRow>(
+                r#"
+                INSERT INTO bookmark_permissions
+                    (tenant_id, resource_type, resource_id, relation, subject_type, subject_id, granted_by, expires_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (tenant_id, resource_type, resource_id, relation, subject_type, subject_id,
+                    COALESCE(subject_tenant_id, 0)) DO UPDATE
+                    SET granted_by = EXCLUDED.granted_by, expires_at = EXCLUDED.expires_at
+                RETURNING *
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(t.resource_type.as_str())
+            .bind(&t.resource_id)
+            .bind(t.relation.as_str())
+            .bind(t.subject_type.as_str())
+            .bind(&t.subject_id)
+            .bind(granted_by)
+            .bind(t.expires_at)
+            .fetch_one(&mut *tx)
+            .await?;
+            rows.push(row);
+        }
+
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    async fn batch_revoke(
+        &self,
+        tenant_id: i32,
+        tuples: &[TupleRevoke],
+    ) -> anyhow::Result<Vec<u64>> {
+        let mut tx = self.pool.begin().await?;
+        let mut removed = Vec::with_capacity(tuples.len());
+
+        for t in tuples {
+            let result = sqlx::query(
+                r#"
+                DELETE FROM bookmark_permissions
+                WHERE tenant_id = $1
+                  AND resource_type = $2
+                  AND resource_id = $3
+                  AND ($4 IS NULL OR relation = $4)
+                  AND subject_type = $5
+                  AND subject_id = $6
+                  AND subject_tenant_id IS NULL
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(t.resource_type.as_str())
+            .bind(&t.resource_id)
+            .bind(t.relation.map(|r| r.as_str()))
+            .bind(t.subject_type.as_str())
+            .bind(&t.subject_id)
+            .execute(&mut *tx)
+            .await?;
+            removed.push(result.rows_affected());
+        }
+
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    async fn delete_permission(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        relation: Option<Relation>,
+        subject_type: SubjectType,
+        subject_id: &str,
+    ) -> anyhow::Result<u64> {
+        let result = if let Some(rel) = relation {
+            sqlx::query(
+                r#"
+                DELETE FROM bookmark_permissions
+                WHERE tenant_id = $1
+                  AND resource_type = $2
+                  AND resource_id = $3
+                  AND relation = $4
+                  AND subject_type = $5
+                  AND subject_id = $6
+                  AND subject_tenant_id IS NULL
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(resource_type.as_str())
+            .bind(resource_id)
+            .bind(rel.as_str())
+            .bind(subject_type.as_str())
+            .bind(subject_id)
+            .execute(&self.pool)
+            .await?
+        } else {
+            sqlx::query(
+                r#"
+                DELETE FROM bookmark_permissions
+                WHERE tenant_id = $1
+                  AND resource_type = $2
+                  AND resource_id = $3
+                  AND subject_type = $4
+                  AND subject_id = $5
+                  AND subject_tenant_id IS NULL
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(resource_type.as_str())
+            .bind(resource_id)
+            .bind(subject_type.as_str())
+            .bind(subject_id)
+            .execute(&self.pool)
+            .await?
+        };
+
+        Ok(result.rows_affected())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn transfer_ownership(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        from_user: &str,
+        to_user: &str,
+        keep_as_editor: bool,
+        granted_by: Option<i32>,
+    ) -> anyhow::Result<Option<PermissionRow>> {
+        let mut tx = self.pool.begin().await?;
+
+        let removed = sqlx::query(
+            r#"
+            DELETE FROM bookmark_permissions
+            WHERE tenant_id = $1
+              AND resource_type = $2
+              AND resource_id = $3
+              AND relation = $4
+              AND subject_type = $5
+              AND subject_id = $6
+              AND subject_tenant_id IS NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(resource_type.as_str())
+        .bind(resource_id)
+        .bind(Relation::Owner.as_str())
+        .bind(SubjectType::User.as_str())
+        .bind(from_user)
+        .execute(&mut *tx)
+        .await?;
+
+        if removed.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let owner = sqlx::query_as::<_, PermissionRow>(
+            r#"
+            INSERT INTO bookmark_permissions
+                (tenant_id, resource_type, resource_id, relation, subject_type, subject_id, granted_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NULL)
+            ON CONFLICT (tenant_id, resource_type, resource_id, relation, subject_type, subject_id,
+                COALESCE(subject_tenant_id, 0)) DO UPDATE
+                SET granted_by = EXCLUDED.granted_by, expires_at = NULL
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(resource_type.as_str())
+        .bind(resource_id)
+        .bind(Relation::Owner.as_str())
+        .bind(SubjectType::User.as_str())
+        .bind(to_user)
+        .bind(granted_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if keep_as_editor {
+            sqlx::query(
+                r#"
+                INSERT INTO bookmark_permissions
+                    (tenant_id, resource_type, resource_id, relation, subject_type, subject_id, granted_by)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (tenant_id, resource_type, resource_id, relation, subject_type, subject_id,
+                    COALESCE(subject_tenant_id, 0)) DO NOTHING
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(resource_type.as_str())
+            .bind(resource_id)
+            .bind(Relation::Editor.as_str())
+            .bind(SubjectType::User.as_str())
+            .bind(from_user)
+            .bind(granted_by)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(Some(owner))
+    }
+
+    async fn purge_expired(&self) -> anyhow::Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM bookmark_permissions WHERE expires_at IS NOT NULL AND expires_at < $1",
+        )
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn backfill_relation_codes(&self) -> anyhow::Result<u64> {
+        // SQLite tuples only store the relation name.
+        Ok(0)
+    }
+
+    async fn delete_all_for_resource(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+    ) -> anyhow::Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM bookmark_permissions
+            WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(resource_type.as_str())
+        .bind(resource_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn get_direct_permissions(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+    ) -> anyhow::Result<Vec<PermissionRow>> {
+        let rows = sqlx::query_as::<_, PermissionRow>(
+            r#"
+            SELECT * FROM bookmark_permissions
+            WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3
+            ORDER BY create_time DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(resource_type.as_str())
+        .bind(resource_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn list_resources_by_subject(
+        &self,
+        tenant_id: i32,
+        subject_type: SubjectType,
+        subject_id: &str,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT resource_id FROM bookmark_permissions
+            WHERE tenant_id = $1
+              AND subject_type = $2
+              AND subject_id = $3
+              AND resource_type = $4
+              AND subject_tenant_id IS NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(subject_type.as_str())
+        .bind(subject_id)
+        .bind(resource_type.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.0).collect())
+    }
+
+    async fn list_tenant_resources(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>> {
+        let sql = match resource_type {
+            ResourceType::Bookmark => {
+                "SELECT id FROM bookmark_bookmarks WHERE tenant_id = $1"
+            }
+        };
+        let rows: Vec<(String,)> = sqlx::query_as(sql)
+            .bind(tenant_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|r| r.0).collect())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn grant_federated(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        subject_tenant_id: i32,
+        user_id: &str,
+        granted_by: Option<i32>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<PermissionRow> {
+        let row = sqlx::query_as::<_, PermissionRow>(
+            r#"
+            INSERT INTO bookmark_permissions
+                (tenant_id, resource_type, resource_id, relation, subject_type, subject_id,
+                 subject_tenant_id, granted_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (tenant_id, resource_type, resource_id, relation, subject_type, subject_id,
+                COALESCE(subject_tenant_id, 0)) DO UPDATE
+                SET granted_by = EXCLUDED.granted_by, expires_at = EXCLUDED.expires_at
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(resource_type.as_str())
+        .bind(resource_id)
+        .bind(Relation::Viewer.as_str())
+        .bind(SubjectType::User.as_str())
+        .bind(user_id)
+        .bind(subject_tenant_id)
+        .bind(granted_by)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn has_federated_permission(
+        &self,
+        subject_tenant_id: i32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        user_id: &str,
+    ) -> anyhow::Result<Option<PermissionRow>> {
+        let row = sqlx::query_as::<_, PermissionRow>(
+            r#"
+            SELECT * FROM bookmark_permissions
+            WHERE subject_tenant_id = $1
+              AND subject_type = $2
+              AND subject_id = $3
+              AND resource_type = $4
+              AND resource_id = $5
+              AND (expires_at IS NULL OR expires_at > $6)
+            LIMIT 1
+            "#,
+        )
+        .bind(subject_tenant_id)
+        .bind(SubjectType::User.as_str())
+        .bind(user_id)
+        .bind(resource_type.as_str())
+        .bind(resource_id)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list_federated_resources(
+        &self,
+        subject_tenant_id: i32,
+        user_id: &str,
+        resource_type: ResourceType,
+    ) -> anyhow::Result<Vec<String>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT resource_id FROM bookmark_permissions
+            WHERE subject_tenant_id = $1
+              AND subject_type = $2
+              AND subject_id = $3
+              AND resource_type = $4
+              AND (expires_at IS NULL OR expires_at > $5)
+            "#,
+        )
+        .bind(subject_tenant_id)
+        .bind(SubjectType::User.as_str())
+        .bind(user_id)
+        .bind(resource_type.as_str())
+        .bind(Utc::now())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.0).collect())
+    }
+
+    async fn filter_owned(
+        &self,
+        tenant_id: i32,
+        resource_type: ResourceType,
+        user_id: &str,
+        resource_ids: &[String],
+    ) -> anyhow::Result<Vec<String>> {
+        if resource_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT resource_id FROM bookmark_permissions
+            WHERE tenant_id = $1
+              AND resource_type = $2
+              AND relation = $3
+              AND subject_type = $4
+              AND subject_id = $5
+              AND resource_id IN (SELECT value FROM json_each($6))
+              AND (expires_at IS NULL OR expires_at > $7)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(resource_type.as_str())
+        .bind(Relation::Owner.as_str())
+        .bind(SubjectType::User.as_str())
+        .bind(user_id)
+        .bind(json_list(resource_ids))
+        .bind(Utc::now())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.0).collect())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn list_resources_for_subject(
+        &self,
+        tenant_id: i32,
+        subject_type: SubjectType,
+        subject_id: &str,
+        owned_by: Option<&str>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<SubjectResourceRow>, i64)> {
+        let offset = (page.saturating_sub(1)) * page_size;
+
+        const FROM_WHERE: &str = r#"
+            FROM bookmark_permissions p
+            JOIN bookmark_bookmarks b ON b.id = p.resource_id AND b.tenant_id = p.tenant_id
+            WHERE ((p.tenant_id = $1 AND p.subject_tenant_id IS NULL) OR p.subject_tenant_id = $1)
+              AND p.resource_type = $2
+              AND p.subject_type = $3
+              AND p.subject_id = $4
+              AND ($5 IS NULL OR EXISTS (
+                  SELECT 1 FROM bookmark_permissions o
+                  WHERE o.tenant_id = p.tenant_id
+                    AND o.resource_type = p.resource_type
+                    AND o.resource_id = p.resource_id
+                    AND o.relation = $6
+                    AND o.subject_type = $7
+                    AND o.subject_id = $5
+                    AND o.subject_tenant_id IS NULL
+              ))
+        "#;
+
+        let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) {FROM_WHERE}"))
+            .bind(tenant_id)
+            .bind(ResourceType::Bookmark.as_str())
+            .bind(subject_type.as_str())
+            .bind(subject_id)
+            .bind(owned_by)
+            .bind(Relation::Owner.as_str())
+            .bind(SubjectType::User.as_str())
+            .fetch_one(&self.pool)
+            .await?;
+
+        let rows = sqlx::query_as::<_, SubjectResourceRow>(&format!(
+            r#"
+            SELECT p.resource_type, p.resource_id, p.relation, p.expires_at, b.title,
+                   CASE WHEN b.is_private THEN '' ELSE b.url END AS url
+            {FROM_WHERE}
+            ORDER BY b.title, p.resource_id
+            LIMIT $8 OFFSET $9
+            "#
+        ))
+        .bind(tenant_id)
+        .bind(ResourceType::Bookmark.as_str())
+        .bind(subject_type.as_str())
+        .bind(subject_id)
+        .bind(owned_by)
+        .bind(Relation::Owner.as_str())
+        .bind(SubjectType::User.as_str())
+        .bind(page_size as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((rows, total))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn list_permissions_filtered(
+        &self,
+        tenant_id: i32,
+        resource_type: Option<ResourceType>,
+        resource_id: Option<&str>,
+        subject_type: Option<SubjectType>,
+        subject_id: Option<&str>,
+        after: Option<(DateTime<Utc>, i32)>,
+        page: u32,
+        page_size: u32,
+    ) -> anyhow::Result<(Vec<PermissionRow>, i64)> {
+        let offset = if after.is_some() {
+            0
+        } else {
+            (page.saturating_sub(1)) * page_size
+        };
+
+        // Build dynamic query with optional filters
+        let mut conditions = vec!["tenant_id = $1".to_string()];
+        let mut param_idx = 2u32;
+
+        if resource_type.is_some() {
+            conditions.push(format!("resource_type = ${param_idx}"));
+            param_idx += 1;
+        }
+        if resource_id.is_some() {
+            conditions.push(format!("resource_id = ${param_idx}"));
+            param_idx += 1;
+        }
+        if subject_type.is_some() {
+            conditions.push(format!("subject_type = ${param_idx}"));
+            param_idx += 1;
+        }
+        if subject_id.is_some() {
+            conditions.push(format!("subject_id = ${param_idx}"));
+            param_idx += 1;
+        }
+
+        let where_clause = conditions.join(" AND ");
+        let count_sql = format!("SELECT COUNT(*) FROM bookmark_permissions WHERE {where_clause}");
+
+        if after.is_some() {
+            conditions.push(format!(
+                "(create_time, id) < (${param_idx}, ${})",
+                param_idx + 1
+            ));
+            param_idx += 2;
+        }
+        let data_where = conditions.join(" AND ");
+        let query_sql = format!(
+            "SELECT * FROM bookmark_permissions WHERE {data_where} ORDER BY create_time DESC, id DESC LIMIT ${param_idx} OFFSET ${}",
+            param_idx + 1
+        );
+
+        // Execute count query
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql).bind(tenant_id);
+        if let Some(rt) = &resource_type {
+            count_query = count_query.bind(rt.as_str());
+        }
+        if let Some(ri) = resource_id {
+            count_query = count_query.bind(ri);
+        }
+        if let Some(st) = &subject_type {
+            count_query = count_query.bind(st.as_str());
+        }
+        if let Some(si) = subject_id {
+            count_query = count_query.bind(si);
+        }
+        let (total,) = count_query.fetch_one(&self.pool).await?;
+
+        // Execute data query
+        let mut data_query = sqlx::query_as::<_, PermissionRow>(&query_sql).bind(tenant_id);
+        if let Some(rt) = &resource_type {
+            data_query = data_query.bind(rt.as_str());
+        }
+        if let Some(ri) = resource_id {
+            data_query = data_query.bind(ri);
+        }
+        if let Some(st) = &subject_type {
+            data_query = data_query.bind(st.as_str());
+        }
+        if let Some(si) = subject_id {
+            data_query = data_query.bind(si);
+        }
+        if let Some((create_time, id)) = after {
+            data_query = data_query.bind(create_time).bind(id);
+        }
+        data_query = data_query.bind(page_size as i64 + 1).bind(offset as i64);
+        let rows = data_query.fetch_all(&self.pool).await?;
+
+        Ok((rows, total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::sqlite::test_pool;
+
+    #[tokio::test]
+    async fn federated_grants_are_separate_from_local_tuples() {
+        let pool = test_pool().await;
+        let repo = SqlitePermissionRepo::new(pool);
+        let bookmark = ResourceType::Bookmark;
+
+        // User 5 of tenant 2 and user 5 of tenant 1 are different people.
+        repo.create_permission(
+            2,
+            bookmark,
+            "b1",
+            Relation::Viewer,
+            SubjectType::User,
+            "5",
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        repo.grant_federated(2, bookmark, "b1", 1, "5", None, None)
+            .await
+            .unwrap();
+        let rows = repo
+            .get_direct_permissions(2, bookmark, "b1")
+            .await
+            .unwrap();
+        let mut tenants: Vec<_> = rows.iter().map(|r| r.subject_tenant_id).collect();
+        tenants.sort();
+        assert_eq!(tenants, [None, Some(1)]);
+
+        let removed = repo
+            .delete_permission(2, bookmark, "b1", None, SubjectType::User, "5")
+            .await
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert!(repo
+            .has_federated_permission(1, bookmark, "b1", "5")
+            .await
+            .unwrap()
+            .is_some());
+
+        // An expired grant no longer gives access.
+        let past = Utc::now() - chrono::Duration::minutes(1);
+        repo.grant_federated(2, bookmark, "b1", 1, "5", None, Some(past))
+            .await
+            .unwrap();
+        assert!(repo
+            .has_federated_permission(1, bookmark, "b1", "5")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(repo
+            .list_federated_resources(1, "5", bookmark)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+use chrono::Utc;
+use sqlx::sqlite::SqliteRow;
+use sqlx::types::Json;
+use sqlx::{Row, SqlitePool};
+
+use crate::data::permission_template_repo::{
+    PermissionTemplateRow, PermissionTemplateStore, TemplateGrant,
+};
+
+fn template_row(row: &SqliteRow) -> sqlx::Result<PermissionTemplateRow> {
+    Ok(PermissionTemplateRow {
+        id: row.try_get("id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        grants: row.try_get("grants")?,
+        created_by: row.try_get("created_by")?,
+        create_time: row.try_get("create_time")?,
+        update_time: row.try_get("update_time")?,
+    })
+}
+
+#[derive(Clone)]
+pub struct SqlitePermissionTemplateRepo {
+    pool: SqlitePool,
+}
+
+impl SqlitePermissionTemplateRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl PermissionTemplateStore for SqlitePermissionTemplateRepo {
+    async fn create(
+        &self,
+        tenant_id: i32,
+        name: &str,
+        description: &str,
+        grants: &[TemplateGrant],
+        created_by: &str,
+    ) -> anyhow::Result<Option<PermissionTemplateRow>> {
+        let now = Utc::now();
+        let row = sqlx::query(
+            r#"
+            INSERT INTO bookmark_permission_templates
+                (tenant_id, name, description, grants, created_by, create_time, update_time)
+            VALUES ($1, $2, $3, $4, $5, $6, $6)
+            ON CONFLICT (tenant_id, name) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .bind(description)
+        .bind(Json(grants))
+        .bind(created_by)
+        .bind(now)
+        .try_map(|r| template_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list(&self, tenant_id: i32) -> anyhow::Result<Vec<PermissionTemplateRow>> {
+        let rows = sqlx::query(
+            "SELECT * FROM bookmark_permission_templates WHERE tenant_id = $1 ORDER BY name",
+        )
+        .bind(tenant_id)
+        .try_map(|r| template_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn get(&self, tenant_id: i32, id: i32) -> anyhow::Result<Option<PermissionTemplateRow>> {
+        let row = sqlx::query(
+            "SELECT * FROM bookmark_permission_templates WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .try_map(|r| template_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn update(
+        &self,
+        tenant_id: i32,
+        id: i32,
+        description: &str,
+        grants: &[TemplateGrant],
+    ) -> anyhow::Result<Option<PermissionTemplateRow>> {
+        let row = sqlx::query(
+            r#"
+            UPDATE bookmark_permission_templates
+            SET description = $3, grants = $4, update_time = $5
+            WHERE tenant_id = $1 AND id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .bind(description)
+        .bind(Json(grants))
+        .bind(Utc::now())
+        .try_map(|r| template_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn delete(&self, tenant_id: i32, id: i32) -> anyhow::Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM bookmark_permission_templates WHERE tenant_id = $1 AND id = $2")
+                .bind(tenant_id)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
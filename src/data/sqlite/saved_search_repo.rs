@@ -0,0 +1,273 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+
+use crate::data::saved_search_repo::{NewSavedSearch, SavedSearchRow, SavedSearchStore};
+
+fn saved_search_row(row: &SqliteRow) -> sqlx::Result<SavedSearchRow> {
+    Ok(SavedSearchRow {
+        id: row.try_get("id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        user_id: row.try_get("user_id")?,
+        name: row.try_get("name")?,
+        query: row.try_get("query")?,
+        filter: row.try_get("filter")?,
+        alert_interval_secs: row.try_get("alert_interval_secs")?,
+        next_alert_at: row.try_get("next_alert_at")?,
+        last_alert_time: row.try_get("last_alert_time")?,
+        create_time: row.try_get("create_time")?,
+        update_time: row.try_get("update_time")?,
+    })
+}
+
+#[derive(Clone)]
+pub struct SqliteSavedSearchRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteSavedSearchRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl SavedSearchStore for SqliteSavedSearchRepo {
+    async fn create(&self, s: &NewSavedSearch) -> anyhow::Result<Option<SavedSearchRow>> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO bookmark_saved_searches
+                (tenant_id, user_id, name, query, filter, create_time, update_time)
+            VALUES ($1, $2, $3, $4, $5, $6, $6)
+            ON CONFLICT (tenant_id, user_id, name) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(s.tenant_id)
+        .bind(&s.user_id)
+        .bind(&s.name)
+        .bind(&s.query)
+        .bind(&s.filter)
+        .bind(Utc::now())
+        .try_map(|r| saved_search_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn get(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        id: i32,
+    ) -> anyhow::Result<Option<SavedSearchRow>> {
+        let row = sqlx::query(
+            "SELECT * FROM bookmark_saved_searches \
+             WHERE tenant_id = $1 AND user_id = $2 AND id = $3",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(id)
+        .try_map(|r| saved_search_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list(&self, tenant_id: i32, user_id: &str) -> anyhow::Result<Vec<SavedSearchRow>> {
+        let rows = sqlx::query(
+            "SELECT * FROM bookmark_saved_searches \
+             WHERE tenant_id = $1 AND user_id = $2 ORDER BY name",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .try_map(|r| saved_search_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn delete(&self, tenant_id: i32, user_id: &str, id: i32) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            "DELETE FROM bookmark_saved_searches \
+             WHERE tenant_id = $1 AND user_id = $2 AND id = $3",
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn subscribe(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        id: i32,
+        interval_secs: i64,
+    ) -> anyhow::Result<Option<SavedSearchRow>> {
+        let now = Utc::now();
+        let row = sqlx::query(
+            r#"
+            UPDATE bookmark_saved_searches
+            SET alert_interval_secs = $4,
+                next_alert_at = $6,
+                last_alert_time = CASE
+                    WHEN alert_interval_secs IS NULL THEN $5 ELSE last_alert_time
+                END,
+                update_time = $5
+            WHERE tenant_id = $1 AND user_id = $2 AND id = $3
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(id)
+        .bind(interval_secs)
+        .bind(now)
+        .bind(now + Duration::seconds(interval_secs))
+        .try_map(|r| saved_search_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn unsubscribe(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        id: i32,
+    ) -> anyhow::Result<Option<SavedSearchRow>> {
+        let row = sqlx::query(
+            r#"
+            UPDATE bookmark_saved_searches
+            SET alert_interval_secs = NULL, next_alert_at = NULL, update_time = $4
+            WHERE tenant_id = $1 AND user_id = $2 AND id = $3
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(id)
+        .bind(Utc::now())
+        .try_map(|r| saved_search_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn claim_due(
+        &self,
+        limit: i64,
+        lease_until: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<SavedSearchRow>> {
+        let rows = sqlx::query(
+            r#"
+            UPDATE bookmark_saved_searches SET next_alert_at = $2
+            WHERE id IN (
+                SELECT id FROM bookmark_saved_searches
+                WHERE alert_interval_secs IS NOT NULL AND next_alert_at <= $3
+                ORDER BY next_alert_at
+                LIMIT $1
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_until)
+        .bind(Utc::now())
+        .try_map(|r| saved_search_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn mark_alerted(
+        &self,
+        id: i32,
+        through: DateTime<Utc>,
+        next_alert_at: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE bookmark_saved_searches
+            SET last_alert_time = $2, next_alert_at = $3
+            WHERE id = $1 AND alert_interval_secs IS NOT NULL
+            "#,
+        )
+        .bind(id)
+        .bind(through)
+        .bind(next_alert_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::sqlite::test_pool;
+
+    #[tokio::test]
+    async fn subscriptions_are_leased_until_alerted_or_unsubscribed() {
+        let pool = test_pool().await;
+        let repo = SqliteSavedSearchRepo::new(pool);
+
+        let search = repo
+            .create(&NewSavedSearch {
+                tenant_id: 1,
+                user_id: "7".to_string(),
+                name: "rust".to_string(),
+                query: "rust".to_string(),
+                filter: "domain:github.com".to_string(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(search.alert_interval_secs.is_none());
+        assert!(repo.claim_due(10, Utc::now()).await.unwrap().is_empty());
+
+        // Only the owner can subscribe.
+        assert!(repo
+            .subscribe(1, "8", search.id, 0)
+            .await
+            .unwrap()
+            .is_none());
+        let subscribed = repo.subscribe(1, "7", search.id, 0).await.unwrap().unwrap();
+        assert_eq!(subscribed.alert_interval_secs, Some(0));
+        let since = subscribed.last_alert_time.unwrap();
+
+        // A claimed search is leased to the claimer.
+        let lease_until = Utc::now() + Duration::minutes(5);
+        let due = repo.claim_due(10, lease_until).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].last_alert_time, Some(since));
+        assert!(repo.claim_due(10, lease_until).await.unwrap().is_empty());
+
+        let through = Utc::now();
+        repo.mark_alerted(search.id, through, through)
+            .await
+            .unwrap();
+        let due = repo.claim_due(10, lease_until).await.unwrap();
+        assert_eq!(due[0].last_alert_time, Some(through));
+
+        let unsubscribed = repo.unsubscribe(1, "7", search.id).await.unwrap().unwrap();
+        assert!(unsubscribed.alert_interval_secs.is_none());
+        repo.mark_alerted(search.id, Utc::now(), Utc::now())
+            .await
+            .unwrap();
+        let listed = repo.list(1, "7").await.unwrap();
+        assert!(listed[0].next_alert_at.is_none());
+        assert!(repo.claim_due(10, lease_until).await.unwrap().is_empty());
+    }
+}
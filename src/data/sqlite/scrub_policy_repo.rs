@@ -0,0 +1,72 @@
+use chrono::Utc;
+use sqlx::sqlite::SqliteRow;
+use sqlx::types::Json;
+use sqlx::{Row, SqlitePool};
+
+use crate::data::scrub_policy_repo::{ScrubPolicyRow, ScrubPolicyStore};
+
+fn scrub_policy_row(row: &SqliteRow) -> sqlx::Result<ScrubPolicyRow> {
+    Ok(ScrubPolicyRow {
+        tenant_id: row.try_get("tenant_id")?,
+        enabled: row.try_get("enabled")?,
+        strip_params: row.try_get::<Json<Vec<String>>, _>("strip_params")?.0,
+        param_patterns: row.try_get::<Json<Vec<String>>, _>("param_patterns")?.0,
+        update_time: row.try_get("update_time")?,
+    })
+}
+
+#[derive(Clone)]
+pub struct SqliteScrubPolicyRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteScrubPolicyRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl ScrubPolicyStore for SqliteScrubPolicyRepo {
+    async fn get(&self, tenant_id: i32) -> anyhow::Result<Option<ScrubPolicyRow>> {
+        let row = sqlx::query("SELECT * FROM bookmark_url_scrub_policies WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .try_map(|r| scrub_policy_row(&r))
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row)
+    }
+
+    async fn upsert(
+        &self,
+        tenant_id: i32,
+        enabled: bool,
+        strip_params: &[String],
+        param_patterns: &[String],
+    ) -> anyhow::Result<ScrubPolicyRow> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO bookmark_url_scrub_policies
+                (tenant_id, enabled, strip_params, param_patterns, update_time)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (tenant_id) DO UPDATE
+                SET enabled = excluded.enabled,
+                    strip_params = excluded.strip_params,
+                    param_patterns = excluded.param_patterns,
+                    update_time = excluded.update_time
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(enabled)
+        .bind(Json(strip_params))
+        .bind(Json(param_patterns))
+        .bind(Utc::now())
+        .try_map(|r| scrub_policy_row(&r))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use crate::data::share_notification_repo::{
+    NewShareNotification, ShareNotificationRow, ShareNotificationStore,
+};
+
+const COLUMNS: &str = "id, tenant_id, permission_id, bookmark_id, recipient_id, shared_by, \
+                       relation, message, expires_at, attempts, create_time";
+
+#[derive(Clone)]
+pub struct SqliteShareNotificationRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteShareNotificationRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl ShareNotificationStore for SqliteShareNotificationRepo {
+    async fn create(
+        &self,
+        n: &NewShareNotification,
+        lease_until: DateTime<Utc>,
+    ) -> anyhow::Result<ShareNotificationRow> {
+        let row = sqlx::query_as::<_, ShareNotificationRow>(&format!(
+            r#"
+            INSERT INTO bookmark_share_notifications
+                (tenant_id, permission_id, bookmark_id, recipient_id, shared_by, relation,
+                 message, expires_at, next_attempt_at, create_time)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING {COLUMNS}
+            "#
+        ))
+        .bind(n.tenant_id)
+        .bind(n.permission_id)
+        .bind(&n.bookmark_id)
+        .bind(&n.recipient_id)
+        .bind(&n.shared_by)
+        .bind(&n.relation)
+        .bind(&n.message)
+        .bind(n.expires_at)
+        .bind(lease_until)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn claim_due(
+        &self,
+        limit: i64,
+        lease_until: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<ShareNotificationRow>> {
+        let rows = sqlx::query_as::<_, ShareNotificationRow>(&format!(
+            r#"
+            UPDATE bookmark_share_notifications SET next_attempt_at = $2
+            WHERE id IN (
+                SELECT id FROM bookmark_share_notifications
+                WHERE status = 'pending' AND next_attempt_at <= $3
+                ORDER BY next_attempt_at
+                LIMIT $1
+            )
+            RETURNING {COLUMNS}
+            "#
+        ))
+        .bind(limit)
+        .bind(lease_until)
+        .bind(Utc::now())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn mark_sent(&self, id: i32) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE bookmark_share_notifications
+            SET status = 'sent', attempts = attempts + 1, last_error = NULL, sent_time = $2
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_failure(
+        &self,
+        id: i32,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE bookmark_share_notifications
+            SET attempts = attempts + 1,
+                last_error = $2,
+                status = CASE WHEN $3 IS NULL THEN 'failed' ELSE status END,
+                next_attempt_at = COALESCE($3, next_attempt_at)
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .bind(retry_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
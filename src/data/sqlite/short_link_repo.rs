@@ -0,0 +1,105 @@
+use chrono::Utc;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use super::uuid_col;
+use crate::data::short_link_repo::{ShortLinkRow, ShortLinkStore};
+
+fn short_link_row(row: &SqliteRow) -> sqlx::Result<ShortLinkRow> {
+    Ok(ShortLinkRow {
+        tenant_id: row.try_get("tenant_id")?,
+        code: row.try_get("code")?,
+        bookmark_id: uuid_col(row, "bookmark_id")?,
+        created_by: row.try_get("created_by")?,
+        create_time: row.try_get("create_time")?,
+        revoked_at: row.try_get("revoked_at")?,
+    })
+}
+
+#[derive(Clone)]
+pub struct SqliteShortLinkRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteShortLinkRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl ShortLinkStore for SqliteShortLinkRepo {
+    async fn create(
+        &self,
+        tenant_id: i32,
+        code: &str,
+        bookmark_id: Uuid,
+        created_by: &str,
+    ) -> anyhow::Result<Option<ShortLinkRow>> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO bookmark_short_links (tenant_id, code, bookmark_id, created_by, create_time)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (tenant_id, code) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(code)
+        .bind(bookmark_id.hyphenated())
+        .bind(created_by)
+        .bind(Utc::now())
+        .try_map(|r| short_link_row(&r))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn get(&self, tenant_id: i32, code: &str) -> anyhow::Result<Option<ShortLinkRow>> {
+        let row =
+            sqlx::query("SELECT * FROM bookmark_short_links WHERE tenant_id = $1 AND code = $2")
+                .bind(tenant_id)
+                .bind(code)
+                .try_map(|r| short_link_row(&r))
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(row)
+    }
+
+    async fn revoke(&self, tenant_id: i32, code: &str) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE bookmark_short_links SET revoked_at = $3
+            WHERE tenant_id = $1 AND code = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(code)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn resolve(&self, tenant_id: i32, code: &str) -> anyhow::Result<Option<(Uuid, String)>> {
+        let row = sqlx::query(
+            r#"
+            SELECT b.id, b.url
+            FROM bookmark_short_links l
+            JOIN bookmark_bookmarks b ON b.id = l.bookmark_id
+            WHERE l.tenant_id = $1 AND l.code = $2 AND l.revoked_at IS NULL AND NOT b.is_private
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(code)
+        .try_map(|r: SqliteRow| Ok((uuid_col(&r, "id")?, r.try_get("url")?)))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::{NaiveDate, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::types::Json;
+use sqlx::{Row, SqlitePool};
+
+use super::NORMALIZED_HOST;
+use crate::authz::relations::SubjectType;
+use crate::data::stats_repo::{
+    DailyStatsRow, StatsStore, TenantStatsRow, SEARCH_OPERATION, VISIT_OPERATION,
+};
+
+fn daily_stats_row(row: &SqliteRow) -> sqlx::Result<DailyStatsRow> {
+    Ok(DailyStatsRow {
+        tenant_id: row.try_get("tenant_id")?,
+        day: row.try_get("day")?,
+        creates: row.try_get("creates")?,
+        edits: row.try_get("edits")?,
+        searches: row.try_get("searches")?,
+        visits: row.try_get("visits")?,
+        top_domains: row.try_get::<Json<_>, _>("top_domains")?,
+        update_time: row.try_get("update_time")?,
+    })
+}
+
+#[derive(Clone)]
+pub struct SqliteStatsRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteStatsRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl StatsStore for SqliteStatsRepo {
+    /// Bookmark size is the byte length of the text columns; SQLite has no
+    /// per-row storage size.
+    async fn tenant_stats(&self, tenant_id: i32) -> anyhow::Result<TenantStatsRow> {
+        let (bookmark_count, tag_count, bookmark_bytes): (i64, i64, i64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*),
+                   (SELECT COUNT(DISTINCT t.value) FROM bookmark_bookmarks, json_each(tags) AS t
+                    WHERE tenant_id = $1),
+                   COALESCE(SUM(
+                       length(CAST(url AS BLOB)) + length(CAST(title AS BLOB))
+                       + length(CAST(description AS BLOB)) + length(CAST(tags AS BLOB))
+                       + length(CAST(metadata AS BLOB)) + length(CAST(notes AS BLOB))
+                   ), 0)
+            FROM bookmark_bookmarks
+            WHERE tenant_id = $1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let (archive_count, archive_bytes): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*), COALESCE(SUM(content_bytes), 0)
+            FROM bookmark_archives
+            WHERE tenant_id = $1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let (users_with_grants,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(DISTINCT subject_id) FROM bookmark_permissions
+            WHERE tenant_id = $1 AND subject_type = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(SubjectType::User.as_str())
+        .fetch_one(&self.pool)
+        .await?;
+
+        let by_relation: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT relation, COUNT(*) FROM bookmark_permissions
+            WHERE tenant_id = $1
+            GROUP BY relation
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(TenantStatsRow {
+            bookmark_count,
+            tag_count,
+            users_with_grants,
+            permissions_by_relation: by_relation.into_iter().collect(),
+            bookmark_bytes,
+            archive_count,
+            archive_bytes,
+        })
+    }
+
+    /// Timestamps are UTC text starting with the date, so days are their
+    /// first ten characters and bounds compare as text.
+    async fn roll_up_daily_stats(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        top_domains: u32,
+    ) -> anyhow::Result<u64> {
+        let since = from.to_string();
+        let until = to.succ_opt().unwrap_or(to).to_string();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM bookmark_daily_stats WHERE day >= $1 AND day < $2")
+            .bind(&since)
+            .bind(&until)
+            .execute(&mut *tx)
+            .await?;
+
+        let result = sqlx::query(&format!(
+            r#"
+            WITH created AS (
+                SELECT tenant_id, substr(create_time, 1, 10) AS day, COUNT(*) AS n
+                FROM bookmark_bookmarks
+                WHERE create_time >= $1 AND create_time < $2
+                GROUP BY 1, 2
+            ), edited AS (
+                SELECT tenant_id, substr(create_time, 1, 10) AS day, COUNT(*) AS n
+                FROM bookmark_revisions
+                WHERE create_time >= $1 AND create_time < $2
+                GROUP BY 1, 2
+            ), audited AS (
+                SELECT tenant_id, substr(create_time, 1, 10) AS day,
+                       COUNT(*) FILTER (WHERE operation = $3) AS searches,
+                       COUNT(*) FILTER (WHERE operation = $4) AS visits
+                FROM bookmark_audit_events
+                WHERE create_time >= $1 AND create_time < $2 AND operation IN ($3, $4)
+                GROUP BY 1, 2
+            ), domains AS (
+                SELECT tenant_id, day,
+                       json_group_array(json_object('domain', domain, 'count', n)
+                                        ORDER BY n DESC, domain) AS top
+                FROM (
+                    SELECT tenant_id, day, domain, n,
+                           ROW_NUMBER() OVER (PARTITION BY tenant_id, day
+                                              ORDER BY n DESC, domain) AS rank
+                    FROM (
+                        SELECT tenant_id, substr(create_time, 1, 10) AS day,
+                               {NORMALIZED_HOST} AS domain, COUNT(*) AS n
+                        FROM bookmark_bookmarks
+                        WHERE create_time >= $1 AND create_time < $2 AND normalized_url <> ''
+                        GROUP BY 1, 2, 3
+                    ) counted
+                ) ranked
+                WHERE rank <= $5
+                GROUP BY 1, 2
+            ), days AS (
+                SELECT tenant_id, day FROM created
+                UNION SELECT tenant_id, day FROM edited
+                UNION SELECT tenant_id, day FROM audited
+            )
+            INSERT INTO bookmark_daily_stats
+                (tenant_id, day, creates, edits, searches, visits, top_domains, update_time)
+            SELECT d.tenant_id, d.day, COALESCE(c.n, 0), COALESCE(e.n, 0),
+                   COALESCE(a.searches, 0), COALESCE(a.visits, 0), COALESCE(t.top, '[]'), $6
+            FROM days d
+            LEFT JOIN created c USING (tenant_id, day)
+            LEFT JOIN edited e USING (tenant_id, day)
+            LEFT JOIN audited a USING (tenant_id, day)
+            LEFT JOIN domains t USING (tenant_id, day)
+            "#
+        ))
+        .bind(&since)
+        .bind(&until)
+        .bind(SEARCH_OPERATION)
+        .bind(VISIT_OPERATION)
+        .bind(top_domains as i64)
+        .bind(Utc::now())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn daily_stats(
+        &self,
+        tenant_id: i32,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> anyhow::Result<Vec<DailyStatsRow>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM bookmark_daily_stats
+            WHERE tenant_id = $1 AND day BETWEEN $2 AND $3
+            ORDER BY day
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(from.to_string())
+        .bind(to.to_string())
+        .try_map(|r| daily_stats_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
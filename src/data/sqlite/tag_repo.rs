@@ -0,0 +1,106 @@
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::data::tag_repo::{TagFields, TagRow, TagStore};
+
+#[derive(Clone)]
+pub struct SqliteTagRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteTagRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl TagStore for SqliteTagRepo {
+    async fn create(
+        &self,
+        tenant_id: i32,
+        name: &str,
+        fields: &TagFields,
+        created_by: &str,
+    ) -> anyhow::Result<Option<TagRow>> {
+        let row = sqlx::query_as::<_, TagRow>(
+            r#"
+            INSERT INTO bookmark_tags
+                (tenant_id, name, color, emoji, description, created_by, create_time, update_time)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+            ON CONFLICT (tenant_id, name) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .bind(&fields.color)
+        .bind(&fields.emoji)
+        .bind(&fields.description)
+        .bind(created_by)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn get(&self, tenant_id: i32, id: i32) -> anyhow::Result<Option<TagRow>> {
+        let row = sqlx::query_as::<_, TagRow>(
+            "SELECT * FROM bookmark_tags WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list(&self, tenant_id: i32) -> anyhow::Result<Vec<TagRow>> {
+        let rows = sqlx::query_as::<_, TagRow>(
+            "SELECT * FROM bookmark_tags WHERE tenant_id = $1 ORDER BY name",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn update(
+        &self,
+        tenant_id: i32,
+        id: i32,
+        fields: &TagFields,
+    ) -> anyhow::Result<Option<TagRow>> {
+        let row = sqlx::query_as::<_, TagRow>(
+            r#"
+            UPDATE bookmark_tags
+            SET color = $3, emoji = $4, description = $5, update_time = $6
+            WHERE tenant_id = $1 AND id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .bind(&fields.color)
+        .bind(&fields.emoji)
+        .bind(&fields.description)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn delete(&self, tenant_id: i32, id: i32) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM bookmark_tags WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,129 @@
+use chrono::Utc;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Column, Row, SqliteConnection, SqlitePool, TypeInfo, ValueRef};
+
+use crate::authz::relations::{Relation, ResourceType, SubjectType};
+use crate::data::user_data_repo::{
+    erase_steps, BookmarkDisposition, ErasureSummary, UserDataStore, UserRecordKind,
+};
+
+#[derive(Clone)]
+pub struct SqliteUserDataRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteUserDataRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Row as a JSON object keyed by column name, typed by each value's storage
+/// class. JSON-encoded columns such as `tags` stay strings.
+fn row_to_json(row: &SqliteRow) -> sqlx::Result<serde_json::Value> {
+    let mut record = serde_json::Map::with_capacity(row.columns().len());
+    for column in row.columns() {
+        let i = column.ordinal();
+        let raw = row.try_get_raw(i)?;
+        let value = if raw.is_null() {
+            serde_json::Value::Null
+        } else {
+            match raw.type_info().name() {
+                "INTEGER" => row.try_get::<i64, _>(i)?.into(),
+                "REAL" => row.try_get::<f64, _>(i)?.into(),
+                "BLOB" => serde_json::Value::Null,
+                _ => row.try_get::<String, _>(i)?.into(),
+            }
+        };
+        record.insert(column.name().to_string(), value);
+    }
+    Ok(serde_json::Value::Object(record))
+}
+
+#[tonic::async_trait]
+impl UserDataStore for SqliteUserDataRepo {
+    async fn user_records(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        kind: UserRecordKind,
+        offset: i64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(&format!("SELECT t.* {} LIMIT $3 OFFSET $4", kind.source()))
+            .bind(tenant_id)
+            .bind(user_id)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(row_to_json).collect::<sqlx::Result<_>>()?)
+    }
+
+    async fn erase_user(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        disposition: BookmarkDisposition,
+    ) -> anyhow::Result<ErasureSummary> {
+        let mut tx = self.pool.begin().await?;
+        let mut summary = ErasureSummary::default();
+
+        if let BookmarkDisposition::Reassign(new_owner) = disposition {
+            summary.bookmarks_reassigned =
+                reassign_in(&mut tx, tenant_id, user_id, new_owner).await?;
+        }
+        for (tally, sql) in erase_steps(disposition) {
+            let result = sqlx::query(&sql)
+                .bind(tenant_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+            summary.add(tally, result.rows_affected());
+        }
+
+        tx.commit().await?;
+        Ok(summary)
+    }
+}
+
+async fn reassign_in(
+    conn: &mut SqliteConnection,
+    tenant_id: i32,
+    user_id: &str,
+    new_owner: i32,
+) -> anyhow::Result<u64> {
+    sqlx::query(
+        r#"
+        INSERT INTO bookmark_permissions
+            (tenant_id, resource_type, resource_id, relation, subject_type, subject_id)
+        SELECT tenant_id, $3, id, $4, $5, $6
+        FROM bookmark_bookmarks
+        WHERE tenant_id = $1 AND CAST(created_by AS TEXT) = $2
+        ON CONFLICT (tenant_id, resource_type, resource_id, relation, subject_type, subject_id,
+            COALESCE(subject_tenant_id, 0)) DO NOTHING
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(user_id)
+    .bind(ResourceType::Bookmark.as_str())
+    .bind(Relation::Owner.as_str())
+    .bind(SubjectType::User.as_str())
+    .bind(new_owner.to_string())
+    .execute(&mut *conn)
+    .await?;
+
+    let result = sqlx::query(
+        "UPDATE bookmark_bookmarks SET created_by = $3, update_time = $4 \
+         WHERE tenant_id = $1 AND CAST(created_by AS TEXT) = $2",
+    )
+    .bind(tenant_id)
+    .bind(user_id)
+    .bind(new_owner)
+    .bind(Utc::now())
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,176 @@
+use chrono::Utc;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use super::{json_list, uuid_col};
+use crate::data::user_flag_repo::{ReadingStatus, UserFlagRow, UserFlagStore};
+
+fn user_flag_row(row: &SqliteRow) -> sqlx::Result<UserFlagRow> {
+    Ok(UserFlagRow {
+        tenant_id: row.try_get("tenant_id")?,
+        user_id: row.try_get("user_id")?,
+        bookmark_id: uuid_col(row, "bookmark_id")?,
+        is_pinned: row.try_get("is_pinned")?,
+        pinned_at: row.try_get("pinned_at")?,
+        reading_status: row.try_get("reading_status")?,
+        reading_status_time: row.try_get("reading_status_time")?,
+        update_time: row.try_get("update_time")?,
+        sort_order: row.try_get("sort_order")?,
+    })
+}
+
+#[derive(Clone)]
+pub struct SqliteUserFlagRepo {
+    pool: SqlitePool,
+}
+
+impl SqliteUserFlagRepo {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl UserFlagStore for SqliteUserFlagRepo {
+    async fn list_for_user(
+        &self,
+        user_id: &str,
+        bookmark_ids: &[Uuid],
+    ) -> anyhow::Result<Vec<UserFlagRow>> {
+        if bookmark_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM bookmark_user_flags
+            WHERE user_id = $1 AND bookmark_id IN (SELECT value FROM json_each($2))
+            "#,
+        )
+        .bind(user_id)
+        .bind(json_list(bookmark_ids))
+        .try_map(|r| user_flag_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn set_pinned(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        bookmark_id: Uuid,
+        pinned: bool,
+    ) -> anyhow::Result<UserFlagRow> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO bookmark_user_flags
+                (tenant_id, user_id, bookmark_id, is_pinned, pinned_at, update_time)
+            VALUES ($1, $2, $3, $4, CASE WHEN $4 THEN $5 END, $5)
+            ON CONFLICT (user_id, bookmark_id) DO UPDATE SET
+                is_pinned = excluded.is_pinned,
+                pinned_at = CASE
+                    WHEN NOT excluded.is_pinned THEN NULL
+                    ELSE COALESCE(bookmark_user_flags.pinned_at, $5)
+                END,
+                sort_order = CASE
+                    WHEN excluded.is_pinned THEN bookmark_user_flags.sort_order
+                END,
+                update_time = $5
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(bookmark_id.hyphenated())
+        .bind(pinned)
+        .bind(Utc::now())
+        .try_map(|r| user_flag_row(&r))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list_pins(&self, tenant_id: i32, user_id: &str) -> anyhow::Result<Vec<UserFlagRow>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT f.* FROM bookmark_user_flags f
+            JOIN bookmark_bookmarks b ON b.id = f.bookmark_id
+            WHERE f.tenant_id = $1 AND f.user_id = $2 AND f.is_pinned
+            ORDER BY f.sort_order NULLS LAST, b.create_time DESC, b.id DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .try_map(|r| user_flag_row(&r))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn set_sort_orders(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        orders: &[(Uuid, String)],
+    ) -> anyhow::Result<u64> {
+        let orders: Vec<(String, &str)> = orders
+            .iter()
+            .map(|(id, key)| (id.to_string(), key.as_str()))
+            .collect();
+        let result = sqlx::query(
+            r#"
+            UPDATE bookmark_user_flags
+            SET sort_order = json_extract(o.value, '$[1]'), update_time = $4
+            FROM json_each($3) o
+            WHERE tenant_id = $1
+              AND user_id = $2
+              AND bookmark_id = json_extract(o.value, '$[0]')
+              AND is_pinned
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(json_list(&orders))
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn set_reading_status(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        bookmark_id: Uuid,
+        status: ReadingStatus,
+    ) -> anyhow::Result<UserFlagRow> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO bookmark_user_flags
+                (tenant_id, user_id, bookmark_id, reading_status, reading_status_time, update_time)
+            VALUES ($1, $2, $3, $4, $5, $5)
+            ON CONFLICT (user_id, bookmark_id) DO UPDATE SET
+                reading_status = excluded.reading_status,
+                reading_status_time = $5,
+                update_time = $5
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(bookmark_id.hyphenated())
+        .bind(status.as_str())
+        .bind(Utc::now())
+        .try_map(|r| user_flag_row(&r))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+}
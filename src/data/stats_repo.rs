@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::PgPool;
+
+use crate::authz::relations::{Relation, SubjectType};
+
+/// Aggregate usage figures for one tenant.
+#[derive(Debug, Default)]
+pub struct TenantStatsRow {
+    pub bookmark_count: i64,
+    /// Distinct tags across the tenant's bookmarks.
+    pub tag_count: i64,
+    /// Distinct users holding at least one direct grant.
+    pub users_with_grants: i64,
+    pub permissions_by_relation: HashMap<String, i64>,
+    /// Stored size of the bookmark rows.
+    pub bookmark_bytes: i64,
+    pub archive_count: i64,
+    pub archive_bytes: i64,
+}
+
+/// Audited RPCs counted as searches in the daily stats.
+pub const SEARCH_OPERATION: &str = "/bookmark.service.v1.BookmarkService/SearchBookmarks";
+/// Audited RPCs counted as visits in the daily stats.
+pub const VISIT_OPERATION: &str = "/bookmark.service.v1.BookmarkService/RecordVisit";
+
+/// Bookmarks created on one domain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainCount {
+    pub domain: String,
+    pub count: i64,
+}
+
+/// One tenant's usage over one UTC day.
+#[derive(Debug, sqlx::FromRow)]
+pub struct DailyStatsRow {
+    pub tenant_id: i32,
+    pub day: NaiveDate,
+    pub creates: i64,
+    /// Revisions recorded, so each edit of each bookmark counts once.
+    pub edits: i64,
+    pub searches: i64,
+    pub visits: i64,
+    /// Domains of the bookmarks created that day, most bookmarked first.
+    pub top_domains: Json<Vec<DomainCount>>,
+    pub update_time: DateTime<Utc>,
+}
+
+/// Cross-table aggregates for the admin dashboard.
+#[tonic::async_trait]
+pub trait StatsStore: Send + Sync {
+    async fn tenant_stats(&self, tenant_id: i32) -> anyhow::Result<TenantStatsRow>;
+
+    /// Recompute every tenant's daily stats for the days `from..=to`, keeping
+    /// up to `top_domains` domains per day. Days are replaced in one
+    /// transaction, so reruns are idempotent. Returns the rows written.
+    async fn roll_up_daily_stats(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        top_domains: u32,
+    ) -> anyhow::Result<u64>;
+
+    /// The tenant's rolled-up days in `from..=to`, oldest first. Days without
+    /// activity have no row.
+    async fn daily_stats(
+        &self,
+        tenant_id: i32,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> anyhow::Result<Vec<DailyStatsRow>>;
+}
+
+/// Start of `from` and of the day after `to`, bounding a rollup in UTC.
+fn day_bounds(from: NaiveDate, to: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = |d: NaiveDate| d.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    (start(from), start(to.succ_opt().unwrap_or(to)))
+}
+
+#[derive(Clone)]
+pub struct StatsRepo {
+    pool: PgPool,
+}
+
+impl StatsRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl StatsStore for StatsRepo {
+    async fn tenant_stats(&self, tenant_id: i32) -> anyhow::Result<TenantStatsRow> {
+        let (bookmark_count, tag_count, bookmark_bytes): (i64, i64, i64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*),
+                   (SELECT COUNT(DISTINCT t) FROM bookmark_bookmarks, UNNEST(tags) AS t
+                    WHERE tenant_id = $1),
+                   COALESCE(SUM(pg_column_size(b.*)), 0)::BIGINT
+            FROM bookmark_bookmarks b
+            WHERE tenant_id = $1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let (archive_count, archive_bytes): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*), COALESCE(SUM(content_bytes), 0)::BIGINT
+            FROM bookmark_archives
+            WHERE tenant_id = $1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let (users_with_grants,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(DISTINCT subject_id) FROM bookmark_permissions
+            WHERE tenant_id = $1 AND subject_type = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(SubjectType::User.as_str())
+        .fetch_one(&self.pool)
+        .await?;
+
+        let by_relation: Vec<(i16, i64)> = sqlx::query_as(
+            r#"
+            SELECT relation_code, COUNT(*) FROM bookmark_permissions
+            WHERE tenant_id = $1
+            GROUP BY relation_code
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(TenantStatsRow {
+            bookmark_count,
+            tag_count,
+            users_with_grants,
+            permissions_by_relation: by_relation
+                .into_iter()
+                .filter_map(|(code, n)| Some((Relation::from_code(code)?.as_str().to_string(), n)))
+                .collect(),
+            bookmark_bytes,
+            archive_count,
+            archive_bytes,
+        })
+    }
+
+    async fn roll_up_daily_stats(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        top_domains: u32,
+    ) -> anyhow::Result<u64> {
+        let (since, until) = day_bounds(from, to);
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM bookmark_daily_stats WHERE day BETWEEN $1 AND $2")
+            .bind(from)
+            .bind(to)
+            .execute(&mut *tx)
+            .await?;
+
+        let result = sqlx::query(
+            r#"
+            WITH created AS (
+                SELECT tenant_id, (create_time AT TIME ZONE 'UTC')::DATE AS day, COUNT(*) AS n
+                FROM bookmark_bookmarks
+                WHERE create_time >= $1 AND create_time < $2
+                GROUP BY 1, 2
+            ), edited AS (
+                SELECT tenant_id, (create_time AT TIME ZONE 'UTC')::DATE AS day, COUNT(*) AS n
+                FROM bookmark_revisions
+                WHERE create_time >= $1 AND create_time < $2
+                GROUP BY 1, 2
+            ), audited AS (
+                SELECT tenant_id, (create_time AT TIME ZONE 'UTC')::DATE AS day,
+                       COUNT(*) FILTER (WHERE operation = $3) AS searches,
+                       COUNT(*) FILTER (WHERE operation = $4) AS visits
+                FROM bookmark_audit_events
+                WHERE create_time >= $1 AND create_time < $2 AND operation IN ($3, $4)
+                GROUP BY 1, 2
+            ), domains AS (
+                SELECT tenant_id, day,
+                       jsonb_agg(jsonb_build_object('domain', domain, 'count', n)
+                                 ORDER BY n DESC, domain) AS top
+                FROM (
+                    SELECT tenant_id, day, domain, n,
+                           ROW_NUMBER() OVER (PARTITION BY tenant_id, day
+                                              ORDER BY n DESC, domain) AS rank
+                    FROM (
+                        SELECT tenant_id, (create_time AT TIME ZONE 'UTC')::DATE AS day,
+                               SUBSTRING(normalized_url FROM '^[^/?:]*') AS domain, COUNT(*) AS n
+                        FROM bookmark_bookmarks
+                        WHERE create_time >= $1 AND create_time < $2 AND normalized_url <> ''
+                        GROUP BY 1, 2, 3
+                    ) counted
+                ) ranked
+                WHERE rank <= $5
+                GROUP BY 1, 2
+            ), days AS (
+                SELECT tenant_id, day FROM created
+                UNION SELECT tenant_id, day FROM edited
+                UNION SELECT tenant_id, day FROM audited
+            )
+            INSERT INTO bookmark_daily_stats
+                (tenant_id, day, creates, edits, searches, visits, top_domains)
+            SELECT d.tenant_id, d.day, COALESCE(c.n, 0), COALESCE(e.n, 0),
+                   COALESCE(a.searches, 0), COALESCE(a.visits, 0), COALESCE(t.top, '[]')
+            FROM days d
+            LEFT JOIN created c USING (tenant_id, day)
+            LEFT JOIN edited e USING (tenant_id, day)
+            LEFT JOIN audited a USING (tenant_id, day)
+            LEFT JOIN domains t USING (tenant_id, day)
+            "#,
+        )
+        .bind(since)
+        .bind(until)
+        .bind(SEARCH_OPERATION)
+        .bind(VISIT_OPERATION)
+        .bind(top_domains as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn daily_stats(
+        &self,
+        tenant_id: i32,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> anyhow::Result<Vec<DailyStatsRow>> {
+        let rows = sqlx::query_as::<_, DailyStatsRow>(
+            r#"
+            SELECT * FROM bookmark_daily_stats
+            WHERE tenant_id = $1 AND day BETWEEN $2 AND $3
+            ORDER BY day
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
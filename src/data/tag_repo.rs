@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TagRow {
+    pub id: i32,
+    pub tenant_id: i32,
+    pub name: String,
+    pub color: String,
+    pub emoji: String,
+    pub description: String,
+    pub created_by: String,
+    pub create_time: DateTime<Utc>,
+    pub update_time: DateTime<Utc>,
+}
+
+/// Everything about a tag's metadata except its name, which is fixed at
+/// creation so it keeps matching the bookmarks that carry it.
+#[derive(Debug)]
+pub struct TagFields {
+    pub color: String,
+    pub emoji: String,
+    pub description: String,
+}
+
+#[tonic::async_trait]
+pub trait TagStore: Send + Sync {
+    /// Returns `None` if the tenant already has metadata for that name.
+    async fn create(
+        &self,
+        tenant_id: i32,
+        name: &str,
+        fields: &TagFields,
+        created_by: &str,
+    ) -> anyhow::Result<Option<TagRow>>;
+
+    async fn get(&self, tenant_id: i32, id: i32) -> anyhow::Result<Option<TagRow>>;
+
+    async fn list(&self, tenant_id: i32) -> anyhow::Result<Vec<TagRow>>;
+
+    async fn update(
+        &self,
+        tenant_id: i32,
+        id: i32,
+        fields: &TagFields,
+    ) -> anyhow::Result<Option<TagRow>>;
+
+    /// Returns false if the tenant has no tag with that ID.
+    async fn delete(&self, tenant_id: i32, id: i32) -> anyhow::Result<bool>;
+}
+
+#[derive(Clone)]
+pub struct TagRepo {
+    pool: PgPool,
+}
+
+impl TagRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl TagStore for TagRepo {
+    async fn create(
+        &self,
+        tenant_id: i32,
+        name: &str,
+        fields: &TagFields,
+        created_by: &str,
+    ) -> anyhow::Result<Option<TagRow>> {
+        let row = sqlx::query_as::<_, TagRow>(
+            r#"
+            INSERT INTO bookmark_tags (tenant_id, name, color, emoji, description, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (tenant_id, name) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .bind(&fields.color)
+        .bind(&fields.emoji)
+        .bind(&fields.description)
+        .bind(created_by)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn get(&self, tenant_id: i32, id: i32) -> anyhow::Result<Option<TagRow>> {
+        let row = sqlx::query_as::<_, TagRow>(
+            "SELECT * FROM bookmark_tags WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list(&self, tenant_id: i32) -> anyhow::Result<Vec<TagRow>> {
+        let rows = sqlx::query_as::<_, TagRow>(
+            "SELECT * FROM bookmark_tags WHERE tenant_id = $1 ORDER BY name",
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn update(
+        &self,
+        tenant_id: i32,
+        id: i32,
+        fields: &TagFields,
+    ) -> anyhow::Result<Option<TagRow>> {
+        let row = sqlx::query_as::<_, TagRow>(
+            r#"
+            UPDATE bookmark_tags
+            SET color = $3, emoji = $4, description = $5, update_time = NOW()
+            WHERE tenant_id = $1 AND id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(id)
+        .bind(&fields.color)
+        .bind(&fields.emoji)
+        .bind(&fields.description)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn delete(&self, tenant_id: i32, id: i32) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM bookmark_tags WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
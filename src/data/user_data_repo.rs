@@ -0,0 +1,393 @@
+use sqlx::{PgConnection, PgPool};
+
+use crate::authz::relations::{Relation, ResourceType, SubjectType};
+
+/// Written over an erased user's id in text columns that cannot be cleared.
+pub const ERASED_USER: &str = "erased";
+
+/// Selects the ids, as text, of the bookmarks user `$2` created in tenant `$1`.
+const USER_BOOKMARKS: &str = "SELECT CAST(id AS TEXT) FROM bookmark_bookmarks \
+     WHERE tenant_id = $1 AND CAST(created_by AS TEXT) = $2";
+
+/// Kinds of records tied to a user, in the order a data export lists them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserRecordKind {
+    /// Bookmarks the user created.
+    Bookmark,
+    /// Revisions recorded when the user edited a bookmark.
+    Revision,
+    /// The user's pins and reading statuses.
+    Flag,
+    /// Permission tuples granted to the user directly.
+    Grant,
+    GroupMembership,
+    AccessRequest,
+    AuditEvent,
+}
+
+impl UserRecordKind {
+    pub const ALL: &[Self] = &[
+        Self::Bookmark,
+        Self::Revision,
+        Self::Flag,
+        Self::Grant,
+        Self::GroupMembership,
+        Self::AccessRequest,
+        Self::AuditEvent,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Bookmark => "bookmark",
+            Self::Revision => "revision",
+            Self::Flag => "flag",
+            Self::Grant => "grant",
+            Self::GroupMembership => "groupMembership",
+            Self::AccessRequest => "accessRequest",
+            Self::AuditEvent => "auditEvent",
+        }
+    }
+
+    /// `FROM ... WHERE ... ORDER BY ...` selecting the kind's rows, aliased `t`,
+    /// for tenant `$1` and user `$2`. Integer user columns are compared as text
+    /// so ids that are not numbers simply match nothing.
+    pub(crate) fn source(self) -> String {
+        let (table, user, order) = match self {
+            Self::Bookmark => (
+                "bookmark_bookmarks",
+                "CAST(t.created_by AS TEXT) = $2".to_string(),
+                "t.create_time, t.id",
+            ),
+            Self::Revision => (
+                "bookmark_revisions",
+                "CAST(t.edited_by AS TEXT) = $2".to_string(),
+                "t.id",
+            ),
+            Self::Flag => (
+                "bookmark_user_flags",
+                "t.user_id = $2".to_string(),
+                "t.bookmark_id",
+            ),
+            Self::Grant => (
+                "bookmark_permissions",
+                format!(
+                    "t.subject_type = '{}' AND t.subject_id = $2",
+                    SubjectType::User.as_str()
+                ),
+                "t.id",
+            ),
+            Self::GroupMembership => (
+                "bookmark_group_members",
+                "t.user_id = $2".to_string(),
+                "t.group_id",
+            ),
+            Self::AccessRequest => (
+                "bookmark_access_requests",
+                "t.requester_id = $2".to_string(),
+                "t.id",
+            ),
+            Self::AuditEvent => (
+                "bookmark_audit_events",
+                "t.user_id = $2".to_string(),
+                "t.id",
+            ),
+        };
+        format!("FROM {table} t WHERE t.tenant_id = $1 AND {user} ORDER BY {order}")
+    }
+}
+
+/// What happens to the bookmarks an erased user created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookmarkDisposition {
+    /// Delete them along with every tuple on them.
+    Delete,
+    /// Make the given user their creator and an owner.
+    Reassign(i32),
+}
+
+/// Rows touched by erasing a user.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ErasureSummary {
+    pub bookmarks_deleted: u64,
+    pub bookmarks_reassigned: u64,
+    /// The user's own tuples, plus tuples on their deleted bookmarks.
+    pub permissions_removed: u64,
+    pub group_memberships_removed: u64,
+    pub flags_removed: u64,
+    /// The user's own requests, plus requests for their deleted bookmarks.
+    pub access_requests_removed: u64,
+    pub audit_events_anonymized: u64,
+    /// Rows whose `created_by`, `granted_by`, `edited_by`, `added_by`,
+    /// `shared_by` or `resolved_by` named the user.
+    pub references_anonymized: u64,
+}
+
+/// Counter in [`ErasureSummary`] that a statement's affected rows add to.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Tally {
+    BookmarksDeleted,
+    Permissions,
+    GroupMemberships,
+    Flags,
+    AccessRequests,
+    AuditEvents,
+    References,
+    /// Cleared without being reported.
+    Uncounted,
+}
+
+impl ErasureSummary {
+    pub(crate) fn add(&mut self, tally: Tally, rows: u64) {
+        match tally {
+            Tally::BookmarksDeleted => self.bookmarks_deleted += rows,
+            Tally::Permissions => self.permissions_removed += rows,
+            Tally::GroupMemberships => self.group_memberships_removed += rows,
+            Tally::Flags => self.flags_removed += rows,
+            Tally::AccessRequests => self.access_requests_removed += rows,
+            Tally::AuditEvents => self.audit_events_anonymized += rows,
+            Tally::References => self.references_anonymized += rows,
+            Tally::Uncounted => {}
+        }
+    }
+}
+
+/// Statements erasing user `$2` in tenant `$1`, in order, valid on both
+/// Postgres and SQLite. Reassigning bookmarks happens before these run, since
+/// it needs the tuple table's dialect-specific conflict target.
+pub(crate) fn erase_steps(disposition: BookmarkDisposition) -> Vec<(Tally, String)> {
+    let mut steps = Vec::new();
+    if disposition == BookmarkDisposition::Delete {
+        steps.push((
+            Tally::Permissions,
+            format!(
+                "DELETE FROM bookmark_permissions WHERE tenant_id = $1 AND resource_type = '{}' \
+                 AND resource_id IN ({USER_BOOKMARKS})",
+                ResourceType::Bookmark.as_str()
+            ),
+        ));
+        steps.push((
+            Tally::AccessRequests,
+            format!(
+                "DELETE FROM bookmark_access_requests WHERE tenant_id = $1 \
+                 AND bookmark_id IN ({USER_BOOKMARKS})"
+            ),
+        ));
+        steps.push((
+            Tally::BookmarksDeleted,
+            "DELETE FROM bookmark_bookmarks WHERE tenant_id = $1 AND CAST(created_by AS TEXT) = $2"
+                .to_string(),
+        ));
+    }
+
+    let deletes = [
+        (
+            Tally::Permissions,
+            format!(
+                "bookmark_permissions WHERE (tenant_id = $1 OR subject_tenant_id = $1) \
+                 AND subject_type = '{}' AND subject_id = $2",
+                SubjectType::User.as_str()
+            ),
+        ),
+        (
+            Tally::GroupMemberships,
+            "bookmark_group_members WHERE tenant_id = $1 AND user_id = $2".to_string(),
+        ),
+        (
+            Tally::Flags,
+            "bookmark_user_flags WHERE tenant_id = $1 AND user_id = $2".to_string(),
+        ),
+        (
+            Tally::AccessRequests,
+            "bookmark_access_requests WHERE tenant_id = $1 AND requester_id = $2".to_string(),
+        ),
+        (
+            Tally::Uncounted,
+            "bookmark_share_notifications WHERE tenant_id = $1 AND recipient_id = $2".to_string(),
+        ),
+        (
+            Tally::Uncounted,
+            "bookmark_idempotency_keys WHERE tenant_id = $1 AND user_id = $2".to_string(),
+        ),
+        (
+            Tally::Uncounted,
+            "bookmark_saved_searches WHERE tenant_id = $1 AND user_id = $2".to_string(),
+        ),
+    ];
+    steps.extend(
+        deletes
+            .into_iter()
+            .map(|(tally, rest)| (tally, format!("DELETE FROM {rest}"))),
+    );
+
+    steps.push((
+        Tally::AuditEvents,
+        format!(
+            "UPDATE bookmark_audit_events SET user_id = '{ERASED_USER}' \
+             WHERE tenant_id = $1 AND user_id = $2"
+        ),
+    ));
+
+    // Integer columns are cleared; text columns that must be set are overwritten.
+    let references = [
+        ("bookmark_permissions", "granted_by", None),
+        ("bookmark_revisions", "edited_by", None),
+        ("bookmark_group_members", "added_by", None),
+        ("bookmark_access_requests", "resolved_by", Some(ERASED_USER)),
+        (
+            "bookmark_share_notifications",
+            "shared_by",
+            Some(ERASED_USER),
+        ),
+        ("bookmark_api_keys", "created_by", Some(ERASED_USER)),
+        ("bookmark_short_links", "created_by", Some(ERASED_USER)),
+        (
+            "bookmark_permission_templates",
+            "created_by",
+            Some(ERASED_USER),
+        ),
+        ("bookmark_auto_tag_rules", "created_by", Some(ERASED_USER)),
+        ("bookmark_tags", "created_by", Some(ERASED_USER)),
+    ];
+    steps.extend(references.into_iter().map(|(table, column, value)| {
+        let value = value.map_or("NULL".to_string(), |v| format!("'{v}'"));
+        (
+            Tally::References,
+            format!(
+                "UPDATE {table} SET {column} = {value} \
+                 WHERE tenant_id = $1 AND CAST({column} AS TEXT) = $2"
+            ),
+        )
+    }));
+
+    steps
+}
+
+/// Reads and erases everything stored about one user, for data-subject
+/// access and erasure requests.
+#[tonic::async_trait]
+pub trait UserDataStore: Send + Sync {
+    /// A page of the user's records of one kind, each row as a JSON object
+    /// keyed by column name.
+    async fn user_records(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        kind: UserRecordKind,
+        offset: i64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<serde_json::Value>>;
+
+    /// Erase the user from a tenant in one transaction: deal with the
+    /// bookmarks they created per `disposition`, delete their tuples,
+    /// memberships, flags, requests and saved searches, and anonymize every
+    /// other reference to them, audit events included.
+    async fn erase_user(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        disposition: BookmarkDisposition,
+    ) -> anyhow::Result<ErasureSummary>;
+}
+
+#[derive(Clone)]
+pub struct UserDataRepo {
+    pool: PgPool,
+}
+
+impl UserDataRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl UserDataStore for UserDataRepo {
+    async fn user_records(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        kind: UserRecordKind,
+        offset: i64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<serde_json::Value>> {
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(&format!(
+            "SELECT to_jsonb(t) {} LIMIT $3 OFFSET $4",
+            kind.source()
+        ))
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(record,)| record).collect())
+    }
+
+    async fn erase_user(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        disposition: BookmarkDisposition,
+    ) -> anyhow::Result<ErasureSummary> {
+        let mut tx = self.pool.begin().await?;
+        let mut summary = ErasureSummary::default();
+
+        if let BookmarkDisposition::Reassign(new_owner) = disposition {
+            summary.bookmarks_reassigned =
+                reassign_in(&mut tx, tenant_id, user_id, new_owner).await?;
+        }
+        for (tally, sql) in erase_steps(disposition) {
+            let result = sqlx::query(&sql)
+                .bind(tenant_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+            summary.add(tally, result.rows_affected());
+        }
+
+        tx.commit().await?;
+        Ok(summary)
+    }
+}
+
+/// Give `new_owner` an owner tuple on, and the authorship of, every bookmark
+/// the user created. Returns the number of bookmarks reassigned.
+async fn reassign_in(
+    conn: &mut PgConnection,
+    tenant_id: i32,
+    user_id: &str,
+    new_owner: i32,
+) -> anyhow::Result<u64> {
+    sqlx::query(
+        r#"
+        INSERT INTO bookmark_permissions
+            (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id)
+        SELECT tenant_id, $3, CAST(id AS TEXT), $4, $5, $6
+        FROM bookmark_bookmarks
+        WHERE tenant_id = $1 AND CAST(created_by AS TEXT) = $2
+        ON CONFLICT (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id,
+            COALESCE(subject_tenant_id, 0)) DO NOTHING
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(user_id)
+    .bind(ResourceType::Bookmark.as_str())
+    .bind(Relation::Owner.code())
+    .bind(SubjectType::User.as_str())
+    .bind(new_owner.to_string())
+    .execute(&mut *conn)
+    .await?;
+
+    let result = sqlx::query(
+        "UPDATE bookmark_bookmarks SET created_by = $3, update_time = NOW() \
+         WHERE tenant_id = $1 AND CAST(created_by AS TEXT) = $2",
+    )
+    .bind(tenant_id)
+    .bind(user_id)
+    .bind(new_owner)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
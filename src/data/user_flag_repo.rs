@@ -0,0 +1,249 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Where a user is with a bookmark. Bookmarks without a flags row are unread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadingStatus {
+    Unread,
+    Reading,
+    Done,
+    Archived,
+}
+
+impl ReadingStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unread => "READING_STATUS_UNREAD",
+            Self::Reading => "READING_STATUS_READING",
+            Self::Done => "READING_STATUS_DONE",
+            Self::Archived => "READING_STATUS_ARCHIVED",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "READING_STATUS_UNREAD" => Some(Self::Unread),
+            "READING_STATUS_READING" => Some(Self::Reading),
+            "READING_STATUS_DONE" => Some(Self::Done),
+            "READING_STATUS_ARCHIVED" => Some(Self::Archived),
+            _ => None,
+        }
+    }
+
+    pub fn from_proto(v: i32) -> Option<Self> {
+        match v {
+            1 => Some(Self::Unread),
+            2 => Some(Self::Reading),
+            3 => Some(Self::Done),
+            4 => Some(Self::Archived),
+            _ => None,
+        }
+    }
+
+    pub fn to_proto(self) -> i32 {
+        match self {
+            Self::Unread => 1,
+            Self::Reading => 2,
+            Self::Done => 3,
+            Self::Archived => 4,
+        }
+    }
+}
+
+/// Per-user state on a bookmark that other viewers do not see.
+#[derive(Debug, sqlx::FromRow)]
+pub struct UserFlagRow {
+    pub tenant_id: i32,
+    pub user_id: String,
+    pub bookmark_id: Uuid,
+    pub is_pinned: bool,
+    pub pinned_at: Option<DateTime<Utc>>,
+    pub reading_status: String,
+    pub reading_status_time: Option<DateTime<Utc>>,
+    pub update_time: DateTime<Utc>,
+    /// Fractional-ranking key placing a pin in the user's manual order.
+    pub sort_order: Option<String>,
+}
+
+/// Per-user pin and reading-status storage.
+#[tonic::async_trait]
+pub trait UserFlagStore: Send + Sync {
+    /// The user's flags for the given bookmarks; bookmarks without flags are omitted.
+    async fn list_for_user(
+        &self,
+        user_id: &str,
+        bookmark_ids: &[Uuid],
+    ) -> anyhow::Result<Vec<UserFlagRow>>;
+
+    /// Unpinning also clears the pin's place in the manual order.
+    async fn set_pinned(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        bookmark_id: Uuid,
+        pinned: bool,
+    ) -> anyhow::Result<UserFlagRow>;
+
+    /// The user's pins in manual order: positioned pins by `sort_order`, then
+    /// the rest newest bookmark first.
+    async fn list_pins(&self, tenant_id: i32, user_id: &str) -> anyhow::Result<Vec<UserFlagRow>>;
+
+    /// Set `sort_order` on the user's pins in one statement, skipping rows no
+    /// longer pinned. Returns the number updated.
+    async fn set_sort_orders(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        orders: &[(Uuid, String)],
+    ) -> anyhow::Result<u64>;
+
+    async fn set_reading_status(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        bookmark_id: Uuid,
+        status: ReadingStatus,
+    ) -> anyhow::Result<UserFlagRow>;
+}
+
+#[derive(Clone)]
+pub struct UserFlagRepo {
+    pool: PgPool,
+}
+
+impl UserFlagRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[tonic::async_trait]
+impl UserFlagStore for UserFlagRepo {
+    async fn list_for_user(
+        &self,
+        user_id: &str,
+        bookmark_ids: &[Uuid],
+    ) -> anyhow::Result<Vec<UserFlagRow>> {
+        if bookmark_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let rows = sqlx::query_as::<_, UserFlagRow>(
+            "SELECT * FROM bookmark_user_flags WHERE user_id = $1 AND bookmark_id = ANY($2)",
+        )
+        .bind(user_id)
+        .bind(bookmark_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn set_pinned(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        bookmark_id: Uuid,
+        pinned: bool,
+    ) -> anyhow::Result<UserFlagRow> {
+        let row = sqlx::query_as::<_, UserFlagRow>(
+            r#"
+            INSERT INTO bookmark_user_flags (tenant_id, user_id, bookmark_id, is_pinned, pinned_at)
+            VALUES ($1, $2, $3, $4, CASE WHEN $4 THEN NOW() END)
+            ON CONFLICT (user_id, bookmark_id) DO UPDATE SET
+                is_pinned = EXCLUDED.is_pinned,
+                pinned_at = CASE
+                    WHEN NOT EXCLUDED.is_pinned THEN NULL
+                    ELSE COALESCE(bookmark_user_flags.pinned_at, NOW())
+                END,
+                sort_order = CASE
+                    WHEN EXCLUDED.is_pinned THEN bookmark_user_flags.sort_order
+                END,
+                update_time = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(bookmark_id)
+        .bind(pinned)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list_pins(&self, tenant_id: i32, user_id: &str) -> anyhow::Result<Vec<UserFlagRow>> {
+        let rows = sqlx::query_as::<_, UserFlagRow>(
+            r#"
+            SELECT f.* FROM bookmark_user_flags f
+            JOIN bookmark_bookmarks b ON b.id = f.bookmark_id
+            WHERE f.tenant_id = $1 AND f.user_id = $2 AND f.is_pinned
+            ORDER BY f.sort_order NULLS LAST, b.create_time DESC, b.id DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn set_sort_orders(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        orders: &[(Uuid, String)],
+    ) -> anyhow::Result<u64> {
+        let (ids, keys): (Vec<Uuid>, Vec<&str>) =
+            orders.iter().map(|(id, key)| (*id, key.as_str())).unzip();
+        let result = sqlx::query(
+            r#"
+            UPDATE bookmark_user_flags f
+            SET sort_order = o.sort_order, update_time = NOW()
+            FROM UNNEST($3::UUID[], $4::TEXT[]) AS o(bookmark_id, sort_order)
+            WHERE f.tenant_id = $1 AND f.user_id = $2
+              AND f.bookmark_id = o.bookmark_id AND f.is_pinned
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(&ids)
+        .bind(&keys)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn set_reading_status(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        bookmark_id: Uuid,
+        status: ReadingStatus,
+    ) -> anyhow::Result<UserFlagRow> {
+        let row = sqlx::query_as::<_, UserFlagRow>(
+            r#"
+            INSERT INTO bookmark_user_flags
+                (tenant_id, user_id, bookmark_id, reading_status, reading_status_time)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (user_id, bookmark_id) DO UPDATE SET
+                reading_status = EXCLUDED.reading_status,
+                reading_status_time = NOW(),
+                update_time = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(user_id)
+        .bind(bookmark_id)
+        .bind(status.as_str())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+}
@@ -0,0 +1,299 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+use crate::config::EventsConfig;
+use crate::data::bookmark_repo::BookmarkRow;
+use crate::data::permission_repo::PermissionRow;
+
+/// Events buffered for each in-process subscriber before it is considered lagging.
+const LOCAL_CAPACITY: usize = 1024;
+
+/// Domain events other platform modules can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventType {
+    BookmarkCreated,
+    BookmarkUpdated,
+    BookmarkDeleted,
+    PermissionGranted,
+    PermissionRevoked,
+}
+
+impl EventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BookmarkCreated => "BookmarkCreated",
+            Self::BookmarkUpdated => "BookmarkUpdated",
+            Self::BookmarkDeleted => "BookmarkDeleted",
+            Self::PermissionGranted => "PermissionGranted",
+            Self::PermissionRevoked => "PermissionRevoked",
+        }
+    }
+
+    /// Suffix appended to the NATS subject prefix.
+    fn subject(&self) -> &'static str {
+        match self {
+            Self::BookmarkCreated => "bookmark.created",
+            Self::BookmarkUpdated => "bookmark.updated",
+            Self::BookmarkDeleted => "bookmark.deleted",
+            Self::PermissionGranted => "permission.granted",
+            Self::PermissionRevoked => "permission.revoked",
+        }
+    }
+}
+
+/// Envelope published for every event. `data` depends on the event type.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: &'static str,
+    pub tenant_id: i32,
+    pub actor_id: String,
+    pub occurred_at: String,
+    pub data: Value,
+    #[serde(skip)]
+    kind: EventType,
+    /// Partition key (the resource ID) so events for one resource stay ordered.
+    #[serde(skip)]
+    key: String,
+}
+
+impl Event {
+    fn new(event_type: EventType, tenant_id: i32, actor_id: &str, key: String, data: Value) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            event_type: event_type.as_str(),
+            tenant_id,
+            actor_id: actor_id.to_string(),
+            occurred_at: chrono::Utc::now().to_rfc3339(),
+            data,
+            kind: event_type,
+            key,
+        }
+    }
+
+    pub fn kind(&self) -> EventType {
+        self.kind
+    }
+
+    /// ID of the bookmark (or permission resource) the event is about.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// `BookmarkCreated` or `BookmarkUpdated` carrying the bookmark's current
+    /// state. A private bookmark's URL is left out.
+    pub fn bookmark(event_type: EventType, actor_id: &str, row: &BookmarkRow) -> Self {
+        let data = serde_json::json!({
+            "id": row.id.to_string(),
+            "url": (!row.is_private).then_some(&row.url),
+            "isPrivate": row.is_private,
+            "title": row.title,
+            "description": row.description,
+            "tags": row.tags,
+            "createdBy": row.created_by,
+            "createTime": row.create_time.to_rfc3339(),
+            "updateTime": row.update_time.to_rfc3339(),
+        });
+        Self::new(event_type, row.tenant_id, actor_id, row.id.to_string(), data)
+    }
+
+    /// A write another instance made, seen through the change feed. The actor
+    /// is unknown and `data` holds only what the notification carried.
+    pub fn from_change_feed(
+        event_type: EventType,
+        tenant_id: i32,
+        key: String,
+        data: Value,
+    ) -> Self {
+        Self::new(event_type, tenant_id, "", key, data)
+    }
+
+    pub fn bookmark_deleted(tenant_id: i32, actor_id: &str, id: &str) -> Self {
+        Self::new(
+            EventType::BookmarkDeleted,
+            tenant_id,
+            actor_id,
+            id.to_string(),
+            serde_json::json!({ "id": id }),
+        )
+    }
+
+    pub fn permission_granted(actor_id: &str, row: &PermissionRow) -> Self {
+        let data = serde_json::json!({
+            "resourceType": row.resource_type,
+            "resourceId": row.resource_id,
+            "relation": row.relation,
+            "subjectType": row.subject_type,
+            "subjectId": row.subject_id,
+            "subjectTenantId": row.subject_tenant_id,
+            "expiresAt": row.expires_at.map(|dt| dt.to_rfc3339()),
+        });
+        Self::new(
+            EventType::PermissionGranted,
+            row.tenant_id,
+            actor_id,
+            row.resource_id.clone(),
+            data,
+        )
+    }
+
+    /// `relation` is `None` when every relation of the subject was revoked.
+    pub fn permission_revoked(
+        tenant_id: i32,
+        actor_id: &str,
+        resource_type: &str,
+        resource_id: &str,
+        relation: Option<&str>,
+        subject_type: &str,
+        subject_id: &str,
+    ) -> Self {
+        let data = serde_json::json!({
+            "resourceType": resource_type,
+            "resourceId": resource_id,
+            "relation": relation,
+            "subjectType": subject_type,
+            "subjectId": subject_id,
+        });
+        Self::new(
+            EventType::PermissionRevoked,
+            tenant_id,
+            actor_id,
+            resource_id.to_string(),
+            data,
+        )
+    }
+}
+
+enum Sink {
+    Nats {
+        client: async_nats::Client,
+        prefix: String,
+    },
+    #[cfg(feature = "kafka")]
+    Kafka {
+        producer: rdkafka::producer::FutureProducer,
+        topic: String,
+    },
+}
+
+/// Publishes events to the configured broker and to in-process subscribers.
+/// Cheap to clone; a disabled publisher only reaches in-process subscribers.
+#[derive(Clone)]
+pub struct EventPublisher {
+    sink: Option<Arc<Sink>>,
+    local: broadcast::Sender<Arc<Event>>,
+}
+
+impl EventPublisher {
+    pub fn disabled() -> Self {
+        Self {
+            sink: None,
+            local: broadcast::channel(LOCAL_CAPACITY).0,
+        }
+    }
+
+    pub async fn connect(cfg: &EventsConfig) -> anyhow::Result<Self> {
+        if !cfg.enabled {
+            return Ok(Self::disabled());
+        }
+
+        let sink = match cfg.driver.as_str() {
+            "nats" => Sink::Nats {
+                client: async_nats::connect(cfg.url.as_str()).await?,
+                prefix: cfg.subject.clone(),
+            },
+            #[cfg(feature = "kafka")]
+            "kafka" => Sink::Kafka {
+                producer: rdkafka::ClientConfig::new()
+                    .set("bootstrap.servers", &cfg.url)
+                    .set("message.timeout.ms", "5000")
+                    .create()?,
+                topic: cfg.subject.clone(),
+            },
+            #[cfg(not(feature = "kafka"))]
+            "kafka" => anyhow::bail!("kafka event driver requires the `kafka` build feature"),
+            other => anyhow::bail!("unknown event driver {other:?}"),
+        };
+
+        Ok(Self {
+            sink: Some(Arc::new(sink)),
+            local: broadcast::channel(LOCAL_CAPACITY).0,
+        })
+    }
+
+    /// Receive every event published by this process from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<Event>> {
+        self.local.subscribe()
+    }
+
+    /// Deliver to in-process subscribers only, for events the instance that
+    /// raised them has already sent to the broker.
+    pub fn publish_local(&self, event: Event) {
+        let _ = self.local.send(Arc::new(event));
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    /// Publish in the background. Delivery failures are logged and never
+    /// surface to the request that produced the event.
+    pub fn publish(&self, event: Event) {
+        let event = Arc::new(event);
+        // Having no subscribers is not an error.
+        let _ = self.local.send(event.clone());
+        let Some(sink) = self.sink.clone() else {
+            return;
+        };
+
+        tokio::spawn(async move {
+            let payload = match serde_json::to_vec(&*event) {
+                Ok(p) => p,
+                Err(e) => {
+                    tracing::error!(error = %e, event_type = event.event_type, "failed to encode event");
+                    return;
+                }
+            };
+
+            let result: anyhow::Result<()> = match sink.as_ref() {
+                Sink::Nats { client, prefix } => {
+                    client
+                        .publish(format!("{prefix}.{}", event.kind.subject()), payload.into())
+                        .await
+                        .map_err(Into::into)
+                }
+                #[cfg(feature = "kafka")]
+                Sink::Kafka { producer, topic } => {
+                    let record = rdkafka::producer::FutureRecord::to(topic)
+                        .key(&event.key)
+                        .payload(&payload)
+                        .headers(rdkafka::message::OwnedHeaders::new().insert(
+                            rdkafka::message::Header {
+                                key: "event-type",
+                                value: Some(event.event_type),
+                            },
+                        ));
+                    producer
+                        .send(record, std::time::Duration::from_secs(0))
+                        .await
+                        .map(|_| ())
+                        .map_err(|(e, _)| e.into())
+                }
+            };
+
+            if let Err(e) = result {
+                tracing::warn!(
+                    error = %e,
+                    event_type = event.event_type,
+                    event_id = %event.id,
+                    "failed to publish event"
+                );
+            }
+        });
+    }
+}
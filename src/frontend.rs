@@ -1,19 +1,293 @@
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use axum::Router;
+use axum::body::Body;
+use axum::extract::{self, Request};
+use axum::handler::HandlerWithoutStateExt;
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use sha2::{Digest, Sha256};
+use tower::{Layer, ServiceExt};
 use tower_http::cors::CorsLayer;
-use tower_http::services::ServeDir;
+use tower_http::services::{ServeDir, ServeFile};
 
+use crate::config::{parse_duration, FrontendConfig};
+use crate::data::bookmark_repo::BookmarkStore;
+use crate::data::short_link_repo::ShortLinkStore;
+use crate::health::{HealthAggregator, HealthStatus, Readiness};
+
+/// Component name used in the health aggregator.
+const HEALTH_COMPONENT: &str = "frontend";
+/// Module Federation entry point loaded by the shell.
+const REMOTE_ENTRY: &str = "remoteEntry.js";
+/// Served for client-side routes that match no file.
+const INDEX_HTML: &str = "index.html";
+
+/// Content-hashed file names never change content, so caches may keep them.
+const IMMUTABLE_CACHE: &str = "public, max-age=31536000, immutable";
+/// Everything else (index.html, the remote entry) must be revalidated.
+const REVALIDATE_CACHE: &str = "no-cache";
+
+/// Stores behind the `/s/{tenant_id}/{code}` short-link redirect.
+#[derive(Clone)]
+pub struct ShortLinks {
+    pub links: Arc<dyn ShortLinkStore>,
+    pub bookmarks: Arc<dyn BookmarkStore>,
+}
+
+/// Serve probes and short links, plus the frontend assets when `dist_path` is set.
 pub async fn start_frontend_server(
     addr: SocketAddr,
-    dist_path: &str,
+    dist_path: Option<&str>,
+    health: HealthAggregator,
+    readiness: Readiness,
+    short_links: ShortLinks,
 ) -> Result<(), anyhow::Error> {
     let app = Router::new()
-        .fallback_service(ServeDir::new(dist_path))
-        .layer(CorsLayer::permissive());
+        .route("/health", get(move || health_handler(health.clone())))
+        .route("/healthz", get(liveness_handler))
+        .route("/readyz", get(move || readiness_handler(readiness.clone())))
+        .route(
+            "/s/{tenant_id}/{code}",
+            get(move |path| short_link_handler(short_links.clone(), path)),
+        );
+
+    // Prefer precompressed `.br`/`.gz` siblings, and serve index.html for
+    // unknown paths so deep links reach the SPA router.
+    let app = match dist_path {
+        Some(dist_path) => {
+            let index = Path::new(dist_path).join(INDEX_HTML);
+            let static_files = ServeDir::new(dist_path)
+                .precompressed_br()
+                .precompressed_gzip()
+                .fallback((move |req: Request| spa_fallback(index.clone(), req)).into_service());
+            app.fallback_service(middleware::from_fn(static_headers).layer(static_files))
+        }
+        None => app,
+    }
+    .layer(CorsLayer::permissive());
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
     tracing::info!("Frontend server listening on {}", addr);
     axum::serve(listener, app).await?;
     Ok(())
 }
+
+/// Serve `index.html` for client-side routes; missing files that look like
+/// assets (the last segment has an extension) stay 404.
+async fn spa_fallback(index: PathBuf, req: Request) -> Response {
+    let is_asset = req
+        .uri()
+        .path()
+        .rsplit('/')
+        .next()
+        .is_some_and(|name| name.contains('.'));
+    if is_asset || !matches!(*req.method(), Method::GET | Method::HEAD) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    match ServeFile::new(index)
+        .precompressed_br()
+        .precompressed_gzip()
+        .oneshot(req)
+        .await
+    {
+        Ok(resp) => resp.map(Body::new),
+        Err(e) => match e {},
+    }
+}
+
+/// Whether the file name carries a content hash, e.g. `index-4f9a1c2b.js` or
+/// `main.3b7e91d0c4.css`: the segment before the extension is at least eight
+/// word characters and contains a digit.
+fn is_hashed_asset(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let Some((stem, _ext)) = name.rsplit_once('.') else {
+        return false;
+    };
+    let hash = stem.rsplit(['-', '.']).next().unwrap_or_default();
+    stem.len() > hash.len()
+        && hash.len() >= 8
+        && hash.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+        && hash.bytes().any(|b| b.is_ascii_digit())
+}
+
+/// Add cache headers and a weak ETag to static responses, answering matching
+/// `If-None-Match` requests with 304.
+async fn static_headers(req: Request, next: Next) -> Response {
+    let immutable = is_hashed_asset(req.uri().path());
+    let if_none_match = req.headers().get(header::IF_NONE_MATCH).cloned();
+
+    let mut resp = next.run(req).await;
+    let status = resp.status();
+    if status != StatusCode::OK && status != StatusCode::NOT_MODIFIED {
+        return resp;
+    }
+
+    let headers = resp.headers_mut();
+    let cache_control = if immutable { IMMUTABLE_CACHE } else { REVALIDATE_CACHE };
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(cache_control));
+    headers.insert(header::VARY, HeaderValue::from_static("accept-encoding"));
+    let Some(etag) = weak_etag(headers) else {
+        return resp;
+    };
+    headers.insert(header::ETAG, etag.clone());
+
+    if if_none_match.is_some_and(|v| etag_matches(&v, &etag)) {
+        let mut not_modified = StatusCode::NOT_MODIFIED.into_response();
+        for name in [header::ETAG, header::CACHE_CONTROL, header::VARY, header::LAST_MODIFIED] {
+            if let Some(value) = resp.headers().get(&name) {
+                not_modified.headers_mut().insert(name, value.clone());
+            }
+        }
+        return not_modified;
+    }
+    resp
+}
+
+/// ETag derived from the file's modification time, size and encoding, so each
+/// precompressed variant gets its own tag.
+fn weak_etag(headers: &HeaderMap) -> Option<HeaderValue> {
+    let last_modified = headers.get(header::LAST_MODIFIED)?;
+    let mut hasher = Sha256::new();
+    for name in [header::CONTENT_LENGTH, header::CONTENT_ENCODING] {
+        hasher.update(headers.get(name).map_or(&[][..], |v| v.as_bytes()));
+        hasher.update([0]);
+    }
+    hasher.update(last_modified.as_bytes());
+    let digest = hasher.finalize();
+    let tag = u64::from_be_bytes(digest[..8].try_into().ok()?);
+    HeaderValue::try_from(format!("W/\"{tag:016x}\"")).ok()
+}
+
+/// Weak comparison against an `If-None-Match` list.
+fn etag_matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let Ok(list) = if_none_match.to_str() else {
+        return false;
+    };
+    let strip = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = strip(etag.to_str().unwrap_or_default());
+    list.split(',').any(|tag| tag.trim() == "*" || strip(tag) == etag)
+}
+
+async fn health_handler(health: HealthAggregator) -> Json<serde_json::Value> {
+    let (status, message) = health.overall();
+    let components: serde_json::Map<String, serde_json::Value> = health
+        .snapshot()
+        .into_iter()
+        .map(|(name, c)| {
+            (
+                name,
+                serde_json::json!({
+                    "status": c.status.as_str(),
+                    "message": c.message,
+                    "checkedAt": c.checked_at.to_rfc3339(),
+                }),
+            )
+        })
+        .collect();
+
+    Json(serde_json::json!({
+        "status": status.as_str(),
+        "message": message,
+        "components": components,
+    }))
+}
+
+/// Liveness: answering at all means the process is alive.
+async fn liveness_handler() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "alive" }))
+}
+
+/// Readiness: 503 with the reasons while the instance should not get traffic.
+async fn readiness_handler(readiness: Readiness) -> Response {
+    let problems = readiness.problems();
+    if problems.is_empty() {
+        return Json(serde_json::json!({ "status": "ready" })).into_response();
+    }
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(serde_json::json!({ "status": "not ready", "problems": problems })),
+    )
+        .into_response()
+}
+
+/// Count a visit and 302 to the bookmark's URL; 404 for unknown or revoked codes.
+async fn short_link_handler(
+    short_links: ShortLinks,
+    extract::Path((tenant_id, code)): extract::Path<(i32, String)>,
+) -> Response {
+    let (bookmark_id, url) = match short_links.links.resolve(tenant_id, &code).await {
+        Ok(Some(target)) => target,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            tracing::error!(tenant_id, code = %code, error = %e, "short link lookup failed");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let Ok(location) = HeaderValue::try_from(url) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    if let Err(e) = short_links.bookmarks.record_visit(tenant_id, bookmark_id).await {
+        tracing::warn!(bookmark_id = %bookmark_id, error = %e, "failed to record short link visit");
+    }
+    (StatusCode::FOUND, [(header::LOCATION, location)]).into_response()
+}
+
+/// Validate the dist directory: the remote entry must exist, and the manifest
+/// must match the configured hash when one is set.
+pub fn verify_assets(dist_path: &Path, cfg: &FrontendConfig) -> Result<(), String> {
+    if !dist_path.join(REMOTE_ENTRY).is_file() {
+        return Err(format!("{REMOTE_ENTRY} missing from {}", dist_path.display()));
+    }
+
+    let expected = cfg.expected_manifest_sha256.trim().to_lowercase();
+    if expected.is_empty() {
+        return Ok(());
+    }
+
+    let manifest_path = dist_path.join(&cfg.manifest_file);
+    let manifest = std::fs::read(&manifest_path)
+        .map_err(|e| format!("read {}: {e}", manifest_path.display()))?;
+    let actual = format!("{:x}", Sha256::digest(&manifest));
+
+    if actual != expected {
+        return Err(format!(
+            "{} hash mismatch: expected {expected}, got {actual}",
+            cfg.manifest_file
+        ));
+    }
+    Ok(())
+}
+
+/// Verify the frontend assets now and then periodically, reporting the result
+/// to the health aggregator so a bad deploy shows up as Degraded.
+pub fn spawn_asset_monitor(
+    dist_path: String,
+    cfg: FrontendConfig,
+    health: HealthAggregator,
+) -> tokio::task::JoinHandle<()> {
+    let interval = parse_duration(&cfg.check_interval).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "invalid frontend check_interval, using 60s");
+        std::time::Duration::from_secs(60)
+    });
+    let dist_path = PathBuf::from(dist_path);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match verify_assets(&dist_path, &cfg) {
+                Ok(()) => health.set(HEALTH_COMPONENT, HealthStatus::Healthy, "assets verified"),
+                Err(reason) => {
+                    tracing::warn!(reason = %reason, "frontend asset verification failed");
+                    health.set(HEALTH_COMPONENT, HealthStatus::Degraded, reason);
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::watch;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+
+use crate::data::db::Database;
+
+/// gRPC health service name that is serving for as long as the process runs.
+pub const LIVENESS_SERVICE: &str = "liveness";
+/// gRPC health service name that is serving only while the instance is ready;
+/// the overall (empty) service name follows it too.
+pub const READINESS_SERVICE: &str = "readiness";
+/// Path prefix of the gRPC health service, which probes call without credentials.
+pub const HEALTH_SERVICE_PREFIX: &str = "/grpc.health.v1.Health/";
+
+/// How often readiness is re-evaluated.
+const READINESS_INTERVAL: Duration = Duration::from_secs(5);
+/// A database check slower than this counts as a failure.
+const DATABASE_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+/// Component name used in the health aggregator.
+const DATABASE_COMPONENT: &str = "database";
+
+/// Health of a single component, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HealthStatus {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+impl HealthStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Degraded => "degraded",
+            Self::Unhealthy => "unhealthy",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ComponentHealth {
+    pub status: HealthStatus,
+    pub message: String,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Collects the latest health of each component; the module is only as healthy
+/// as its worst component.
+#[derive(Clone, Default)]
+pub struct HealthAggregator {
+    components: Arc<RwLock<HashMap<String, ComponentHealth>>>,
+}
+
+impl HealthAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, component: &str, status: HealthStatus, message: impl Into<String>) {
+        let message = message.into();
+        let mut components = self.components.write().unwrap_or_else(|e| e.into_inner());
+        let changed = components
+            .get(component)
+            .is_none_or(|c| c.status != status);
+        if changed {
+            tracing::info!(
+                component,
+                status = status.as_str(),
+                message = %message,
+                "component health changed"
+            );
+        }
+        components.insert(
+            component.to_string(),
+            ComponentHealth {
+                status,
+                message,
+                checked_at: Utc::now(),
+            },
+        );
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, ComponentHealth> {
+        self.components
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Worst status across all components, with a summary of the unhealthy ones.
+    pub fn overall(&self) -> (HealthStatus, String) {
+        let components = self.snapshot();
+        let status = components
+            .values()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(HealthStatus::Healthy);
+
+        let mut problems: Vec<String> = components
+            .iter()
+            .filter(|(_, c)| c.status != HealthStatus::Healthy)
+            .map(|(name, c)| format!("{name}: {}", c.message))
+            .collect();
+        problems.sort();
+
+        let message = if problems.is_empty() {
+            "Bookmark service is healthy".to_string()
+        } else {
+            problems.join("; ")
+        };
+        (status, message)
+    }
+}
+
+/// Whether this instance should receive traffic. Unlike liveness, readiness is
+/// lost while the database is unreachable or behind on migrations, and in
+/// maintenance mode.
+#[derive(Clone)]
+pub struct Readiness {
+    problems: Arc<RwLock<Vec<String>>>,
+}
+
+impl Readiness {
+    pub fn new() -> Self {
+        Self {
+            problems: Arc::new(RwLock::new(vec!["not checked yet".to_string()])),
+        }
+    }
+
+    /// Why the instance is not ready; empty when it is.
+    pub fn problems(&self) -> Vec<String> {
+        self.problems
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn set(&self, problems: Vec<String>) {
+        let mut current = self.problems.write().unwrap_or_else(|e| e.into_inner());
+        if *current != problems {
+            if problems.is_empty() {
+                tracing::info!("instance is ready");
+            } else {
+                tracing::warn!(problems = ?problems, "instance is not ready");
+            }
+        }
+        *current = problems;
+    }
+}
+
+/// Check readiness periodically, and right away when maintenance mode is toggled,
+/// publishing the result to `readiness`, the gRPC health service and the
+/// database component of `health`.
+pub fn spawn_readiness_probe(
+    db: Database,
+    mut maintenance: watch::Receiver<bool>,
+    readiness: Readiness,
+    mut reporter: HealthReporter,
+    health: HealthAggregator,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        reporter
+            .set_service_status(LIVENESS_SERVICE, ServingStatus::Serving)
+            .await;
+        let mut ticker = tokio::time::interval(READINESS_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                Ok(()) = maintenance.changed() => {}
+            }
+
+            let mut problems = Vec::new();
+            let check = tokio::time::timeout(DATABASE_CHECK_TIMEOUT, db.check_ready()).await;
+            let database = match check {
+                Ok(result) => result.map_err(|e| e.to_string()),
+                Err(_) => Err(format!("check timed out after {DATABASE_CHECK_TIMEOUT:?}")),
+            };
+            match database {
+                Ok(()) => health.set(DATABASE_COMPONENT, HealthStatus::Healthy, "ready"),
+                Err(reason) => {
+                    problems.push(format!("{DATABASE_COMPONENT}: {reason}"));
+                    health.set(DATABASE_COMPONENT, HealthStatus::Unhealthy, reason);
+                }
+            }
+            if *maintenance.borrow_and_update() {
+                problems.push("maintenance mode".to_string());
+            }
+
+            let status = if problems.is_empty() {
+                ServingStatus::Serving
+            } else {
+                ServingStatus::NotServing
+            };
+            for service in ["", READINESS_SERVICE] {
+                reporter.set_service_status(service, status).await;
+            }
+            readiness.set(problems);
+        }
+    })
+}
@@ -0,0 +1,182 @@
+//! Log output selection and rotating log files. Time-based rotation uses
+//! `tracing-appender`'s rolling appender; size-based rotation renames
+//! `<prefix>.log` to `<prefix>.1.log` (shifting older files up) once it would
+//! grow past `max_bytes`. Either way writes go through a background thread,
+//! so a slow disk does not stall request handling.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+use crate::config::LogFileConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogOutput {
+    Stdout,
+    File,
+    Both,
+}
+
+impl LogOutput {
+    pub fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "stdout" => Ok(Self::Stdout),
+            "file" => Ok(Self::File),
+            "both" => Ok(Self::Both),
+            other => anyhow::bail!("unknown log output {other:?}"),
+        }
+    }
+
+    pub fn to_stdout(self) -> bool {
+        matches!(self, Self::Stdout | Self::Both)
+    }
+
+    pub fn to_file(self) -> bool {
+        matches!(self, Self::File | Self::Both)
+    }
+}
+
+/// Time-based schedule for `rotation`, or `None` for size-based rotation.
+fn time_rotation(rotation: &str) -> anyhow::Result<Option<Rotation>> {
+    match rotation {
+        "minutely" => Ok(Some(Rotation::MINUTELY)),
+        "hourly" => Ok(Some(Rotation::HOURLY)),
+        "daily" => Ok(Some(Rotation::DAILY)),
+        "never" => Ok(Some(Rotation::NEVER)),
+        "size" => Ok(None),
+        other => anyhow::bail!("unknown rotation {other:?}"),
+    }
+}
+
+/// Check a file config without creating anything.
+pub fn validate(config: &LogFileConfig) -> anyhow::Result<()> {
+    if config.prefix.is_empty() {
+        anyhow::bail!("prefix must not be empty");
+    }
+    if time_rotation(&config.rotation)?.is_none() && config.max_bytes == 0 {
+        anyhow::bail!("max_bytes must be positive with size rotation");
+    }
+    Ok(())
+}
+
+/// Open the log file and start its writer thread. Lines buffered in the
+/// writer are flushed when the guard drops, so keep it alive until exit.
+pub fn writer(config: &LogFileConfig) -> anyhow::Result<(NonBlocking, WorkerGuard)> {
+    validate(config)?;
+    let writer = match time_rotation(&config.rotation)? {
+        Some(rotation) => tracing_appender::non_blocking(
+            RollingFileAppender::builder()
+                .rotation(rotation)
+                .filename_prefix(&config.prefix)
+                .filename_suffix("log")
+                .max_log_files(config.max_files + 1)
+                .build(&config.dir)?,
+        ),
+        None => tracing_appender::non_blocking(SizeRollingFile::open(
+            Path::new(&config.dir),
+            &config.prefix,
+            config.max_bytes,
+            config.max_files,
+        )?),
+    };
+    Ok(writer)
+}
+
+/// A file rolled over once it reaches a size limit. Each formatted event is one
+/// `write` call, so files split between lines.
+struct SizeRollingFile {
+    dir: PathBuf,
+    prefix: String,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    written: u64,
+}
+
+impl SizeRollingFile {
+    fn open(dir: &Path, prefix: &str, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let file = append(&rotated_path(dir, prefix, 0))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            prefix: prefix.to_string(),
+            max_bytes,
+            max_files,
+            written: file.metadata()?.len(),
+            file,
+        })
+    }
+
+    fn path(&self, n: usize) -> PathBuf {
+        rotated_path(&self.dir, &self.prefix, n)
+    }
+
+    fn roll(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        match fs::remove_file(self.path(self.max_files)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        for n in (0..self.max_files).rev() {
+            match fs::rename(self.path(n), self.path(n + 1)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        self.file = append(&self.path(0))?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+/// `<prefix>.log` for the current file, `<prefix>.<n>.log` for rotated ones.
+fn rotated_path(dir: &Path, prefix: &str, n: usize) -> PathBuf {
+    match n {
+        0 => dir.join(format!("{prefix}.log")),
+        n => dir.join(format!("{prefix}.{n}.log")),
+    }
+}
+
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+impl Write for SizeRollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.roll()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_rotation_keeps_max_files() {
+        let dir = std::env::temp_dir().join(format!("log-file-{}", uuid::Uuid::new_v4()));
+        let mut file = SizeRollingFile::open(&dir, "svc", 10, 2).unwrap();
+        for line in ["aaaaaa\n", "bbbbbb\n", "cccccc\n", "dddddd\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("svc.log"), "dddddd\n");
+        assert_eq!(read("svc.1.log"), "cccccc\n");
+        assert_eq!(read("svc.2.log"), "bbbbbb\n");
+        assert!(!dir.join("svc.3.log").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
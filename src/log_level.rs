@@ -0,0 +1,176 @@
+//! Runtime control of the log filter. The configured filter comes from
+//! `logger.yaml` (or `RUST_LOG`) and follows config reloads; an override set
+//! through `SetLogLevel` takes precedence until it expires or is cleared.
+
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Handle for swapping the log filter at runtime.
+pub type LogFilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Longest an override may stay in effect before reverting.
+pub const MAX_REVERT_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLevelStatus {
+    pub filter: String,
+    pub configured: String,
+    pub revert_time: Option<DateTime<Utc>>,
+}
+
+struct Override {
+    filter: String,
+    revert_time: Option<DateTime<Utc>>,
+}
+
+struct State {
+    configured: String,
+    active: Option<Override>,
+    /// Bumped on every change so a pending revert can tell it was superseded.
+    generation: u64,
+}
+
+/// Cheap to clone; clones share state.
+#[derive(Clone)]
+pub struct LogLevel {
+    handle: LogFilterHandle,
+    state: Arc<Mutex<State>>,
+}
+
+impl LogLevel {
+    pub fn new(handle: LogFilterHandle, configured: &str) -> Self {
+        Self {
+            handle,
+            state: Arc::new(Mutex::new(State {
+                configured: configured.to_string(),
+                active: None,
+                generation: 0,
+            })),
+        }
+    }
+
+    pub fn status(&self) -> LogLevelStatus {
+        let state = self.lock();
+        LogLevelStatus {
+            filter: state
+                .active
+                .as_ref()
+                .map_or(&state.configured, |o| &o.filter)
+                .clone(),
+            configured: state.configured.clone(),
+            revert_time: state.active.as_ref().and_then(|o| o.revert_time),
+        }
+    }
+
+    /// Apply `filter` until `revert_after` elapses, or indefinitely without one.
+    pub fn set_override(
+        &self,
+        filter: &str,
+        revert_after: Option<Duration>,
+    ) -> anyhow::Result<LogLevelStatus> {
+        let parsed = EnvFilter::try_new(filter)?;
+        let revert_time = revert_after
+            .map(|d| chrono::Duration::from_std(d).map(|d| Utc::now() + d))
+            .transpose()?;
+        let generation = {
+            let mut state = self.lock();
+            self.handle.reload(parsed)?;
+            state.generation += 1;
+            state.active = Some(Override {
+                filter: filter.to_string(),
+                revert_time,
+            });
+            state.generation
+        };
+        tracing::warn!(filter = %filter, revert_time = ?revert_time, "log filter overridden");
+
+        if let Some(delay) = revert_after {
+            let this = self.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                if let Err(e) = this.revert_if(Some(generation)) {
+                    tracing::warn!(error = %e, "failed to revert log filter override");
+                }
+            });
+        }
+        Ok(self.status())
+    }
+
+    /// Drop any override and go back to the configured filter.
+    pub fn clear_override(&self) -> anyhow::Result<LogLevelStatus> {
+        self.revert_if(None)?;
+        Ok(self.status())
+    }
+
+    /// Record a newly configured filter. An edit to the configured level wins
+    /// over an active override.
+    pub fn set_configured(&self, filter: &str) -> anyhow::Result<()> {
+        let parsed = EnvFilter::try_new(filter)?;
+        let mut state = self.lock();
+        if state.configured == filter {
+            return Ok(());
+        }
+        self.handle.reload(parsed)?;
+        tracing::info!(from = %state.configured, to = %filter, "log level reloaded");
+        state.configured = filter.to_string();
+        state.active = None;
+        state.generation += 1;
+        Ok(())
+    }
+
+    /// Restore the configured filter, unless `generation` is given and a later
+    /// change has superseded it.
+    fn revert_if(&self, generation: Option<u64>) -> anyhow::Result<()> {
+        let mut state = self.lock();
+        if state.active.is_none() || generation.is_some_and(|g| g != state.generation) {
+            return Ok(());
+        }
+        self.handle.reload(EnvFilter::try_new(&state.configured)?)?;
+        state.active = None;
+        state.generation += 1;
+        tracing::warn!(filter = %state.configured, "log filter override reverted");
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_level() -> (reload::Layer<EnvFilter, Registry>, LogLevel) {
+        let (layer, handle) = reload::Layer::new(EnvFilter::new("info"));
+        (layer, LogLevel::new(handle, "info"))
+    }
+
+    #[tokio::test]
+    async fn override_reverts_unless_superseded() {
+        let (_layer, level) = log_level();
+
+        level
+            .set_override("debug", Some(Duration::from_millis(20)))
+            .unwrap();
+        assert_eq!(level.status().filter, "debug");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(level.status().filter, "info");
+
+        level
+            .set_override("debug", Some(Duration::from_millis(20)))
+            .unwrap();
+        level.set_override("trace", None).unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let status = level.status();
+        assert_eq!(status.filter, "trace");
+        assert_eq!(status.revert_time, None);
+
+        level.set_configured("warn").unwrap();
+        assert_eq!(level.status().filter, "warn");
+        assert!(level.set_override("not a [filter", None).is_err());
+    }
+}
@@ -1,69 +1,315 @@
 #![allow(dead_code, clippy::result_large_err)]
 
 mod authz;
+mod backup;
 mod cert;
+mod change_feed;
+mod cli;
 mod client;
 mod config;
+mod config_watcher;
 mod data;
+mod events;
 mod frontend;
+mod health;
+mod log_file;
+mod log_level;
+mod metrics;
 mod middleware;
+mod redact;
 mod registration;
+mod seed;
 mod service;
+mod shutdown;
+mod telemetry;
 
 use std::net::SocketAddr;
-use std::path::Path;
+use std::sync::Arc;
 
+use clap::Parser;
 use tokio::signal;
-use tokio::sync::watch;
 use tonic::transport::Server;
 
+use crate::authz::cache::DecisionCache;
 use crate::authz::checker::Checker;
 use crate::authz::engine::Engine;
-use crate::config::{DataConfig, LoggerConfig, ServerConfig};
-use crate::data::bookmark_repo::BookmarkRepo;
-use crate::data::permission_repo::PermissionRepo;
+use crate::backup::storage::BackupStorage;
+use crate::config::{DataConfig, LoggerConfig, PolicyConfig, RegistrationConfig, ServerConfig};
+use crate::health::{HealthAggregator, Readiness};
+use crate::events::EventPublisher;
 use crate::client::admin_client::AdminClient;
+use crate::metrics::layer::MetricsLayer;
+use crate::metrics::slo::SloTracker;
+use crate::metrics::Metrics;
+use crate::service::bookmark_service::proto::bookmark_access_request_service_server::BookmarkAccessRequestServiceServer;
+use crate::service::bookmark_service::proto::bookmark_admin_service_server::BookmarkAdminServiceServer;
+use crate::service::bookmark_service::proto::bookmark_api_key_service_server::BookmarkApiKeyServiceServer;
+use crate::service::bookmark_service::proto::auto_tag_rule_service_server::AutoTagRuleServiceServer;
 use crate::service::bookmark_service::proto::backup_service_server::BackupServiceServer;
 use crate::service::bookmark_service::proto::bookmark_permission_service_server::BookmarkPermissionServiceServer;
+use crate::service::bookmark_service::proto::bookmark_permission_template_service_server::BookmarkPermissionTemplateServiceServer;
 use crate::service::bookmark_service::proto::bookmark_service_server::BookmarkServiceServer;
 use crate::service::bookmark_service::proto::bookmark_user_service_server::BookmarkUserServiceServer;
+use crate::service::bookmark_service::proto::saved_search_service_server::SavedSearchServiceServer;
+use crate::service::bookmark_service::proto::tag_service_server::TagServiceServer;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = cli::Cli::parse();
+    let command = cli.command.clone().unwrap_or(cli::Command::Serve);
+
     // 1. Load config
-    let config_dir = std::env::var("CONFIG_DIR").unwrap_or_else(|_| "configs".to_string());
+    let config_dir = cli.config_dir.as_path();
 
-    let logger_cfg: LoggerConfig =
-        config::load_config(Path::new(&config_dir).join("logger.yaml").as_ref())?;
-    let server_cfg: ServerConfig =
-        config::load_config(Path::new(&config_dir).join("server.yaml").as_ref())?;
-    let data_cfg: DataConfig =
-        config::load_config(Path::new(&config_dir).join("data.yaml").as_ref())?;
+    let logger_cfg: LoggerConfig = config::load_config(&config_dir.join("logger.yaml"))?;
+    let server_cfg: ServerConfig = config::load_config(&config_dir.join("server.yaml"))?;
+    let data_cfg: DataConfig = config::load_config(&config_dir.join("data.yaml"))?;
+    let policy_path = config_dir.join("policy.yaml");
+    let policy_cfg: PolicyConfig = if policy_path.exists() {
+        config::load_config(&policy_path)?
+    } else {
+        PolicyConfig::default()
+    };
+    let registration_path = config_dir.join("registration.yaml");
+    let registration_cfg: RegistrationConfig = if registration_path.exists() {
+        config::load_config(&registration_path)?
+    } else {
+        RegistrationConfig::default()
+    };
+    if let cli::Command::CheckConfig = command {
+        return cli::check_config(
+            &logger_cfg,
+            &server_cfg,
+            &data_cfg,
+            &policy_cfg,
+            &registration_cfg,
+        );
+    }
 
     // 2. Init tracing/logging
-    init_tracing(&logger_cfg.logger);
+    let (tracer_provider, log_level, _log_guard) = init_tracing(&logger_cfg.logger)?;
+    let config_watcher = config_watcher::ConfigWatcher::new(
+        config_dir,
+        &logger_cfg,
+        &server_cfg,
+        log_level.clone(),
+    );
     tracing::info!("starting bookmark service v1.0.0");
 
     // 3. Load mTLS certs (optional)
     let tls_config = cert::load_tls_config();
 
-    // 4. Create DB pool, run migrations
-    let pool = data::db::create_pool(&data_cfg).await?;
-    data::db::run_migrations(&pool).await?;
+    // 4. Create DB pool, run migrations. While serving, statements are bounded
+    // (by default by the request timeout); one-off commands may run as long as
+    // they need.
+    let request_timeout = config::parse_duration(&server_cfg.server.grpc.timeout)?;
+    let drain_timeout = config::parse_duration(&server_cfg.server.shutdown.drain_timeout)?;
+    let statement_timeout = match data_cfg.data.database.statement_timeout.as_str() {
+        "" => request_timeout,
+        timeout => config::parse_duration(timeout)?,
+    };
+    let statement_timeout = (matches!(command, cli::Command::Serve) && !statement_timeout.is_zero())
+        .then_some(statement_timeout);
+    let db = data::db::create_pool(&data_cfg, statement_timeout).await?;
+    db.run_migrations(cli.allow_unsafe_migrations).await?;
+
+    match command {
+        cli::Command::Migrate => {
+            tracing::info!("migrations applied");
+            return Ok(());
+        }
+        cli::Command::ExportBackup(args) => {
+            return cli::export_backup(&db, &server_cfg, &data_cfg, args).await
+        }
+        cli::Command::ImportBackup(args) => {
+            return cli::import_backup(&db, &server_cfg, &data_cfg, args).await
+        }
+        cli::Command::Seed(args) => return seed::seed_file(&db.stores(), &args.file).await,
+        cli::Command::SplitTags(args) => return cli::split_tags(&db, args).await,
+        cli::Command::Serve | cli::Command::CheckConfig => {}
+    }
 
     // 5. Create repos, authz engine, services
-    let bookmark_repo = BookmarkRepo::new(pool.clone());
-    let permission_repo = PermissionRepo::new(pool.clone());
-    let engine = Engine::new(permission_repo);
+    let stores = db.stores();
+    if let Some(path) = std::env::var_os("SEED_DATA_PATH") {
+        seed::seed_file(&stores, path.as_ref()).await?;
+    }
+    match stores.bookmarks.backfill_normalized_urls().await {
+        Ok(0) => {}
+        Ok(n) => tracing::info!(rows = n, "backfilled normalized bookmark URLs"),
+        Err(e) => tracing::warn!(error = %e, "failed to backfill normalized bookmark URLs"),
+    }
+    match stores.bookmarks.backfill_kinds().await {
+        Ok(0) => {}
+        Ok(n) => tracing::info!(rows = n, "backfilled bookmark kinds"),
+        Err(e) => tracing::warn!(error = %e, "failed to backfill bookmark kinds"),
+    }
+    match stores.permissions.backfill_relation_codes().await {
+        Ok(0) => {}
+        Ok(n) => tracing::info!(rows = n, "backfilled permission relation codes"),
+        Err(e) => tracing::warn!(error = %e, "failed to backfill permission relation codes"),
+    }
+    let engine = Engine::new(
+        stores.permissions.clone(),
+        stores.groups.clone(),
+        DecisionCache::new(&data_cfg.data.authz_cache),
+    )
+    .with_federation(server_cfg.server.sharing.federation);
     let checker = Checker::new(engine);
+    let mut jobs = shutdown::Jobs::new();
+    jobs.add(
+        "pool_metrics",
+        data::pool_metrics::spawn_pool_metrics(
+            db.pools(),
+            config::parse_duration(&data_cfg.data.database.pool_metrics_interval)?,
+            jobs.signal(),
+        ),
+    );
+    let breaker_cfg = &data_cfg.data.database.circuit_breaker;
+    let breaker = if breaker_cfg.enabled {
+        let breaker = data::breaker::DbBreaker::new(
+            breaker_cfg.failure_threshold,
+            config::parse_duration(&breaker_cfg.probe_interval)?,
+        );
+        let (_, primary) = db.pools().swap_remove(0);
+        jobs.add("db_breaker_probe", breaker.spawn_probe(primary, jobs.signal()));
+        data::breaker::install(breaker.clone());
+        Some(breaker)
+    } else {
+        None
+    };
+    if let Some(purge) = authz::purge::spawn_expired_permission_purge(
+        checker.engine().store().clone(),
+        &data_cfg.data.permission_purge,
+        jobs.signal(),
+    ) {
+        jobs.add("permission_purge", purge);
+    }
+
+    let events = match EventPublisher::connect(&data_cfg.data.events).await {
+        Ok(p) => {
+            if p.is_enabled() {
+                tracing::info!(driver = %data_cfg.data.events.driver, "event publisher connected");
+            }
+            p
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to connect event publisher, events will not be published");
+            EventPublisher::disabled()
+        }
+    };
+    if data_cfg.data.change_feed.enabled {
+        match db.postgres() {
+            Some(pool) => {
+                let feed = change_feed::spawn_change_feed(
+                    pool,
+                    checker.engine().cache().clone(),
+                    events.clone(),
+                    jobs.signal(),
+                )
+                .await?;
+                jobs.add("change_feed", feed);
+                tracing::info!("change feed listening");
+            }
+            None => tracing::warn!("change_feed needs the postgresql driver, ignoring it"),
+        }
+    }
 
+    let idempotency = service::idempotency::Idempotency::new(
+        stores.idempotency.clone(),
+        &data_cfg.data.idempotency,
+    )
+    .with_lease(request_timeout);
+    jobs.add(
+        "idempotency_purge",
+        service::idempotency::spawn_expired_key_purge(
+            stores.idempotency.clone(),
+            &data_cfg.data.idempotency,
+            jobs.signal(),
+        ),
+    );
+
+    let metadata_fetcher =
+        service::page_metadata::MetadataFetcher::new(&server_cfg.server.metadata_fetch)?;
+    server_cfg.server.search.validate()?;
+    let url_policy = service::url_policy::UrlPolicy::new(&server_cfg.server.url_policy);
+    let field_cipher = service::field_cipher::FieldCipher::new(
+        service::field_cipher::key_wrapper_from_config(&data_cfg.data.field_encryption)?,
+        stores.field_keys.clone(),
+    );
     let bookmark_svc = service::bookmark_service::BookmarkServiceImpl::new(
-        bookmark_repo,
+        stores.bookmarks.clone(),
         checker.clone(),
+        stores.scrub_policies.clone(),
+        metadata_fetcher.clone(),
+        service::archiver::Archiver::new(
+            stores.archives.clone(),
+            metadata_fetcher,
+            &server_cfg.server.archive,
+            config_watcher.archive_enabled(),
+        )?,
+        stores.user_flags.clone(),
+        stores.short_links.clone(),
+        stores.changes.clone(),
+        stores.auto_tag_rules.clone(),
+        idempotency.clone(),
+        events.clone(),
+        url_policy.clone(),
+        field_cipher.clone(),
+    )
+    .with_fuzzy_threshold(server_cfg.server.search.fuzzy_threshold);
+    let auto_tag_svc = service::auto_tag_rule_service::AutoTagRuleServiceImpl::new(
+        stores.auto_tag_rules.clone(),
+        stores.bookmarks.clone(),
+        events.clone(),
     );
-    let permission_svc =
-        service::permission_service::PermissionServiceImpl::new(checker.clone());
-    let backup_svc = service::backup_service::BackupServiceImpl::new(pool.clone());
+    let tag_svc = service::tag_service::TagServiceImpl::new(stores.tags.clone());
+    let saved_searches = &server_cfg.server.search.saved;
+    let saved_search_svc = service::saved_search_service::SavedSearchServiceImpl::new(
+        stores.saved_searches.clone(),
+        saved_searches.max_per_user,
+        config::parse_duration(&saved_searches.min_alert_interval)?,
+        config::parse_duration(&saved_searches.default_alert_interval)?,
+    );
+    // Backups stream Postgres-specific SQL and are not offered on SQLite.
+    let backup_storage = BackupStorage::from_config(&data_cfg.data.backup_storage)?;
+    let backup_svc = db
+        .postgres()
+        .cloned()
+        .map(|pool| {
+            service::backup_service::BackupServiceImpl::new(
+                pool,
+                idempotency.clone(),
+                checker.engine().cache().clone(),
+                backup_storage,
+                field_cipher.clone(),
+                url_policy.clone(),
+            )
+        });
+
+    let slo_tracker = Arc::new(SloTracker::new(&server_cfg.server.slo));
+    let metrics = Metrics::new().with_observer(slo_tracker.clone());
+
+    let (audit_log, audit_writer) =
+        service::audit_log::AuditLog::spawn(stores.audit.clone(), &data_cfg.data.audit);
+    if let Some(writer) = audit_writer {
+        jobs.add("audit_writer", writer);
+    }
+    if let Some(retention) = service::audit_log::spawn_audit_retention(
+        stores.audit.clone(),
+        &data_cfg.data.audit,
+        jobs.signal(),
+    ) {
+        jobs.add("audit_retention", retention);
+    }
+    if let Some(rollup) = service::usage_stats::spawn_daily_stats_rollup(
+        stores.stats.clone(),
+        &data_cfg.data.usage_stats,
+        jobs.signal(),
+    ) {
+        jobs.add("daily_stats_rollup", rollup);
+    }
+    let audit = middleware::audit::AuditLayer::new(audit_log);
 
     // 5b. Create admin client for user/role listing
     let admin_endpoint =
@@ -78,31 +324,143 @@ async fn main() -> anyhow::Result<()> {
             None
         }
     };
-    let user_svc = admin_client
-        .map(|c| service::user_service::UserServiceImpl::new(c));
+    let admin_svc = service::admin_service::AdminServiceImpl::new(
+        slo_tracker,
+        stores.audit.clone(),
+        stores.stats.clone(),
+        stores.permissions.clone(),
+        stores.groups.clone(),
+        admin_client.clone(),
+        stores.user_data.clone(),
+        field_cipher.clone(),
+        checker.engine().cache().clone(),
+        log_level,
+    );
+    let sharing = &server_cfg.server.sharing;
+    let notifier =
+        service::share_notifier::notifier_from_config(&sharing.notifier, admin_client.clone())?;
+    let share_notifications = service::share_notifier::ShareNotifications::new(
+        stores.share_notifications.clone(),
+        notifier.clone(),
+        &sharing.notifier,
+    );
+    jobs.add(
+        "share_notification_retry",
+        share_notifications.clone().spawn_retry(jobs.signal()),
+    );
+    if saved_searches.alerts_enabled {
+        let alerts = service::saved_search_alerts::SavedSearchAlerts::new(
+            stores.saved_searches.clone(),
+            stores.bookmarks.clone(),
+            checker.clone(),
+            notifier.clone(),
+            saved_searches,
+        );
+        jobs.add("saved_search_alerts", alerts.spawn(jobs.signal()));
+    }
+    let share_expiry = config::parse_duration(&sharing.default_expiry)?;
+    let permission_svc = service::permission_service::PermissionServiceImpl::new(
+        checker.clone(),
+        admin_client.clone(),
+        events.clone(),
+        idempotency,
+        share_notifications,
+        chrono::Duration::from_std(share_expiry)?,
+    );
+    let template_svc = service::permission_template_service::PermissionTemplateServiceImpl::new(
+        stores.permission_templates.clone(),
+        checker.clone(),
+        events.clone(),
+    );
+    let access_request_svc = service::access_request_service::AccessRequestServiceImpl::new(
+        stores.access_requests.clone(),
+        stores.bookmarks.clone(),
+        checker.clone(),
+        notifier,
+        events,
+    );
+    let user_svc = admin_client.map(service::user_service::UserServiceImpl::new);
+
+    let health = HealthAggregator::new();
+    let readiness = Readiness::new();
+    let (health_reporter, health_svc) = tonic_health::server::health_reporter();
+    health::spawn_readiness_probe(
+        db.clone(),
+        config_watcher.maintenance(),
+        readiness.clone(),
+        health_reporter,
+        health.clone(),
+    );
 
-    // 6. Start frontend HTTP server (serves Module Federation assets)
+    // 6. Start the HTTP server: probes, short links and Module Federation assets
     let frontend_dist = std::env::var("FRONTEND_DIST_PATH")
         .unwrap_or_else(|_| "/app/frontend-dist".to_string());
-    if std::path::Path::new(&frontend_dist).exists() {
-        let frontend_addr: SocketAddr = server_cfg
-            .server
-            .http
-            .as_ref()
-            .map(|h| h.addr.as_str())
-            .unwrap_or("0.0.0.0:9701")
-            .parse()?;
-        let dist_path = frontend_dist.clone();
-        tokio::spawn(async move {
-            if let Err(e) = frontend::start_frontend_server(frontend_addr, &dist_path).await {
-                tracing::error!(error = %e, "Frontend server failed");
-            }
-        });
+    let dist_path = std::path::Path::new(&frontend_dist)
+        .exists()
+        .then(|| frontend_dist.clone());
+    if dist_path.is_some() {
+        frontend::spawn_asset_monitor(
+            frontend_dist.clone(),
+            server_cfg.server.frontend.clone(),
+            health.clone(),
+        );
         tracing::info!(path = %frontend_dist, "Frontend serving static files");
     } else {
-        tracing::info!(path = %frontend_dist, "No frontend dist directory found, skipping frontend server");
+        tracing::info!(path = %frontend_dist, "No frontend dist directory found, serving probes and short links only");
     }
 
+    let frontend_addr: SocketAddr = server_cfg
+        .server
+        .http
+        .as_ref()
+        .map(|h| h.addr.as_str())
+        .unwrap_or("0.0.0.0:9701")
+        .parse()?;
+    let frontend_health = health.clone();
+    let short_links = frontend::ShortLinks {
+        links: stores.short_links.clone(),
+        bookmarks: stores.bookmarks.clone(),
+    };
+    tokio::spawn(async move {
+        if let Err(e) = frontend::start_frontend_server(
+            frontend_addr,
+            dist_path.as_deref(),
+            frontend_health,
+            readiness,
+            short_links,
+        )
+        .await
+        {
+            tracing::error!(error = %e, "Frontend server failed");
+        }
+    });
+
+    let method_policy = middleware::policy::MethodPolicy::from_config(&policy_cfg.policy)?;
+    let api_key_cfg = &server_cfg.server.api_keys;
+    let jwt_layer = if server_cfg.server.jwt.enabled {
+        let validator = middleware::jwt::JwtValidator::start(&server_cfg.server.jwt).await?;
+        tracing::info!(
+            jwks_url = %server_cfg.server.jwt.jwks_url,
+            required = server_cfg.server.jwt.required,
+            "JWT validation enabled"
+        );
+        Some(middleware::jwt::JwtLayer::new(validator, api_key_cfg.enabled))
+    } else {
+        None
+    };
+
+    let api_key_auth = middleware::api_key::ApiKeyAuthenticator::new(
+        stores.api_keys.clone(),
+        config::parse_duration(&api_key_cfg.cache_ttl)?,
+    );
+    let api_key_svc = service::api_key_service::ApiKeyServiceImpl::new(
+        stores.api_keys.clone(),
+        api_key_auth.clone(),
+    );
+    let api_key_layer = api_key_cfg
+        .enabled
+        .then(|| middleware::api_key::ApiKeyLayer::new(api_key_auth));
+
     // 7. Build tonic server
     let addr: SocketAddr = server_cfg.server.grpc.addr.parse()?;
 
@@ -116,62 +474,217 @@ async fn main() -> anyhow::Result<()> {
         tracing::warn!("running without mTLS");
     }
 
+    // Message size limits and compression, applied to every generated server.
+    let grpc_cfg = &server_cfg.server.grpc;
+    let accept_compression = grpc_cfg
+        .compression
+        .accept
+        .iter()
+        .map(|name| config::parse_compression(name))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let send_compression = grpc_cfg
+        .compression
+        .send
+        .iter()
+        .map(|name| config::parse_compression(name))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    macro_rules! configure_grpc {
+        ($server:expr) => {{
+            let mut server = $server
+                .max_decoding_message_size(grpc_cfg.max_decoding_message_size)
+                .max_encoding_message_size(grpc_cfg.max_encoding_message_size);
+            for &encoding in &accept_compression {
+                server = server.accept_compressed(encoding);
+            }
+            for &encoding in &send_compression {
+                server = server.send_compressed(encoding);
+            }
+            server
+        }};
+    }
+
+    // gRPC-Web lets the Module Federation frontend call the services from the
+    // browser; both layers pass native gRPC through untouched.
+    let web_cfg = &server_cfg.server.grpc.web;
+    if web_cfg.enabled {
+        tracing::info!(origins = ?web_cfg.allowed_origins, "gRPC-Web enabled");
+    }
+    middleware::panic::install_hook();
+    let in_flight = middleware::drain::InFlight::new();
     let mut router = server
-        .add_service(BookmarkServiceServer::with_interceptor(
-            bookmark_svc,
-            middleware::audit::audit_interceptor,
+        .accept_http1(web_cfg.enabled)
+        .layer(tower::util::option_layer(
+            web_cfg
+                .enabled
+                .then(|| middleware::grpc_web::cors_layer(web_cfg)),
         ))
-        .add_service(BookmarkPermissionServiceServer::with_interceptor(
-            permission_svc,
-            middleware::audit::audit_interceptor,
+        .layer(tower::util::option_layer(
+            web_cfg.enabled.then(tonic_web::GrpcWebLayer::new),
         ))
-        .add_service(BackupServiceServer::new(backup_svc));
+        .layer(tower::util::option_layer(jwt_layer))
+        .layer(tower::util::option_layer(api_key_layer))
+        .layer(middleware::trace::TraceLayer)
+        .layer(middleware::drain::InFlightLayer::new(in_flight.clone()))
+        .layer(MetricsLayer::new(metrics))
+        .layer(middleware::panic::CatchPanicLayer)
+        .layer(middleware::deadline::DeadlineLayer::new(request_timeout))
+        .layer(middleware::rate_limit::RateLimitLayer::new(
+            config_watcher.rate_limit(),
+        ))
+        .layer(middleware::concurrency::ConcurrencyLimitLayer::new(
+            &server_cfg.server.concurrency_limit,
+        )?)
+        .layer(middleware::db_breaker::DbBreakerLayer::new(breaker))
+        .layer(middleware::mtls::MtlsLayer::new(&server_cfg.server.mtls))
+        .layer(audit)
+        .layer(middleware::policy::PolicyLayer::new(method_policy))
+        .add_service(health_svc)
+        .add_service(configure_grpc!(BookmarkServiceServer::new(bookmark_svc)))
+        .add_service(configure_grpc!(BookmarkPermissionServiceServer::new(permission_svc)))
+        .add_service(configure_grpc!(BookmarkPermissionTemplateServiceServer::new(template_svc)))
+        .add_service(configure_grpc!(AutoTagRuleServiceServer::new(auto_tag_svc)))
+        .add_service(configure_grpc!(TagServiceServer::new(tag_svc)))
+        .add_service(configure_grpc!(SavedSearchServiceServer::new(saved_search_svc)))
+        .add_service(configure_grpc!(BookmarkAccessRequestServiceServer::new(access_request_svc)))
+        .add_optional_service(backup_svc.map(|svc| configure_grpc!(BackupServiceServer::new(svc))))
+        .add_service(configure_grpc!(BookmarkAdminServiceServer::new(admin_svc)))
+        .add_service(configure_grpc!(BookmarkApiKeyServiceServer::new(api_key_svc)));
 
-    if let Some(user_svc) = user_svc {
-        router = router.add_service(BookmarkUserServiceServer::with_interceptor(
-            user_svc,
-            middleware::audit::audit_interceptor,
-        ));
-    }
+    router = router.add_optional_service(
+        user_svc.map(|user_svc| configure_grpc!(BookmarkUserServiceServer::new(user_svc))),
+    );
+
+    config_watcher.spawn();
 
     // 9. Start registration background task
-    let (shutdown_tx, shutdown_rx) = watch::channel(false);
-    let reg_handle = registration::start_registration(shutdown_rx);
+    jobs.add(
+        "registration",
+        registration::start_registration(registration_cfg.registration, jobs.signal(), health),
+    );
 
-    // 10. Serve
+    // 10. Serve until a shutdown signal stops accepting new RPCs
     tracing::info!(addr = %addr, "gRPC server listening");
 
-    let graceful = router.serve_with_shutdown(addr, async {
-        shutdown_signal().await;
-        tracing::info!("shutdown signal received");
-    });
-
-    graceful.await?;
+    let mut stopping = jobs.signal();
+    let mut serve =
+        Box::pin(router.serve_with_shutdown(addr, async move { stopping.recv().await }));
+    tokio::select! {
+        result = &mut serve => result?,
+        _ = shutdown_signal() => {}
+    }
 
-    // 11. Graceful shutdown: unregister, drain connections
-    let _ = shutdown_tx.send(true);
-    let _ = reg_handle.await;
+    // 11. Graceful shutdown: in-flight RPCs and background jobs (including
+    // unregistration) share one drain deadline, then are cancelled.
+    tracing::info!(drain_timeout = ?drain_timeout, "shutdown signal received, draining");
+    let deadline = tokio::time::Instant::now() + drain_timeout;
+    jobs.stop();
+    match tokio::time::timeout_at(deadline, &mut serve).await {
+        Ok(result) => result?,
+        Err(_) => tracing::warn!(
+            rpcs = ?in_flight.snapshot(),
+            "drain timeout elapsed, cancelled in-flight RPCs"
+        ),
+    }
+    // Dropping the server releases the audit log senders so the writer can flush.
+    drop(serve);
+    jobs.shutdown(deadline).await;
 
     tracing::info!("bookmark service stopped");
+
+    // Flush spans still buffered by the batch exporter.
+    if let Some(provider) = tracer_provider {
+        if let Err(e) = provider.shutdown() {
+            eprintln!("failed to flush traces: {e}");
+        }
+    }
     Ok(())
 }
 
-fn init_tracing(logger: &config::LoggerSection) {
-    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(&logger.level));
+fn init_tracing(
+    logger: &config::LoggerSection,
+) -> anyhow::Result<(
+    Option<opentelemetry_sdk::trace::TracerProvider>,
+    log_level::LogLevel,
+    Option<tracing_appender::non_blocking::WorkerGuard>,
+)> {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
 
-    match logger.format.as_str() {
-        "json" => {
-            tracing_subscriber::fmt()
-                .with_env_filter(filter)
-                .json()
-                .init();
-        }
-        _ => {
-            tracing_subscriber::fmt()
-                .with_env_filter(filter)
-                .init();
+    let output = log_file::LogOutput::from_str(&logger.output)?;
+    let redactor = if logger.redact_pii {
+        if logger.redact_key.is_empty() {
+            anyhow::bail!("logger.redact_key is required when redact_pii is enabled");
         }
+        let redactor = redact::Redactor::new(logger.redact_key.as_bytes());
+        redact::install(redactor.clone());
+        Some(redactor)
+    } else {
+        None
+    };
+    let (file_writer, guard) = if output.to_file() {
+        let (writer, guard) = log_file::writer(&logger.file)?;
+        (Some(writer), Some(guard))
+    } else {
+        (None, None)
+    };
+
+    let configured = std::env::var(tracing_subscriber::EnvFilter::DEFAULT_ENV)
+        .ok()
+        .filter(|f| tracing_subscriber::EnvFilter::try_new(f).is_ok())
+        .unwrap_or_else(|| logger.level.clone());
+    let filter = tracing_subscriber::EnvFilter::new(&configured);
+    let (filter, handle) = tracing_subscriber::reload::Layer::new(filter);
+
+    let stdout_layer = output
+        .to_stdout()
+        .then(|| fmt_layer(&logger.format, std::io::stdout, false, redactor.as_ref()));
+    let file_layer =
+        file_writer.map(|w| fmt_layer(&logger.format, w, true, redactor.as_ref()));
+
+    let provider = telemetry::init_tracer_provider(&logger.tracing);
+    let otel_layer = provider
+        .as_ref()
+        .map(|p| tracing_opentelemetry::layer().with_tracer(telemetry::tracer(p)));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(stdout_layer)
+        .with(file_layer)
+        .with(otel_layer)
+        .init();
+
+    Ok((provider, log_level::LogLevel::new(handle, &configured), guard))
+}
+
+/// Formatting layer for one log destination. Files are written `plain`,
+/// without ANSI colours.
+fn fmt_layer<S, W>(
+    format: &str,
+    writer: W,
+    plain: bool,
+    redactor: Option<&redact::Redactor>,
+) -> Box<dyn tracing_subscriber::Layer<S> + Send + Sync>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    W: for<'w> tracing_subscriber::fmt::MakeWriter<'w> + Send + Sync + 'static,
+{
+    use tracing_subscriber::Layer;
+
+    let layer = tracing_subscriber::fmt::layer().with_writer(writer);
+    let layer = if plain { layer.with_ansi(false) } else { layer };
+    match (format, redactor) {
+        ("json", Some(r)) => layer
+            .json()
+            .map_event_format(|f| redact::RedactJson::new(f, r.clone()))
+            .boxed(),
+        ("json", None) => layer.json().boxed(),
+        (_, Some(r)) => layer
+            .fmt_fields(redact::RedactFields::new(
+                tracing_subscriber::fmt::format::DefaultFields::new(),
+                r.clone(),
+            ))
+            .boxed(),
+        (_, None) => layer.boxed(),
     }
 }
 
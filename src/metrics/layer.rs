@@ -0,0 +1,88 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use tower::{Layer, Service};
+
+use crate::metrics::{Metrics, RpcObservation};
+
+/// Tower layer that times every gRPC call and reports it to [`Metrics`].
+#[derive(Clone)]
+pub struct MetricsLayer {
+    metrics: Metrics,
+}
+
+impl MetricsLayer {
+    pub fn new(metrics: Metrics) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MetricsService<S> {
+    inner: S,
+    metrics: Metrics,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for MetricsService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        // Take the service that was driven to readiness and leave a clone behind.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let metrics = self.metrics.clone();
+        let method = req.uri().path().to_string();
+
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = inner.call(req).await;
+
+            metrics.record(RpcObservation {
+                method,
+                code: grpc_code(&result),
+                latency: start.elapsed(),
+            });
+
+            result
+        })
+    }
+}
+
+/// gRPC status code of a finished call. Unary errors are sent trailers-only, so
+/// the status is in the headers; a missing header means the call completed
+/// normally (streams report theirs in trailers, after this point).
+pub fn grpc_code<B, E>(result: &Result<http::Response<B>, E>) -> i32 {
+    match result {
+        Ok(resp) => resp
+            .headers()
+            .get("grpc-status")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| s.parse::<i32>().ok())
+            .unwrap_or(0),
+        Err(_) => tonic::Code::Unknown as i32,
+    }
+}
@@ -0,0 +1,58 @@
+pub mod layer;
+pub mod slo;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A single completed RPC as seen by the metrics layer.
+#[derive(Debug, Clone)]
+pub struct RpcObservation {
+    /// Full gRPC path, e.g. `/bookmark.service.v1.BookmarkService/GetBookmark`.
+    pub method: String,
+    /// gRPC status code (`0` = OK).
+    pub code: i32,
+    pub latency: Duration,
+}
+
+impl RpcObservation {
+    /// Whether the status counts against availability (server-side failure).
+    pub fn is_server_error(&self) -> bool {
+        use tonic::Code;
+        matches!(
+            Code::from_i32(self.code),
+            Code::Unknown
+                | Code::DeadlineExceeded
+                | Code::Internal
+                | Code::Unavailable
+                | Code::DataLoss
+        )
+    }
+}
+
+/// Consumer of RPC observations recorded by [`layer::MetricsLayer`].
+pub trait RpcObserver: Send + Sync {
+    fn observe(&self, obs: &RpcObservation);
+}
+
+/// Fan-out registry of observers shared by the metrics layer.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    observers: Vec<Arc<dyn RpcObserver>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_observer(mut self, observer: Arc<dyn RpcObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    pub fn record(&self, obs: RpcObservation) {
+        for observer in &self.observers {
+            observer.observe(&obs);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::{SloConfig, SloObjective};
+use crate::metrics::{RpcObservation, RpcObserver};
+
+/// Length of the long burn-rate window, in one-minute buckets.
+const LONG_WINDOW_MINUTES: i64 = 60;
+/// Length of the short burn-rate window, in one-minute buckets.
+const SHORT_WINDOW_MINUTES: i64 = 5;
+
+/// Alert severity derived from multi-window burn rates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BurnAlert {
+    #[default]
+    None,
+    /// Slow burn: the 1h window exceeds the slow threshold.
+    Ticket,
+    /// Fast burn: both the 5m and 1h windows exceed the fast threshold.
+    Page,
+}
+
+impl BurnAlert {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Ticket => "ticket",
+            Self::Page => "page",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Bucket {
+    minute: i64,
+    total: u64,
+    errors: u64,
+    slow: u64,
+}
+
+#[derive(Default)]
+struct MethodWindow {
+    buckets: VecDeque<Bucket>,
+    /// Last alert level that was logged.
+    alert: BurnAlert,
+}
+
+impl MethodWindow {
+    fn record(&mut self, minute: i64, server_error: bool, slow: bool) {
+        match self.buckets.back_mut() {
+            Some(b) if b.minute == minute => {}
+            _ => self.buckets.push_back(Bucket {
+                minute,
+                ..Default::default()
+            }),
+        }
+        while self
+            .buckets
+            .front()
+            .is_some_and(|b| b.minute <= minute - LONG_WINDOW_MINUTES)
+        {
+            self.buckets.pop_front();
+        }
+
+        let bucket = self.buckets.back_mut().expect("bucket pushed above");
+        bucket.total += 1;
+        bucket.errors += server_error as u64;
+        bucket.slow += slow as u64;
+    }
+
+    fn sum(&self, now_minute: i64, minutes: i64) -> Bucket {
+        self.buckets
+            .iter()
+            .filter(|b| b.minute > now_minute - minutes)
+            .fold(Bucket::default(), |acc, b| Bucket {
+                minute: acc.minute,
+                total: acc.total + b.total,
+                errors: acc.errors + b.errors,
+                slow: acc.slow + b.slow,
+            })
+    }
+}
+
+/// Point-in-time SLO compliance for one method.
+#[derive(Debug, Clone)]
+pub struct SloStatus {
+    pub method: String,
+    pub objective: SloObjective,
+    pub requests_5m: u64,
+    pub requests_1h: u64,
+    pub availability_1h: f64,
+    pub latency_compliance_1h: f64,
+    pub availability_burn_rate_5m: f64,
+    pub availability_burn_rate_1h: f64,
+    pub latency_burn_rate_5m: f64,
+    pub latency_burn_rate_1h: f64,
+    pub alert: BurnAlert,
+}
+
+/// Tracks per-RPC latency and availability objectives over rolling windows and
+/// derives error-budget burn rates from them.
+pub struct SloTracker {
+    objectives: HashMap<String, SloObjective>,
+    default_objective: Option<SloObjective>,
+    fast_burn_threshold: f64,
+    slow_burn_threshold: f64,
+    windows: Mutex<HashMap<String, MethodWindow>>,
+}
+
+impl SloTracker {
+    pub fn new(config: &SloConfig) -> Self {
+        let mut objectives = HashMap::new();
+        let mut default_objective = None;
+        for obj in &config.objectives {
+            if obj.method == "*" {
+                default_objective = Some(obj.clone());
+            } else {
+                objectives.insert(obj.method.clone(), obj.clone());
+            }
+        }
+
+        Self {
+            objectives,
+            default_objective,
+            fast_burn_threshold: config.fast_burn_threshold,
+            slow_burn_threshold: config.slow_burn_threshold,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn objective_for(&self, method: &str) -> Option<&SloObjective> {
+        self.objectives
+            .get(method)
+            .or(self.default_objective.as_ref())
+    }
+
+    /// Current status of every tracked method, or of a single method.
+    pub fn status(&self, method: Option<&str>) -> Vec<SloStatus> {
+        let now = current_minute();
+        let windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out: Vec<SloStatus> = windows
+            .iter()
+            .filter(|(m, _)| method.is_none_or(|want| want == m.as_str()))
+            .filter_map(|(m, w)| {
+                let objective = self.objective_for(m)?;
+                Some(self.compute_status(m, objective, w, now))
+            })
+            .collect();
+        out.sort_by(|a, b| a.method.cmp(&b.method));
+        out
+    }
+
+    fn compute_status(
+        &self,
+        method: &str,
+        objective: &SloObjective,
+        window: &MethodWindow,
+        now: i64,
+    ) -> SloStatus {
+        let short = window.sum(now, SHORT_WINDOW_MINUTES);
+        let long = window.sum(now, LONG_WINDOW_MINUTES);
+
+        let availability_budget = 1.0 - objective.availability_target;
+        let latency_budget = 1.0 - objective.latency_target;
+
+        let availability_burn_rate_5m = burn_rate(short.errors, short.total, availability_budget);
+        let availability_burn_rate_1h = burn_rate(long.errors, long.total, availability_budget);
+        let latency_burn_rate_5m = burn_rate(short.slow, short.total, latency_budget);
+        let latency_burn_rate_1h = burn_rate(long.slow, long.total, latency_budget);
+
+        let alert = self.classify(
+            availability_burn_rate_5m.max(latency_burn_rate_5m),
+            availability_burn_rate_1h.max(latency_burn_rate_1h),
+        );
+
+        SloStatus {
+            method: method.to_string(),
+            objective: objective.clone(),
+            requests_5m: short.total,
+            requests_1h: long.total,
+            availability_1h: good_ratio(long.errors, long.total),
+            latency_compliance_1h: good_ratio(long.slow, long.total),
+            availability_burn_rate_5m,
+            availability_burn_rate_1h,
+            latency_burn_rate_5m,
+            latency_burn_rate_1h,
+            alert,
+        }
+    }
+
+    fn classify(&self, short_rate: f64, long_rate: f64) -> BurnAlert {
+        if short_rate >= self.fast_burn_threshold && long_rate >= self.fast_burn_threshold {
+            BurnAlert::Page
+        } else if long_rate >= self.slow_burn_threshold {
+            BurnAlert::Ticket
+        } else {
+            BurnAlert::None
+        }
+    }
+}
+
+impl RpcObserver for SloTracker {
+    fn observe(&self, obs: &RpcObservation) {
+        let Some(objective) = self.objective_for(&obs.method) else {
+            return;
+        };
+        let slow = obs.latency > Duration::from_millis(objective.latency_ms);
+        let now = current_minute();
+
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        let window = windows.entry(obs.method.clone()).or_default();
+        window.record(now, obs.is_server_error(), slow);
+
+        // Log only on alert transitions so a sustained burn does not flood the logs.
+        let status = self.compute_status(&obs.method, objective, window, now);
+        if window.alert != status.alert {
+            window.alert = status.alert;
+            match status.alert {
+                BurnAlert::None => tracing::info!(
+                    method = %status.method,
+                    "SLO burn rate back within budget"
+                ),
+                alert => tracing::warn!(
+                    method = %status.method,
+                    severity = alert.as_str(),
+                    availability_burn_rate_5m = status.availability_burn_rate_5m,
+                    availability_burn_rate_1h = status.availability_burn_rate_1h,
+                    latency_burn_rate_5m = status.latency_burn_rate_5m,
+                    latency_burn_rate_1h = status.latency_burn_rate_1h,
+                    "SLO error budget burning"
+                ),
+            }
+        }
+    }
+}
+
+fn current_minute() -> i64 {
+    chrono::Utc::now().timestamp() / 60
+}
+
+/// Ratio of bad events to the error budget; 1.0 means the budget is consumed
+/// exactly at the rate the objective allows.
+fn burn_rate(bad: u64, total: u64, budget: f64) -> f64 {
+    if total == 0 || budget <= 0.0 {
+        return 0.0;
+    }
+    (bad as f64 / total as f64) / budget
+}
+
+fn good_ratio(bad: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 1.0;
+    }
+    1.0 - bad as f64 / total as f64
+}
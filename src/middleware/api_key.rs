@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+use tonic::body::BoxBody;
+use tonic::Status;
+use tower::{Layer, Service};
+use uuid::Uuid;
+
+use crate::data::api_key_repo::{ApiKeyRow, ApiKeyStore};
+use crate::middleware::jwt::Identity;
+use crate::service::error::ServiceError;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Hex SHA-256 of an API key secret, as stored in `bookmark_api_keys.key_hash`.
+pub fn hash_key(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+struct CachedKey {
+    key_id: Uuid,
+    identity: Identity,
+    /// Key expiry, re-checked on every cache hit.
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    cached_at: Instant,
+}
+
+/// Resolves `x-api-key` secrets to service-account identities, caching hits
+/// for `ttl` so hot clients do not cost a query per request.
+pub struct ApiKeyAuthenticator {
+    repo: Arc<dyn ApiKeyStore>,
+    ttl: Duration,
+    cache: RwLock<HashMap<String, CachedKey>>,
+}
+
+impl ApiKeyAuthenticator {
+    pub fn new(repo: Arc<dyn ApiKeyStore>, ttl: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            repo,
+            ttl,
+            cache: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Drop a revoked key from this replica's cache so it stops working immediately.
+    pub fn evict(&self, key_id: Uuid) {
+        self.cache
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, cached| cached.key_id != key_id);
+    }
+
+    async fn authenticate(&self, secret: &str) -> Result<Identity, Status> {
+        let hash = hash_key(secret);
+        {
+            let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(cached) = cache.get(&hash) {
+                let expired = cached.expires_at.is_some_and(|t| t <= chrono::Utc::now());
+                if cached.cached_at.elapsed() < self.ttl && !expired {
+                    return Ok(cached.identity.clone());
+                }
+            }
+        }
+
+        let row = self
+            .repo
+            .find_active(&hash)
+            .await
+            .map_err(ServiceError::database)?;
+        let Some(row) = row else {
+            self.cache
+                .write()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&hash);
+            return Err(Status::unauthenticated("invalid or revoked API key"));
+        };
+
+        // Only refreshed on cache misses, so last_used_at is accurate to `ttl`.
+        let repo = self.repo.clone();
+        let key_id = row.id;
+        tokio::spawn(async move {
+            if let Err(e) = repo.touch(key_id).await {
+                tracing::warn!(error = %e, key_id = %key_id, "failed to record API key use");
+            }
+        });
+
+        let identity = service_account(&row);
+        self.cache
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                hash,
+                CachedKey {
+                    key_id: row.id,
+                    identity: identity.clone(),
+                    expires_at: row.expires_at,
+                    cached_at: Instant::now(),
+                },
+            );
+        Ok(identity)
+    }
+}
+
+/// The key's ID doubles as the service account's user ID, so bookmarks it
+/// creates are owned by (and permissions granted to) that key.
+fn service_account(row: &ApiKeyRow) -> Identity {
+    Identity {
+        tenant_id: row.tenant_id.to_string(),
+        user_id: row.id.to_string(),
+        username: format!("apikey:{}", row.name),
+        roles: row.roles.clone(),
+    }
+}
+
+/// Tower layer that authenticates `x-api-key` and sets `x-md-global-*` metadata
+/// for the key's service account. Requests without the header pass through.
+#[derive(Clone)]
+pub struct ApiKeyLayer {
+    auth: Arc<ApiKeyAuthenticator>,
+}
+
+impl ApiKeyLayer {
+    pub fn new(auth: Arc<ApiKeyAuthenticator>) -> Self {
+        Self { auth }
+    }
+}
+
+impl<S> Layer<S> for ApiKeyLayer {
+    type Service = ApiKeyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ApiKeyService {
+            inner,
+            auth: self.auth.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ApiKeyService<S> {
+    inner: S,
+    auth: Arc<ApiKeyAuthenticator>,
+}
+
+impl<S, ReqBody> Service<http::Request<ReqBody>> for ApiKeyService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let secret = req
+            .headers()
+            .get(API_KEY_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let Some(secret) = secret else {
+            return Box::pin(async move { inner.call(req).await });
+        };
+
+        let auth = self.auth.clone();
+        Box::pin(async move {
+            match auth.authenticate(&secret).await {
+                Ok(identity) => {
+                    let headers = req.headers_mut();
+                    identity.apply(headers);
+                    headers.remove(API_KEY_HEADER);
+                    inner.call(req).await
+                }
+                Err(status) => {
+                    tracing::debug!(reason = %status.message(), "rejected API key");
+                    Ok(status.into_http())
+                }
+            }
+        })
+    }
+}
@@ -1,26 +1,152 @@
-use tonic::{Request, Status};
-
-/// Audit logging interceptor that logs every RPC call.
-/// Records operation, tenant, user, and timestamp.
-pub fn audit_interceptor(req: Request<()>) -> Result<Request<()>, Status> {
-    let tenant_id = extract_metadata(&req, "x-md-global-tenant-id");
-    let user_id = extract_metadata(&req, "x-md-global-user-id");
-
-    tracing::info!(
-        service = "bookmark-service",
-        tenant_id = %tenant_id,
-        user_id = %user_id,
-        timestamp = %chrono::Utc::now().to_rfc3339(),
-        "audit: rpc call"
-    );
-
-    Ok(req)
-}
-
-fn extract_metadata(req: &Request<()>, key: &str) -> String {
-    req.metadata()
-        .get(key)
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("")
-        .to_string()
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use tower::{Layer, Service};
+
+use crate::data::audit_repo::NewAuditEvent;
+use crate::metrics::layer::grpc_code;
+use crate::middleware::mtls::ClientInfo;
+use crate::service::audit_log::AuditLog;
+
+/// Health probes are frequent and carry no caller, so they are not audited.
+const UNAUDITED_PREFIX: &str = "/grpc.health.v1.";
+
+/// Tower layer that audits every RPC once it completes: method, tenant, user,
+/// client certificate, status code and latency go to the log and to the
+/// persistent audit log. Sits inside the mTLS layer, for the client identity,
+/// and outside the policy layer, so denials are audited too.
+#[derive(Clone)]
+pub struct AuditLayer {
+    log: AuditLog,
+}
+
+impl AuditLayer {
+    pub fn new(log: AuditLog) -> Self {
+        Self { log }
+    }
+}
+
+impl<S> Layer<S> for AuditLayer {
+    type Service = AuditService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuditService {
+            inner,
+            log: self.log.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AuditService<S> {
+    inner: S,
+    log: AuditLog,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for AuditService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let operation = req.uri().path();
+        if operation.starts_with(UNAUDITED_PREFIX) {
+            return Box::pin(inner.call(req));
+        }
+        let header = |key: &str| {
+            req.headers()
+                .get(key)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_string()
+        };
+        let call = AuditedCall {
+            log: self.log.clone(),
+            tenant_id: header("x-md-global-tenant-id"),
+            user_id: header("x-md-global-user-id"),
+            operation: operation.to_string(),
+            client: req
+                .extensions()
+                .get::<ClientInfo>()
+                .map(|c| c.name().to_string())
+                .unwrap_or_default(),
+            create_time: Utc::now(),
+            start: Instant::now(),
+            done: false,
+        };
+
+        Box::pin(async move {
+            let result = inner.call(req).await;
+            call.finish(grpc_code(&result));
+            result
+        })
+    }
+}
+
+/// A call in progress. Recorded with its status when it finishes, or as
+/// CANCELLED if dropped first (deadline exceeded or client gone).
+struct AuditedCall {
+    log: AuditLog,
+    tenant_id: String,
+    user_id: String,
+    operation: String,
+    client: String,
+    create_time: DateTime<Utc>,
+    start: Instant,
+    done: bool,
+}
+
+impl AuditedCall {
+    fn finish(mut self, code: i32) {
+        self.record(code);
+    }
+
+    fn record(&mut self, code: i32) {
+        if std::mem::replace(&mut self.done, true) {
+            return;
+        }
+        let duration_ms = i32::try_from(self.start.elapsed().as_millis()).unwrap_or(i32::MAX);
+        tracing::info!(
+            service = "bookmark-service",
+            tenant_id = %self.tenant_id,
+            user_id = %self.user_id,
+            operation = %self.operation,
+            client = %self.client,
+            code,
+            status = ?tonic::Code::from_i32(code),
+            duration_ms,
+            "audit: rpc call"
+        );
+
+        self.log.record(NewAuditEvent {
+            tenant_id: self.tenant_id.parse().unwrap_or(0),
+            user_id: std::mem::take(&mut self.user_id),
+            operation: std::mem::take(&mut self.operation),
+            create_time: self.create_time,
+            client: std::mem::take(&mut self.client),
+            status_code: code,
+            duration_ms,
+        });
+    }
+}
+
+impl Drop for AuditedCall {
+    fn drop(&mut self) {
+        self.record(tonic::Code::Cancelled as i32);
+    }
 }
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tonic::body::BoxBody;
+use tonic::Status;
+use tower::{Layer, Service};
+
+use crate::config::ConcurrencyLimitConfig;
+
+/// Check limits without building the layer.
+pub fn validate(cfg: &ConcurrencyLimitConfig) -> anyhow::Result<()> {
+    if cfg.max_in_flight == 0 {
+        anyhow::bail!("max_in_flight must be positive");
+    }
+    let mut seen = std::collections::HashSet::new();
+    for limit in &cfg.methods {
+        if !limit.method.starts_with('/') {
+            anyhow::bail!("method {:?} must be a full gRPC path", limit.method);
+        }
+        if limit.max_in_flight == 0 {
+            anyhow::bail!("max_in_flight for {} must be positive", limit.method);
+        }
+        if !seen.insert(limit.method.as_str()) {
+            anyhow::bail!("method {} is listed twice", limit.method);
+        }
+    }
+    Ok(())
+}
+
+/// Global and per-method permits. A call takes its method's permit first, so a
+/// burst of one heavy method cannot use up the global ones it will not get.
+struct Limits {
+    global: Arc<Semaphore>,
+    methods: HashMap<String, Arc<Semaphore>>,
+}
+
+impl Limits {
+    fn try_acquire(&self, method: &str) -> Result<Vec<OwnedSemaphorePermit>, &'static str> {
+        let mut permits = Vec::with_capacity(2);
+        if let Some(sem) = self.methods.get(method) {
+            permits.push(
+                sem.clone()
+                    .try_acquire_owned()
+                    .map_err(|_| "method concurrency limit reached")?,
+            );
+        }
+        permits.push(
+            self.global
+                .clone()
+                .try_acquire_owned()
+                .map_err(|_| "server concurrency limit reached")?,
+        );
+        Ok(permits)
+    }
+}
+
+/// Tower layer that sheds load: once a method or the whole server has as many
+/// calls in flight as configured, further calls are rejected with
+/// RESOURCE_EXHAUSTED so clients back off instead of piling up. Permits are
+/// held until the response headers are ready; server streams are not counted
+/// past that point.
+#[derive(Clone)]
+pub struct ConcurrencyLimitLayer {
+    limits: Option<Arc<Limits>>,
+}
+
+impl ConcurrencyLimitLayer {
+    pub fn new(cfg: &ConcurrencyLimitConfig) -> anyhow::Result<Self> {
+        validate(cfg)?;
+        let limits = cfg.enabled.then(|| {
+            Arc::new(Limits {
+                global: Arc::new(Semaphore::new(cfg.max_in_flight)),
+                methods: cfg
+                    .methods
+                    .iter()
+                    .map(|m| (m.method.clone(), Arc::new(Semaphore::new(m.max_in_flight))))
+                    .collect(),
+            })
+        });
+        Ok(Self { limits })
+    }
+}
+
+impl<S> Layer<S> for ConcurrencyLimitLayer {
+    type Service = ConcurrencyLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConcurrencyLimitService {
+            inner,
+            limits: self.limits.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ConcurrencyLimitService<S> {
+    inner: S,
+    limits: Option<Arc<Limits>>,
+}
+
+impl<S, ReqBody> Service<http::Request<ReqBody>> for ConcurrencyLimitService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let permits = match &self.limits {
+            Some(limits) => match limits.try_acquire(req.uri().path()) {
+                Ok(permits) => permits,
+                Err(reason) => {
+                    tracing::debug!(method = %req.uri().path(), reason, "load shed");
+                    let status = Status::resource_exhausted(reason);
+                    return Box::pin(async move { Ok(status.into_http()) });
+                }
+            },
+            None => Vec::new(),
+        };
+
+        Box::pin(async move {
+            let result = inner.call(req).await;
+            drop(permits);
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::MethodConcurrencyLimit;
+
+    use super::*;
+
+    #[test]
+    fn method_limit_applies_before_global() {
+        let layer = ConcurrencyLimitLayer::new(&ConcurrencyLimitConfig {
+            enabled: true,
+            max_in_flight: 2,
+            methods: vec![MethodConcurrencyLimit {
+                method: "/svc/Heavy".to_string(),
+                max_in_flight: 1,
+            }],
+        })
+        .unwrap();
+        let limits = layer.limits.unwrap();
+
+        let heavy = limits.try_acquire("/svc/Heavy").unwrap();
+        assert_eq!(
+            limits.try_acquire("/svc/Heavy").unwrap_err(),
+            "method concurrency limit reached"
+        );
+        let light = limits.try_acquire("/svc/Light").unwrap();
+        assert_eq!(
+            limits.try_acquire("/svc/Light").unwrap_err(),
+            "server concurrency limit reached"
+        );
+        drop((heavy, light));
+        assert!(limits.try_acquire("/svc/Heavy").is_ok());
+    }
+}
@@ -0,0 +1,87 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tonic::body::BoxBody;
+use tonic::{Code, Status};
+use tonic_types::{ErrorDetails, StatusExt};
+use tower::{Layer, Service};
+
+use crate::data::breaker::DbBreaker;
+use crate::metrics::layer::grpc_code;
+
+/// Health checks report readiness themselves and must keep answering.
+const EXEMPT_PREFIX: &str = "/grpc.health.v1.";
+
+/// Tower layer that fails RPCs with UNAVAILABLE and a `RetryInfo` detail while
+/// the database circuit breaker is open, and tells the breaker when a call
+/// succeeds. Without a breaker (disabled in config) requests pass through.
+#[derive(Clone)]
+pub struct DbBreakerLayer {
+    breaker: Option<DbBreaker>,
+}
+
+impl DbBreakerLayer {
+    pub fn new(breaker: Option<DbBreaker>) -> Self {
+        Self { breaker }
+    }
+}
+
+impl<S> Layer<S> for DbBreakerLayer {
+    type Service = DbBreakerService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DbBreakerService {
+            inner,
+            breaker: self.breaker.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DbBreakerService<S> {
+    inner: S,
+    breaker: Option<DbBreaker>,
+}
+
+impl<S, ReqBody> Service<http::Request<ReqBody>> for DbBreakerService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let breaker = match &self.breaker {
+            Some(breaker) if !req.uri().path().starts_with(EXEMPT_PREFIX) => breaker.clone(),
+            _ => return Box::pin(inner.call(req)),
+        };
+        if let Some(retry_after) = breaker.retry_after() {
+            tracing::debug!(method = %req.uri().path(), "database circuit open, failing fast");
+            let status = Status::with_error_details(
+                Code::Unavailable,
+                "database unavailable",
+                ErrorDetails::with_retry_info(Some(retry_after)),
+            );
+            return Box::pin(async move { Ok(status.into_http()) });
+        }
+
+        Box::pin(async move {
+            let result = inner.call(req).await;
+            if grpc_code(&result) == Code::Ok as i32 {
+                breaker.record_success();
+            }
+            result
+        })
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use tower::{Layer, Service};
+
+/// RPCs currently being handled, by method path, so a shutdown that runs out
+/// of time can report what it cut off.
+#[derive(Clone, Default)]
+pub struct InFlight {
+    methods: Arc<Mutex<BTreeMap<String, usize>>>,
+}
+
+impl InFlight {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Methods with at least one call still waiting for its response.
+    pub fn snapshot(&self) -> BTreeMap<String, usize> {
+        self.methods.lock().unwrap().clone()
+    }
+
+    fn enter(&self, method: String) -> InFlightGuard {
+        *self.methods.lock().unwrap().entry(method.clone()).or_default() += 1;
+        InFlightGuard {
+            in_flight: self.clone(),
+            method,
+        }
+    }
+}
+
+/// Decrements the method's count when the call finishes or is dropped.
+struct InFlightGuard {
+    in_flight: InFlight,
+    method: String,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut methods = self.in_flight.methods.lock().unwrap();
+        if let Some(count) = methods.get_mut(&self.method) {
+            *count -= 1;
+            if *count == 0 {
+                methods.remove(&self.method);
+            }
+        }
+    }
+}
+
+/// Tower layer that records each call in [`InFlight`] until its response
+/// headers are ready; server streams are not tracked past that point.
+#[derive(Clone)]
+pub struct InFlightLayer {
+    in_flight: InFlight,
+}
+
+impl InFlightLayer {
+    pub fn new(in_flight: InFlight) -> Self {
+        Self { in_flight }
+    }
+}
+
+impl<S> Layer<S> for InFlightLayer {
+    type Service = InFlightService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InFlightService {
+            inner,
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct InFlightService<S> {
+    inner: S,
+    in_flight: InFlight,
+}
+
+impl<S, ReqBody> Service<http::Request<ReqBody>> for InFlightService<S>
+where
+    S: Service<http::Request<ReqBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let guard = self.in_flight.enter(req.uri().path().to_string());
+        Box::pin(async move {
+            let result = inner.call(req).await;
+            drop(guard);
+            result
+        })
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::config::GrpcWebConfig;
+
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// CORS policy for gRPC-Web: browsers must be allowed to send gRPC-Web requests
+/// with metadata headers and to read the gRPC status headers on the response.
+/// Only listed origins may send credentials (cookies); without a list any
+/// origin may call, but only with an explicit `Authorization` header.
+pub fn cors_layer(cfg: &GrpcWebConfig) -> CorsLayer {
+    let (origins, credentials) = if cfg.allowed_origins.is_empty() {
+        (AllowOrigin::any(), false)
+    } else {
+        let origins = AllowOrigin::list(
+            cfg.allowed_origins
+                .iter()
+                .filter_map(|o| HeaderValue::from_str(o).ok()),
+        );
+        (origins, true)
+    };
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_credentials(credentials)
+        .max_age(DEFAULT_MAX_AGE)
+        .allow_methods([Method::POST, Method::OPTIONS])
+        .allow_headers(tower_http::cors::AllowHeaders::mirror_request())
+        .expose_headers([
+            HeaderName::from_static("grpc-status"),
+            HeaderName::from_static("grpc-message"),
+            HeaderName::from_static("grpc-status-details-bin"),
+        ])
+}
@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use http::HeaderValue;
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde_json::Value;
+use tonic::body::BoxBody;
+use tonic::Status;
+use tower::{Layer, Service};
+
+use crate::config::{parse_duration, JwtConfig};
+use crate::health::HEALTH_SERVICE_PREFIX;
+use crate::middleware::api_key::API_KEY_HEADER;
+
+/// Metadata populated from validated claims. Client-sent values are always discarded.
+const IDENTITY_HEADERS: [&str; 4] = [
+    "x-md-global-tenant-id",
+    "x-md-global-user-id",
+    "x-md-global-username",
+    "x-md-global-roles",
+];
+
+/// Identity derived from validated credentials.
+#[derive(Debug, Clone)]
+pub(crate) struct Identity {
+    pub tenant_id: String,
+    pub user_id: String,
+    pub username: String,
+    pub roles: Vec<String>,
+}
+
+/// Drop any client-sent identity metadata.
+fn strip_identity(headers: &mut http::HeaderMap) {
+    for name in IDENTITY_HEADERS {
+        headers.remove(name);
+    }
+}
+
+impl Identity {
+    /// Replace any client-sent identity metadata with this identity.
+    pub(crate) fn apply(&self, headers: &mut http::HeaderMap) {
+        strip_identity(headers);
+        let values = [
+            self.tenant_id.as_str(),
+            self.user_id.as_str(),
+            self.username.as_str(),
+            &self.roles.join(","),
+        ];
+        for (name, value) in IDENTITY_HEADERS.into_iter().zip(values) {
+            if let Ok(value) = HeaderValue::from_str(value) {
+                headers.insert(name, value);
+            }
+        }
+    }
+}
+
+/// Validates bearer tokens against keys from a JWKS endpoint, refreshed in the background.
+pub struct JwtValidator {
+    cfg: JwtConfig,
+    keys: RwLock<HashMap<String, (DecodingKey, Algorithm)>>,
+}
+
+impl JwtValidator {
+    /// Fetch the key set once and keep refreshing it every `jwks_refresh`.
+    pub async fn start(cfg: &JwtConfig) -> anyhow::Result<Arc<Self>> {
+        if cfg.jwks_url.is_empty() {
+            anyhow::bail!("jwt.jwks_url is required when jwt is enabled");
+        }
+        let refresh = parse_duration(&cfg.jwks_refresh)?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+
+        let validator = Arc::new(Self {
+            cfg: cfg.clone(),
+            keys: RwLock::new(HashMap::new()),
+        });
+        validator.refresh(&client).await?;
+
+        let weak = Arc::downgrade(&validator);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(refresh);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(validator) = weak.upgrade() else {
+                    break;
+                };
+                if let Err(e) = validator.refresh(&client).await {
+                    tracing::warn!(error = %e, "failed to refresh JWKS, keeping previous keys");
+                }
+            }
+        });
+
+        Ok(validator)
+    }
+
+    async fn refresh(&self, client: &reqwest::Client) -> anyhow::Result<()> {
+        let body = client
+            .get(&self.cfg.jwks_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let set: JwkSet = serde_json::from_slice(&body)?;
+
+        let mut keys = HashMap::new();
+        for jwk in &set.keys {
+            let Some(kid) = jwk.common.key_id.clone() else {
+                continue;
+            };
+            let Some(alg) = key_algorithm(jwk) else {
+                continue;
+            };
+            match DecodingKey::from_jwk(jwk) {
+                Ok(key) => {
+                    keys.insert(kid, (key, alg));
+                }
+                Err(e) => tracing::warn!(kid = %kid, error = %e, "skipping unusable JWK"),
+            }
+        }
+        tracing::debug!(keys = keys.len(), "JWKS refreshed");
+        *self.keys.write().unwrap_or_else(|e| e.into_inner()) = keys;
+        Ok(())
+    }
+
+    fn validate(&self, token: &str) -> Result<Identity, Status> {
+        let header = jsonwebtoken::decode_header(token)
+            .map_err(|_| Status::unauthenticated("malformed token"))?;
+        let kid = header
+            .kid
+            .ok_or_else(|| Status::unauthenticated("token has no key id"))?;
+
+        let claims = {
+            let keys = self.keys.read().unwrap_or_else(|e| e.into_inner());
+            let (key, alg) = keys
+                .get(&kid)
+                .ok_or_else(|| Status::unauthenticated("unknown signing key"))?;
+            // The key's algorithm is authoritative; never trust the token header's.
+            if header.alg != *alg {
+                return Err(Status::unauthenticated("token algorithm mismatch"));
+            }
+
+            let mut validation = Validation::new(*alg);
+            if let Some(iss) = &self.cfg.issuer {
+                validation.set_issuer(&[iss]);
+            }
+            match &self.cfg.audience {
+                Some(aud) => validation.set_audience(&[aud]),
+                None => validation.validate_aud = false,
+            }
+            jsonwebtoken::decode::<Value>(token, key, &validation)
+                .map_err(|e| Status::unauthenticated(format!("invalid token: {e}")))?
+                .claims
+        };
+
+        identity_from_claims(&self.cfg, &claims)
+    }
+}
+
+/// The JWK's declared `alg`, or the conventional default for its key type when
+/// the provider omits it.
+fn key_algorithm(jwk: &Jwk) -> Option<Algorithm> {
+    if let Some(alg) = jwk.common.key_algorithm {
+        return alg.to_string().parse().ok();
+    }
+    match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => Some(Algorithm::RS256),
+        AlgorithmParameters::EllipticCurve(ec) => match ec.curve {
+            EllipticCurve::P256 => Some(Algorithm::ES256),
+            EllipticCurve::P384 => Some(Algorithm::ES384),
+            _ => None,
+        },
+        AlgorithmParameters::OctetKeyPair(_) => Some(Algorithm::EdDSA),
+        AlgorithmParameters::OctetKey(_) => None,
+    }
+}
+
+fn identity_from_claims(cfg: &JwtConfig, claims: &Value) -> Result<Identity, Status> {
+    let string_claim = |name: &str| match claims.get(name) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Number(n)) => n.to_string(),
+        _ => String::new(),
+    };
+
+    let user_id = string_claim(&cfg.user_claim);
+    if user_id.is_empty() {
+        return Err(Status::unauthenticated("token has no user claim"));
+    }
+    let roles = match claims.get(&cfg.roles_claim) {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        Some(Value::String(s)) => s
+            .split(',')
+            .filter(|r| !r.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(Identity {
+        tenant_id: string_claim(&cfg.tenant_claim),
+        user_id,
+        username: string_claim(&cfg.username_claim),
+        roles,
+    })
+}
+
+/// Tower layer that turns a validated bearer token into `x-md-global-*` metadata.
+#[derive(Clone)]
+pub struct JwtLayer {
+    validator: Arc<JwtValidator>,
+    /// Whether the API key layer runs behind this one. Only then may an
+    /// `x-api-key` request go without a bearer token when one is required.
+    api_keys_enabled: bool,
+}
+
+impl JwtLayer {
+    pub fn new(validator: Arc<JwtValidator>, api_keys_enabled: bool) -> Self {
+        Self {
+            validator,
+            api_keys_enabled,
+        }
+    }
+}
+
+impl<S> Layer<S> for JwtLayer {
+    type Service = JwtService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        JwtService {
+            inner,
+            validator: self.validator.clone(),
+            api_keys_enabled: self.api_keys_enabled,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct JwtService<S> {
+    inner: S,
+    validator: Arc<JwtValidator>,
+    api_keys_enabled: bool,
+}
+
+impl<S, ReqBody> Service<http::Request<ReqBody>> for JwtService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let token = req
+            .headers()
+            .get(http::header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(str::to_string);
+
+        let outcome = match token {
+            Some(token) => self.validator.validate(&token).map(Some),
+            // API-key clients are authenticated by the API key layer instead.
+            None if self.api_keys_enabled && req.headers().contains_key(API_KEY_HEADER) => Ok(None),
+            // Health probes carry no credentials at all, and get no identity.
+            None if self.validator.cfg.required => {
+                if req.uri().path().starts_with(HEALTH_SERVICE_PREFIX) {
+                    strip_identity(req.headers_mut());
+                    Ok(None)
+                } else {
+                    Err(Status::unauthenticated("bearer token required"))
+                }
+            }
+            None => Ok(None),
+        };
+
+        match outcome {
+            Ok(Some(identity)) => identity.apply(req.headers_mut()),
+            Ok(None) => {}
+            Err(status) => {
+                tracing::debug!(reason = %status.message(), "rejected bearer token");
+                return Box::pin(async move { Ok(status.into_http()) });
+            }
+        }
+
+        Box::pin(async move { inner.call(req).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{EncodingKey, Header};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    const SECRET: &[u8] = b"test-signing-secret";
+
+    fn required() -> JwtConfig {
+        JwtConfig {
+            enabled: true,
+            required: true,
+            ..JwtConfig::default()
+        }
+    }
+
+    fn validator(cfg: JwtConfig) -> Arc<JwtValidator> {
+        let key = (DecodingKey::from_secret(SECRET), Algorithm::HS256);
+        Arc::new(JwtValidator {
+            cfg,
+            keys: RwLock::new(HashMap::from([("k1".to_string(), key)])),
+        })
+    }
+
+    /// Run a request through the layer; the response carries the user id the
+    /// inner service saw as `x-seen-user`.
+    async fn call(layer: JwtLayer, req: http::Request<()>) -> http::Response<BoxBody> {
+        let inner = tower::service_fn(|req: http::Request<()>| async move {
+            let mut res = http::Response::new(tonic::body::empty_body());
+            if let Some(user) = req.headers().get("x-md-global-user-id") {
+                res.headers_mut().insert("x-seen-user", user.clone());
+            }
+            Ok::<_, std::convert::Infallible>(res)
+        });
+        layer.layer(inner).oneshot(req).await.unwrap()
+    }
+
+    fn grpc_code(res: &http::Response<BoxBody>) -> Option<&str> {
+        res.headers()
+            .get("grpc-status")
+            .and_then(|v| v.to_str().ok())
+    }
+
+    fn seen_user(res: &http::Response<BoxBody>) -> Option<&str> {
+        res.headers()
+            .get("x-seen-user")
+            .and_then(|v| v.to_str().ok())
+    }
+
+    fn forged(path: &str) -> http::Request<()> {
+        http::Request::builder()
+            .uri(path)
+            .header(API_KEY_HEADER, "anything")
+            .header("x-md-global-user-id", "1")
+            .body(())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn api_key_header_only_skips_required_token_when_api_keys_are_enabled() {
+        let unauthenticated = Some("16");
+        let path = "/bookmark.service.v1.BookmarkService/ListBookmarks";
+
+        let res = call(JwtLayer::new(validator(required()), false), forged(path)).await;
+        assert_eq!(grpc_code(&res), unauthenticated);
+
+        // The API key layer behind this one authenticates the key instead.
+        let res = call(JwtLayer::new(validator(required()), true), forged(path)).await;
+        assert_eq!(grpc_code(&res), None);
+
+        // Health probes pass without credentials, but without an identity too.
+        let health = format!("{HEALTH_SERVICE_PREFIX}Check");
+        let res = call(JwtLayer::new(validator(required()), false), forged(&health)).await;
+        assert_eq!(grpc_code(&res), None);
+        assert_eq!(seen_user(&res), None);
+    }
+
+    fn token(kid: &str, claims: Value) -> String {
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some(kid.to_string());
+        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn claims(cfg: &JwtConfig, exp: i64) -> Value {
+        serde_json::json!({
+            (cfg.user_claim.as_str()): "42",
+            (cfg.tenant_claim.as_str()): 3,
+            (cfg.roles_claim.as_str()): ["editor"],
+            "iss": "https://issuer.example",
+            "aud": "bookmark",
+            "exp": exp,
+        })
+    }
+
+    fn bearer(path: &str, token: &str) -> http::Request<()> {
+        http::Request::builder()
+            .uri(path)
+            .header(http::header::AUTHORIZATION, format!("Bearer {token}"))
+            .header("x-md-global-user-id", "1")
+            .body(())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn validates_bearer_tokens() {
+        let unauthenticated = Some("16");
+        let path = "/bookmark.service.v1.BookmarkService/ListBookmarks";
+        let cfg = JwtConfig {
+            issuer: Some("https://issuer.example".to_string()),
+            audience: Some("bookmark".to_string()),
+            ..required()
+        };
+        let now = chrono::Utc::now().timestamp();
+        let layer = || JwtLayer::new(validator(cfg.clone()), false);
+
+        // A valid token replaces the client-sent identity.
+        let res = call(layer(), bearer(path, &token("k1", claims(&cfg, now + 600)))).await;
+        assert_eq!(grpc_code(&res), None);
+        assert_eq!(seen_user(&res), Some("42"));
+
+        let expired = token("k1", claims(&cfg, now - 3600));
+        let res = call(layer(), bearer(path, &expired)).await;
+        assert_eq!(grpc_code(&res), unauthenticated);
+
+        let mut wrong_audience = claims(&cfg, now + 600);
+        wrong_audience["aud"] = "other".into();
+        let res = call(layer(), bearer(path, &token("k1", wrong_audience))).await;
+        assert_eq!(grpc_code(&res), unauthenticated);
+
+        let mut wrong_issuer = claims(&cfg, now + 600);
+        wrong_issuer["iss"] = "https://elsewhere.example".into();
+        let res = call(layer(), bearer(path, &token("k1", wrong_issuer))).await;
+        assert_eq!(grpc_code(&res), unauthenticated);
+
+        let unknown_kid = token("k2", claims(&cfg, now + 600));
+        let res = call(layer(), bearer(path, &unknown_kid)).await;
+        assert_eq!(grpc_code(&res), unauthenticated);
+        assert_eq!(seen_user(&res), None);
+    }
+
+    #[tokio::test]
+    async fn missing_token_is_rejected_only_when_required() {
+        let path = "/bookmark.service.v1.BookmarkService/ListBookmarks";
+        let request = || {
+            http::Request::builder()
+                .uri(path)
+                .header("x-md-global-user-id", "1")
+                .body(())
+                .unwrap()
+        };
+
+        let res = call(JwtLayer::new(validator(required()), false), request()).await;
+        assert_eq!(grpc_code(&res), Some("16"));
+        assert_eq!(seen_user(&res), None);
+
+        // Without `required`, gateway-provided metadata passes through.
+        let optional = JwtConfig {
+            enabled: true,
+            ..JwtConfig::default()
+        };
+        let res = call(JwtLayer::new(validator(optional), false), request()).await;
+        assert_eq!(grpc_code(&res), None);
+        assert_eq!(seen_user(&res), Some("1"));
+    }
+}
@@ -1,2 +1,13 @@
 pub mod mtls;
+pub mod api_key;
 pub mod audit;
+pub mod concurrency;
+pub mod db_breaker;
+pub mod deadline;
+pub mod drain;
+pub mod grpc_web;
+pub mod jwt;
+pub mod panic;
+pub mod policy;
+pub mod rate_limit;
+pub mod trace;
@@ -1,19 +1,160 @@
-use tonic::{Request, Status};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tonic::body::BoxBody;
+use tonic::transport::server::{TcpConnectInfo, TlsConnectInfo};
+use tonic::Status;
+use tower::{Layer, Service};
+use x509_parser::extensions::GeneralName;
+use x509_parser::x509::AttributeTypeAndValue;
+
+use crate::config::MtlsConfig;
+
+const BACKUP_SERVICE_PREFIX: &str = "/bookmark.service.v1.BackupService/";
 
 /// Client identity extracted from mTLS certificate.
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct ClientInfo {
     pub common_name: String,
     pub organization: String,
+    /// DNS, URI and email subject alternative names.
+    pub sans: Vec<String>,
 }
 
-/// mTLS interceptor that validates client certificates.
-/// In production (with TLS), the transport layer (rustls ServerConfig) handles
-/// certificate validation before requests reach the interceptor.
-/// This interceptor logs requests for observability.
-#[allow(dead_code)]
-pub fn mtls_interceptor(req: Request<()>) -> Result<Request<()>, Status> {
-    tracing::trace!("mTLS interceptor: request passed");
-    Ok(req)
+impl ClientInfo {
+    /// Parse the identity out of a DER-encoded leaf certificate.
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+        let subject = cert.subject();
+        let sans = cert
+            .subject_alternative_name()
+            .ok()
+            .flatten()
+            .map(|ext| {
+                ext.value
+                    .general_names
+                    .iter()
+                    .filter_map(|name| match name {
+                        GeneralName::DNSName(s)
+                        | GeneralName::URI(s)
+                        | GeneralName::RFC822Name(s) => Some(s.to_string()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            common_name: first_attr(subject.iter_common_name()),
+            organization: first_attr(subject.iter_organization()),
+            sans,
+        })
+    }
+
+    /// The CN, or the first SAN for certificates without one.
+    pub fn name(&self) -> &str {
+        if self.common_name.is_empty() {
+            self.sans.first().map(String::as_str).unwrap_or_default()
+        } else {
+            &self.common_name
+        }
+    }
+
+    /// Whether the CN or any SAN is one of `names`.
+    pub fn matches(&self, names: &[String]) -> bool {
+        names
+            .iter()
+            .any(|n| *n == self.common_name || self.sans.contains(n))
+    }
+}
+
+fn first_attr<'a>(mut attrs: impl Iterator<Item = &'a AttributeTypeAndValue<'a>>) -> String {
+    attrs
+        .next()
+        .and_then(|a| a.as_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Tower layer that attaches the verified client certificate's identity as a
+/// [`ClientInfo`] extension and, when configured, only lets trusted client
+/// certificates call the BackupService. Certificate chain validation itself
+/// happens in the TLS handshake before requests get here.
+#[derive(Clone)]
+pub struct MtlsLayer {
+    backup_trusted_clients: Arc<Vec<String>>,
+}
+
+impl MtlsLayer {
+    pub fn new(cfg: &MtlsConfig) -> Self {
+        Self {
+            backup_trusted_clients: Arc::new(cfg.backup_trusted_clients.clone()),
+        }
+    }
+}
+
+impl<S> Layer<S> for MtlsLayer {
+    type Service = MtlsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MtlsService {
+            inner,
+            backup_trusted_clients: self.backup_trusted_clients.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MtlsService<S> {
+    inner: S,
+    backup_trusted_clients: Arc<Vec<String>>,
+}
+
+impl<S, ReqBody> Service<http::Request<ReqBody>> for MtlsService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let client = req
+            .extensions()
+            .get::<TlsConnectInfo<TcpConnectInfo>>()
+            .and_then(|info| info.peer_certs())
+            .and_then(|certs| certs.first().and_then(|leaf| ClientInfo::from_der(leaf)));
+
+        if !self.backup_trusted_clients.is_empty()
+            && req.uri().path().starts_with(BACKUP_SERVICE_PREFIX)
+            && !client
+                .as_ref()
+                .is_some_and(|c| c.matches(&self.backup_trusted_clients))
+        {
+            tracing::warn!(
+                method = %req.uri().path(),
+                client = client.as_ref().map(ClientInfo::name).unwrap_or(""),
+                "audit: backup rpc denied for untrusted client certificate"
+            );
+            let status = Status::permission_denied("client certificate not trusted for backups");
+            return Box::pin(async move { Ok(status.into_http()) });
+        }
+
+        if let Some(client) = client {
+            req.extensions_mut().insert(client);
+        }
+
+        Box::pin(async move { inner.call(req).await })
+    }
 }
@@ -0,0 +1,189 @@
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use opentelemetry::KeyValue;
+use tokio::sync::mpsc;
+use tonic::body::BoxBody;
+use tonic::Status;
+use tower::{Layer, Service};
+
+thread_local! {
+    /// Set while polling a guarded future, so the hook knows the panic is caught.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    /// Location and backtrace of the last caught panic on this thread.
+    static LAST_PANIC: RefCell<Option<(String, Backtrace)>> = const { RefCell::new(None) };
+}
+
+/// Install a panic hook that records backtraces for panics caught by this
+/// module instead of printing them; other panics go to the previous hook.
+pub fn install_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if CATCHING.get() {
+            let location = info.location().map(ToString::to_string).unwrap_or_default();
+            LAST_PANIC.set(Some((location, Backtrace::force_capture())));
+        } else {
+            previous(info);
+        }
+    }));
+}
+
+/// A panic caught while polling a guarded future.
+struct CaughtPanic {
+    message: String,
+    location: String,
+    backtrace: String,
+}
+
+impl CaughtPanic {
+    fn new(payload: Box<dyn Any + Send>) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string());
+        let (location, backtrace) = LAST_PANIC
+            .take()
+            .map(|(location, backtrace)| (location, backtrace.to_string()))
+            .unwrap_or_default();
+        Self {
+            message,
+            location,
+            backtrace,
+        }
+    }
+
+    /// Log the panic and count it against `method`.
+    fn report(&self, method: &str, tenant_id: &str, user_id: &str) {
+        tracing::error!(
+            method,
+            tenant_id,
+            user_id,
+            panic = %self.message,
+            location = %self.location,
+            backtrace = %self.backtrace,
+            "handler panicked"
+        );
+        opentelemetry::global::meter("bookmark")
+            .u64_counter("bookmark.rpc.panics")
+            .with_description("Panics caught in RPC handlers and stream producers")
+            .build()
+            .add(1, &[KeyValue::new("method", method.to_string())]);
+    }
+}
+
+/// Polls the inner future, turning a panic into [`CaughtPanic`].
+struct CatchPanic<F> {
+    inner: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for CatchPanic<F> {
+    type Output = Result<F::Output, CaughtPanic>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let was_catching = CATCHING.replace(true);
+        let result = catch_unwind(AssertUnwindSafe(|| self.inner.as_mut().poll(cx)));
+        CATCHING.set(was_catching);
+        match result {
+            Ok(poll) => poll.map(Ok),
+            Err(payload) => Poll::Ready(Err(CaughtPanic::new(payload))),
+        }
+    }
+}
+
+/// Run `task`, turning a panic into `Status::internal` after reporting it
+/// like a handler panic. For work an RPC hands off to its own task.
+pub async fn guarded<T>(method: &str, task: impl Future<Output = T>) -> Result<T, Status> {
+    CatchPanic {
+        inner: Box::pin(task),
+    }
+    .await
+    .map_err(|panic| {
+        panic.report(method, "", "");
+        Status::internal("internal error")
+    })
+}
+
+/// Spawn the producer of a server stream. A panic ends the stream with
+/// `Status::internal` instead of closing it as if it had completed.
+pub fn spawn_stream<T: Send + 'static>(
+    method: &'static str,
+    tx: mpsc::Sender<Result<T, Status>>,
+    task: impl Future<Output = ()> + Send + 'static,
+) {
+    tokio::spawn(async move {
+        if let Err(status) = guarded(method, task).await {
+            let _ = tx.send(Err(status)).await;
+        }
+    });
+}
+
+/// Tower layer that answers a panicking handler with INTERNAL instead of
+/// dropping the connection, logging the backtrace and counting the panic.
+#[derive(Clone, Default)]
+pub struct CatchPanicLayer;
+
+impl<S> Layer<S> for CatchPanicLayer {
+    type Service = CatchPanicService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CatchPanicService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct CatchPanicService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody> Service<http::Request<ReqBody>> for CatchPanicService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        let method = req.uri().path().to_string();
+        let header = |key: &str| {
+            req.headers()
+                .get(key)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_string()
+        };
+        let tenant_id = header("x-md-global-tenant-id");
+        let user_id = header("x-md-global-user-id");
+
+        Box::pin(async move {
+            // Catch panics from building the future as well as from polling it.
+            let call = async move { inner.call(req).await };
+            match (CatchPanic {
+                inner: Box::pin(call),
+            })
+            .await
+            {
+                Ok(result) => result,
+                Err(panic) => {
+                    panic.report(&method, &tenant_id, &user_id);
+                    Ok(Status::internal("internal error").into_http())
+                }
+            }
+        })
+    }
+}
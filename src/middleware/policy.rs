@@ -0,0 +1,360 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tonic::body::BoxBody;
+use tonic::Status;
+use tower::{Layer, Service};
+
+use crate::config::{PolicyRule, PolicySection};
+
+/// Compiled method policy: ordered rules plus the action for unmatched methods.
+#[derive(Debug)]
+pub struct MethodPolicy {
+    rules: Vec<CompiledRule>,
+    default_allow: bool,
+}
+
+#[derive(Debug)]
+struct CompiledRule {
+    service: String,
+    method: String,
+    roles: Vec<String>,
+    public: bool,
+}
+
+impl MethodPolicy {
+    pub fn from_config(cfg: &PolicySection) -> anyhow::Result<Self> {
+        let default_allow = match cfg.default_action.as_str() {
+            "allow" => true,
+            "deny" => false,
+            other => anyhow::bail!("invalid policy default_action {other:?}"),
+        };
+        let rules = cfg
+            .rules
+            .iter()
+            .map(compile_rule)
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            rules,
+            default_allow,
+        })
+    }
+
+    /// Decide whether a call to `path` (`/package.Service/Method`) may proceed.
+    pub fn check(&self, path: &str, headers: &http::HeaderMap) -> Result<(), Status> {
+        let (service, method) = path
+            .trim_start_matches('/')
+            .split_once('/')
+            .unwrap_or((path, ""));
+
+        let Some(rule) = self.rules.iter().find(|r| r.matches(service, method)) else {
+            return if self.default_allow {
+                Ok(())
+            } else {
+                Err(Status::permission_denied(format!("{path} is not allowed")))
+            };
+        };
+        if rule.public {
+            return Ok(());
+        }
+
+        let header = |key: &str| headers.get(key).and_then(|v| v.to_str().ok()).unwrap_or("");
+        let roles: Vec<&str> = header("x-md-global-roles")
+            .split(',')
+            .filter(|r| !r.is_empty())
+            .collect();
+        let tenant_id = header("x-md-global-tenant-id").parse::<i32>().unwrap_or(0);
+        // Same rule as `extract_context`: tenant 0 is fine as long as roles are present.
+        if header("x-md-global-user-id").is_empty() || (tenant_id == 0 && roles.is_empty()) {
+            return Err(Status::unauthenticated("authentication required"));
+        }
+
+        if rule.roles.is_empty() || rule.roles.iter().any(|r| roles.contains(&r.as_str())) {
+            Ok(())
+        } else {
+            Err(Status::permission_denied(format!(
+                "{path} requires one of roles: {}",
+                rule.roles.join(", ")
+            )))
+        }
+    }
+}
+
+impl CompiledRule {
+    fn matches(&self, service: &str, method: &str) -> bool {
+        let service_ok = self.service == "*"
+            || self.service == service
+            || service.rsplit('.').next() == Some(self.service.as_str());
+        service_ok && (self.method == "*" || self.method == method)
+    }
+}
+
+fn compile_rule(rule: &PolicyRule) -> anyhow::Result<CompiledRule> {
+    let (service, method) = match rule.method.trim_start_matches('/').split_once('/') {
+        Some((s, m)) if !s.is_empty() && !m.is_empty() => (s, m),
+        _ if rule.method == "*" => ("*", "*"),
+        _ => anyhow::bail!("invalid policy method {:?}, expected Service/Method", rule.method),
+    };
+    Ok(CompiledRule {
+        service: service.to_string(),
+        method: method.to_string(),
+        roles: rule.roles.clone(),
+        public: rule.public,
+    })
+}
+
+/// Tower layer that enforces [`MethodPolicy`] before requests reach a service.
+#[derive(Clone)]
+pub struct PolicyLayer {
+    policy: Arc<MethodPolicy>,
+}
+
+impl PolicyLayer {
+    pub fn new(policy: MethodPolicy) -> Self {
+        Self {
+            policy: Arc::new(policy),
+        }
+    }
+}
+
+impl<S> Layer<S> for PolicyLayer {
+    type Service = PolicyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PolicyService {
+            inner,
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PolicyService<S> {
+    inner: S,
+    policy: Arc<MethodPolicy>,
+}
+
+impl<S, ReqBody> Service<http::Request<ReqBody>> for PolicyService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        if let Err(status) = self.policy.check(req.uri().path(), req.headers()) {
+            let header = |key: &str| {
+                req.headers()
+                    .get(key)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("")
+                    .to_string()
+            };
+            tracing::warn!(
+                service = "bookmark-service",
+                operation = %req.uri().path(),
+                tenant_id = %header("x-md-global-tenant-id"),
+                user_id = %header("x-md-global-user-id"),
+                reason = %status.message(),
+                "audit: rpc denied by policy"
+            );
+            return Box::pin(async move { Ok(status.into_http()) });
+        }
+
+        Box::pin(async move { inner.call(req).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use tonic::Code;
+
+    use super::*;
+    use crate::config::{load_config, PolicyConfig};
+
+    const IMPORT_BACKUP: &str = "/bookmark.service.v1.BackupService/ImportBackup";
+
+    fn policy(default_action: &str, rules: &[(&str, &[&str], bool)]) -> MethodPolicy {
+        MethodPolicy::from_config(&PolicySection {
+            default_action: default_action.to_string(),
+            rules: rules
+                .iter()
+                .map(|(method, roles, public)| PolicyRule {
+                    method: method.to_string(),
+                    roles: roles.iter().map(|r| r.to_string()).collect(),
+                    public: *public,
+                })
+                .collect(),
+        })
+        .unwrap()
+    }
+
+    fn caller(user_id: &str, tenant_id: &str, roles: &str) -> http::HeaderMap {
+        let mut headers = http::HeaderMap::new();
+        for (key, value) in [
+            ("x-md-global-user-id", user_id),
+            ("x-md-global-tenant-id", tenant_id),
+            ("x-md-global-roles", roles),
+        ] {
+            if !value.is_empty() {
+                headers.insert(key, value.parse().unwrap());
+            }
+        }
+        headers
+    }
+
+    fn code(result: Result<(), Status>) -> Option<Code> {
+        result.err().map(|s| s.code())
+    }
+
+    #[test]
+    fn short_and_qualified_service_names_match() {
+        let user = caller("7", "1", "");
+        for method in [
+            "BackupService/ImportBackup",
+            "bookmark.service.v1.BackupService/ImportBackup",
+        ] {
+            let p = policy("allow", &[(method, &["super:admin"], false)]);
+            assert_eq!(
+                code(p.check(IMPORT_BACKUP, &user)),
+                Some(Code::PermissionDenied)
+            );
+            // Neither form reaches another method or a service sharing the suffix.
+            let other = "/bookmark.service.v1.BackupService/ExportBackup";
+            assert_eq!(code(p.check(other, &user)), None);
+            let other = "/bookmark.service.v1.OtherBackupService/ImportBackup";
+            assert_eq!(code(p.check(other, &user)), None);
+        }
+    }
+
+    #[test]
+    fn wildcard_rules() {
+        let user = caller("7", "1", "");
+        let p = policy("allow", &[("BackupService/*", &["super:admin"], false)]);
+        assert_eq!(
+            code(p.check(IMPORT_BACKUP, &user)),
+            Some(Code::PermissionDenied)
+        );
+
+        for method in ["*", "*/*", "*/ImportBackup"] {
+            let p = policy("allow", &[(method, &["super:admin"], false)]);
+            assert_eq!(
+                code(p.check(IMPORT_BACKUP, &user)),
+                Some(Code::PermissionDenied)
+            );
+        }
+        let p = policy("allow", &[("*/ExportBackup", &["super:admin"], false)]);
+        assert_eq!(code(p.check(IMPORT_BACKUP, &user)), None);
+    }
+
+    #[test]
+    fn first_matching_rule_applies() {
+        let p = policy(
+            "deny",
+            &[
+                ("grpc.health.v1.Health/*", &[], true),
+                ("BackupService/*", &["super:admin"], false),
+                ("*/*", &[], false),
+            ],
+        );
+        let user = caller("7", "1", "tenant:manager");
+        assert_eq!(
+            code(p.check(IMPORT_BACKUP, &user)),
+            Some(Code::PermissionDenied)
+        );
+        let admin = caller("7", "1", "tenant:manager,super:admin");
+        assert_eq!(code(p.check(IMPORT_BACKUP, &admin)), None);
+        let list = "/bookmark.service.v1.BookmarkService/ListBookmarks";
+        assert_eq!(code(p.check(list, &user)), None);
+    }
+
+    #[test]
+    fn public_rules_skip_authentication() {
+        let p = policy("deny", &[("grpc.health.v1.Health/*", &[], true)]);
+        let anonymous = http::HeaderMap::new();
+        assert_eq!(
+            code(p.check("/grpc.health.v1.Health/Check", &anonymous)),
+            None
+        );
+        assert_eq!(
+            code(p.check("/grpc.health.v1.Health/Watch", &anonymous)),
+            None
+        );
+    }
+
+    #[test]
+    fn default_action_applies_to_unmatched_methods() {
+        let anonymous = http::HeaderMap::new();
+        let p = policy("deny", &[("BackupService/*", &[], false)]);
+        let list = "/bookmark.service.v1.BookmarkService/ListBookmarks";
+        assert_eq!(
+            code(p.check(list, &caller("7", "1", ""))),
+            Some(Code::PermissionDenied)
+        );
+
+        let p = policy("allow", &[("BackupService/*", &[], false)]);
+        assert_eq!(code(p.check(list, &anonymous)), None);
+
+        let invalid = PolicySection {
+            default_action: "maybe".to_string(),
+            rules: vec![],
+        };
+        assert!(MethodPolicy::from_config(&invalid).is_err());
+    }
+
+    #[test]
+    fn missing_identity_is_unauthenticated() {
+        let p = policy("deny", &[("*/*", &[], false)]);
+        let unauthenticated = Some(Code::Unauthenticated);
+        assert_eq!(
+            code(p.check(IMPORT_BACKUP, &http::HeaderMap::new())),
+            unauthenticated
+        );
+        assert_eq!(
+            code(p.check(IMPORT_BACKUP, &caller("", "1", "super:admin"))),
+            unauthenticated
+        );
+        assert_eq!(
+            code(p.check(IMPORT_BACKUP, &caller("7", "0", ""))),
+            unauthenticated
+        );
+        // Platform callers have no tenant but do carry roles.
+        assert_eq!(
+            code(p.check(IMPORT_BACKUP, &caller("7", "0", "platform:admin"))),
+            None
+        );
+    }
+
+    #[test]
+    fn import_backup_needs_an_admin_role() {
+        let shipped: PolicyConfig = load_config(Path::new("configs/policy.yaml")).unwrap();
+        for cfg in [shipped.policy, PolicySection::default()] {
+            let p = MethodPolicy::from_config(&cfg).unwrap();
+            for roles in ["", "tenant:manager", "bookmark:editor"] {
+                assert_eq!(
+                    code(p.check(IMPORT_BACKUP, &caller("7", "1", roles))),
+                    Some(Code::PermissionDenied),
+                    "roles {roles:?}"
+                );
+            }
+            assert_eq!(
+                code(p.check(IMPORT_BACKUP, &caller("7", "1", "super:admin"))),
+                None
+            );
+        }
+    }
+}
@@ -0,0 +1,361 @@
+//! PII redaction for log and audit output, enabled by `logger.redact_pii`.
+//! User ids, usernames and URLs are replaced with a keyed hash, so the same
+//! value always maps to the same token and events stay correlatable without
+//! the value being recoverable. Only structured fields are redacted; messages
+//! are written as-is, so keep PII out of them.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tracing::field::{Field, Visit};
+use tracing_subscriber::field::{MakeVisitor, VisitFmt, VisitOutput};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Field names whose values are redacted, in log fields, span fields and
+/// JSON output (where keys are matched case-insensitively without `_`).
+const PII_FIELDS: &[&str] = &[
+    "user_id",
+    "username",
+    "by",
+    "owner",
+    "recipient",
+    "subject_id",
+    "reassign_to",
+    "created_by",
+    "url",
+    "original_url",
+];
+
+/// Hex characters kept from the hash; enough to keep collisions unlikely
+/// across a tenant's users and URLs.
+const TOKEN_HEX_LEN: usize = 16;
+
+fn is_token(value: &str) -> bool {
+    value
+        .strip_prefix("pii:")
+        .is_some_and(|hex| hex.len() == TOKEN_HEX_LEN && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// `?value` quotes strings; hash the same text `%value` would.
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+fn is_pii(name: &str) -> bool {
+    PII_FIELDS.contains(&name)
+}
+
+/// JSON keys may be camelCase (`userId`) where log fields are snake_case.
+fn is_pii_key(key: &str) -> bool {
+    PII_FIELDS.iter().any(|f| {
+        let mut a = f.chars().filter(|c| *c != '_');
+        let mut b = key.chars().filter(|c| *c != '_');
+        loop {
+            match (a.next(), b.next()) {
+                (None, None) => return true,
+                (Some(x), Some(y)) if x.eq_ignore_ascii_case(&y) => {}
+                _ => return false,
+            }
+        }
+    })
+}
+
+#[derive(Clone)]
+pub struct Redactor {
+    key: Arc<[u8]>,
+}
+
+impl Redactor {
+    pub fn new(key: &[u8]) -> Self {
+        Self { key: key.into() }
+    }
+
+    /// `pii:` followed by a truncated HMAC-SHA256 of `value`. Empty values
+    /// stay empty so "no user" remains visible, and tokens pass through, so
+    /// a value hashed before logging is not hashed again.
+    pub fn token(&self, value: &str) -> String {
+        if value.is_empty() || is_token(value) {
+            return value.to_string();
+        }
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(value.as_bytes());
+        let digest = mac.finalize().into_bytes();
+        let mut token = String::with_capacity(4 + TOKEN_HEX_LEN);
+        token.push_str("pii:");
+        for byte in &digest[..TOKEN_HEX_LEN / 2] {
+            token.push_str(&format!("{byte:02x}"));
+        }
+        token
+    }
+
+    /// Replace PII values anywhere in a JSON document.
+    pub fn redact_json(&self, value: &mut serde_json::Value) {
+        use serde_json::Value;
+        match value {
+            Value::Object(map) => {
+                for (key, v) in map.iter_mut() {
+                    match v {
+                        Value::String(s) if is_pii_key(key) => *s = self.token(unquote(s)),
+                        Value::Number(n) if is_pii_key(key) => {
+                            *v = Value::String(self.token(&n.to_string()))
+                        }
+                        _ => self.redact_json(v),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|v| self.redact_json(v)),
+            _ => {}
+        }
+    }
+}
+
+static ACTIVE: OnceLock<Redactor> = OnceLock::new();
+
+/// Make `redactor` apply to values redacted outside the log formatter, such as
+/// audit exports and trace span attributes.
+pub fn install(redactor: Redactor) {
+    let _ = ACTIVE.set(redactor);
+}
+
+/// `value` as it may appear in output: hashed when redaction is on.
+pub fn value(value: &str) -> Cow<'_, str> {
+    match ACTIVE.get() {
+        Some(r) => Cow::Owned(r.token(value)),
+        None => Cow::Borrowed(value),
+    }
+}
+
+/// Field formatter wrapper for the text format; hashes PII fields of events
+/// and spans before the inner formatter sees them.
+pub struct RedactFields<M> {
+    inner: M,
+    redactor: Redactor,
+}
+
+impl<M> RedactFields<M> {
+    pub fn new(inner: M, redactor: Redactor) -> Self {
+        Self { inner, redactor }
+    }
+}
+
+impl<T, M: MakeVisitor<T>> MakeVisitor<T> for RedactFields<M> {
+    type Visitor = RedactVisitor<M::Visitor>;
+
+    fn make_visitor(&self, target: T) -> Self::Visitor {
+        RedactVisitor {
+            inner: self.inner.make_visitor(target),
+            redactor: self.redactor.clone(),
+        }
+    }
+}
+
+pub struct RedactVisitor<V> {
+    inner: V,
+    redactor: Redactor,
+}
+
+impl<V: Visit> RedactVisitor<V> {
+    fn record_token(&mut self, field: &Field, value: &str) {
+        let token = self.redactor.token(unquote(value));
+        self.inner.record_debug(field, &format_args!("{token}"));
+    }
+}
+
+impl<V: Visit> Visit for RedactVisitor<V> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if is_pii(field.name()) {
+            self.record_token(field, &format!("{value:?}"));
+        } else {
+            self.inner.record_debug(field, value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if is_pii(field.name()) {
+            self.record_token(field, value);
+        } else {
+            self.inner.record_str(field, value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if is_pii(field.name()) {
+            self.record_token(field, &value.to_string());
+        } else {
+            self.inner.record_i64(field, value);
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if is_pii(field.name()) {
+            self.record_token(field, &value.to_string());
+        } else {
+            self.inner.record_u64(field, value);
+        }
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.inner.record_f64(field, value);
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.inner.record_bool(field, value);
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.inner.record_error(field, value);
+    }
+}
+
+impl<V: VisitOutput<fmt::Result>> VisitOutput<fmt::Result> for RedactVisitor<V> {
+    fn finish(self) -> fmt::Result {
+        self.inner.finish()
+    }
+}
+
+impl<V: VisitFmt> VisitFmt for RedactVisitor<V> {
+    fn writer(&mut self) -> &mut dyn fmt::Write {
+        self.inner.writer()
+    }
+}
+
+/// Event formatter wrapper for the JSON format, whose event fields bypass the
+/// field formatter: each line is re-parsed and PII keys hashed.
+pub struct RedactJson<F> {
+    inner: F,
+    redactor: Redactor,
+}
+
+impl<F> RedactJson<F> {
+    pub fn new(inner: F, redactor: Redactor) -> Self {
+        Self { inner, redactor }
+    }
+}
+
+impl<S, N, F> FormatEvent<S, N> for RedactJson<F>
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'w> FormatFields<'w> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &tracing::Event<'_>,
+    ) -> fmt::Result {
+        let mut line = String::new();
+        self.inner
+            .format_event(ctx, Writer::new(&mut line), event)?;
+        match serde_json::from_str::<serde_json::Value>(&line) {
+            Ok(mut value) => {
+                self.redactor.redact_json(&mut value);
+                writeln!(writer, "{value}")
+            }
+            Err(_) => writer.write_str(&line),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tracing_subscriber::fmt::format::DefaultFields;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn emit() {
+        let span = tracing::info_span!("rpc", user_id = "7");
+        let _enter = span.enter();
+        tracing::info!(user_id = 7, url = ?"https://x.test", tenant_id = 1, "hello");
+    }
+
+    #[test]
+    fn tokens_are_stable_and_keyed() {
+        let a = Redactor::new(b"key-a");
+        let b = Redactor::new(b"key-b");
+        assert_eq!(a.token("42"), a.token("42"));
+        assert_ne!(a.token("42"), a.token("43"));
+        assert_ne!(a.token("42"), b.token("42"));
+        assert!(a.token("42").starts_with("pii:"));
+        assert_eq!(a.token(""), "");
+        assert_eq!(a.token(&a.token("42")), a.token("42"));
+    }
+
+    #[test]
+    fn json_redacts_nested_and_camel_case_keys() {
+        let r = Redactor::new(b"k");
+        let mut value = serde_json::json!({
+            "fields": { "message": "hi", "user_id": 7, "url": "https://x.test" },
+            "span": { "name": "rpc", "user_id": "7" },
+            "userId": "7",
+            "tenant_id": 1,
+        });
+        r.redact_json(&mut value);
+        assert_eq!(value["fields"]["user_id"], r.token("7"));
+        assert_eq!(value["span"]["user_id"], r.token("7"));
+        assert_eq!(value["userId"], r.token("7"));
+        assert_eq!(value["fields"]["url"], r.token("https://x.test"));
+        assert_eq!(value["fields"]["message"], "hi");
+        assert_eq!(value["tenant_id"], 1);
+    }
+
+    #[test]
+    fn formatters_redact_event_and_span_fields() {
+        let r = Redactor::new(b"k");
+        let (user, url) = (r.token("7"), r.token("https://x.test"));
+
+        let text = Buffer::default();
+        let writer = text.clone();
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .fmt_fields(RedactFields::new(DefaultFields::new(), r.clone()));
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), emit);
+        let text = String::from_utf8(text.0.lock().unwrap().clone()).unwrap();
+        assert!(text.contains(&format!("rpc{{user_id={user}}}")), "{text}");
+        assert!(
+            text.contains(&format!("user_id={user} url={url} tenant_id=1")),
+            "{text}"
+        );
+
+        let json = Buffer::default();
+        let writer = json.clone();
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(move || writer.clone())
+            .json()
+            .map_event_format(|f| RedactJson::new(f, r.clone()));
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), emit);
+        let json = String::from_utf8(json.0.lock().unwrap().clone()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["fields"]["user_id"], user.as_str());
+        assert_eq!(value["fields"]["url"], url.as_str());
+        assert_eq!(value["span"]["user_id"], user.as_str());
+        assert!(!json.contains("x.test"), "{json}");
+    }
+}
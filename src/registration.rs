@@ -1,9 +1,14 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use tokio::sync::watch;
+use opentelemetry::metrics::Counter;
+use opentelemetry::KeyValue;
 use tonic::transport::{Channel, Endpoint};
+use uuid::Uuid;
 
 use crate::cert::load_client_tls_config;
+use crate::config::{parse_duration, BackoffConfig, RegistrationSection};
+use crate::health::{HealthAggregator, HealthStatus};
+use crate::shutdown::ShutdownSignal;
 
 /// Generated module registration client.
 pub mod proto {
@@ -18,15 +23,76 @@ const MODULE_NAME: &str = "Bookmark";
 const VERSION: &str = "1.0.0";
 const DESCRIPTION: &str = "URL Bookmark Management with Zanzibar-like permissions";
 
-const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
-const RETRY_INTERVAL: Duration = Duration::from_secs(5);
-const MAX_RETRIES: u32 = 60;
-const STARTUP_DELAY: Duration = Duration::from_secs(3);
+/// Parse a configured duration, falling back to `default` with a warning.
+fn duration_or(what: &str, value: &str, default: Duration) -> Duration {
+    parse_duration(value).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, default = ?default, "invalid registration.{what}");
+        default
+    })
+}
 
-/// Start module registration lifecycle in a background task.
-/// Returns a shutdown sender — drop it to trigger unregistration.
+/// Exponential backoff with jitter, bounded by the total time spent retrying.
+struct Backoff {
+    interval: Duration,
+    max_interval: Duration,
+    multiplier: f64,
+    /// `None` retries forever.
+    max_elapsed: Option<Duration>,
+    started: Instant,
+}
+
+impl Backoff {
+    fn new(cfg: &BackoffConfig) -> Self {
+        let initial_interval = duration_or(
+            "backoff.initial_interval",
+            &cfg.initial_interval,
+            Duration::from_secs(1),
+        );
+        let max_interval =
+            duration_or("backoff.max_interval", &cfg.max_interval, Duration::from_secs(60));
+        let max_elapsed =
+            duration_or("backoff.max_elapsed", &cfg.max_elapsed, Duration::from_secs(30 * 60));
+        Self {
+            interval: initial_interval.min(max_interval),
+            max_interval,
+            multiplier: cfg.multiplier.max(1.0),
+            max_elapsed: (!max_elapsed.is_zero()).then_some(max_elapsed),
+            started: Instant::now(),
+        }
+    }
+
+    /// Delay before the next attempt, or `None` once waiting would run past
+    /// `max_elapsed`.
+    fn next_delay(&mut self) -> Option<Duration> {
+        // Uniform in [interval / 2, interval] so restarted replicas spread out.
+        let bytes = Uuid::new_v4().into_bytes();
+        let random = u32::from_be_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
+        let delay = self.interval.mul_f64(0.5 + f64::from(random) / f64::from(u32::MAX) / 2.0);
+
+        if let Some(max_elapsed) = self.max_elapsed {
+            if self.started.elapsed() + delay > max_elapsed {
+                return None;
+            }
+        }
+        self.interval = self.interval.mul_f64(self.multiplier).min(self.max_interval);
+        Some(delay)
+    }
+}
+
+/// Why the heartbeat loop stopped.
+enum HeartbeatExit {
+    Shutdown,
+    /// The gateway appears to have lost the registration; the reason is
+    /// recorded on the re-registration metric.
+    Reregister(&'static str),
+}
+
+/// Start module registration lifecycle in a background task. The module is
+/// unregistered once `shutdown` fires.
 pub fn start_registration(
-    shutdown_rx: watch::Receiver<bool>,
+    cfg: RegistrationSection,
+    mut shutdown: ShutdownSignal,
+    health: HealthAggregator,
 ) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
         let admin_endpoint = std::env::var("ADMIN_GRPC_ENDPOINT").unwrap_or_default();
@@ -37,38 +103,94 @@ pub fn start_registration(
 
         tracing::info!(endpoint = %admin_endpoint, "will register with admin gateway");
 
-        // Wait for gRPC server to be ready
-        tokio::time::sleep(STARTUP_DELAY).await;
+        // Nothing to unregister until this succeeds, so shutdown simply abandons it.
+        let startup = async {
+            // Wait for gRPC server to be ready
+            let delay = duration_or("startup_delay", &cfg.startup_delay, Duration::from_secs(3));
+            tokio::time::sleep(delay).await;
 
-        let channel = match connect_with_retry(&admin_endpoint).await {
-            Some(ch) => ch,
-            None => {
+            let Some(channel) = connect_with_retry(&admin_endpoint, &cfg.backoff).await else {
                 tracing::error!("failed to connect to admin gateway after retries");
+                return None;
+            };
+
+            let mut client = ModuleRegistrationServiceClient::new(channel);
+
+            // Register
+            if let Err(e) = register(&mut client, &cfg.backoff).await {
+                tracing::error!(error = %e, "failed to register with admin gateway");
+                return None;
+            }
+            Some(client)
+        };
+        let mut client = tokio::select! {
+            client = startup => match client {
+                Some(client) => client,
+                None => return,
+            },
+            _ = shutdown.recv() => {
+                tracing::info!("shutdown before registration completed");
                 return;
             }
         };
 
-        let mut client = ModuleRegistrationServiceClient::new(channel);
+        // Heartbeat loop, re-registering whenever the gateway seems to have
+        // forgotten the module (e.g. it restarted and lost its state).
+        let heartbeat_interval =
+            duration_or("heartbeat_interval", &cfg.heartbeat_interval, Duration::from_secs(30));
+        let reregistrations: Counter<u64> = opentelemetry::global::meter("bookmark")
+            .u64_counter("bookmark.registration.reregistrations")
+            .with_description("Re-registrations with the admin gateway after lost heartbeats")
+            .build();
+        loop {
+            let exit = heartbeat_loop(
+                &mut client,
+                heartbeat_interval,
+                cfg.reregister_after_failures,
+                &mut shutdown,
+                &health,
+            )
+            .await;
+            let HeartbeatExit::Reregister(reason) = exit else {
+                break;
+            };
 
-        // Register
-        if let Err(e) = register(&mut client).await {
-            tracing::error!(error = %e, "failed to register with admin gateway");
-            return;
+            reregistrations.add(1, &[KeyValue::new("reason", reason)]);
+            tracing::warn!(
+                from = "registered",
+                to = "reregistering",
+                reason,
+                "registration state changed"
+            );
+            tokio::select! {
+                result = register(&mut client, &cfg.backoff) => match result {
+                    Ok(()) => tracing::info!(
+                        from = "reregistering",
+                        to = "registered",
+                        "registration state changed"
+                    ),
+                    // Keep heartbeating; the next failures trigger another attempt.
+                    Err(e) => {
+                        tracing::error!(error = %e, "failed to re-register with admin gateway")
+                    }
+                },
+                _ = shutdown.recv() => break,
+            }
         }
 
-        // Heartbeat loop
-        heartbeat_loop(&mut client, shutdown_rx).await;
-
         // Unregister on shutdown
         unregister(&mut client).await;
     })
 }
 
-async fn connect_with_retry(endpoint: &str) -> Option<Channel> {
+async fn connect_with_retry(endpoint: &str, backoff: &BackoffConfig) -> Option<Channel> {
     let client_tls = load_client_tls_config();
     let scheme = if client_tls.is_some() { "https" } else { "http" };
+    let mut backoff = Backoff::new(backoff);
+    let mut attempt = 0u32;
 
-    for attempt in 1..=MAX_RETRIES {
+    loop {
+        attempt += 1;
         let mut ep = match Endpoint::from_shared(format!("{scheme}://{endpoint}")) {
             Ok(ep) => ep,
             Err(_) => return None,
@@ -81,16 +203,17 @@ async fn connect_with_retry(endpoint: &str) -> Option<Channel> {
         match ep.connect().await {
             Ok(ch) => return Some(ch),
             Err(e) => {
-                tracing::warn!(attempt, error = %e, "connection attempt failed");
-                tokio::time::sleep(RETRY_INTERVAL).await;
+                let delay = backoff.next_delay()?;
+                tracing::warn!(attempt, error = %e, retry_in = ?delay, "connection attempt failed");
+                tokio::time::sleep(delay).await;
             }
         }
     }
-    None
 }
 
 async fn register(
     client: &mut ModuleRegistrationServiceClient<Channel>,
+    backoff: &BackoffConfig,
 ) -> anyhow::Result<()> {
     let grpc_endpoint = std::env::var("GRPC_ADVERTISE_ADDR")
         .unwrap_or_else(|_| "0.0.0.0:9700".to_string());
@@ -123,7 +246,10 @@ async fn register(
         auth_token,
     };
 
-    for attempt in 1..=MAX_RETRIES {
+    let mut backoff = Backoff::new(backoff);
+    let mut attempt = 0u32;
+    loop {
+        attempt += 1;
         match client.register_module(req.clone()).await {
             Ok(resp) => {
                 let resp = resp.into_inner();
@@ -136,45 +262,69 @@ async fn register(
                 return Ok(());
             }
             Err(e) => {
-                tracing::warn!(attempt, error = %e, "registration attempt failed");
-                tokio::time::sleep(RETRY_INTERVAL).await;
+                let Some(delay) = backoff.next_delay() else {
+                    anyhow::bail!("gave up after {attempt} attempts: {e}");
+                };
+                tracing::warn!(
+                    attempt,
+                    error = %e,
+                    retry_in = ?delay,
+                    "registration attempt failed"
+                );
+                tokio::time::sleep(delay).await;
             }
         }
     }
-
-    anyhow::bail!("exhausted registration retries")
 }
 
+/// Send heartbeats until shutdown, an unacknowledged heartbeat, or
+/// `max_failures` consecutive failed heartbeats.
 async fn heartbeat_loop(
     client: &mut ModuleRegistrationServiceClient<Channel>,
-    mut shutdown_rx: watch::Receiver<bool>,
-) {
-    tracing::info!(interval = ?HEARTBEAT_INTERVAL, "starting heartbeat");
-    let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+    heartbeat_interval: Duration,
+    max_failures: u32,
+    shutdown: &mut ShutdownSignal,
+    health: &HealthAggregator,
+) -> HeartbeatExit {
+    tracing::info!(interval = ?heartbeat_interval, "starting heartbeat");
+    let mut interval = tokio::time::interval(heartbeat_interval);
     interval.tick().await; // skip first immediate tick
+    let mut failures = 0u32;
 
     loop {
         tokio::select! {
             _ = interval.tick() => {
+                let (status, message) = health.overall();
+                let module_health = match status {
+                    HealthStatus::Healthy => ModuleHealth::Healthy,
+                    HealthStatus::Degraded => ModuleHealth::Degraded,
+                    HealthStatus::Unhealthy => ModuleHealth::Unhealthy,
+                };
                 let req = HeartbeatRequest {
                     module_id: MODULE_ID.to_string(),
-                    health: ModuleHealth::Healthy.into(),
-                    message: "Bookmark service is healthy".to_string(),
+                    health: module_health.into(),
+                    message,
                 };
                 match client.heartbeat(req).await {
                     Ok(resp) => {
                         if !resp.into_inner().acknowledged {
                             tracing::warn!("heartbeat not acknowledged");
+                            return HeartbeatExit::Reregister("not_acknowledged");
                         }
+                        failures = 0;
                     }
                     Err(e) => {
-                        tracing::warn!(error = %e, "heartbeat failed");
+                        failures += 1;
+                        tracing::warn!(error = %e, failures, "heartbeat failed");
+                        if failures >= max_failures {
+                            return HeartbeatExit::Reregister("heartbeat_failures");
+                        }
                     }
                 }
             }
-            _ = shutdown_rx.changed() => {
+            _ = shutdown.recv() => {
                 tracing::info!("heartbeat stopped due to shutdown");
-                break;
+                return HeartbeatExit::Shutdown;
             }
         }
     }
@@ -0,0 +1,264 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::Utc;
+
+use crate::authz::relations::{Relation, ResourceType, SubjectType, WILDCARD_RESOURCE};
+use crate::client::admin_client::AdminClient;
+use crate::data::group_repo::GroupStore;
+use crate::data::permission_repo::PermissionStore;
+use crate::service::audit_log::csv_field;
+
+/// Tuples read per page while loading a tenant's grants.
+const TUPLE_PAGE: u32 = 1000;
+
+pub const CSV_HEADER: &str = "subject_type,subject_id,subject_name,bookmark_id,relation,via\n";
+
+/// One subject's effective access to one bookmark.
+#[derive(Debug)]
+pub struct AccessRow<'a> {
+    pub subject_type: SubjectType,
+    pub subject_id: &'a str,
+    pub subject_name: &'a str,
+    pub bookmark_id: &'a str,
+    pub relation: Relation,
+    /// Grants giving `relation`, e.g. `group:eng` or `tenant/*`.
+    pub via: &'a [String],
+}
+
+/// Bookmarks a subject reaches, with the relation and the grants giving it.
+pub type Reach = BTreeMap<String, (Relation, Vec<String>)>;
+
+/// A report subject: a user or role and the display name the directory has for it.
+#[derive(Debug)]
+pub struct Subject {
+    pub subject_type: SubjectType,
+    pub id: String,
+    pub name: String,
+}
+
+/// A tenant's unexpired grants, loaded once and expanded per subject.
+pub struct AccessGraph {
+    grants: HashMap<(SubjectType, String), Vec<(String, Relation)>>,
+    members: HashMap<String, Vec<String>>,
+    /// Every bookmark in the tenant; only loaded when a wildcard tuple exists.
+    bookmarks: Vec<String>,
+}
+
+impl AccessGraph {
+    pub async fn load(
+        perms: &dyn PermissionStore,
+        groups: &dyn GroupStore,
+        tenant_id: i32,
+    ) -> anyhow::Result<Self> {
+        let now = Utc::now();
+        let mut grants: HashMap<(SubjectType, String), Vec<(String, Relation)>> = HashMap::new();
+        let mut wildcard = false;
+        let mut after = None;
+        loop {
+            let (mut rows, _) = perms
+                .list_permissions_filtered(
+                    tenant_id,
+                    Some(ResourceType::Bookmark),
+                    None,
+                    None,
+                    None,
+                    after,
+                    1,
+                    TUPLE_PAGE,
+                )
+                .await?;
+            let more = rows.len() > TUPLE_PAGE as usize;
+            rows.truncate(TUPLE_PAGE as usize);
+            after = rows.last().map(|r| (r.create_time, r.id));
+
+            for row in rows {
+                if row.expires_at.is_some_and(|t| t < now) {
+                    continue;
+                }
+                let (Some(subject_type), Some(relation)) = (
+                    SubjectType::from_str(&row.subject_type),
+                    Relation::from_str(&row.relation),
+                ) else {
+                    continue;
+                };
+                wildcard |= row.resource_id == WILDCARD_RESOURCE;
+                grants
+                    .entry((subject_type, row.subject_id))
+                    .or_default()
+                    .push((row.resource_id, relation));
+            }
+            if !more {
+                break;
+            }
+        }
+
+        let mut members = HashMap::new();
+        for (subject_type, group_id) in grants.keys() {
+            if *subject_type != SubjectType::Group {
+                continue;
+            }
+            let users = groups
+                .list_members(tenant_id, group_id)
+                .await?
+                .into_iter()
+                .map(|m| m.user_id)
+                .collect();
+            members.insert(group_id.clone(), users);
+        }
+
+        let bookmarks = if wildcard {
+            perms
+                .list_tenant_resources(tenant_id, ResourceType::Bookmark)
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            grants,
+            members,
+            bookmarks,
+        })
+    }
+
+    /// Users and roles to report on, sorted by type then id: everyone holding
+    /// a grant or belonging to a granted group, plus the directory's users and
+    /// roles for the tenant when a directory is available.
+    pub async fn subjects(
+        &self,
+        tenant_id: i32,
+        directory: Option<&AdminClient>,
+    ) -> Vec<Subject> {
+        let mut users: BTreeMap<String, String> = BTreeMap::new();
+        let mut roles: BTreeMap<String, String> = BTreeMap::new();
+        for (subject_type, id) in self.grants.keys() {
+            match subject_type {
+                SubjectType::User => users.entry(id.clone()).or_default(),
+                SubjectType::Role => roles.entry(id.clone()).or_default(),
+                _ => continue,
+            };
+        }
+        for user in self.members.values().flatten() {
+            users.entry(user.clone()).or_default();
+        }
+
+        if let Some(admin) = directory {
+            match admin.list_users().await {
+                Ok(resp) => {
+                    for u in resp.items {
+                        if u.tenant_id.unwrap_or(0) as i32 == tenant_id {
+                            users.insert(u.id.to_string(), u.username);
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!(error = %e, "access report: failed to list users"),
+            }
+            match admin.list_roles().await {
+                Ok(resp) => {
+                    // Roles are platform-wide; only name the ones already found.
+                    for r in resp.items {
+                        if let Some(name) = roles.get_mut(&r.id.to_string()) {
+                            *name = r.name;
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!(error = %e, "access report: failed to list roles"),
+            }
+        }
+
+        let subject = |subject_type| {
+            move |(id, name): (String, String)| Subject {
+                subject_type,
+                id,
+                name,
+            }
+        };
+        users
+            .into_iter()
+            .map(subject(SubjectType::User))
+            .chain(roles.into_iter().map(subject(SubjectType::Role)))
+            .collect()
+    }
+
+    /// Everything the subject reaches through its own grants, its groups'
+    /// grants and tenant-wide grants.
+    pub fn reach(&self, subject: &Subject) -> Reach {
+        let mut reach = Reach::new();
+        self.add_grants(&mut reach, subject.subject_type, &subject.id);
+        if subject.subject_type == SubjectType::User {
+            for (group_id, users) in &self.members {
+                if users.contains(&subject.id) {
+                    self.add_grants(&mut reach, SubjectType::Group, group_id);
+                }
+            }
+        }
+        self.add_grants(&mut reach, SubjectType::Tenant, "all");
+        reach
+    }
+
+    fn add_grants(&self, reach: &mut Reach, subject_type: SubjectType, subject_id: &str) {
+        let Some(tuples) = self.grants.get(&(subject_type, subject_id.to_string())) else {
+            return;
+        };
+        let via = match subject_type {
+            SubjectType::User => "direct".to_string(),
+            SubjectType::Role => format!("role:{subject_id}"),
+            SubjectType::Group => format!("group:{subject_id}"),
+            SubjectType::Tenant => "tenant".to_string(),
+        };
+        for (resource_id, relation) in tuples {
+            if resource_id == WILDCARD_RESOURCE {
+                let via = format!("{via}/*");
+                for bookmark_id in &self.bookmarks {
+                    merge(reach, bookmark_id, *relation, &via);
+                }
+            } else {
+                merge(reach, resource_id, *relation, &via);
+            }
+        }
+    }
+}
+
+/// Keep the highest relation per bookmark, collecting every grant giving it.
+fn merge(reach: &mut Reach, bookmark_id: &str, relation: Relation, via: &str) {
+    match reach.get_mut(bookmark_id) {
+        Some((current, sources)) => {
+            if relation.hierarchy_level() > current.hierarchy_level() {
+                *current = relation;
+                *sources = vec![via.to_string()];
+            } else if relation == *current && !sources.iter().any(|s| s == via) {
+                sources.push(via.to_string());
+            }
+        }
+        None => {
+            reach.insert(bookmark_id.to_string(), (relation, vec![via.to_string()]));
+        }
+    }
+}
+
+pub fn write_ndjson(buf: &mut Vec<u8>, row: &AccessRow<'_>) {
+    let value = serde_json::json!({
+        "subjectType": row.subject_type.as_str(),
+        "subjectId": row.subject_id,
+        "subjectName": row.subject_name,
+        "bookmarkId": row.bookmark_id,
+        "relation": row.relation.as_str(),
+        "via": row.via,
+    });
+    // Serializing a `Value` into a Vec cannot fail.
+    let _ = serde_json::to_writer(&mut *buf, &value);
+    buf.push(b'\n');
+}
+
+pub fn write_csv(buf: &mut Vec<u8>, row: &AccessRow<'_>) {
+    let line = format!(
+        "{},{},{},{},{},{}\n",
+        row.subject_type.as_str(),
+        csv_field(row.subject_id),
+        csv_field(row.subject_name),
+        csv_field(row.bookmark_id),
+        row.relation.as_str(),
+        csv_field(&row.via.join(";")),
+    );
+    buf.extend_from_slice(line.as_bytes());
+}
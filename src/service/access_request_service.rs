@@ -0,0 +1,367 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use crate::authz::checker::Checker;
+use crate::authz::engine::CheckContext;
+use crate::authz::relations::{Relation, ResourceType, SubjectType, WILDCARD_RESOURCE};
+use crate::data::access_request_repo::{
+    AccessRequestFilter, AccessRequestRow, AccessRequestStore, NewAccessRequest, STATUS_APPROVED,
+    STATUS_DENIED, STATUS_PENDING,
+};
+use crate::data::bookmark_repo::BookmarkStore;
+use crate::events::{Event, EventPublisher};
+use crate::service::bookmark_service::proto::{
+    bookmark_access_request_service_server::BookmarkAccessRequestService, AccessRequest,
+    AccessRequestScope, AccessRequestStatus, ListAccessRequestsRequest,
+    ListAccessRequestsResponse, RequestAccessRequest, ResolveAccessRequestRequest,
+};
+use crate::service::bookmark_service::timestamp_to_datetime;
+use crate::service::context_helper::extract_context;
+use crate::service::error::ServiceError;
+use crate::service::page_token;
+use crate::service::share_notifier::ShareNotifier;
+
+const MAX_MESSAGE_LEN: usize = 1000;
+
+pub struct AccessRequestServiceImpl {
+    requests: Arc<dyn AccessRequestStore>,
+    bookmarks: Arc<dyn BookmarkStore>,
+    checker: Checker,
+    notifier: Arc<dyn ShareNotifier>,
+    events: EventPublisher,
+}
+
+impl AccessRequestServiceImpl {
+    pub fn new(
+        requests: Arc<dyn AccessRequestStore>,
+        bookmarks: Arc<dyn BookmarkStore>,
+        checker: Checker,
+        notifier: Arc<dyn ShareNotifier>,
+        events: EventPublisher,
+    ) -> Self {
+        Self {
+            requests,
+            bookmarks,
+            checker,
+            notifier,
+            events,
+        }
+    }
+
+    /// Whether the user already holds every permission `relation` would grant.
+    async fn has_access(
+        &self,
+        tenant_id: i32,
+        user_id: &str,
+        role_ids: &[String],
+        bookmark_id: &str,
+        relation: Relation,
+    ) -> bool {
+        for &permission in relation.granted_permissions() {
+            let ctx = CheckContext {
+                tenant_id,
+                user_id: user_id.to_string(),
+                resource_type: ResourceType::Bookmark,
+                resource_id: bookmark_id.to_string(),
+                permission,
+            };
+            if !self.checker.engine().check(&ctx, role_ids).await.allowed {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Notify the bookmark's direct, unexpired user owners in the background.
+    async fn notify_owners(&self, request: AccessRequestRow) {
+        let owners: Vec<String> = match self
+            .checker
+            .engine()
+            .store()
+            .get_direct_permissions(request.tenant_id, ResourceType::Bookmark, &request.bookmark_id)
+            .await
+        {
+            Ok(rows) => rows
+                .into_iter()
+                .filter(|p| {
+                    p.relation == Relation::Owner.as_str()
+                        && p.subject_type == SubjectType::User.as_str()
+                        && p.subject_tenant_id.is_none()
+                        && p.expires_at.is_none_or(|t| t > Utc::now())
+                })
+                .map(|p| p.subject_id)
+                .collect(),
+            Err(e) => {
+                tracing::warn!(id = request.id, error = %e, "failed to look up bookmark owners");
+                return;
+            }
+        };
+
+        let notifier = self.notifier.clone();
+        tokio::spawn(async move {
+            for owner in &owners {
+                if let Err(e) = notifier.notify_access_request(&request, owner).await {
+                    tracing::warn!(
+                        id = request.id,
+                        owner = %owner,
+                        error = %e,
+                        "access request notification failed"
+                    );
+                }
+            }
+        });
+    }
+}
+
+fn parse_request_id(id: u32) -> Result<i32, Status> {
+    i32::try_from(id)
+        .ok()
+        .filter(|id| *id > 0)
+        .ok_or_else(|| Status::invalid_argument("invalid access request id"))
+}
+
+fn check_text(field: &str, value: &str) -> Result<(), Status> {
+    if value.chars().count() > MAX_MESSAGE_LEN {
+        return Err(Status::invalid_argument(format!(
+            "{field} is longer than {MAX_MESSAGE_LEN} characters"
+        )));
+    }
+    Ok(())
+}
+
+fn status_from_proto(status: i32) -> Result<Option<&'static str>, Status> {
+    match AccessRequestStatus::try_from(status) {
+        Ok(AccessRequestStatus::Unspecified) => Ok(None),
+        Ok(AccessRequestStatus::Pending) => Ok(Some(STATUS_PENDING)),
+        Ok(AccessRequestStatus::Approved) => Ok(Some(STATUS_APPROVED)),
+        Ok(AccessRequestStatus::Denied) => Ok(Some(STATUS_DENIED)),
+        Err(_) => Err(Status::invalid_argument("invalid status")),
+    }
+}
+
+#[tonic::async_trait]
+impl BookmarkAccessRequestService for AccessRequestServiceImpl {
+    async fn request_access(
+        &self,
+        request: Request<RequestAccessRequest>,
+    ) -> Result<Response<AccessRequest>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        if req.bookmark_id == WILDCARD_RESOURCE {
+            return Err(Status::invalid_argument("bookmark_id must name a single bookmark"));
+        }
+        let bookmark_uuid = Uuid::parse_str(&req.bookmark_id)
+            .map_err(|_| Status::invalid_argument("invalid bookmark_id"))?;
+        let relation = match req.relation {
+            0 => Relation::Viewer,
+            v => Relation::from_proto(v)
+                .ok_or_else(|| Status::invalid_argument("invalid relation"))?,
+        };
+        if relation == Relation::Owner {
+            return Err(Status::invalid_argument("OWNER cannot be requested"));
+        }
+        check_text("message", &req.message)?;
+
+        self.bookmarks
+            .get_by_id(ctx.tenant_id, bookmark_uuid)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("bookmark not found"))?;
+        if self
+            .has_access(ctx.tenant_id, &ctx.user_id, &ctx.role_ids, &req.bookmark_id, relation)
+            .await
+        {
+            return Err(Status::failed_precondition(
+                "you already have the requested access",
+            ));
+        }
+
+        let row = self
+            .requests
+            .create(&NewAccessRequest {
+                tenant_id: ctx.tenant_id,
+                bookmark_id: req.bookmark_id,
+                requester_id: ctx.user_id.clone(),
+                relation: relation.as_str().to_string(),
+                message: req.message,
+            })
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| {
+                Status::already_exists("an access request for this bookmark is already pending")
+            })?;
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            request_id = row.id,
+            bookmark_id = %row.bookmark_id,
+            by = %ctx.user_id,
+            "access requested"
+        );
+        self.notify_owners(row.clone()).await;
+
+        Ok(Response::new(request_to_proto(row)))
+    }
+
+    async fn resolve_access_request(
+        &self,
+        request: Request<ResolveAccessRequestRequest>,
+    ) -> Result<Response<AccessRequest>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let id = parse_request_id(req.id)?;
+        check_text("note", &req.note)?;
+        let expires_at = req
+            .expires_at
+            .as_ref()
+            .map(timestamp_to_datetime)
+            .transpose()?;
+        if expires_at.is_some_and(|t| t <= Utc::now()) {
+            return Err(Status::invalid_argument("expires_at must be in the future"));
+        }
+
+        let pending = self
+            .requests
+            .get(ctx.tenant_id, id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("access request not found"))?;
+        if pending.status != STATUS_PENDING {
+            return Err(Status::failed_precondition("access request is already resolved"));
+        }
+        self.checker
+            .can_share(ctx.tenant_id, &ctx.user_id, &pending.bookmark_id, &ctx.role_ids)
+            .await?;
+
+        let (status, permission_id) = if req.approve {
+            let relation = Relation::from_str(&pending.relation).ok_or_else(|| {
+                Status::internal(format!("access request {id} has an invalid relation"))
+            })?;
+            let grant = self
+                .checker
+                .engine()
+                .store()
+                .create_permission(
+                    ctx.tenant_id,
+                    ResourceType::Bookmark,
+                    &pending.bookmark_id,
+                    relation,
+                    SubjectType::User,
+                    &pending.requester_id,
+                    ctx.user_id.parse::<i32>().ok(),
+                    expires_at,
+                )
+                .await
+                .map_err(ServiceError::database)?;
+            self.events
+                .publish(Event::permission_granted(&ctx.user_id, &grant));
+            (STATUS_APPROVED, Some(grant.id))
+        } else {
+            (STATUS_DENIED, None)
+        };
+
+        let row = self
+            .requests
+            .resolve(ctx.tenant_id, id, status, &ctx.user_id, &req.note, permission_id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::failed_precondition("access request is already resolved"))?;
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            request_id = id,
+            status,
+            by = %ctx.user_id,
+            "access request resolved"
+        );
+
+        Ok(Response::new(request_to_proto(row)))
+    }
+
+    async fn list_access_requests(
+        &self,
+        request: Request<ListAccessRequestsRequest>,
+    ) -> Result<Response<ListAccessRequestsResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let mut filter = AccessRequestFilter {
+            bookmark_id: req.bookmark_id.as_deref().filter(|s| !s.is_empty()),
+            status: status_from_proto(req.status)?,
+            ..Default::default()
+        };
+        match AccessRequestScope::try_from(req.scope) {
+            Ok(AccessRequestScope::Unspecified | AccessRequestScope::Mine) => {
+                filter.requester_id = Some(&ctx.user_id);
+            }
+            Ok(AccessRequestScope::ToReview) => filter.owned_by = Some(&ctx.user_id),
+            Ok(AccessRequestScope::All) => {
+                if !ctx.is_tenant_manager() {
+                    return Err(Status::permission_denied("tenant manager role required"));
+                }
+            }
+            Err(_) => return Err(Status::invalid_argument("invalid scope")),
+        }
+        let page_size = req.page_size.unwrap_or(20).clamp(1, 100);
+        let after = page_token::decode(&req.page_token)?
+            .map(|c| {
+                c.id.parse::<i32>()
+                    .map(|id| (c.create_time, id))
+                    .map_err(|_| Status::invalid_argument("invalid page_token"))
+            })
+            .transpose()?;
+
+        let mut rows = self
+            .requests
+            .list(ctx.tenant_id, &filter, after, page_size)
+            .await
+            .map_err(ServiceError::database)?;
+
+        let next_page_token = if rows.len() > page_size as usize {
+            rows.truncate(page_size as usize);
+            rows.last()
+                .map(|r| page_token::encode(r.create_time, &r.id.to_string()))
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+
+        Ok(Response::new(ListAccessRequestsResponse {
+            requests: rows.into_iter().map(request_to_proto).collect(),
+            next_page_token,
+        }))
+    }
+}
+
+fn to_timestamp(ts: chrono::DateTime<chrono::Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: ts.timestamp(),
+        nanos: ts.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn request_to_proto(row: AccessRequestRow) -> AccessRequest {
+    let status = match row.status.as_str() {
+        STATUS_PENDING => AccessRequestStatus::Pending,
+        STATUS_APPROVED => AccessRequestStatus::Approved,
+        STATUS_DENIED => AccessRequestStatus::Denied,
+        _ => AccessRequestStatus::Unspecified,
+    };
+    AccessRequest {
+        id: row.id as u32,
+        bookmark_id: row.bookmark_id,
+        requester_id: row.requester_id,
+        relation: Relation::from_str(&row.relation).map_or(0, Relation::to_proto),
+        message: row.message,
+        status: status as i32,
+        resolved_by: row.resolved_by.unwrap_or_default(),
+        resolution_note: row.resolution_note,
+        create_time: Some(to_timestamp(row.create_time)),
+        resolve_time: row.resolve_time.map(to_timestamp),
+    }
+}
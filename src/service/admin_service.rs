@@ -0,0 +1,727 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::NaiveDate;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use crate::authz::cache::DecisionCache;
+use crate::client::admin_client::AdminClient;
+use crate::data::audit_repo::AuditStore;
+use crate::data::group_repo::GroupStore;
+use crate::data::permission_repo::PermissionStore;
+use crate::data::stats_repo::{DailyStatsRow, StatsStore};
+use crate::data::user_data_repo::{BookmarkDisposition, UserDataStore, UserRecordKind};
+use crate::log_level::{self, LogLevel};
+use crate::metrics::slo::SloTracker;
+use crate::middleware::panic::spawn_stream;
+use crate::service::access_report::{self, AccessGraph, AccessRow};
+use crate::service::audit_log::{write_csv, write_ndjson, CSV_HEADER};
+use crate::service::bookmark_service::proto::{
+    bookmark_admin_service_server::BookmarkAdminService, AccessReportChunk, AuditExportChunk,
+    AuditExportFormat, BookmarkDisposition as ProtoBookmarkDisposition, DailyUsage, DomainCount,
+    EraseUserRequest, EraseUserResponse, ExportAuditEventsRequest, ExportUserDataRequest,
+    GenerateAccessReportRequest, GetLogLevelRequest, GetSloStatusRequest, GetSloStatusResponse,
+    GetTenantStatsRequest, GetUsageReportRequest, LogLevelStatus, SetLogLevelRequest, SloStatus,
+    TenantStats, UsageCounts, UsageReport, UserDataChunk,
+};
+use crate::service::bookmark_service::{cipher_status, timestamp_to_datetime};
+use crate::service::context_helper::{extract_context, RequestContext};
+use crate::service::error::ServiceError;
+use crate::service::field_cipher::{FieldCipher, SealedField};
+
+/// Audit rows fetched per page while streaming an export.
+const AUDIT_PAGE_SIZE: i64 = 1000;
+/// Flush an export chunk once it reaches this many bytes.
+const AUDIT_CHUNK_BYTES: usize = 64 * 1024;
+/// Days in a usage report when no start date is given.
+const DEFAULT_REPORT_DAYS: u64 = 30;
+/// Longest usage report, in days.
+const MAX_REPORT_DAYS: i64 = 366;
+/// Domains kept in a report's merged totals.
+const REPORT_TOP_DOMAINS: usize = 10;
+
+pub struct AdminServiceImpl {
+    slo: Arc<SloTracker>,
+    audit: Arc<dyn AuditStore>,
+    stats: Arc<dyn StatsStore>,
+    permissions: Arc<dyn PermissionStore>,
+    groups: Arc<dyn GroupStore>,
+    /// Names users and roles in access reports when available.
+    admin_client: Option<AdminClient>,
+    user_data: Arc<dyn UserDataStore>,
+    /// Opens private bookmarks' sealed values for data exports.
+    cipher: FieldCipher,
+    /// Cleared after an erasure removes tuples behind the store's back.
+    authz_cache: DecisionCache,
+    log_level: LogLevel,
+}
+
+impl AdminServiceImpl {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        slo: Arc<SloTracker>,
+        audit: Arc<dyn AuditStore>,
+        stats: Arc<dyn StatsStore>,
+        permissions: Arc<dyn PermissionStore>,
+        groups: Arc<dyn GroupStore>,
+        admin_client: Option<AdminClient>,
+        user_data: Arc<dyn UserDataStore>,
+        cipher: FieldCipher,
+        authz_cache: DecisionCache,
+        log_level: LogLevel,
+    ) -> Self {
+        Self {
+            slo,
+            audit,
+            stats,
+            permissions,
+            groups,
+            admin_client,
+            user_data,
+            cipher,
+            authz_cache,
+            log_level,
+        }
+    }
+}
+
+/// Open the sealed URLs and notes of an exported private bookmark or revision,
+/// so the export carries what the user saved rather than ciphertext.
+async fn reveal_record(
+    cipher: &FieldCipher,
+    kind: UserRecordKind,
+    record: &mut serde_json::Value,
+) -> anyhow::Result<()> {
+    let (id_column, fields): (&str, &[(&str, SealedField)]) = match kind {
+        UserRecordKind::Bookmark => (
+            "id",
+            &[
+                ("url", SealedField::Url),
+                ("original_url", SealedField::OriginalUrl),
+                ("notes", SealedField::Notes),
+            ],
+        ),
+        UserRecordKind::Revision => (
+            "bookmark_id",
+            &[
+                ("url", SealedField::Url),
+                ("original_url", SealedField::OriginalUrl),
+            ],
+        ),
+        _ => return Ok(()),
+    };
+    let Some(record) = record.as_object_mut() else {
+        return Ok(());
+    };
+    // SQLite rows carry booleans as integers.
+    let private = match record.get("is_private") {
+        Some(serde_json::Value::Bool(b)) => *b,
+        Some(serde_json::Value::Number(n)) => n.as_i64() != Some(0),
+        _ => false,
+    };
+    if !private {
+        return Ok(());
+    }
+    let tenant_id = record
+        .get("tenant_id")
+        .and_then(serde_json::Value::as_i64)
+        .context("record has no tenant_id")? as i32;
+    let bookmark_id: Uuid = record
+        .get(id_column)
+        .and_then(serde_json::Value::as_str)
+        .with_context(|| format!("record has no {id_column}"))?
+        .parse()?;
+    for &(column, field) in fields {
+        if let Some(serde_json::Value::String(value)) = record.get(column) {
+            let opened = cipher.open(tenant_id, bookmark_id, field, value).await?;
+            record.insert(column.to_string(), serde_json::Value::String(opened));
+        }
+    }
+    Ok(())
+}
+
+/// The tenant a per-tenant report covers: the caller's by default, which needs a
+/// tenant manager, or any other for a platform admin.
+fn report_tenant(ctx: &RequestContext, requested: Option<u32>) -> Result<i32, Status> {
+    match requested {
+        Some(tid) if tid as i32 != ctx.tenant_id => {
+            if !ctx.is_platform_admin() {
+                return Err(Status::permission_denied("platform admin role required"));
+            }
+            Ok(tid as i32)
+        }
+        _ => {
+            if !ctx.is_tenant_manager() {
+                return Err(Status::permission_denied("tenant manager role required"));
+            }
+            Ok(ctx.tenant_id)
+        }
+    }
+}
+
+/// The tenant a per-user operation acts in: the caller's by default, or any
+/// other for a platform admin.
+fn user_tenant(ctx: &RequestContext, requested: Option<u32>) -> Result<i32, Status> {
+    match requested {
+        Some(tid) if tid as i32 != ctx.tenant_id => {
+            if !ctx.is_platform_admin() {
+                return Err(Status::permission_denied("platform admin role required"));
+            }
+            Ok(tid as i32)
+        }
+        _ => Ok(ctx.tenant_id),
+    }
+}
+
+fn log_level_to_proto(status: log_level::LogLevelStatus) -> LogLevelStatus {
+    LogLevelStatus {
+        filter: status.filter,
+        configured_filter: status.configured,
+        revert_time: status.revert_time.map(|t| prost_types::Timestamp {
+            seconds: t.timestamp(),
+            nanos: t.timestamp_subsec_nanos() as i32,
+        }),
+        instance: crate::change_feed::instance_name().to_string(),
+    }
+}
+
+fn parse_date(field: &str, value: &str) -> Result<Option<NaiveDate>, Status> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| Status::invalid_argument(format!("{field} must be YYYY-MM-DD")))
+}
+
+fn daily_usage(row: DailyStatsRow) -> DailyUsage {
+    DailyUsage {
+        date: row.day.to_string(),
+        counts: Some(UsageCounts {
+            creates: row.creates as u64,
+            edits: row.edits as u64,
+            searches: row.searches as u64,
+            visits: row.visits as u64,
+            top_domains: row
+                .top_domains
+                .0
+                .into_iter()
+                .map(|d| DomainCount {
+                    domain: d.domain,
+                    count: d.count as u64,
+                })
+                .collect(),
+        }),
+    }
+}
+
+/// Sum the days' counts, merging their top domains.
+fn usage_totals(days: &[DailyUsage]) -> UsageCounts {
+    let mut totals = UsageCounts::default();
+    let mut domains: HashMap<&str, u64> = HashMap::new();
+    for counts in days.iter().filter_map(|d| d.counts.as_ref()) {
+        totals.creates += counts.creates;
+        totals.edits += counts.edits;
+        totals.searches += counts.searches;
+        totals.visits += counts.visits;
+        for d in &counts.top_domains {
+            *domains.entry(&d.domain).or_default() += d.count;
+        }
+    }
+    let mut domains: Vec<(&str, u64)> = domains.into_iter().collect();
+    domains.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    totals.top_domains = domains
+        .into_iter()
+        .take(REPORT_TOP_DOMAINS)
+        .map(|(domain, count)| DomainCount {
+            domain: domain.to_string(),
+            count,
+        })
+        .collect();
+    totals
+}
+
+#[tonic::async_trait]
+impl BookmarkAdminService for AdminServiceImpl {
+    type ExportAuditEventsStream = ReceiverStream<Result<AuditExportChunk, Status>>;
+    type GenerateAccessReportStream = ReceiverStream<Result<AccessReportChunk, Status>>;
+    type ExportUserDataStream = ReceiverStream<Result<UserDataChunk, Status>>;
+
+    async fn get_slo_status(
+        &self,
+        request: Request<GetSloStatusRequest>,
+    ) -> Result<Response<GetSloStatusResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        if !ctx.is_platform_admin() {
+            return Err(Status::permission_denied("platform admin role required"));
+        }
+        let req = request.into_inner();
+
+        let statuses = self
+            .slo
+            .status(req.method.as_deref())
+            .into_iter()
+            .map(|s| SloStatus {
+                method: s.method,
+                latency_target_ms: s.objective.latency_ms,
+                latency_target: s.objective.latency_target,
+                availability_target: s.objective.availability_target,
+                requests_5m: s.requests_5m,
+                requests_1h: s.requests_1h,
+                availability_1h: s.availability_1h,
+                latency_compliance_1h: s.latency_compliance_1h,
+                availability_burn_rate_5m: s.availability_burn_rate_5m,
+                availability_burn_rate_1h: s.availability_burn_rate_1h,
+                latency_burn_rate_5m: s.latency_burn_rate_5m,
+                latency_burn_rate_1h: s.latency_burn_rate_1h,
+                alert: s.alert.as_str().to_string(),
+            })
+            .collect();
+
+        Ok(Response::new(GetSloStatusResponse { statuses }))
+    }
+
+    async fn export_audit_events(
+        &self,
+        request: Request<ExportAuditEventsRequest>,
+    ) -> Result<Response<Self::ExportAuditEventsStream>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        // Tenant managers export their own tenant; platform admins any (0 = all).
+        let tenant_filter = match req.tenant_id {
+            Some(0) if ctx.is_platform_admin() => None,
+            Some(tid) if tid as i32 != ctx.tenant_id => {
+                if !ctx.is_platform_admin() {
+                    return Err(Status::permission_denied("platform admin role required"));
+                }
+                Some(tid as i32)
+            }
+            _ => {
+                if !ctx.is_tenant_manager() {
+                    return Err(Status::permission_denied("tenant manager role required"));
+                }
+                Some(ctx.tenant_id)
+            }
+        };
+        let since = req.since.as_ref().map(timestamp_to_datetime).transpose()?;
+        let until = req.until.as_ref().map(timestamp_to_datetime).transpose()?;
+        let csv = AuditExportFormat::try_from(req.format) == Ok(AuditExportFormat::Csv);
+
+        tracing::info!(
+            tenant_id = ?tenant_filter,
+            by = %ctx.user_id,
+            csv,
+            "exporting audit events"
+        );
+
+        let (tx, rx) = mpsc::channel(4);
+        let repo = self.audit.clone();
+        let method = "/bookmark.service.v1.BookmarkAdminService/ExportAuditEvents";
+        spawn_stream(method, tx.clone(), async move {
+            let mut buf = Vec::with_capacity(AUDIT_CHUNK_BYTES);
+            if csv {
+                buf.extend_from_slice(CSV_HEADER.as_bytes());
+            }
+            let mut sequence = 0;
+            let mut total = 0;
+            let mut after_id = 0;
+            loop {
+                let rows = match repo
+                    .list_page(tenant_filter, since, until, after_id, AUDIT_PAGE_SIZE)
+                    .await
+                {
+                    Ok(rows) => rows,
+                    Err(e) => {
+                        let _ = tx
+                            .send(Err(ServiceError::database(e).into()))
+                            .await;
+                        return;
+                    }
+                };
+                let done = (rows.len() as i64) < AUDIT_PAGE_SIZE;
+                for row in &rows {
+                    if csv {
+                        write_csv(&mut buf, row);
+                    } else {
+                        write_ndjson(&mut buf, row);
+                    }
+                    total += 1;
+                    after_id = row.id;
+
+                    if buf.len() >= AUDIT_CHUNK_BYTES {
+                        let chunk = AuditExportChunk {
+                            data: std::mem::take(&mut buf),
+                            sequence,
+                            last: false,
+                            total: 0,
+                        };
+                        sequence += 1;
+                        // The receiver is gone when the client cancels the stream.
+                        if tx.send(Ok(chunk)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                if done {
+                    break;
+                }
+            }
+
+            let _ = tx
+                .send(Ok(AuditExportChunk {
+                    data: buf,
+                    sequence,
+                    last: true,
+                    total,
+                }))
+                .await;
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+    async fn get_tenant_stats(
+        &self,
+        request: Request<GetTenantStatsRequest>,
+    ) -> Result<Response<TenantStats>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let tenant_id = report_tenant(&ctx, req.tenant_id)?;
+
+        let stats = self
+            .stats
+            .tenant_stats(tenant_id)
+            .await
+            .map_err(ServiceError::database)?;
+
+        Ok(Response::new(TenantStats {
+            tenant_id: tenant_id as u32,
+            bookmark_count: stats.bookmark_count as u64,
+            tag_count: stats.tag_count as u64,
+            users_with_grants: stats.users_with_grants as u64,
+            permissions_by_relation: stats
+                .permissions_by_relation
+                .into_iter()
+                .map(|(relation, count)| (relation, count as u64))
+                .collect(),
+            bookmark_bytes: stats.bookmark_bytes as u64,
+            archive_count: stats.archive_count as u64,
+            archive_bytes: stats.archive_bytes as u64,
+        }))
+    }
+
+    async fn get_usage_report(
+        &self,
+        request: Request<GetUsageReportRequest>,
+    ) -> Result<Response<UsageReport>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let tenant_id = report_tenant(&ctx, req.tenant_id)?;
+        let end = parse_date("end_date", &req.end_date)?
+            .unwrap_or_else(|| chrono::Utc::now().date_naive());
+        let start = parse_date("start_date", &req.start_date)?
+            .unwrap_or_else(|| end - chrono::Days::new(DEFAULT_REPORT_DAYS - 1));
+        if start > end {
+            return Err(Status::invalid_argument("start_date is after end_date"));
+        }
+        if (end - start).num_days() >= MAX_REPORT_DAYS {
+            return Err(Status::invalid_argument(format!(
+                "a report covers at most {MAX_REPORT_DAYS} days"
+            )));
+        }
+
+        let days: Vec<DailyUsage> = self
+            .stats
+            .daily_stats(tenant_id, start, end)
+            .await
+            .map_err(ServiceError::database)?
+            .into_iter()
+            .map(daily_usage)
+            .collect();
+        let totals = usage_totals(&days);
+
+        Ok(Response::new(UsageReport {
+            tenant_id: tenant_id as u32,
+            start_date: start.to_string(),
+            end_date: end.to_string(),
+            days,
+            totals: Some(totals),
+        }))
+    }
+
+    async fn generate_access_report(
+        &self,
+        request: Request<GenerateAccessReportRequest>,
+    ) -> Result<Response<Self::GenerateAccessReportStream>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let tenant_id = report_tenant(&ctx, req.tenant_id)?;
+        let csv = AuditExportFormat::try_from(req.format) == Ok(AuditExportFormat::Csv);
+
+        tracing::info!(tenant_id, by = %ctx.user_id, csv, "generating access report");
+
+        let (tx, rx) = mpsc::channel(4);
+        let permissions = self.permissions.clone();
+        let groups = self.groups.clone();
+        let directory = self.admin_client.clone();
+        let method = "/bookmark.service.v1.BookmarkAdminService/GenerateAccessReport";
+        spawn_stream(method, tx.clone(), async move {
+            let graph =
+                match AccessGraph::load(permissions.as_ref(), groups.as_ref(), tenant_id).await {
+                    Ok(graph) => graph,
+                    Err(e) => {
+                        let _ = tx.send(Err(ServiceError::database(e).into())).await;
+                        return;
+                    }
+                };
+
+            let mut buf = Vec::with_capacity(AUDIT_CHUNK_BYTES);
+            if csv {
+                buf.extend_from_slice(access_report::CSV_HEADER.as_bytes());
+            }
+            let mut sequence = 0;
+            let mut total = 0;
+            for subject in graph.subjects(tenant_id, directory.as_ref()).await {
+                for (bookmark_id, (relation, via)) in &graph.reach(&subject) {
+                    let row = AccessRow {
+                        subject_type: subject.subject_type,
+                        subject_id: &subject.id,
+                        subject_name: &subject.name,
+                        bookmark_id,
+                        relation: *relation,
+                        via,
+                    };
+                    if csv {
+                        access_report::write_csv(&mut buf, &row);
+                    } else {
+                        access_report::write_ndjson(&mut buf, &row);
+                    }
+                    total += 1;
+
+                    if buf.len() >= AUDIT_CHUNK_BYTES {
+                        let chunk = AccessReportChunk {
+                            data: std::mem::take(&mut buf),
+                            sequence,
+                            last: false,
+                            total: 0,
+                        };
+                        sequence += 1;
+                        if tx.send(Ok(chunk)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            let _ = tx
+                .send(Ok(AccessReportChunk {
+                    data: buf,
+                    sequence,
+                    last: true,
+                    total,
+                }))
+                .await;
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn export_user_data(
+        &self,
+        request: Request<ExportUserDataRequest>,
+    ) -> Result<Response<Self::ExportUserDataStream>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        if req.user_id.is_empty() {
+            return Err(Status::invalid_argument("user_id is required"));
+        }
+        if req.user_id != ctx.user_id && !ctx.is_platform_admin() {
+            return Err(Status::permission_denied(
+                "only the user or a platform admin can export a user's data",
+            ));
+        }
+        let tenant_id = user_tenant(&ctx, req.tenant_id)?;
+
+        tracing::info!(tenant_id, user_id = %req.user_id, by = %ctx.user_id, "exporting user data");
+
+        let (tx, rx) = mpsc::channel(4);
+        let user_data = self.user_data.clone();
+        let cipher = self.cipher.clone();
+        let user_id = req.user_id;
+        let method = "/bookmark.service.v1.BookmarkAdminService/ExportUserData";
+        spawn_stream(method, tx.clone(), async move {
+            let mut buf = Vec::with_capacity(AUDIT_CHUNK_BYTES);
+            let header = serde_json::json!({
+                "type": "export",
+                "userId": user_id,
+                "tenantId": tenant_id,
+                "exportTime": chrono::Utc::now(),
+            });
+            let _ = serde_json::to_writer(&mut buf, &header);
+            buf.push(b'\n');
+
+            let mut sequence = 0;
+            let mut total = 0;
+            for &kind in UserRecordKind::ALL {
+                let mut offset = 0;
+                loop {
+                    let records = match user_data
+                        .user_records(tenant_id, &user_id, kind, offset, AUDIT_PAGE_SIZE)
+                        .await
+                    {
+                        Ok(records) => records,
+                        Err(e) => {
+                            let _ = tx.send(Err(ServiceError::database(e).into())).await;
+                            return;
+                        }
+                    };
+                    let fetched = records.len() as i64;
+                    for mut data in records {
+                        if let Err(e) = reveal_record(&cipher, kind, &mut data).await {
+                            let _ = tx.send(Err(cipher_status(e))).await;
+                            return;
+                        }
+                        let line = serde_json::json!({ "type": kind.as_str(), "data": data });
+                        let _ = serde_json::to_writer(&mut buf, &line);
+                        buf.push(b'\n');
+                        total += 1;
+                    }
+
+                    if buf.len() >= AUDIT_CHUNK_BYTES {
+                        let chunk = UserDataChunk {
+                            data: std::mem::take(&mut buf),
+                            sequence,
+                            last: false,
+                            total: 0,
+                        };
+                        sequence += 1;
+                        if tx.send(Ok(chunk)).await.is_err() {
+                            return;
+                        }
+                    }
+                    if fetched < AUDIT_PAGE_SIZE {
+                        break;
+                    }
+                    offset += fetched;
+                }
+            }
+
+            let _ = tx
+                .send(Ok(UserDataChunk {
+                    data: buf,
+                    sequence,
+                    last: true,
+                    total,
+                }))
+                .await;
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn erase_user(
+        &self,
+        request: Request<EraseUserRequest>,
+    ) -> Result<Response<EraseUserResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        if !ctx.is_platform_admin() {
+            return Err(Status::permission_denied("platform admin role required"));
+        }
+        if req.user_id.is_empty() {
+            return Err(Status::invalid_argument("user_id is required"));
+        }
+        let tenant_id = user_tenant(&ctx, req.tenant_id)?;
+        let disposition = match ProtoBookmarkDisposition::try_from(req.bookmarks) {
+            Ok(ProtoBookmarkDisposition::Delete) => BookmarkDisposition::Delete,
+            Ok(ProtoBookmarkDisposition::Reassign) => {
+                let new_owner = req.reassign_to.parse::<i32>().map_err(|_| {
+                    Status::invalid_argument("reassign_to must be a numeric user id")
+                })?;
+                if req.reassign_to == req.user_id {
+                    return Err(Status::invalid_argument(
+                        "reassign_to must differ from user_id",
+                    ));
+                }
+                BookmarkDisposition::Reassign(new_owner)
+            }
+            _ => return Err(Status::invalid_argument("bookmarks disposition is required")),
+        };
+
+        let summary = self
+            .user_data
+            .erase_user(tenant_id, &req.user_id, disposition)
+            .await
+            .map_err(ServiceError::database)?;
+        self.authz_cache.invalidate_all();
+
+        tracing::info!(
+            tenant_id,
+            user_id = %req.user_id,
+            by = %ctx.user_id,
+            ?summary,
+            "user erased"
+        );
+
+        Ok(Response::new(EraseUserResponse {
+            bookmarks_deleted: summary.bookmarks_deleted,
+            bookmarks_reassigned: summary.bookmarks_reassigned,
+            permissions_removed: summary.permissions_removed,
+            group_memberships_removed: summary.group_memberships_removed,
+            flags_removed: summary.flags_removed,
+            access_requests_removed: summary.access_requests_removed,
+            audit_events_anonymized: summary.audit_events_anonymized,
+            references_anonymized: summary.references_anonymized,
+        }))
+    }
+
+    async fn set_log_level(
+        &self,
+        request: Request<SetLogLevelRequest>,
+    ) -> Result<Response<LogLevelStatus>, Status> {
+        let ctx = extract_context(&request)?;
+        if !ctx.is_platform_admin() {
+            return Err(Status::permission_denied("platform admin role required"));
+        }
+        let req = request.into_inner();
+
+        let status = if req.filter.is_empty() {
+            self.log_level.clear_override()
+        } else {
+            let revert_after = Duration::from_secs(req.revert_after_seconds.into());
+            if revert_after > log_level::MAX_REVERT_AFTER {
+                return Err(Status::invalid_argument(format!(
+                    "revert_after_seconds must be at most {}",
+                    log_level::MAX_REVERT_AFTER.as_secs()
+                )));
+            }
+            self.log_level
+                .set_override(&req.filter, (!revert_after.is_zero()).then_some(revert_after))
+        }
+        .map_err(|e| Status::invalid_argument(format!("invalid filter: {e}")))?;
+        tracing::info!(by = %ctx.user_id, filter = %status.filter, "log level set");
+
+        Ok(Response::new(log_level_to_proto(status)))
+    }
+
+    async fn get_log_level(
+        &self,
+        request: Request<GetLogLevelRequest>,
+    ) -> Result<Response<LogLevelStatus>, Status> {
+        let ctx = extract_context(&request)?;
+        if !ctx.is_platform_admin() {
+            return Err(Status::permission_denied("platform admin role required"));
+        }
+        Ok(Response::new(log_level_to_proto(self.log_level.status())))
+    }
+}
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use crate::data::api_key_repo::{ApiKeyRow, ApiKeyStore};
+use crate::middleware::api_key::{hash_key, ApiKeyAuthenticator};
+use crate::service::bookmark_service::proto::{
+    bookmark_api_key_service_server::BookmarkApiKeyService, ApiKey, CreateApiKeyRequest,
+    CreateApiKeyResponse, ListApiKeysRequest, ListApiKeysResponse, RevokeApiKeyRequest,
+};
+use crate::service::bookmark_service::timestamp_to_datetime;
+use crate::service::context_helper::{extract_context, RequestContext};
+use crate::service::error::ServiceError;
+
+/// Prefix on every secret so leaked keys are easy to recognise and scan for.
+const KEY_PREFIX: &str = "tbk_";
+/// Characters of the secret stored in clear for display.
+const DISPLAY_PREFIX_LEN: usize = 12;
+const MAX_NAME_LEN: usize = 255;
+
+pub struct ApiKeyServiceImpl {
+    repo: Arc<dyn ApiKeyStore>,
+    auth: Arc<ApiKeyAuthenticator>,
+}
+
+impl ApiKeyServiceImpl {
+    pub fn new(repo: Arc<dyn ApiKeyStore>, auth: Arc<ApiKeyAuthenticator>) -> Self {
+        Self { repo, auth }
+    }
+}
+
+/// Only tenant managers using their own credentials may manage keys; a key
+/// must not be able to mint or revoke keys.
+fn require_key_manager(ctx: &RequestContext) -> Result<(), Status> {
+    if ctx.username.starts_with("apikey:") {
+        return Err(Status::permission_denied(
+            "API keys cannot be managed with an API key",
+        ));
+    }
+    if !ctx.is_tenant_manager() {
+        return Err(Status::permission_denied("tenant manager role required"));
+    }
+    Ok(())
+}
+
+/// 32 random bytes from two v4 UUIDs (OS CSPRNG), URL-safe encoded.
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+    bytes[16..].copy_from_slice(Uuid::new_v4().as_bytes());
+    format!("{KEY_PREFIX}{}", URL_SAFE_NO_PAD.encode(bytes))
+}
+
+#[tonic::async_trait]
+impl BookmarkApiKeyService for ApiKeyServiceImpl {
+    async fn create_api_key(
+        &self,
+        request: Request<CreateApiKeyRequest>,
+    ) -> Result<Response<CreateApiKeyResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        require_key_manager(&ctx)?;
+        let req = request.into_inner();
+
+        let name = req.name.trim();
+        if name.is_empty() {
+            return Err(Status::invalid_argument("name is required"));
+        }
+        if name.len() > MAX_NAME_LEN {
+            return Err(Status::invalid_argument("name is too long"));
+        }
+        // Keys cannot escalate beyond the roles of whoever minted them.
+        if !ctx.is_platform_admin() {
+            if let Some(role) = req.roles.iter().find(|r| !ctx.role_ids.contains(r)) {
+                return Err(Status::permission_denied(format!(
+                    "cannot grant role {role:?} you do not hold"
+                )));
+            }
+        }
+        let expires_at = req
+            .expires_at
+            .as_ref()
+            .map(timestamp_to_datetime)
+            .transpose()?;
+        if expires_at.is_some_and(|t| t <= chrono::Utc::now()) {
+            return Err(Status::invalid_argument("expires_at must be in the future"));
+        }
+
+        let secret = generate_secret();
+        let row = self
+            .repo
+            .create(
+                Uuid::new_v4(),
+                ctx.tenant_id,
+                name,
+                &secret[..DISPLAY_PREFIX_LEN],
+                &hash_key(&secret),
+                &req.roles,
+                &ctx.user_id,
+                expires_at,
+            )
+            .await
+            .map_err(ServiceError::database)?;
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            key_id = %row.id,
+            by = %ctx.user_id,
+            "API key created"
+        );
+
+        Ok(Response::new(CreateApiKeyResponse {
+            api_key: Some(row_to_proto(row)),
+            key: secret,
+        }))
+    }
+
+    async fn list_api_keys(
+        &self,
+        request: Request<ListApiKeysRequest>,
+    ) -> Result<Response<ListApiKeysResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        require_key_manager(&ctx)?;
+        let req = request.into_inner();
+
+        let rows = self
+            .repo
+            .list(ctx.tenant_id, req.include_revoked)
+            .await
+            .map_err(ServiceError::database)?;
+
+        Ok(Response::new(ListApiKeysResponse {
+            api_keys: rows.into_iter().map(row_to_proto).collect(),
+        }))
+    }
+
+    async fn revoke_api_key(
+        &self,
+        request: Request<RevokeApiKeyRequest>,
+    ) -> Result<Response<()>, Status> {
+        let ctx = extract_context(&request)?;
+        require_key_manager(&ctx)?;
+        let req = request.into_inner();
+
+        let id = Uuid::parse_str(&req.id).map_err(|_| Status::invalid_argument("invalid UUID"))?;
+        let revoked = self
+            .repo
+            .revoke(ctx.tenant_id, id)
+            .await
+            .map_err(ServiceError::database)?;
+        if !revoked {
+            return Err(Status::not_found("API key not found"));
+        }
+        self.auth.evict(id);
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            key_id = %id,
+            by = %ctx.user_id,
+            "API key revoked"
+        );
+
+        Ok(Response::new(()))
+    }
+}
+
+fn to_timestamp(ts: chrono::DateTime<chrono::Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: ts.timestamp(),
+        nanos: ts.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn row_to_proto(row: ApiKeyRow) -> ApiKey {
+    ApiKey {
+        id: row.id.to_string(),
+        name: row.name,
+        key_prefix: row.key_prefix,
+        roles: row.roles,
+        created_by: row.created_by,
+        create_time: Some(to_timestamp(row.create_time)),
+        expires_at: row.expires_at.map(to_timestamp),
+        revoked_at: row.revoked_at.map(to_timestamp),
+        last_used_at: row.last_used_at.map(to_timestamp),
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::{Arc, LazyLock};
+
+use regex::Regex;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::config::ArchiveConfig;
+use crate::data::archive_repo::ArchiveStore;
+use crate::service::page_metadata::{decode_entities, MetadataFetcher};
+
+const HIDDEN_TAGS: &str = "script|style|noscript|template|svg|head";
+
+static HIDDEN_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"(?is)<({HIDDEN_TAGS})\b.*?</({HIDDEN_TAGS})>")).unwrap()
+});
+static BLOCK_END_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)<br\s*/?>|</(p|div|li|tr|h[1-6]|section|article|blockquote|pre)>").unwrap()
+});
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+
+/// Snapshot format stored for archived pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Text,
+    Html,
+}
+
+impl ArchiveFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Html => "html",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "text" => Some(Self::Text),
+            "html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+/// Stores a snapshot of a bookmarked page so it stays readable if the page goes away.
+#[derive(Clone)]
+pub struct Archiver {
+    repo: Arc<dyn ArchiveStore>,
+    fetcher: MetadataFetcher,
+    format: ArchiveFormat,
+    max_bytes: usize,
+    /// Live `archive.enabled`; see `ConfigWatcher`.
+    enabled: watch::Receiver<bool>,
+}
+
+impl Archiver {
+    pub fn new(
+        repo: Arc<dyn ArchiveStore>,
+        fetcher: MetadataFetcher,
+        cfg: &ArchiveConfig,
+        enabled: watch::Receiver<bool>,
+    ) -> anyhow::Result<Self> {
+        let format = ArchiveFormat::from_str(&cfg.format)
+            .ok_or_else(|| anyhow::anyhow!("invalid archive format {:?}", cfg.format))?;
+        Ok(Self {
+            repo,
+            fetcher,
+            format,
+            max_bytes: cfg.max_bytes,
+            enabled,
+        })
+    }
+
+    pub fn repo(&self) -> &dyn ArchiveStore {
+        self.repo.as_ref()
+    }
+
+    /// Snapshot the page in the background; failures are logged and otherwise ignored.
+    pub fn spawn_snapshot(&self, tenant_id: i32, bookmark_id: Uuid, url: String) {
+        if !*self.enabled.borrow() {
+            return;
+        }
+        let archiver = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = archiver.snapshot(tenant_id, bookmark_id, &url).await {
+                tracing::warn!(
+                    bookmark_id = %bookmark_id,
+                    url = %url,
+                    error = %e,
+                    "page archival failed"
+                );
+            }
+        });
+    }
+
+    async fn snapshot(&self, tenant_id: i32, bookmark_id: Uuid, url: &str) -> Result<(), String> {
+        let (html, truncated) = self.fetcher.fetch_page(url, self.max_bytes).await?;
+        let text = extract_text(&html);
+        let content = match self.format {
+            ArchiveFormat::Html => &html,
+            ArchiveFormat::Text => &text,
+        };
+
+        self.repo
+            .upsert(
+                bookmark_id,
+                tenant_id,
+                url,
+                self.format.as_str(),
+                content,
+                &text,
+                truncated,
+            )
+            .await
+            .map_err(|e| format!("database error: {e}"))?;
+
+        tracing::debug!(bookmark_id = %bookmark_id, bytes = content.len(), "page archived");
+        Ok(())
+    }
+}
+
+/// Reduce an HTML page to readable text, keeping one line per block element.
+pub fn extract_text(html: &str) -> String {
+    let without_hidden = HIDDEN_RE.replace_all(html, " ");
+    let with_breaks = BLOCK_END_RE.replace_all(&without_hidden, "\n");
+    let text = TAG_RE.replace_all(&with_breaks, " ");
+
+    text.lines()
+        .map(decode_entities)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
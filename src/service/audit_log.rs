@@ -0,0 +1,200 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+use crate::config::{parse_duration, AuditConfig};
+use crate::data::audit_repo::{AuditEventRow, AuditStore, NewAuditEvent};
+use crate::redact;
+use crate::shutdown::ShutdownSignal;
+
+/// Events buffered between request handlers and the database writer.
+const QUEUE_CAPACITY: usize = 10_000;
+/// Maximum events written per INSERT.
+const WRITE_BATCH: usize = 500;
+/// Rows read per page when archiving before a prune.
+const ARCHIVE_PAGE: i64 = 1000;
+
+/// Persists audit events without blocking the request path: events are queued
+/// and written in batches by a background task.
+#[derive(Clone)]
+pub struct AuditLog {
+    tx: Option<mpsc::Sender<NewAuditEvent>>,
+}
+
+impl AuditLog {
+    pub fn disabled() -> Self {
+        Self { tx: None }
+    }
+
+    /// Start the writer. It exits once every clone of the returned log has been
+    /// dropped and the queue is flushed, so its handle can be awaited on shutdown.
+    pub fn spawn(
+        repo: Arc<dyn AuditStore>,
+        cfg: &AuditConfig,
+    ) -> (Self, Option<tokio::task::JoinHandle<()>>) {
+        if !cfg.enabled {
+            return (Self::disabled(), None);
+        }
+
+        let (tx, mut rx) = mpsc::channel::<NewAuditEvent>(QUEUE_CAPACITY);
+        let writer = tokio::spawn(async move {
+            let mut batch = Vec::with_capacity(WRITE_BATCH);
+            while let Some(event) = rx.recv().await {
+                batch.push(event);
+                while batch.len() < WRITE_BATCH {
+                    match rx.try_recv() {
+                        Ok(event) => batch.push(event),
+                        Err(_) => break,
+                    }
+                }
+                if let Err(e) = repo.insert_batch(&batch).await {
+                    tracing::error!(error = %e, dropped = batch.len(), "failed to write audit events");
+                }
+                batch.clear();
+            }
+        });
+
+        (Self { tx: Some(tx) }, Some(writer))
+    }
+
+    /// Queue an event. Drops it with a warning if the writer has fallen behind.
+    pub fn record(&self, event: NewAuditEvent) {
+        let Some(tx) = &self.tx else { return };
+        if let Err(e) = tx.try_send(event) {
+            tracing::warn!(error = %e, "audit queue full, dropping event");
+        }
+    }
+}
+
+/// Periodically prune audit events older than `retention_days`, first appending
+/// them to an NDJSON file under `archive_dir` when one is configured.
+pub fn spawn_audit_retention(
+    repo: Arc<dyn AuditStore>,
+    cfg: &AuditConfig,
+    mut shutdown: ShutdownSignal,
+) -> Option<tokio::task::JoinHandle<()>> {
+    if !cfg.enabled || cfg.retention_days == 0 {
+        return None;
+    }
+    let interval = parse_duration(&cfg.prune_interval).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "invalid audit prune_interval, using 24h");
+        Duration::from_secs(24 * 3600)
+    });
+    let retention = chrono::Duration::days(cfg.retention_days as i64);
+    let archive_dir = cfg.archive_dir.clone().map(PathBuf::from);
+
+    Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.recv() => break,
+            }
+            let cutoff = chrono::Utc::now() - retention;
+            let result = match &archive_dir {
+                Some(dir) => archive_and_prune(repo.as_ref(), dir, cutoff).await,
+                None => repo.delete_older_than(cutoff, None).await,
+            };
+            match result {
+                Ok(pruned) => tracing::info!(
+                    service = "bookmark-service",
+                    pruned,
+                    cutoff = %cutoff.to_rfc3339(),
+                    "audit: old audit events pruned"
+                ),
+                Err(e) => tracing::error!(error = %e, "failed to prune audit events"),
+            }
+        }
+    }))
+}
+
+/// Copy expired events to `audit-<cutoff>.ndjson`, deleting each page only after
+/// it has been written and synced.
+async fn archive_and_prune(
+    repo: &dyn AuditStore,
+    dir: &std::path::Path,
+    cutoff: chrono::DateTime<chrono::Utc>,
+) -> anyhow::Result<u64> {
+    tokio::fs::create_dir_all(dir).await?;
+    let path = dir.join(format!("audit-{}.ndjson", cutoff.format("%Y%m%dT%H%M%SZ")));
+    let mut file: Option<tokio::fs::File> = None;
+
+    let mut pruned = 0;
+    loop {
+        let rows = repo
+            .list_page(None, None, Some(cutoff), 0, ARCHIVE_PAGE)
+            .await?;
+        let Some(last) = rows.last() else { break };
+        let last_id = last.id;
+
+        let file = match &mut file {
+            Some(f) => f,
+            None => file.insert(
+                tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .await?,
+            ),
+        };
+        let mut buf = Vec::new();
+        for row in &rows {
+            write_ndjson(&mut buf, row);
+        }
+        file.write_all(&buf).await?;
+        file.sync_data().await?;
+
+        pruned += repo.delete_older_than(cutoff, Some(last_id)).await?;
+        if (rows.len() as i64) < ARCHIVE_PAGE {
+            break;
+        }
+    }
+    Ok(pruned)
+}
+
+pub const CSV_HEADER: &str =
+    "id,tenant_id,user_id,operation,create_time,client,status_code,duration_ms\n";
+
+pub fn write_ndjson(buf: &mut Vec<u8>, row: &AuditEventRow) {
+    let value = serde_json::json!({
+        "id": row.id,
+        "tenantId": row.tenant_id,
+        "userId": redact::value(&row.user_id),
+        "operation": row.operation,
+        "createTime": row.create_time.to_rfc3339(),
+        "client": row.client,
+        "statusCode": row.status_code,
+        "durationMs": row.duration_ms,
+    });
+    // Serializing a `Value` into a Vec cannot fail.
+    let _ = serde_json::to_writer(&mut *buf, &value);
+    buf.push(b'\n');
+}
+
+pub fn write_csv(buf: &mut Vec<u8>, row: &AuditEventRow) {
+    let optional = |v: Option<i32>| v.map(|v| v.to_string()).unwrap_or_default();
+    let line = format!(
+        "{},{},{},{},{},{},{},{}\n",
+        row.id,
+        row.tenant_id,
+        csv_field(&redact::value(&row.user_id)),
+        csv_field(&row.operation),
+        row.create_time.to_rfc3339(),
+        csv_field(&row.client),
+        optional(row.status_code),
+        optional(row.duration_ms),
+    );
+    buf.extend_from_slice(line.as_bytes());
+}
+
+/// Quote a CSV field when it contains a delimiter, quote, or line break.
+pub(crate) fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
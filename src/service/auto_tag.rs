@@ -0,0 +1,108 @@
+use regex::{Regex, RegexBuilder};
+use url::Url;
+
+use crate::data::auto_tag_rule_repo::AutoTagRuleRow;
+
+/// Compiled size limit for a rule pattern, so one rule cannot make every
+/// create expensive.
+const PATTERN_SIZE_LIMIT: usize = 256 * 1024;
+
+/// Compile a path or title pattern: case-insensitive, searched anywhere in
+/// the text. An empty pattern matches everything and compiles to `None`.
+pub fn compile_pattern(pattern: &str) -> Result<Option<Regex>, regex::Error> {
+    if pattern.is_empty() {
+        return Ok(None);
+    }
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .size_limit(PATTERN_SIZE_LIMIT)
+        .build()
+        .map(Some)
+}
+
+struct CompiledRule {
+    domain: String,
+    path: Option<Regex>,
+    title: Option<Regex>,
+    tags: Vec<String>,
+}
+
+impl CompiledRule {
+    fn matches(&self, host: &str, path: &str, title: &str) -> bool {
+        let domain_ok = self.domain.is_empty()
+            || host == self.domain
+            || host
+                .strip_suffix(&self.domain)
+                .is_some_and(|sub| sub.ends_with('.'));
+        domain_ok
+            && self.path.as_ref().is_none_or(|re| re.is_match(path))
+            && self.title.as_ref().is_none_or(|re| re.is_match(title))
+    }
+}
+
+/// A tenant's auto-tagging rules, ready to run against bookmarks.
+pub struct AutoTagger {
+    rules: Vec<CompiledRule>,
+}
+
+impl AutoTagger {
+    /// Compile the enabled rules. Patterns are validated when rules are
+    /// saved; one that no longer compiles is skipped.
+    pub fn new(rows: &[AutoTagRuleRow]) -> Self {
+        let rules = rows
+            .iter()
+            .filter(|r| r.enabled)
+            .filter_map(|r| {
+                let path = compile_pattern(&r.path_pattern);
+                let title = compile_pattern(&r.title_pattern);
+                match (path, title) {
+                    (Ok(path), Ok(title)) => Some(CompiledRule {
+                        domain: r.domain.to_ascii_lowercase(),
+                        path,
+                        title,
+                        tags: r.tags.clone(),
+                    }),
+                    (Err(e), _) | (_, Err(e)) => {
+                        tracing::warn!(
+                            rule_id = r.id,
+                            error = %e,
+                            "skipping invalid auto-tag rule"
+                        );
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Add the tags of every matching rule that `tags` lacks. Returns whether
+    /// any tag was added.
+    pub fn apply(&self, url: &str, title: &str, tags: &mut Vec<String>) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let parsed = Url::parse(url).ok();
+        let host = parsed
+            .as_ref()
+            .and_then(|u| u.host_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let path = parsed.as_ref().map_or("", |u| u.path());
+
+        let mut added = false;
+        for rule in self.rules.iter().filter(|r| r.matches(&host, path, title)) {
+            for tag in &rule.tags {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                    added = true;
+                }
+            }
+        }
+        added
+    }
+}
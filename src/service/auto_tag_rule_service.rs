@@ -0,0 +1,366 @@
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use crate::data::auto_tag_rule_repo::{AutoTagRuleFields, AutoTagRuleRow, AutoTagRuleStore};
+use crate::data::bookmark_repo::{BookmarkChanges, BookmarkStore};
+use crate::events::{Event, EventPublisher, EventType};
+use crate::service::auto_tag::{compile_pattern, AutoTagger};
+use crate::service::bookmark_service::proto::{
+    auto_tag_rule_service_server::AutoTagRuleService, AutoTagRule, CreateAutoTagRuleRequest,
+    DeleteAutoTagRuleRequest, ListAutoTagRulesRequest, ListAutoTagRulesResponse,
+    ReapplyRulesRequest, ReapplyRulesResponse, UpdateAutoTagRuleRequest,
+};
+use crate::service::context_helper::{extract_context, RequestContext};
+use crate::service::error::ServiceError;
+
+const MAX_NAME_LEN: usize = 255;
+const MAX_PATTERN_LEN: usize = 1024;
+const MAX_RULE_TAGS: usize = 20;
+const MAX_RULES: usize = 200;
+
+/// Bookmarks read per page by `ReapplyRules`.
+const REAPPLY_PAGE_SIZE: u32 = 500;
+
+pub struct AutoTagRuleServiceImpl {
+    rules: Arc<dyn AutoTagRuleStore>,
+    bookmarks: Arc<dyn BookmarkStore>,
+    events: EventPublisher,
+}
+
+impl AutoTagRuleServiceImpl {
+    pub fn new(
+        rules: Arc<dyn AutoTagRuleStore>,
+        bookmarks: Arc<dyn BookmarkStore>,
+        events: EventPublisher,
+    ) -> Self {
+        Self {
+            rules,
+            bookmarks,
+            events,
+        }
+    }
+}
+
+fn require_tenant_manager(ctx: &RequestContext) -> Result<(), Status> {
+    if !ctx.is_tenant_manager() {
+        return Err(Status::permission_denied("tenant manager role required"));
+    }
+    Ok(())
+}
+
+/// Validate a rule's patterns and tags from a request.
+fn parse_fields(
+    domain: &str,
+    path_pattern: &str,
+    title_pattern: &str,
+    tags: &[String],
+    enabled: bool,
+) -> Result<AutoTagRuleFields, Status> {
+    let domain = domain.trim().trim_start_matches("*.").trim_matches('.').to_ascii_lowercase();
+    if domain.len() > MAX_NAME_LEN || domain.contains(['/', ':', ' ']) {
+        return Err(Status::invalid_argument("invalid domain"));
+    }
+    if domain.is_empty() && path_pattern.is_empty() && title_pattern.is_empty() {
+        return Err(Status::invalid_argument(
+            "one of domain, path_pattern or title_pattern is required",
+        ));
+    }
+    for (field, pattern) in [("path_pattern", path_pattern), ("title_pattern", title_pattern)] {
+        if pattern.len() > MAX_PATTERN_LEN {
+            return Err(Status::invalid_argument(format!("{field} is too long")));
+        }
+        compile_pattern(pattern)
+            .map_err(|e| Status::invalid_argument(format!("invalid {field}: {e}")))?;
+    }
+
+    let mut rule_tags: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(Status::invalid_argument("tags must not be empty"));
+        }
+        if !rule_tags.iter().any(|t| t == tag) {
+            rule_tags.push(tag.to_string());
+        }
+    }
+    if rule_tags.is_empty() {
+        return Err(Status::invalid_argument("at least one tag is required"));
+    }
+    if rule_tags.len() > MAX_RULE_TAGS {
+        return Err(Status::invalid_argument(format!(
+            "a rule adds at most {MAX_RULE_TAGS} tags"
+        )));
+    }
+
+    Ok(AutoTagRuleFields {
+        domain,
+        path_pattern: path_pattern.to_string(),
+        title_pattern: title_pattern.to_string(),
+        tags: rule_tags,
+        enabled,
+    })
+}
+
+fn parse_rule_id(id: u32) -> Result<i32, Status> {
+    i32::try_from(id)
+        .ok()
+        .filter(|id| *id > 0)
+        .ok_or_else(|| Status::invalid_argument("invalid rule id"))
+}
+
+#[tonic::async_trait]
+impl AutoTagRuleService for AutoTagRuleServiceImpl {
+    async fn create_auto_tag_rule(
+        &self,
+        request: Request<CreateAutoTagRuleRequest>,
+    ) -> Result<Response<AutoTagRule>, Status> {
+        let ctx = extract_context(&request)?;
+        require_tenant_manager(&ctx)?;
+        let req = request.into_inner();
+
+        let name = req.name.trim();
+        if name.is_empty() {
+            return Err(Status::invalid_argument("name is required"));
+        }
+        if name.len() > MAX_NAME_LEN {
+            return Err(Status::invalid_argument("name is too long"));
+        }
+        let fields = parse_fields(
+            &req.domain,
+            &req.path_pattern,
+            &req.title_pattern,
+            &req.tags,
+            req.enabled.unwrap_or(true),
+        )?;
+
+        let existing = self
+            .rules
+            .list(ctx.tenant_id)
+            .await
+            .map_err(ServiceError::database)?;
+        if existing.len() >= MAX_RULES {
+            return Err(Status::failed_precondition(format!(
+                "a tenant holds at most {MAX_RULES} auto-tag rules"
+            )));
+        }
+
+        let row = self
+            .rules
+            .create(ctx.tenant_id, name, &fields, &ctx.user_id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| {
+                Status::already_exists(format!("a rule named {name:?} already exists"))
+            })?;
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            rule_id = row.id,
+            by = %ctx.user_id,
+            "auto-tag rule created"
+        );
+
+        Ok(Response::new(rule_to_proto(row)))
+    }
+
+    async fn list_auto_tag_rules(
+        &self,
+        request: Request<ListAutoTagRulesRequest>,
+    ) -> Result<Response<ListAutoTagRulesResponse>, Status> {
+        let ctx = extract_context(&request)?;
+
+        let rows = self
+            .rules
+            .list(ctx.tenant_id)
+            .await
+            .map_err(ServiceError::database)?;
+
+        Ok(Response::new(ListAutoTagRulesResponse {
+            rules: rows.into_iter().map(rule_to_proto).collect(),
+        }))
+    }
+
+    async fn update_auto_tag_rule(
+        &self,
+        request: Request<UpdateAutoTagRuleRequest>,
+    ) -> Result<Response<AutoTagRule>, Status> {
+        let ctx = extract_context(&request)?;
+        require_tenant_manager(&ctx)?;
+        let req = request.into_inner();
+
+        let id = parse_rule_id(req.id)?;
+        let fields = parse_fields(
+            &req.domain,
+            &req.path_pattern,
+            &req.title_pattern,
+            &req.tags,
+            req.enabled,
+        )?;
+
+        let row = self
+            .rules
+            .update(ctx.tenant_id, id, &fields)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("auto-tag rule not found"))?;
+
+        Ok(Response::new(rule_to_proto(row)))
+    }
+
+    async fn delete_auto_tag_rule(
+        &self,
+        request: Request<DeleteAutoTagRuleRequest>,
+    ) -> Result<Response<()>, Status> {
+        let ctx = extract_context(&request)?;
+        require_tenant_manager(&ctx)?;
+        let req = request.into_inner();
+
+        let id = parse_rule_id(req.id)?;
+        let deleted = self
+            .rules
+            .delete(ctx.tenant_id, id)
+            .await
+            .map_err(ServiceError::database)?;
+        if !deleted {
+            return Err(Status::not_found("auto-tag rule not found"));
+        }
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            rule_id = id,
+            by = %ctx.user_id,
+            "auto-tag rule deleted"
+        );
+
+        Ok(Response::new(()))
+    }
+
+    async fn reapply_rules(
+        &self,
+        request: Request<ReapplyRulesRequest>,
+    ) -> Result<Response<ReapplyRulesResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        require_tenant_manager(&ctx)?;
+        let req = request.into_inner();
+
+        let wanted = req
+            .rule_ids
+            .iter()
+            .map(|id| parse_rule_id(*id))
+            .collect::<Result<Vec<_>, Status>>()?;
+        let mut rows = self
+            .rules
+            .list(ctx.tenant_id)
+            .await
+            .map_err(ServiceError::database)?;
+        if !wanted.is_empty() {
+            if let Some(missing) = wanted.iter().find(|id| !rows.iter().any(|r| r.id == **id)) {
+                return Err(Status::not_found(format!("auto-tag rule {missing} not found")));
+            }
+            rows.retain(|r| wanted.contains(&r.id));
+        }
+        let tagger = AutoTagger::new(&rows);
+
+        let mut scanned = 0u32;
+        let mut updated = 0u32;
+        if !tagger.is_empty() {
+            let edited_by = ctx.user_id.parse::<i32>().ok();
+            let mut page = 1;
+            loop {
+                let (bookmarks, _) = self
+                    .bookmarks
+                    .list_by_tenant(ctx.tenant_id, page, REAPPLY_PAGE_SIZE)
+                    .await
+                    .map_err(ServiceError::database)?;
+                let last_page = bookmarks.len() < REAPPLY_PAGE_SIZE as usize;
+                scanned += bookmarks.len() as u32;
+
+                let changes: Vec<BookmarkChanges> = bookmarks
+                    .into_iter()
+                    // A private bookmark's URL is sealed, so rules cannot match it.
+                    .filter(|b| !b.is_private)
+                    .filter_map(|b| {
+                        let mut tags = b.tags;
+                        if !tagger.apply(&b.url, &b.title, &mut tags) {
+                            return None;
+                        }
+                        Some(BookmarkChanges {
+                            id: b.id,
+                            url: None,
+                            title: None,
+                            description: None,
+                            tags: Some(tags),
+                            original_url: None,
+                            metadata: None,
+                            kind: None,
+                            notes: None,
+                            is_private: None,
+                        })
+                    })
+                    .collect();
+                if !changes.is_empty() {
+                    let outcomes = self
+                        .bookmarks
+                        .batch_update(ctx.tenant_id, &changes, edited_by)
+                        .await
+                        .map_err(ServiceError::database)?;
+                    for outcome in outcomes {
+                        match outcome {
+                            Ok(Some(row)) => {
+                                updated += 1;
+                                self.events.publish(Event::bookmark(
+                                    EventType::BookmarkUpdated,
+                                    &ctx.user_id,
+                                    &row,
+                                ));
+                            }
+                            Ok(None) => {}
+                            Err(e) => {
+                                tracing::warn!(error = %e, "auto-tag update failed");
+                            }
+                        }
+                    }
+                }
+
+                if last_page {
+                    break;
+                }
+                page += 1;
+            }
+        }
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            rules = rows.len(),
+            scanned,
+            updated,
+            by = %ctx.user_id,
+            "auto-tag rules reapplied"
+        );
+
+        Ok(Response::new(ReapplyRulesResponse { scanned, updated }))
+    }
+}
+
+fn to_timestamp(ts: chrono::DateTime<chrono::Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: ts.timestamp(),
+        nanos: ts.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn rule_to_proto(row: AutoTagRuleRow) -> AutoTagRule {
+    AutoTagRule {
+        id: row.id as u32,
+        name: row.name,
+        domain: row.domain,
+        path_pattern: row.path_pattern,
+        title_pattern: row.title_pattern,
+        tags: row.tags,
+        enabled: row.enabled,
+        created_by: row.created_by,
+        create_time: Some(to_timestamp(row.create_time)),
+        update_time: Some(to_timestamp(row.update_time)),
+    }
+}
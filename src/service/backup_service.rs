@@ -1,83 +1,320 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use chrono::Utc;
-use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::types::Json;
+use sqlx::{Connection, PgConnection, PgPool};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
 use uuid::Uuid;
 
+use crate::authz::cache::DecisionCache;
+use crate::authz::relations::{Relation, ResourceType, WILDCARD_RESOURCE};
+use crate::backup::migrations::{self, CURRENT_VERSION};
+use crate::backup::storage::{BackupStorage, ObjectUpload};
+use crate::backup::{
+    BackupData, BackupEntities, BookmarkBackup, FieldKeyBackup, PermissionBackup,
+};
+use crate::data::bookmark_repo::BookmarkKind;
+use crate::data::field_key_repo::FieldKeyRow;
+use crate::data::permission_repo::{permission_rows, PermissionRecord, PermissionRow};
+use crate::middleware::panic::{guarded, spawn_stream};
+use crate::service::bookmark_kind::classify_url;
+use crate::service::bookmark_service::cipher_status;
 use crate::service::bookmark_service::proto::backup_service_server::BackupService;
 use crate::service::bookmark_service::proto::{
-    EntityImportResult, ExportBackupRequest, ExportBackupResponse, ImportBackupRequest,
-    ImportBackupResponse, RestoreMode,
+    EntityImportResult, ExportBackupChunk, ExportBackupRequest, ExportBackupResponse,
+    ExportBackupToStorageResponse,
+    ImportBackupRequest, ImportBackupResponse, RestoreMode,
 };
-use crate::service::context_helper::extract_context;
+use crate::service::context_helper::{extract_context, RequestContext};
+use crate::service::error::ServiceError;
+use crate::service::field_cipher::{self, FieldCipher, SealedField};
+use crate::service::idempotency::Idempotency;
+use crate::service::url_normalizer::normalize_url;
+use crate::service::url_policy::UrlPolicy;
 
 const BACKUP_MODULE: &str = "bookmark";
-const BACKUP_VERSION: &str = "1.0";
+
+/// Rows fetched per page while streaming an export.
+const STREAM_PAGE_SIZE: i64 = 500;
+/// Flush a chunk once its buffered NDJSON reaches this many bytes.
+const STREAM_CHUNK_BYTES: usize = 64 * 1024;
+/// Chunks buffered between the export task and the client.
+const STREAM_CHANNEL_CAPACITY: usize = 4;
+/// Skip and overwrite restores of at least this many bookmarks are staged with
+/// `COPY` and merged in one statement instead of row by row.
+const COPY_IMPORT_MIN_ROWS: usize = 500;
+
+/// Entity types picked by a request's `entity_types`; an empty list picks all.
+#[derive(Debug, Clone, Copy)]
+struct EntitySelection {
+    bookmarks: bool,
+    permissions: bool,
+}
+
+impl EntitySelection {
+    fn parse(types: &[String]) -> Result<Self, Status> {
+        if types.is_empty() {
+            return Ok(Self {
+                bookmarks: true,
+                permissions: true,
+            });
+        }
+        let mut selection = Self {
+            bookmarks: false,
+            permissions: false,
+        };
+        for entity_type in types {
+            match entity_type.as_str() {
+                "bookmarks" => selection.bookmarks = true,
+                "permissions" => selection.permissions = true,
+                other => {
+                    return Err(Status::invalid_argument(format!(
+                        "unknown entity type: {other}"
+                    )))
+                }
+            }
+        }
+        Ok(selection)
+    }
+}
 
 pub struct BackupServiceImpl {
     pool: PgPool,
+    idempotency: Idempotency,
+    /// Cleared after an import, which writes permission tuples directly.
+    authz_cache: DecisionCache,
+    /// Target of `ExportBackupToStorage`; the RPC fails when unset.
+    storage: Option<BackupStorage>,
+    /// Re-seals private bookmarks' values when they are restored under new IDs.
+    cipher: FieldCipher,
+    /// Restored URLs are held to the same schemes as created ones.
+    url_policy: UrlPolicy,
 }
 
 impl BackupServiceImpl {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(
+        pool: PgPool,
+        idempotency: Idempotency,
+        authz_cache: DecisionCache,
+        storage: Option<BackupStorage>,
+        cipher: FieldCipher,
+        url_policy: UrlPolicy,
+    ) -> Self {
+        Self {
+            pool,
+            idempotency,
+            authz_cache,
+            storage,
+            cipher,
+            url_policy,
+        }
     }
-}
 
-// --- Serde models for JSON backup data ---
+    /// Seal the values of private bookmarks given new IDs again for those IDs:
+    /// sealed values are bound to the bookmark they belong to. Data keys are
+    /// looked up in the backup as well as the store, since they are restored
+    /// after this runs.
+    async fn rebind_private(
+        &self,
+        data: &mut BackupEntities,
+        ids: &HashMap<String, String>,
+    ) -> Result<(), Status> {
+        let keys: Vec<FieldKeyRow> = data
+            .field_keys
+            .iter()
+            .filter_map(|item| serde_json::from_value::<FieldKeyBackup>(item.clone()).ok())
+            .filter_map(|key| {
+                Some(FieldKeyRow {
+                    id: Uuid::parse_str(&key.id).ok()?,
+                    tenant_id: key.tenant_id,
+                    wrapped_key: key.wrapped_key,
+                    kek_id: key.kek_id,
+                    create_time: chrono::DateTime::parse_from_rfc3339(&key.create_time)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                })
+            })
+            .collect();
+        let old_ids: HashMap<&str, &str> = ids
+            .iter()
+            .map(|(old, new)| (new.as_str(), old.as_str()))
+            .collect();
+        let fields = [
+            ("url", SealedField::Url),
+            ("originalUrl", SealedField::OriginalUrl),
+            ("notes", SealedField::Notes),
+        ];
 
-#[derive(Serialize, Deserialize)]
+        for item in &mut data.bookmarks {
+            if item.get("isPrivate").and_then(|v| v.as_bool()) != Some(true) {
+                continue;
+            }
+            let (Some(tenant_id), Some(new)) = (
+                item.get("tenantId").and_then(|v| v.as_i64()),
+                item.get("id").and_then(|v| v.as_str()),
+            ) else {
+                continue;
+            };
+            let Some(old) = old_ids.get(new) else {
+                continue;
+            };
+            let (Ok(from), Ok(to)) = (Uuid::parse_str(old), Uuid::parse_str(new)) else {
+                // Import reports the bad ID.
+                continue;
+            };
+            if !self.cipher.is_enabled() {
+                return Err(Status::failed_precondition(
+                    "restoring private bookmarks under new IDs requires field encryption",
+                ));
+            }
+            for (column, field) in fields {
+                let Some(value) = item.get(column).and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                let sealed = self
+                    .cipher
+                    .rebind(tenant_id as i32, from, to, field, value, &keys)
+                    .await
+                    .map_err(cipher_status)?;
+                item[column] = serde_json::Value::String(sealed);
+            }
+        }
+        Ok(())
+    }
+
+    /// `ImportBackup` without idempotency-key handling.
+    async fn do_import_backup(
+        &self,
+        request: Request<ImportBackupRequest>,
+    ) -> Result<Response<ImportBackupResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let mode = RestoreMode::try_from(req.mode).unwrap_or(RestoreMode::Skip);
+        let selection = EntitySelection::parse(&req.entity_types)?;
+
+        let mut doc: serde_json::Value = serde_json::from_slice(&req.data)
+            .map_err(|e| Status::invalid_argument(format!("invalid backup data: {e}")))?;
+
+        let module = doc.get("module").and_then(|v| v.as_str()).unwrap_or("");
+        if module != BACKUP_MODULE {
+            return Err(Status::invalid_argument(format!(
+                "backup module mismatch: expected {BACKUP_MODULE}, got {module}"
+            )));
+        }
+
+        let original_version = doc
+            .get("version")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        let upgrades = migrations::upgrade(&mut doc).map_err(Status::invalid_argument)?;
+
+        let mut backup: BackupData = serde_json::from_value(doc)
+            .map_err(|e| Status::invalid_argument(format!("invalid backup data: {e}")))?;
+
+        if mode == RestoreMode::RegenerateIds {
+            let ids = backup.data.regenerate_bookmark_ids();
+            tracing::info!(remapped = ids.len(), "regenerated bookmark ids for import");
+            self.rebind_private(&mut backup.data, &ids).await?;
+        }
+
+        tracing::info!(
+            module = %backup.module,
+            version = %original_version,
+            mode = ?mode,
+            entities = ?selection,
+            client = %ctx.client_name(),
+            "importing bookmark backup"
+        );
+
+        let mut warnings = Vec::new();
+        for step in upgrades {
+            warnings.push(format!("upgraded backup format {step}"));
+        }
+        let results = if req.atomic {
+            self.import_atomic(&backup.data, selection, mode, &mut warnings)
+                .await?
+        } else {
+            let mut conn = self
+                .pool
+                .acquire()
+                .await
+                .map_err(ServiceError::database)?;
+            let data = &backup.data;
+            let mut results = Vec::new();
+            if selection.bookmarks {
+                // Keys first so restored private bookmarks can be opened
+                if !data.field_keys.is_empty() {
+                    results.push(
+                        self.import_field_keys(&mut conn, &data.field_keys, false, &mut warnings)
+                            .await,
+                    );
+                }
+                results.push(
+                    self.import_bookmarks(&mut conn, &data.bookmarks, mode, false, &mut warnings)
+                        .await,
+                );
+            }
+            // Permissions after bookmarks so references exist
+            if selection.permissions {
+                let permissions = &data.permissions;
+                results.push(
+                    self.import_permissions(&mut conn, permissions, mode, false, &mut warnings)
+                        .await,
+                );
+            }
+            results
+        };
+        self.authz_cache.invalidate_all();
+
+        let success = results.iter().all(|r| r.failed == 0);
+
+        Ok(Response::new(ImportBackupResponse {
+            success,
+            results,
+            warnings,
+        }))
+    }
+}
+
+/// Header line written at the start of a streamed (NDJSON) export.
+#[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct BackupData {
-    module: String,
-    version: String,
+struct StreamHeader<'a> {
+    module: &'a str,
+    version: &'a str,
     exported_at: String,
     tenant_id: u32,
     full_backup: bool,
-    data: BackupEntities,
-}
-
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct BackupEntities {
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    bookmarks: Vec<serde_json::Value>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    permissions: Vec<serde_json::Value>,
 }
 
-#[derive(Serialize, Deserialize)]
+/// One entity line of a streamed (NDJSON) export.
+#[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct BookmarkBackup {
-    id: String,
-    tenant_id: i32,
-    url: String,
-    title: String,
-    description: String,
-    tags: Vec<String>,
-    created_by: Option<i32>,
-    create_time: String,
-    update_time: String,
+struct StreamEntity<'a> {
+    entity_type: &'a str,
+    data: serde_json::Value,
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct PermissionBackup {
-    tenant_id: i32,
-    resource_type: String,
-    resource_id: String,
-    relation: String,
-    subject_type: String,
-    subject_id: String,
-    granted_by: Option<i32>,
-    expires_at: Option<String>,
-    create_time: String,
+/// Resolve which tenant an export covers. Platform admins exporting without a
+/// tenant (or tenant 0) get a full backup across all tenants.
+fn resolve_export_scope(ctx: &RequestContext, requested: Option<u32>) -> (i32, bool) {
+    match requested {
+        Some(0) | None if ctx.is_platform_admin() => (0_i32, true),
+        Some(tid) => (tid as i32, false),
+        _ => (ctx.tenant_id, false),
+    }
 }
 
 #[tonic::async_trait]
 impl BackupService for BackupServiceImpl {
+    type StreamExportBackupStream = ReceiverStream<Result<ExportBackupChunk, Status>>;
+
     async fn export_backup(
         &self,
         request: Request<ExportBackupRequest>,
@@ -85,72 +322,100 @@ impl BackupService for BackupServiceImpl {
         let ctx = extract_context(&request)?;
         let req = request.into_inner();
 
-        let is_platform_admin = ctx
-            .role_ids
-            .iter()
-            .any(|r| r == "platform:admin" || r == "super:admin");
-
-        let (tenant_id, full_backup) = match req.tenant_id {
-            Some(0) | None if is_platform_admin => (0_i32, true),
-            Some(tid) => (tid as i32, false),
-            _ => (ctx.tenant_id, false),
-        };
+        let (tenant_id, full_backup) = resolve_export_scope(&ctx, req.tenant_id);
+        let selection = EntitySelection::parse(&req.entity_types)?;
 
         tracing::info!(
             tenant_id,
             full_backup,
+            entities = ?selection,
+            client = %ctx.client_name(),
             "exporting bookmark backup"
         );
 
         // Export bookmarks
-        let bookmarks: Vec<serde_json::Value> = if full_backup {
-            let rows = sqlx::query_as::<_, BookmarkRow>(
-                "SELECT * FROM bookmark_bookmarks ORDER BY create_time",
+        let bookmarks: Vec<serde_json::Value> = if !selection.bookmarks {
+            Vec::new()
+        } else if full_backup {
+            let rows = sqlx::query_as!(
+                BookmarkRow,
+                r#"SELECT id, tenant_id, url, title, description, tags, created_by,
+                          create_time, update_time, original_url, notes, metadata AS "metadata: _",
+                          kind, is_private
+                   FROM bookmark_bookmarks ORDER BY create_time"#,
             )
             .fetch_all(&self.pool)
             .await
-            .map_err(|e| Status::internal(format!("query bookmarks: {e}")))?;
+            .map_err(ServiceError::database)?;
             rows.into_iter().map(|r| bookmark_to_json(&r)).collect()
         } else {
-            let rows = sqlx::query_as::<_, BookmarkRow>(
-                "SELECT * FROM bookmark_bookmarks WHERE tenant_id = $1 ORDER BY create_time",
+            let rows = sqlx::query_as!(
+                BookmarkRow,
+                r#"SELECT id, tenant_id, url, title, description, tags, created_by,
+                          create_time, update_time, original_url, notes, metadata AS "metadata: _",
+                          kind, is_private
+                   FROM bookmark_bookmarks WHERE tenant_id = $1 ORDER BY create_time"#,
+                tenant_id,
             )
-            .bind(tenant_id)
             .fetch_all(&self.pool)
             .await
-            .map_err(|e| Status::internal(format!("query bookmarks: {e}")))?;
+            .map_err(ServiceError::database)?;
             rows.into_iter().map(|r| bookmark_to_json(&r)).collect()
         };
 
+        // Export the data keys private bookmarks are sealed with, still wrapped
+        let field_keys: Vec<serde_json::Value> = if !selection.bookmarks {
+            Vec::new()
+        } else {
+            let rows = sqlx::query_as!(
+                FieldKeyRow,
+                r#"SELECT * FROM bookmark_field_keys
+                   WHERE ($1::INTEGER IS NULL OR tenant_id = $1)
+                   ORDER BY create_time"#,
+                (!full_backup).then_some(tenant_id),
+            )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ServiceError::database)?;
+            rows.iter().map(field_key_to_json).collect()
+        };
+
         // Export permissions
-        let permissions: Vec<serde_json::Value> = if full_backup {
-            let rows = sqlx::query_as::<_, PermissionRow>(
+        let permissions: Vec<serde_json::Value> = if !selection.permissions {
+            Vec::new()
+        } else if full_backup {
+            let rows = sqlx::query_as!(
+                PermissionRecord,
                 "SELECT * FROM bookmark_permissions ORDER BY create_time",
             )
             .fetch_all(&self.pool)
             .await
-            .map_err(|e| Status::internal(format!("query permissions: {e}")))?;
+            .and_then(permission_rows)
+            .map_err(ServiceError::database)?;
             rows.into_iter().map(|r| permission_to_json(&r)).collect()
         } else {
-            let rows = sqlx::query_as::<_, PermissionRow>(
+            let rows = sqlx::query_as!(
+                PermissionRecord,
                 "SELECT * FROM bookmark_permissions WHERE tenant_id = $1 ORDER BY create_time",
+                tenant_id,
             )
-            .bind(tenant_id)
             .fetch_all(&self.pool)
             .await
-            .map_err(|e| Status::internal(format!("query permissions: {e}")))?;
+            .and_then(permission_rows)
+            .map_err(ServiceError::database)?;
             rows.into_iter().map(|r| permission_to_json(&r)).collect()
         };
 
         let backup = BackupData {
             module: BACKUP_MODULE.to_string(),
-            version: BACKUP_VERSION.to_string(),
+            version: CURRENT_VERSION.to_string(),
             exported_at: Utc::now().to_rfc3339(),
             tenant_id: tenant_id as u32,
             full_backup,
             data: BackupEntities {
                 bookmarks,
                 permissions,
+                field_keys,
             },
         };
 
@@ -158,17 +423,22 @@ impl BackupService for BackupServiceImpl {
             .map_err(|e| Status::internal(format!("serialize backup: {e}")))?;
 
         let mut entity_counts = HashMap::new();
-        entity_counts.insert("bookmarks".to_string(), backup.data.bookmarks.len() as i64);
-        entity_counts.insert(
-            "permissions".to_string(),
-            backup.data.permissions.len() as i64,
-        );
+        if selection.bookmarks {
+            entity_counts.insert("bookmarks".to_string(), backup.data.bookmarks.len() as i64);
+            entity_counts.insert("fieldKeys".to_string(), backup.data.field_keys.len() as i64);
+        }
+        if selection.permissions {
+            entity_counts.insert(
+                "permissions".to_string(),
+                backup.data.permissions.len() as i64,
+            );
+        }
 
         let now = Utc::now();
         Ok(Response::new(ExportBackupResponse {
             data,
             module: BACKUP_MODULE.to_string(),
-            version: BACKUP_VERSION.to_string(),
+            version: CURRENT_VERSION.to_string(),
             exported_at: Some(prost_types::Timestamp {
                 seconds: now.timestamp(),
                 nanos: now.timestamp_subsec_nanos() as i32,
@@ -178,69 +448,253 @@ impl BackupService for BackupServiceImpl {
         }))
     }
 
-    async fn import_backup(
+    async fn stream_export_backup(
         &self,
-        request: Request<ImportBackupRequest>,
-    ) -> Result<Response<ImportBackupResponse>, Status> {
-        let _ctx = extract_context(&request)?;
+        request: Request<ExportBackupRequest>,
+    ) -> Result<Response<Self::StreamExportBackupStream>, Status> {
+        let ctx = extract_context(&request)?;
         let req = request.into_inner();
 
-        let mode = RestoreMode::try_from(req.mode).unwrap_or(RestoreMode::Skip);
+        let (tenant_id, full_backup) = resolve_export_scope(&ctx, req.tenant_id);
+        let selection = EntitySelection::parse(&req.entity_types)?;
 
-        let backup: BackupData = serde_json::from_slice(&req.data)
-            .map_err(|e| Status::invalid_argument(format!("invalid backup data: {e}")))?;
+        tracing::info!(
+            tenant_id,
+            full_backup,
+            entities = ?selection,
+            client = %ctx.client_name(),
+            "streaming bookmark backup export"
+        );
 
-        if backup.module != BACKUP_MODULE {
-            return Err(Status::invalid_argument(format!(
-                "backup module mismatch: expected {BACKUP_MODULE}, got {}",
-                backup.module
-            )));
-        }
+        let (tx, rx) = mpsc::channel(STREAM_CHANNEL_CAPACITY);
+        let pool = self.pool.clone();
+        let tenant_filter = if full_backup { None } else { Some(tenant_id) };
+
+        let method = "/bookmark.service.v1.BackupService/StreamExportBackup";
+        spawn_stream(method, tx.clone(), async move {
+            let mut writer = ChunkWriter::new(tx, StreamFormat::Ndjson);
+            if let Err(status) = stream_export(
+                &pool,
+                tenant_filter,
+                tenant_id,
+                full_backup,
+                selection,
+                &mut writer,
+            )
+            .await
+            {
+                tracing::error!(error = %status.message(), "streaming backup export failed");
+                let _ = writer.tx.send(Err(status)).await;
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn export_backup_to_storage(
+        &self,
+        request: Request<ExportBackupRequest>,
+    ) -> Result<Response<ExportBackupToStorageResponse>, Status> {
+        let storage = self
+            .storage
+            .clone()
+            .ok_or_else(|| Status::failed_precondition("backup storage is not configured"))?;
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let (tenant_id, full_backup) = resolve_export_scope(&ctx, req.tenant_id);
+        let selection = EntitySelection::parse(&req.entity_types)?;
+        let exported_at = Utc::now();
+        let scope = if full_backup {
+            "full".to_string()
+        } else {
+            format!("tenant-{tenant_id}")
+        };
+        let key = storage.key(&format!(
+            "bookmark-{scope}-{}.json",
+            exported_at.format("%Y%m%dT%H%M%SZ")
+        ));
 
         tracing::info!(
-            module = %backup.module,
-            version = %backup.version,
-            mode = ?mode,
-            "importing bookmark backup"
+            tenant_id,
+            full_backup,
+            entities = ?selection,
+            bucket = %storage.bucket(),
+            key = %key,
+            client = %ctx.client_name(),
+            "exporting bookmark backup to storage"
         );
 
-        let mut warnings = Vec::new();
-        let mut results = Vec::new();
+        let storage_err = |e: anyhow::Error| Status::internal(format!("backup storage: {e}"));
+        let mut upload = storage
+            .begin(&key, "application/json")
+            .await
+            .map_err(storage_err)?;
 
-        // Import bookmarks
-        let bookmark_result =
-            self.import_bookmarks(&backup.data.bookmarks, mode, &mut warnings).await;
-        results.push(bookmark_result);
+        let (tx, mut rx) = mpsc::channel(STREAM_CHANNEL_CAPACITY);
+        let pool = self.pool.clone();
+        let tenant_filter = if full_backup { None } else { Some(tenant_id) };
+        let method = "/bookmark.service.v1.BackupService/ExportBackupToStorage";
+        let export = tokio::spawn(guarded(method, async move {
+            let mut writer = ChunkWriter::new(tx, StreamFormat::Document);
+            stream_export(
+                &pool,
+                tenant_filter,
+                tenant_id,
+                full_backup,
+                selection,
+                &mut writer,
+            )
+            .await
+        }));
 
-        // Import permissions (after bookmarks so references exist)
-        let permission_result = self
-            .import_permissions(&backup.data.permissions, mode, &mut warnings)
-            .await;
-        results.push(permission_result);
+        let uploaded = upload_parts(&storage, upload.as_mut(), &mut rx).await;
+        // Closing the channel stops the export if the upload gave up early.
+        drop(rx);
+        // A panicked export still aborts the upload below.
+        let exported = export
+            .await
+            .map_err(|e| Status::internal(format!("backup export task: {e}")))
+            .and_then(|guarded| guarded)
+            .and_then(|exported| exported);
 
-        let success = results.iter().all(|r| r.failed == 0);
+        let summary = match (uploaded, exported) {
+            (Ok(Some(summary)), Ok(())) => summary,
+            (Err(status), _) | (_, Err(status)) => {
+                upload.abort().await;
+                return Err(status);
+            }
+            (Ok(None), Ok(())) => {
+                upload.abort().await;
+                return Err(Status::internal("backup export ended without its last chunk"));
+            }
+        };
+        upload.complete().await.map_err(storage_err)?;
 
-        Ok(Response::new(ImportBackupResponse {
-            success,
-            results,
-            warnings,
+        tracing::info!(
+            key = %key,
+            size_bytes = summary.size_bytes,
+            checksum = %summary.checksum_sha256,
+            "bookmark backup uploaded"
+        );
+
+        Ok(Response::new(ExportBackupToStorageResponse {
+            bucket: storage.bucket().to_string(),
+            key,
+            checksum_sha256: summary.checksum_sha256,
+            size_bytes: summary.size_bytes,
+            version: CURRENT_VERSION.to_string(),
+            exported_at: Some(prost_types::Timestamp {
+                seconds: exported_at.timestamp(),
+                nanos: exported_at.timestamp_subsec_nanos() as i32,
+            }),
+            tenant_id: tenant_id as u32,
+            entity_counts: summary.entity_counts,
         }))
     }
+
+    async fn import_backup(
+        &self,
+        request: Request<ImportBackupRequest>,
+    ) -> Result<Response<ImportBackupResponse>, Status> {
+        let claim = self.idempotency.begin("ImportBackup", &request).await?;
+        if let Some(replay) = claim.replay()? {
+            return Ok(replay);
+        }
+        let result = self.do_import_backup(request).await;
+        self.idempotency.finish(claim, &result).await;
+        result
+    }
 }
 
 impl BackupServiceImpl {
+    /// Import every entity in one transaction, stopping at the first failure and
+    /// rolling back so a partial restore never leaves permissions without bookmarks.
+    async fn import_atomic(
+        &self,
+        data: &BackupEntities,
+        selection: EntitySelection,
+        mode: RestoreMode,
+        warnings: &mut Vec<String>,
+    ) -> Result<Vec<EntityImportResult>, Status> {
+        let mut tx = self.pool.begin().await.map_err(ServiceError::database)?;
+
+        let mut results = Vec::new();
+        if selection.bookmarks {
+            if !data.field_keys.is_empty() {
+                results.push(
+                    self.import_field_keys(&mut tx, &data.field_keys, true, warnings)
+                        .await,
+                );
+            }
+            results.push(
+                self.import_bookmarks(&mut tx, &data.bookmarks, mode, true, warnings)
+                    .await,
+            );
+        }
+        if selection.permissions {
+            let permission_result = if results.iter().all(|r| r.failed == 0) {
+                self.import_permissions(&mut tx, &data.permissions, mode, true, warnings)
+                    .await
+            } else {
+                EntityImportResult {
+                    entity_type: "permissions".to_string(),
+                    total: data.permissions.len() as i64,
+                    ..Default::default()
+                }
+            };
+            results.push(permission_result);
+        }
+
+        let failed: i64 = results.iter().map(|r| r.failed).sum();
+        if failed == 0 {
+            tx.commit().await.map_err(ServiceError::database)?;
+            return Ok(results);
+        }
+
+        tx.rollback().await.map_err(ServiceError::database)?;
+        for result in &mut results {
+            result.created = 0;
+            result.updated = 0;
+        }
+        warnings.push(format!(
+            "atomic import rolled back after {failed} failed item(s); nothing was written"
+        ));
+        Ok(results)
+    }
+
     async fn import_bookmarks(
         &self,
+        conn: &mut PgConnection,
         items: &[serde_json::Value],
         mode: RestoreMode,
+        atomic: bool,
         warnings: &mut Vec<String>,
     ) -> EntityImportResult {
+        if items.len() >= COPY_IMPORT_MIN_ROWS && mode != RestoreMode::Merge {
+            match self.copy_import_bookmarks(conn, items, mode).await {
+                Ok((result, copy_warnings)) => {
+                    warnings.extend(copy_warnings);
+                    return result;
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "bulk bookmark import failed, importing row by row");
+                    warnings.push(format!(
+                        "bulk bookmark import failed, importing row by row: {e}"
+                    ));
+                }
+            }
+        }
+
         let mut created = 0i64;
         let mut updated = 0i64;
         let mut skipped = 0i64;
         let mut failed = 0i64;
 
         for item in items {
+            if atomic && failed > 0 {
+                break;
+            }
             let bk: BookmarkBackup = match serde_json::from_value(item.clone()) {
                 Ok(b) => b,
                 Err(e) => {
@@ -258,36 +712,47 @@ impl BackupServiceImpl {
                     continue;
                 }
             };
+            if let Err(e) = check_restored_url(&self.url_policy, &bk) {
+                warnings.push(format!("skip bookmark {}: {}", bk.id, e.message()));
+                failed += 1;
+                continue;
+            }
 
             // Check if exists
-            let existing: Option<(Uuid,)> =
-                sqlx::query_as("SELECT id FROM bookmark_bookmarks WHERE id = $1")
-                    .bind(id)
-                    .fetch_optional(&self.pool)
-                    .await
-                    .unwrap_or(None);
+            let existing = sqlx::query_scalar!("SELECT id FROM bookmark_bookmarks WHERE id = $1", id)
+                .fetch_optional(&mut *conn)
+                .await
+                .unwrap_or(None);
 
             if existing.is_some() {
                 match mode {
-                    RestoreMode::Skip => {
+                    RestoreMode::Skip | RestoreMode::RegenerateIds => {
                         skipped += 1;
                         continue;
                     }
                     RestoreMode::Overwrite => {
-                        let res = sqlx::query(
+                        let res = sqlx::query!(
                             r#"UPDATE bookmark_bookmarks
                                SET url = $2, title = $3, description = $4, tags = $5,
-                                   created_by = $6, tenant_id = $7, update_time = NOW()
+                                   created_by = $6, tenant_id = $7, original_url = $8,
+                                   normalized_url = $9, notes = $10, metadata = $11,
+                                   kind = $12, is_private = $13, update_time = NOW()
                                WHERE id = $1"#,
+                            id,
+                            bk.url,
+                            bk.title,
+                            bk.description,
+                            &bk.tags,
+                            bk.created_by,
+                            bk.tenant_id,
+                            bk.original_url,
+                            restored_normalized_url(&bk),
+                            bk.notes,
+                            Json(&bk.metadata) as _,
+                            restored_kind(&bk),
+                            bk.is_private,
                         )
-                        .bind(id)
-                        .bind(&bk.url)
-                        .bind(&bk.title)
-                        .bind(&bk.description)
-                        .bind(&bk.tags)
-                        .bind(bk.created_by)
-                        .bind(bk.tenant_id)
-                        .execute(&self.pool)
+                        .execute(&mut *conn)
                         .await;
 
                         match res {
@@ -298,20 +763,34 @@ impl BackupServiceImpl {
                             }
                         }
                     }
+                    RestoreMode::Merge => match self.merge_bookmark(conn, id, &bk).await {
+                        Ok(true) => updated += 1,
+                        Ok(false) => skipped += 1,
+                        Err(e) => {
+                            warnings.push(format!("merge bookmark {}: {e}", bk.id));
+                            failed += 1;
+                        }
+                    },
                 }
             } else {
-                let res = sqlx::query(
-                    r#"INSERT INTO bookmark_bookmarks (id, tenant_id, url, title, description, tags, created_by)
-                       VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+                let res = sqlx::query!(
+                    r#"INSERT INTO bookmark_bookmarks (id, tenant_id, url, title, description, tags, created_by, original_url, normalized_url, notes, metadata, kind, is_private)
+                       VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)"#,
+                    id,
+                    bk.tenant_id,
+                    bk.url,
+                    bk.title,
+                    bk.description,
+                    &bk.tags,
+                    bk.created_by,
+                    bk.original_url,
+                    restored_normalized_url(&bk),
+                    bk.notes,
+                    Json(&bk.metadata) as _,
+                    restored_kind(&bk),
+                    bk.is_private,
                 )
-                .bind(id)
-                .bind(bk.tenant_id)
-                .bind(&bk.url)
-                .bind(&bk.title)
-                .bind(&bk.description)
-                .bind(&bk.tags)
-                .bind(bk.created_by)
-                .execute(&self.pool)
+                .execute(&mut *conn)
                 .await;
 
                 match res {
@@ -334,18 +813,285 @@ impl BackupServiceImpl {
         }
     }
 
+    /// Import bookmarks in one transaction: stage them in a temp table with `COPY`,
+    /// then insert with a single `INSERT ... ON CONFLICT`. Counts match the row by
+    /// row path, including for IDs repeated within the backup. Nothing is written
+    /// on error.
+    async fn copy_import_bookmarks(
+        &self,
+        conn: &mut PgConnection,
+        items: &[serde_json::Value],
+        mode: RestoreMode,
+    ) -> Result<(EntityImportResult, Vec<String>), sqlx::Error> {
+        let mut warnings = Vec::new();
+        let mut failed = 0i64;
+        let mut repeated = 0i64;
+        let mut staged: Vec<(Uuid, BookmarkBackup)> = Vec::with_capacity(items.len());
+        let mut positions: HashMap<Uuid, usize> = HashMap::with_capacity(items.len());
+
+        for item in items {
+            let bk: BookmarkBackup = match serde_json::from_value(item.clone()) {
+                Ok(b) => b,
+                Err(e) => {
+                    warnings.push(format!("skip invalid bookmark: {e}"));
+                    failed += 1;
+                    continue;
+                }
+            };
+            let id = match Uuid::parse_str(&bk.id) {
+                Ok(id) => id,
+                Err(e) => {
+                    warnings.push(format!("skip bookmark with bad UUID {}: {e}", bk.id));
+                    failed += 1;
+                    continue;
+                }
+            };
+            if let Err(e) = check_restored_url(&self.url_policy, &bk) {
+                warnings.push(format!("skip bookmark {}: {}", bk.id, e.message()));
+                failed += 1;
+                continue;
+            }
+            // Row by row, a later copy of an ID skips or overwrites the earlier one.
+            if let Some(&pos) = positions.get(&id) {
+                repeated += 1;
+                if mode == RestoreMode::Overwrite {
+                    staged[pos] = (id, bk);
+                }
+                continue;
+            }
+            positions.insert(id, staged.len());
+            staged.push((id, bk));
+        }
+
+        let mut csv = String::new();
+        for (id, bk) in &staged {
+            let tags = serde_json::to_string(&bk.tags).unwrap_or_else(|_| "[]".to_string());
+            let metadata =
+                serde_json::to_string(&bk.metadata).unwrap_or_else(|_| "{}".to_string());
+            write_csv_row(
+                &mut csv,
+                &[
+                    Some(&id.to_string()),
+                    Some(&bk.tenant_id.to_string()),
+                    Some(&bk.url),
+                    Some(&bk.title),
+                    Some(&bk.description),
+                    Some(&tags),
+                    bk.created_by.map(|v| v.to_string()).as_deref(),
+                    bk.original_url.as_deref(),
+                    restored_normalized_url(bk).as_deref(),
+                    Some(&bk.notes),
+                    Some(&metadata),
+                    Some(restored_kind(bk)),
+                    Some(if bk.is_private { "true" } else { "false" }),
+                ],
+            );
+        }
+
+        let mut tx = conn.begin().await?;
+        sqlx::query(
+            r#"CREATE TEMP TABLE bookmark_import (
+                   id UUID, tenant_id INTEGER, url TEXT, title TEXT, description TEXT,
+                   tags JSONB, created_by INTEGER, original_url TEXT, normalized_url TEXT,
+                   notes TEXT, metadata JSONB, kind TEXT, is_private BOOLEAN
+               ) ON COMMIT DROP"#,
+        )
+        .execute(&mut *tx)
+        .await?;
+        let mut copy = tx
+            .copy_in_raw("COPY bookmark_import FROM STDIN (FORMAT csv)")
+            .await?;
+        copy.send(csv.into_bytes()).await?;
+        copy.finish().await?;
+
+        let conflict = if mode == RestoreMode::Overwrite {
+            r#"DO UPDATE SET url = EXCLUDED.url, title = EXCLUDED.title,
+                   description = EXCLUDED.description, tags = EXCLUDED.tags,
+                   created_by = EXCLUDED.created_by, tenant_id = EXCLUDED.tenant_id,
+                   original_url = EXCLUDED.original_url,
+                   normalized_url = EXCLUDED.normalized_url, notes = EXCLUDED.notes,
+                   metadata = EXCLUDED.metadata, kind = EXCLUDED.kind,
+                   is_private = EXCLUDED.is_private, update_time = NOW()"#
+        } else {
+            "DO NOTHING"
+        };
+        // `xmax = 0` holds for freshly inserted rows but not for updated ones.
+        let written: Vec<(bool,)> = sqlx::query_as(&format!(
+            r#"INSERT INTO bookmark_bookmarks (id, tenant_id, url, title, description, tags, created_by, original_url, normalized_url, notes, metadata, kind, is_private)
+               SELECT id, tenant_id, url, title, description,
+                      ARRAY(SELECT jsonb_array_elements_text(tags)), created_by, original_url,
+                      normalized_url, notes, metadata, kind, is_private
+               FROM bookmark_import
+               ON CONFLICT (id) {conflict}
+               RETURNING xmax = 0"#
+        ))
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let created = written.iter().filter(|(inserted,)| *inserted).count() as i64;
+        let (updated, skipped) = if mode == RestoreMode::Overwrite {
+            (written.len() as i64 - created + repeated, 0)
+        } else {
+            (0, staged.len() as i64 - created + repeated)
+        };
+
+        Ok((
+            EntityImportResult {
+                entity_type: "bookmarks".to_string(),
+                total: items.len() as i64,
+                created,
+                updated,
+                skipped,
+                failed,
+            },
+            warnings,
+        ))
+    }
+
+    /// Merge a backed-up bookmark into an existing one: tags are unioned and the
+    /// remaining fields come from whichever side has the newer `update_time`.
+    /// Returns whether the stored row changed.
+    async fn merge_bookmark(
+        &self,
+        conn: &mut PgConnection,
+        id: Uuid,
+        bk: &BookmarkBackup,
+    ) -> Result<bool, sqlx::Error> {
+        let current = sqlx::query!(
+            "SELECT tags, update_time FROM bookmark_bookmarks WHERE id = $1",
+            id,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let incoming_time = chrono::DateTime::parse_from_rfc3339(&bk.update_time)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc));
+        let incoming_newer = incoming_time.is_some_and(|t| t > current.update_time);
+
+        let tags = merge_tags(&current.tags, &bk.tags);
+        let tags_changed = tags != current.tags;
+
+        if incoming_newer {
+            sqlx::query!(
+                r#"UPDATE bookmark_bookmarks
+                   SET url = $2, title = $3, description = $4, tags = $5,
+                       original_url = $6, update_time = $7, normalized_url = $8, notes = $9,
+                       metadata = $10, kind = $11, is_private = $12
+                   WHERE id = $1"#,
+                id,
+                bk.url,
+                bk.title,
+                bk.description,
+                &tags,
+                bk.original_url,
+                incoming_time,
+                restored_normalized_url(bk),
+                bk.notes,
+                Json(&bk.metadata) as _,
+                restored_kind(bk),
+                bk.is_private,
+            )
+            .execute(&mut *conn)
+            .await?;
+            Ok(true)
+        } else if tags_changed {
+            sqlx::query!("UPDATE bookmark_bookmarks SET tags = $2 WHERE id = $1", id, &tags)
+                .execute(&mut *conn)
+                .await?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Restore wrapped data keys. Keys are immutable, so one that already
+    /// exists is skipped whatever the restore mode.
+    async fn import_field_keys(
+        &self,
+        conn: &mut PgConnection,
+        items: &[serde_json::Value],
+        atomic: bool,
+        warnings: &mut Vec<String>,
+    ) -> EntityImportResult {
+        let mut created = 0i64;
+        let mut skipped = 0i64;
+        let mut failed = 0i64;
+
+        for item in items {
+            if atomic && failed > 0 {
+                break;
+            }
+            let key: FieldKeyBackup = match serde_json::from_value(item.clone()) {
+                Ok(k) => k,
+                Err(e) => {
+                    warnings.push(format!("skip invalid field key: {e}"));
+                    failed += 1;
+                    continue;
+                }
+            };
+            let Ok(id) = Uuid::parse_str(&key.id) else {
+                warnings.push(format!("skip field key with bad UUID {}", key.id));
+                failed += 1;
+                continue;
+            };
+            let create_time = chrono::DateTime::parse_from_rfc3339(&key.create_time)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now());
+
+            let res = sqlx::query!(
+                r#"INSERT INTO bookmark_field_keys (id, tenant_id, wrapped_key, kek_id, create_time)
+                   VALUES ($1, $2, $3, $4, $5)
+                   ON CONFLICT (id) DO NOTHING"#,
+                id,
+                key.tenant_id,
+                key.wrapped_key,
+                key.kek_id,
+                create_time,
+            )
+            .execute(&mut *conn)
+            .await;
+
+            match res {
+                Ok(r) if r.rows_affected() > 0 => created += 1,
+                Ok(_) => skipped += 1,
+                Err(e) => {
+                    warnings.push(format!("create field key {}: {e}", key.id));
+                    failed += 1;
+                }
+            }
+        }
+
+        EntityImportResult {
+            entity_type: "fieldKeys".to_string(),
+            total: items.len() as i64,
+            created,
+            updated: 0,
+            skipped,
+            failed,
+        }
+    }
+
     async fn import_permissions(
         &self,
+        conn: &mut PgConnection,
         items: &[serde_json::Value],
         mode: RestoreMode,
+        atomic: bool,
         warnings: &mut Vec<String>,
     ) -> EntityImportResult {
+        self.warn_missing_bookmarks(conn, items, warnings).await;
+
         let mut created = 0i64;
         let mut updated = 0i64;
         let mut skipped = 0i64;
         let mut failed = 0i64;
 
         for item in items {
+            if atomic && failed > 0 {
+                break;
+            }
             let perm: PermissionBackup = match serde_json::from_value(item.clone()) {
                 Ok(p) => p,
                 Err(e) => {
@@ -354,26 +1100,32 @@ impl BackupServiceImpl {
                     continue;
                 }
             };
+            let Some(relation) = Relation::from_str(&perm.relation) else {
+                warnings.push(format!("skip permission with unknown relation {:?}", perm.relation));
+                failed += 1;
+                continue;
+            };
 
             // Check if exists (by unique constraint columns)
-            let existing: Option<(i32,)> = sqlx::query_as(
+            let existing = sqlx::query_scalar!(
                 r#"SELECT id FROM bookmark_permissions
                    WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3
-                     AND relation = $4 AND subject_type = $5 AND subject_id = $6"#,
+                     AND relation_code = $4 AND subject_type = $5 AND subject_id = $6"#,
+                perm.tenant_id,
+                perm.resource_type,
+                perm.resource_id,
+                relation.code(),
+                perm.subject_type,
+                perm.subject_id,
             )
-            .bind(perm.tenant_id)
-            .bind(&perm.resource_type)
-            .bind(&perm.resource_id)
-            .bind(&perm.relation)
-            .bind(&perm.subject_type)
-            .bind(&perm.subject_id)
-            .fetch_optional(&self.pool)
+            .fetch_optional(&mut *conn)
             .await
             .unwrap_or(None);
 
             if existing.is_some() {
                 match mode {
-                    RestoreMode::Skip => {
+                    // Tuples on regenerated bookmarks are new; wildcard ones can still exist.
+                    RestoreMode::Skip | RestoreMode::RegenerateIds => {
                         skipped += 1;
                         continue;
                     }
@@ -384,21 +1136,22 @@ impl BackupServiceImpl {
                             .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
                             .map(|dt| dt.with_timezone(&Utc));
 
-                        let res = sqlx::query(
+                        let res = sqlx::query!(
                             r#"UPDATE bookmark_permissions
-                               SET granted_by = $7, expires_at = $8
+                               SET granted_by = $7, expires_at = $8, subject_tenant_id = $9
                                WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3
-                                 AND relation = $4 AND subject_type = $5 AND subject_id = $6"#,
+                                 AND relation_code = $4 AND subject_type = $5 AND subject_id = $6"#,
+                            perm.tenant_id,
+                            perm.resource_type,
+                            perm.resource_id,
+                            relation.code(),
+                            perm.subject_type,
+                            perm.subject_id,
+                            perm.granted_by,
+                            expires_at,
+                            perm.subject_tenant_id,
                         )
-                        .bind(perm.tenant_id)
-                        .bind(&perm.resource_type)
-                        .bind(&perm.resource_id)
-                        .bind(&perm.relation)
-                        .bind(&perm.subject_type)
-                        .bind(&perm.subject_id)
-                        .bind(perm.granted_by)
-                        .bind(expires_at)
-                        .execute(&self.pool)
+                        .execute(&mut *conn)
                         .await;
 
                         match res {
@@ -409,6 +1162,45 @@ impl BackupServiceImpl {
                             }
                         }
                     }
+                    RestoreMode::Merge => {
+                        let expires_at = perm
+                            .expires_at
+                            .as_deref()
+                            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                            .map(|dt| dt.with_timezone(&Utc));
+
+                        // Union semantics: the tuple already exists, so only widen its
+                        // expiry (NULL = never expires wins) and never narrow it.
+                        let res = sqlx::query!(
+                            r#"UPDATE bookmark_permissions
+                               SET expires_at = CASE
+                                   WHEN expires_at IS NULL OR $7::TIMESTAMPTZ IS NULL THEN NULL
+                                   ELSE GREATEST(expires_at, $7)
+                               END
+                               WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3
+                                 AND relation_code = $4 AND subject_type = $5 AND subject_id = $6
+                                 AND expires_at IS NOT NULL
+                                 AND ($7::TIMESTAMPTZ IS NULL OR expires_at < $7)"#,
+                            perm.tenant_id,
+                            perm.resource_type,
+                            perm.resource_id,
+                            relation.code(),
+                            perm.subject_type,
+                            perm.subject_id,
+                            expires_at,
+                        )
+                        .execute(&mut *conn)
+                        .await;
+
+                        match res {
+                            Ok(r) if r.rows_affected() > 0 => updated += 1,
+                            Ok(_) => skipped += 1,
+                            Err(e) => {
+                                warnings.push(format!("merge permission: {e}"));
+                                failed += 1;
+                            }
+                        }
+                    }
                 }
             } else {
                 let expires_at = perm
@@ -417,20 +1209,21 @@ impl BackupServiceImpl {
                     .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
                     .map(|dt| dt.with_timezone(&Utc));
 
-                let res = sqlx::query(
+                let res = sqlx::query!(
                     r#"INSERT INTO bookmark_permissions
-                       (tenant_id, resource_type, resource_id, relation, subject_type, subject_id, granted_by, expires_at)
-                       VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+                       (tenant_id, resource_type, resource_id, relation_code, subject_type, subject_id, granted_by, expires_at, subject_tenant_id)
+                       VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"#,
+                    perm.tenant_id,
+                    perm.resource_type,
+                    perm.resource_id,
+                    relation.code(),
+                    perm.subject_type,
+                    perm.subject_id,
+                    perm.granted_by,
+                    expires_at,
+                    perm.subject_tenant_id,
                 )
-                .bind(perm.tenant_id)
-                .bind(&perm.resource_type)
-                .bind(&perm.resource_id)
-                .bind(&perm.relation)
-                .bind(&perm.subject_type)
-                .bind(&perm.subject_id)
-                .bind(perm.granted_by)
-                .bind(expires_at)
-                .execute(&self.pool)
+                .execute(&mut *conn)
                 .await;
 
                 match res {
@@ -452,11 +1245,319 @@ impl BackupServiceImpl {
             failed,
         }
     }
+
+    /// Warn about bookmark permissions whose bookmark is neither stored nor part of
+    /// this restore, e.g. when only permissions are restored. They are still imported.
+    async fn warn_missing_bookmarks(
+        &self,
+        conn: &mut PgConnection,
+        items: &[serde_json::Value],
+        warnings: &mut Vec<String>,
+    ) {
+        let referenced: BTreeSet<&str> = items
+            .iter()
+            .filter(|item| {
+                item.get("resourceType").and_then(|v| v.as_str())
+                    == Some(ResourceType::Bookmark.as_str())
+            })
+            .filter_map(|item| item.get("resourceId").and_then(|v| v.as_str()))
+            .filter(|id| *id != WILDCARD_RESOURCE)
+            .collect();
+        if referenced.is_empty() {
+            return;
+        }
+
+        let mut uuids = Vec::with_capacity(referenced.len());
+        let mut malformed = Vec::new();
+        for id in referenced {
+            match Uuid::parse_str(id) {
+                Ok(uuid) => uuids.push(uuid),
+                Err(_) => malformed.push(id),
+            }
+        }
+        let found = match sqlx::query_scalar!(
+            "SELECT id FROM bookmark_bookmarks WHERE id = ANY($1)",
+            &uuids,
+        )
+        .fetch_all(&mut *conn)
+        .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                tracing::warn!(error = %e, "checking permission references failed");
+                return;
+            }
+        };
+        let found: HashSet<Uuid> = found.into_iter().collect();
+
+        for id in malformed {
+            warnings.push(format!("permission references missing bookmark {id}"));
+        }
+        for id in uuids.iter().filter(|id| !found.contains(id)) {
+            warnings.push(format!("permission references missing bookmark {id}"));
+        }
+    }
+}
+
+// --- Streaming export ---
+
+/// Layout of a streamed export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamFormat {
+    /// A header line followed by one line per entity, as `StreamExportBackup` sends.
+    Ndjson,
+    /// The JSON document `ExportBackup` returns, written a piece at a time.
+    Document,
 }
 
-// --- SQLx row types for raw queries ---
+/// Buffers the export and emits it as bounded-size chunks.
+struct ChunkWriter {
+    tx: mpsc::Sender<Result<ExportBackupChunk, Status>>,
+    format: StreamFormat,
+    buf: Vec<u8>,
+    sequence: u64,
+    counts: HashMap<String, i64>,
+    /// Entity type whose array is open in a [`StreamFormat::Document`] export.
+    open_array: Option<String>,
+}
+
+impl ChunkWriter {
+    fn new(tx: mpsc::Sender<Result<ExportBackupChunk, Status>>, format: StreamFormat) -> Self {
+        Self {
+            tx,
+            format,
+            buf: Vec::with_capacity(STREAM_CHUNK_BYTES),
+            sequence: 0,
+            counts: HashMap::new(),
+            open_array: None,
+        }
+    }
+
+    async fn write_header(&mut self, header: &StreamHeader<'_>) -> Result<(), Status> {
+        if self.format == StreamFormat::Ndjson {
+            return self.write_line(header).await;
+        }
+        // The header's fields open the document; entity arrays follow under "data".
+        let mut head = serde_json::to_vec(header)
+            .map_err(|e| Status::internal(format!("serialize backup: {e}")))?;
+        head.pop();
+        self.buf.extend_from_slice(&head);
+        self.buf.extend_from_slice(br#","data":{"#);
+        Ok(())
+    }
+
+    async fn write_line<T: Serialize>(&mut self, value: &T) -> Result<(), Status> {
+        serde_json::to_writer(&mut self.buf, value)
+            .map_err(|e| Status::internal(format!("serialize backup: {e}")))?;
+        self.buf.push(b'\n');
+        if self.buf.len() >= STREAM_CHUNK_BYTES {
+            self.flush(false).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_entity(
+        &mut self,
+        entity_type: &str,
+        data: serde_json::Value,
+    ) -> Result<(), Status> {
+        *self.counts.entry(entity_type.to_string()).or_insert(0) += 1;
+        if self.format == StreamFormat::Ndjson {
+            return self.write_line(&StreamEntity { entity_type, data }).await;
+        }
+
+        if self.open_array.as_deref() == Some(entity_type) {
+            self.buf.push(b',');
+        } else {
+            if self.open_array.is_some() {
+                self.buf.extend_from_slice(b"],");
+            }
+            self.buf.extend_from_slice(format!("\"{entity_type}\":[").as_bytes());
+            self.open_array = Some(entity_type.to_string());
+        }
+        serde_json::to_writer(&mut self.buf, &data)
+            .map_err(|e| Status::internal(format!("serialize backup: {e}")))?;
+        if self.buf.len() >= STREAM_CHUNK_BYTES {
+            self.flush(false).await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self, last: bool) -> Result<(), Status> {
+        if last && self.format == StreamFormat::Document {
+            if self.open_array.take().is_some() {
+                self.buf.push(b']');
+            }
+            self.buf.extend_from_slice(b"}}");
+        }
+        let chunk = ExportBackupChunk {
+            data: std::mem::replace(&mut self.buf, Vec::with_capacity(STREAM_CHUNK_BYTES)),
+            sequence: self.sequence,
+            last,
+            entity_counts: if last { self.counts.clone() } else { HashMap::new() },
+        };
+        self.sequence += 1;
+        // The receiver is gone when the client cancels the stream.
+        self.tx
+            .send(Ok(chunk))
+            .await
+            .map_err(|_| Status::cancelled("client disconnected"))
+    }
+}
+
+/// Page through bookmarks and permissions with keyset pagination so only one
+/// page of rows and one chunk of output are held in memory at a time.
+async fn stream_export(
+    pool: &PgPool,
+    tenant_filter: Option<i32>,
+    tenant_id: i32,
+    full_backup: bool,
+    selection: EntitySelection,
+    writer: &mut ChunkWriter,
+) -> Result<(), Status> {
+    writer
+        .write_header(&StreamHeader {
+            module: BACKUP_MODULE,
+            version: CURRENT_VERSION,
+            exported_at: Utc::now().to_rfc3339(),
+            tenant_id: tenant_id as u32,
+            full_backup,
+        })
+        .await?;
+
+    if selection.bookmarks {
+        let mut cursor: Option<(chrono::DateTime<Utc>, Uuid)> = None;
+        loop {
+            let rows = sqlx::query_as!(
+                BookmarkRow,
+                r#"
+                SELECT id, tenant_id, url, title, description, tags, created_by,
+                       create_time, update_time, original_url, notes, metadata AS "metadata: _",
+                       kind, is_private
+                FROM bookmark_bookmarks
+                WHERE ($1::INTEGER IS NULL OR tenant_id = $1)
+                  AND ($2::TIMESTAMPTZ IS NULL OR (create_time, id) > ($2, $3))
+                ORDER BY create_time, id
+                LIMIT $4
+                "#,
+                tenant_filter,
+                cursor.map(|c| c.0),
+                cursor.map(|c| c.1),
+                STREAM_PAGE_SIZE,
+            )
+            .fetch_all(pool)
+            .await
+            .map_err(ServiceError::database)?;
+
+            let Some(last) = rows.last() else { break };
+            cursor = Some((last.create_time, last.id));
+            let page_len = rows.len() as i64;
+
+            for row in &rows {
+                writer.write_entity("bookmarks", bookmark_to_json(row)).await?;
+            }
+            if page_len < STREAM_PAGE_SIZE {
+                break;
+            }
+        }
+
+        // Few per tenant, so fetched in one query
+        let keys = sqlx::query_as!(
+            FieldKeyRow,
+            r#"SELECT * FROM bookmark_field_keys
+               WHERE ($1::INTEGER IS NULL OR tenant_id = $1)
+               ORDER BY create_time"#,
+            tenant_filter,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(ServiceError::database)?;
+        for key in &keys {
+            writer.write_entity("fieldKeys", field_key_to_json(key)).await?;
+        }
+    }
+
+    if selection.permissions {
+        let mut last_id = 0_i32;
+        loop {
+            let rows = sqlx::query_as!(
+                PermissionRecord,
+                r#"
+                SELECT * FROM bookmark_permissions
+                WHERE ($1::INTEGER IS NULL OR tenant_id = $1) AND id > $2
+                ORDER BY id
+                LIMIT $3
+                "#,
+                tenant_filter,
+                last_id,
+                STREAM_PAGE_SIZE,
+            )
+            .fetch_all(pool)
+            .await
+            .and_then(permission_rows)
+            .map_err(ServiceError::database)?;
+
+            let Some(last) = rows.last() else { break };
+            last_id = last.id;
+            let page_len = rows.len() as i64;
+
+            for row in &rows {
+                writer
+                    .write_entity("permissions", permission_to_json(row))
+                    .await?;
+            }
+            if page_len < STREAM_PAGE_SIZE {
+                break;
+            }
+        }
+    }
+
+    writer.flush(true).await
+}
+
+/// What [`upload_parts`] uploaded.
+struct UploadSummary {
+    checksum_sha256: String,
+    size_bytes: i64,
+    entity_counts: HashMap<String, i64>,
+}
+
+/// Re-chunk export output into parts of the storage's part size and upload them,
+/// hashing as they go. `None` when the export stopped before its last chunk.
+async fn upload_parts(
+    storage: &BackupStorage,
+    upload: &mut dyn ObjectUpload,
+    rx: &mut mpsc::Receiver<Result<ExportBackupChunk, Status>>,
+) -> Result<Option<UploadSummary>, Status> {
+    let storage_err = |e: anyhow::Error| Status::internal(format!("backup storage: {e}"));
+    let mut hasher = Sha256::new();
+    let mut size_bytes = 0i64;
+    let mut part = Vec::with_capacity(storage.part_size());
+
+    while let Some(chunk) = rx.recv().await {
+        let chunk = chunk?;
+        hasher.update(&chunk.data);
+        size_bytes += chunk.data.len() as i64;
+        part.extend_from_slice(&chunk.data);
+        if part.len() >= storage.part_size() || chunk.last {
+            upload
+                .put_part(std::mem::replace(&mut part, Vec::with_capacity(storage.part_size())))
+                .await
+                .map_err(storage_err)?;
+        }
+        if chunk.last {
+            return Ok(Some(UploadSummary {
+                checksum_sha256: format!("{:x}", hasher.finalize()),
+                size_bytes,
+                entity_counts: chunk.entity_counts,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+// --- Row types for the checked queries ---
 
-#[derive(sqlx::FromRow)]
 struct BookmarkRow {
     id: Uuid,
     tenant_id: i32,
@@ -467,21 +1568,62 @@ struct BookmarkRow {
     created_by: Option<i32>,
     create_time: chrono::DateTime<Utc>,
     update_time: chrono::DateTime<Utc>,
+    original_url: Option<String>,
+    notes: String,
+    metadata: Json<HashMap<String, String>>,
+    kind: Option<String>,
+    is_private: bool,
 }
 
-#[derive(sqlx::FromRow)]
-struct PermissionRow {
-    #[allow(dead_code)]
-    id: i32,
-    tenant_id: i32,
-    resource_type: String,
-    resource_id: String,
-    relation: String,
-    subject_type: String,
-    subject_id: String,
-    granted_by: Option<i32>,
-    expires_at: Option<chrono::DateTime<Utc>>,
-    create_time: chrono::DateTime<Utc>,
+/// Append a line for `COPY ... (FORMAT csv)`. Every value is quoted, so only the
+/// unquoted empty field written for `None` reads back as NULL.
+fn write_csv_row(buf: &mut String, fields: &[Option<&str>]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        if let Some(value) = field {
+            buf.push('"');
+            buf.push_str(&value.replace('"', "\"\""));
+            buf.push('"');
+        }
+    }
+    buf.push('\n');
+}
+
+/// Kind to store for a restored bookmark: the backed-up one when it is known,
+/// otherwise classified from the URL.
+fn restored_kind(bk: &BookmarkBackup) -> &'static str {
+    bk.kind
+        .as_deref()
+        .and_then(BookmarkKind::from_str)
+        .unwrap_or_else(|| classify_url(&bk.url))
+        .as_str()
+}
+
+/// Hold a restored URL to the scheme policy. Sealed values were checked when
+/// first saved; `isPrivate` comes from the backup, so it alone exempts nothing.
+fn check_restored_url(policy: &UrlPolicy, bk: &BookmarkBackup) -> Result<(), Status> {
+    if field_cipher::is_sealed(&bk.url) {
+        return Ok(());
+    }
+    policy.check(&bk.url)
+}
+
+/// Normalized URL to store for a restored bookmark; private bookmarks have none.
+fn restored_normalized_url(bk: &BookmarkBackup) -> Option<String> {
+    (!bk.is_private).then(|| normalize_url(&bk.url))
+}
+
+/// Union of two tag lists, keeping the order of `current` followed by new tags.
+fn merge_tags(current: &[String], incoming: &[String]) -> Vec<String> {
+    let mut merged = current.to_vec();
+    for tag in incoming {
+        if !merged.contains(tag) {
+            merged.push(tag.clone());
+        }
+    }
+    merged
 }
 
 fn bookmark_to_json(row: &BookmarkRow) -> serde_json::Value {
@@ -495,6 +1637,21 @@ fn bookmark_to_json(row: &BookmarkRow) -> serde_json::Value {
         "createdBy": row.created_by,
         "createTime": row.create_time.to_rfc3339(),
         "updateTime": row.update_time.to_rfc3339(),
+        "originalUrl": row.original_url,
+        "notes": row.notes,
+        "metadata": row.metadata.0,
+        "kind": row.kind,
+        "isPrivate": row.is_private,
+    })
+}
+
+fn field_key_to_json(row: &FieldKeyRow) -> serde_json::Value {
+    serde_json::json!({
+        "id": row.id.to_string(),
+        "tenantId": row.tenant_id,
+        "wrappedKey": row.wrapped_key,
+        "kekId": row.kek_id,
+        "createTime": row.create_time.to_rfc3339(),
     })
 }
 
@@ -509,5 +1666,43 @@ fn permission_to_json(row: &PermissionRow) -> serde_json::Value {
         "grantedBy": row.granted_by,
         "expiresAt": row.expires_at.map(|dt| dt.to_rfc3339()),
         "createTime": row.create_time.to_rfc3339(),
+        "subjectTenantId": row.subject_tenant_id,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::UrlPolicyConfig;
+
+    fn restored(url: &str, is_private: bool) -> BookmarkBackup {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4().to_string(),
+            "tenantId": 1,
+            "url": url,
+            "title": "",
+            "description": "",
+            "tags": [],
+            "createdBy": null,
+            "createTime": "2024-01-01T00:00:00Z",
+            "updateTime": "2024-01-01T00:00:00Z",
+            "isPrivate": is_private,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn restored_urls_are_checked_unless_sealed() {
+        let policy = UrlPolicy::new(&UrlPolicyConfig::default());
+        let sealed = format!("enc:v1:{}:{}", Uuid::new_v4(), "A".repeat(40));
+
+        assert!(check_restored_url(&policy, &restored("https://example.com/", false)).is_ok());
+        assert!(check_restored_url(&policy, &restored(&sealed, true)).is_ok());
+        // Claiming to be private does not exempt a plaintext URL.
+        for url in ["javascript:alert(1)", "file:///etc/passwd"] {
+            assert!(check_restored_url(&policy, &restored(url, true)).is_err());
+            assert!(check_restored_url(&policy, &restored(url, false)).is_err());
+        }
+        assert!(check_restored_url(&policy, &restored("enc:v1:javascript:x", true)).is_err());
+    }
+}
@@ -0,0 +1,43 @@
+use crate::data::bookmark_repo::BookmarkRow;
+use crate::service::audit_log::csv_field;
+
+pub const CSV_HEADER: &str = "url,title,description,tags,created_by,create_time,update_time\n";
+
+/// One element of a JSON array; the caller writes the brackets and commas.
+pub fn write_json(buf: &mut Vec<u8>, row: &BookmarkRow) {
+    let value = serde_json::json!({
+        "id": row.id.to_string(),
+        "url": row.url,
+        "title": row.title,
+        "description": row.description,
+        "tags": row.tags,
+        "createdBy": row.created_by,
+        "createTime": row.create_time.to_rfc3339(),
+        "updateTime": row.update_time.to_rfc3339(),
+    });
+    // Serializing a `Value` into a Vec cannot fail.
+    let _ = serde_json::to_writer(&mut *buf, &value);
+}
+
+pub fn write_csv(buf: &mut Vec<u8>, row: &BookmarkRow) {
+    let line = format!(
+        "{},{},{},{},{},{},{}\n",
+        csv_field(&row.url),
+        csv_field(&spreadsheet_safe(&row.title)),
+        csv_field(&spreadsheet_safe(&row.description)),
+        csv_field(&spreadsheet_safe(&row.tags.join(", "))),
+        row.created_by.map(|id| id.to_string()).unwrap_or_default(),
+        row.create_time.to_rfc3339(),
+        row.update_time.to_rfc3339(),
+    );
+    buf.extend_from_slice(line.as_bytes());
+}
+
+/// Prefix text a spreadsheet would evaluate as a formula with `'`.
+fn spreadsheet_safe(s: &str) -> String {
+    if s.starts_with(['=', '+', '-', '@']) {
+        format!("'{s}")
+    } else {
+        s.to_string()
+    }
+}
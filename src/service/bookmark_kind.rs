@@ -0,0 +1,110 @@
+use url::Url;
+
+use crate::data::bookmark_repo::BookmarkKind;
+
+/// Hosts whose pages are videos, with the paths that hold one (empty: any path).
+const VIDEO_HOSTS: &[(&str, &[&str])] = &[
+    ("youtube.com", &["/watch", "/shorts/", "/embed/", "/live/"]),
+    ("m.youtube.com", &["/watch", "/shorts/"]),
+    ("youtu.be", &[]),
+    ("vimeo.com", &[]),
+    ("dailymotion.com", &["/video/"]),
+    ("twitch.tv", &["/videos/"]),
+];
+
+/// Code hosts where `/owner/name` is a repository.
+const REPO_HOSTS: &[&str] = &["github.com", "gitlab.com", "bitbucket.org", "codeberg.org"];
+
+/// First path segments on code hosts that are site pages, not owners.
+const REPO_HOST_PAGES: &[&str] = &[
+    "about",
+    "collections",
+    "explore",
+    "features",
+    "marketplace",
+    "orgs",
+    "settings",
+    "sponsors",
+    "topics",
+    "trending",
+    "users",
+];
+
+const IMAGE_EXTENSIONS: &[&str] = &["avif", "bmp", "gif", "jpeg", "jpg", "png", "svg", "webp"];
+const VIDEO_EXTENSIONS: &[&str] = &["m4v", "mkv", "mov", "mp4", "ogv", "webm"];
+
+/// Kind of a URL judged from the URL alone: known video and code hosts, then
+/// the file extension. Anything else is `Other` until its page is fetched.
+pub fn classify_url(url: &str) -> BookmarkKind {
+    let Ok(url) = Url::parse(url) else {
+        return BookmarkKind::Other;
+    };
+    host_kind(&url)
+        .or_else(|| extension_kind(&url))
+        .unwrap_or_default()
+}
+
+/// Kind of a fetched page. Known hosts win, since a repository or video page
+/// is served as HTML; otherwise the Content-Type decides, with an OpenGraph
+/// `video.*` type marking HTML pages as videos. A missing Content-Type is
+/// treated as HTML, as the fetcher does.
+pub fn classify(url: &str, content_type: Option<&str>, og_type: Option<&str>) -> BookmarkKind {
+    if let Some(kind) = Url::parse(url).ok().as_ref().and_then(host_kind) {
+        return kind;
+    }
+    let mime = content_type
+        .map(|ct| ct.split(';').next().unwrap_or_default().trim().to_ascii_lowercase());
+    match mime.as_deref() {
+        Some("application/pdf") => BookmarkKind::Pdf,
+        Some(m) if m.starts_with("image/") => BookmarkKind::Image,
+        Some(m) if m.starts_with("video/") => BookmarkKind::Video,
+        None | Some("text/html" | "application/xhtml+xml") => {
+            if og_type.is_some_and(|t| t.to_ascii_lowercase().starts_with("video")) {
+                BookmarkKind::Video
+            } else {
+                BookmarkKind::Article
+            }
+        }
+        Some(_) => BookmarkKind::Other,
+    }
+}
+
+fn host_kind(url: &Url) -> Option<BookmarkKind> {
+    let host = url.host_str()?.to_ascii_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let path = url.path();
+
+    for (video_host, paths) in VIDEO_HOSTS {
+        if host == *video_host {
+            let is_video = if paths.is_empty() {
+                path.len() > 1
+            } else {
+                paths.iter().any(|p| path.starts_with(p))
+            };
+            return is_video.then_some(BookmarkKind::Video);
+        }
+    }
+
+    if REPO_HOSTS.contains(&host) {
+        let mut segments = path.split('/').filter(|s| !s.is_empty());
+        let owner = segments.next()?;
+        segments.next()?;
+        return (!REPO_HOST_PAGES.contains(&owner)).then_some(BookmarkKind::Repo);
+    }
+    None
+}
+
+fn extension_kind(url: &Url) -> Option<BookmarkKind> {
+    let file = url.path_segments()?.next_back()?;
+    let (_, ext) = file.rsplit_once('.')?;
+    let ext = ext.to_ascii_lowercase();
+    if ext == "pdf" {
+        Some(BookmarkKind::Pdf)
+    } else if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        Some(BookmarkKind::Image)
+    } else if VIDEO_EXTENSIONS.contains(&ext.as_str()) {
+        Some(BookmarkKind::Video)
+    } else {
+        None
+    }
+}
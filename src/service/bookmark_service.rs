@@ -1,10 +1,44 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
 use uuid::Uuid;
 
 use crate::authz::checker::Checker;
 use crate::authz::relations::{Relation, ResourceType, SubjectType};
-use crate::data::bookmark_repo::{BookmarkRepo, BookmarkRow};
-use crate::service::context_helper::extract_context;
+use crate::config::SearchConfig;
+use crate::data::bookmark_repo::{
+    BookmarkChanges, BookmarkFilter, BookmarkKind, BookmarkRow, BookmarkStore, BulkTagFilter,
+    NewBookmark, RevisionRow, TagOrder, TagPathRow, TAG_SEPARATOR,
+};
+use crate::data::auto_tag_rule_repo::AutoTagRuleStore;
+use crate::data::change_repo::ChangeStore;
+use crate::data::filter_expr::FilterExpr;
+use crate::data::scrub_policy_repo::ScrubPolicyStore;
+use crate::data::short_link_repo::{ShortLinkRow, ShortLinkStore};
+use crate::data::user_flag_repo::{ReadingStatus, UserFlagStore};
+use crate::events::{Event, EventPublisher, EventType};
+use crate::middleware::panic::spawn_stream;
+use crate::service::context_helper::{extract_context, RequestContext};
+use crate::service::idempotency::Idempotency;
+use crate::service::import::{self, ImportedBookmark};
+use crate::service::archiver::Archiver;
+use crate::service::auto_tag::AutoTagger;
+use crate::service::bookmark_export;
+use crate::service::bookmark_kind::classify_url;
+use crate::service::error::ServiceError;
+use crate::service::field_cipher::{FieldCipher, SealedField};
+use crate::service::page_metadata::MetadataFetcher;
+use crate::service::page_token;
+use crate::service::rank;
+use crate::service::url_normalizer::normalize_url;
+use crate::service::url_policy::UrlPolicy;
+use crate::service::url_scrubber::{compile_patterns, UrlScrubber};
 
 /// Generated proto types.
 pub mod proto {
@@ -13,46 +47,198 @@ pub mod proto {
 
 use proto::bookmark_service_server::BookmarkService;
 use proto::{
-    Bookmark, CreateBookmarkRequest, DeleteBookmarkRequest, GetBookmarkRequest,
-    ListBookmarksRequest, ListBookmarksResponse, UpdateBookmarkRequest,
+    ArchivedContent, BatchBookmarksResponse, CreateShortLinkRequest, RevokeShortLinkRequest,
+    ShortLink, BookmarkExportChunk, BookmarkExportFormat, BatchCreateBookmarksRequest, BatchDeleteBookmarksRequest,
+    BatchItemResult, BatchUpdateBookmarksRequest, Bookmark, BookmarkRevision,
+    CreateBookmarkRequest, DedupeMode, DeleteBookmarkRequest, DuplicateGroup,
+    ExportBookmarksRequest, FindDuplicatesRequest, FindDuplicatesResponse, GetArchivedContentRequest, GetBookmarkRequest,
+    GetTagStatsRequest, GetTagStatsResponse, ListTagTreeRequest, ListTagTreeResponse, TagNode,
+    GetUrlScrubPolicyRequest, ImportBookmarksRequest, ImportFormat,
+    ListBookmarkRevisionsRequest, ListBookmarkRevisionsResponse, ListBookmarksRequest,
+    ListBookmarksResponse, ListMostVisitedRequest, PinBookmarkRequest, PreviewUrlRequest,
+    BookmarkOrder, RecordVisitRequest, ReorderBookmarksRequest,
+    RecordVisitResponse, RevertBookmarkRequest, SearchBookmarksRequest, SetReadingStatusRequest,
+    SearchBookmarksResponse, SearchHit, SyncChangesRequest, SyncChangesResponse, TagStat,
+    TagStatsOrder,
+    UnpinBookmarkRequest, UpdateTagsBulkRequest, UpdateTagsBulkResponse,
+    BookmarkChangeEvent, BookmarkChangeType, WatchBookmarksRequest, UpdateBookmarkNotesRequest,
+    UpdateBookmarkRequest,
+    UpdateUrlScrubPolicyRequest, UrlPreview, UrlScrubPolicy,
 };
 
+/// A change to the caller's private flags on a bookmark.
+enum UserFlagUpdate {
+    Pinned(bool),
+    ReadingStatus(ReadingStatus),
+}
+
+/// Maximum number of items accepted by a batch RPC.
+const MAX_BATCH_SIZE: usize = 100;
+
+/// Maximum number of tags added or removed by one `UpdateTagsBulk` call.
+const MAX_BULK_TAGS: usize = 50;
+
+/// Maximum number of bookmarks in one imported file.
+const MAX_IMPORT_SIZE: usize = 10_000;
+
+/// Length of a generated short-link code, and how often to retry on collision.
+const SHORT_CODE_BYTES: usize = 6;
+const SHORT_CODE_ATTEMPTS: usize = 5;
+
+/// Bookmarks fetched per page while streaming an export.
+const EXPORT_PAGE_SIZE: u32 = 500;
+/// Flush an export chunk once it reaches this many bytes.
+const EXPORT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Default and maximum change-log entries read by one `SyncChanges` call.
+const SYNC_DEFAULT_PAGE_SIZE: u32 = 500;
+const SYNC_MAX_PAGE_SIZE: u32 = 1000;
+
+/// Maximum size of a bookmark's notes, in bytes.
+const MAX_NOTES_BYTES: usize = 64 * 1024;
+
+/// Limits on a bookmark's metadata map.
+const MAX_METADATA_ENTRIES: usize = 50;
+const MAX_METADATA_KEY_BYTES: usize = 64;
+const MAX_METADATA_VALUE_BYTES: usize = 1024;
+const MAX_METADATA_TOTAL_BYTES: usize = 16 * 1024;
+
+#[derive(Clone)]
 pub struct BookmarkServiceImpl {
-    repo: BookmarkRepo,
+    repo: Arc<dyn BookmarkStore>,
     checker: Checker,
+    scrub_repo: Arc<dyn ScrubPolicyStore>,
+    fetcher: MetadataFetcher,
+    archiver: Archiver,
+    flags: Arc<dyn UserFlagStore>,
+    short_links: Arc<dyn ShortLinkStore>,
+    changes: Arc<dyn ChangeStore>,
+    auto_tag_rules: Arc<dyn AutoTagRuleStore>,
+    idempotency: Idempotency,
+    events: EventPublisher,
+    url_policy: UrlPolicy,
+    cipher: FieldCipher,
+    fuzzy_threshold: f32,
 }
 
 impl BookmarkServiceImpl {
-    pub fn new(repo: BookmarkRepo, checker: Checker) -> Self {
-        Self { repo, checker }
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        repo: Arc<dyn BookmarkStore>,
+        checker: Checker,
+        scrub_repo: Arc<dyn ScrubPolicyStore>,
+        fetcher: MetadataFetcher,
+        archiver: Archiver,
+        flags: Arc<dyn UserFlagStore>,
+        short_links: Arc<dyn ShortLinkStore>,
+        changes: Arc<dyn ChangeStore>,
+        auto_tag_rules: Arc<dyn AutoTagRuleStore>,
+        idempotency: Idempotency,
+        events: EventPublisher,
+        url_policy: UrlPolicy,
+        cipher: FieldCipher,
+    ) -> Self {
+        Self {
+            repo,
+            checker,
+            scrub_repo,
+            fetcher,
+            archiver,
+            flags,
+            short_links,
+            changes,
+            auto_tag_rules,
+            idempotency,
+            events,
+            url_policy,
+            cipher,
+            fuzzy_threshold: SearchConfig::default().fuzzy_threshold,
+        }
     }
-}
 
-#[tonic::async_trait]
-impl BookmarkService for BookmarkServiceImpl {
-    async fn create_bookmark(
+    /// Trigram similarity a title or host needs to match a fuzzy search.
+    pub fn with_fuzzy_threshold(mut self, threshold: f32) -> Self {
+        self.fuzzy_threshold = threshold;
+        self
+    }
+
+    /// `CreateBookmark` without idempotency-key handling.
+    async fn do_create_bookmark(
         &self,
         request: Request<CreateBookmarkRequest>,
     ) -> Result<Response<Bookmark>, Status> {
         let ctx = extract_context(&request)?;
-        let req = request.into_inner();
+        let mut req = request.into_inner();
 
         if req.url.is_empty() {
             return Err(Status::invalid_argument("url is required"));
         }
+        self.url_policy.check(&req.url)?;
+
+        let (url, original_url) = self.scrub_url(ctx.tenant_id, &req.url).await?;
+
+        // Private bookmarks have no normalized URL to compare.
+        let dedupe = DedupeMode::try_from(req.dedupe).unwrap_or(DedupeMode::Unspecified);
+        if dedupe != DedupeMode::Unspecified && !req.is_private {
+            if let Some(existing) = self.find_accessible_duplicate(&ctx, &url).await? {
+                let id = existing.id.to_string();
+                if dedupe == DedupeMode::Reject {
+                    let mut status = Status::already_exists(format!(
+                        "bookmark with this URL already exists: {id}"
+                    ));
+                    if let Ok(value) = id.parse() {
+                        status.metadata_mut().insert("x-existing-bookmark-id", value);
+                    }
+                    return Err(status);
+                }
+                let is_owner = !self.owned_ids(&ctx, &[id]).await?.is_empty();
+                let existing = self.reveal(existing).await?;
+                return Ok(Response::new(row_to_proto(existing, is_owner)));
+            }
+        }
+
+        // Enrichment is best effort: a slow or broken page never fails the create.
+        let mut kind = classify_url(&url);
+        if req.fetch_metadata && (req.title.is_empty() || req.description.is_empty()) {
+            match self.fetcher.fetch(&req.url).await {
+                Ok(meta) => {
+                    kind = meta.kind;
+                    if req.title.is_empty() {
+                        req.title = meta.title.unwrap_or_default();
+                    }
+                    if req.description.is_empty() {
+                        req.description = meta.description.unwrap_or_default();
+                    }
+                }
+                Err(e) => tracing::debug!(url = %url, error = %e, "metadata fetch failed"),
+            }
+        }
+        self.auto_tagger(ctx.tenant_id)
+            .await?
+            .apply(&url, &req.title, &mut req.tags);
+        let id = Uuid::new_v4();
+        let (url, original_url) = if req.is_private {
+            self.seal_urls(ctx.tenant_id, id, url, original_url).await?
+        } else {
+            (url, original_url)
+        };
 
         let row = self
             .repo
             .create(
                 ctx.tenant_id,
-                &req.url,
+                id,
+                &url,
                 &req.title,
                 &req.description,
                 &req.tags,
                 ctx.user_id.parse::<i32>().ok(),
+                original_url.as_deref(),
+                kind,
+                req.is_private,
             )
             .await
-            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+            .map_err(ServiceError::database)?;
 
         // Grant OWNER permission to the creator
         let _ = self
@@ -71,7 +257,397 @@ impl BookmarkService for BookmarkServiceImpl {
             )
             .await;
 
-        Ok(Response::new(row_to_proto(row)))
+        if !row.is_private {
+            self.archiver
+                .spawn_snapshot(ctx.tenant_id, row.id, row.url.clone());
+        }
+        self.events
+            .publish(Event::bookmark(EventType::BookmarkCreated, &ctx.user_id, &row));
+
+        Ok(Response::new(row_to_proto(self.reveal(row).await?, true)))
+    }
+
+    /// Seal a private bookmark's URL and pre-scrub original.
+    async fn seal_urls(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+        url: String,
+        original_url: Option<String>,
+    ) -> Result<(String, Option<String>), Status> {
+        let original_url = match original_url {
+            Some(original) => {
+                Some(self.seal(tenant_id, id, SealedField::OriginalUrl, &original).await?)
+            }
+            None => None,
+        };
+        Ok((self.seal(tenant_id, id, SealedField::Url, &url).await?, original_url))
+    }
+
+    async fn seal(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+        field: SealedField,
+        value: &str,
+    ) -> Result<String, Status> {
+        if !self.cipher.is_enabled() {
+            return Err(Status::failed_precondition(
+                "private bookmarks require field encryption, which is not configured",
+            ));
+        }
+        self.cipher
+            .seal(tenant_id, id, field, value)
+            .await
+            .map_err(cipher_status)
+    }
+
+    async fn open(
+        &self,
+        tenant_id: i32,
+        id: Uuid,
+        field: SealedField,
+        value: &str,
+    ) -> Result<String, Status> {
+        self.cipher
+            .open(tenant_id, id, field, value)
+            .await
+            .map_err(cipher_status)
+    }
+
+    /// Decrypt a private bookmark's URLs and notes. Only for bookmarks the
+    /// caller passed a Read check on.
+    async fn reveal(&self, row: BookmarkRow) -> Result<BookmarkRow, Status> {
+        reveal(&self.cipher, row).await
+    }
+
+    async fn reveal_all(&self, rows: Vec<BookmarkRow>) -> Result<Vec<BookmarkRow>, Status> {
+        reveal_all(&self.cipher, rows).await
+    }
+
+    async fn reveal_revision(&self, mut row: RevisionRow) -> Result<RevisionRow, Status> {
+        if row.is_private {
+            let (tenant_id, id) = (row.tenant_id, row.bookmark_id);
+            row.url = self.open(tenant_id, id, SealedField::Url, &row.url).await?;
+            if let Some(original) = row.original_url.take() {
+                row.original_url = Some(
+                    self.open(tenant_id, id, SealedField::OriginalUrl, &original)
+                        .await?,
+                );
+            }
+        }
+        Ok(row)
+    }
+
+    /// Put an update's URLs and notes in the form the bookmark stores them:
+    /// sealed when it is (or becomes) private. Changing `is_private` rewrites
+    /// the stored URLs and notes even when the update does not touch them.
+    async fn protect_changes(
+        &self,
+        tenant_id: i32,
+        changes: &mut BookmarkChanges,
+    ) -> Result<(), Status> {
+        if changes.url.is_none() && changes.is_private.is_none() {
+            return Ok(());
+        }
+        let current = self
+            .repo
+            .get_by_id(tenant_id, changes.id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("bookmark not found"))?;
+        let private = changes.is_private.unwrap_or(current.is_private);
+        changes.is_private = Some(private);
+        let toggled = private != current.is_private;
+
+        let id = changes.id;
+        if toggled {
+            if changes.url.is_none() {
+                changes.url = Some(self.open(tenant_id, id, SealedField::Url, &current.url).await?);
+                changes.original_url = match &current.original_url {
+                    Some(original) => Some(
+                        self.open(tenant_id, id, SealedField::OriginalUrl, original)
+                            .await?,
+                    ),
+                    None => None,
+                };
+            }
+            changes.notes = Some(
+                self.open(tenant_id, id, SealedField::Notes, &current.notes)
+                    .await?,
+            );
+        }
+        if private {
+            if let Some(url) = changes.url.take() {
+                let (url, original_url) = self
+                    .seal_urls(tenant_id, id, url, changes.original_url.take())
+                    .await?;
+                changes.url = Some(url);
+                changes.original_url = original_url;
+            }
+            if let Some(notes) = &changes.notes {
+                changes.notes = Some(self.seal(tenant_id, id, SealedField::Notes, notes).await?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the page snapshot of a private bookmark; snapshots are not
+    /// encrypted.
+    async fn forget_snapshot(&self, tenant_id: i32, id: Uuid) {
+        if let Err(e) = self.archiver.repo().delete(tenant_id, id).await {
+            tracing::warn!(
+                bookmark_id = %id,
+                error = %e,
+                "failed to drop snapshot of private bookmark"
+            );
+        }
+    }
+
+    async fn scrubber(&self, tenant_id: i32) -> Result<UrlScrubber, Status> {
+        let policy = self
+            .scrub_repo
+            .get(tenant_id)
+            .await
+            .map_err(ServiceError::database)?;
+        UrlScrubber::from_policy(policy.as_ref())
+    }
+
+    /// The tenant's enabled auto-tagging rules, compiled.
+    async fn auto_tagger(&self, tenant_id: i32) -> Result<AutoTagger, Status> {
+        let rules = self
+            .auto_tag_rules
+            .list(tenant_id)
+            .await
+            .map_err(ServiceError::database)?;
+        Ok(AutoTagger::new(&rules))
+    }
+
+    /// Apply the tenant's scrubbing policy to a URL. Returns the URL to store and,
+    /// when anything was stripped, the original to preserve.
+    async fn scrub_url(&self, tenant_id: i32, url: &str) -> Result<(String, Option<String>), Status> {
+        Ok(split_scrubbed(&self.scrubber(tenant_id).await?, url))
+    }
+
+    /// First bookmark with the same normalized URL that the caller can read.
+    async fn find_accessible_duplicate(
+        &self,
+        ctx: &RequestContext,
+        url: &str,
+    ) -> Result<Option<BookmarkRow>, Status> {
+        let candidates = self
+            .repo
+            .find_by_normalized_url(ctx.tenant_id, &normalize_url(url))
+            .await
+            .map_err(ServiceError::database)?;
+
+        for row in candidates {
+            if self
+                .checker
+                .can_read(ctx.tenant_id, &ctx.user_id, &row.id.to_string(), &ctx.role_ids)
+                .await
+                .is_ok()
+            {
+                return Ok(Some(row));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Bookmark IDs (of those given) that the caller owns directly.
+    async fn owned_ids(
+        &self,
+        ctx: &RequestContext,
+        ids: &[String],
+    ) -> Result<std::collections::HashSet<String>, Status> {
+        let owned = self
+            .checker
+            .engine()
+            .store()
+            .filter_owned(ctx.tenant_id, ResourceType::Bookmark, &ctx.user_id, ids)
+            .await
+            .map_err(ServiceError::authz)?;
+        Ok(owned.into_iter().collect())
+    }
+
+    /// Turn a published event into a change for a watcher, or `None` when the
+    /// watcher cannot see it. `visible` holds the bookmarks the watcher can read.
+    async fn watch_change(
+        &self,
+        ctx: &RequestContext,
+        event: &Event,
+        visible: &mut std::collections::HashSet<String>,
+    ) -> Result<Option<BookmarkChangeEvent>, Status> {
+        let id = event.key();
+        let change_type = match event.kind() {
+            EventType::BookmarkCreated => BookmarkChangeType::Created,
+            EventType::BookmarkUpdated => BookmarkChangeType::Updated,
+            // Permissions are gone once the bookmark is, so rely on what was visible.
+            EventType::BookmarkDeleted => {
+                return Ok(visible
+                    .remove(id)
+                    .then(|| change_event(BookmarkChangeType::Deleted, id, None, event)));
+            }
+            EventType::PermissionGranted | EventType::PermissionRevoked => {
+                if event.data["resourceType"] != ResourceType::Bookmark.as_str() {
+                    return Ok(None);
+                }
+                BookmarkChangeType::Created
+            }
+        };
+
+        let readable = self
+            .checker
+            .can_read(ctx.tenant_id, &ctx.user_id, id, &ctx.role_ids)
+            .await
+            .is_ok();
+        // Events are handled after the fact; a bookmark deleted since then gets
+        // its own `BookmarkDeleted` event.
+        let Ok(uuid) = Uuid::parse_str(id) else {
+            return Ok(None);
+        };
+        let Some(row) = self
+            .repo
+            .get_by_id(ctx.tenant_id, uuid)
+            .await
+            .map_err(ServiceError::database)?
+        else {
+            return Ok(None);
+        };
+        if !readable {
+            return Ok(visible
+                .remove(id)
+                .then(|| change_event(BookmarkChangeType::AccessRevoked, id, None, event)));
+        }
+        // A permission change on a bookmark the watcher could already read
+        // changes nothing they can see.
+        let permission_change = matches!(
+            event.kind(),
+            EventType::PermissionGranted | EventType::PermissionRevoked
+        );
+        if !visible.insert(id.to_string()) && permission_change {
+            return Ok(None);
+        }
+
+        let is_owner = !self.owned_ids(ctx, &[id.to_string()]).await?.is_empty();
+        let mut bookmarks = [row_to_proto(self.reveal(row).await?, is_owner)];
+        self.apply_user_flags(ctx, &mut bookmarks).await?;
+        let [bookmark] = bookmarks;
+
+        Ok(Some(change_event(change_type, id, Some(bookmark), event)))
+    }
+
+    /// Fill in the caller's private flags (pins, reading status) on bookmarks about
+    /// to be returned.
+    async fn apply_user_flags(
+        &self,
+        ctx: &RequestContext,
+        bookmarks: &mut [Bookmark],
+    ) -> Result<(), Status> {
+        let ids: Vec<Uuid> = bookmarks
+            .iter()
+            .filter_map(|b| Uuid::parse_str(&b.id).ok())
+            .collect();
+        let flags = self
+            .flags
+            .list_for_user(&ctx.user_id, &ids)
+            .await
+            .map_err(ServiceError::database)?;
+
+        for flag in flags {
+            let id = flag.bookmark_id.to_string();
+            if let Some(b) = bookmarks.iter_mut().find(|b| b.id == id) {
+                b.is_pinned = flag.is_pinned;
+                b.pinned_at = flag.pinned_at.map(|ts| prost_types::Timestamp {
+                    seconds: ts.timestamp(),
+                    nanos: ts.timestamp_subsec_nanos() as i32,
+                });
+                b.reading_status = ReadingStatus::from_str(&flag.reading_status)
+                    .unwrap_or(ReadingStatus::Unread)
+                    .to_proto();
+                b.sort_order = flag.sort_order.unwrap_or_default();
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply `update` to the caller's flags on a readable bookmark and return it.
+    async fn update_user_flags(
+        &self,
+        ctx: &RequestContext,
+        id: &str,
+        update: UserFlagUpdate,
+    ) -> Result<Bookmark, Status> {
+        let uuid = parse_uuid(id)?;
+
+        self.checker
+            .can_read(ctx.tenant_id, &ctx.user_id, id, &ctx.role_ids)
+            .await?;
+
+        let row = self
+            .repo
+            .get_by_id(ctx.tenant_id, uuid)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("bookmark not found"))?;
+
+        let result = match update {
+            UserFlagUpdate::Pinned(pinned) => {
+                self.flags
+                    .set_pinned(ctx.tenant_id, &ctx.user_id, uuid, pinned)
+                    .await
+            }
+            UserFlagUpdate::ReadingStatus(status) => {
+                self.flags
+                    .set_reading_status(ctx.tenant_id, &ctx.user_id, uuid, status)
+                    .await
+            }
+        };
+        result.map_err(ServiceError::database)?;
+
+        let is_owner = !self.owned_ids(ctx, &[id.to_string()]).await?.is_empty();
+        let mut bookmark = row_to_proto(self.reveal(row).await?, is_owner);
+        self.apply_user_flags(ctx, std::slice::from_mut(&mut bookmark))
+            .await?;
+        Ok(bookmark)
+    }
+
+    /// Carry an imported bookmark's pin and reading status over to the caller's
+    /// flags. Failures are logged; the bookmark itself was already created.
+    async fn apply_imported_flags(&self, ctx: &RequestContext, id: Uuid, item: &ImportedBookmark) {
+        if item.pinned {
+            if let Err(e) = self.flags.set_pinned(ctx.tenant_id, &ctx.user_id, id, true).await {
+                tracing::warn!(bookmark_id = %id, error = %e, "failed to pin imported bookmark");
+            }
+        }
+        if let Some(status) = item.reading_status {
+            if let Err(e) = self
+                .flags
+                .set_reading_status(ctx.tenant_id, &ctx.user_id, id, status)
+                .await
+            {
+                tracing::warn!(bookmark_id = %id, error = %e, "failed to set imported reading status");
+            }
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl BookmarkService for BookmarkServiceImpl {
+    type ExportBookmarksStream = ReceiverStream<Result<BookmarkExportChunk, Status>>;
+    type WatchBookmarksStream = ReceiverStream<Result<BookmarkChangeEvent, Status>>;
+
+    async fn create_bookmark(
+        &self,
+        request: Request<CreateBookmarkRequest>,
+    ) -> Result<Response<Bookmark>, Status> {
+        let claim = self.idempotency.begin("CreateBookmark", &request).await?;
+        if let Some(replay) = claim.replay()? {
+            return Ok(replay);
+        }
+        let result = self.do_create_bookmark(request).await;
+        self.idempotency.finish(claim, &result).await;
+        result
     }
 
     async fn get_bookmark(
@@ -88,14 +664,34 @@ impl BookmarkService for BookmarkServiceImpl {
             .can_read(ctx.tenant_id, &ctx.user_id, &req.id, &ctx.role_ids)
             .await?;
 
-        let row = self
+        let mut row = self
             .repo
-            .get_by_id(id)
+            .get_by_id(ctx.tenant_id, id)
             .await
-            .map_err(|e| Status::internal(format!("database error: {e}")))?
-            .ok_or_else(|| Status::not_found("bookmark not found"))?;
+            .map_err(ServiceError::database)?;
+        if row.is_none() {
+            // Not local: the read may have been allowed by a federated tuple.
+            if let Some(owner_tenant) = self
+                .checker
+                .engine()
+                .federated_tenant(ctx.tenant_id, &ctx.user_id, ResourceType::Bookmark, &req.id)
+                .await
+                .map_err(ServiceError::authz)?
+            {
+                row = self
+                    .repo
+                    .get_by_id(owner_tenant, id)
+                    .await
+                    .map_err(ServiceError::database)?;
+            }
+        }
+        let row = row.ok_or_else(|| Status::not_found("bookmark not found"))?;
 
-        Ok(Response::new(row_to_proto(row)))
+        let is_owner = !self.owned_ids(&ctx, &[req.id]).await?.is_empty();
+        let mut bookmark = row_to_proto(self.reveal(row).await?, is_owner);
+        self.apply_user_flags(&ctx, std::slice::from_mut(&mut bookmark))
+            .await?;
+        Ok(Response::new(bookmark))
     }
 
     async fn list_bookmarks(
@@ -106,31 +702,85 @@ impl BookmarkService for BookmarkServiceImpl {
         let req = request.into_inner();
 
         let page = req.page.unwrap_or(1).max(1);
-        let page_size = req.page_size.unwrap_or(20).min(100);
+        let page_size = req.page_size.unwrap_or(20).clamp(1, 100);
+        let manual_order = BookmarkOrder::try_from(req.order) == Ok(BookmarkOrder::Manual);
+        if manual_order && !req.pinned_only {
+            return Err(Status::invalid_argument("manual order requires pinned_only"));
+        }
+        if manual_order && !req.page_token.is_empty() {
+            return Err(Status::invalid_argument("manual order pages by page, not page_token"));
+        }
+        let after = page_token::decode(&req.page_token)?
+            .map(|c| {
+                Uuid::parse_str(&c.id)
+                    .map(|id| (c.create_time, id))
+                    .map_err(|_| Status::invalid_argument("invalid page_token"))
+            })
+            .transpose()?;
 
-        // Get accessible bookmark IDs from authz
-        let accessible_ids = self
+        let filter = BookmarkFilter {
+            created_after: req
+                .created_after
+                .as_ref()
+                .map(timestamp_to_datetime)
+                .transpose()?,
+            created_before: req
+                .created_before
+                .as_ref()
+                .map(timestamp_to_datetime)
+                .transpose()?,
+            created_by: req.created_by.map(|v| v as i32),
+            user_id: ctx.user_id.clone(),
+            pinned_only: req.pinned_only,
+            reading_status: ReadingStatus::from_proto(req.reading_status),
+            metadata: (!req.metadata_filter.is_empty()).then_some(req.metadata_filter),
+            kind: BookmarkKind::from_proto(req.kind),
+            manual_order,
+            federated: self.checker.engine().federation(),
+            tag: tag_filter(req.tag_filter.as_deref().unwrap_or_default())?,
+            expr: filter_expr(&req.filter)?,
+        };
+
+        let subjects = self
             .checker
-            .list_accessible_bookmarks(ctx.tenant_id, &ctx.user_id, &ctx.role_ids)
+            .subjects(ctx.tenant_id, &ctx.user_id, &ctx.role_ids)
             .await
-            .map_err(|e| Status::internal(format!("authz error: {e}")))?;
-
-        let uuids: Vec<Uuid> = accessible_ids
-            .iter()
-            .filter_map(|id| Uuid::parse_str(id).ok())
-            .collect();
+            .map_err(ServiceError::authz)?;
 
-        let (rows, total) = self
+        let (mut rows, total) = self
             .repo
-            .list_by_ids(ctx.tenant_id, &uuids, page, page_size)
+            .list_accessible(ctx.tenant_id, &subjects, &filter, after, page, page_size)
             .await
-            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+            .map_err(ServiceError::database)?;
+
+        let next_page_token = if rows.len() > page_size as usize {
+            rows.truncate(page_size as usize);
+            rows.last()
+                .filter(|_| !manual_order)
+                .map(|r| page_token::encode(r.create_time, &r.id.to_string()))
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
 
-        let bookmarks: Vec<Bookmark> = rows.into_iter().map(row_to_proto).collect();
+        let page_ids: Vec<String> = rows.iter().map(|r| r.id.to_string()).collect();
+        let owned = self.owned_ids(&ctx, &page_ids).await?;
+
+        let mut bookmarks: Vec<Bookmark> = self
+            .reveal_all(rows)
+            .await?
+            .into_iter()
+            .map(|r| {
+                let is_owner = owned.contains(&r.id.to_string());
+                row_to_proto(r, is_owner)
+            })
+            .collect();
+        self.apply_user_flags(&ctx, &mut bookmarks).await?;
 
         Ok(Response::new(ListBookmarksResponse {
             bookmarks,
             total: total as u32,
+            next_page_token,
         }))
     }
 
@@ -148,26 +798,36 @@ impl BookmarkService for BookmarkServiceImpl {
             .can_write(ctx.tenant_id, &ctx.user_id, &req.id, &ctx.role_ids)
             .await?;
 
-        let tags = if req.update_tags {
-            Some(req.tags.as_slice())
-        } else {
-            None
-        };
+        let mut changes = masked_changes(id, &req)?;
+        if let Some(u) = &changes.url {
+            self.url_policy.check(u)?;
+            let (url, original) = self.scrub_url(ctx.tenant_id, u).await?;
+            changes.kind = Some(classify_url(&url));
+            changes.url = Some(url);
+            changes.original_url = original;
+        }
+        self.protect_changes(ctx.tenant_id, &mut changes).await?;
 
         let row = self
             .repo
-            .update(
-                id,
-                req.url.as_deref(),
-                req.title.as_deref(),
-                req.description.as_deref(),
-                tags,
-            )
+            .update(ctx.tenant_id, &changes, ctx.user_id.parse::<i32>().ok())
             .await
-            .map_err(|e| Status::internal(format!("database error: {e}")))?
+            .map_err(ServiceError::database)?
             .ok_or_else(|| Status::not_found("bookmark not found"))?;
 
-        Ok(Response::new(row_to_proto(row)))
+        if changes.url.is_some() {
+            if row.is_private {
+                self.forget_snapshot(row.tenant_id, row.id).await;
+            } else {
+                self.archiver
+                    .spawn_snapshot(ctx.tenant_id, row.id, row.url.clone());
+            }
+        }
+        self.events
+            .publish(Event::bookmark(EventType::BookmarkUpdated, &ctx.user_id, &row));
+
+        let is_owner = !self.owned_ids(&ctx, &[req.id]).await?.is_empty();
+        Ok(Response::new(row_to_proto(self.reveal(row).await?, is_owner)))
     }
 
     async fn delete_bookmark(
@@ -186,9 +846,9 @@ impl BookmarkService for BookmarkServiceImpl {
 
         let deleted = self
             .repo
-            .delete(id)
+            .delete(ctx.tenant_id, id)
             .await
-            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+            .map_err(ServiceError::database)?;
 
         if !deleted {
             return Err(Status::not_found("bookmark not found"));
@@ -202,30 +862,1911 @@ impl BookmarkService for BookmarkServiceImpl {
             .delete_all_for_resource(ctx.tenant_id, ResourceType::Bookmark, &req.id)
             .await;
 
+        self.events
+            .publish(Event::bookmark_deleted(ctx.tenant_id, &ctx.user_id, &req.id));
+
         Ok(Response::new(()))
     }
-}
 
-fn row_to_proto(row: BookmarkRow) -> Bookmark {
-    Bookmark {
-        id: row.id.to_string(),
-        tenant_id: row.tenant_id as u32,
-        url: row.url,
-        title: row.title,
-        description: row.description,
-        tags: row.tags,
-        created_by: row.created_by.map(|v| v as u32),
-        create_time: Some(prost_types::Timestamp {
-            seconds: row.create_time.timestamp(),
-            nanos: row.create_time.timestamp_subsec_nanos() as i32,
-        }),
-        update_time: Some(prost_types::Timestamp {
-            seconds: row.update_time.timestamp(),
-            nanos: row.update_time.timestamp_subsec_nanos() as i32,
-        }),
+    async fn batch_create_bookmarks(
+        &self,
+        request: Request<BatchCreateBookmarksRequest>,
+    ) -> Result<Response<BatchBookmarksResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+        check_batch_size(req.items.len())?;
+
+        let scrubber = self.scrubber(ctx.tenant_id).await?;
+        let tagger = self.auto_tagger(ctx.tenant_id).await?;
+        let mut results = Vec::with_capacity(req.items.len());
+        let mut indexes = Vec::new();
+        let mut items = Vec::new();
+
+        for (index, mut item) in req.items.into_iter().enumerate() {
+            if item.url.is_empty() {
+                results.push(item_error(
+                    index,
+                    String::new(),
+                    Status::invalid_argument("url is required"),
+                ));
+                continue;
+            }
+            if let Err(status) = self.url_policy.check(&item.url) {
+                results.push(item_error(index, String::new(), status));
+                continue;
+            }
+            let (url, original_url) = split_scrubbed(&scrubber, &item.url);
+            tagger.apply(&url, &item.title, &mut item.tags);
+            let kind = classify_url(&url);
+            let id = Uuid::new_v4();
+            let (url, original_url) = if item.is_private {
+                match self.seal_urls(ctx.tenant_id, id, url, original_url).await {
+                    Ok(sealed) => sealed,
+                    Err(status) => {
+                        results.push(item_error(index, String::new(), status));
+                        continue;
+                    }
+                }
+            } else {
+                (url, original_url)
+            };
+            indexes.push(index);
+            items.push(NewBookmark {
+                id,
+                kind,
+                url,
+                title: item.title,
+                description: item.description,
+                tags: item.tags,
+                original_url,
+                is_private: item.is_private,
+            });
+        }
+
+        let created = self
+            .repo
+            .batch_create(ctx.tenant_id, &ctx.user_id, ctx.user_id.parse::<i32>().ok(), &items)
+            .await
+            .map_err(ServiceError::database)?;
+
+        for (index, outcome) in indexes.into_iter().zip(created) {
+            results.push(match outcome {
+                Ok(row) => {
+                    if !row.is_private {
+                        self.archiver
+                            .spawn_snapshot(ctx.tenant_id, row.id, row.url.clone());
+                    }
+                    self.events
+                        .publish(Event::bookmark(EventType::BookmarkCreated, &ctx.user_id, &row));
+                    let id = row.id.to_string();
+                    match self.reveal(row).await {
+                        Ok(row) => item_ok(index, row, true),
+                        Err(status) => item_error(index, id, status),
+                    }
+                }
+                Err(e) => item_error(
+                    index,
+                    String::new(),
+                    ServiceError::database(e).into(),
+                ),
+            });
+        }
+
+        Ok(Response::new(batch_response(results)))
     }
-}
 
-fn parse_uuid(s: &str) -> Result<Uuid, Status> {
-    Uuid::parse_str(s).map_err(|_| Status::invalid_argument("invalid UUID"))
+    async fn import_bookmarks(
+        &self,
+        request: Request<ImportBookmarksRequest>,
+    ) -> Result<Response<BatchBookmarksResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let parsed = match ImportFormat::try_from(req.format) {
+            Ok(ImportFormat::Pocket) => import::pocket::parse(&req.data),
+            Ok(ImportFormat::Raindrop) => import::raindrop::parse(&req.data),
+            Ok(ImportFormat::Chrome) => import::chrome::parse(&req.data),
+            _ => return Err(Status::invalid_argument("unsupported import format")),
+        }
+        .map_err(|e| Status::invalid_argument(format!("invalid export file: {e}")))?;
+        if parsed.len() > MAX_IMPORT_SIZE {
+            return Err(Status::invalid_argument(format!(
+                "at most {MAX_IMPORT_SIZE} bookmarks per import"
+            )));
+        }
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            user_id = %ctx.user_id,
+            count = parsed.len(),
+            "importing bookmarks"
+        );
+
+        let scrubber = self.scrubber(ctx.tenant_id).await?;
+        let tagger = self.auto_tagger(ctx.tenant_id).await?;
+        let mut results = Vec::with_capacity(parsed.len());
+
+        // Pages are not snapshotted: an import can hold thousands of links.
+        for (chunk_index, chunk) in parsed.chunks(MAX_BATCH_SIZE).enumerate() {
+            let mut accepted = Vec::with_capacity(chunk.len());
+            let mut items = Vec::with_capacity(chunk.len());
+            for (offset, item) in chunk.iter().enumerate() {
+                let index = chunk_index * MAX_BATCH_SIZE + offset;
+                if let Err(status) = self.url_policy.check(&item.url) {
+                    results.push(item_error(index, String::new(), status));
+                    continue;
+                }
+                let (url, original_url) = split_scrubbed(&scrubber, &item.url);
+                let mut tags = item.tags.clone();
+                tagger.apply(&url, &item.title, &mut tags);
+                accepted.push((index, item));
+                items.push(NewBookmark {
+                    id: Uuid::new_v4(),
+                    kind: classify_url(&url),
+                    url,
+                    title: item.title.clone(),
+                    description: item.description.clone(),
+                    tags,
+                    original_url,
+                    is_private: false,
+                });
+            }
+
+            let created = self
+                .repo
+                .batch_create(ctx.tenant_id, &ctx.user_id, ctx.user_id.parse::<i32>().ok(), &items)
+                .await
+                .map_err(ServiceError::database)?;
+
+            for ((index, item), outcome) in accepted.into_iter().zip(created) {
+                results.push(match outcome {
+                    Ok(row) => {
+                        self.apply_imported_flags(&ctx, row.id, item).await;
+                        self.events
+                            .publish(Event::bookmark(EventType::BookmarkCreated, &ctx.user_id, &row));
+                        item_ok(index, row, true)
+                    }
+                    Err(e) => item_error(
+                        index,
+                        String::new(),
+                        ServiceError::database(e).into(),
+                    ),
+                });
+            }
+        }
+
+        Ok(Response::new(batch_response(results)))
+    }
+
+    async fn export_bookmarks(
+        &self,
+        request: Request<ExportBookmarksRequest>,
+    ) -> Result<Response<Self::ExportBookmarksStream>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+        let csv = BookmarkExportFormat::try_from(req.format) == Ok(BookmarkExportFormat::Csv);
+
+        let subjects = self
+            .checker
+            .subjects(ctx.tenant_id, &ctx.user_id, &ctx.role_ids)
+            .await
+            .map_err(ServiceError::authz)?;
+        let filter = BookmarkFilter {
+            user_id: ctx.user_id.clone(),
+            ..Default::default()
+        };
+
+        let (tx, rx) = mpsc::channel(4);
+        let repo = self.repo.clone();
+        let cipher = self.cipher.clone();
+        let tenant_id = ctx.tenant_id;
+        let method = "/bookmark.service.v1.BookmarkService/ExportBookmarks";
+        spawn_stream(method, tx.clone(), async move {
+            let mut buf = Vec::with_capacity(EXPORT_CHUNK_BYTES);
+            buf.extend_from_slice(if csv {
+                bookmark_export::CSV_HEADER.as_bytes()
+            } else {
+                b"["
+            });
+            let mut sequence = 0;
+            let mut total = 0;
+            let mut after = None;
+            loop {
+                let mut rows = match repo
+                    .list_accessible(tenant_id, &subjects, &filter, after, 1, EXPORT_PAGE_SIZE)
+                    .await
+                {
+                    Ok((rows, _)) => rows,
+                    Err(e) => {
+                        let _ = tx
+                            .send(Err(ServiceError::database(e).into()))
+                            .await;
+                        return;
+                    }
+                };
+                // list_accessible fetches one extra row to signal another page.
+                let done = rows.len() <= EXPORT_PAGE_SIZE as usize;
+                rows.truncate(EXPORT_PAGE_SIZE as usize);
+                let rows = match reveal_all(&cipher, rows).await {
+                    Ok(rows) => rows,
+                    Err(status) => {
+                        let _ = tx.send(Err(status)).await;
+                        return;
+                    }
+                };
+                for row in &rows {
+                    if csv {
+                        bookmark_export::write_csv(&mut buf, row);
+                    } else {
+                        if total > 0 {
+                            buf.push(b',');
+                        }
+                        bookmark_export::write_json(&mut buf, row);
+                    }
+                    total += 1;
+                    after = Some((row.create_time, row.id));
+
+                    if buf.len() >= EXPORT_CHUNK_BYTES {
+                        let chunk = BookmarkExportChunk {
+                            data: std::mem::take(&mut buf),
+                            sequence,
+                            last: false,
+                            total: 0,
+                        };
+                        sequence += 1;
+                        // The receiver is gone when the client cancels the stream.
+                        if tx.send(Ok(chunk)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                if done {
+                    break;
+                }
+            }
+            if !csv {
+                buf.extend_from_slice(b"]\n");
+            }
+
+            let _ = tx
+                .send(Ok(BookmarkExportChunk {
+                    data: buf,
+                    sequence,
+                    last: true,
+                    total,
+                }))
+                .await;
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn sync_changes(
+        &self,
+        request: Request<SyncChangesRequest>,
+    ) -> Result<Response<SyncChangesResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+        let cursor = page_token::decode_change_cursor(&req.cursor)?;
+        let page_size = match req.page_size {
+            0 => SYNC_DEFAULT_PAGE_SIZE,
+            n => n.min(SYNC_MAX_PAGE_SIZE),
+        };
+
+        let changes = self
+            .changes
+            .list_changes(ctx.tenant_id, cursor, page_size as i64)
+            .await
+            .map_err(ServiceError::database)?;
+        let has_more = changes.len() == page_size as usize;
+        let next_cursor = changes.last().map_or(cursor, |c| (c.txid, c.seq));
+
+        // Only the latest change to each bookmark matters.
+        let mut latest: HashMap<Uuid, bool> = HashMap::new();
+        for change in &changes {
+            latest.insert(change.bookmark_id, change.deleted);
+        }
+        let mut deleted_ids = Vec::new();
+        let mut upserted_ids = Vec::new();
+        for (id, deleted) in latest {
+            if deleted {
+                deleted_ids.push(id.to_string());
+            } else {
+                upserted_ids.push(id);
+            }
+        }
+
+        let mut upserted = Vec::new();
+        if !upserted_ids.is_empty() {
+            let accessible: std::collections::HashSet<String> = self
+                .checker
+                .list_accessible_bookmarks(ctx.tenant_id, &ctx.user_id, &ctx.role_ids)
+                .await
+                .map_err(ServiceError::authz)?
+                .into_iter()
+                .collect();
+            upserted_ids.retain(|id| accessible.contains(&id.to_string()));
+
+            let filter = BookmarkFilter {
+                user_id: ctx.user_id.clone(),
+                ..Default::default()
+            };
+            let (rows, _) = self
+                .repo
+                .list_by_ids(
+                    ctx.tenant_id,
+                    &upserted_ids,
+                    &filter,
+                    None,
+                    1,
+                    upserted_ids.len() as u32,
+                )
+                .await
+                .map_err(ServiceError::database)?;
+
+            let ids: Vec<String> = rows.iter().map(|r| r.id.to_string()).collect();
+            let owned = self.owned_ids(&ctx, &ids).await?;
+            upserted = self
+                .reveal_all(rows)
+                .await?
+                .into_iter()
+                .map(|r| {
+                    let is_owner = owned.contains(&r.id.to_string());
+                    row_to_proto(r, is_owner)
+                })
+                .collect();
+            self.apply_user_flags(&ctx, &mut upserted).await?;
+        }
+
+        Ok(Response::new(SyncChangesResponse {
+            upserted,
+            deleted_ids,
+            next_cursor: page_token::encode_change_cursor(next_cursor.0, next_cursor.1),
+            has_more,
+        }))
+    }
+
+    async fn watch_bookmarks(
+        &self,
+        request: Request<WatchBookmarksRequest>,
+    ) -> Result<Response<Self::WatchBookmarksStream>, Status> {
+        let ctx = extract_context(&request)?;
+
+        // Subscribe before listing so no change slips in between.
+        let mut events = self.events.subscribe();
+        let mut visible: std::collections::HashSet<String> = self
+            .checker
+            .list_accessible_bookmarks(ctx.tenant_id, &ctx.user_id, &ctx.role_ids)
+            .await
+            .map_err(ServiceError::authz)?
+            .into_iter()
+            .collect();
+
+        let (tx, rx) = mpsc::channel(16);
+        let svc = self.clone();
+        let method = "/bookmark.service.v1.BookmarkService/WatchBookmarks";
+        spawn_stream(method, tx.clone(), async move {
+            loop {
+                let event = tokio::select! {
+                    received = events.recv() => match received {
+                        Ok(event) => event,
+                        Err(RecvError::Lagged(_)) => {
+                            let status = Status::aborted("watcher fell behind; resync and watch again");
+                            let _ = tx.send(Err(status)).await;
+                            return;
+                        }
+                        Err(RecvError::Closed) => return,
+                    },
+                    // Stop as soon as the client goes away rather than at the next event.
+                    _ = tx.closed() => return,
+                };
+                if event.tenant_id != ctx.tenant_id {
+                    continue;
+                }
+
+                match svc.watch_change(&ctx, &event, &mut visible).await {
+                    Ok(Some(change)) => {
+                        if tx.send(Ok(change)).await.is_err() {
+                            return;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(status) => {
+                        let _ = tx.send(Err(status)).await;
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn batch_update_bookmarks(
+        &self,
+        request: Request<BatchUpdateBookmarksRequest>,
+    ) -> Result<Response<BatchBookmarksResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+        check_batch_size(req.items.len())?;
+
+        let scrubber = self.scrubber(ctx.tenant_id).await?;
+        let mut results = Vec::with_capacity(req.items.len());
+        let mut indexes = Vec::new();
+        let mut items = Vec::new();
+
+        for (index, item) in req.items.into_iter().enumerate() {
+            let id = match parse_uuid(&item.id) {
+                Ok(id) => id,
+                Err(status) => {
+                    results.push(item_error(index, item.id, status));
+                    continue;
+                }
+            };
+            if let Err(status) = self
+                .checker
+                .can_write(ctx.tenant_id, &ctx.user_id, &item.id, &ctx.role_ids)
+                .await
+            {
+                results.push(item_error(index, item.id, status));
+                continue;
+            }
+            let mut changes = match masked_changes(id, &item) {
+                Ok(changes) => changes,
+                Err(status) => {
+                    results.push(item_error(index, item.id, status));
+                    continue;
+                }
+            };
+            if let Some(u) = &changes.url {
+                if let Err(status) = self.url_policy.check(u) {
+                    results.push(item_error(index, item.id, status));
+                    continue;
+                }
+                let (url, original) = split_scrubbed(&scrubber, u);
+                changes.kind = Some(classify_url(&url));
+                changes.url = Some(url);
+                changes.original_url = original;
+            }
+            if let Err(status) = self.protect_changes(ctx.tenant_id, &mut changes).await {
+                results.push(item_error(index, item.id, status));
+                continue;
+            }
+            indexes.push(index);
+            items.push(changes);
+        }
+
+        let ids: Vec<String> = items.iter().map(|i| i.id.to_string()).collect();
+        let owned = self.owned_ids(&ctx, &ids).await?;
+
+        let updated = self
+            .repo
+            .batch_update(ctx.tenant_id, &items, ctx.user_id.parse::<i32>().ok())
+            .await
+            .map_err(ServiceError::database)?;
+
+        let outcomes = indexes.into_iter().zip(ids).zip(&items).zip(updated);
+        for (((index, id), changes), outcome) in outcomes {
+            results.push(match outcome {
+                Ok(Some(row)) => {
+                    if row.is_private && changes.url.is_some() {
+                        self.forget_snapshot(row.tenant_id, row.id).await;
+                    }
+                    self.events
+                        .publish(Event::bookmark(EventType::BookmarkUpdated, &ctx.user_id, &row));
+                    let is_owner = owned.contains(&id);
+                    match self.reveal(row).await {
+                        Ok(row) => item_ok(index, row, is_owner),
+                        Err(status) => item_error(index, id, status),
+                    }
+                }
+                Ok(None) => item_error(index, id, Status::not_found("bookmark not found")),
+                Err(e) => item_error(index, id, ServiceError::database(e).into()),
+            });
+        }
+
+        Ok(Response::new(batch_response(results)))
+    }
+
+    async fn batch_delete_bookmarks(
+        &self,
+        request: Request<BatchDeleteBookmarksRequest>,
+    ) -> Result<Response<BatchBookmarksResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+        check_batch_size(req.ids.len())?;
+
+        let mut results = Vec::with_capacity(req.ids.len());
+        let mut indexes = Vec::new();
+        let mut ids = Vec::new();
+
+        for (index, raw) in req.ids.into_iter().enumerate() {
+            let id = match parse_uuid(&raw) {
+                Ok(id) => id,
+                Err(status) => {
+                    results.push(item_error(index, raw, status));
+                    continue;
+                }
+            };
+            if let Err(status) = self
+                .checker
+                .can_delete(ctx.tenant_id, &ctx.user_id, &raw, &ctx.role_ids)
+                .await
+            {
+                results.push(item_error(index, raw, status));
+                continue;
+            }
+            indexes.push(index);
+            ids.push(id);
+        }
+
+        let deleted = self
+            .repo
+            .batch_delete(ctx.tenant_id, &ids)
+            .await
+            .map_err(ServiceError::database)?;
+
+        for ((index, id), outcome) in indexes.into_iter().zip(ids).zip(deleted) {
+            let id = id.to_string();
+            results.push(match outcome {
+                Ok(true) => {
+                    // The repo deletes the bookmark's tuples in the same transaction.
+                    self.checker.engine().cache().invalidate_resource(
+                        ctx.tenant_id,
+                        ResourceType::Bookmark,
+                        &id,
+                    );
+                    self.events
+                        .publish(Event::bookmark_deleted(ctx.tenant_id, &ctx.user_id, &id));
+                    BatchItemResult {
+                        index: index as u32,
+                        id,
+                        ..Default::default()
+                    }
+                }
+                Ok(false) => item_error(index, id, Status::not_found("bookmark not found")),
+                Err(e) => item_error(index, id, ServiceError::database(e).into()),
+            });
+        }
+
+        Ok(Response::new(batch_response(results)))
+    }
+
+    async fn update_tags_bulk(
+        &self,
+        request: Request<UpdateTagsBulkRequest>,
+    ) -> Result<Response<UpdateTagsBulkResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+        let domain = req.domain.trim().trim_matches('.').to_ascii_lowercase();
+        let filter = BulkTagFilter {
+            tag: tag_filter(&req.tag)?,
+            domain: non_empty(domain.strip_prefix("www.").unwrap_or(&domain)),
+            query: non_empty(&req.query),
+        };
+        if filter.tag.is_none() && filter.domain.is_none() && filter.query.is_none() {
+            return Err(Status::invalid_argument("one of tag, domain or query is required"));
+        }
+        let add = clean_tags(&req.add_tags)?;
+        let remove = clean_tags(&req.remove_tags)?;
+        if add.is_empty() && remove.is_empty() {
+            return Err(Status::invalid_argument("add_tags or remove_tags is required"));
+        }
+        if let Some(tag) = add.iter().find(|t| remove.contains(t)) {
+            return Err(Status::invalid_argument(format!(
+                "tag {tag:?} is both added and removed"
+            )));
+        }
+
+        let subjects = self
+            .checker
+            .subjects(ctx.tenant_id, &ctx.user_id, &ctx.role_ids)
+            .await
+            .map_err(ServiceError::authz)?;
+        let rows = self
+            .repo
+            .update_tags_bulk(
+                ctx.tenant_id,
+                &subjects,
+                &filter,
+                &add,
+                &remove,
+                ctx.user_id.parse::<i32>().ok(),
+            )
+            .await
+            .map_err(ServiceError::database)?;
+
+        for row in &rows {
+            self.events
+                .publish(Event::bookmark(EventType::BookmarkUpdated, &ctx.user_id, row));
+        }
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            user_id = %ctx.user_id,
+            updated = rows.len(),
+            "bulk tag update"
+        );
+
+        Ok(Response::new(UpdateTagsBulkResponse {
+            updated: rows.len() as u32,
+        }))
+    }
+
+    async fn preview_url(
+        &self,
+        request: Request<PreviewUrlRequest>,
+    ) -> Result<Response<UrlPreview>, Status> {
+        extract_context(&request)?;
+        let req = request.into_inner();
+
+        if req.url.is_empty() {
+            return Err(Status::invalid_argument("url is required"));
+        }
+
+        let meta = self.fetcher.fetch(&req.url).await.map_err(|e| {
+            Status::failed_precondition(format!("could not fetch page metadata: {e}"))
+        })?;
+
+        Ok(Response::new(UrlPreview {
+            url: req.url,
+            title: meta.title,
+            description: meta.description,
+            image_url: meta.image_url,
+            site_name: meta.site_name,
+            kind: meta.kind.to_proto(),
+        }))
+    }
+
+    async fn search_bookmarks(
+        &self,
+        request: Request<SearchBookmarksRequest>,
+    ) -> Result<Response<SearchBookmarksResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let query = req.query.trim();
+        if query.is_empty() {
+            return Err(Status::invalid_argument("query is required"));
+        }
+
+        let page = req.page.unwrap_or(1).max(1);
+        let page_size = req.page_size.unwrap_or(20).min(100);
+
+        let accessible_ids = self
+            .checker
+            .list_accessible_bookmarks(ctx.tenant_id, &ctx.user_id, &ctx.role_ids)
+            .await
+            .map_err(ServiceError::authz)?;
+        let uuids: Vec<Uuid> = accessible_ids
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
+
+        let fuzzy_threshold = req.fuzzy.then_some(self.fuzzy_threshold);
+        let expr = filter_expr(&req.filter)?;
+        let (rows, total) = self
+            .repo
+            .search(
+                ctx.tenant_id,
+                &uuids,
+                query,
+                req.search_content,
+                fuzzy_threshold,
+                expr.as_ref(),
+                page,
+                page_size,
+            )
+            .await
+            .map_err(ServiceError::database)?;
+
+        let page_ids: Vec<String> = rows.iter().map(|r| r.bookmark.id.to_string()).collect();
+        let owned = self.owned_ids(&ctx, &page_ids).await?;
+
+        let mut hits = Vec::with_capacity(rows.len());
+        for r in rows {
+            let is_owner = owned.contains(&r.bookmark.id.to_string());
+            hits.push(SearchHit {
+                bookmark: Some(row_to_proto(self.reveal(r.bookmark).await?, is_owner)),
+                matched_content: r.matched_content,
+                snippet: r.snippet,
+                fuzzy_match: r.fuzzy_match,
+            });
+        }
+
+        Ok(Response::new(SearchBookmarksResponse {
+            hits,
+            total: total as u32,
+        }))
+    }
+
+    async fn find_duplicates(
+        &self,
+        request: Request<FindDuplicatesRequest>,
+    ) -> Result<Response<FindDuplicatesResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let accessible: std::collections::HashSet<String> = self
+            .checker
+            .list_accessible_bookmarks(ctx.tenant_id, &ctx.user_id, &ctx.role_ids)
+            .await
+            .map_err(ServiceError::authz)?
+            .into_iter()
+            .collect();
+
+        let rows = match req.url.as_deref().filter(|u| !u.is_empty()) {
+            Some(url) => {
+                // Compare against the URL as it would be stored.
+                let (url, _) = self.scrub_url(ctx.tenant_id, url).await?;
+                self.repo
+                    .find_by_normalized_url(ctx.tenant_id, &normalize_url(&url))
+                    .await
+                    .map_err(ServiceError::database)?
+                    .into_iter()
+                    .filter(|r| accessible.contains(&r.id.to_string()))
+                    .collect()
+            }
+            None => {
+                let uuids: Vec<Uuid> = accessible
+                    .iter()
+                    .filter_map(|id| Uuid::parse_str(id).ok())
+                    .collect();
+                self.repo
+                    .find_duplicate_groups(ctx.tenant_id, &uuids)
+                    .await
+                    .map_err(ServiceError::database)?
+            }
+        };
+
+        let ids: Vec<String> = rows.iter().map(|r| r.id.to_string()).collect();
+        let owned = self.owned_ids(&ctx, &ids).await?;
+
+        // Rows arrive ordered by normalized URL, so each group is contiguous.
+        let mut groups: Vec<DuplicateGroup> = Vec::new();
+        for row in rows {
+            let key = row.normalized_url.clone().unwrap_or_default();
+            let is_owner = owned.contains(&row.id.to_string());
+            let bookmark = row_to_proto(self.reveal(row).await?, is_owner);
+            match groups.last_mut() {
+                Some(g) if g.normalized_url == key => g.bookmarks.push(bookmark),
+                _ => groups.push(DuplicateGroup {
+                    normalized_url: key,
+                    bookmarks: vec![bookmark],
+                }),
+            }
+        }
+
+        Ok(Response::new(FindDuplicatesResponse { groups }))
+    }
+
+    async fn record_visit(
+        &self,
+        request: Request<RecordVisitRequest>,
+    ) -> Result<Response<RecordVisitResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let id = parse_uuid(&req.id)?;
+
+        self.checker
+            .can_read(ctx.tenant_id, &ctx.user_id, &req.id, &ctx.role_ids)
+            .await?;
+
+        let (visit_count, last_visited_at) = self
+            .repo
+            .record_visit(ctx.tenant_id, id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("bookmark not found"))?;
+
+        Ok(Response::new(RecordVisitResponse {
+            visit_count: visit_count as u32,
+            last_visited_at: Some(prost_types::Timestamp {
+                seconds: last_visited_at.timestamp(),
+                nanos: last_visited_at.timestamp_subsec_nanos() as i32,
+            }),
+        }))
+    }
+
+    async fn update_bookmark_notes(
+        &self,
+        request: Request<UpdateBookmarkNotesRequest>,
+    ) -> Result<Response<Bookmark>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let id = parse_uuid(&req.id)?;
+        if req.notes.len() > MAX_NOTES_BYTES {
+            return Err(Status::invalid_argument(format!(
+                "notes exceed {MAX_NOTES_BYTES} bytes"
+            )));
+        }
+
+        self.checker
+            .can_write(ctx.tenant_id, &ctx.user_id, &req.id, &ctx.role_ids)
+            .await?;
+
+        let current = self
+            .repo
+            .get_by_id(ctx.tenant_id, id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("bookmark not found"))?;
+        let notes = if current.is_private {
+            self.seal(ctx.tenant_id, id, SealedField::Notes, &req.notes)
+                .await?
+        } else {
+            req.notes
+        };
+
+        let row = self
+            .repo
+            .update_notes(ctx.tenant_id, id, &notes)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("bookmark not found"))?;
+        self.events
+            .publish(Event::bookmark(EventType::BookmarkUpdated, &ctx.user_id, &row));
+
+        let is_owner = !self.owned_ids(&ctx, &[req.id]).await?.is_empty();
+        let mut bookmark = row_to_proto(self.reveal(row).await?, is_owner);
+        self.apply_user_flags(&ctx, std::slice::from_mut(&mut bookmark))
+            .await?;
+        Ok(Response::new(bookmark))
+    }
+
+    async fn pin_bookmark(
+        &self,
+        request: Request<PinBookmarkRequest>,
+    ) -> Result<Response<Bookmark>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+        let bookmark = self
+            .update_user_flags(&ctx, &req.id, UserFlagUpdate::Pinned(true))
+            .await?;
+        Ok(Response::new(bookmark))
+    }
+
+    async fn unpin_bookmark(
+        &self,
+        request: Request<UnpinBookmarkRequest>,
+    ) -> Result<Response<Bookmark>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+        let bookmark = self
+            .update_user_flags(&ctx, &req.id, UserFlagUpdate::Pinned(false))
+            .await?;
+        Ok(Response::new(bookmark))
+    }
+
+    async fn reorder_bookmarks(
+        &self,
+        request: Request<ReorderBookmarksRequest>,
+    ) -> Result<Response<Bookmark>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let id = parse_uuid(&req.id)?;
+        let parse_neighbour = |s: &str| -> Result<Option<Uuid>, Status> {
+            if s.is_empty() {
+                return Ok(None);
+            }
+            let neighbour = parse_uuid(s)?;
+            if neighbour == id {
+                return Err(Status::invalid_argument("a bookmark cannot be its own neighbour"));
+            }
+            Ok(Some(neighbour))
+        };
+        let previous = parse_neighbour(&req.previous_id)?;
+        let next = parse_neighbour(&req.next_id)?;
+        if previous.is_none() && next.is_none() {
+            return Err(Status::invalid_argument("previous_id or next_id is required"));
+        }
+
+        self.checker
+            .can_read(ctx.tenant_id, &ctx.user_id, &req.id, &ctx.role_ids)
+            .await?;
+
+        let pins = self
+            .flags
+            .list_pins(ctx.tenant_id, &ctx.user_id)
+            .await
+            .map_err(ServiceError::database)?;
+
+        // Pins never positioned follow the positioned ones; give them keys in
+        // their current order so the move has neighbours to rank against.
+        let mut updates: Vec<(Uuid, String)> = Vec::new();
+        let mut order: Vec<(Uuid, String)> = Vec::with_capacity(pins.len());
+        let unkeyed = pins.iter().filter(|p| p.sort_order.is_none()).count();
+        let last_key = pins.iter().filter_map(|p| p.sort_order.as_deref()).next_back();
+        let mut fresh = rank::keys_between(last_key, None, unkeyed).into_iter();
+        for pin in &pins {
+            let key = match &pin.sort_order {
+                Some(key) => key.clone(),
+                None => {
+                    let key = fresh.next().unwrap_or_default();
+                    updates.push((pin.bookmark_id, key.clone()));
+                    key
+                }
+            };
+            order.push((pin.bookmark_id, key));
+        }
+
+        let not_pinned = || Status::failed_precondition("bookmark is not pinned");
+        let position = |order: &[(Uuid, String)], id: Uuid| {
+            order.iter().position(|(pin, _)| *pin == id).ok_or_else(not_pinned)
+        };
+        order.remove(position(&order, id)?);
+        let (lower, upper) = match (previous, next) {
+            (Some(previous), next) => {
+                let at = position(&order, previous)?;
+                let after = order.get(at + 1);
+                if let Some(next) = next {
+                    position(&order, next)?;
+                    if after.is_none_or(|(pin, _)| *pin != next) {
+                        return Err(Status::invalid_argument(
+                            "previous_id and next_id are not adjacent",
+                        ));
+                    }
+                }
+                (Some(&order[at].1), after.map(|(_, key)| key))
+            }
+            (None, Some(next)) => {
+                let at = position(&order, next)?;
+                let before = at.checked_sub(1).map(|i| &order[i].1);
+                (before, Some(&order[at].1))
+            }
+            (None, None) => unreachable!("checked above"),
+        };
+        let key = rank::key_between(lower.map(String::as_str), upper.map(String::as_str));
+        updates.retain(|(pin, _)| *pin != id);
+        updates.push((id, key));
+
+        self.flags
+            .set_sort_orders(ctx.tenant_id, &ctx.user_id, &updates)
+            .await
+            .map_err(ServiceError::database)?;
+
+        let row = self
+            .repo
+            .get_by_id(ctx.tenant_id, id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("bookmark not found"))?;
+        let is_owner = !self.owned_ids(&ctx, &[req.id]).await?.is_empty();
+        let mut bookmark = row_to_proto(self.reveal(row).await?, is_owner);
+        self.apply_user_flags(&ctx, std::slice::from_mut(&mut bookmark))
+            .await?;
+        Ok(Response::new(bookmark))
+    }
+
+    async fn set_reading_status(
+        &self,
+        request: Request<SetReadingStatusRequest>,
+    ) -> Result<Response<Bookmark>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let status = ReadingStatus::from_proto(req.status)
+            .ok_or_else(|| Status::invalid_argument("invalid reading status"))?;
+        let bookmark = self
+            .update_user_flags(&ctx, &req.id, UserFlagUpdate::ReadingStatus(status))
+            .await?;
+        Ok(Response::new(bookmark))
+    }
+
+    async fn list_most_visited(
+        &self,
+        request: Request<ListMostVisitedRequest>,
+    ) -> Result<Response<ListBookmarksResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let limit = req.limit.unwrap_or(10).clamp(1, 100);
+
+        let accessible_ids = self
+            .checker
+            .list_accessible_bookmarks(ctx.tenant_id, &ctx.user_id, &ctx.role_ids)
+            .await
+            .map_err(ServiceError::authz)?;
+        let uuids: Vec<Uuid> = accessible_ids
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
+
+        let rows = self
+            .repo
+            .list_most_visited(ctx.tenant_id, &uuids, limit)
+            .await
+            .map_err(ServiceError::database)?;
+
+        let ids: Vec<String> = rows.iter().map(|r| r.id.to_string()).collect();
+        let owned = self.owned_ids(&ctx, &ids).await?;
+
+        let mut bookmarks: Vec<Bookmark> = self
+            .reveal_all(rows)
+            .await?
+            .into_iter()
+            .map(|r| {
+                let is_owner = owned.contains(&r.id.to_string());
+                row_to_proto(r, is_owner)
+            })
+            .collect();
+        self.apply_user_flags(&ctx, &mut bookmarks).await?;
+
+        Ok(Response::new(ListBookmarksResponse {
+            total: bookmarks.len() as u32,
+            bookmarks,
+            next_page_token: String::new(),
+        }))
+    }
+
+    async fn get_tag_stats(
+        &self,
+        request: Request<GetTagStatsRequest>,
+    ) -> Result<Response<GetTagStatsResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let limit = req.limit.unwrap_or(100).clamp(1, 1000);
+        let order = match TagStatsOrder::try_from(req.order) {
+            Ok(TagStatsOrder::Unspecified) => TagOrder::MostUsed,
+            Ok(TagStatsOrder::LeastUsed) => TagOrder::LeastUsed,
+            Ok(TagStatsOrder::MostRecent) => TagOrder::MostRecent,
+            Ok(TagStatsOrder::LeastRecent) => TagOrder::LeastRecent,
+            Err(_) => return Err(Status::invalid_argument("unknown tag stats order")),
+        };
+
+        let subjects = self
+            .checker
+            .subjects(ctx.tenant_id, &ctx.user_id, &ctx.role_ids)
+            .await
+            .map_err(ServiceError::authz)?;
+        let rows = self
+            .repo
+            .tag_stats(ctx.tenant_id, &subjects, req.prefix.trim(), order, limit)
+            .await
+            .map_err(ServiceError::database)?;
+
+        let timestamp = |ts: chrono::DateTime<chrono::Utc>| prost_types::Timestamp {
+            seconds: ts.timestamp(),
+            nanos: ts.timestamp_subsec_nanos() as i32,
+        };
+        Ok(Response::new(GetTagStatsResponse {
+            total_tags: rows.first().map_or(0, |r| r.total_count as u32),
+            tags: rows
+                .into_iter()
+                .map(|r| TagStat {
+                    tag: r.tag,
+                    bookmark_count: r.bookmark_count as u32,
+                    first_used_time: Some(timestamp(r.first_used)),
+                    last_used_time: Some(timestamp(r.last_used)),
+                })
+                .collect(),
+        }))
+    }
+
+    async fn list_tag_tree(
+        &self,
+        request: Request<ListTagTreeRequest>,
+    ) -> Result<Response<ListTagTreeResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+        let root = req.root.trim().trim_end_matches(TAG_SEPARATOR);
+
+        let subjects = self
+            .checker
+            .subjects(ctx.tenant_id, &ctx.user_id, &ctx.role_ids)
+            .await
+            .map_err(ServiceError::authz)?;
+        let rows = self
+            .repo
+            .tag_tree(ctx.tenant_id, &subjects, root)
+            .await
+            .map_err(ServiceError::database)?;
+
+        Ok(Response::new(ListTagTreeResponse {
+            nodes: tag_tree(&rows, root, req.depth),
+        }))
+    }
+
+    async fn get_archived_content(
+        &self,
+        request: Request<GetArchivedContentRequest>,
+    ) -> Result<Response<ArchivedContent>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let id = parse_uuid(&req.id)?;
+
+        self.checker
+            .can_read(ctx.tenant_id, &ctx.user_id, &req.id, &ctx.role_ids)
+            .await?;
+
+        let row = self
+            .archiver
+            .repo()
+            .get(ctx.tenant_id, id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("no archived content for this bookmark"))?;
+
+        Ok(Response::new(ArchivedContent {
+            bookmark_id: row.bookmark_id.to_string(),
+            url: row.url,
+            format: row.format,
+            content: row.content,
+            truncated: row.truncated,
+            archive_time: Some(prost_types::Timestamp {
+                seconds: row.archive_time.timestamp(),
+                nanos: row.archive_time.timestamp_subsec_nanos() as i32,
+            }),
+        }))
+    }
+
+    async fn list_bookmark_revisions(
+        &self,
+        request: Request<ListBookmarkRevisionsRequest>,
+    ) -> Result<Response<ListBookmarkRevisionsResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let id = parse_uuid(&req.id)?;
+
+        self.checker
+            .can_read(ctx.tenant_id, &ctx.user_id, &req.id, &ctx.role_ids)
+            .await?;
+
+        let page = req.page.unwrap_or(1).max(1);
+        let page_size = req.page_size.unwrap_or(20).min(100);
+
+        let (rows, total) = self
+            .repo
+            .list_revisions(ctx.tenant_id, id, page, page_size)
+            .await
+            .map_err(ServiceError::database)?;
+
+        let is_owner = !self.owned_ids(&ctx, &[req.id]).await?.is_empty();
+        let mut revisions = Vec::with_capacity(rows.len());
+        for row in rows {
+            revisions.push(revision_to_proto(self.reveal_revision(row).await?, is_owner));
+        }
+        Ok(Response::new(ListBookmarkRevisionsResponse {
+            revisions,
+            total: total as u32,
+        }))
+    }
+
+    async fn revert_bookmark(
+        &self,
+        request: Request<RevertBookmarkRequest>,
+    ) -> Result<Response<Bookmark>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let id = parse_uuid(&req.id)?;
+
+        self.checker
+            .can_write(ctx.tenant_id, &ctx.user_id, &req.id, &ctx.role_ids)
+            .await?;
+
+        let revision = self
+            .repo
+            .get_revision(ctx.tenant_id, id, req.revision_id as i64)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("revision not found"))?;
+
+        // Restore the stored values verbatim; the URL was already scrubbed when
+        // saved. The bookmark keeps its current privacy.
+        let revision = self.reveal_revision(revision).await?;
+        let mut changes = BookmarkChanges {
+            id,
+            kind: Some(classify_url(&revision.url)),
+            url: Some(revision.url),
+            title: Some(revision.title),
+            description: Some(revision.description),
+            tags: Some(revision.tags),
+            original_url: revision.original_url,
+            metadata: None,
+            notes: None,
+            is_private: None,
+        };
+        self.protect_changes(ctx.tenant_id, &mut changes).await?;
+        let row = self
+            .repo
+            .update(ctx.tenant_id, &changes, ctx.user_id.parse::<i32>().ok())
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("bookmark not found"))?;
+        self.events
+            .publish(Event::bookmark(EventType::BookmarkUpdated, &ctx.user_id, &row));
+
+        let is_owner = !self.owned_ids(&ctx, &[req.id]).await?.is_empty();
+        Ok(Response::new(row_to_proto(self.reveal(row).await?, is_owner)))
+    }
+
+    async fn get_url_scrub_policy(
+        &self,
+        request: Request<GetUrlScrubPolicyRequest>,
+    ) -> Result<Response<UrlScrubPolicy>, Status> {
+        let ctx = extract_context(&request)?;
+
+        let row = self
+            .scrub_repo
+            .get(ctx.tenant_id)
+            .await
+            .map_err(ServiceError::database)?;
+
+        let policy = match row {
+            Some(r) => UrlScrubPolicy {
+                enabled: r.enabled,
+                strip_params: r.strip_params,
+                param_patterns: r.param_patterns,
+            },
+            None => UrlScrubPolicy {
+                enabled: true,
+                strip_params: crate::service::url_scrubber::DEFAULT_STRIP_PARAMS
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                param_patterns: vec![],
+            },
+        };
+
+        Ok(Response::new(policy))
+    }
+
+    async fn update_url_scrub_policy(
+        &self,
+        request: Request<UpdateUrlScrubPolicyRequest>,
+    ) -> Result<Response<UrlScrubPolicy>, Status> {
+        let ctx = extract_context(&request)?;
+        if !ctx.is_tenant_manager() {
+            return Err(Status::permission_denied("tenant manager role required"));
+        }
+
+        let policy = request
+            .into_inner()
+            .policy
+            .ok_or_else(|| Status::invalid_argument("policy is required"))?;
+
+        // Reject invalid patterns up front rather than on the next save.
+        compile_patterns(&policy.param_patterns)?;
+
+        let row = self
+            .scrub_repo
+            .upsert(
+                ctx.tenant_id,
+                policy.enabled,
+                &policy.strip_params,
+                &policy.param_patterns,
+            )
+            .await
+            .map_err(ServiceError::database)?;
+
+        Ok(Response::new(UrlScrubPolicy {
+            enabled: row.enabled,
+            strip_params: row.strip_params,
+            param_patterns: row.param_patterns,
+        }))
+    }
+
+    async fn create_short_link(
+        &self,
+        request: Request<CreateShortLinkRequest>,
+    ) -> Result<Response<ShortLink>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let id = parse_uuid(&req.bookmark_id)?;
+        if let Some(code) = &req.code {
+            validate_short_code(code)?;
+        }
+
+        // Anyone with the link reaches the URL, so this is a form of sharing.
+        self.checker
+            .can_share(ctx.tenant_id, &ctx.user_id, &req.bookmark_id, &ctx.role_ids)
+            .await?;
+        let bookmark = self
+            .repo
+            .get_by_id(ctx.tenant_id, id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("bookmark not found"))?;
+        if bookmark.is_private {
+            return Err(Status::failed_precondition(
+                "private bookmarks cannot have short links",
+            ));
+        }
+
+        let attempts = if req.code.is_some() { 1 } else { SHORT_CODE_ATTEMPTS };
+        for _ in 0..attempts {
+            let code = req.code.clone().unwrap_or_else(generate_short_code);
+            let created = self
+                .short_links
+                .create(ctx.tenant_id, &code, id, &ctx.user_id)
+                .await
+                .map_err(ServiceError::database)?;
+            if let Some(row) = created {
+                tracing::info!(
+                    tenant_id = ctx.tenant_id,
+                    bookmark_id = %id,
+                    code = %row.code,
+                    by = %ctx.user_id,
+                    "short link created"
+                );
+                return Ok(Response::new(short_link_to_proto(row)));
+            }
+        }
+
+        Err(match req.code {
+            Some(_) => Status::already_exists("short code is already in use"),
+            None => Status::unavailable("could not allocate a short code; try again"),
+        })
+    }
+
+    async fn revoke_short_link(
+        &self,
+        request: Request<RevokeShortLinkRequest>,
+    ) -> Result<Response<()>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let link = self
+            .short_links
+            .get(ctx.tenant_id, &req.code)
+            .await
+            .map_err(ServiceError::database)?
+            .filter(|l| l.revoked_at.is_none())
+            .ok_or_else(|| Status::not_found("short link not found"))?;
+
+        self.checker
+            .can_share(
+                ctx.tenant_id,
+                &ctx.user_id,
+                &link.bookmark_id.to_string(),
+                &ctx.role_ids,
+            )
+            .await?;
+
+        self.short_links
+            .revoke(ctx.tenant_id, &req.code)
+            .await
+            .map_err(ServiceError::database)?;
+        tracing::info!(tenant_id = ctx.tenant_id, code = %req.code, by = %ctx.user_id, "short link revoked");
+
+        Ok(Response::new(()))
+    }
+}
+
+async fn reveal(cipher: &FieldCipher, mut row: BookmarkRow) -> Result<BookmarkRow, Status> {
+    if row.is_private {
+        let (tenant_id, id) = (row.tenant_id, row.id);
+        row.url = cipher
+            .open(tenant_id, id, SealedField::Url, &row.url)
+            .await
+            .map_err(cipher_status)?;
+        if let Some(original) = row.original_url.take() {
+            row.original_url = Some(
+                cipher
+                    .open(tenant_id, id, SealedField::OriginalUrl, &original)
+                    .await
+                    .map_err(cipher_status)?,
+            );
+        }
+        row.notes = cipher
+            .open(tenant_id, id, SealedField::Notes, &row.notes)
+            .await
+            .map_err(cipher_status)?;
+    }
+    Ok(row)
+}
+
+async fn reveal_all(
+    cipher: &FieldCipher,
+    rows: Vec<BookmarkRow>,
+) -> Result<Vec<BookmarkRow>, Status> {
+    let mut revealed = Vec::with_capacity(rows.len());
+    for row in rows {
+        revealed.push(reveal(cipher, row).await?);
+    }
+    Ok(revealed)
+}
+
+/// Failure to seal or open a value: logged in full, INTERNAL to the caller.
+pub(crate) fn cipher_status(e: anyhow::Error) -> Status {
+    tracing::error!(error = %format_args!("{e:#}"), "field encryption failed");
+    Status::internal("internal error")
+}
+
+fn short_link_to_proto(row: ShortLinkRow) -> ShortLink {
+    ShortLink {
+        path: format!("/s/{}/{}", row.tenant_id, row.code),
+        code: row.code,
+        bookmark_id: row.bookmark_id.to_string(),
+        create_time: Some(prost_types::Timestamp {
+            seconds: row.create_time.timestamp(),
+            nanos: row.create_time.timestamp_subsec_nanos() as i32,
+        }),
+    }
+}
+
+/// Random URL-safe code from a v4 UUID's bytes (OS CSPRNG).
+fn generate_short_code() -> String {
+    URL_SAFE_NO_PAD.encode(&Uuid::new_v4().as_bytes()[..SHORT_CODE_BYTES])
+}
+
+fn validate_short_code(code: &str) -> Result<(), Status> {
+    let valid = (3..=64).contains(&code.len())
+        && code
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+    if valid {
+        Ok(())
+    } else {
+        Err(Status::invalid_argument(
+            "code must be 3-64 characters of A-Z, a-z, 0-9, '_' or '-'",
+        ))
+    }
+}
+
+/// Convert a row to its proto form. The pre-scrub URL is only exposed to owners.
+fn row_to_proto(row: BookmarkRow, include_original_url: bool) -> Bookmark {
+    Bookmark {
+        id: row.id.to_string(),
+        tenant_id: row.tenant_id as u32,
+        url: row.url,
+        title: row.title,
+        description: row.description,
+        tags: row.tags,
+        created_by: row.created_by.map(|v| v as u32),
+        create_time: Some(prost_types::Timestamp {
+            seconds: row.create_time.timestamp(),
+            nanos: row.create_time.timestamp_subsec_nanos() as i32,
+        }),
+        update_time: Some(prost_types::Timestamp {
+            seconds: row.update_time.timestamp(),
+            nanos: row.update_time.timestamp_subsec_nanos() as i32,
+        }),
+        original_url: if include_original_url {
+            row.original_url
+        } else {
+            None
+        },
+        visit_count: row.visit_count as u32,
+        last_visited_at: row.last_visited_at.map(|ts| prost_types::Timestamp {
+            seconds: ts.timestamp(),
+            nanos: ts.timestamp_subsec_nanos() as i32,
+        }),
+        is_pinned: false,
+        pinned_at: None,
+        reading_status: ReadingStatus::Unread.to_proto(),
+        notes: row.notes,
+        metadata: row.metadata.0,
+        kind: row
+            .kind
+            .as_deref()
+            .and_then(BookmarkKind::from_str)
+            .unwrap_or_default()
+            .to_proto(),
+        sort_order: String::new(),
+        is_private: row.is_private,
+    }
+}
+
+fn change_event(
+    change_type: BookmarkChangeType,
+    id: &str,
+    bookmark: Option<Bookmark>,
+    event: &Event,
+) -> BookmarkChangeEvent {
+    let occurred_at = chrono::DateTime::parse_from_rfc3339(&event.occurred_at)
+        .map(|ts| prost_types::Timestamp {
+            seconds: ts.timestamp(),
+            nanos: ts.timestamp_subsec_nanos() as i32,
+        })
+        .ok();
+    BookmarkChangeEvent {
+        r#type: change_type as i32,
+        bookmark_id: id.to_string(),
+        bookmark,
+        occurred_at,
+    }
+}
+
+fn validate_metadata(metadata: &HashMap<String, String>) -> Result<(), Status> {
+    if metadata.len() > MAX_METADATA_ENTRIES {
+        return Err(Status::invalid_argument(format!(
+            "metadata has more than {MAX_METADATA_ENTRIES} entries"
+        )));
+    }
+    let mut total = 0;
+    for (key, value) in metadata {
+        if key.is_empty() || key.len() > MAX_METADATA_KEY_BYTES {
+            return Err(Status::invalid_argument(format!(
+                "metadata key must be 1-{MAX_METADATA_KEY_BYTES} bytes"
+            )));
+        }
+        if value.len() > MAX_METADATA_VALUE_BYTES {
+            return Err(Status::invalid_argument(format!(
+                "metadata value for {key:?} exceeds {MAX_METADATA_VALUE_BYTES} bytes"
+            )));
+        }
+        total += key.len() + value.len();
+    }
+    if total > MAX_METADATA_TOTAL_BYTES {
+        return Err(Status::invalid_argument(format!(
+            "metadata exceeds {MAX_METADATA_TOTAL_BYTES} bytes"
+        )));
+    }
+    Ok(())
+}
+
+/// Fields an update touches. Without `update_mask` only the `optional` scalar
+/// fields that are present change; with one, each listed field is set to the
+/// request's value, so an empty value clears it.
+fn masked_changes(id: Uuid, req: &UpdateBookmarkRequest) -> Result<BookmarkChanges, Status> {
+    let mut changes = BookmarkChanges {
+        id,
+        url: None,
+        title: None,
+        description: None,
+        tags: None,
+        original_url: None,
+        metadata: None,
+        kind: None,
+        notes: None,
+        is_private: None,
+    };
+    let paths = req.update_mask.as_ref().map(|m| m.paths.as_slice()).unwrap_or_default();
+    if paths.is_empty() {
+        changes.url = req.url.clone().filter(|u| !u.is_empty());
+        changes.title = req.title.clone();
+        changes.description = req.description.clone();
+        changes.is_private = req.is_private;
+        return Ok(changes);
+    }
+
+    for path in paths {
+        match path.as_str() {
+            "url" => match req.url.as_deref() {
+                Some(u) if !u.is_empty() => changes.url = Some(u.to_string()),
+                _ => return Err(Status::invalid_argument("url cannot be cleared")),
+            },
+            "title" => changes.title = Some(req.title.clone().unwrap_or_default()),
+            "description" => changes.description = Some(req.description.clone().unwrap_or_default()),
+            "tags" => changes.tags = Some(req.tags.clone()),
+            "is_private" => changes.is_private = Some(req.is_private.unwrap_or_default()),
+            "metadata" => {
+                validate_metadata(&req.metadata)?;
+                changes.metadata = Some(req.metadata.clone());
+            }
+            other => {
+                return Err(Status::invalid_argument(format!(
+                    "unsupported update_mask path {other:?}"
+                )))
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// Split a URL into the value to store and, if the scrubber changed it, the original.
+fn split_scrubbed(scrubber: &UrlScrubber, url: &str) -> (String, Option<String>) {
+    match scrubber.scrub(url) {
+        Some(scrubbed) => (scrubbed, Some(url.to_string())),
+        None => (url.to_string(), None),
+    }
+}
+
+/// Trimmed, de-duplicated tags for a bulk tag change.
+fn clean_tags(tags: &[String]) -> Result<Vec<String>, Status> {
+    if tags.len() > MAX_BULK_TAGS {
+        return Err(Status::invalid_argument(format!(
+            "at most {MAX_BULK_TAGS} tags to add or remove"
+        )));
+    }
+    let mut cleaned: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(Status::invalid_argument("tags must not be empty"));
+        }
+        if !cleaned.iter().any(|t| t == tag) {
+            cleaned.push(tag.to_string());
+        }
+    }
+    Ok(cleaned)
+}
+
+/// A trimmed tag filter; `None` when blank. A namespace filter such as
+/// `lang/` must name the namespace.
+fn tag_filter(raw: &str) -> Result<Option<String>, Status> {
+    let filter = raw.trim();
+    if filter.is_empty() {
+        return Ok(None);
+    }
+    if filter.strip_suffix(TAG_SEPARATOR) == Some("") {
+        return Err(Status::invalid_argument(
+            "tag filter needs a namespace before '/'",
+        ));
+    }
+    Ok(Some(filter.to_string()))
+}
+
+/// A parsed `filter` expression; `None` when blank.
+pub fn filter_expr(raw: &str) -> Result<Option<FilterExpr>, Status> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(None);
+    }
+    FilterExpr::parse(raw)
+        .map(Some)
+        .map_err(|e| Status::invalid_argument(format!("invalid filter: {e}")))
+}
+
+/// Nest tag paths into nodes. `rows` hold every namespace above each path, so
+/// each node's parent is present; the top level is `root` itself when set.
+fn tag_tree(rows: &[TagPathRow], root: &str, depth: Option<u32>) -> Vec<TagNode> {
+    let mut sorted: Vec<&TagPathRow> = rows.iter().collect();
+    sorted.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut children: HashMap<Option<&str>, Vec<&TagPathRow>> = HashMap::new();
+    for row in sorted {
+        let parent = if row.path == root {
+            None
+        } else {
+            row.path
+                .rsplit_once(TAG_SEPARATOR)
+                .map(|(parent, _)| parent)
+        };
+        children.entry(parent).or_default().push(row);
+    }
+
+    fn nest(
+        children: &HashMap<Option<&str>, Vec<&TagPathRow>>,
+        parent: Option<&str>,
+        depth: Option<u32>,
+    ) -> Vec<TagNode> {
+        let Some(rows) = children.get(&parent) else {
+            return vec![];
+        };
+        rows.iter()
+            .map(|row| TagNode {
+                name: row.path.rsplit(TAG_SEPARATOR).next().unwrap_or_default().to_string(),
+                path: row.path.clone(),
+                bookmark_count: row.bookmark_count as u32,
+                total_count: row.subtree_count as u32,
+                children: match depth {
+                    Some(0) => vec![],
+                    _ => nest(children, Some(&row.path), depth.map(|d| d - 1)),
+                },
+            })
+            .collect()
+    }
+    nest(&children, None, depth)
+}
+
+fn check_batch_size(len: usize) -> Result<(), Status> {
+    if len == 0 {
+        return Err(Status::invalid_argument("at least one item is required"));
+    }
+    if len > MAX_BATCH_SIZE {
+        return Err(Status::invalid_argument(format!(
+            "at most {MAX_BATCH_SIZE} items per batch"
+        )));
+    }
+    Ok(())
+}
+
+fn item_ok(index: usize, row: BookmarkRow, include_original_url: bool) -> BatchItemResult {
+    BatchItemResult {
+        index: index as u32,
+        id: row.id.to_string(),
+        bookmark: Some(row_to_proto(row, include_original_url)),
+        code: 0,
+        message: String::new(),
+    }
+}
+
+fn item_error(index: usize, id: String, status: Status) -> BatchItemResult {
+    BatchItemResult {
+        index: index as u32,
+        id,
+        bookmark: None,
+        code: status.code() as i32,
+        message: status.message().to_string(),
+    }
+}
+
+/// Order results by request index and tally successes and failures.
+fn batch_response(mut results: Vec<BatchItemResult>) -> BatchBookmarksResponse {
+    results.sort_by_key(|r| r.index);
+    let failed = results.iter().filter(|r| r.code != 0).count() as u32;
+    BatchBookmarksResponse {
+        succeeded: results.len() as u32 - failed,
+        failed,
+        results,
+    }
+}
+
+fn revision_to_proto(row: RevisionRow, include_original_url: bool) -> BookmarkRevision {
+    BookmarkRevision {
+        id: row.id as u64,
+        bookmark_id: row.bookmark_id.to_string(),
+        url: row.url,
+        title: row.title,
+        description: row.description,
+        tags: row.tags,
+        original_url: if include_original_url {
+            row.original_url
+        } else {
+            None
+        },
+        edited_by: row.edited_by.map(|v| v as u32),
+        create_time: Some(prost_types::Timestamp {
+            seconds: row.create_time.timestamp(),
+            nanos: row.create_time.timestamp_subsec_nanos() as i32,
+        }),
+        is_private: row.is_private,
+    }
+}
+
+pub fn timestamp_to_datetime(
+    ts: &prost_types::Timestamp,
+) -> Result<chrono::DateTime<chrono::Utc>, Status> {
+    chrono::DateTime::from_timestamp(ts.seconds, ts.nanos.max(0) as u32)
+        .ok_or_else(|| Status::invalid_argument("invalid timestamp"))
+}
+
+fn parse_uuid(s: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(s).map_err(|_| Status::invalid_argument("invalid UUID"))
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::watch;
+    use tonic::metadata::MetadataValue;
+    use tonic::Code;
+
+    use super::*;
+    use crate::authz::cache::DecisionCache;
+    use crate::authz::engine::Engine;
+    use crate::config::{ArchiveConfig, MetadataFetchConfig, UrlPolicyConfig};
+    use crate::data::db::Database;
+    use crate::data::sqlite::test_pool;
+
+    async fn service() -> BookmarkServiceImpl {
+        let pool = test_pool().await;
+        let stores = Database::Sqlite(pool).stores();
+        let engine = Engine::new(
+            stores.permissions.clone(),
+            stores.groups.clone(),
+            DecisionCache::disabled(),
+        );
+        let fetcher = MetadataFetcher::new(&MetadataFetchConfig::default()).unwrap();
+        let archiver = Archiver::new(
+            stores.archives.clone(),
+            fetcher.clone(),
+            &ArchiveConfig::default(),
+            watch::channel(false).1,
+        )
+        .unwrap();
+        BookmarkServiceImpl::new(
+            stores.bookmarks.clone(),
+            Checker::new(engine),
+            stores.scrub_policies.clone(),
+            fetcher,
+            archiver,
+            stores.user_flags.clone(),
+            stores.short_links.clone(),
+            stores.changes.clone(),
+            stores.auto_tag_rules.clone(),
+            Idempotency::new(stores.idempotency.clone(), &Default::default()),
+            EventPublisher::disabled(),
+            UrlPolicy::new(&UrlPolicyConfig::default()),
+            FieldCipher::new(None, stores.field_keys.clone()),
+        )
+    }
+
+    fn request<T>(tenant_id: i32, user_id: &str, message: T) -> Request<T> {
+        let mut req = Request::new(message);
+        let md = req.metadata_mut();
+        md.insert("x-md-global-tenant-id", tenant_id.into());
+        md.insert(
+            "x-md-global-user-id",
+            MetadataValue::try_from(user_id).unwrap(),
+        );
+        md.insert("x-md-global-roles", MetadataValue::from_static("user"));
+        req
+    }
+
+    #[tokio::test]
+    async fn other_tenants_bookmarks_are_not_found() {
+        let svc = service().await;
+        let created = svc
+            .create_bookmark(request(
+                2,
+                "7",
+                CreateBookmarkRequest {
+                    url: "https://example.com/".to_string(),
+                    title: "Example".to_string(),
+                    ..Default::default()
+                },
+            ))
+            .await
+            .unwrap()
+            .into_inner();
+
+        // A stale tuple in tenant 1 naming tenant 2's bookmark passes the
+        // permission check; the lookups themselves must stay in tenant 1.
+        svc.checker
+            .engine()
+            .store()
+            .create_permission(
+                1,
+                ResourceType::Bookmark,
+                &created.id,
+                Relation::Owner,
+                SubjectType::User,
+                "5",
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let get = svc
+            .get_bookmark(request(
+                1,
+                "5",
+                GetBookmarkRequest {
+                    id: created.id.clone(),
+                },
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(get.code(), Code::NotFound);
+
+        let update = svc
+            .update_bookmark(request(
+                1,
+                "5",
+                UpdateBookmarkRequest {
+                    id: created.id.clone(),
+                    title: Some("Mine now".to_string()),
+                    ..Default::default()
+                },
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(update.code(), Code::NotFound);
+
+        let delete = svc
+            .delete_bookmark(request(
+                1,
+                "5",
+                DeleteBookmarkRequest {
+                    id: created.id.clone(),
+                },
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(delete.code(), Code::NotFound);
+
+        let unchanged = svc
+            .get_bookmark(request(
+                2,
+                "7",
+                GetBookmarkRequest {
+                    id: created.id.clone(),
+                },
+            ))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(unchanged.title, "Example");
+    }
 }
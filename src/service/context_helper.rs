@@ -1,5 +1,7 @@
 use tonic::{Request, Status};
 
+use crate::middleware::mtls::ClientInfo;
+
 /// Metadata keys using Kratos x-md-global- prefix for cross-service propagation.
 const MD_TENANT_ID: &str = "x-md-global-tenant-id";
 const MD_USER_ID: &str = "x-md-global-user-id";
@@ -7,11 +9,33 @@ const MD_USERNAME: &str = "x-md-global-username";
 const MD_ROLES: &str = "x-md-global-roles";
 
 /// Extracted request context.
+#[derive(Clone)]
 pub struct RequestContext {
     pub tenant_id: i32,
     pub user_id: String,
     pub username: String,
     pub role_ids: Vec<String>,
+    /// Identity of the mTLS client certificate the request arrived on, if any.
+    pub client: Option<ClientInfo>,
+}
+
+impl RequestContext {
+    /// Whether the caller holds a platform-wide administrator role.
+    pub fn is_platform_admin(&self) -> bool {
+        self.role_ids
+            .iter()
+            .any(|r| r == "platform:admin" || r == "super:admin")
+    }
+
+    /// Whether the caller may manage settings for their tenant.
+    pub fn is_tenant_manager(&self) -> bool {
+        self.is_platform_admin() || self.role_ids.iter().any(|r| r == "tenant:manager")
+    }
+
+    /// Client certificate name for logs; empty without mTLS.
+    pub fn client_name(&self) -> &str {
+        self.client.as_ref().map(ClientInfo::name).unwrap_or_default()
+    }
 }
 
 /// Extract tenant_id, user_id, username, and roles from gRPC metadata.
@@ -48,6 +72,7 @@ pub fn extract_context<T>(req: &Request<T>) -> Result<RequestContext, Status> {
         user_id,
         username,
         role_ids,
+        client: req.extensions().get::<ClientInfo>().cloned(),
     })
 }
 
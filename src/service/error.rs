@@ -0,0 +1,104 @@
+use opentelemetry::KeyValue;
+use tonic::Status;
+
+/// Postgres `query_canceled`, raised when `statement_timeout` fires.
+const QUERY_CANCELED: &str = "57014";
+
+/// Failure from the data or authorization layer. Converting it to a [`Status`]
+/// logs the full error and hands the caller a sanitized message, so table
+/// names, constraint names and SQL never leave the service.
+#[derive(Debug)]
+pub enum ServiceError {
+    Database(anyhow::Error),
+    Authz(anyhow::Error),
+}
+
+impl ServiceError {
+    pub fn database(e: impl Into<anyhow::Error>) -> Self {
+        Self::Database(e.into())
+    }
+
+    pub fn authz(e: impl Into<anyhow::Error>) -> Self {
+        Self::Authz(e.into())
+    }
+}
+
+impl From<ServiceError> for Status {
+    fn from(err: ServiceError) -> Self {
+        let (kind, e) = match &err {
+            ServiceError::Database(e) => ("database", e),
+            ServiceError::Authz(e) => ("authz", e),
+        };
+        let sqlx_error = e
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<sqlx::Error>());
+        if let Some(sqlx_error) = sqlx_error {
+            let class = sqlx_class(sqlx_error);
+            if matches!(class, "connection" | "pool_timeout") {
+                crate::data::breaker::record_connection_failure();
+            }
+            opentelemetry::global::meter("bookmark")
+                .u64_counter("bookmark.db.errors")
+                .with_description("Database errors that failed a request, by class")
+                .build()
+                .add(
+                    1,
+                    &[
+                        KeyValue::new("kind", kind),
+                        KeyValue::new("error", class),
+                    ],
+                );
+        }
+        let status = sqlx_error.map_or_else(|| Status::internal("internal error"), sqlx_status);
+        if status.code() == tonic::Code::Internal {
+            tracing::error!(kind, error = %format_args!("{e:#}"), "request failed");
+        } else {
+            tracing::warn!(
+                kind,
+                code = ?status.code(),
+                error = %format_args!("{e:#}"),
+                "request rejected by the database"
+            );
+        }
+        status
+    }
+}
+
+/// Low-cardinality label for an error, for metrics.
+fn sqlx_class(e: &sqlx::Error) -> &'static str {
+    match e {
+        sqlx::Error::Database(db) if db.code().as_deref() == Some(QUERY_CANCELED) => "timeout",
+        sqlx::Error::Database(_) => "database",
+        sqlx::Error::RowNotFound => "row_not_found",
+        sqlx::Error::PoolTimedOut => "pool_timeout",
+        sqlx::Error::PoolClosed => "pool_closed",
+        sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::Protocol(_) => "connection",
+        _ => "other",
+    }
+}
+
+/// Map the sqlx errors a caller can act on; everything else is INTERNAL.
+fn sqlx_status(e: &sqlx::Error) -> Status {
+    use sqlx::error::ErrorKind;
+
+    match e {
+        sqlx::Error::Database(db) => match db.kind() {
+            ErrorKind::UniqueViolation => Status::already_exists("resource already exists"),
+            ErrorKind::ForeignKeyViolation => {
+                Status::failed_precondition("a referenced resource does not exist or is in use")
+            }
+            ErrorKind::NotNullViolation | ErrorKind::CheckViolation => {
+                Status::failed_precondition("request violates a data constraint")
+            }
+            _ if db.code().as_deref() == Some(QUERY_CANCELED) => {
+                Status::deadline_exceeded("query took too long")
+            }
+            _ => Status::internal("internal error"),
+        },
+        sqlx::Error::RowNotFound => Status::not_found("resource not found"),
+        sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_) => {
+            Status::unavailable("database unavailable")
+        }
+        _ => Status::internal("internal error"),
+    }
+}
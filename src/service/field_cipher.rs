@@ -0,0 +1,599 @@
+//! Encryption at rest of private bookmarks' URLs and notes.
+//!
+//! Values are sealed with AES-256-GCM under a per-tenant data key. Data keys
+//! are stored wrapped by a key-encryption key kept outside the database: a
+//! configured master key or a Vault transit key. A sealed value names the data
+//! key that sealed it, `enc:v1:<key id>:<base64 of nonce, ciphertext and tag>`,
+//! so backups can carry sealed values and wrapped keys without ever holding
+//! plaintext. The tenant, bookmark and field a value belongs to are
+//! authenticated with it, so a sealed value copied anywhere else fails to open.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use chrono::Utc;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::config::{parse_duration, FieldEncryptionConfig};
+use crate::data::field_key_repo::{FieldKeyRow, FieldKeyStore};
+
+const PREFIX: &str = "enc:v1:";
+const KEY_LEN: usize = 32;
+/// Unwrapped data keys kept in memory.
+const MAX_CACHED_KEYS: u64 = 10_000;
+
+/// Wraps and unwraps data keys with a key-encryption key.
+#[tonic::async_trait]
+pub trait KeyWrapper: Send + Sync {
+    /// Identifies the key-encryption key; stored next to every key it wraps.
+    fn id(&self) -> &str;
+    async fn wrap(&self, key: &[u8]) -> anyhow::Result<String>;
+    async fn unwrap(&self, wrapped: &str) -> anyhow::Result<Vec<u8>>;
+}
+
+/// A bookmark field that private bookmarks keep sealed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SealedField {
+    Url,
+    OriginalUrl,
+    Notes,
+}
+
+impl SealedField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Url => "url",
+            Self::OriginalUrl => "original_url",
+            Self::Notes => "notes",
+        }
+    }
+}
+
+/// Build the key wrapper selected by `cfg.driver`; `None` for the `none` driver.
+pub fn key_wrapper_from_config(
+    cfg: &FieldEncryptionConfig,
+) -> anyhow::Result<Option<Arc<dyn KeyWrapper>>> {
+    let wrapper: Arc<dyn KeyWrapper> = match cfg.driver.as_str() {
+        "none" => return Ok(None),
+        "local" => Arc::new(LocalKek::new(&cfg.master_key)?),
+        "vault" => Arc::new(VaultKek::new(cfg)?),
+        other => anyhow::bail!("unknown field encryption driver {other:?}"),
+    };
+    Ok(Some(wrapper))
+}
+
+/// Seals and opens field values, creating each tenant's data key on first use.
+#[derive(Clone)]
+pub struct FieldCipher {
+    inner: Option<Arc<Inner>>,
+}
+
+struct Inner {
+    kek: Arc<dyn KeyWrapper>,
+    store: Arc<dyn FieldKeyStore>,
+    rng: SystemRandom,
+    /// Unwrapped data keys by ID.
+    keys: moka::sync::Cache<Uuid, Arc<DataKey>>,
+    /// Each tenant's current data key.
+    current: moka::sync::Cache<i32, Uuid>,
+}
+
+/// An unwrapped data key and the tenant it belongs to.
+struct DataKey {
+    tenant_id: i32,
+    key: LessSafeKey,
+}
+
+impl FieldCipher {
+    /// Without a key wrapper, sealing fails and only plain values can be opened.
+    pub fn new(kek: Option<Arc<dyn KeyWrapper>>, store: Arc<dyn FieldKeyStore>) -> Self {
+        let inner = kek.map(|kek| {
+            Arc::new(Inner {
+                kek,
+                store,
+                rng: SystemRandom::new(),
+                keys: moka::sync::Cache::new(MAX_CACHED_KEYS),
+                current: moka::sync::Cache::new(MAX_CACHED_KEYS),
+            })
+        });
+        Self { inner }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Seal `field` of the tenant's bookmark `bookmark_id` with the tenant's
+    /// current data key. Empty values stay empty.
+    pub async fn seal(
+        &self,
+        tenant_id: i32,
+        bookmark_id: Uuid,
+        field: SealedField,
+        plaintext: &str,
+    ) -> anyhow::Result<String> {
+        if plaintext.is_empty() {
+            return Ok(String::new());
+        }
+        let inner = self.enabled()?;
+        let (key_id, key) = inner.current_key(tenant_id).await?;
+        inner.seal_with(
+            key_id,
+            &key,
+            &aad(key_id, tenant_id, bookmark_id, field),
+            plaintext,
+        )
+    }
+
+    /// Open a value produced by [`FieldCipher::seal`] for the same tenant,
+    /// bookmark and field. Values that are not sealed are returned unchanged.
+    pub async fn open(
+        &self,
+        tenant_id: i32,
+        bookmark_id: Uuid,
+        field: SealedField,
+        value: &str,
+    ) -> anyhow::Result<String> {
+        let Some((key_id, payload)) = parse_sealed(value)? else {
+            return Ok(value.to_string());
+        };
+        let key = self.enabled()?.key(key_id, tenant_id, &[]).await?;
+        open_with(&key, &aad(key_id, tenant_id, bookmark_id, field), payload)
+    }
+
+    /// Seal a value of bookmark `from` again for bookmark `to`, under the same
+    /// data key, as when a backup is restored under new IDs. `keys` are
+    /// consulted for data keys that are not stored yet.
+    pub async fn rebind(
+        &self,
+        tenant_id: i32,
+        from: Uuid,
+        to: Uuid,
+        field: SealedField,
+        value: &str,
+        keys: &[FieldKeyRow],
+    ) -> anyhow::Result<String> {
+        let Some((key_id, payload)) = parse_sealed(value)? else {
+            return Ok(value.to_string());
+        };
+        let inner = self.enabled()?;
+        let key = inner.key(key_id, tenant_id, keys).await?;
+        let plaintext = open_with(&key, &aad(key_id, tenant_id, from, field), payload)?;
+        inner.seal_with(key_id, &key, &aad(key_id, tenant_id, to, field), &plaintext)
+    }
+
+    fn enabled(&self) -> anyhow::Result<&Inner> {
+        self.inner
+            .as_deref()
+            .context("field encryption is not configured")
+    }
+}
+
+/// The associated data authenticated with a sealed value.
+fn aad(key_id: Uuid, tenant_id: i32, bookmark_id: Uuid, field: SealedField) -> String {
+    format!("{key_id}:{tenant_id}:{bookmark_id}:{}", field.as_str())
+}
+
+/// Whether `value` is a well-formed sealed value rather than plaintext.
+pub fn is_sealed(value: &str) -> bool {
+    matches!(parse_sealed(value), Ok(Some(_)))
+}
+
+/// The key ID and payload of a sealed value; `None` for a plain value.
+fn parse_sealed(value: &str) -> anyhow::Result<Option<(Uuid, Vec<u8>)>> {
+    let Some(rest) = value.strip_prefix(PREFIX) else {
+        return Ok(None);
+    };
+    let (key_id, payload) = rest.split_once(':').context("malformed sealed value")?;
+    let key_id = Uuid::parse_str(key_id).context("malformed sealed value")?;
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload)
+        .context("malformed sealed value")?;
+    if payload.len() < NONCE_LEN {
+        anyhow::bail!("malformed sealed value");
+    }
+    Ok(Some((key_id, payload)))
+}
+
+fn open_with(key: &DataKey, aad: &str, payload: Vec<u8>) -> anyhow::Result<String> {
+    let (nonce, sealed) = payload.split_at(NONCE_LEN);
+    let mut sealed = sealed.to_vec();
+    let nonce = Nonce::try_assume_unique_for_key(nonce)
+        .map_err(|_| anyhow::anyhow!("malformed sealed value"))?;
+    let plaintext = key
+        .key
+        .open_in_place(nonce, Aad::from(aad.as_bytes()), &mut sealed)
+        .map_err(|_| anyhow::anyhow!("sealed value failed authentication"))?;
+    Ok(String::from_utf8(plaintext.to_vec())?)
+}
+
+impl Inner {
+    fn seal_with(
+        &self,
+        key_id: Uuid,
+        key: &DataKey,
+        aad: &str,
+        plaintext: &str,
+    ) -> anyhow::Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| anyhow::anyhow!("system random number generator failed"))?;
+        let mut sealed = plaintext.as_bytes().to_vec();
+        key.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(aad.as_bytes()),
+                &mut sealed,
+            )
+            .map_err(|_| anyhow::anyhow!("sealing failed"))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&sealed);
+        Ok(format!(
+            "{PREFIX}{key_id}:{}",
+            URL_SAFE_NO_PAD.encode(payload)
+        ))
+    }
+
+    async fn current_key(&self, tenant_id: i32) -> anyhow::Result<(Uuid, Arc<DataKey>)> {
+        if let Some(id) = self.current.get(&tenant_id) {
+            return Ok((id, self.key(id, tenant_id, &[]).await?));
+        }
+        let row = match self.store.current(tenant_id).await? {
+            Some(row) => row,
+            None => self.create_key(tenant_id).await?,
+        };
+        let key = self.unwrap_row(&row).await?;
+        self.current.insert(tenant_id, row.id);
+        Ok((row.id, key))
+    }
+
+    /// Concurrent first uses may each create a key; all remain valid and the
+    /// newest becomes current.
+    async fn create_key(&self, tenant_id: i32) -> anyhow::Result<FieldKeyRow> {
+        let mut key = [0u8; KEY_LEN];
+        self.rng
+            .fill(&mut key)
+            .map_err(|_| anyhow::anyhow!("system random number generator failed"))?;
+        let row = FieldKeyRow {
+            id: Uuid::new_v4(),
+            tenant_id,
+            wrapped_key: self.kek.wrap(&key).await?,
+            kek_id: self.kek.id().to_string(),
+            create_time: Utc::now(),
+        };
+        self.store.insert(&row).await?;
+        tracing::info!(tenant_id, key_id = %row.id, "created field encryption key");
+        Ok(row)
+    }
+
+    /// Data key `id`, which must belong to `tenant_id`. `extra` is searched,
+    /// without caching, when the key is not stored.
+    async fn key(
+        &self,
+        id: Uuid,
+        tenant_id: i32,
+        extra: &[FieldKeyRow],
+    ) -> anyhow::Result<Arc<DataKey>> {
+        let key = match self.keys.get(&id) {
+            Some(key) => key,
+            None => match self.store.get(id).await? {
+                Some(row) => self.unwrap_row(&row).await?,
+                None => {
+                    let row = extra
+                        .iter()
+                        .find(|row| row.id == id)
+                        .with_context(|| format!("unknown field encryption key {id}"))?;
+                    Arc::new(self.unwrap_key(row).await?)
+                }
+            },
+        };
+        if key.tenant_id != tenant_id {
+            anyhow::bail!("field encryption key {id} does not belong to tenant {tenant_id}");
+        }
+        Ok(key)
+    }
+
+    async fn unwrap_row(&self, row: &FieldKeyRow) -> anyhow::Result<Arc<DataKey>> {
+        if let Some(key) = self.keys.get(&row.id) {
+            return Ok(key);
+        }
+        let key = Arc::new(self.unwrap_key(row).await?);
+        self.keys.insert(row.id, key.clone());
+        Ok(key)
+    }
+
+    async fn unwrap_key(&self, row: &FieldKeyRow) -> anyhow::Result<DataKey> {
+        if row.kek_id != self.kek.id() {
+            anyhow::bail!(
+                "field encryption key {} is wrapped by {:?}, not the configured {:?}",
+                row.id,
+                row.kek_id,
+                self.kek.id()
+            );
+        }
+        let raw = self.kek.unwrap(&row.wrapped_key).await?;
+        Ok(DataKey {
+            tenant_id: row.tenant_id,
+            key: aes_key(&raw)?,
+        })
+    }
+}
+
+fn aes_key(raw: &[u8]) -> anyhow::Result<LessSafeKey> {
+    let key = UnboundKey::new(&AES_256_GCM, raw)
+        .map_err(|_| anyhow::anyhow!("expected a {KEY_LEN}-byte key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+// --- Master key from configuration ---
+
+struct LocalKek {
+    id: String,
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl LocalKek {
+    fn new(master_key: &str) -> anyhow::Result<Self> {
+        if master_key.is_empty() {
+            anyhow::bail!("data.field_encryption.master_key is required for the local driver");
+        }
+        let raw = STANDARD
+            .decode(master_key.trim())
+            .context("data.field_encryption.master_key is not valid base64")?;
+        let key = aes_key(&raw).context("data.field_encryption.master_key")?;
+        // A fingerprint, so keys wrapped by a different master key are reported
+        // as such rather than failing to authenticate.
+        let fingerprint = Sha256::digest(&raw);
+        Ok(Self {
+            id: format!("local:{}", hex(&fingerprint[..8])),
+            key,
+            rng: SystemRandom::new(),
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl KeyWrapper for LocalKek {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    async fn wrap(&self, key: &[u8]) -> anyhow::Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| anyhow::anyhow!("system random number generator failed"))?;
+        let mut sealed = key.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(self.id.as_bytes()),
+                &mut sealed,
+            )
+            .map_err(|_| anyhow::anyhow!("wrapping failed"))?;
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&sealed);
+        Ok(STANDARD.encode(payload))
+    }
+
+    async fn unwrap(&self, wrapped: &str) -> anyhow::Result<Vec<u8>> {
+        let payload = STANDARD.decode(wrapped).context("malformed wrapped key")?;
+        if payload.len() < NONCE_LEN {
+            anyhow::bail!("malformed wrapped key");
+        }
+        let (nonce, sealed) = payload.split_at(NONCE_LEN);
+        let mut sealed = sealed.to_vec();
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| anyhow::anyhow!("malformed wrapped key"))?;
+        let key = self
+            .key
+            .open_in_place(nonce, Aad::from(self.id.as_bytes()), &mut sealed)
+            .map_err(|_| anyhow::anyhow!("wrapped key failed authentication"))?;
+        Ok(key.to_vec())
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+// --- HashiCorp Vault transit engine ---
+
+struct VaultKek {
+    id: String,
+    client: reqwest::Client,
+    encrypt_url: String,
+    decrypt_url: String,
+    token: String,
+}
+
+impl VaultKek {
+    fn new(cfg: &FieldEncryptionConfig) -> anyhow::Result<Self> {
+        if cfg.endpoint.is_empty() || cfg.key_name.is_empty() {
+            anyhow::bail!(
+                "data.field_encryption.endpoint and key_name are required for the vault driver"
+            );
+        }
+        let timeout: Duration = parse_duration(&cfg.timeout)?;
+        let base = format!(
+            "{}/v1/{}",
+            cfg.endpoint.trim_end_matches('/'),
+            cfg.mount.trim_matches('/')
+        );
+        Ok(Self {
+            id: format!("vault:{}", cfg.key_name),
+            client: reqwest::Client::builder().timeout(timeout).build()?,
+            encrypt_url: format!("{base}/encrypt/{}", cfg.key_name),
+            decrypt_url: format!("{base}/decrypt/{}", cfg.key_name),
+            token: cfg.token.clone(),
+        })
+    }
+
+    /// POST `body` and return the response's `data.<field>`.
+    async fn call(
+        &self,
+        url: &str,
+        body: serde_json::Value,
+        field: &str,
+    ) -> anyhow::Result<String> {
+        let resp = self
+            .client
+            .post(url)
+            .header("X-Vault-Token", &self.token)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(&body)?)
+            .send()
+            .await?;
+        let status = resp.status();
+        let bytes = resp.bytes().await?;
+        if !status.is_success() {
+            anyhow::bail!(
+                "vault returned {status}: {}",
+                String::from_utf8_lossy(&bytes).trim()
+            );
+        }
+        let value: serde_json::Value = serde_json::from_slice(&bytes)?;
+        value["data"][field]
+            .as_str()
+            .map(str::to_string)
+            .with_context(|| format!("vault response has no data.{field}"))
+    }
+}
+
+#[tonic::async_trait]
+impl KeyWrapper for VaultKek {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    async fn wrap(&self, key: &[u8]) -> anyhow::Result<String> {
+        let body = serde_json::json!({ "plaintext": STANDARD.encode(key) });
+        self.call(&self.encrypt_url, body, "ciphertext").await
+    }
+
+    async fn unwrap(&self, wrapped: &str) -> anyhow::Result<Vec<u8>> {
+        let body = serde_json::json!({ "ciphertext": wrapped });
+        let plaintext = self.call(&self.decrypt_url, body, "plaintext").await?;
+        Ok(STANDARD
+            .decode(plaintext)
+            .context("malformed vault plaintext")?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::sqlite::field_key_repo::SqliteFieldKeyRepo;
+    use crate::data::sqlite::test_pool;
+
+    async fn cipher() -> FieldCipher {
+        let pool = test_pool().await;
+        let kek = LocalKek::new(&STANDARD.encode([7u8; KEY_LEN])).unwrap();
+        FieldCipher::new(Some(Arc::new(kek)), Arc::new(SqliteFieldKeyRepo::new(pool)))
+    }
+
+    #[tokio::test]
+    async fn sealed_values_open_only_where_they_were_sealed() {
+        let cipher = cipher().await;
+        let id = Uuid::new_v4();
+        let url = "https://example.com/secret";
+
+        let sealed = cipher.seal(1, id, SealedField::Url, url).await.unwrap();
+        assert!(sealed.starts_with(PREFIX));
+        assert!(!sealed.contains("example.com"));
+        assert_eq!(
+            cipher.open(1, id, SealedField::Url, &sealed).await.unwrap(),
+            url
+        );
+
+        // Another bookmark, another field, or another tenant's key.
+        let other = Uuid::new_v4();
+        assert!(cipher
+            .open(1, other, SealedField::Url, &sealed)
+            .await
+            .is_err());
+        assert!(cipher
+            .open(1, id, SealedField::Notes, &sealed)
+            .await
+            .is_err());
+        let foreign = cipher.seal(2, id, SealedField::Url, url).await.unwrap();
+        let err = cipher
+            .open(1, id, SealedField::Url, &foreign)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("does not belong to tenant 1"));
+
+        let rebound = cipher
+            .rebind(1, id, other, SealedField::Url, &sealed, &[])
+            .await
+            .unwrap();
+        assert_eq!(
+            cipher
+                .open(1, other, SealedField::Url, &rebound)
+                .await
+                .unwrap(),
+            url
+        );
+        assert!(cipher
+            .open(1, id, SealedField::Url, &rebound)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn tampered_values_fail_to_open() {
+        let cipher = cipher().await;
+        let id = Uuid::new_v4();
+        let sealed = cipher
+            .seal(1, id, SealedField::Notes, "remember this")
+            .await
+            .unwrap();
+
+        let (head, payload) = sealed.rsplit_once(':').unwrap();
+        let mut bytes = URL_SAFE_NO_PAD.decode(payload).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let tampered = format!("{head}:{}", URL_SAFE_NO_PAD.encode(bytes));
+        let err = cipher
+            .open(1, id, SealedField::Notes, &tampered)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("failed authentication"));
+
+        let unknown = format!("{PREFIX}{}:{payload}", Uuid::new_v4());
+        assert!(cipher
+            .open(1, id, SealedField::Notes, &unknown)
+            .await
+            .is_err());
+        assert!(cipher
+            .open(1, id, SealedField::Notes, &format!("{PREFIX}garbage"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn plain_values_pass_through() {
+        let cipher = cipher().await;
+        let id = Uuid::new_v4();
+        assert_eq!(
+            cipher.seal(1, id, SealedField::Notes, "").await.unwrap(),
+            ""
+        );
+        assert_eq!(
+            cipher
+                .open(1, id, SealedField::Url, "https://a.example")
+                .await
+                .unwrap(),
+            "https://a.example"
+        );
+    }
+}
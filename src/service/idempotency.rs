@@ -0,0 +1,180 @@
+//! Replay of mutating RPCs retried with the same `x-idempotency-key`.
+//!
+//! The first request with a key claims it; its response is stored and returned
+//! unchanged to retries until the key expires. Requests are compared by the
+//! SHA-256 of their protobuf encoding, so reusing a key for a different request
+//! is rejected instead of silently replaying the wrong response. A request
+//! still running holds its key only until its deadline has passed, so a claim
+//! whose handler was cancelled or crashed does not block retries for the TTL.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use prost::Message;
+use sha2::{Digest, Sha256};
+use tonic::{Request, Response, Status};
+
+use crate::config::{parse_duration, IdempotencyConfig};
+use crate::data::idempotency_repo::{IdempotencyKey, IdempotencyRow, IdempotencyStore};
+use crate::service::context_helper::extract_context;
+use crate::service::error::ServiceError;
+use crate::shutdown::ShutdownSignal;
+
+const MD_IDEMPOTENCY_KEY: &str = "x-idempotency-key";
+const MAX_KEY_LEN: usize = 255;
+/// Added to a pending claim's lease so a request finishing right at its
+/// deadline still records its response before a retry can take the key.
+const LEASE_GRACE: Duration = Duration::from_secs(60);
+
+/// Outcome of [`Idempotency::begin`].
+pub enum Claim {
+    /// No key was sent; the request runs as usual.
+    Untracked,
+    /// An earlier request with the key completed; this is its encoded response.
+    Replay(Vec<u8>),
+    /// This request owns the key and must hand the claim to [`Idempotency::finish`].
+    Owned(IdempotencyKey),
+}
+
+impl Claim {
+    /// The stored response when this is a retry of a completed request.
+    pub fn replay<R: Message + Default>(&self) -> Result<Option<Response<R>>, Status> {
+        let Self::Replay(bytes) = self else {
+            return Ok(None);
+        };
+        R::decode(bytes.as_slice())
+            .map(|r| Some(Response::new(r)))
+            .map_err(|e| Status::internal(format!("stored response is corrupt: {e}")))
+    }
+}
+
+#[derive(Clone)]
+pub struct Idempotency {
+    store: Arc<dyn IdempotencyStore>,
+    ttl: chrono::Duration,
+    /// How long a request in progress holds its key.
+    lease: chrono::Duration,
+}
+
+impl Idempotency {
+    pub fn new(store: Arc<dyn IdempotencyStore>, cfg: &IdempotencyConfig) -> Self {
+        let ttl = parse_duration(&cfg.ttl).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "invalid idempotency ttl, using 24h");
+            Duration::from_secs(24 * 3600)
+        });
+        let ttl = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::hours(24));
+        Self {
+            store,
+            ttl,
+            lease: ttl,
+        }
+    }
+
+    /// Let pending claims lapse once requests bounded by `deadline` are over,
+    /// rather than after the full TTL. A zero deadline keeps the TTL.
+    pub fn with_lease(mut self, deadline: Duration) -> Self {
+        if !deadline.is_zero() {
+            self.lease = chrono::Duration::from_std(deadline + LEASE_GRACE)
+                .unwrap_or(self.ttl)
+                .min(self.ttl);
+        }
+        self
+    }
+
+    /// Claim the request's idempotency key, if it has one.
+    pub async fn begin<T: Message>(
+        &self,
+        method: &'static str,
+        request: &Request<T>,
+    ) -> Result<Claim, Status> {
+        let Some(key) = request.metadata().get(MD_IDEMPOTENCY_KEY) else {
+            return Ok(Claim::Untracked);
+        };
+        let key = key
+            .to_str()
+            .ok()
+            .filter(|k| !k.is_empty() && k.len() <= MAX_KEY_LEN)
+            .ok_or_else(|| {
+                Status::invalid_argument(format!(
+                    "{MD_IDEMPOTENCY_KEY} must be 1-{MAX_KEY_LEN} printable ASCII characters"
+                ))
+            })?;
+        let ctx = extract_context(request)?;
+        let key = IdempotencyKey {
+            tenant_id: ctx.tenant_id,
+            user_id: ctx.user_id,
+            method,
+            key: key.to_string(),
+        };
+        let request_hash = Sha256::digest(request.get_ref().encode_to_vec()).to_vec();
+
+        let existing = self
+            .store
+            .claim(&key, &request_hash, chrono::Utc::now() + self.lease)
+            .await
+            .map_err(ServiceError::database)?;
+        match existing {
+            None => Ok(Claim::Owned(key)),
+            Some(row) if row.request_hash != request_hash => Err(Status::invalid_argument(
+                "idempotency key was already used for a different request",
+            )),
+            Some(IdempotencyRow {
+                response: Some(response),
+                ..
+            }) => Ok(Claim::Replay(response)),
+            Some(_) => Err(Status::aborted(
+                "a request with this idempotency key is still in progress",
+            )),
+        }
+    }
+
+    /// Store a successful response for replay until the TTL runs out, or
+    /// release the key after a failure so the client can retry.
+    pub async fn finish<R: Message>(&self, claim: Claim, result: &Result<Response<R>, Status>) {
+        let Claim::Owned(key) = claim else {
+            return;
+        };
+        let recorded = match result {
+            Ok(response) => {
+                self.store
+                    .complete(
+                        &key,
+                        &response.get_ref().encode_to_vec(),
+                        chrono::Utc::now() + self.ttl,
+                    )
+                    .await
+            }
+            Err(_) => self.store.release(&key).await,
+        };
+        if let Err(e) = recorded {
+            tracing::warn!(error = %e, method = key.method, "failed to record idempotency key");
+        }
+    }
+}
+
+/// Periodically delete expired idempotency keys.
+pub fn spawn_expired_key_purge(
+    store: Arc<dyn IdempotencyStore>,
+    cfg: &IdempotencyConfig,
+    mut shutdown: ShutdownSignal,
+) -> tokio::task::JoinHandle<()> {
+    let interval = parse_duration(&cfg.purge_interval).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "invalid idempotency purge_interval, using 1h");
+        Duration::from_secs(3600)
+    });
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.recv() => break,
+            }
+            match store.purge_expired().await {
+                Ok(0) => {}
+                Ok(purged) => tracing::debug!(purged, "purged expired idempotency keys"),
+                Err(e) => tracing::error!(error = %e, "failed to purge idempotency keys"),
+            }
+        }
+    })
+}
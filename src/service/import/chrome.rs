@@ -0,0 +1,56 @@
+//! Chrome's `Bookmarks` JSON file (also used by Edge and Brave).
+
+use serde_json::Value;
+
+use super::{folder_tag, ImportedBookmark};
+
+/// Walk every root (bookmark bar, other, mobile). Each bookmark is tagged with
+/// its folder path below the root, e.g. `Dev/Rust`.
+pub fn parse(data: &[u8]) -> anyhow::Result<Vec<ImportedBookmark>> {
+    let file: Value = serde_json::from_slice(data)?;
+    let Some(roots) = file.get("roots").and_then(Value::as_object) else {
+        anyhow::bail!("missing roots object");
+    };
+
+    let mut items = Vec::new();
+    for root in roots.values() {
+        let mut path = Vec::new();
+        for child in children(root) {
+            walk(child, &mut path, &mut items);
+        }
+    }
+    Ok(items)
+}
+
+fn walk<'a>(node: &'a Value, path: &mut Vec<&'a str>, items: &mut Vec<ImportedBookmark>) {
+    let name = node.get("name").and_then(Value::as_str).unwrap_or("");
+    match node.get("type").and_then(Value::as_str) {
+        Some("url") => {
+            let url = node.get("url").and_then(Value::as_str).unwrap_or("");
+            if url.is_empty() {
+                return;
+            }
+            items.push(ImportedBookmark {
+                url: url.to_string(),
+                title: name.to_string(),
+                tags: folder_tag(path.iter().copied()).into_iter().collect(),
+                ..Default::default()
+            });
+        }
+        Some("folder") => {
+            path.push(name);
+            for child in children(node) {
+                walk(child, path, items);
+            }
+            path.pop();
+        }
+        _ => {}
+    }
+}
+
+fn children(node: &Value) -> &[Value] {
+    node.get("children")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
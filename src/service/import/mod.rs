@@ -0,0 +1,117 @@
+//! Parsers for bookmark exports from other services. Each parser turns a file
+//! into [`ImportedBookmark`]s; creating them is up to the caller.
+
+pub mod chrome;
+pub mod pocket;
+pub mod raindrop;
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::data::user_flag_repo::ReadingStatus;
+use crate::service::page_metadata::decode_entities;
+
+static ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?is)([a-z_:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+/// One bookmark read from an export file.
+#[derive(Debug, Default)]
+pub struct ImportedBookmark {
+    pub url: String,
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub pinned: bool,
+    pub reading_status: Option<ReadingStatus>,
+}
+
+/// Attributes of an HTML start tag, keyed by lowercase name, entities decoded.
+fn attributes(tag: &str) -> HashMap<String, String> {
+    ATTR_RE
+        .captures_iter(tag)
+        .map(|attr| {
+            let value = attr.get(2).or(attr.get(3)).map_or("", |m| m.as_str());
+            (attr[1].to_ascii_lowercase(), decode_entities(value))
+        })
+        .collect()
+}
+
+/// Split a tag list on `sep`, trimming and dropping empty entries.
+fn split_tags(tags: &str, sep: char) -> Vec<String> {
+    tags.split(sep)
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Tag for a folder path, e.g. `["Dev", "Rust"]` becomes `Dev/Rust`.
+fn folder_tag<'a>(segments: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let path = segments
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    (!path.is_empty()).then_some(path)
+}
+
+/// One CSV record with fields looked up by header name.
+struct CsvRecord<'a> {
+    headers: &'a csv::StringRecord,
+    record: &'a csv::StringRecord,
+}
+
+impl CsvRecord<'_> {
+    /// Trimmed field under the header `name` (case-insensitive); empty when absent.
+    fn get(&self, name: &str) -> &str {
+        self.headers
+            .iter()
+            .position(|h| h.trim_start_matches('\u{feff}').eq_ignore_ascii_case(name))
+            .and_then(|i| self.record.get(i))
+            .unwrap_or("")
+            .trim()
+    }
+}
+
+/// Parse a CSV export with a header row that must include a `url` column.
+/// Records without a URL are skipped.
+fn read_csv(
+    data: &[u8],
+    mut to_bookmark: impl FnMut(&CsvRecord) -> ImportedBookmark,
+) -> anyhow::Result<Vec<ImportedBookmark>> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(data);
+    let headers = reader.headers()?.clone();
+    if !headers
+        .iter()
+        .any(|h| h.trim_start_matches('\u{feff}').eq_ignore_ascii_case("url"))
+    {
+        anyhow::bail!("missing url column");
+    }
+
+    let mut items = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let record = CsvRecord {
+            headers: &headers,
+            record: &record,
+        };
+        if !record.get("url").is_empty() {
+            items.push(to_bookmark(&record));
+        }
+    }
+    Ok(items)
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(value, "1" | "true" | "TRUE" | "True")
+}
+
+/// Whether an export file looks like HTML rather than CSV.
+fn is_html(data: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&data[..data.len().min(512)]).to_ascii_lowercase();
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    head.starts_with("<!doctype") || head.starts_with("<html")
+}
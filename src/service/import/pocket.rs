@@ -0,0 +1,68 @@
+//! Pocket exports: the legacy `ril_export.html` and the newer CSV export.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::{attributes, is_html, is_truthy, read_csv, split_tags, ImportedBookmark};
+use crate::data::user_flag_repo::ReadingStatus;
+use crate::service::page_metadata::decode_entities;
+
+/// Section headings (`<h1>Unread</h1>`, `<h1>Read Archive</h1>`) and links, in order.
+static HTML_ITEM_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<h1[^>]*>(.*?)</h1>|<a(\s[^>]*)>(.*?)</a>").unwrap());
+
+/// Parse either export format. Favorites become pins and archived items are
+/// marked done.
+pub fn parse(data: &[u8]) -> anyhow::Result<Vec<ImportedBookmark>> {
+    if is_html(data) {
+        Ok(parse_html(&String::from_utf8_lossy(data)))
+    } else {
+        parse_csv(data)
+    }
+}
+
+/// `<a href=".." time_added=".." tags="a,b">Title</a>`; items under the
+/// "Read Archive" heading are archived.
+fn parse_html(html: &str) -> Vec<ImportedBookmark> {
+    let mut archived = false;
+    let mut items = Vec::new();
+    for cap in HTML_ITEM_RE.captures_iter(html) {
+        if let Some(heading) = cap.get(1) {
+            archived = heading.as_str().to_ascii_lowercase().contains("archive");
+            continue;
+        }
+        let attrs = attributes(&cap[2]);
+        let Some(url) = attrs.get("href").filter(|u| !u.is_empty()) else {
+            continue;
+        };
+        items.push(ImportedBookmark {
+            url: url.clone(),
+            title: decode_entities(cap[3].trim()),
+            description: String::new(),
+            tags: attrs
+                .get("tags")
+                .map(|t| split_tags(t, ','))
+                .unwrap_or_default(),
+            pinned: attrs.get("favorite").is_some_and(|f| is_truthy(f)),
+            reading_status: archived.then_some(ReadingStatus::Done),
+        });
+    }
+    items
+}
+
+/// Columns `title,url,time_added,tags,status` (and `favorite` when present);
+/// tags are `|`-separated.
+fn parse_csv(data: &[u8]) -> anyhow::Result<Vec<ImportedBookmark>> {
+    read_csv(data, |record| ImportedBookmark {
+        url: record.get("url").to_string(),
+        title: record.get("title").to_string(),
+        description: String::new(),
+        tags: split_tags(record.get("tags"), '|'),
+        pinned: is_truthy(record.get("favorite")),
+        reading_status: record
+            .get("status")
+            .eq_ignore_ascii_case("archive")
+            .then_some(ReadingStatus::Done),
+    })
+}
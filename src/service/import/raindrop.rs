@@ -0,0 +1,30 @@
+//! Raindrop.io CSV exports.
+
+use super::{folder_tag, is_truthy, read_csv, split_tags, ImportedBookmark};
+
+/// Columns `id,title,note,excerpt,url,folder,tags,created,cover,highlights,favorite`,
+/// looked up by header name. Tags are comma-separated, the collection path in
+/// `folder` becomes one more tag, and favorites are pinned.
+pub fn parse(data: &[u8]) -> anyhow::Result<Vec<ImportedBookmark>> {
+    read_csv(data, |record| {
+        let mut tags = split_tags(record.get("tags"), ',');
+        if let Some(folder) = folder_tag(record.get("folder").split('/')) {
+            if !tags.contains(&folder) {
+                tags.push(folder);
+            }
+        }
+        let excerpt = record.get("excerpt");
+        ImportedBookmark {
+            url: record.get("url").to_string(),
+            title: record.get("title").to_string(),
+            description: if excerpt.is_empty() {
+                record.get("note").to_string()
+            } else {
+                excerpt.to_string()
+            },
+            tags,
+            pinned: is_truthy(record.get("favorite")),
+            reading_status: None,
+        }
+    })
+}
@@ -1,5 +1,31 @@
+pub mod access_report;
+pub mod access_request_service;
+pub mod admin_service;
+pub mod api_key_service;
+pub mod archiver;
+pub mod audit_log;
+pub mod auto_tag;
+pub mod auto_tag_rule_service;
 pub mod backup_service;
+pub mod bookmark_export;
+pub mod bookmark_kind;
 pub mod bookmark_service;
 pub mod permission_service;
+pub mod permission_template_service;
 pub mod user_service;
 pub mod context_helper;
+pub mod error;
+pub mod field_cipher;
+pub mod idempotency;
+pub mod import;
+pub mod page_metadata;
+pub mod page_token;
+pub mod rank;
+pub mod saved_search_alerts;
+pub mod saved_search_service;
+pub mod share_notifier;
+pub mod tag_service;
+pub mod url_normalizer;
+pub mod url_policy;
+pub mod url_scrubber;
+pub mod usage_stats;
@@ -0,0 +1,223 @@
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use regex::Regex;
+
+use crate::config::{parse_duration, MetadataFetchConfig};
+use crate::data::bookmark_repo::BookmarkKind;
+use crate::service::bookmark_kind::classify;
+use crate::service::url_policy::{FetchGuard, GuardedResolver};
+
+static TITLE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap());
+static META_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?is)<meta\s[^>]*>").unwrap());
+static ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)([a-z_:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap()
+});
+
+/// Metadata extracted from a page's `<head>`.
+#[derive(Debug, Default, Clone)]
+pub struct PageMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub site_name: Option<String>,
+    /// OpenGraph `og:type`, e.g. `article` or `video.other`.
+    pub og_type: Option<String>,
+    /// Set by [`MetadataFetcher::fetch`] from the response; `Other` when parsed directly.
+    pub kind: BookmarkKind,
+}
+
+/// Redirects followed before a fetch gives up.
+const MAX_REDIRECTS: usize = 5;
+
+/// Fetches pages and extracts `<title>`, meta description and OpenGraph tags.
+/// Every outbound request, redirects included, goes through a [`FetchGuard`].
+#[derive(Clone)]
+pub struct MetadataFetcher {
+    client: reqwest::Client,
+    guard: Arc<FetchGuard>,
+    timeout: Duration,
+    max_bytes: usize,
+}
+
+impl MetadataFetcher {
+    pub fn new(cfg: &MetadataFetchConfig) -> anyhow::Result<Self> {
+        let timeout = parse_duration(&cfg.timeout)?;
+        let guard = Arc::new(FetchGuard::new(cfg)?);
+        let redirect_guard = guard.clone();
+        let client = reqwest::Client::builder()
+            .user_agent(cfg.user_agent.clone())
+            .timeout(timeout)
+            .dns_resolver(Arc::new(GuardedResolver(guard.clone())))
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() > MAX_REDIRECTS {
+                    return attempt.error("too many redirects");
+                }
+                match redirect_guard.check_url(attempt.url()) {
+                    Ok(()) => attempt.follow(),
+                    Err(e) => attempt.error(format!("redirect refused: {e}")),
+                }
+            }))
+            .build()?;
+        Ok(Self {
+            client,
+            guard,
+            timeout,
+            max_bytes: cfg.max_bytes,
+        })
+    }
+
+    /// Fetch `url` and extract its metadata, giving up after the configured
+    /// timeout. Non-HTML responses are not read; only their kind is set.
+    pub async fn fetch(&self, url: &str) -> Result<PageMetadata, String> {
+        let parsed = self.parse_checked(url)?;
+        tokio::time::timeout(self.timeout, async {
+            let resp = self.get(parsed).await?;
+            let content_type = content_type(&resp);
+            if !is_html(content_type.as_deref()) {
+                return Ok(PageMetadata {
+                    kind: classify(url, content_type.as_deref(), None),
+                    ..Default::default()
+                });
+            }
+            let (html, _) = read_body(resp, self.max_bytes).await?;
+            let mut meta = parse_metadata(&html);
+            meta.kind = classify(url, content_type.as_deref(), meta.og_type.as_deref());
+            Ok(meta)
+        })
+        .await
+        .map_err(|_| "timed out".to_string())?
+    }
+
+    /// Fetch up to `max_bytes` of an HTML page. Returns the body and whether it
+    /// was cut short.
+    pub async fn fetch_page(&self, url: &str, max_bytes: usize) -> Result<(String, bool), String> {
+        let parsed = self.parse_checked(url)?;
+        tokio::time::timeout(self.timeout, async {
+            let resp = self.get(parsed).await?;
+            if !is_html(content_type(&resp).as_deref()) {
+                return Err("response is not HTML".to_string());
+            }
+            read_body(resp, max_bytes).await
+        })
+        .await
+        .map_err(|_| "timed out".to_string())?
+    }
+
+    fn parse_checked(&self, url: &str) -> Result<url::Url, String> {
+        let parsed = url::Url::parse(url).map_err(|e| format!("invalid url: {e}"))?;
+        self.guard.check_url(&parsed)?;
+        Ok(parsed)
+    }
+
+    async fn get(&self, url: url::Url) -> Result<reqwest::Response, String> {
+        self.client
+            .get(url)
+            .header(reqwest::header::ACCEPT, "text/html,application/xhtml+xml")
+            .send()
+            .await
+            .map_err(|e| error_chain(&e))?
+            .error_for_status()
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn content_type(resp: &reqwest::Response) -> Option<String> {
+    resp.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+fn is_html(content_type: Option<&str>) -> bool {
+    content_type.is_none_or(|ct| ct.contains("html"))
+}
+
+/// Read up to `max_bytes` of a response body. Returns the text and whether it
+/// was cut short.
+async fn read_body(
+    mut resp: reqwest::Response,
+    max_bytes: usize,
+) -> Result<(String, bool), String> {
+    // Stop reading once the cap is reached rather than buffering huge pages.
+    let mut body = Vec::new();
+    let mut truncated = false;
+    while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+        body.extend_from_slice(&chunk);
+        if body.len() >= max_bytes {
+            truncated = body.len() > max_bytes;
+            body.truncate(max_bytes);
+            break;
+        }
+    }
+    Ok((String::from_utf8_lossy(&body).into_owned(), truncated))
+}
+
+/// Render an error with its sources, which carry the reason a blocked
+/// address or redirect was refused.
+fn error_chain(e: &dyn std::error::Error) -> String {
+    let mut message = e.to_string();
+    let mut source = e.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+    message
+}
+
+/// Extract metadata from raw HTML; OpenGraph values win over plain tags.
+pub fn parse_metadata(html: &str) -> PageMetadata {
+    let mut meta = PageMetadata::default();
+    let mut description = None;
+    let mut og_title = None;
+
+    for tag in META_RE.find_iter(html) {
+        let mut key = None;
+        let mut content = None;
+        for attr in ATTR_RE.captures_iter(tag.as_str()) {
+            let value = attr.get(2).or(attr.get(3)).map(|m| m.as_str());
+            match attr[1].to_ascii_lowercase().as_str() {
+                "name" | "property" => key = value.map(str::to_ascii_lowercase),
+                "content" => content = value.map(decode_entities),
+                _ => {}
+            }
+        }
+        let (Some(key), Some(content)) = (key, content) else {
+            continue;
+        };
+        if content.is_empty() {
+            continue;
+        }
+        match key.as_str() {
+            "og:title" => og_title = Some(content),
+            "og:description" => meta.description = Some(content),
+            "description" => description = Some(content),
+            "og:image" => meta.image_url = Some(content),
+            "og:site_name" => meta.site_name = Some(content),
+            "og:type" => meta.og_type = Some(content),
+            _ => {}
+        }
+    }
+
+    meta.title = og_title.or_else(|| {
+        TITLE_RE
+            .captures(html)
+            .map(|c| decode_entities(c[1].trim()))
+            .filter(|t| !t.is_empty())
+    });
+    meta.description = meta.description.or(description);
+    meta
+}
+
+pub fn decode_entities(s: &str) -> String {
+    s.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
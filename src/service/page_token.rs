@@ -0,0 +1,113 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use tonic::Status;
+
+/// Keyset position: the `(create_time, id)` of the last row on the previous page.
+#[derive(Debug, Clone)]
+pub struct PageCursor {
+    pub create_time: DateTime<Utc>,
+    pub id: String,
+}
+
+/// Encode a cursor as an opaque, URL-safe token.
+pub fn encode(create_time: DateTime<Utc>, id: &str) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}:{id}", create_time.timestamp_micros()))
+}
+
+/// Decode a token produced by [`encode`]. An empty token means "first page".
+pub fn decode(token: &str) -> Result<Option<PageCursor>, Status> {
+    if token.is_empty() {
+        return Ok(None);
+    }
+
+    let invalid = || Status::invalid_argument("invalid page_token");
+    let raw = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+    let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+    let (micros, id) = raw.split_once(':').ok_or_else(invalid)?;
+    let micros: i64 = micros.parse().map_err(|_| invalid())?;
+
+    Ok(Some(PageCursor {
+        create_time: DateTime::from_timestamp_micros(micros).ok_or_else(invalid)?,
+        id: id.to_string(),
+    }))
+}
+
+/// Encode a change-log position for `SyncChanges`.
+pub fn encode_change_cursor(txid: i64, seq: i64) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{txid}:{seq}"))
+}
+
+/// Decode a token produced by [`encode_change_cursor`]. An empty token is the
+/// start of the log.
+pub fn decode_change_cursor(token: &str) -> Result<(i64, i64), Status> {
+    if token.is_empty() {
+        return Ok((0, 0));
+    }
+
+    let invalid = || Status::invalid_argument("invalid cursor");
+    let raw = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+    let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+    let (txid, seq) = raw.split_once(':').ok_or_else(invalid)?;
+    Ok((
+        txid.parse().map_err(|_| invalid())?,
+        seq.parse().map_err(|_| invalid())?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use tonic::Code;
+
+    use super::*;
+
+    fn encoded(raw: &[u8]) -> String {
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    #[test]
+    fn page_tokens_round_trip() {
+        let time = DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap();
+        let id = "7f1c1a52-0d4e-4b8e-9a3f-2b6f0c9d1e11";
+        let cursor = decode(&encode(time, id)).unwrap().unwrap();
+        assert_eq!(cursor.create_time, time);
+        assert_eq!(cursor.id, id);
+        assert!(decode("").unwrap().is_none());
+    }
+
+    #[test]
+    fn tampered_page_tokens_are_invalid_arguments() {
+        let time = DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap();
+        let token = encode(time, "id");
+        for bad in [
+            format!("{token}!"),
+            token[1..].to_string(),
+            encoded(b"\xff\xfe:id"),
+            encoded(b"1700000000"),
+            encoded(b"soon:id"),
+            encoded(b"99999999999999999999:id"),
+            encoded(b"9223372036854775807:id"),
+        ] {
+            let err = decode(&bad).unwrap_err();
+            assert_eq!(err.code(), Code::InvalidArgument, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn change_cursors_round_trip_and_reject_tampering() {
+        assert_eq!(
+            decode_change_cursor(&encode_change_cursor(12, 3)).unwrap(),
+            (12, 3)
+        );
+        assert_eq!(decode_change_cursor("").unwrap(), (0, 0));
+        for bad in [
+            "not base64!".to_string(),
+            encoded(b"12"),
+            encoded(b"12:x"),
+            encoded(b"x:3"),
+        ] {
+            let err = decode_change_cursor(&bad).unwrap_err();
+            assert_eq!(err.code(), Code::InvalidArgument, "{bad:?}");
+        }
+    }
+}
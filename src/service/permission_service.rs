@@ -1,34 +1,78 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
 use tonic::{Request, Response, Status};
 
 use crate::authz::checker::Checker;
-use crate::authz::relations::{Permission, Relation, ResourceType, SubjectType};
-use crate::data::permission_repo::PermissionRow;
-use crate::service::context_helper::extract_context;
+use crate::authz::engine::{StepOutcome, TraceStep};
+use crate::authz::relations::{
+    Permission, Relation, ResourceType, SubjectType, WILDCARD_RESOURCE,
+};
+use crate::client::admin_client::AdminClient;
+use crate::data::group_repo::GroupMemberRow;
+use crate::data::permission_repo::{PermissionRow, TupleGrant, TupleRevoke};
+use crate::data::share_notification_repo::NewShareNotification;
+use crate::events::{Event, EventPublisher};
+use crate::service::bookmark_service::timestamp_to_datetime;
+use crate::service::context_helper::{extract_context, RequestContext};
+use crate::service::idempotency::Idempotency;
+use crate::service::page_token;
+use crate::service::share_notifier::ShareNotifications;
 
 // Re-use the proto module from bookmark_service (same package)
 use crate::service::bookmark_service::proto;
+use crate::service::error::ServiceError;
 
 use proto::bookmark_permission_service_server::BookmarkPermissionService;
 use proto::{
-    CheckAccessRequest, CheckAccessResponse, GetEffectivePermissionsRequest,
+    AddGroupMemberRequest, BatchAccessResponse, BatchAccessResult, BatchGrantAccessRequest,
+    BatchRevokeAccessRequest, CheckAccessRequest, CheckAccessResponse, DecisionOutcome, DecisionStep,
+    GetEffectivePermissionsRequest,
     GetEffectivePermissionsResponse, GrantAccessRequest, GrantAccessResponse,
-    ListAccessibleResourcesRequest, ListAccessibleResourcesResponse, ListPermissionsRequest,
-    ListPermissionsResponse, PermissionTuple, RevokeAccessRequest,
+    GrantFederatedAccessRequest, GroupMember,
+    ListAccessibleResourcesRequest, ListAccessibleResourcesResponse, ListGroupMembersRequest,
+    ListGroupMembersResponse, ListPermissionsRequest,
+    ListPermissionsResponse, ListResourcesForSubjectRequest, ListResourcesForSubjectResponse,
+    ListSubjectsRequest, ListSubjectsResponse, PermissionDecision, PermissionTuple,
+    RemoveGroupMemberRequest,
+    RevokeAccessRequest, ShareBookmarkRequest, ShareBookmarkResponse,
+    SubjectAccess, SubjectResource, TransferOwnershipRequest, TransferOwnershipResponse,
 };
 
+/// Items accepted by one batch grant or revoke.
+const MAX_BATCH_TUPLES: usize = 500;
+
 pub struct PermissionServiceImpl {
     checker: Checker,
+    admin_client: Option<AdminClient>,
+    events: EventPublisher,
+    idempotency: Idempotency,
+    shares: ShareNotifications,
+    /// Expiry of a `ShareBookmark` grant that sets none.
+    share_expiry: chrono::Duration,
 }
 
 impl PermissionServiceImpl {
-    pub fn new(checker: Checker) -> Self {
-        Self { checker }
+    pub fn new(
+        checker: Checker,
+        admin_client: Option<AdminClient>,
+        events: EventPublisher,
+        idempotency: Idempotency,
+        shares: ShareNotifications,
+        share_expiry: chrono::Duration,
+    ) -> Self {
+        Self {
+            checker,
+            admin_client,
+            events,
+            idempotency,
+            shares,
+            share_expiry,
+        }
     }
-}
 
-#[tonic::async_trait]
-impl BookmarkPermissionService for PermissionServiceImpl {
-    async fn grant_access(
+    /// `GrantAccess` without idempotency-key handling.
+    async fn do_grant_access(
         &self,
         request: Request<GrantAccessRequest>,
     ) -> Result<Response<GrantAccessResponse>, Status> {
@@ -78,7 +122,306 @@ impl BookmarkPermissionService for PermissionServiceImpl {
                 expires_at,
             )
             .await
-            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+            .map_err(ServiceError::database)?;
+
+        self.events
+            .publish(Event::permission_granted(&ctx.user_id, &row));
+
+        Ok(Response::new(GrantAccessResponse {
+            permission: Some(row_to_proto(row)),
+        }))
+    }
+
+    /// Validate one item of a batch grant and check the caller may share its resource.
+    async fn authorize_grant(
+        &self,
+        ctx: &RequestContext,
+        req: GrantAccessRequest,
+    ) -> Result<TupleGrant, Status> {
+        let resource_type = ResourceType::from_proto(req.resource_type)
+            .ok_or_else(|| Status::invalid_argument("invalid resource_type"))?;
+        let relation = Relation::from_proto(req.relation)
+            .ok_or_else(|| Status::invalid_argument("invalid relation"))?;
+        let subject_type = SubjectType::from_proto(req.subject_type)
+            .ok_or_else(|| Status::invalid_argument("invalid subject_type"))?;
+        if req.resource_id.is_empty() || req.subject_id.is_empty() {
+            return Err(Status::invalid_argument(
+                "resource_id and subject_id are required",
+            ));
+        }
+        let expires_at = req
+            .expires_at
+            .as_ref()
+            .map(timestamp_to_datetime)
+            .transpose()?;
+
+        self.checker
+            .can_share(ctx.tenant_id, &ctx.user_id, &req.resource_id, &ctx.role_ids)
+            .await?;
+
+        Ok(TupleGrant {
+            resource_type,
+            resource_id: req.resource_id,
+            relation,
+            subject_type,
+            subject_id: req.subject_id,
+            expires_at,
+        })
+    }
+
+    /// Validate one item of a batch revoke and check the caller may share its resource.
+    async fn authorize_revoke(
+        &self,
+        ctx: &RequestContext,
+        req: RevokeAccessRequest,
+    ) -> Result<TupleRevoke, Status> {
+        let resource_type = ResourceType::from_proto(req.resource_type)
+            .ok_or_else(|| Status::invalid_argument("invalid resource_type"))?;
+        let subject_type = SubjectType::from_proto(req.subject_type)
+            .ok_or_else(|| Status::invalid_argument("invalid subject_type"))?;
+        let relation = match req.relation {
+            Some(v) => Some(
+                Relation::from_proto(v)
+                    .ok_or_else(|| Status::invalid_argument("invalid relation"))?,
+            ),
+            None => None,
+        };
+        if req.resource_id.is_empty() || req.subject_id.is_empty() {
+            return Err(Status::invalid_argument(
+                "resource_id and subject_id are required",
+            ));
+        }
+
+        self.checker
+            .can_share(ctx.tenant_id, &ctx.user_id, &req.resource_id, &ctx.role_ids)
+            .await?;
+
+        Ok(TupleRevoke {
+            resource_type,
+            resource_id: req.resource_id,
+            relation,
+            subject_type,
+            subject_id: req.subject_id,
+        })
+    }
+
+    /// Ensure a user exists in admin-service and belongs to the given tenant.
+    async fn ensure_user_in_tenant(&self, tenant_id: i32, user_id: &str) -> Result<(), Status> {
+        let admin = self
+            .admin_client
+            .as_ref()
+            .ok_or_else(|| Status::unavailable("user directory unavailable"))?;
+        let id = user_id
+            .parse::<u32>()
+            .map_err(|_| Status::invalid_argument("invalid user id"))?;
+
+        let user = admin
+            .find_user(id)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, "failed to look up user in admin-service");
+                Status::unavailable("failed to look up user")
+            })?
+            .ok_or_else(|| Status::not_found("user not found"))?;
+
+        if user.tenant_id.unwrap_or(0) as i32 != tenant_id {
+            return Err(Status::failed_precondition(
+                "user does not belong to this tenant",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl BookmarkPermissionService for PermissionServiceImpl {
+    async fn grant_access(
+        &self,
+        request: Request<GrantAccessRequest>,
+    ) -> Result<Response<GrantAccessResponse>, Status> {
+        let claim = self.idempotency.begin("GrantAccess", &request).await?;
+        if let Some(replay) = claim.replay()? {
+            return Ok(replay);
+        }
+        let result = self.do_grant_access(request).await;
+        self.idempotency.finish(claim, &result).await;
+        result
+    }
+
+    async fn share_bookmark(
+        &self,
+        request: Request<ShareBookmarkRequest>,
+    ) -> Result<Response<ShareBookmarkResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        if req.bookmark_id.is_empty() || req.user_id.is_empty() {
+            return Err(Status::invalid_argument("bookmark_id and user_id are required"));
+        }
+        if req.bookmark_id == WILDCARD_RESOURCE {
+            return Err(Status::invalid_argument("bookmark_id must name a single bookmark"));
+        }
+        let relation = match req.relation {
+            0 => Relation::Viewer,
+            v => Relation::from_proto(v)
+                .ok_or_else(|| Status::invalid_argument("invalid relation"))?,
+        };
+        if relation == Relation::Owner {
+            return Err(Status::invalid_argument(
+                "OWNER cannot be shared; use TransferOwnership",
+            ));
+        }
+        let expires_at = match req.expires_at {
+            Some(ts) => chrono::DateTime::from_timestamp(ts.seconds, ts.nanos as u32)
+                .ok_or_else(|| Status::invalid_argument("invalid expires_at"))?,
+            None => Utc::now() + self.share_expiry,
+        };
+        if expires_at <= Utc::now() {
+            return Err(Status::invalid_argument("expires_at must be in the future"));
+        }
+
+        self.checker
+            .can_share(ctx.tenant_id, &ctx.user_id, &req.bookmark_id, &ctx.role_ids)
+            .await?;
+        // Without a user directory the recipient cannot be verified up front.
+        if self.admin_client.is_some() {
+            self.ensure_user_in_tenant(ctx.tenant_id, &req.user_id)
+                .await?;
+        }
+
+        let row = self
+            .checker
+            .engine()
+            .store()
+            .create_permission(
+                ctx.tenant_id,
+                ResourceType::Bookmark,
+                &req.bookmark_id,
+                relation,
+                SubjectType::User,
+                &req.user_id,
+                ctx.user_id.parse::<i32>().ok(),
+                Some(expires_at),
+            )
+            .await
+            .map_err(ServiceError::database)?;
+
+        let notification = self
+            .shares
+            .store()
+            .create(
+                &NewShareNotification {
+                    tenant_id: ctx.tenant_id,
+                    permission_id: row.id,
+                    bookmark_id: req.bookmark_id.clone(),
+                    recipient_id: req.user_id.clone(),
+                    shared_by: ctx.user_id.clone(),
+                    relation: relation.as_str().to_string(),
+                    message: req.message,
+                    expires_at,
+                },
+                self.shares.lease_until(),
+            )
+            .await
+            .map_err(ServiceError::database)?;
+        let notification_id = notification.id as u32;
+        self.shares.deliver(notification);
+
+        self.events
+            .publish(Event::permission_granted(&ctx.user_id, &row));
+
+        Ok(Response::new(ShareBookmarkResponse {
+            permission: Some(row_to_proto(row)),
+            notification_id,
+        }))
+    }
+
+    async fn grant_federated_access(
+        &self,
+        request: Request<GrantFederatedAccessRequest>,
+    ) -> Result<Response<GrantAccessResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        if !self.checker.engine().federation() {
+            return Err(Status::failed_precondition(
+                "cross-tenant sharing is disabled",
+            ));
+        }
+        if !ctx.is_platform_admin() {
+            return Err(Status::permission_denied("platform admin role required"));
+        }
+        if req.bookmark_id.is_empty() || req.user_id.is_empty() {
+            return Err(Status::invalid_argument(
+                "bookmark_id and user_id are required",
+            ));
+        }
+        if req.bookmark_id == WILDCARD_RESOURCE {
+            return Err(Status::invalid_argument(
+                "bookmark_id must name a single bookmark",
+            ));
+        }
+        if req.tenant_id == 0 || req.subject_tenant_id == 0 {
+            return Err(Status::invalid_argument(
+                "tenant_id and subject_tenant_id are required",
+            ));
+        }
+        if req.tenant_id == req.subject_tenant_id {
+            return Err(Status::invalid_argument(
+                "subject_tenant_id must differ from tenant_id; use ShareBookmark",
+            ));
+        }
+        let expires_at = req
+            .expires_at
+            .as_ref()
+            .map(timestamp_to_datetime)
+            .transpose()?;
+        if expires_at.is_some_and(|ts| ts <= Utc::now()) {
+            return Err(Status::invalid_argument("expires_at must be in the future"));
+        }
+        let tenant_id = req.tenant_id as i32;
+        let subject_tenant_id = req.subject_tenant_id as i32;
+
+        let store = self.checker.engine().store();
+        // Every bookmark has an owner tuple in its own tenant.
+        let owned = store
+            .get_direct_permissions(tenant_id, ResourceType::Bookmark, &req.bookmark_id)
+            .await
+            .map_err(ServiceError::database)?
+            .iter()
+            .any(|row| row.relation == Relation::Owner.as_str() && row.subject_tenant_id.is_none());
+        if !owned {
+            return Err(Status::not_found("bookmark not found"));
+        }
+        // Without a user directory the recipient cannot be verified up front.
+        if self.admin_client.is_some() {
+            self.ensure_user_in_tenant(subject_tenant_id, &req.user_id)
+                .await?;
+        }
+
+        let row = store
+            .grant_federated(
+                tenant_id,
+                ResourceType::Bookmark,
+                &req.bookmark_id,
+                subject_tenant_id,
+                &req.user_id,
+                ctx.user_id.parse::<i32>().ok(),
+                expires_at,
+            )
+            .await
+            .map_err(ServiceError::database)?;
+
+        tracing::info!(
+            tenant_id,
+            subject_tenant_id,
+            bookmark_id = %req.bookmark_id,
+            user_id = %req.user_id,
+            granted_by = %ctx.user_id,
+            "granted federated bookmark access"
+        );
+        self.events
+            .publish(Event::permission_granted(&ctx.user_id, &row));
 
         Ok(Response::new(GrantAccessResponse {
             permission: Some(row_to_proto(row)),
@@ -120,11 +463,156 @@ impl BookmarkPermissionService for PermissionServiceImpl {
                 &req.subject_id,
             )
             .await
-            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+            .map_err(ServiceError::database)?;
+
+        self.events.publish(Event::permission_revoked(
+            ctx.tenant_id,
+            &ctx.user_id,
+            resource_type.as_str(),
+            &req.resource_id,
+            relation.map(|r| r.as_str()),
+            subject_type.as_str(),
+            &req.subject_id,
+        ));
 
         Ok(Response::new(()))
     }
 
+    async fn batch_grant_access(
+        &self,
+        request: Request<BatchGrantAccessRequest>,
+    ) -> Result<Response<BatchAccessResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+        check_batch_size(req.grants.len())?;
+
+        let mut results = Vec::with_capacity(req.grants.len());
+        let mut indexes = Vec::new();
+        let mut tuples = Vec::new();
+        let mut seen = HashSet::new();
+
+        for (index, item) in req.grants.into_iter().enumerate() {
+            let tuple = match self.authorize_grant(&ctx, item).await {
+                Ok(tuple) => tuple,
+                Err(status) => {
+                    results.push(access_error(index, status));
+                    continue;
+                }
+            };
+            let key = tuple_key(
+                tuple.resource_type.as_str(),
+                &tuple.resource_id,
+                tuple.relation.as_str(),
+                tuple.subject_type.as_str(),
+                &tuple.subject_id,
+            );
+            if !seen.insert(key) {
+                results.push(access_error(
+                    index,
+                    Status::invalid_argument("duplicate tuple in batch"),
+                ));
+                continue;
+            }
+            indexes.push(index);
+            tuples.push(tuple);
+        }
+
+        let rows = self
+            .checker
+            .engine()
+            .store()
+            .batch_grant(ctx.tenant_id, &tuples, ctx.user_id.parse::<i32>().ok())
+            .await
+            .map_err(ServiceError::database)?;
+        let mut by_key: HashMap<_, _> = rows
+            .into_iter()
+            .map(|row| {
+                let key = tuple_key(
+                    &row.resource_type,
+                    &row.resource_id,
+                    &row.relation,
+                    &row.subject_type,
+                    &row.subject_id,
+                );
+                (key, row)
+            })
+            .collect();
+
+        for (index, tuple) in indexes.into_iter().zip(&tuples) {
+            let key = tuple_key(
+                tuple.resource_type.as_str(),
+                &tuple.resource_id,
+                tuple.relation.as_str(),
+                tuple.subject_type.as_str(),
+                &tuple.subject_id,
+            );
+            results.push(match by_key.remove(&key) {
+                Some(row) => {
+                    self.events
+                        .publish(Event::permission_granted(&ctx.user_id, &row));
+                    BatchAccessResult {
+                        index: index as u32,
+                        permission: Some(row_to_proto(row)),
+                        ..Default::default()
+                    }
+                }
+                None => access_error(index, Status::internal("tuple was not written")),
+            });
+        }
+
+        Ok(Response::new(batch_access_response(results)))
+    }
+
+    async fn batch_revoke_access(
+        &self,
+        request: Request<BatchRevokeAccessRequest>,
+    ) -> Result<Response<BatchAccessResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+        check_batch_size(req.revokes.len())?;
+
+        let mut results = Vec::with_capacity(req.revokes.len());
+        let mut indexes = Vec::new();
+        let mut tuples = Vec::new();
+
+        for (index, item) in req.revokes.into_iter().enumerate() {
+            match self.authorize_revoke(&ctx, item).await {
+                Ok(tuple) => {
+                    indexes.push(index);
+                    tuples.push(tuple);
+                }
+                Err(status) => results.push(access_error(index, status)),
+            }
+        }
+
+        let removed = self
+            .checker
+            .engine()
+            .store()
+            .batch_revoke(ctx.tenant_id, &tuples)
+            .await
+            .map_err(ServiceError::database)?;
+
+        for ((index, tuple), revoked) in indexes.into_iter().zip(&tuples).zip(removed) {
+            self.events.publish(Event::permission_revoked(
+                ctx.tenant_id,
+                &ctx.user_id,
+                tuple.resource_type.as_str(),
+                &tuple.resource_id,
+                tuple.relation.map(|r| r.as_str()),
+                tuple.subject_type.as_str(),
+                &tuple.subject_id,
+            ));
+            results.push(BatchAccessResult {
+                index: index as u32,
+                revoked: revoked as u32,
+                ..Default::default()
+            });
+        }
+
+        Ok(Response::new(batch_access_response(results)))
+    }
+
     async fn list_permissions(
         &self,
         request: Request<ListPermissionsRequest>,
@@ -135,9 +623,16 @@ impl BookmarkPermissionService for PermissionServiceImpl {
         let resource_type = req.resource_type.and_then(ResourceType::from_proto);
         let subject_type = req.subject_type.and_then(SubjectType::from_proto);
         let page = req.page.unwrap_or(1).max(1);
-        let page_size = req.page_size.unwrap_or(20).min(100);
+        let page_size = req.page_size.unwrap_or(20).clamp(1, 100);
+        let after = page_token::decode(&req.page_token)?
+            .map(|c| {
+                c.id.parse::<i32>()
+                    .map(|id| (c.create_time, id))
+                    .map_err(|_| Status::invalid_argument("invalid page_token"))
+            })
+            .transpose()?;
 
-        let (rows, total) = self
+        let (mut rows, total) = self
             .checker
             .engine()
             .store()
@@ -147,17 +642,28 @@ impl BookmarkPermissionService for PermissionServiceImpl {
                 req.resource_id.as_deref(),
                 subject_type,
                 req.subject_id.as_deref(),
+                after,
                 page,
                 page_size,
             )
             .await
-            .map_err(|e| Status::internal(format!("database error: {e}")))?;
+            .map_err(ServiceError::database)?;
+
+        let next_page_token = if rows.len() > page_size as usize {
+            rows.truncate(page_size as usize);
+            rows.last()
+                .map(|r| page_token::encode(r.create_time, &r.id.to_string()))
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
 
         let permissions: Vec<PermissionTuple> = rows.into_iter().map(row_to_proto).collect();
 
         Ok(Response::new(ListPermissionsResponse {
             permissions,
             total: total as u32,
+            next_page_token,
         }))
     }
 
@@ -170,22 +676,57 @@ impl BookmarkPermissionService for PermissionServiceImpl {
 
         let resource_type = ResourceType::from_proto(req.resource_type)
             .ok_or_else(|| Status::invalid_argument("invalid resource_type"))?;
-        let permission = Permission::from_proto(req.permission)
-            .ok_or_else(|| Status::invalid_argument("invalid permission"))?;
+        // `permission` plus any extra `permissions`, deduplicated in order.
+        let mut permissions = Vec::new();
+        for value in std::iter::once(req.permission)
+            .filter(|p| *p != 0)
+            .chain(req.permissions.iter().copied())
+        {
+            let permission = Permission::from_proto(value)
+                .ok_or_else(|| Status::invalid_argument("invalid permission"))?;
+            if !permissions.contains(&permission) {
+                permissions.push(permission);
+            }
+        }
+        if permissions.is_empty() {
+            return Err(Status::invalid_argument("invalid permission"));
+        }
 
-        let check_ctx = crate::authz::engine::CheckContext {
-            tenant_id: ctx.tenant_id,
-            user_id: req.user_id.clone(),
-            resource_type,
-            resource_id: req.resource_id.clone(),
-            permission,
-        };
+        let engine = self.checker.engine();
+        let mut results = Vec::with_capacity(permissions.len());
+        for permission in permissions {
+            let check_ctx = crate::authz::engine::CheckContext {
+                tenant_id: ctx.tenant_id,
+                user_id: req.user_id.clone(),
+                resource_type,
+                resource_id: req.resource_id.clone(),
+                permission,
+            };
+            let (result, trace) = if req.explain {
+                engine.explain(&check_ctx, &ctx.role_ids).await
+            } else {
+                (engine.check(&check_ctx, &ctx.role_ids).await, Vec::new())
+            };
+            results.push(PermissionDecision {
+                permission: permission.to_proto(),
+                allowed: result.allowed,
+                reason: result.reason,
+                trace: trace.into_iter().map(step_to_proto).collect(),
+            });
+        }
 
-        let result = self.checker.engine().check(&check_ctx, &ctx.role_ids).await;
+        let allowed = results.iter().all(|r| r.allowed);
+        let reason = results
+            .iter()
+            .find(|r| !r.allowed)
+            .unwrap_or(&results[0])
+            .reason
+            .clone();
 
         Ok(Response::new(CheckAccessResponse {
-            allowed: result.allowed,
-            reason: Some(result.reason),
+            allowed,
+            reason: Some(reason),
+            results,
         }))
     }
 
@@ -203,7 +744,7 @@ impl BookmarkPermissionService for PermissionServiceImpl {
             .checker
             .list_accessible_bookmarks(ctx.tenant_id, &req.user_id, &ctx.role_ids)
             .await
-            .map_err(|e| Status::internal(format!("authz error: {e}")))?;
+            .map_err(ServiceError::authz)?;
 
         Ok(Response::new(ListAccessibleResourcesResponse {
             total: ids.len() as u32,
@@ -233,9 +774,385 @@ impl BookmarkPermissionService for PermissionServiceImpl {
             highest_relation: highest_relation.map(|r| r.to_proto()).unwrap_or(0),
         }))
     }
+
+    async fn transfer_ownership(
+        &self,
+        request: Request<TransferOwnershipRequest>,
+    ) -> Result<Response<TransferOwnershipResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let resource_type = ResourceType::from_proto(req.resource_type)
+            .ok_or_else(|| Status::invalid_argument("invalid resource_type"))?;
+        if req.resource_id.is_empty() || req.new_owner_id.is_empty() {
+            return Err(Status::invalid_argument(
+                "resource_id and new_owner_id are required",
+            ));
+        }
+
+        let current_owner = match req.current_owner_id.as_deref() {
+            Some(id) if !id.is_empty() && id != ctx.user_id => {
+                if !ctx.is_tenant_manager() {
+                    return Err(Status::permission_denied(
+                        "only tenant managers can transfer another user's ownership",
+                    ));
+                }
+                id.to_string()
+            }
+            _ => ctx.user_id.clone(),
+        };
+
+        if current_owner == req.new_owner_id {
+            return Err(Status::invalid_argument("new owner is already the owner"));
+        }
+
+        self.ensure_user_in_tenant(ctx.tenant_id, &req.new_owner_id)
+            .await?;
+
+        let owner = self
+            .checker
+            .engine()
+            .store()
+            .transfer_ownership(
+                ctx.tenant_id,
+                resource_type,
+                &req.resource_id,
+                &current_owner,
+                &req.new_owner_id,
+                req.keep_previous_as_editor,
+                ctx.user_id.parse::<i32>().ok(),
+            )
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| {
+                Status::failed_precondition("current owner does not own this resource")
+            })?;
+
+        tracing::info!(
+            resource_id = %req.resource_id,
+            from = %current_owner,
+            to = %req.new_owner_id,
+            by = %ctx.user_id,
+            "ownership transferred"
+        );
+
+        self.events.publish(Event::permission_revoked(
+            ctx.tenant_id,
+            &ctx.user_id,
+            resource_type.as_str(),
+            &req.resource_id,
+            Some(Relation::Owner.as_str()),
+            SubjectType::User.as_str(),
+            &current_owner,
+        ));
+        self.events
+            .publish(Event::permission_granted(&ctx.user_id, &owner));
+
+        Ok(Response::new(TransferOwnershipResponse {
+            owner: Some(row_to_proto(owner)),
+        }))
+    }
+
+    async fn list_subjects(
+        &self,
+        request: Request<ListSubjectsRequest>,
+    ) -> Result<Response<ListSubjectsResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let resource_type = ResourceType::from_proto(req.resource_type)
+            .ok_or_else(|| Status::invalid_argument("invalid resource_type"))?;
+        if req.resource_id.is_empty() {
+            return Err(Status::invalid_argument("resource_id is required"));
+        }
+
+        // Who can access a resource is only visible to those who can share it
+        self.checker
+            .can_share(
+                ctx.tenant_id,
+                &ctx.user_id,
+                &req.resource_id,
+                &ctx.role_ids,
+            )
+            .await?;
+
+        let rows = self
+            .checker
+            .engine()
+            .store()
+            .get_direct_permissions(ctx.tenant_id, resource_type, &req.resource_id)
+            .await
+            .map_err(ServiceError::database)?;
+
+        let now = chrono::Utc::now();
+        let subjects = rows
+            .into_iter()
+            .filter_map(|row| {
+                let expired = row.expires_at.is_some_and(|ts| ts < now);
+                if expired && !req.include_expired {
+                    return None;
+                }
+                let relation = Relation::from_str(&row.relation);
+                let subject_type = SubjectType::from_str(&row.subject_type);
+                Some(SubjectAccess {
+                    subject_type: subject_type.map(|t| t.to_proto()).unwrap_or(0),
+                    subject_id: row.subject_id,
+                    relation: relation.map(|r| r.to_proto()).unwrap_or(0),
+                    permissions: relation
+                        .map(|r| r.granted_permissions().iter().map(|p| p.to_proto()).collect())
+                        .unwrap_or_default(),
+                    inherited: !matches!(subject_type, Some(SubjectType::User)),
+                    expired,
+                    expires_at: row.expires_at.map(|ts| prost_types::Timestamp {
+                        seconds: ts.timestamp(),
+                        nanos: ts.timestamp_subsec_nanos() as i32,
+                    }),
+                    granted_by: row.granted_by.map(|v| v as u32),
+                    create_time: Some(prost_types::Timestamp {
+                        seconds: row.create_time.timestamp(),
+                        nanos: row.create_time.timestamp_subsec_nanos() as i32,
+                    }),
+                    subject_tenant_id: row.subject_tenant_id.map(|v| v as u32),
+                })
+            })
+            .collect();
+
+        Ok(Response::new(ListSubjectsResponse { subjects }))
+    }
+
+    async fn list_resources_for_subject(
+        &self,
+        request: Request<ListResourcesForSubjectRequest>,
+    ) -> Result<Response<ListResourcesForSubjectResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let subject_type = SubjectType::from_proto(req.subject_type)
+            .ok_or_else(|| Status::invalid_argument("invalid subject_type"))?;
+        if req.subject_id.is_empty() {
+            return Err(Status::invalid_argument("subject_id is required"));
+        }
+
+        let page = req.page.unwrap_or(1).max(1);
+        let page_size = req.page_size.unwrap_or(20).min(100);
+
+        // Managers see every grant; everyone else only sees grants on bookmarks they own.
+        let owned_by = if ctx.is_tenant_manager() {
+            None
+        } else {
+            Some(ctx.user_id.as_str())
+        };
+
+        let (rows, total) = self
+            .checker
+            .engine()
+            .store()
+            .list_resources_for_subject(
+                ctx.tenant_id,
+                subject_type,
+                &req.subject_id,
+                owned_by,
+                page,
+                page_size,
+            )
+            .await
+            .map_err(ServiceError::database)?;
+
+        let now = chrono::Utc::now();
+        let resources = rows
+            .into_iter()
+            .map(|r| SubjectResource {
+                resource_type: match r.resource_type.as_str() {
+                    "RESOURCE_TYPE_BOOKMARK" => 1,
+                    _ => 0,
+                },
+                resource_id: r.resource_id,
+                relation: Relation::from_str(&r.relation)
+                    .map(|rel| rel.to_proto())
+                    .unwrap_or(0),
+                title: r.title,
+                url: r.url,
+                expired: r.expires_at.is_some_and(|ts| ts < now),
+                expires_at: r.expires_at.map(|ts| prost_types::Timestamp {
+                    seconds: ts.timestamp(),
+                    nanos: ts.timestamp_subsec_nanos() as i32,
+                }),
+            })
+            .collect();
+
+        Ok(Response::new(ListResourcesForSubjectResponse {
+            resources,
+            total: total as u32,
+        }))
+    }
+
+    async fn add_group_member(
+        &self,
+        request: Request<AddGroupMemberRequest>,
+    ) -> Result<Response<GroupMember>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        if !ctx.is_tenant_manager() {
+            return Err(Status::permission_denied(
+                "only tenant managers can manage groups",
+            ));
+        }
+        if req.group_id.is_empty() || req.user_id.is_empty() {
+            return Err(Status::invalid_argument("group_id and user_id are required"));
+        }
+
+        self.ensure_user_in_tenant(ctx.tenant_id, &req.user_id)
+            .await?;
+
+        let row = self
+            .checker
+            .engine()
+            .groups()
+            .add_member(
+                ctx.tenant_id,
+                &req.group_id,
+                &req.user_id,
+                ctx.user_id.parse::<i32>().ok(),
+            )
+            .await
+            .map_err(ServiceError::database)?;
+
+        Ok(Response::new(member_to_proto(row)))
+    }
+
+    async fn remove_group_member(
+        &self,
+        request: Request<RemoveGroupMemberRequest>,
+    ) -> Result<Response<()>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        if !ctx.is_tenant_manager() {
+            return Err(Status::permission_denied(
+                "only tenant managers can manage groups",
+            ));
+        }
+
+        self.checker
+            .engine()
+            .groups()
+            .remove_member(ctx.tenant_id, &req.group_id, &req.user_id)
+            .await
+            .map_err(ServiceError::database)?;
+
+        Ok(Response::new(()))
+    }
+
+    async fn list_group_members(
+        &self,
+        request: Request<ListGroupMembersRequest>,
+    ) -> Result<Response<ListGroupMembersResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let rows = self
+            .checker
+            .engine()
+            .groups()
+            .list_members(ctx.tenant_id, &req.group_id)
+            .await
+            .map_err(ServiceError::database)?;
+
+        // Members may see their own group; everyone else needs to be a manager.
+        if !ctx.is_tenant_manager() && !rows.iter().any(|r| r.user_id == ctx.user_id) {
+            return Err(Status::permission_denied("not a member of this group"));
+        }
+
+        Ok(Response::new(ListGroupMembersResponse {
+            members: rows.into_iter().map(member_to_proto).collect(),
+        }))
+    }
+}
+
+fn check_batch_size(len: usize) -> Result<(), Status> {
+    if len == 0 {
+        return Err(Status::invalid_argument("at least one item is required"));
+    }
+    if len > MAX_BATCH_TUPLES {
+        return Err(Status::invalid_argument(format!(
+            "at most {MAX_BATCH_TUPLES} items per batch"
+        )));
+    }
+    Ok(())
+}
+
+/// Identifies a tuple within a batch, in its stored string form.
+fn tuple_key(
+    resource_type: &str,
+    resource_id: &str,
+    relation: &str,
+    subject_type: &str,
+    subject_id: &str,
+) -> (String, String, String, String, String) {
+    (
+        resource_type.to_string(),
+        resource_id.to_string(),
+        relation.to_string(),
+        subject_type.to_string(),
+        subject_id.to_string(),
+    )
+}
+
+fn access_error(index: usize, status: Status) -> BatchAccessResult {
+    BatchAccessResult {
+        index: index as u32,
+        code: status.code() as i32,
+        message: status.message().to_string(),
+        ..Default::default()
+    }
+}
+
+/// Order results by request index and tally successes and failures.
+fn batch_access_response(mut results: Vec<BatchAccessResult>) -> BatchAccessResponse {
+    results.sort_by_key(|r| r.index);
+    let failed = results.iter().filter(|r| r.code != 0).count() as u32;
+    BatchAccessResponse {
+        succeeded: results.len() as u32 - failed,
+        failed,
+        results,
+    }
+}
+
+fn member_to_proto(row: GroupMemberRow) -> GroupMember {
+    GroupMember {
+        group_id: row.group_id,
+        user_id: row.user_id,
+        added_by: row.added_by.map(|v| v as u32),
+        create_time: Some(prost_types::Timestamp {
+            seconds: row.create_time.timestamp(),
+            nanos: row.create_time.timestamp_subsec_nanos() as i32,
+        }),
+    }
+}
+
+fn step_to_proto(step: TraceStep) -> DecisionStep {
+    let outcome = match step.outcome {
+        StepOutcome::NoTuple => DecisionOutcome::NoTuple,
+        StepOutcome::Granted => DecisionOutcome::Granted,
+        StepOutcome::Insufficient => DecisionOutcome::Insufficient,
+        StepOutcome::Expired => DecisionOutcome::Expired,
+        StepOutcome::LookupFailed => DecisionOutcome::LookupFailed,
+    };
+    DecisionStep {
+        subject_type: step.subject_type.to_proto(),
+        subject_id: step.subject_id,
+        resource_id: step.resource_id,
+        relation: step.relation.map(Relation::to_proto),
+        expires_at: step.expires_at.map(|ts| prost_types::Timestamp {
+            seconds: ts.timestamp(),
+            nanos: ts.timestamp_subsec_nanos() as i32,
+        }),
+        outcome: outcome as i32,
+    }
 }
 
-fn row_to_proto(row: PermissionRow) -> PermissionTuple {
+pub(crate) fn row_to_proto(row: PermissionRow) -> PermissionTuple {
     PermissionTuple {
         id: row.id as u32,
         tenant_id: row.tenant_id as u32,
@@ -260,6 +1177,7 @@ fn row_to_proto(row: PermissionRow) -> PermissionTuple {
             "SUBJECT_TYPE_USER" => 1,
             "SUBJECT_TYPE_ROLE" => 2,
             "SUBJECT_TYPE_TENANT" => 3,
+            "SUBJECT_TYPE_GROUP" => 4,
             _ => 0,
         },
         subject_id: row.subject_id,
@@ -272,5 +1190,6 @@ fn row_to_proto(row: PermissionRow) -> PermissionTuple {
             seconds: row.create_time.timestamp(),
             nanos: row.create_time.timestamp_subsec_nanos() as i32,
         }),
+        subject_tenant_id: row.subject_tenant_id.map(|v| v as u32),
     }
 }
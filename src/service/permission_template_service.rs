@@ -0,0 +1,309 @@
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use crate::authz::checker::Checker;
+use crate::authz::relations::{Permission, Relation, ResourceType, SubjectType};
+use crate::data::permission_template_repo::{
+    PermissionTemplateRow, PermissionTemplateStore, TemplateGrant,
+};
+use crate::events::{Event, EventPublisher};
+use crate::service::bookmark_service::proto::{
+    bookmark_permission_template_service_server::BookmarkPermissionTemplateService,
+    ApplyTemplateRequest, ApplyTemplateResponse, CreatePermissionTemplateRequest,
+    DeletePermissionTemplateRequest, ListPermissionTemplatesRequest,
+    ListPermissionTemplatesResponse, PermissionTemplate, TemplateGrant as ProtoTemplateGrant,
+    UpdatePermissionTemplateRequest,
+};
+use crate::service::bookmark_service::timestamp_to_datetime;
+use crate::service::context_helper::{extract_context, RequestContext};
+use crate::service::error::ServiceError;
+use crate::service::permission_service::row_to_proto;
+
+const MAX_NAME_LEN: usize = 255;
+const MAX_GRANTS: usize = 50;
+
+pub struct PermissionTemplateServiceImpl {
+    templates: Arc<dyn PermissionTemplateStore>,
+    checker: Checker,
+    events: EventPublisher,
+}
+
+impl PermissionTemplateServiceImpl {
+    pub fn new(
+        templates: Arc<dyn PermissionTemplateStore>,
+        checker: Checker,
+        events: EventPublisher,
+    ) -> Self {
+        Self {
+            templates,
+            checker,
+            events,
+        }
+    }
+}
+
+fn require_tenant_manager(ctx: &RequestContext) -> Result<(), Status> {
+    if !ctx.is_tenant_manager() {
+        return Err(Status::permission_denied("tenant manager role required"));
+    }
+    Ok(())
+}
+
+/// Validate grants from a request and convert them to their stored form.
+fn parse_grants(grants: &[ProtoTemplateGrant]) -> Result<Vec<TemplateGrant>, Status> {
+    if grants.is_empty() {
+        return Err(Status::invalid_argument("at least one grant is required"));
+    }
+    if grants.len() > MAX_GRANTS {
+        return Err(Status::invalid_argument(format!(
+            "a template holds at most {MAX_GRANTS} grants"
+        )));
+    }
+    grants
+        .iter()
+        .map(|g| {
+            let relation = Relation::from_proto(g.relation)
+                .ok_or_else(|| Status::invalid_argument("invalid relation"))?;
+            let subject_type = SubjectType::from_proto(g.subject_type)
+                .ok_or_else(|| Status::invalid_argument("invalid subject_type"))?;
+            if g.subject_id.is_empty() {
+                return Err(Status::invalid_argument("subject_id is required"));
+            }
+            Ok(TemplateGrant {
+                relation: relation.as_str().to_string(),
+                subject_type: subject_type.as_str().to_string(),
+                subject_id: g.subject_id.clone(),
+            })
+        })
+        .collect()
+}
+
+fn parse_template_id(id: u32) -> Result<i32, Status> {
+    i32::try_from(id)
+        .ok()
+        .filter(|id| *id > 0)
+        .ok_or_else(|| Status::invalid_argument("invalid template id"))
+}
+
+#[tonic::async_trait]
+impl BookmarkPermissionTemplateService for PermissionTemplateServiceImpl {
+    async fn create_permission_template(
+        &self,
+        request: Request<CreatePermissionTemplateRequest>,
+    ) -> Result<Response<PermissionTemplate>, Status> {
+        let ctx = extract_context(&request)?;
+        require_tenant_manager(&ctx)?;
+        let req = request.into_inner();
+
+        let name = req.name.trim();
+        if name.is_empty() {
+            return Err(Status::invalid_argument("name is required"));
+        }
+        if name.len() > MAX_NAME_LEN {
+            return Err(Status::invalid_argument("name is too long"));
+        }
+        let grants = parse_grants(&req.grants)?;
+
+        let row = self
+            .templates
+            .create(ctx.tenant_id, name, &req.description, &grants, &ctx.user_id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| {
+                Status::already_exists(format!("a template named {name:?} already exists"))
+            })?;
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            template_id = row.id,
+            by = %ctx.user_id,
+            "permission template created"
+        );
+
+        Ok(Response::new(template_to_proto(row)))
+    }
+
+    async fn list_permission_templates(
+        &self,
+        request: Request<ListPermissionTemplatesRequest>,
+    ) -> Result<Response<ListPermissionTemplatesResponse>, Status> {
+        let ctx = extract_context(&request)?;
+
+        let rows = self
+            .templates
+            .list(ctx.tenant_id)
+            .await
+            .map_err(ServiceError::database)?;
+
+        Ok(Response::new(ListPermissionTemplatesResponse {
+            templates: rows.into_iter().map(template_to_proto).collect(),
+        }))
+    }
+
+    async fn update_permission_template(
+        &self,
+        request: Request<UpdatePermissionTemplateRequest>,
+    ) -> Result<Response<PermissionTemplate>, Status> {
+        let ctx = extract_context(&request)?;
+        require_tenant_manager(&ctx)?;
+        let req = request.into_inner();
+
+        let id = parse_template_id(req.id)?;
+        let grants = parse_grants(&req.grants)?;
+
+        let row = self
+            .templates
+            .update(ctx.tenant_id, id, &req.description, &grants)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("permission template not found"))?;
+
+        Ok(Response::new(template_to_proto(row)))
+    }
+
+    async fn delete_permission_template(
+        &self,
+        request: Request<DeletePermissionTemplateRequest>,
+    ) -> Result<Response<()>, Status> {
+        let ctx = extract_context(&request)?;
+        require_tenant_manager(&ctx)?;
+        let req = request.into_inner();
+
+        let id = parse_template_id(req.id)?;
+        let deleted = self
+            .templates
+            .delete(ctx.tenant_id, id)
+            .await
+            .map_err(ServiceError::database)?;
+        if !deleted {
+            return Err(Status::not_found("permission template not found"));
+        }
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            template_id = id,
+            by = %ctx.user_id,
+            "permission template deleted"
+        );
+
+        Ok(Response::new(()))
+    }
+
+    async fn apply_template(
+        &self,
+        request: Request<ApplyTemplateRequest>,
+    ) -> Result<Response<ApplyTemplateResponse>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let id = parse_template_id(req.template_id)?;
+        let resource_type = ResourceType::from_proto(req.resource_type)
+            .ok_or_else(|| Status::invalid_argument("invalid resource_type"))?;
+        if req.resource_id.is_empty() {
+            return Err(Status::invalid_argument("resource_id is required"));
+        }
+        let expires_at = req
+            .expires_at
+            .as_ref()
+            .map(timestamp_to_datetime)
+            .transpose()?;
+        if expires_at.is_some_and(|t| t <= chrono::Utc::now()) {
+            return Err(Status::invalid_argument("expires_at must be in the future"));
+        }
+
+        self.checker
+            .require_permission(
+                ctx.tenant_id,
+                &ctx.user_id,
+                resource_type,
+                &req.resource_id,
+                Permission::Share,
+                &ctx.role_ids,
+            )
+            .await?;
+
+        let template = self
+            .templates
+            .get(ctx.tenant_id, id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("permission template not found"))?;
+        let grants = template
+            .grants
+            .0
+            .iter()
+            .map(|g| {
+                let relation = Relation::from_str(&g.relation);
+                let subject_type = SubjectType::from_str(&g.subject_type);
+                relation
+                    .zip(subject_type)
+                    .map(|(r, s)| (r, s, g.subject_id.clone()))
+                    .ok_or_else(|| {
+                        Status::internal(format!("permission template {id} has an invalid grant"))
+                    })
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        let rows = self
+            .checker
+            .engine()
+            .store()
+            .create_permissions(
+                ctx.tenant_id,
+                resource_type,
+                &req.resource_id,
+                &grants,
+                ctx.user_id.parse::<i32>().ok(),
+                expires_at,
+            )
+            .await
+            .map_err(ServiceError::database)?;
+
+        for row in &rows {
+            self.events
+                .publish(Event::permission_granted(&ctx.user_id, row));
+        }
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            template_id = id,
+            resource_id = %req.resource_id,
+            grants = rows.len(),
+            by = %ctx.user_id,
+            "permission template applied"
+        );
+
+        Ok(Response::new(ApplyTemplateResponse {
+            permissions: rows.into_iter().map(row_to_proto).collect(),
+        }))
+    }
+}
+
+fn to_timestamp(ts: chrono::DateTime<chrono::Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: ts.timestamp(),
+        nanos: ts.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn template_to_proto(row: PermissionTemplateRow) -> PermissionTemplate {
+    PermissionTemplate {
+        id: row.id as u32,
+        name: row.name,
+        description: row.description,
+        grants: row
+            .grants
+            .0
+            .into_iter()
+            .map(|g| ProtoTemplateGrant {
+                relation: Relation::from_str(&g.relation).map_or(0, Relation::to_proto),
+                subject_type: SubjectType::from_str(&g.subject_type)
+                    .map_or(0, SubjectType::to_proto),
+                subject_id: g.subject_id,
+            })
+            .collect(),
+        created_by: row.created_by,
+        create_time: Some(to_timestamp(row.create_time)),
+        update_time: Some(to_timestamp(row.update_time)),
+    }
+}
@@ -0,0 +1,173 @@
+//! Fractional ranking: string keys that sort bytewise, where a key can always
+//! be made between any two others, so moving an item rewrites only that item.
+//!
+//! A key is a base-62 fraction (`"V"` is one half) and never ends in the
+//! lowest digit, which leaves room below every key.
+
+const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const BASE: u8 = DIGITS.len() as u8;
+
+/// A key sorting strictly between `lower` and `upper`; `None` leaves that
+/// side open. An `upper` that isn't above `lower` is ignored, since concurrent
+/// moves can leave two neighbours with the same key; the result then only
+/// sorts after `lower`.
+pub fn key_between(lower: Option<&str>, upper: Option<&str>) -> String {
+    let lower = lower.map(decode).unwrap_or_default();
+    let upper = upper.map(decode).filter(|upper| lower < *upper);
+    encode(&midpoint(&lower, upper.as_deref()))
+}
+
+/// `n` ascending keys between `lower` and `upper`, spread by bisection so
+/// they stay short.
+pub fn keys_between(lower: Option<&str>, upper: Option<&str>, n: usize) -> Vec<String> {
+    if n == 0 {
+        return vec![];
+    }
+    let mid = key_between(lower, upper);
+    let below = (n - 1) / 2;
+    let mut keys = keys_between(lower, Some(&mid), below);
+    let above = keys_between(Some(&mid), upper, n - 1 - below);
+    keys.push(mid);
+    keys.extend(above);
+    keys
+}
+
+fn decode(key: &str) -> Vec<u8> {
+    let mut digits: Vec<u8> = key
+        .bytes()
+        .map(|b| DIGITS.iter().position(|d| *d == b).unwrap_or(0) as u8)
+        .collect();
+    // Trailing zeros don't change the fraction.
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    digits
+}
+
+fn encode(digits: &[u8]) -> String {
+    digits.iter().map(|d| DIGITS[*d as usize] as char).collect()
+}
+
+/// Digits of a fraction between `lower` and `upper` (1 when `None`).
+fn midpoint(lower: &[u8], upper: Option<&[u8]>) -> Vec<u8> {
+    if let Some(upper) = upper {
+        let shared = upper
+            .iter()
+            .enumerate()
+            .take_while(|(i, d)| lower.get(*i).copied().unwrap_or(0) == **d)
+            .count();
+        if shared > 0 {
+            let mut digits = upper[..shared].to_vec();
+            let rest = lower.get(shared..).unwrap_or_default();
+            digits.extend(midpoint(rest, Some(&upper[shared..])));
+            return digits;
+        }
+    }
+
+    let low = lower.first().copied().unwrap_or(0);
+    let high = upper.map_or(BASE, |u| u[0]);
+    if high - low > 1 {
+        return vec![(low + high) / 2];
+    }
+    // Adjacent first digits: `upper` with more digits is itself above its
+    // first digit; otherwise keep `low` and split the rest of `lower`.
+    if upper.is_some_and(|u| u.len() > 1) {
+        return vec![high];
+    }
+    let mut digits = vec![low];
+    digits.extend(midpoint(lower.get(1..).unwrap_or_default(), None));
+    digits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn between(lower: Option<&str>, upper: Option<&str>) -> String {
+        let key = key_between(lower, upper);
+        if let Some(lower) = lower {
+            assert!(lower < key.as_str(), "{lower:?} < {key:?}");
+        }
+        if let Some(upper) = upper {
+            assert!(key.as_str() < upper, "{key:?} < {upper:?}");
+        }
+        assert!(!key.ends_with('0'), "{key:?} ends in the lowest digit");
+        key
+    }
+
+    #[test]
+    fn open_bounds() {
+        assert_eq!(between(None, None), "V");
+        between(None, Some("V"));
+        between(Some("V"), None);
+        between(None, Some("1"));
+        between(Some("z"), None);
+        between(Some("zzz"), None);
+        between(None, Some("01"));
+    }
+
+    #[test]
+    fn adjacent_first_digits() {
+        assert_eq!(between(Some("A"), Some("B")), "AV");
+        assert_eq!(between(Some("A"), Some("BV")), "B");
+        between(Some("Az"), Some("B"));
+        between(Some("Azz"), Some("B1"));
+        between(Some("V"), Some("V1"));
+    }
+
+    #[test]
+    fn repeated_inserts_into_the_same_gap() {
+        // Always just above the lower key.
+        let (lower, mut upper) = ("A".to_string(), "B".to_string());
+        for _ in 0..200 {
+            upper = between(Some(&lower), Some(&upper));
+        }
+        // Always just below the upper key.
+        let (mut lower, upper) = ("A".to_string(), "B".to_string());
+        for _ in 0..200 {
+            lower = between(Some(&lower), Some(&upper));
+        }
+        // Always in front of the list.
+        let mut first = "V".to_string();
+        for _ in 0..200 {
+            first = between(None, Some(&first));
+        }
+    }
+
+    #[test]
+    fn colliding_bounds_sort_after_lower() {
+        assert_eq!(key_between(Some("V"), Some("V")), between(Some("V"), None));
+        assert_eq!(
+            key_between(Some("V1"), Some("V")),
+            between(Some("V1"), None)
+        );
+        assert!(key_between(Some("AV"), Some("A0")).as_str() > "AV");
+
+        let keys = keys_between(Some("A"), Some("A"), 5);
+        assert_eq!(keys.len(), 5);
+        assert!(keys.windows(2).all(|w| w[0] < w[1]), "{keys:?}");
+        assert!(keys[0].as_str() > "A", "{keys:?}");
+    }
+
+    #[test]
+    fn keys_between_are_strictly_ascending() {
+        for (lower, upper) in [(None, None), (Some("A"), Some("B")), (Some("z"), None)] {
+            let keys = keys_between(lower, upper, 50);
+            assert_eq!(keys.len(), 50);
+            let mut bounded = Vec::new();
+            bounded.extend(lower.map(str::to_string));
+            bounded.extend(keys);
+            bounded.extend(upper.map(str::to_string));
+            assert!(bounded.windows(2).all(|w| w[0] < w[1]), "{bounded:?}");
+        }
+        assert!(keys_between(None, None, 0).is_empty());
+    }
+
+    #[test]
+    fn midpoint_of_digits() {
+        assert_eq!(midpoint(&[], None), vec![31]);
+        assert_eq!(midpoint(&[10], Some(&[20])), vec![15]);
+        assert_eq!(midpoint(&[10, 5], Some(&[10, 9])), vec![10, 7]);
+        assert_eq!(midpoint(&[10], Some(&[11])), vec![10, 31]);
+    }
+}
@@ -0,0 +1,179 @@
+//! Alerts for subscribed saved searches.
+//!
+//! [`SavedSearchAlerts::spawn`] periodically leases the subscriptions that are
+//! due, runs each search as its owner over the bookmarks created since its
+//! last alert, and hands any matches to the [`ShareNotifier`]. Alerts leave
+//! out role grants: roles only arrive with a request. A search whose
+//! evaluation or delivery fails keeps its window and is retried once its lease
+//! runs out, so no match is skipped.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::authz::checker::Checker;
+use crate::config::{parse_duration, SavedSearchConfig};
+use crate::data::bookmark_repo::BookmarkStore;
+use crate::data::filter_expr::{Cmp, FilterExpr, Predicate, TimeField};
+use crate::data::saved_search_repo::{SavedSearchRow, SavedSearchStore};
+use crate::service::share_notifier::ShareNotifier;
+use crate::shutdown::ShutdownSignal;
+
+/// Subscriptions leased per check.
+const ALERT_BATCH: i64 = 100;
+
+/// New matches of a saved search, for its owner.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedSearchAlert {
+    pub saved_search_id: i32,
+    pub tenant_id: i32,
+    pub user_id: String,
+    pub name: String,
+    /// Up to `max_matches` matches, best ranked first.
+    pub matches: Vec<AlertMatch>,
+    /// Every match created in the window.
+    pub total: i64,
+    /// The window: bookmarks created after `since`, up to `through`.
+    pub since: DateTime<Utc>,
+    pub through: DateTime<Utc>,
+}
+
+/// A bookmark in an alert. URLs are left out since private bookmarks only
+/// store them sealed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertMatch {
+    pub bookmark_id: String,
+    pub title: String,
+}
+
+#[derive(Clone)]
+pub struct SavedSearchAlerts {
+    store: Arc<dyn SavedSearchStore>,
+    bookmarks: Arc<dyn BookmarkStore>,
+    checker: Checker,
+    notifier: Arc<dyn ShareNotifier>,
+    check_interval: Duration,
+    max_matches: u32,
+}
+
+impl SavedSearchAlerts {
+    pub fn new(
+        store: Arc<dyn SavedSearchStore>,
+        bookmarks: Arc<dyn BookmarkStore>,
+        checker: Checker,
+        notifier: Arc<dyn ShareNotifier>,
+        cfg: &SavedSearchConfig,
+    ) -> Self {
+        let check_interval = parse_duration(&cfg.check_interval).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "invalid search.saved.check_interval, using 5m");
+            Duration::from_secs(300)
+        });
+        Self {
+            store,
+            bookmarks,
+            checker,
+            notifier,
+            check_interval,
+            max_matches: cfg.max_matches.clamp(1, 100),
+        }
+    }
+
+    /// Run the search over its window and notify the owner of any matches.
+    async fn evaluate(&self, search: &SavedSearchRow) -> anyhow::Result<()> {
+        let through = Utc::now();
+        let since = search.last_alert_time.unwrap_or(search.create_time);
+        let mut terms = vec![
+            FilterExpr::Match(Predicate::Time(TimeField::Created, Cmp::Gt, since)),
+            FilterExpr::Match(Predicate::Time(TimeField::Created, Cmp::Le, through)),
+        ];
+        if !search.filter.is_empty() {
+            terms.push(FilterExpr::parse(&search.filter).map_err(anyhow::Error::msg)?);
+        }
+        let expr = FilterExpr::And(terms);
+
+        // No request, so no roles: only what is shared with the owner directly.
+        let accessible: Vec<Uuid> = self
+            .checker
+            .list_accessible_bookmarks(search.tenant_id, &search.user_id, &[])
+            .await?
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
+        let (rows, total) = self
+            .bookmarks
+            .search(
+                search.tenant_id,
+                &accessible,
+                &search.query,
+                false,
+                None,
+                Some(&expr),
+                1,
+                self.max_matches,
+            )
+            .await?;
+
+        if total > 0 {
+            let alert = SavedSearchAlert {
+                saved_search_id: search.id,
+                tenant_id: search.tenant_id,
+                user_id: search.user_id.clone(),
+                name: search.name.clone(),
+                matches: rows
+                    .into_iter()
+                    .map(|r| AlertMatch {
+                        bookmark_id: r.bookmark.id.to_string(),
+                        title: r.bookmark.title,
+                    })
+                    .collect(),
+                total,
+                since,
+                through,
+            };
+            self.notifier.notify_saved_search(&alert).await?;
+            tracing::debug!(id = search.id, total, "saved search alert sent");
+        }
+
+        let interval = chrono::Duration::seconds(search.alert_interval_secs.unwrap_or_default());
+        self.store
+            .mark_alerted(search.id, through, through + interval)
+            .await
+    }
+
+    /// Periodically evaluate the subscriptions that are due.
+    pub fn spawn(self, mut shutdown: ShutdownSignal) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.check_interval);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = shutdown.recv() => break,
+                }
+                let lease_until = Utc::now()
+                    + chrono::Duration::from_std(self.check_interval).unwrap_or_default();
+                let due = match self.store.claim_due(ALERT_BATCH, lease_until).await {
+                    Ok(due) => due,
+                    Err(e) => {
+                        tracing::error!(error = %e, "failed to load due saved search alerts");
+                        continue;
+                    }
+                };
+                for search in &due {
+                    if let Err(e) = self.evaluate(search).await {
+                        tracing::warn!(
+                            id = search.id,
+                            tenant_id = search.tenant_id,
+                            error = %e,
+                            "saved search alert failed"
+                        );
+                    }
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,229 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tonic::{Request, Response, Status};
+
+use crate::data::saved_search_repo::{NewSavedSearch, SavedSearchRow, SavedSearchStore};
+use crate::service::bookmark_service::filter_expr;
+use crate::service::bookmark_service::proto::{
+    saved_search_service_server::SavedSearchService, CreateSavedSearchRequest,
+    DeleteSavedSearchRequest, ListSavedSearchesRequest, ListSavedSearchesResponse, SavedSearch,
+    SubscribeSavedSearchRequest, UnsubscribeSavedSearchRequest,
+};
+use crate::service::context_helper::extract_context;
+use crate::service::error::ServiceError;
+
+const MAX_NAME_LEN: usize = 255;
+const MAX_QUERY_LEN: usize = 1024;
+
+pub struct SavedSearchServiceImpl {
+    store: Arc<dyn SavedSearchStore>,
+    max_per_user: usize,
+    min_alert_interval: Duration,
+    default_alert_interval: Duration,
+}
+
+impl SavedSearchServiceImpl {
+    pub fn new(
+        store: Arc<dyn SavedSearchStore>,
+        max_per_user: usize,
+        min_alert_interval: Duration,
+        default_alert_interval: Duration,
+    ) -> Self {
+        Self {
+            store,
+            max_per_user,
+            min_alert_interval,
+            default_alert_interval,
+        }
+    }
+
+    /// Seconds between alerts for a subscribe request, no shorter than the
+    /// configured minimum.
+    fn alert_interval(&self, requested: Option<u32>) -> Result<i64, Status> {
+        let interval = requested.map_or(self.default_alert_interval, |secs| {
+            Duration::from_secs(secs.into())
+        });
+        if interval < self.min_alert_interval {
+            return Err(Status::invalid_argument(format!(
+                "alert_interval_seconds must be at least {}",
+                self.min_alert_interval.as_secs()
+            )));
+        }
+        Ok(interval.as_secs() as i64)
+    }
+}
+
+fn parse_saved_search_id(id: u32) -> Result<i32, Status> {
+    i32::try_from(id)
+        .ok()
+        .filter(|id| *id > 0)
+        .ok_or_else(|| Status::invalid_argument("invalid saved search id"))
+}
+
+#[tonic::async_trait]
+impl SavedSearchService for SavedSearchServiceImpl {
+    async fn create_saved_search(
+        &self,
+        request: Request<CreateSavedSearchRequest>,
+    ) -> Result<Response<SavedSearch>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let name = req.name.trim();
+        if name.is_empty() {
+            return Err(Status::invalid_argument("name is required"));
+        }
+        if name.len() > MAX_NAME_LEN {
+            return Err(Status::invalid_argument("name is too long"));
+        }
+        let query = req.query.trim();
+        if query.is_empty() {
+            return Err(Status::invalid_argument("query is required"));
+        }
+        if query.len() > MAX_QUERY_LEN {
+            return Err(Status::invalid_argument("query is too long"));
+        }
+        let filter = req.filter.trim();
+        filter_expr(filter)?;
+
+        let existing = self
+            .store
+            .list(ctx.tenant_id, &ctx.user_id)
+            .await
+            .map_err(ServiceError::database)?;
+        if existing.len() >= self.max_per_user {
+            return Err(Status::failed_precondition(format!(
+                "a user keeps at most {} saved searches",
+                self.max_per_user
+            )));
+        }
+
+        let row = self
+            .store
+            .create(&NewSavedSearch {
+                tenant_id: ctx.tenant_id,
+                user_id: ctx.user_id.clone(),
+                name: name.to_string(),
+                query: query.to_string(),
+                filter: filter.to_string(),
+            })
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| {
+                Status::already_exists(format!("saved search {name:?} already exists"))
+            })?;
+
+        Ok(Response::new(saved_search_to_proto(row)))
+    }
+
+    async fn list_saved_searches(
+        &self,
+        request: Request<ListSavedSearchesRequest>,
+    ) -> Result<Response<ListSavedSearchesResponse>, Status> {
+        let ctx = extract_context(&request)?;
+
+        let rows = self
+            .store
+            .list(ctx.tenant_id, &ctx.user_id)
+            .await
+            .map_err(ServiceError::database)?;
+
+        Ok(Response::new(ListSavedSearchesResponse {
+            saved_searches: rows.into_iter().map(saved_search_to_proto).collect(),
+        }))
+    }
+
+    async fn delete_saved_search(
+        &self,
+        request: Request<DeleteSavedSearchRequest>,
+    ) -> Result<Response<()>, Status> {
+        let ctx = extract_context(&request)?;
+        let id = parse_saved_search_id(request.into_inner().id)?;
+
+        let deleted = self
+            .store
+            .delete(ctx.tenant_id, &ctx.user_id, id)
+            .await
+            .map_err(ServiceError::database)?;
+        if !deleted {
+            return Err(Status::not_found("saved search not found"));
+        }
+
+        Ok(Response::new(()))
+    }
+
+    async fn subscribe_saved_search(
+        &self,
+        request: Request<SubscribeSavedSearchRequest>,
+    ) -> Result<Response<SavedSearch>, Status> {
+        let ctx = extract_context(&request)?;
+        let req = request.into_inner();
+
+        let id = parse_saved_search_id(req.id)?;
+        let interval = self.alert_interval(req.alert_interval_seconds)?;
+
+        let row = self
+            .store
+            .subscribe(ctx.tenant_id, &ctx.user_id, id, interval)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("saved search not found"))?;
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            saved_search_id = id,
+            interval_secs = interval,
+            by = %ctx.user_id,
+            "saved search subscribed"
+        );
+
+        Ok(Response::new(saved_search_to_proto(row)))
+    }
+
+    async fn unsubscribe_saved_search(
+        &self,
+        request: Request<UnsubscribeSavedSearchRequest>,
+    ) -> Result<Response<SavedSearch>, Status> {
+        let ctx = extract_context(&request)?;
+        let id = parse_saved_search_id(request.into_inner().id)?;
+
+        let row = self
+            .store
+            .unsubscribe(ctx.tenant_id, &ctx.user_id, id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("saved search not found"))?;
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            saved_search_id = id,
+            by = %ctx.user_id,
+            "saved search unsubscribed"
+        );
+
+        Ok(Response::new(saved_search_to_proto(row)))
+    }
+}
+
+fn to_timestamp(ts: chrono::DateTime<chrono::Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: ts.timestamp(),
+        nanos: ts.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn saved_search_to_proto(row: SavedSearchRow) -> SavedSearch {
+    SavedSearch {
+        id: row.id as u32,
+        name: row.name,
+        query: row.query,
+        filter: row.filter,
+        subscribed: row.alert_interval_secs.is_some(),
+        alert_interval_seconds: row.alert_interval_secs.unwrap_or_default() as u32,
+        next_alert_time: row.next_alert_at.map(to_timestamp),
+        last_alert_time: row.last_alert_time.map(to_timestamp),
+        create_time: Some(to_timestamp(row.create_time)),
+        update_time: Some(to_timestamp(row.update_time)),
+    }
+}
@@ -0,0 +1,322 @@
+//! Telling users a bookmark was shared with them.
+//!
+//! `ShareBookmark` records a pending notification next to the grant and hands
+//! it to [`ShareNotifications::deliver`]. Deliveries that fail are retried by
+//! [`ShareNotifications::spawn_retry`] until `max_attempts` is reached.
+//!
+//! The same notifiers tell bookmark owners about access requests; those are
+//! sent once, without retries, since the request stays listed until resolved.
+//! They also deliver saved search alerts, which the alert job retries itself.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::client::admin_client::AdminClient;
+use crate::config::{parse_duration, NotifierConfig};
+use crate::data::access_request_repo::AccessRequestRow;
+use crate::data::share_notification_repo::{ShareNotificationRow, ShareNotificationStore};
+use crate::service::saved_search_alerts::SavedSearchAlert;
+use crate::shutdown::ShutdownSignal;
+
+/// Notifications leased per retry sweep.
+const RETRY_BATCH: i64 = 100;
+
+/// Delivers a share notification to its recipient.
+#[tonic::async_trait]
+pub trait ShareNotifier: Send + Sync {
+    async fn notify(&self, share: &ShareNotificationRow) -> anyhow::Result<()>;
+
+    /// Tell one owner of the requested bookmark about an access request.
+    async fn notify_access_request(
+        &self,
+        request: &AccessRequestRow,
+        owner_id: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Tell a user about new matches of a saved search they subscribed to.
+    async fn notify_saved_search(&self, alert: &SavedSearchAlert) -> anyhow::Result<()>;
+}
+
+/// Accepts every notification without sending anything.
+pub struct NoopNotifier;
+
+#[tonic::async_trait]
+impl ShareNotifier for NoopNotifier {
+    async fn notify(&self, share: &ShareNotificationRow) -> anyhow::Result<()> {
+        tracing::debug!(id = share.id, recipient = %share.recipient_id, "share notifier disabled");
+        Ok(())
+    }
+
+    async fn notify_access_request(
+        &self,
+        request: &AccessRequestRow,
+        owner_id: &str,
+    ) -> anyhow::Result<()> {
+        tracing::debug!(id = request.id, owner = %owner_id, "share notifier disabled");
+        Ok(())
+    }
+
+    async fn notify_saved_search(&self, alert: &SavedSearchAlert) -> anyhow::Result<()> {
+        tracing::debug!(
+            id = alert.saved_search_id,
+            user = %alert.user_id,
+            "share notifier disabled"
+        );
+        Ok(())
+    }
+}
+
+/// POSTs the notification as JSON; any 2xx response counts as delivered.
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: &str, timeout: Duration) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        Ok(Self {
+            client,
+            url: url.to_string(),
+        })
+    }
+
+    async fn post(&self, payload: serde_json::Value) -> anyhow::Result<()> {
+        let body = serde_json::to_vec(&payload)?;
+        self.client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl ShareNotifier for WebhookNotifier {
+    async fn notify(&self, share: &ShareNotificationRow) -> anyhow::Result<()> {
+        self.post(serde_json::json!({
+            "type": "BookmarkShared",
+            "data": share,
+        }))
+        .await
+    }
+
+    async fn notify_access_request(
+        &self,
+        request: &AccessRequestRow,
+        owner_id: &str,
+    ) -> anyhow::Result<()> {
+        self.post(serde_json::json!({
+            "type": "AccessRequested",
+            "recipientId": owner_id,
+            "data": request,
+        }))
+        .await
+    }
+
+    async fn notify_saved_search(&self, alert: &SavedSearchAlert) -> anyhow::Result<()> {
+        self.post(serde_json::json!({
+            "type": "SavedSearchMatched",
+            "recipientId": alert.user_id,
+            "data": alert,
+        }))
+        .await
+    }
+}
+
+/// Sends an in-app message through the admin gateway.
+pub struct PlatformNotifier {
+    admin: AdminClient,
+}
+
+impl PlatformNotifier {
+    pub fn new(admin: AdminClient) -> Self {
+        Self { admin }
+    }
+}
+
+#[tonic::async_trait]
+impl ShareNotifier for PlatformNotifier {
+    async fn notify(&self, share: &ShareNotificationRow) -> anyhow::Result<()> {
+        let recipient = share
+            .recipient_id
+            .parse::<u32>()
+            .map_err(|_| anyhow::anyhow!("recipient {:?} is not a user id", share.recipient_id))?;
+        let mut content = format!(
+            "A bookmark was shared with you as {} until {}.",
+            share.relation.trim_start_matches("RELATION_").to_lowercase(),
+            share.expires_at.format("%Y-%m-%d %H:%M UTC"),
+        );
+        if !share.message.is_empty() {
+            content.push_str("\n\n");
+            content.push_str(&share.message);
+        }
+        self.admin
+            .send_message(share.tenant_id as u32, recipient, "Bookmark shared with you", &content)
+            .await?;
+        Ok(())
+    }
+
+    async fn notify_access_request(
+        &self,
+        request: &AccessRequestRow,
+        owner_id: &str,
+    ) -> anyhow::Result<()> {
+        let recipient = owner_id
+            .parse::<u32>()
+            .map_err(|_| anyhow::anyhow!("owner {owner_id:?} is not a user id"))?;
+        let mut content = format!(
+            "User {} asked for {} access to bookmark {}.",
+            request.requester_id,
+            request.relation.trim_start_matches("RELATION_").to_lowercase(),
+            request.bookmark_id,
+        );
+        if !request.message.is_empty() {
+            content.push_str("\n\n");
+            content.push_str(&request.message);
+        }
+        self.admin
+            .send_message(request.tenant_id as u32, recipient, "Access requested", &content)
+            .await?;
+        Ok(())
+    }
+
+    async fn notify_saved_search(&self, alert: &SavedSearchAlert) -> anyhow::Result<()> {
+        let recipient = alert
+            .user_id
+            .parse::<u32>()
+            .map_err(|_| anyhow::anyhow!("user {:?} is not a user id", alert.user_id))?;
+        let mut content = format!(
+            "{} new bookmark(s) match your saved search \"{}\":\n",
+            alert.total, alert.name,
+        );
+        for m in &alert.matches {
+            content.push_str(&format!("\n- {} ({})", m.title, m.bookmark_id));
+        }
+        let more = alert.total - alert.matches.len() as i64;
+        if more > 0 {
+            content.push_str(&format!("\n\n...and {more} more."));
+        }
+        self.admin
+            .send_message(alert.tenant_id as u32, recipient, "New saved search matches", &content)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Build the notifier selected by `cfg.driver`.
+pub fn notifier_from_config(
+    cfg: &NotifierConfig,
+    admin_client: Option<AdminClient>,
+) -> anyhow::Result<Arc<dyn ShareNotifier>> {
+    Ok(match cfg.driver.as_str() {
+        "none" => Arc::new(NoopNotifier),
+        "webhook" => {
+            if cfg.webhook_url.is_empty() {
+                anyhow::bail!("sharing.notifier.webhook_url is required for the webhook driver");
+            }
+            Arc::new(WebhookNotifier::new(&cfg.webhook_url, parse_duration(&cfg.timeout)?)?)
+        }
+        "platform" => {
+            let admin = admin_client.ok_or_else(|| {
+                anyhow::anyhow!("the platform notifier needs ADMIN_GRPC_ENDPOINT")
+            })?;
+            Arc::new(PlatformNotifier::new(admin))
+        }
+        other => anyhow::bail!("unknown share notifier driver {other:?}"),
+    })
+}
+
+/// Pending share notifications and the notifier that delivers them.
+#[derive(Clone)]
+pub struct ShareNotifications {
+    store: Arc<dyn ShareNotificationStore>,
+    notifier: Arc<dyn ShareNotifier>,
+    max_attempts: i32,
+    retry_interval: Duration,
+}
+
+impl ShareNotifications {
+    pub fn new(
+        store: Arc<dyn ShareNotificationStore>,
+        notifier: Arc<dyn ShareNotifier>,
+        cfg: &NotifierConfig,
+    ) -> Self {
+        let retry_interval = parse_duration(&cfg.retry_interval).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "invalid sharing.notifier.retry_interval, using 1m");
+            Duration::from_secs(60)
+        });
+        Self {
+            store,
+            notifier,
+            max_attempts: cfg.max_attempts.max(1),
+            retry_interval,
+        }
+    }
+
+    pub fn store(&self) -> &Arc<dyn ShareNotificationStore> {
+        &self.store
+    }
+
+    /// When a delivery starting now may next be retried by another worker.
+    pub fn lease_until(&self) -> chrono::DateTime<Utc> {
+        Utc::now() + chrono::Duration::from_std(self.retry_interval).unwrap_or_default()
+    }
+
+    /// Deliver in the background and record the outcome.
+    pub fn deliver(&self, share: ShareNotificationRow) {
+        let this = self.clone();
+        tokio::spawn(async move { this.attempt(&share).await });
+    }
+
+    async fn attempt(&self, share: &ShareNotificationRow) {
+        let recorded = match self.notifier.notify(share).await {
+            Ok(()) => self.store.mark_sent(share.id).await,
+            Err(e) => {
+                let give_up = share.attempts + 1 >= self.max_attempts;
+                tracing::warn!(
+                    id = share.id,
+                    attempt = share.attempts + 1,
+                    give_up,
+                    error = %e,
+                    "share notification failed"
+                );
+                let retry_at = (!give_up).then(|| self.lease_until());
+                self.store
+                    .record_failure(share.id, &e.to_string(), retry_at)
+                    .await
+            }
+        };
+        if let Err(e) = recorded {
+            tracing::error!(id = share.id, error = %e, "failed to record share notification");
+        }
+    }
+
+    /// Periodically retry notifications whose delivery failed or was interrupted.
+    pub fn spawn_retry(self, mut shutdown: ShutdownSignal) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.retry_interval);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = shutdown.recv() => break,
+                }
+                let due = match self.store.claim_due(RETRY_BATCH, self.lease_until()).await {
+                    Ok(due) => due,
+                    Err(e) => {
+                        tracing::error!(error = %e, "failed to load pending share notifications");
+                        continue;
+                    }
+                };
+                for share in &due {
+                    self.attempt(share).await;
+                }
+            }
+        })
+    }
+}
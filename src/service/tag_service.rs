@@ -0,0 +1,220 @@
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use crate::data::bookmark_repo::TAG_SEPARATOR;
+use crate::data::tag_repo::{TagFields, TagRow, TagStore};
+use crate::service::bookmark_service::proto::{
+    tag_service_server::TagService, CreateTagRequest, DeleteTagRequest, GetTagRequest,
+    ListTagsRequest, ListTagsResponse, Tag, UpdateTagRequest,
+};
+use crate::service::context_helper::{extract_context, RequestContext};
+use crate::service::error::ServiceError;
+
+const MAX_NAME_LEN: usize = 255;
+const MAX_EMOJI_CHARS: usize = 8;
+const MAX_DESCRIPTION_LEN: usize = 1024;
+const MAX_TAGS: usize = 1000;
+
+pub struct TagServiceImpl {
+    tags: Arc<dyn TagStore>,
+}
+
+impl TagServiceImpl {
+    pub fn new(tags: Arc<dyn TagStore>) -> Self {
+        Self { tags }
+    }
+}
+
+fn require_tenant_manager(ctx: &RequestContext) -> Result<(), Status> {
+    if !ctx.is_tenant_manager() {
+        return Err(Status::permission_denied("tenant manager role required"));
+    }
+    Ok(())
+}
+
+/// Validate a tag's color, emoji and description from a request. Colors are
+/// stored as lowercase `#rrggbb`.
+fn parse_fields(color: &str, emoji: &str, description: &str) -> Result<TagFields, Status> {
+    let color = color.trim().to_ascii_lowercase();
+    if !color.is_empty() {
+        let valid = color
+            .strip_prefix('#')
+            .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()));
+        if !valid {
+            return Err(Status::invalid_argument("color must be #rrggbb"));
+        }
+    }
+
+    let emoji = emoji.trim();
+    if emoji.chars().count() > MAX_EMOJI_CHARS || emoji.contains(char::is_whitespace) {
+        return Err(Status::invalid_argument("invalid emoji"));
+    }
+
+    let description = description.trim();
+    if description.chars().count() > MAX_DESCRIPTION_LEN {
+        return Err(Status::invalid_argument("description is too long"));
+    }
+
+    Ok(TagFields {
+        color,
+        emoji: emoji.to_string(),
+        description: description.to_string(),
+    })
+}
+
+fn parse_tag_id(id: u32) -> Result<i32, Status> {
+    i32::try_from(id)
+        .ok()
+        .filter(|id| *id > 0)
+        .ok_or_else(|| Status::invalid_argument("invalid tag id"))
+}
+
+#[tonic::async_trait]
+impl TagService for TagServiceImpl {
+    async fn create_tag(
+        &self,
+        request: Request<CreateTagRequest>,
+    ) -> Result<Response<Tag>, Status> {
+        let ctx = extract_context(&request)?;
+        require_tenant_manager(&ctx)?;
+        let req = request.into_inner();
+
+        let name = req.name.trim();
+        if name.is_empty() {
+            return Err(Status::invalid_argument("name is required"));
+        }
+        if name.len() > MAX_NAME_LEN {
+            return Err(Status::invalid_argument("name is too long"));
+        }
+        if name.ends_with(TAG_SEPARATOR) {
+            return Err(Status::invalid_argument(format!(
+                "name must not end with '{TAG_SEPARATOR}'"
+            )));
+        }
+        let fields = parse_fields(&req.color, &req.emoji, &req.description)?;
+
+        let existing = self
+            .tags
+            .list(ctx.tenant_id)
+            .await
+            .map_err(ServiceError::database)?;
+        if existing.len() >= MAX_TAGS {
+            return Err(Status::failed_precondition(format!(
+                "a tenant describes at most {MAX_TAGS} tags"
+            )));
+        }
+
+        let row = self
+            .tags
+            .create(ctx.tenant_id, name, &fields, &ctx.user_id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::already_exists(format!("tag {name:?} already exists")))?;
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            tag_id = row.id,
+            by = %ctx.user_id,
+            "tag created"
+        );
+
+        Ok(Response::new(tag_to_proto(row)))
+    }
+
+    async fn get_tag(&self, request: Request<GetTagRequest>) -> Result<Response<Tag>, Status> {
+        let ctx = extract_context(&request)?;
+        let id = parse_tag_id(request.into_inner().id)?;
+
+        let row = self
+            .tags
+            .get(ctx.tenant_id, id)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("tag not found"))?;
+
+        Ok(Response::new(tag_to_proto(row)))
+    }
+
+    async fn list_tags(
+        &self,
+        request: Request<ListTagsRequest>,
+    ) -> Result<Response<ListTagsResponse>, Status> {
+        let ctx = extract_context(&request)?;
+
+        let rows = self
+            .tags
+            .list(ctx.tenant_id)
+            .await
+            .map_err(ServiceError::database)?;
+
+        Ok(Response::new(ListTagsResponse {
+            tags: rows.into_iter().map(tag_to_proto).collect(),
+        }))
+    }
+
+    async fn update_tag(
+        &self,
+        request: Request<UpdateTagRequest>,
+    ) -> Result<Response<Tag>, Status> {
+        let ctx = extract_context(&request)?;
+        require_tenant_manager(&ctx)?;
+        let req = request.into_inner();
+
+        let id = parse_tag_id(req.id)?;
+        let fields = parse_fields(&req.color, &req.emoji, &req.description)?;
+
+        let row = self
+            .tags
+            .update(ctx.tenant_id, id, &fields)
+            .await
+            .map_err(ServiceError::database)?
+            .ok_or_else(|| Status::not_found("tag not found"))?;
+
+        Ok(Response::new(tag_to_proto(row)))
+    }
+
+    async fn delete_tag(&self, request: Request<DeleteTagRequest>) -> Result<Response<()>, Status> {
+        let ctx = extract_context(&request)?;
+        require_tenant_manager(&ctx)?;
+        let id = parse_tag_id(request.into_inner().id)?;
+
+        let deleted = self
+            .tags
+            .delete(ctx.tenant_id, id)
+            .await
+            .map_err(ServiceError::database)?;
+        if !deleted {
+            return Err(Status::not_found("tag not found"));
+        }
+
+        tracing::info!(
+            tenant_id = ctx.tenant_id,
+            tag_id = id,
+            by = %ctx.user_id,
+            "tag deleted"
+        );
+
+        Ok(Response::new(()))
+    }
+}
+
+fn to_timestamp(ts: chrono::DateTime<chrono::Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: ts.timestamp(),
+        nanos: ts.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn tag_to_proto(row: TagRow) -> Tag {
+    Tag {
+        id: row.id as u32,
+        name: row.name,
+        color: row.color,
+        emoji: row.emoji,
+        description: row.description,
+        created_by: row.created_by,
+        create_time: Some(to_timestamp(row.create_time)),
+        update_time: Some(to_timestamp(row.update_time)),
+    }
+}
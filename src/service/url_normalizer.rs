@@ -0,0 +1,45 @@
+use url::Url;
+
+/// Canonical form of a URL used to detect duplicates within a tenant.
+///
+/// `http` and `https` are treated alike, as are hosts with and without a
+/// leading `www.`. Fragments and trailing slashes are dropped and query
+/// parameters are sorted. Unparseable input is only trimmed and lower-cased.
+pub fn normalize_url(raw: &str) -> String {
+    let raw = raw.trim();
+    let Ok(url) = Url::parse(raw) else {
+        return raw.to_lowercase();
+    };
+
+    let scheme = url.scheme();
+    let Some(host) = url.host_str() else {
+        let mut url = url;
+        url.set_fragment(None);
+        return url.to_string();
+    };
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    let mut out = String::new();
+    if !matches!(scheme, "http" | "https") {
+        out.push_str(scheme);
+        out.push_str("://");
+    }
+    out.push_str(host);
+    // `port()` is already `None` for the scheme's default port.
+    if let Some(port) = url.port() {
+        out.push(':');
+        out.push_str(&port.to_string());
+    }
+    out.push_str(url.path().trim_end_matches('/'));
+
+    if let Some(query) = url.query() {
+        let mut pairs: Vec<&str> = query.split('&').filter(|p| !p.is_empty()).collect();
+        pairs.sort_unstable();
+        if !pairs.is_empty() {
+            out.push('?');
+            out.push_str(&pairs.join("&"));
+        }
+    }
+
+    out
+}
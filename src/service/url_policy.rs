@@ -0,0 +1,312 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+
+use ipnet::IpNet;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use tonic::Status;
+use url::{Host, Url};
+
+use crate::config::{MetadataFetchConfig, UrlPolicyConfig};
+
+/// Schemes a bookmark URL may use, so `javascript:`, `data:` or `file:` links
+/// never reach the frontend.
+#[derive(Clone)]
+pub struct UrlPolicy {
+    allowed_schemes: Arc<Vec<String>>,
+}
+
+impl UrlPolicy {
+    pub fn new(cfg: &UrlPolicyConfig) -> Self {
+        Self {
+            allowed_schemes: Arc::new(
+                cfg.allowed_schemes
+                    .iter()
+                    .map(|s| s.to_ascii_lowercase())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Reject URLs that are not absolute or use a scheme outside the allow-list.
+    pub fn check(&self, url: &str) -> Result<(), Status> {
+        let parsed =
+            Url::parse(url).map_err(|e| Status::invalid_argument(format!("invalid url: {e}")))?;
+        if !self.allowed_schemes.iter().any(|s| s == parsed.scheme()) {
+            return Err(Status::invalid_argument(format!(
+                "url scheme {:?} is not allowed",
+                parsed.scheme()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Addresses outbound fetches may reach. Checked on the URL (IP literals and
+/// host names) and again on every address a host name resolves to, so DNS
+/// cannot point a public name at an internal service.
+pub struct FetchGuard {
+    block_private: bool,
+    allowed_networks: Vec<IpNet>,
+    blocked_hosts: Vec<String>,
+}
+
+impl FetchGuard {
+    pub fn new(cfg: &MetadataFetchConfig) -> anyhow::Result<Self> {
+        let allowed_networks = cfg
+            .allowed_networks
+            .iter()
+            .map(|n| {
+                n.parse()
+                    .map_err(|e| anyhow::anyhow!("invalid allowed_networks entry {n:?}: {e}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            block_private: cfg.block_private_networks,
+            allowed_networks,
+            blocked_hosts: cfg
+                .blocked_hosts
+                .iter()
+                .map(|h| h.trim_end_matches('.').to_ascii_lowercase())
+                .collect(),
+        })
+    }
+
+    /// Check scheme and host of a URL about to be fetched or redirected to.
+    pub fn check_url(&self, url: &Url) -> Result<(), String> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("unsupported scheme {}", url.scheme()));
+        }
+        match url.host() {
+            Some(Host::Domain(host)) => {
+                let host = host.trim_end_matches('.').to_ascii_lowercase();
+                let blocked = self.blocked_hosts.iter().any(|b| {
+                    host == *b || host.strip_suffix(b.as_str()).is_some_and(|p| p.ends_with('.'))
+                });
+                if blocked {
+                    return Err(format!("host {host} is blocked"));
+                }
+                Ok(())
+            }
+            Some(Host::Ipv4(ip)) => self.check_ip(IpAddr::V4(ip)),
+            Some(Host::Ipv6(ip)) => self.check_ip(IpAddr::V6(ip)),
+            None => Err("url has no host".to_string()),
+        }
+    }
+
+    fn check_ip(&self, ip: IpAddr) -> Result<(), String> {
+        if self.allows_ip(ip) {
+            Ok(())
+        } else {
+            Err(format!("address {ip} is blocked"))
+        }
+    }
+
+    fn allows_ip(&self, ip: IpAddr) -> bool {
+        !self.block_private
+            || is_public(ip)
+            || self.allowed_networks.iter().any(|n| n.contains(&ip))
+    }
+}
+
+/// Whether `ip` is routable on the public internet. IPv4 addresses embedded in
+/// IPv6 (mapped, NAT64) are judged as IPv4.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match embedded_v4(ip) {
+            Some(v4) => is_public_v4(v4),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_multicast()
+        || ip.is_broadcast()
+        || a == 0
+        // Shared address space (carrier-grade NAT, some cloud metadata services).
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments.
+        || (a == 192 && b == 0 && c == 0)
+        // Benchmarking.
+        || (a == 198 && (b == 18 || b == 19))
+        // Reserved for future use.
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local (fc00::/7), e.g. fd00:ec2::254.
+        || (first & 0xfe00) == 0xfc00
+        // Link-local (fe80::/10).
+        || (first & 0xffc0) == 0xfe80
+        // Site-local (fec0::/10, deprecated).
+        || (first & 0xffc0) == 0xfec0)
+}
+
+fn embedded_v4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return Some(v4);
+    }
+    // NAT64 well-known prefix 64:ff9b::/96.
+    let s = ip.segments();
+    if s[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [.., a, b, c, d] = ip.octets();
+        return Some(Ipv4Addr::new(a, b, c, d));
+    }
+    None
+}
+
+/// DNS resolver for the fetch client that drops blocked addresses, failing
+/// the request when nothing is left.
+pub struct GuardedResolver(pub Arc<FetchGuard>);
+
+impl Resolve for GuardedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let guard = self.0.clone();
+        Box::pin(async move {
+            let host = name.as_str();
+            let allowed: Vec<SocketAddr> = tokio::net::lookup_host((host, 0))
+                .await?
+                .filter(|addr| guard.allows_ip(addr.ip()))
+                .collect();
+            if allowed.is_empty() {
+                return Err(format!("{host} resolves only to blocked addresses").into());
+            }
+            Ok(Box::new(allowed.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v4(s: &str) -> Ipv4Addr {
+        s.parse().unwrap()
+    }
+
+    fn v6(s: &str) -> Ipv6Addr {
+        s.parse().unwrap()
+    }
+
+    fn guard(allowed_networks: &[&str], blocked_hosts: &[&str]) -> FetchGuard {
+        FetchGuard::new(&MetadataFetchConfig {
+            allowed_networks: allowed_networks.iter().map(|n| n.to_string()).collect(),
+            blocked_hosts: blocked_hosts.iter().map(|h| h.to_string()).collect(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn check(guard: &FetchGuard, url: &str) -> Result<(), String> {
+        guard.check_url(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn classifies_ipv4_addresses() {
+        for public in [
+            "1.1.1.1",
+            "8.8.8.8",
+            "100.63.255.255",
+            "100.128.0.0",
+            "198.20.0.1",
+        ] {
+            assert!(is_public_v4(v4(public)), "{public} is public");
+        }
+        for internal in [
+            "0.0.0.0",
+            "0.1.2.3",
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "100.127.255.255",
+            "192.0.0.8",
+            "198.18.0.1",
+            "224.0.0.1",
+            "240.0.0.1",
+            "255.255.255.255",
+        ] {
+            assert!(!is_public_v4(v4(internal)), "{internal} is not public");
+        }
+    }
+
+    #[test]
+    fn classifies_ipv6_addresses() {
+        for public in ["2606:4700:4700::1111", "2001:4860:4860::8888"] {
+            assert!(is_public_v6(v6(public)), "{public} is public");
+        }
+        for internal in [
+            "::",
+            "::1",
+            "fc00::1",
+            "fd00:ec2::254",
+            "fe80::1",
+            "febf::1",
+            "fec0::1",
+            "ff02::1",
+        ] {
+            assert!(!is_public_v6(v6(internal)), "{internal} is not public");
+        }
+    }
+
+    #[test]
+    fn embedded_ipv4_is_judged_as_ipv4() {
+        assert_eq!(embedded_v4(v6("::ffff:10.0.0.1")), Some(v4("10.0.0.1")));
+        assert_eq!(
+            embedded_v4(v6("64:ff9b::a9fe:a9fe")),
+            Some(v4("169.254.169.254"))
+        );
+        assert_eq!(embedded_v4(v6("2606:4700::1")), None);
+
+        assert!(!is_public(IpAddr::V6(v6("::ffff:127.0.0.1"))));
+        assert!(!is_public(IpAddr::V6(v6("64:ff9b::a9fe:a9fe"))));
+        assert!(is_public(IpAddr::V6(v6("::ffff:8.8.8.8"))));
+        assert!(is_public(IpAddr::V6(v6("64:ff9b::808:808"))));
+    }
+
+    #[test]
+    fn blocks_private_literals_unless_allowed() {
+        let guard = guard(&["10.1.0.0/16"], &[]);
+        assert!(check(&guard, "http://8.8.8.8/").is_ok());
+        assert!(check(&guard, "http://169.254.169.254/latest/meta-data").is_err());
+        assert!(check(&guard, "http://[::ffff:169.254.169.254]/").is_err());
+        assert!(check(&guard, "http://[fd00:ec2::254]/").is_err());
+        assert!(check(&guard, "http://10.1.2.3/").is_ok());
+        assert!(check(&guard, "http://10.2.0.1/").is_err());
+        assert!(check(&guard, "ftp://8.8.8.8/").is_err());
+    }
+
+    #[test]
+    fn blocked_hosts_match_the_host_and_its_subdomains() {
+        let guard = guard(&[], &["metadata.google.internal", "Example.COM."]);
+        assert!(check(&guard, "http://metadata.google.internal/").is_err());
+        assert!(check(&guard, "http://METADATA.google.internal./").is_err());
+        assert!(check(&guard, "http://example.com/").is_err());
+        assert!(check(&guard, "http://a.b.example.com/").is_err());
+        assert!(check(&guard, "http://notexample.com/").is_ok());
+        assert!(check(&guard, "http://example.com.evil.net/").is_ok());
+    }
+
+    #[test]
+    fn url_policy_allows_only_listed_schemes() {
+        let policy = UrlPolicy::new(&UrlPolicyConfig {
+            allowed_schemes: vec!["HTTPS".to_string()],
+        });
+        assert!(policy.check("https://example.com/").is_ok());
+        assert!(policy.check("http://example.com/").is_err());
+        assert!(policy.check("javascript:alert(1)").is_err());
+        assert!(policy.check("not a url").is_err());
+    }
+}
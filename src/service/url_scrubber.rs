@@ -0,0 +1,94 @@
+use regex::Regex;
+use tonic::Status;
+
+use crate::data::scrub_policy_repo::ScrubPolicyRow;
+
+/// Tracking parameters stripped when a tenant has not configured a policy.
+/// A trailing `*` matches any parameter name with that prefix.
+pub const DEFAULT_STRIP_PARAMS: &[&str] = &["utm_*", "fbclid", "gclid"];
+
+/// Compiled URL scrubbing policy for one tenant.
+pub struct UrlScrubber {
+    enabled: bool,
+    strip_params: Vec<String>,
+    param_patterns: Vec<Regex>,
+}
+
+impl UrlScrubber {
+    /// Build the scrubber for a tenant, falling back to the defaults when the
+    /// tenant has no stored policy.
+    pub fn from_policy(policy: Option<&ScrubPolicyRow>) -> Result<Self, Status> {
+        match policy {
+            Some(p) => Ok(Self {
+                enabled: p.enabled,
+                strip_params: p.strip_params.clone(),
+                param_patterns: compile_patterns(&p.param_patterns)?,
+            }),
+            None => Ok(Self {
+                enabled: true,
+                strip_params: DEFAULT_STRIP_PARAMS.iter().map(|s| s.to_string()).collect(),
+                param_patterns: Vec::new(),
+            }),
+        }
+    }
+
+    /// Remove matching query parameters from `raw`. Returns `None` when nothing
+    /// was stripped (or the URL could not be parsed), so callers keep the original.
+    pub fn scrub(&self, raw: &str) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+
+        let mut url = url::Url::parse(raw).ok()?;
+        let query = url.query()?.to_string();
+
+        // Work on the raw pairs so kept parameters retain their original encoding.
+        let (kept, dropped): (Vec<&str>, Vec<&str>) = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .partition(|pair| {
+                let name = pair.split('=').next().unwrap_or("");
+                let name = decode_param_name(name);
+                !self.should_strip(&name)
+            });
+
+        if dropped.is_empty() {
+            return None;
+        }
+
+        if kept.is_empty() {
+            url.set_query(None);
+        } else {
+            url.set_query(Some(&kept.join("&")));
+        }
+        Some(url.to_string())
+    }
+
+    fn should_strip(&self, name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        self.strip_params.iter().any(|p| {
+            let p = p.to_ascii_lowercase();
+            match p.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == p,
+            }
+        }) || self.param_patterns.iter().any(|re| re.is_match(&name))
+    }
+}
+
+/// Validate and compile custom parameter-name patterns.
+pub fn compile_patterns(patterns: &[String]) -> Result<Vec<Regex>, Status> {
+    patterns
+        .iter()
+        .map(|p| {
+            Regex::new(p).map_err(|e| Status::invalid_argument(format!("invalid pattern {p:?}: {e}")))
+        })
+        .collect()
+}
+
+fn decode_param_name(s: &str) -> String {
+    url::form_urlencoded::parse(s.as_bytes())
+        .next()
+        .map(|(k, _)| k.into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,45 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{parse_duration, UsageStatsConfig};
+use crate::data::stats_repo::StatsStore;
+use crate::shutdown::ShutdownSignal;
+
+/// Periodically recompute the daily stats of the last `lookback_days`,
+/// today included, for every tenant.
+pub fn spawn_daily_stats_rollup(
+    stats: Arc<dyn StatsStore>,
+    cfg: &UsageStatsConfig,
+    mut shutdown: ShutdownSignal,
+) -> Option<tokio::task::JoinHandle<()>> {
+    if !cfg.enabled || cfg.lookback_days == 0 {
+        return None;
+    }
+    let interval = parse_duration(&cfg.interval).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "invalid usage_stats interval, using 24h");
+        Duration::from_secs(24 * 3600)
+    });
+    let lookback = chrono::Days::new(cfg.lookback_days as u64 - 1);
+    let top_domains = cfg.top_domains;
+
+    Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.recv() => break,
+            }
+            let to = chrono::Utc::now().date_naive();
+            let from = to - lookback;
+            match stats.roll_up_daily_stats(from, to, top_domains).await {
+                Ok(rows) => tracing::info!(
+                    from = %from,
+                    to = %to,
+                    rows,
+                    "usage: daily stats rolled up"
+                ),
+                Err(e) => tracing::error!(error = %e, "failed to roll up daily stats"),
+            }
+        }
+    }))
+}
@@ -0,0 +1,66 @@
+//! Bounded shutdown of background jobs: every job watches one stop signal and
+//! gets until a shared deadline to finish before it is aborted.
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Resolves once shutdown has begun. Periodic jobs select on it between runs
+/// so an iteration already in progress can finish.
+#[derive(Clone)]
+pub struct ShutdownSignal(watch::Receiver<bool>);
+
+impl ShutdownSignal {
+    pub async fn recv(&mut self) {
+        // A dropped sender also means shutdown.
+        let _ = self.0.wait_for(|stopping| *stopping).await;
+    }
+}
+
+/// Named background tasks stopped together on shutdown.
+pub struct Jobs {
+    stop: watch::Sender<bool>,
+    tasks: Vec<(&'static str, JoinHandle<()>)>,
+}
+
+impl Jobs {
+    pub fn new() -> Self {
+        Self {
+            stop: watch::Sender::new(false),
+            tasks: Vec::new(),
+        }
+    }
+
+    pub fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal(self.stop.subscribe())
+    }
+
+    pub fn add(&mut self, name: &'static str, handle: JoinHandle<()>) {
+        self.tasks.push((name, handle));
+    }
+
+    /// Tell every job to stop without waiting for them.
+    pub fn stop(&self) {
+        self.stop.send_replace(true);
+    }
+
+    /// Stop every job, wait for them until `deadline`, then abort the rest.
+    pub async fn shutdown(self, deadline: Instant) {
+        self.stop();
+        let mut cancelled = Vec::new();
+        for (name, mut handle) in self.tasks {
+            match tokio::time::timeout_at(deadline, &mut handle).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) if e.is_panic() => tracing::error!(job = name, "job panicked"),
+                Ok(Err(_)) => {}
+                Err(_) => {
+                    handle.abort();
+                    cancelled.push(name);
+                }
+            }
+        }
+        if !cancelled.is_empty() {
+            tracing::warn!(jobs = ?cancelled, "drain timeout elapsed, cancelled background jobs");
+        }
+    }
+}
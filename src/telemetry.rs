@@ -0,0 +1,66 @@
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Sampler, TracerProvider};
+use opentelemetry_sdk::Resource;
+
+use crate::config::TracingConfig;
+
+/// Build the OTLP tracer provider and install the W3C trace-context propagator.
+/// Returns `None` when export is disabled or the exporter cannot be built.
+pub fn init_tracer_provider(cfg: &TracingConfig) -> Option<TracerProvider> {
+    // Propagation is installed even without export so `traceparent` is passed on.
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+    if !cfg.enabled {
+        return None;
+    }
+
+    let exporter = match opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(&cfg.otlp_endpoint)
+        .build()
+    {
+        Ok(e) => e,
+        Err(e) => {
+            eprintln!("failed to build OTLP exporter for {}: {e}", cfg.otlp_endpoint);
+            return None;
+        }
+    };
+
+    let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+        cfg.sample_rate.clamp(0.0, 1.0),
+    )));
+
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_sampler(sampler)
+        .with_resource(Resource::new(vec![
+            KeyValue::new("service.name", cfg.service_name.clone()),
+            KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+        ]))
+        .build();
+
+    opentelemetry::global::set_tracer_provider(provider.clone());
+    Some(provider)
+}
+
+/// Tracer used by the `tracing-opentelemetry` layer.
+pub fn tracer(provider: &TracerProvider) -> opentelemetry_sdk::trace::Tracer {
+    provider.tracer("bookmark-service")
+}
+
+/// Reads propagation headers (e.g. `traceparent`) from incoming request metadata.
+pub struct HeaderExtractor<'a>(pub &'a http::HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}